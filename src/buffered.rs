@@ -0,0 +1,260 @@
+//! Buffering for asynchronous streams, for protocols which read and write a little at a time.
+//!
+//! Every read from and write to a local socket or a pipe is a system call, which makes line-oriented protocols and
+//! parsers which pull a few bytes at a time needlessly expensive when they're used on the streams directly.
+//! [`BufferedStream`] wraps any asynchronous stream with a read buffer, which also gives it the `AsyncBufRead` trait
+//! needed for line-by-line reading, and a write buffer, which collects small writes until it fills up or is flushed.
+//!
+//! Both the [Tokio](tokio::io) and the [`futures`](futures_io) flavors of the traits are implemented whenever the
+//! inner stream implements them, which is the case for all of the asynchronous stream types of this crate.
+//!
+//! The write buffer is only sent when it's full or when the stream is explicitly flushed or shut down. Forgetting to
+//! flush after a request is thus a common way for request-response protocols to stall, with both ends waiting for the
+//! other. Reading doesn't flush the write buffer, since that would make the stream decide on behalf of the protocol
+//! where message boundaries lie.
+//!
+//! # Example
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! use interprocess::{buffered::BufferedStream, local_socket::tokio::LocalSocketStream};
+//! use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+//!
+//! let conn = LocalSocketStream::connect("/tmp/example.sock").await?;
+//! let mut conn = BufferedStream::new(conn);
+//! conn.write_all(b"HELLO\n").await?;
+//! conn.flush().await?;
+//! let mut line = String::new();
+//! conn.read_line(&mut line).await?;
+//! println!("Server answered: {}", line.trim_end());
+//! # Ok(()) }
+//! ```
+
+use futures_core::ready;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, ReadBuf};
+
+/// The default capacity of both buffers, which is the size of a pipe buffer on Linux: a single read can take
+/// everything a pipe holds, and a single write of a full buffer fits into an empty pipe.
+pub const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+/// An asynchronous stream with a read buffer and a write buffer.
+///
+/// See the [module-level documentation](self) for more.
+pub struct BufferedStream<S> {
+    inner: S,
+    rbuf: Box<[u8]>,
+    rpos: usize,
+    rend: usize,
+    wbuf: Vec<u8>,
+    /// How much of the write buffer has been written out by a flush which hasn't finished yet.
+    wpos: usize,
+    wcap: usize,
+}
+impl<S> BufferedStream<S> {
+    /// Wraps the stream with buffers of the [default capacity](DEFAULT_CAPACITY).
+    #[inline]
+    pub fn new(inner: S) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, DEFAULT_CAPACITY, inner)
+    }
+    /// Wraps the stream with buffers of the given capacities.
+    ///
+    /// # Panics
+    /// Panics if either of the capacities is zero.
+    pub fn with_capacity(read_capacity: usize, write_capacity: usize, inner: S) -> Self {
+        assert!(
+            read_capacity != 0 && write_capacity != 0,
+            "buffer capacity must be greater than zero"
+        );
+        Self {
+            inner,
+            rbuf: vec![0; read_capacity].into_boxed_slice(),
+            rpos: 0,
+            rend: 0,
+            wbuf: Vec::with_capacity(write_capacity),
+            wpos: 0,
+            wcap: write_capacity,
+        }
+    }
+    /// Borrows the stream.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+    /// Mutably borrows the stream.
+    ///
+    /// Reading from or writing to the stream directly bypasses the buffers, and thus mixes up the order of the data
+    /// with whatever is in them.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+    /// Unwraps the stream, discarding the contents of both buffers. Flush the stream beforehand to keep what's in the
+    /// write buffer from being lost.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+    /// Returns the data which has been received but not read yet.
+    #[inline]
+    pub fn read_buffer(&self) -> &[u8] {
+        &self.rbuf[self.rpos..self.rend]
+    }
+    /// Returns the data which has been written but not sent yet.
+    #[inline]
+    pub fn write_buffer(&self) -> &[u8] {
+        &self.wbuf[self.wpos..]
+    }
+}
+impl<S: Unpin> BufferedStream<S> {
+    fn fill_buf_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        read: impl FnOnce(Pin<&mut S>, &mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>>,
+    ) -> Poll<io::Result<&[u8]>> {
+        if self.rpos >= self.rend {
+            let n = ready!(read(Pin::new(&mut self.inner), cx, &mut self.rbuf))?;
+            self.rpos = 0;
+            self.rend = n;
+        }
+        Poll::Ready(Ok(self.read_buffer()))
+    }
+    fn consume(&mut self, amt: usize) {
+        self.rpos = (self.rpos + amt).min(self.rend);
+    }
+    fn read_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        read: impl FnOnce(Pin<&mut S>, &mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>>,
+    ) -> Poll<io::Result<usize>> {
+        // Reads which would fill the whole buffer anyway go straight to the stream.
+        if self.rpos >= self.rend && buf.len() >= self.rbuf.len() {
+            return read(Pin::new(&mut self.inner), cx, buf);
+        }
+        let available = ready!(self.fill_buf_with(cx, read))?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Poll::Ready(Ok(n))
+    }
+    fn flush_buf_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut write: impl FnMut(Pin<&mut S>, &mut Context<'_>, &[u8]) -> Poll<io::Result<usize>>,
+    ) -> Poll<io::Result<()>> {
+        while self.wpos < self.wbuf.len() {
+            match ready!(write(Pin::new(&mut self.inner), cx, &self.wbuf[self.wpos..]))? {
+                0 => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    )))
+                }
+                n => self.wpos += n,
+            }
+        }
+        self.wbuf.clear();
+        self.wpos = 0;
+        Poll::Ready(Ok(()))
+    }
+    fn write_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        mut write: impl FnMut(Pin<&mut S>, &mut Context<'_>, &[u8]) -> Poll<io::Result<usize>>,
+    ) -> Poll<io::Result<usize>> {
+        if self.wbuf.len() + buf.len() > self.wcap {
+            ready!(self.flush_buf_with(cx, &mut write))?;
+        }
+        // Writes which wouldn't fit into the buffer even when it's empty go straight to the stream.
+        if buf.len() >= self.wcap {
+            write(Pin::new(&mut self.inner), cx, buf)
+        } else {
+            self.wbuf.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+    }
+}
+impl<S: Debug> Debug for BufferedStream<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferedStream")
+            .field("inner", &self.inner)
+            .field("read_buffered", &(self.rend - self.rpos))
+            .field("read_capacity", &self.rbuf.len())
+            .field("write_buffered", &(self.wbuf.len() - self.wpos))
+            .field("write_capacity", &self.wcap)
+            .finish()
+    }
+}
+
+impl<S: futures_io::AsyncRead + Unpin> futures_io::AsyncRead for BufferedStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().read_with(cx, buf, S::poll_read)
+    }
+}
+impl<S: futures_io::AsyncRead + Unpin> futures_io::AsyncBufRead for BufferedStream<S> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.get_mut().fill_buf_with(cx, S::poll_read)
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().consume(amt)
+    }
+}
+impl<S: futures_io::AsyncWrite + Unpin> futures_io::AsyncWrite for BufferedStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().write_with(cx, buf, S::poll_write)
+    }
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        ready!(slf.flush_buf_with(cx, S::poll_write))?;
+        Pin::new(&mut slf.inner).poll_flush(cx)
+    }
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        ready!(slf.flush_buf_with(cx, S::poll_write))?;
+        Pin::new(&mut slf.inner).poll_close(cx)
+    }
+}
+
+fn read_into_slice<S: AsyncRead>(inner: Pin<&mut S>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+    let mut buf = ReadBuf::new(buf);
+    ready!(inner.poll_read(cx, &mut buf))?;
+    Poll::Ready(Ok(buf.filled().len()))
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for BufferedStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let n = ready!(self.get_mut().read_with(cx, buf.initialize_unfilled(), read_into_slice))?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+impl<S: AsyncRead + Unpin> AsyncBufRead for BufferedStream<S> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.get_mut().fill_buf_with(cx, read_into_slice)
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().consume(amt)
+    }
+}
+impl<S: AsyncWrite + Unpin> AsyncWrite for BufferedStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().write_with(cx, buf, S::poll_write)
+    }
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        ready!(slf.flush_buf_with(cx, S::poll_write))?;
+        Pin::new(&mut slf.inner).poll_flush(cx)
+    }
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        ready!(slf.flush_buf_with(cx, S::poll_write))?;
+        Pin::new(&mut slf.inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,446 @@
+//! Message channels with backpressure on top of any asynchronous stream.
+//!
+//! [`bounded()`] turns one end of a connection into a [`Sender`] and a [`Receiver`] of messages, with credit-based flow
+//! control between the two ends: every receiver has a capacity, which is the number of messages it's willing to buffer,
+//! and the sender on the other end can't have more messages than that in flight at once. Once it does,
+//! [`send()`](Sender::send) waits for the receiver to catch up, instead of piling messages up in memory on either end,
+//! which is what happens with a plain stream whose reader falls behind once the system's buffers fill up – or, worse,
+//! what happens with an unbounded queue in front of it.
+//!
+//! Both ends of the connection have to use this module, and each picks its own capacity. Messages are delivered in
+//! order, and their boundaries are preserved.
+//!
+//! The [`Receiver`] is also a [`Stream`](futures_core::Stream) of messages, and the [`Sender`] a
+//! [`Sink`](futures_sink::Sink), for use with `select!` and the rest of the asynchronous ecosystem.
+//!
+//! ## Wire format
+//! Every frame is a [message](crate::framing) with a [32-bit length prefix](crate::framing::LengthPrefix::U32) which
+//! starts with a kind byte:
+//! - **Hello** (1), the first frame sent by either end, carries its capacity as a little-endian 32-bit integer
+//! - **Message** (2) carries a message
+//! - **Acknowledgement** (3) carries the number of messages received since the last one as a little-endian 32-bit
+//!   integer, which the other end may send again
+//! - **Close** (4) means that the sender has been dropped and no more messages will follow
+//!
+//! # Example
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! use interprocess::{channel, local_socket::tokio::LocalSocketStream};
+//!
+//! let conn = LocalSocketStream::connect("/tmp/example-channel.sock").await?;
+//! let (tx, mut rx) = channel::bounded(conn, 16);
+//!
+//! tokio::spawn(async move {
+//!     for i in 0..1000_u32 {
+//!         // Waits whenever the other end has 16 messages it hasn't received yet.
+//!         tx.send(&i.to_le_bytes()).await?;
+//!     }
+//!     std::io::Result::Ok(())
+//! });
+//! while let Some(msg) = rx.recv().await? {
+//!     println!("{msg:?}");
+//! }
+//! # Ok(()) }
+//! ```
+
+use crate::framing::{LengthPrefix, MessageReader, MessageWriter, DEFAULT_MAX_SIZE};
+use futures_core::{ready, Stream};
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_sink::Sink;
+use futures_util::{
+    future::poll_fn,
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    io, mem,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+use tokio::{
+    sync::{
+        mpsc::{self, error::TrySendError},
+        Semaphore, TryAcquireError,
+    },
+    task::JoinHandle,
+};
+
+const HELLO: u8 = 1;
+const MESSAGE: u8 = 2;
+const ACK: u8 = 3;
+const CLOSE: u8 = 4;
+
+/// Splits one end of a connection into a sender and a receiver of messages, which buffers up to `capacity` messages
+/// received from the other end.
+///
+/// The stream can be any of the asynchronous streams of this crate, or anything else which implements the
+/// [`futures`](futures_io) flavors of `AsyncRead` and `AsyncWrite`. Frames are sent and received by two tasks spawned
+/// on the Tokio runtime, which are stopped once both halves have been dropped, after which the stream is closed.
+///
+/// # Panics
+/// If `capacity` is zero or doesn't fit into 32 bits.
+pub fn bounded<S>(stream: S, capacity: usize) -> (Sender, Receiver)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let capacity = u32::try_from(capacity)
+        .ok()
+        .filter(|&c| c != 0)
+        .expect("channel capacity must be non-zero and fit into 32 bits");
+    let (reader, writer) = stream.split();
+    let link = Arc::new(Link {
+        credit: Semaphore::new(0),
+        lost: Mutex::new(None),
+        closed_by_peer: AtomicBool::new(false),
+    });
+    let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+    let _ = outgoing.send(frame(HELLO, &capacity.to_le_bytes()));
+    tokio::spawn(write_frames(
+        outgoing_rx,
+        MessageWriter::new(writer).with_prefix(LengthPrefix::U32),
+        Arc::clone(&link),
+    ));
+    let (incoming, rx) = mpsc::channel(capacity as usize);
+    let reader = Arc::new(ReaderTask(tokio::spawn(read_frames(
+        MessageReader::new(reader).with_prefix(LengthPrefix::U32),
+        incoming,
+        outgoing.clone(),
+        Arc::clone(&link),
+    ))));
+    let sender = Sender {
+        outgoing: outgoing.clone(),
+        link: Arc::clone(&link),
+        reservation: Mutex::default(),
+        _reader: Arc::clone(&reader),
+    };
+    let receiver = Receiver {
+        rx,
+        outgoing,
+        link,
+        unacked: 0,
+        // Batches up acknowledgements, rather than sending one for every message.
+        ack_threshold: (capacity / 2).max(1),
+        _reader: reader,
+    };
+    (sender, receiver)
+}
+
+/// State shared between the halves and the tasks.
+struct Link {
+    /// The number of messages the other end is willing to receive.
+    credit: Semaphore,
+    /// Set once the connection is gone, to the kind of error to report.
+    lost: Mutex<Option<io::ErrorKind>>,
+    /// Whether the sender on the other end has been dropped.
+    closed_by_peer: AtomicBool,
+}
+impl Link {
+    fn lose(&self, kind: io::ErrorKind) {
+        self.lost.lock().unwrap().get_or_insert(kind);
+        self.credit.close();
+    }
+    fn error(&self) -> io::Error {
+        let kind = self.lost.lock().unwrap().unwrap_or(io::ErrorKind::ConnectionAborted);
+        io::Error::new(kind, "the connection was lost")
+    }
+}
+
+/// The state of acquiring credit for a message sent through the `Sink` implementation of [`Sender`].
+#[derive(Default)]
+enum Reservation {
+    #[default]
+    None,
+    Pending(Pin<Box<dyn Future<Output = io::Result<()>> + Send>>),
+    Held,
+}
+
+/// Stops the reading task once both halves have been dropped.
+struct ReaderTask(JoinHandle<()>);
+impl Drop for ReaderTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn ack(count: u32) -> Vec<u8> {
+    frame(ACK, &count.to_le_bytes())
+}
+fn frame(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(1 + payload.len());
+    frame.push(kind);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Writes frames to the stream until every sender of them is gone, then closes it.
+async fn write_frames<W: AsyncWrite + Unpin>(
+    mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    mut writer: MessageWriter<W>,
+    link: Arc<Link>,
+) {
+    while let Some(frame) = rx.recv().await {
+        if writer.write_message_async(&frame).await.is_err() {
+            link.lose(io::ErrorKind::ConnectionAborted);
+            return;
+        }
+    }
+    let _ = writer.get_mut().close().await;
+}
+
+/// Dispatches frames from the other end until the connection ends or the other end breaks the protocol.
+async fn read_frames<R: AsyncRead + Unpin>(
+    mut reader: MessageReader<R>,
+    incoming: mpsc::Sender<Vec<u8>>,
+    outgoing: mpsc::UnboundedSender<Vec<u8>>,
+    link: Arc<Link>,
+) {
+    let mut incoming = Some(incoming);
+    let mut greeted = false;
+    loop {
+        let frame = match reader.read_message_async().await {
+            Ok(Some(frame)) => frame,
+            // Dropping the sender of incoming messages ends the receiver once it has caught up.
+            Ok(None) => return link.lose(io::ErrorKind::BrokenPipe),
+            Err(_) => return link.lose(io::ErrorKind::ConnectionAborted),
+        };
+        let valid = match frame.split_first() {
+            Some((&HELLO, payload)) if !greeted => match read_u32(payload) {
+                Some(capacity) => {
+                    greeted = true;
+                    link.credit.add_permits(capacity as usize);
+                    true
+                }
+                None => false,
+            },
+            Some((&MESSAGE, msg)) => match incoming.as_ref().map(|tx| tx.try_send(msg.to_vec())) {
+                Some(Ok(())) => true,
+                // More messages in flight than the capacity allows.
+                Some(Err(TrySendError::Full(_))) => false,
+                // The receiver is gone, and so is the message, but the other end can send the next one.
+                Some(Err(TrySendError::Closed(_))) | None => outgoing.send(ack(1)).is_ok(),
+            },
+            Some((&ACK, payload)) => match read_u32(payload) {
+                // Acknowledging far more messages than were sent would overflow the semaphore.
+                Some(count) if count as usize > Semaphore::MAX_PERMITS - link.credit.available_permits() => false,
+                Some(count) => {
+                    link.credit.add_permits(count as usize);
+                    true
+                }
+                None => false,
+            },
+            Some((&CLOSE, [])) => {
+                link.closed_by_peer.store(true, Ordering::Release);
+                incoming = None;
+                true
+            }
+            _ => false,
+        };
+        if !valid {
+            return link.lose(io::ErrorKind::InvalidData);
+        }
+    }
+}
+
+fn read_u32(payload: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(payload.try_into().ok()?))
+}
+
+/// The sending half of a channel created by [`bounded()`].
+///
+/// Sending only needs a shared reference, so the sender can be put in an [`Arc`] and used from many tasks at once.
+/// Dropping it tells the receiver on the other end that no more messages will follow.
+pub struct Sender {
+    outgoing: mpsc::UnboundedSender<Vec<u8>>,
+    link: Arc<Link>,
+    /// Credit for the next message sent through the `Sink` implementation. Only ever accessed through a mutable
+    /// reference; the mutex keeps the sender `Sync`.
+    reservation: Mutex<Reservation>,
+    _reader: Arc<ReaderTask>,
+}
+impl Sender {
+    /// Sends a message, first waiting for the receiver on the other end to make room for it if it already has as many
+    /// messages in flight as its capacity allows.
+    ///
+    /// Dropping the future before it completes doesn't send anything.
+    ///
+    /// # Errors
+    /// - [`InvalidInput`](io::ErrorKind::InvalidInput) if the message is larger than the
+    ///   [maximum size](DEFAULT_MAX_SIZE) of a frame allows.
+    /// - [`BrokenPipe`](io::ErrorKind::BrokenPipe) if the other end has closed the connection, and
+    ///   [`ConnectionAborted`](io::ErrorKind::ConnectionAborted) or [`InvalidData`](io::ErrorKind::InvalidData) if the
+    ///   connection failed or the other end broke the protocol.
+    pub async fn send(&self, msg: &[u8]) -> io::Result<()> {
+        check_size(msg)?;
+        let permit = self.link.credit.acquire().await.map_err(|_| self.link.error())?;
+        permit.forget();
+        self.enqueue(msg)
+    }
+    /// Sends a message if the receiver on the other end has room for it, failing with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) otherwise.
+    ///
+    /// Other errors are reported in the same way as with [`send()`](Self::send).
+    pub fn try_send(&self, msg: &[u8]) -> io::Result<()> {
+        check_size(msg)?;
+        match self.link.credit.try_acquire() {
+            Ok(permit) => permit.forget(),
+            Err(TryAcquireError::NoPermits) => return Err(io::ErrorKind::WouldBlock.into()),
+            Err(TryAcquireError::Closed) => return Err(self.link.error()),
+        }
+        self.enqueue(msg)
+    }
+    /// Returns the number of messages which can currently be sent without waiting.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.link.credit.available_permits()
+    }
+
+    fn enqueue(&self, msg: &[u8]) -> io::Result<()> {
+        self.outgoing.send(frame(MESSAGE, msg)).map_err(|_| self.link.error())
+    }
+}
+/// Sends messages of any type which can be viewed as bytes, such as `Vec<u8>` or `&[u8]`, waiting for credit in
+/// [`poll_ready()`](Sink::poll_ready) in the same way as [`send()`](Sender::send) does, which makes the sender usable
+/// with [`send_all()`](futures_util::SinkExt::send_all) and the rest of the asynchronous ecosystem.
+///
+/// Messages are handed to the task which writes them out as soon as they're sent, and so flushing has nothing to wait
+/// for. Closing doesn't do anything either: the receiver on the other end finds out that no more messages will follow
+/// once the sender is dropped.
+impl<M: AsRef<[u8]>> Sink<M> for Sender {
+    type Error = io::Error;
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        let reservation = slf.reservation.get_mut().unwrap();
+        loop {
+            match reservation {
+                Reservation::Held => return Poll::Ready(Ok(())),
+                Reservation::None => {
+                    let link = Arc::clone(&slf.link);
+                    *reservation = Reservation::Pending(Box::pin(async move {
+                        let permit = link.credit.acquire().await.map_err(|_| link.error())?;
+                        permit.forget();
+                        Ok(())
+                    }));
+                }
+                Reservation::Pending(acquire) => {
+                    let result = ready!(acquire.as_mut().poll(cx));
+                    *reservation = Reservation::None;
+                    result?;
+                    *reservation = Reservation::Held;
+                }
+            }
+        }
+    }
+    /// Sends the message with the credit obtained by [`poll_ready()`](Sink::poll_ready), or, if it wasn't called
+    /// beforehand, in the same way as [`try_send()`](Sender::try_send).
+    fn start_send(self: Pin<&mut Self>, item: M) -> io::Result<()> {
+        let slf = self.get_mut();
+        let msg = item.as_ref();
+        check_size(msg)?;
+        match mem::take(slf.reservation.get_mut().unwrap()) {
+            Reservation::Held => slf.enqueue(msg),
+            _ => slf.try_send(msg),
+        }
+    }
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let _ = self.outgoing.send(frame(CLOSE, &[]));
+    }
+}
+impl Debug for Sender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+fn check_size(msg: &[u8]) -> io::Result<()> {
+    if msg.len() >= DEFAULT_MAX_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "message exceeds the maximum size",
+        ));
+    }
+    Ok(())
+}
+
+/// The receiving half of a channel created by [`bounded()`].
+///
+/// Dropping it makes the other end discard its messages from then on, without making the sender there wait.
+pub struct Receiver {
+    rx: mpsc::Receiver<Vec<u8>>,
+    outgoing: mpsc::UnboundedSender<Vec<u8>>,
+    link: Arc<Link>,
+    unacked: u32,
+    ack_threshold: u32,
+    _reader: Arc<ReaderTask>,
+}
+impl Receiver {
+    /// Receives the next message, or returns `None` if the sender on the other end has been dropped and all of its
+    /// messages have been received.
+    ///
+    /// If the connection ends without the sender having been dropped, fails with the same errors as
+    /// [`Sender::send()`] does in that case. The future is cancel-safe.
+    pub async fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+    /// Polls for the next message, with the same outcomes as [`recv()`](Self::recv).
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Vec<u8>>>> {
+        let Some(msg) = ready!(self.rx.poll_recv(cx)) else {
+            return Poll::Ready(match self.link.closed_by_peer.load(Ordering::Acquire) {
+                true => Ok(None),
+                false => Err(self.link.error()),
+            });
+        };
+        self.unacked += 1;
+        if self.unacked >= self.ack_threshold {
+            let count = std::mem::take(&mut self.unacked);
+            // If the connection is gone, the next call reports it.
+            let _ = self.outgoing.send(ack(count));
+        }
+        Poll::Ready(Ok(Some(msg)))
+    }
+}
+/// Yields the messages received by [`recv()`](Receiver::recv), ending once it would return `None` and yielding the
+/// error if it would fail, which makes receiving messages compose with `select!`, timeouts and the rest of the
+/// [`StreamExt`](futures_util::StreamExt) combinators.
+impl Stream for Receiver {
+    type Item = io::Result<Vec<u8>>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx).map(Result::transpose)
+    }
+}
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        // Gives back the credit for the messages which will never be received, so that the sender on the other end
+        // doesn't wait for it forever. Those which arrive from now on are acknowledged as they come.
+        self.rx.close();
+        let mut count = self.unacked;
+        while self.rx.try_recv().is_ok() {
+            count += 1;
+        }
+        if count != 0 {
+            let _ = self.outgoing.send(ack(count));
+        }
+    }
+}
+impl Debug for Receiver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
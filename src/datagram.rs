@@ -0,0 +1,259 @@
+//! Traits for sending and receiving discrete messages, regardless of the transport that carries them.
+//!
+//! Several IPC primitives preserve the boundaries between messages rather than exposing a byte stream: Ud-socket
+//! datagrams, message-mode named pipes on Windows, and their asynchronous counterparts. Their inherent methods differ in
+//! the details, such as whether they take `&self` or `&mut self` and whether they report partial writes. [`Datagram`]
+//! and [`AsyncDatagram`] paper over those differences, so that code which only needs to send and receive whole messages
+//! can be written once and used with any of them.
+//!
+//! ## Semantics
+//! - Every successful send transmits exactly one whole message. If the system reports having sent only part of it, the
+//!   send fails with a [`PartialMsgWriteError`] in an error of kind [`Other`](io::ErrorKind::Other).
+//! - Every successful receive takes exactly one message off the queue, returning the number of bytes written into the
+//!   buffer. If the message doesn't fit, it's truncated to the size of the buffer and the rest of it is discarded, just
+//!   like with [`UdpSocket::recv()`](std::net::UdpSocket::recv). Use a buffer as large as the largest message the
+//!   protocol allows, or the [`reliable_recv_msg`](crate::reliable_recv_msg) traits, which never lose any part of a
+//!   message.
+//! - A message can be sent from several separate slices with `send_vectored`, which spares the caller from
+//!   concatenating them. Transports which support gathering writes, such as Ud-socket datagrams, send them with a
+//!   single system call; the others, such as named pipes, which Windows can't write to from several buffers at once,
+//!   concatenate the slices into a temporary buffer. [`MessageBuilder`](crate::message_builder::MessageBuilder) helps
+//!   with putting such messages together.
+//! - Transports which have addresses also support sending to and receiving from a specific address. Connection-oriented
+//!   transports, such as named pipes, have [`Infallible`](std::convert::Infallible) as their address type, fail to
+//!   send to an address with [`Unsupported`](io::ErrorKind::Unsupported), and never report the address of the sender.
+//!
+//! ## Platform support
+//! The traits are implemented for:
+//! - [`UdDatagram`](crate::os::unix::udsocket::UdDatagram) on Unix, along with its Tokio and async-std flavors
+//! - Duplex message-mode named pipe streams on Windows, along with their Tokio flavor
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::datagram::Datagram;
+//! use std::io;
+//!
+//! /// Works the same over any datagram transport.
+//! fn ping(conn: &mut impl Datagram) -> io::Result<bool> {
+//!     conn.send(b"ping")?;
+//!     let mut buf = [0; 4];
+//!     let len = conn.recv(&mut buf)?;
+//!     Ok(&buf[..len] == b"pong")
+//! }
+//! ```
+
+use crate::reliable_recv_msg::PartialMsgWriteError;
+use std::{
+    future::Future,
+    io::{self, IoSlice},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Sending and receiving discrete messages, optionally with addresses.
+///
+/// See the [module-level documentation](self) for more.
+pub trait Datagram {
+    /// The address of a sender or receiver of messages.
+    type Address;
+
+    /// Sends one message to the peer the transport is connected to.
+    fn send(&mut self, msg: &[u8]) -> io::Result<()>;
+    /// Receives one message, returning the number of bytes written into the buffer.
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Sends one message, made up of the given slices in order, to the peer the transport is connected to.
+    ///
+    /// Concatenates the slices and calls [`send()`](Self::send) by default, for transports without gathering writes.
+    fn send_vectored(&mut self, parts: &[IoSlice<'_>]) -> io::Result<()> {
+        self.send(&concat(parts))
+    }
+
+    /// Sends one message to the given address.
+    ///
+    /// Fails with [`Unsupported`](io::ErrorKind::Unsupported) by default, for transports without addresses.
+    fn send_to(&mut self, msg: &[u8], addr: &Self::Address) -> io::Result<()> {
+        let _ = (msg, addr);
+        Err(no_addresses())
+    }
+    /// Receives one message, returning the number of bytes written into the buffer and the address of the sender, if it
+    /// has one.
+    ///
+    /// Never reports an address by default, for transports without addresses.
+    fn recv_from(&mut self, buf: &mut [u8]) -> io::Result<(usize, Option<Self::Address>)> {
+        Ok((self.recv(buf)?, None))
+    }
+}
+
+/// Asynchronously sending and receiving discrete messages, optionally with addresses.
+///
+/// See the [module-level documentation](self) for more, and [`AsyncDatagramExt`] for the futures.
+pub trait AsyncDatagram {
+    /// The address of a sender or receiver of messages.
+    type Address;
+
+    /// Polls a future that sends one message to the peer the transport is connected to.
+    fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, msg: &[u8]) -> Poll<io::Result<()>>;
+    /// Polls a future that receives one message, returning the number of bytes written into the buffer.
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+
+    /// Polls a future that sends one message, made up of the given slices in order, to the peer the transport is
+    /// connected to.
+    ///
+    /// Concatenates the slices and calls [`poll_send()`](Self::poll_send) by default, for transports without gathering
+    /// writes.
+    fn poll_send_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, parts: &[IoSlice<'_>]) -> Poll<io::Result<()>> {
+        self.poll_send(cx, &concat(parts))
+    }
+
+    /// Polls a future that sends one message to the given address.
+    ///
+    /// Fails with [`Unsupported`](io::ErrorKind::Unsupported) by default, for transports without addresses.
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        msg: &[u8],
+        addr: &Self::Address,
+    ) -> Poll<io::Result<()>> {
+        let _ = (cx, msg, addr);
+        Poll::Ready(Err(no_addresses()))
+    }
+    /// Polls a future that receives one message, returning the number of bytes written into the buffer and the address
+    /// of the sender, if it has one.
+    ///
+    /// Never reports an address by default, for transports without addresses.
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Option<Self::Address>)>> {
+        self.poll_recv(cx, buf).map(|r| r.map(|size| (size, None)))
+    }
+}
+
+/// Futures for asynchronously sending and receiving discrete messages.
+///
+/// See the [module-level documentation](self) for more.
+pub trait AsyncDatagramExt: AsyncDatagram {
+    /// Asynchronously sends one message to the peer the transport is connected to.
+    fn send<'a, 'b>(&'a mut self, msg: &'b [u8]) -> SendDatagram<'a, 'b, Self>
+    where
+        Self: Unpin,
+    {
+        SendDatagram(self, msg)
+    }
+    /// Asynchronously receives one message, returning the number of bytes written into the buffer.
+    fn recv<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvDatagram<'a, 'b, Self>
+    where
+        Self: Unpin,
+    {
+        RecvDatagram(self, buf)
+    }
+    /// Asynchronously sends one message, made up of the given slices in order, to the peer the transport is connected
+    /// to.
+    fn send_vectored<'a, 'b>(&'a mut self, parts: &'b [IoSlice<'b>]) -> SendDatagramVectored<'a, 'b, Self>
+    where
+        Self: Unpin,
+    {
+        SendDatagramVectored(self, parts)
+    }
+    /// Asynchronously sends one message to the given address.
+    fn send_to<'a, 'b>(&'a mut self, msg: &'b [u8], addr: &'b Self::Address) -> SendDatagramTo<'a, 'b, Self>
+    where
+        Self: Unpin,
+    {
+        SendDatagramTo(self, msg, addr)
+    }
+    /// Asynchronously receives one message, returning the number of bytes written into the buffer and the address of
+    /// the sender, if it has one.
+    fn recv_from<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvDatagramFrom<'a, 'b, Self>
+    where
+        Self: Unpin,
+    {
+        RecvDatagramFrom(self, buf)
+    }
+}
+impl<T: AsyncDatagram + ?Sized> AsyncDatagramExt for T {}
+
+/// Future type returned by [`.send()`](AsyncDatagramExt::send).
+#[derive(Debug)]
+pub struct SendDatagram<'a, 'b, T: ?Sized>(&'a mut T, &'b [u8]);
+impl<T: AsyncDatagram + Unpin + ?Sized> Future for SendDatagram<'_, '_, T> {
+    type Output = io::Result<()>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendDatagram(slf, msg) = self.get_mut();
+        Pin::new(&mut **slf).poll_send(cx, msg)
+    }
+}
+/// Future type returned by [`.recv()`](AsyncDatagramExt::recv).
+#[derive(Debug)]
+pub struct RecvDatagram<'a, 'b, T: ?Sized>(&'a mut T, &'b mut [u8]);
+impl<T: AsyncDatagram + Unpin + ?Sized> Future for RecvDatagram<'_, '_, T> {
+    type Output = io::Result<usize>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvDatagram(slf, buf) = self.get_mut();
+        Pin::new(&mut **slf).poll_recv(cx, buf)
+    }
+}
+/// Future type returned by [`.send_vectored()`](AsyncDatagramExt::send_vectored).
+#[derive(Debug)]
+pub struct SendDatagramVectored<'a, 'b, T: ?Sized>(&'a mut T, &'b [IoSlice<'b>]);
+impl<T: AsyncDatagram + Unpin + ?Sized> Future for SendDatagramVectored<'_, '_, T> {
+    type Output = io::Result<()>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendDatagramVectored(slf, parts) = self.get_mut();
+        Pin::new(&mut **slf).poll_send_vectored(cx, parts)
+    }
+}
+/// Future type returned by [`.send_to()`](AsyncDatagramExt::send_to).
+pub struct SendDatagramTo<'a, 'b, T: AsyncDatagram + ?Sized>(&'a mut T, &'b [u8], &'b T::Address);
+impl<T: AsyncDatagram + Unpin + ?Sized> Future for SendDatagramTo<'_, '_, T> {
+    type Output = io::Result<()>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendDatagramTo(slf, msg, addr) = self.get_mut();
+        Pin::new(&mut **slf).poll_send_to(cx, msg, addr)
+    }
+}
+/// Future type returned by [`.recv_from()`](AsyncDatagramExt::recv_from).
+#[derive(Debug)]
+pub struct RecvDatagramFrom<'a, 'b, T: ?Sized>(&'a mut T, &'b mut [u8]);
+impl<T: AsyncDatagram + Unpin + ?Sized> Future for RecvDatagramFrom<'_, '_, T> {
+    type Output = io::Result<(usize, Option<T::Address>)>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvDatagramFrom(slf, buf) = self.get_mut();
+        Pin::new(&mut **slf).poll_recv_from(cx, buf)
+    }
+}
+
+/// Turns the number of bytes the system reports having sent into the result of sending a whole message.
+pub(crate) fn check_sent(sent: usize, len: usize) -> io::Result<()> {
+    if sent == len {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::Other, PartialMsgWriteError))
+    }
+}
+/// Sums up the lengths of the slices of a message.
+pub(crate) fn total_len(parts: &[IoSlice<'_>]) -> usize {
+    parts.iter().map(|part| part.len()).sum()
+}
+fn concat(parts: &[IoSlice<'_>]) -> Vec<u8> {
+    let mut msg = Vec::with_capacity(total_len(parts));
+    for part in parts {
+        msg.extend_from_slice(part);
+    }
+    msg
+}
+/// Copies as much of an oversized message as fits into the buffer, for transports which don't truncate by themselves.
+#[cfg(windows)]
+pub(crate) fn truncate_into(msg: &[u8], buf: &mut [u8]) -> usize {
+    let len = msg.len().min(buf.len());
+    buf[..len].copy_from_slice(&msg[..len]);
+    len
+}
+fn no_addresses() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "this transport doesn't support addressing individual messages",
+    )
+}
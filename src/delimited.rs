@@ -0,0 +1,63 @@
+//! Reading up to a delimiter by peeking first, so that nothing past the delimiter is consumed.
+
+use std::io;
+
+/// The most that is peeked at once.
+pub(crate) const PEEK_CHUNK: usize = 512;
+
+/// Returns how much of the peeked data to consume, and whether that includes the delimiter.
+pub(crate) fn delimited_len(peeked: &[u8], delim: u8) -> (usize, bool) {
+    match peeked.iter().position(|&b| b == delim) {
+        Some(idx) => (idx + 1, true),
+        None => (peeked.len(), false),
+    }
+}
+
+/// Appends everything up to and including the delimiter, or up to end-of-file, to `out`, returning how many bytes were
+/// appended. `peek` must wait for data to arrive and report end-of-file by returning zero, like a read would.
+pub(crate) fn read_until_with<T: ?Sized>(
+    stream: &mut T,
+    delim: u8,
+    out: &mut Vec<u8>,
+    mut peek: impl FnMut(&mut T, &mut [u8]) -> io::Result<usize>,
+    mut read: impl FnMut(&mut T, &mut [u8]) -> io::Result<usize>,
+) -> io::Result<usize> {
+    let start = out.len();
+    let mut chunk = [0; PEEK_CHUNK];
+    loop {
+        let peeked = match peek(stream, &mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let (len, found) = delimited_len(&chunk[..peeked], delim);
+        read_exact_with(stream, &mut chunk[..len], &mut read)?;
+        out.extend_from_slice(&chunk[..len]);
+        if found {
+            break;
+        }
+    }
+    Ok(out.len() - start)
+}
+
+fn read_exact_with<T: ?Sized>(
+    stream: &mut T,
+    mut buf: &mut [u8],
+    read: &mut impl FnMut(&mut T, &mut [u8]) -> io::Result<usize>,
+) -> io::Result<()> {
+    while !buf.is_empty() {
+        match read(stream, buf) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "data which was peeked at disappeared before it could be read",
+                ))
+            }
+            Ok(n) => buf = &mut buf[n..],
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
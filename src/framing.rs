@@ -0,0 +1,425 @@
+//! Splitting byte streams into messages by prefixing every message with its length.
+//!
+//! Byte streams, such as local sockets and unnamed pipes, don't preserve the boundaries between writes: a single read
+//! can return parts of several messages, or only part of one. [`MessageWriter`] and [`MessageReader`] wrap any
+//! stream and send and receive whole messages over it, each preceded by its length in one of the
+//! [formats](LengthPrefix) supported by this module.
+//!
+//! Both sides refuse messages longer than a [maximum size](MessageReader::with_max_size), which is
+//! [`DEFAULT_MAX_SIZE`] unless set otherwise, so that a peer which is misbehaving or speaking a different protocol
+//! can't make the reader allocate arbitrary amounts of memory.
+//!
+//! The writer and reader work with any type implementing [`Write`] and [`Read`] respectively. With the `async`
+//! feature, which is enabled by the `tokio` and `async-io` features, they also have asynchronous methods for types
+//! implementing the [`futures`](futures_io) flavors of `AsyncWrite` and `AsyncRead`, which includes all of the
+//! asynchronous stream types of this crate. In that case, [`MessageReader`] and [`TypedStream`] are also
+//! [`Stream`](futures_core::Stream)s of the messages and values they receive, which, unlike the asynchronous reading
+//! methods, can be used with `select!` and timeouts without losing part of a message when a read is cancelled, and
+//! [`MessageWriter`] and [`TypedStream`] are [`Sink`](futures_sink::Sink)s, which buffer messages until they're
+//! flushed. Together, those make for forwarding pipelines with [`forward()`](futures_util::StreamExt::forward).
+//!
+//! A message can also be written from several separate parts, such as a header and a body serialized elsewhere,
+//! without concatenating them first, with [`write_message_vectored()`](MessageWriter::write_message_vectored) or a
+//! [`MessageBuilder`](crate::message_builder::MessageBuilder).
+//!
+//! With the `tokio-util` feature, [`MessageCodec`] implements the same format as a codec for
+//! [`tokio_util::codec`], for use with [`Framed`](tokio_util::codec::Framed) and the rest of its ecosystem.
+//!
+//! With the `bytes` feature, which is also enabled by `tokio-util`, [`MessageReader`] can also hand out messages as
+//! [`Bytes`](bytes::Bytes), which can be kept around or passed to other tasks without copying. Those are cut from a
+//! single receive buffer, which is reused once all of the messages in it have been dropped, so that a busy connection
+//! doesn't cost an allocation per message.
+//!
+//! With the `serde` feature, [`TypedStream`] goes one step further and sends and receives values of a serializable
+//! type, in a [format](Format) such as [bincode](https://docs.rs/bincode) or [postcard](https://docs.rs/postcard),
+//! which are provided by the `bincode` and `postcard` features respectively. With `tokio-util` as well,
+//! [`TypedCodec`] does the same as a codec.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::{
+//!     framing::{MessageReader, MessageWriter},
+//!     unnamed_pipe::pipe,
+//! };
+//! use std::io::BufReader;
+//!
+//! let (tx, rx) = pipe()?;
+//! let mut writer = MessageWriter::new(tx);
+//! // Buffering spares a system call for every byte of the varint length prefixes.
+//! let mut reader = MessageReader::new(BufReader::new(rx));
+//!
+//! writer.write_message(b"Hello")?;
+//! writer.write_message(b"world!")?;
+//! assert_eq!(reader.read_message()?, Some(&b"Hello"[..]));
+//! assert_eq!(reader.read_message()?, Some(&b"world!"[..]));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+#[cfg(feature = "bytes")]
+mod bytes_io;
+#[cfg(feature = "tokio-util")]
+mod codec;
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio-util")))]
+pub use codec::*;
+#[cfg(feature = "async")]
+mod sink;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "serde")]
+mod typed;
+mod vectored;
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "serde")))]
+pub use typed::*;
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, prelude::*},
+};
+
+/// The default maximum size of a message, in bytes.
+pub const DEFAULT_MAX_SIZE: usize = 16 * 1024 * 1024;
+
+/// The longest a varint encoding of a 64-bit length can be.
+const MAX_VARINT_LEN: usize = 10;
+
+/// The format of the length which precedes every message.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LengthPrefix {
+    /// An unsigned LEB128 varint, as used by Protocol Buffers: seven bits of the length per byte, starting with the
+    /// least significant ones, with the most significant bit of every byte but the last one set. Lengths below 128
+    /// take up a single byte. The default.
+    #[default]
+    Varint,
+    /// A little-endian 32-bit integer. Easier to produce and parse by hand, but limits messages to 4 GiB.
+    U32,
+}
+impl LengthPrefix {
+    /// Encodes the length into `buf`, returning the number of bytes used.
+    fn encode(self, len: usize, buf: &mut [u8; MAX_VARINT_LEN]) -> io::Result<usize> {
+        match self {
+            Self::Varint => {
+                let mut len = len as u64;
+                let mut i = 0;
+                loop {
+                    let byte = (len & 0x7F) as u8;
+                    len >>= 7;
+                    if len == 0 {
+                        buf[i] = byte;
+                        return Ok(i + 1);
+                    }
+                    buf[i] = byte | 0x80;
+                    i += 1;
+                }
+            }
+            Self::U32 => {
+                let len = u32::try_from(len).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "message too long for a 32-bit length prefix",
+                    )
+                })?;
+                buf[..4].copy_from_slice(&len.to_le_bytes());
+                Ok(4)
+            }
+        }
+    }
+}
+
+/// Adds the next byte of a varint to the length decoded so far, returning `true` if it was the last one.
+fn push_varint_byte(len: &mut u64, index: usize, byte: u8) -> io::Result<bool> {
+    if index == MAX_VARINT_LEN - 1 && byte > 1 {
+        // Only the lowest bit of the tenth byte fits into 64 bits.
+        return Err(invalid_prefix());
+    }
+    *len |= u64::from(byte & 0x7F) << (7 * index);
+    Ok(byte & 0x80 == 0)
+}
+fn invalid_prefix() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed message length prefix")
+}
+fn check_size(len: u64, max_size: usize) -> io::Result<usize> {
+    usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= max_size)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message exceeds the maximum size"))
+}
+
+/// Replaces the contents of `buf` with the message preceded by its length.
+fn frame_into(buf: &mut Vec<u8>, msg: &[u8], prefix: LengthPrefix, max_size: usize) -> io::Result<()> {
+    buf.clear();
+    append_frame(buf, msg, prefix, max_size)
+}
+/// Appends the message preceded by its length to `buf`, leaving it untouched if the message is too long.
+fn append_frame(buf: &mut Vec<u8>, msg: &[u8], prefix: LengthPrefix, max_size: usize) -> io::Result<()> {
+    if msg.len() > max_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "message exceeds the maximum size",
+        ));
+    }
+    let mut prefix_buf = [0; MAX_VARINT_LEN];
+    let prefix_len = prefix.encode(msg.len(), &mut prefix_buf)?;
+    buf.extend_from_slice(&prefix_buf[..prefix_len]);
+    buf.extend_from_slice(msg);
+    Ok(())
+}
+
+/// Writes messages to a stream, preceding each with its length.
+///
+/// See the [module-level documentation](self) for more.
+pub struct MessageWriter<W> {
+    inner: W,
+    prefix: LengthPrefix,
+    max_size: usize,
+    /// Holds the prefix and the message, so that they're written together.
+    buf: Vec<u8>,
+    /// Messages sent through the `Sink` implementation which haven't been written out yet.
+    #[cfg(feature = "async")]
+    pending: sink::Pending,
+}
+impl<W> MessageWriter<W> {
+    /// Wraps the given stream, with a [varint](LengthPrefix::Varint) length prefix and a maximum message size of
+    /// [`DEFAULT_MAX_SIZE`].
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            prefix: LengthPrefix::Varint,
+            max_size: DEFAULT_MAX_SIZE,
+            buf: Vec::new(),
+            #[cfg(feature = "async")]
+            pending: sink::Pending::default(),
+        }
+    }
+    /// Sets the format of the length prefix, which must match that of the reader on the other end.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+    /// Sets the maximum size of a message. Longer messages are rejected without writing anything.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+    /// Borrows the wrapped stream.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+    /// Mutably borrows the wrapped stream. Writing to it directly will corrupt the framing unless the reader on the
+    /// other end expects that.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+    /// Unwraps the stream.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Puts the prefix and the message into the buffer.
+    fn frame(&mut self, msg: &[u8]) -> io::Result<()> {
+        frame_into(&mut self.buf, msg, self.prefix, self.max_size)
+    }
+}
+impl<W: Write> MessageWriter<W> {
+    /// Writes the message, preceded by its length, in a single [`write_all()`](Write::write_all) call.
+    ///
+    /// The stream isn't flushed, which only matters if it's buffered, such as with a [`BufWriter`](io::BufWriter).
+    /// If an error occurs, an unknown part of the message may have been written, leaving the stream out of sync with
+    /// the framing; the connection should be closed in that case.
+    pub fn write_message(&mut self, msg: &[u8]) -> io::Result<()> {
+        self.frame(msg)?;
+        self.inner.write_all(&self.buf)
+    }
+}
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<W: futures_io::AsyncWrite + Unpin> MessageWriter<W> {
+    /// Asynchronously writes the message, preceded by its length.
+    ///
+    /// See [`write_message()`](Self::write_message) for more.
+    pub async fn write_message_async(&mut self, msg: &[u8]) -> io::Result<()> {
+        use futures_util::io::AsyncWriteExt;
+        self.frame(msg)?;
+        self.inner.write_all(&self.buf).await
+    }
+}
+impl<W: Debug> Debug for MessageWriter<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageWriter")
+            .field("inner", &self.inner)
+            .field("prefix", &self.prefix)
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
+
+/// Reads messages from a stream, each preceded by its length.
+///
+/// The varint length prefix is read one byte at a time, and so the stream should be buffered, such as with a
+/// [`BufReader`](io::BufReader), unless the [32-bit prefix](LengthPrefix::U32) is used.
+///
+/// See the [module-level documentation](self) for more.
+pub struct MessageReader<R> {
+    inner: R,
+    prefix: LengthPrefix,
+    max_size: usize,
+    /// Holds the last message read, which is lent out by the reading methods.
+    buf: Vec<u8>,
+    /// The receive buffer which messages read as `Bytes` are split off from.
+    #[cfg(feature = "bytes")]
+    shared_buf: bytes::BytesMut,
+    /// How far reading as a `Stream` has gotten with the current message.
+    #[cfg(feature = "async")]
+    partial: stream::Partial,
+}
+impl<R> MessageReader<R> {
+    /// Wraps the given stream, with a [varint](LengthPrefix::Varint) length prefix and a maximum message size of
+    /// [`DEFAULT_MAX_SIZE`].
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            prefix: LengthPrefix::Varint,
+            max_size: DEFAULT_MAX_SIZE,
+            buf: Vec::new(),
+            #[cfg(feature = "bytes")]
+            shared_buf: bytes::BytesMut::new(),
+            #[cfg(feature = "async")]
+            partial: stream::Partial::default(),
+        }
+    }
+    /// Sets the format of the length prefix, which must match that of the writer on the other end.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+    /// Sets the maximum size of a message. Longer messages make the reading methods fail with
+    /// [`InvalidData`](io::ErrorKind::InvalidData) before any memory is allocated for them.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+    /// Borrows the wrapped stream.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    /// Mutably borrows the wrapped stream. Reading from it directly will corrupt the framing unless the writer on the
+    /// other end expects that.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+    /// Unwraps the stream.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+impl<R: Read> MessageReader<R> {
+    /// Reads the next message and lends it out until the next call, or returns `None` if the stream ended at a message
+    /// boundary.
+    ///
+    /// End of file in the middle of a message is reported as [`UnexpectedEof`](io::ErrorKind::UnexpectedEof), and a
+    /// malformed or overly large length prefix as [`InvalidData`](io::ErrorKind::InvalidData). The stream should be
+    /// closed after any error, since there's no telling where the next message starts.
+    pub fn read_message(&mut self) -> io::Result<Option<&[u8]>> {
+        let Some(len) = self.read_len()? else { return Ok(None) };
+        self.buf.resize(len, 0);
+        self.inner.read_exact(&mut self.buf)?;
+        Ok(Some(&self.buf))
+    }
+
+    /// Reads the length prefix of the next message, or returns `None` if the stream ended at a message boundary.
+    fn read_len(&mut self) -> io::Result<Option<usize>> {
+        let mut byte = [0];
+        if self.inner.read(&mut byte)? == 0 {
+            return Ok(None);
+        }
+        let len = match self.prefix {
+            LengthPrefix::Varint => {
+                let mut len = 0;
+                let mut index = 0;
+                while !push_varint_byte(&mut len, index, byte[0])? {
+                    index += 1;
+                    self.inner.read_exact(&mut byte)?;
+                }
+                len
+            }
+            LengthPrefix::U32 => {
+                let mut rest = [0; 3];
+                self.inner.read_exact(&mut rest)?;
+                u64::from(u32::from_le_bytes([byte[0], rest[0], rest[1], rest[2]]))
+            }
+        };
+        check_size(len, self.max_size).map(Some)
+    }
+}
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<R: futures_io::AsyncRead + Unpin> MessageReader<R> {
+    /// Asynchronously reads the next message and lends it out until the next call, or returns `None` if the stream
+    /// ended at a message boundary.
+    ///
+    /// See [`read_message()`](Self::read_message) for more. The future isn't cancel-safe: dropping it after it has
+    /// read part of a message leaves the stream out of sync with the framing.
+    pub async fn read_message_async(&mut self) -> io::Result<Option<&[u8]>> {
+        use futures_util::io::AsyncReadExt;
+        let Some(len) = self.read_len_async().await? else {
+            return Ok(None);
+        };
+        self.buf.resize(len, 0);
+        self.inner.read_exact(&mut self.buf).await?;
+        Ok(Some(&self.buf))
+    }
+
+    /// Asynchronously reads the length prefix of the next message, or returns `None` if the stream ended at a message
+    /// boundary.
+    async fn read_len_async(&mut self) -> io::Result<Option<usize>> {
+        use futures_util::io::AsyncReadExt;
+        let mut byte = [0];
+        if self.inner.read(&mut byte).await? == 0 {
+            return Ok(None);
+        }
+        let len = match self.prefix {
+            LengthPrefix::Varint => {
+                let mut len = 0;
+                let mut index = 0;
+                while !push_varint_byte(&mut len, index, byte[0])? {
+                    index += 1;
+                    self.inner.read_exact(&mut byte).await?;
+                }
+                len
+            }
+            LengthPrefix::U32 => {
+                let mut rest = [0; 3];
+                self.inner.read_exact(&mut rest).await?;
+                u64::from(u32::from_le_bytes([byte[0], rest[0], rest[1], rest[2]]))
+            }
+        };
+        check_size(len, self.max_size).map(Some)
+    }
+}
+impl<R: Debug> Debug for MessageReader<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageReader")
+            .field("inner", &self.inner)
+            .field("prefix", &self.prefix)
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
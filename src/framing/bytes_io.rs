@@ -0,0 +1,56 @@
+use super::MessageReader;
+use bytes::Bytes;
+use std::io::{self, prelude::*};
+
+/// The least amount of space set aside in the shared receive buffer whenever it runs out, so that small messages don't
+/// need an allocation each.
+const MIN_RESERVE: usize = 8 * 1024;
+
+impl<R> MessageReader<R> {
+    /// Makes room for a message of the given length at the start of the shared receive buffer.
+    fn prepare_shared_buf(&mut self, len: usize) {
+        let buf = &mut self.shared_buf;
+        buf.clear();
+        // Reclaims the space taken up by earlier messages if they have all been dropped.
+        buf.reserve(len.max(MIN_RESERVE));
+        buf.resize(len, 0);
+    }
+    fn take_shared_buf(&mut self) -> Bytes {
+        self.shared_buf.split().freeze()
+    }
+}
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "bytes")))]
+impl<R: Read> MessageReader<R> {
+    /// Reads the next message and returns it as [`Bytes`], or returns `None` if the stream ended at a message boundary.
+    ///
+    /// Unlike with [`read_message()`](Self::read_message), the message doesn't have to be done with before the next
+    /// one is read. Messages are cut from a receive buffer which is shared between them and reused once all of them
+    /// have been dropped, and so holding on to one message for a long time keeps the memory of those received around
+    /// the same time from being reused.
+    ///
+    /// Errors are reported in the same way as with `read_message()`.
+    pub fn read_message_bytes(&mut self) -> io::Result<Option<Bytes>> {
+        let Some(len) = self.read_len()? else { return Ok(None) };
+        self.prepare_shared_buf(len);
+        self.inner.read_exact(&mut self.shared_buf)?;
+        Ok(Some(self.take_shared_buf()))
+    }
+}
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(all(feature = "bytes", feature = "async"))))]
+impl<R: futures_io::AsyncRead + Unpin> MessageReader<R> {
+    /// Asynchronously reads the next message and returns it as [`Bytes`], or returns `None` if the stream ended at a
+    /// message boundary.
+    ///
+    /// See [`read_message_bytes()`](Self::read_message_bytes) for more. Like
+    /// [`read_message_async()`](Self::read_message_async), the future isn't cancel-safe.
+    pub async fn read_message_bytes_async(&mut self) -> io::Result<Option<Bytes>> {
+        use futures_util::io::AsyncReadExt;
+        let Some(len) = self.read_len_async().await? else {
+            return Ok(None);
+        };
+        self.prepare_shared_buf(len);
+        self.inner.read_exact(&mut self.shared_buf).await?;
+        Ok(Some(self.take_shared_buf()))
+    }
+}
@@ -0,0 +1,261 @@
+use super::{check_size, push_varint_byte, LengthPrefix, DEFAULT_MAX_SIZE, MAX_VARINT_LEN};
+use bytes::{Buf, BufMut, BytesMut};
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+#[cfg(feature = "serde")]
+use {
+    super::Format,
+    serde::{de::DeserializeOwned, Serialize},
+    std::{
+        fmt::{self, Debug, Formatter},
+        marker::PhantomData,
+    },
+};
+
+/// A [`tokio_util::codec`] codec for length-prefixed messages, in the same format as [`MessageWriter`] and
+/// [`MessageReader`](super::MessageReader).
+///
+/// Decoding yields every message as a [`BytesMut`], and anything that can be borrowed as a byte slice can be encoded.
+/// Either end of a connection can thus use the codec while the other uses the writer and reader, or a different
+/// implementation of the same format.
+///
+/// The Tokio-based local socket streams and byte-mode named pipe streams of this crate can be turned into a
+/// [`Framed`] stream and sink of messages with [`framed()`](Self::framed).
+///
+/// # Example
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> std::io::Result<()> {
+/// use futures::{SinkExt, StreamExt};
+/// use interprocess::{framing::MessageCodec, local_socket::tokio::LocalSocketStream};
+///
+/// let conn = LocalSocketStream::connect("/tmp/example.sock").await?;
+/// let mut framed = MessageCodec::new().framed(conn);
+/// framed.send(&b"Hello from the client!"[..]).await?;
+/// if let Some(reply) = framed.next().await.transpose()? {
+///     println!("Server answered: {}", String::from_utf8_lossy(&reply));
+/// }
+/// # Ok(()) }
+/// ```
+///
+/// [`MessageWriter`]: super::MessageWriter
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MessageCodec {
+    prefix: LengthPrefix,
+    max_size: usize,
+}
+impl MessageCodec {
+    /// Creates a codec with a [varint](LengthPrefix::Varint) length prefix and a maximum message size of
+    /// [`DEFAULT_MAX_SIZE`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            prefix: LengthPrefix::Varint,
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+    /// Sets the format of the length prefix, which must match that of the other end.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub const fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+    /// Sets the maximum size of a message. Longer messages are rejected by the encoder with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput), and make the decoder fail with
+    /// [`InvalidData`](io::ErrorKind::InvalidData) before any memory is allocated for them.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub const fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+    /// Wraps the given stream in a [`Framed`] which uses this codec.
+    #[inline]
+    pub fn framed<T: AsyncRead + AsyncWrite>(self, io: T) -> Framed<T, Self> {
+        Framed::new(io, self)
+    }
+
+    /// Decodes the length prefix at the start of `src`, returning the length of the message and of the prefix, or
+    /// `None` if the prefix hasn't been received in full yet.
+    fn decode_prefix(&self, src: &[u8]) -> io::Result<Option<(usize, usize)>> {
+        let (len, prefix_len) = match self.prefix {
+            LengthPrefix::Varint => {
+                let mut len = 0;
+                let mut prefix_len = None;
+                for (index, &byte) in src.iter().take(MAX_VARINT_LEN).enumerate() {
+                    if push_varint_byte(&mut len, index, byte)? {
+                        prefix_len = Some(index + 1);
+                        break;
+                    }
+                }
+                let Some(prefix_len) = prefix_len else { return Ok(None) };
+                (len, prefix_len)
+            }
+            LengthPrefix::U32 => match src.get(..4) {
+                Some(prefix) => (u64::from(u32::from_le_bytes(prefix.try_into().unwrap())), 4),
+                None => return Ok(None),
+            },
+        };
+        Ok(Some((check_size(len, self.max_size)?, prefix_len)))
+    }
+}
+impl Default for MessageCodec {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Decoder for MessageCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        let Some((len, prefix_len)) = self.decode_prefix(src)? else {
+            return Ok(None);
+        };
+        if src.len() - prefix_len < len {
+            src.reserve(prefix_len + len - src.len());
+            return Ok(None);
+        }
+        src.advance(prefix_len);
+        Ok(Some(src.split_to(len)))
+    }
+}
+impl<T: AsRef<[u8]>> Encoder<T> for MessageCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        let msg = item.as_ref();
+        if msg.len() > self.max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "message exceeds the maximum size",
+            ));
+        }
+        let mut prefix = [0; MAX_VARINT_LEN];
+        let prefix_len = self.prefix.encode(msg.len(), &mut prefix)?;
+        dst.reserve(prefix_len + msg.len());
+        dst.put_slice(&prefix[..prefix_len]);
+        dst.put_slice(msg);
+        Ok(())
+    }
+}
+
+/// A [`tokio_util::codec`] codec for values of type `T`, serialized in the format `F`, in the same format as
+/// [`TypedStream`](super::TypedStream).
+///
+/// The messages are those of [`MessageCodec`], with a [32-bit](LengthPrefix::U32) length prefix by default. Values are
+/// decoded straight from the read buffer of the [`Framed`], and encoded into a serialization buffer which the codec
+/// reuses, and so neither takes an allocation of its own with formats which implement
+/// [`serialize_into()`](Format::serialize_into).
+///
+/// # Example
+/// ```no_run
+/// # #[cfg(feature = "bincode")]
+/// # #[tokio::main]
+/// # async fn main() -> std::io::Result<()> {
+/// use futures::{SinkExt, StreamExt};
+/// use interprocess::{
+///     framing::{Bincode, TypedCodec},
+///     local_socket::tokio::LocalSocketStream,
+/// };
+///
+/// let conn = LocalSocketStream::connect("/tmp/example.sock").await?;
+/// let mut framed = TypedCodec::<(u32, String), _>::new(Bincode).framed(conn);
+/// framed.send(&(1, String::from("first"))).await?;
+/// if let Some((id, reply)) = framed.next().await.transpose()? {
+///     println!("Reply to request {id}: {reply}");
+/// }
+/// # Ok(()) }
+/// # #[cfg(not(feature = "bincode"))] fn main() {}
+/// ```
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "serde")))]
+pub struct TypedCodec<T, F> {
+    inner: MessageCodec,
+    format: F,
+    /// Holds the serialized value before it's framed.
+    scratch: Vec<u8>,
+    _phantom: PhantomData<fn(T) -> T>,
+}
+#[cfg(feature = "serde")]
+impl<T, F: Format> TypedCodec<T, F> {
+    /// Creates a codec with a [32-bit](LengthPrefix::U32) length prefix and a maximum message size of
+    /// [`DEFAULT_MAX_SIZE`].
+    #[inline]
+    pub fn new(format: F) -> Self {
+        Self {
+            inner: MessageCodec::new().with_prefix(LengthPrefix::U32),
+            format,
+            scratch: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+#[cfg(feature = "serde")]
+impl<T, F> TypedCodec<T, F> {
+    /// Sets the format of the length prefix, which must match that of the other end.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.inner = self.inner.with_prefix(prefix);
+        self
+    }
+    /// Sets the maximum size of a serialized value. Larger values are rejected by the encoder with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput), and make the decoder fail with
+    /// [`InvalidData`](io::ErrorKind::InvalidData) before any memory is allocated for them.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.inner = self.inner.with_max_size(max_size);
+        self
+    }
+    /// Wraps the given stream in a [`Framed`] which uses this codec.
+    #[inline]
+    pub fn framed<S: AsyncRead + AsyncWrite>(self, io: S) -> Framed<S, Self> {
+        Framed::new(io, self)
+    }
+}
+#[cfg(feature = "serde")]
+impl<T: DeserializeOwned, F: Format> Decoder for TypedCodec<T, F> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<T>> {
+        match self.inner.decode(src)? {
+            Some(msg) => self.format.deserialize(&msg).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+#[cfg(feature = "serde")]
+impl<T: Serialize, F: Format> Encoder<&T> for TypedCodec<T, F> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &T, dst: &mut BytesMut) -> io::Result<()> {
+        self.scratch.clear();
+        self.format.serialize_into(item, &mut self.scratch)?;
+        self.inner.encode(&self.scratch[..], dst)
+    }
+}
+#[cfg(feature = "serde")]
+impl<T: Serialize, F: Format> Encoder<T> for TypedCodec<T, F> {
+    type Error = io::Error;
+
+    #[inline]
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        <Self as Encoder<&T>>::encode(self, &item, dst)
+    }
+}
+#[cfg(feature = "serde")]
+impl<T, F: Debug> Debug for TypedCodec<T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedCodec")
+            .field("format", &self.format)
+            .field("prefix", &self.inner.prefix)
+            .field("max_size", &self.inner.max_size)
+            .finish()
+    }
+}
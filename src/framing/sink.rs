@@ -0,0 +1,102 @@
+use super::{append_frame, LengthPrefix, MessageWriter};
+use futures_core::ready;
+use futures_io::AsyncWrite;
+use futures_sink::Sink;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// How many bytes of framed messages are collected before [`poll_ready()`](Sink::poll_ready) writes them out, which
+/// is the size of a pipe buffer on Linux.
+const HIGH_WATER_MARK: usize = 64 * 1024;
+
+/// Framed messages which have been given to a [`Sink`] but not written out yet.
+#[derive(Debug, Default)]
+pub(super) struct Pending {
+    buf: Vec<u8>,
+    /// How much of the buffer has been written by a flush which hasn't finished yet.
+    written: usize,
+}
+impl Pending {
+    /// Makes room for another message, writing out the buffer if it's full.
+    pub(super) fn poll_ready<W: AsyncWrite + Unpin>(
+        &mut self,
+        inner: &mut W,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.buf.len() >= HIGH_WATER_MARK {
+            ready!(self.poll_write_out(inner, cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+    pub(super) fn push(&mut self, msg: &[u8], prefix: LengthPrefix, max_size: usize) -> io::Result<()> {
+        append_frame(&mut self.buf, msg, prefix, max_size)
+    }
+    pub(super) fn poll_flush<W: AsyncWrite + Unpin>(
+        &mut self,
+        inner: &mut W,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        ready!(self.poll_write_out(inner, cx))?;
+        Pin::new(inner).poll_flush(cx)
+    }
+    pub(super) fn poll_close<W: AsyncWrite + Unpin>(
+        &mut self,
+        inner: &mut W,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        ready!(self.poll_write_out(inner, cx))?;
+        Pin::new(inner).poll_close(cx)
+    }
+
+    fn poll_write_out<W: AsyncWrite + Unpin>(&mut self, inner: &mut W, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.written < self.buf.len() {
+            match ready!(Pin::new(&mut *inner).poll_write(cx, &self.buf[self.written..])) {
+                Ok(0) => return Poll::Ready(Err(io::Error::from(io::ErrorKind::WriteZero))),
+                Ok(n) => self.written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+        }
+        self.buf.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Sends messages of any type which can be viewed as bytes, such as `Vec<u8>`, `&[u8]` or `Bytes`.
+///
+/// Messages are framed into a buffer of their own, which is written out once it holds 64 KiB or more, or when the sink
+/// is flushed or closed, so that sending many small messages doesn't take a system call for each. This buffer is
+/// separate from the one used by [`write_message_async()`](MessageWriter::write_message_async), and so the sink should
+/// be flushed before switching to that method, lest the messages be sent out of order.
+///
+/// Messages longer than the [maximum size](MessageWriter::with_max_size) are rejected by
+/// [`start_send()`](Sink::start_send) with [`InvalidInput`](io::ErrorKind::InvalidInput), without affecting those
+/// sent before them. If writing fails, an unknown part of the buffer may have been written, leaving the stream out of
+/// sync with the framing; the connection should be closed in that case.
+///
+/// Since the message type is generic, `SinkExt` methods which don't take a message, such as `close()`, need it spelled
+/// out: `SinkExt::<Vec<u8>>::close(&mut writer)`.
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<W: AsyncWrite + Unpin, M: AsRef<[u8]>> Sink<M> for MessageWriter<W> {
+    type Error = io::Error;
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        slf.pending.poll_ready(&mut slf.inner, cx)
+    }
+    fn start_send(self: Pin<&mut Self>, item: M) -> io::Result<()> {
+        let slf = self.get_mut();
+        slf.pending.push(item.as_ref(), slf.prefix, slf.max_size)
+    }
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        slf.pending.poll_flush(&mut slf.inner, cx)
+    }
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        slf.pending.poll_close(&mut slf.inner, cx)
+    }
+}
@@ -0,0 +1,89 @@
+use super::{check_size, push_varint_byte, LengthPrefix, MessageReader};
+use futures_core::{ready, Stream};
+use futures_io::AsyncRead;
+use std::{
+    io, mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// How far [`MessageReader::poll_message()`] has gotten with the message it's reading, kept across polls so that a
+/// message is never lost to a future which is dropped halfway through.
+#[derive(Debug, Default)]
+pub(super) struct Partial {
+    /// Number of bytes of the length prefix read so far.
+    prefix_read: usize,
+    /// The length decoded from the bytes of the prefix read so far.
+    len: u64,
+    /// Number of bytes of the message read so far, once the prefix has been read in full.
+    body_read: Option<usize>,
+}
+
+impl<R: AsyncRead + Unpin> MessageReader<R> {
+    /// Reads the next message into a fresh buffer, or returns `None` if the stream ended at a message boundary.
+    ///
+    /// The prefix is read one byte at a time, regardless of its format, so that no byte is read past it.
+    pub(super) fn poll_message(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Vec<u8>>>> {
+        loop {
+            let partial = &mut self.partial;
+            if let Some(body_read) = partial.body_read {
+                if body_read == self.buf.len() {
+                    *partial = Partial::default();
+                    return Poll::Ready(Ok(Some(mem::take(&mut self.buf))));
+                }
+                match ready!(Pin::new(&mut self.inner).poll_read(cx, &mut self.buf[body_read..]))? {
+                    0 => return Poll::Ready(Err(unexpected_eof())),
+                    n => partial.body_read = Some(body_read + n),
+                }
+                continue;
+            }
+
+            let mut byte = [0];
+            if ready!(Pin::new(&mut self.inner).poll_read(cx, &mut byte))? == 0 {
+                return Poll::Ready(match partial.prefix_read {
+                    0 => Ok(None),
+                    _ => Err(unexpected_eof()),
+                });
+            }
+            let index = partial.prefix_read;
+            partial.prefix_read += 1;
+            let done = match self.prefix {
+                LengthPrefix::Varint => push_varint_byte(&mut partial.len, index, byte[0])?,
+                LengthPrefix::U32 => {
+                    partial.len |= u64::from(byte[0]) << (8 * index);
+                    partial.prefix_read == 4
+                }
+            };
+            if done {
+                let len = check_size(partial.len, self.max_size)?;
+                self.buf.clear();
+                self.buf.resize(len, 0);
+                partial.body_read = Some(0);
+            }
+        }
+    }
+}
+
+/// Yields the messages read from the stream, each in a buffer of its own, ending when the stream ends at a message
+/// boundary.
+///
+/// Unlike [`read_message_async()`](MessageReader::read_message_async), this is cancel-safe: a message read partway by
+/// a future which gets dropped, such as the losing branch of a `select!`, is picked up where it was left off by the
+/// next poll. Mixing the two while a message is partway read corrupts the framing.
+///
+/// Errors are the same as those of [`read_message()`](MessageReader::read_message), and the stream should be dropped
+/// after any of them.
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<R: AsyncRead + Unpin> Stream for MessageReader<R> {
+    type Item = io::Result<Vec<u8>>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_message(cx).map(Result::transpose)
+    }
+}
+
+fn unexpected_eof() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "the stream ended in the middle of a message",
+    )
+}
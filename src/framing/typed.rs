@@ -0,0 +1,314 @@
+use super::{frame_into, LengthPrefix, MessageReader};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, prelude::*},
+    marker::PhantomData,
+};
+#[cfg(feature = "async")]
+use {
+    futures_core::{ready, Stream},
+    futures_sink::Sink,
+    std::{
+        pin::Pin,
+        task::{Context, Poll},
+    },
+};
+
+/// A serialization format for the values sent over a [`TypedStream`].
+///
+/// Implemented by [`Bincode`] and [`Postcard`] with the features of the same name, and can be implemented for any other
+/// format which turns values into a byte buffer and back.
+pub trait Format {
+    /// Serializes the value. Failures should be reported as [`InvalidInput`](io::ErrorKind::InvalidInput).
+    fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> io::Result<Vec<u8>>;
+    /// Serializes the value, appending it to the buffer. Failures should be reported as
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput), and may leave part of the value in the buffer.
+    ///
+    /// This is what [`TypedStream`] and `TypedCodec` use, with a buffer which they reuse from one value to the next.
+    /// The default implementation calls [`serialize()`](Self::serialize) and copies the result, which should be
+    /// overridden by formats that can serialize into an existing buffer, so that sending a value doesn't take an
+    /// allocation.
+    fn serialize_into<T: Serialize + ?Sized>(&self, value: &T, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend_from_slice(&self.serialize(value)?);
+        Ok(())
+    }
+    /// Deserializes a value from the bytes of a whole message. Failures should be reported as
+    /// [`InvalidData`](io::ErrorKind::InvalidData).
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> io::Result<T>;
+}
+
+/// The [bincode](https://docs.rs/bincode) format, with its default configuration.
+#[cfg(feature = "bincode")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "bincode")))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bincode;
+#[cfg(feature = "bincode")]
+impl Format for Bincode {
+    fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> io::Result<Vec<u8>> {
+        bincode::serialize(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+    fn serialize_into<T: Serialize + ?Sized>(&self, value: &T, buf: &mut Vec<u8>) -> io::Result<()> {
+        bincode::serialize_into(buf, value).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> io::Result<T> {
+        bincode::deserialize(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// The [postcard](https://docs.rs/postcard) format, which is more compact than [bincode](Bincode) thanks to varint
+/// integers.
+#[cfg(feature = "postcard")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "postcard")))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Postcard;
+#[cfg(feature = "postcard")]
+impl Format for Postcard {
+    fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> io::Result<Vec<u8>> {
+        postcard::to_allocvec(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+    fn serialize_into<T: Serialize + ?Sized>(&self, value: &T, buf: &mut Vec<u8>) -> io::Result<()> {
+        // The buffer is lost on failure, which is rare enough not to matter.
+        let extended = postcard::to_extend(value, std::mem::take(buf));
+        *buf = extended.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok(())
+    }
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> io::Result<T> {
+        postcard::from_bytes(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// A stream which sends and receives values of type `T`, serialized in the format `F`, as length-prefixed messages.
+///
+/// Works with any stream of this crate, or indeed any type implementing [`Read`] and [`Write`], or the
+/// [`futures`](futures_io) flavors of `AsyncRead` and `AsyncWrite` with the `async` feature. Both ends have to agree on
+/// the type, the format and the [length prefix](LengthPrefix), which is [32-bit](LengthPrefix::U32) by default so that
+/// receiving a value takes two reads from the stream regardless of its size. The messages are otherwise the same as
+/// those of [`MessageWriter`](super::MessageWriter) and [`MessageReader`], which can thus talk to a typed stream as
+/// well.
+///
+/// # Example
+/// ```no_run
+/// # #[cfg(feature = "bincode")] {
+/// use interprocess::{
+///     framing::{Bincode, TypedStream},
+///     local_socket::LocalSocketStream,
+/// };
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// enum Request {
+///     Add(i32, i32),
+///     Quit,
+/// }
+///
+/// let conn = LocalSocketStream::connect("/tmp/example.sock")?;
+/// let mut conn = TypedStream::<Request, _, _>::new(conn, Bincode);
+/// conn.send(&Request::Add(2, 2))?;
+/// conn.send(&Request::Quit)?;
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct TypedStream<T, S, F> {
+    inner: MessageReader<S>,
+    format: F,
+    /// Holds the prefix and the serialized value, so that they're written together.
+    buf: Vec<u8>,
+    /// Holds the serialized value before it's framed.
+    scratch: Vec<u8>,
+    /// Values sent through the `Sink` implementation which haven't been written out yet.
+    #[cfg(feature = "async")]
+    pending: super::sink::Pending,
+    _phantom: PhantomData<fn(T) -> T>,
+}
+impl<T, S, F: Format> TypedStream<T, S, F> {
+    /// Wraps the given stream, with a [32-bit](LengthPrefix::U32) length prefix and a maximum message size of
+    /// [`DEFAULT_MAX_SIZE`](super::DEFAULT_MAX_SIZE).
+    #[inline]
+    pub fn new(inner: S, format: F) -> Self {
+        Self {
+            inner: MessageReader::new(inner).with_prefix(LengthPrefix::U32),
+            format,
+            buf: Vec::new(),
+            scratch: Vec::new(),
+            #[cfg(feature = "async")]
+            pending: Default::default(),
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<T, S, F> TypedStream<T, S, F> {
+    /// Sets the format of the length prefix, which must match that of the other end.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.inner.prefix = prefix;
+        self
+    }
+    /// Sets the maximum size of a serialized value. Sending a larger one fails with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) without writing anything, and receiving one fails with
+    /// [`InvalidData`](io::ErrorKind::InvalidData) before any memory is allocated for it.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.inner.max_size = max_size;
+        self
+    }
+    /// Borrows the wrapped stream.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        self.inner.get_ref()
+    }
+    /// Mutably borrows the wrapped stream. Reading from or writing to it directly will corrupt the framing unless the
+    /// other end expects that.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut S {
+        self.inner.get_mut()
+    }
+    /// Unwraps the stream.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner.into_inner()
+    }
+}
+impl<T: Serialize, S, F: Format> TypedStream<T, S, F> {
+    /// Serializes the value and puts it into the buffer, preceded by its length.
+    fn frame(&mut self, value: &T) -> io::Result<()> {
+        self.scratch.clear();
+        self.format.serialize_into(value, &mut self.scratch)?;
+        frame_into(&mut self.buf, &self.scratch, self.inner.prefix, self.inner.max_size)
+    }
+}
+impl<T: Serialize, S: Write, F: Format> TypedStream<T, S, F> {
+    /// Serializes and sends the value in a single [`write_all()`](Write::write_all) call.
+    ///
+    /// If writing fails, an unknown part of the message may have been sent, and the connection should be closed.
+    pub fn send(&mut self, value: &T) -> io::Result<()> {
+        self.frame(value)?;
+        self.inner.get_mut().write_all(&self.buf)
+    }
+}
+impl<T: DeserializeOwned, S: Read, F: Format> TypedStream<T, S, F> {
+    /// Receives and deserializes the next value.
+    ///
+    /// End of file is reported as [`UnexpectedEof`](io::ErrorKind::UnexpectedEof), even if it occurs between
+    /// values, and a message which fails to deserialize as [`InvalidData`](io::ErrorKind::InvalidData).
+    pub fn recv(&mut self) -> io::Result<T> {
+        match self.inner.read_message()? {
+            Some(bytes) => self.format.deserialize(bytes),
+            None => Err(closed()),
+        }
+    }
+}
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<T: Serialize, S: futures_io::AsyncWrite + Unpin, F: Format> TypedStream<T, S, F> {
+    /// Asynchronously serializes and sends the value.
+    ///
+    /// See [`send()`](Self::send) for more.
+    pub async fn send_async(&mut self, value: &T) -> io::Result<()> {
+        use futures_util::io::AsyncWriteExt;
+        self.frame(value)?;
+        self.inner.get_mut().write_all(&self.buf).await
+    }
+}
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<T: DeserializeOwned, S: futures_io::AsyncRead + Unpin, F: Format> TypedStream<T, S, F> {
+    /// Asynchronously receives and deserializes the next value.
+    ///
+    /// See [`recv()`](Self::recv) for more. The future isn't cancel-safe: dropping it after it has read part of a
+    /// message leaves the stream out of sync with the framing.
+    pub async fn recv_async(&mut self) -> io::Result<T> {
+        match self.inner.read_message_async().await? {
+            Some(bytes) => self.format.deserialize(bytes),
+            None => Err(closed()),
+        }
+    }
+}
+/// Yields the values received from the stream, ending when the stream ends between values instead of failing like
+/// [`recv()`](TypedStream::recv) does.
+///
+/// Unlike [`recv_async()`](TypedStream::recv_async), this is cancel-safe, in the same way as the
+/// [`Stream` implementation of `MessageReader`](MessageReader#impl-Stream-for-MessageReader<R>) which it builds upon.
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<T: DeserializeOwned, S: futures_io::AsyncRead + Unpin, F: Format + Unpin> Stream for TypedStream<T, S, F> {
+    type Item = io::Result<T>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let slf = self.get_mut();
+        let msg = ready!(slf.inner.poll_message(cx));
+        Poll::Ready(msg.transpose().map(|msg| {
+            let msg = msg?;
+            let value = slf.format.deserialize(&msg);
+            // Hands the allocation back for the next message.
+            slf.inner.buf = msg;
+            value
+        }))
+    }
+}
+/// Sends values by reference, with the same buffering as the
+/// [`Sink` implementation of `MessageWriter`](super::MessageWriter#impl-Sink<M>-for-MessageWriter<W>).
+///
+/// Values which fail to serialize or are larger than the maximum size are rejected by
+/// [`start_send()`](Sink::start_send) with [`InvalidInput`](io::ErrorKind::InvalidInput), without affecting those sent
+/// before them.
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<T: Serialize, S: futures_io::AsyncWrite + Unpin, F: Format + Unpin> Sink<&T> for TypedStream<T, S, F> {
+    type Error = io::Error;
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        slf.pending.poll_ready(slf.inner.get_mut(), cx)
+    }
+    fn start_send(self: Pin<&mut Self>, item: &T) -> io::Result<()> {
+        let slf = self.get_mut();
+        slf.scratch.clear();
+        slf.format.serialize_into(item, &mut slf.scratch)?;
+        slf.pending.push(&slf.scratch, slf.inner.prefix, slf.inner.max_size)
+    }
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        slf.pending.poll_flush(slf.inner.get_mut(), cx)
+    }
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let slf = self.get_mut();
+        slf.pending.poll_close(slf.inner.get_mut(), cx)
+    }
+}
+/// Sends owned values, in the same way as the implementation for references.
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<T: Serialize, S: futures_io::AsyncWrite + Unpin, F: Format + Unpin> Sink<T> for TypedStream<T, S, F> {
+    type Error = io::Error;
+    #[inline]
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        <Self as Sink<&T>>::poll_ready(self, cx)
+    }
+    #[inline]
+    fn start_send(self: Pin<&mut Self>, item: T) -> io::Result<()> {
+        <Self as Sink<&T>>::start_send(self, &item)
+    }
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        <Self as Sink<&T>>::poll_flush(self, cx)
+    }
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        <Self as Sink<&T>>::poll_close(self, cx)
+    }
+}
+impl<T, S: Debug, F: Debug> Debug for TypedStream<T, S, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedStream")
+            .field("inner", self.inner.get_ref())
+            .field("format", &self.format)
+            .field("prefix", &self.inner.prefix)
+            .field("max_size", &self.inner.max_size)
+            .finish()
+    }
+}
+
+fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "the other end closed the stream")
+}
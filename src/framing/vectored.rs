@@ -0,0 +1,113 @@
+use super::{MessageWriter, MAX_VARINT_LEN};
+use crate::datagram::total_len;
+use std::io::{self, prelude::*, IoSlice};
+
+/// The state of writing out a list of slices in full, resuming after partial writes, like the unstable
+/// `Write::write_all_vectored()`.
+struct Gather<'a> {
+    bufs: Vec<&'a [u8]>,
+    slices: Vec<IoSlice<'a>>,
+    /// The index of the first slice which hasn't been written out completely.
+    start: usize,
+    /// How much of that slice has been written.
+    offset: usize,
+}
+impl<'a> Gather<'a> {
+    fn new(bufs: Vec<&'a [u8]>) -> Self {
+        let slices = bufs.iter().map(|b| IoSlice::new(b)).collect();
+        Self {
+            bufs,
+            slices,
+            start: 0,
+            offset: 0,
+        }
+    }
+    /// Returns the slices which remain to be written, or `None` if there are none left.
+    fn remaining(&mut self) -> Option<&[IoSlice<'a>]> {
+        while self.start < self.bufs.len() && self.offset == self.bufs[self.start].len() {
+            self.start += 1;
+            self.offset = 0;
+        }
+        let first = self.bufs.get(self.start)?;
+        self.slices[self.start] = IoSlice::new(&first[self.offset..]);
+        Some(&self.slices[self.start..])
+    }
+    /// Accounts for the result of a write, returning the error if it's not one to retry after.
+    fn advance(&mut self, result: io::Result<usize>) -> io::Result<()> {
+        let mut written = match result {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero)),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        while written > 0 {
+            let left = self.bufs[self.start].len() - self.offset;
+            if written < left {
+                self.offset += written;
+                break;
+            }
+            written -= left;
+            self.start += 1;
+            self.offset = 0;
+        }
+        Ok(())
+    }
+}
+
+impl<W> MessageWriter<W> {
+    /// Checks the size of a message made of the given parts and encodes its length prefix.
+    fn frame_vectored(&self, parts: &[IoSlice<'_>], prefix_buf: &mut [u8; MAX_VARINT_LEN]) -> io::Result<usize> {
+        let len = total_len(parts);
+        if len > self.max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "message exceeds the maximum size",
+            ));
+        }
+        self.prefix.encode(len, prefix_buf)
+    }
+}
+fn gather<'a>(prefix: &'a [u8], parts: &'a [IoSlice<'_>]) -> Gather<'a> {
+    let mut bufs = Vec::with_capacity(parts.len() + 1);
+    bufs.push(prefix);
+    bufs.extend(parts.iter().map(|p| &**p));
+    Gather::new(bufs)
+}
+
+impl<W: Write> MessageWriter<W> {
+    /// Writes a message made of the given parts, preceded by its total length, with
+    /// [vectored writes](Write::write_vectored), without copying the parts into a contiguous buffer first.
+    ///
+    /// This is what [`MessageBuilder::write_framed()`](crate::message_builder::MessageBuilder::write_framed) uses. The
+    /// parts are written in as few calls as the stream allows, which is one unless it accepts only part of the data
+    /// at a time; streams which don't support vectored writes write the first non-empty part per call instead. Errors
+    /// are reported in the same way as with [`write_message()`](Self::write_message).
+    pub fn write_message_vectored(&mut self, parts: &[IoSlice<'_>]) -> io::Result<()> {
+        let mut prefix_buf = [0; MAX_VARINT_LEN];
+        let prefix_len = self.frame_vectored(parts, &mut prefix_buf)?;
+        let mut gather = gather(&prefix_buf[..prefix_len], parts);
+        while let Some(slices) = gather.remaining() {
+            let result = self.inner.write_vectored(slices);
+            gather.advance(result)?;
+        }
+        Ok(())
+    }
+}
+#[cfg(feature = "async")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+impl<W: futures_io::AsyncWrite + Unpin> MessageWriter<W> {
+    /// Asynchronously writes a message made of the given parts, preceded by its total length, with vectored writes.
+    ///
+    /// See [`write_message_vectored()`](Self::write_message_vectored) for more.
+    pub async fn write_message_vectored_async(&mut self, parts: &[IoSlice<'_>]) -> io::Result<()> {
+        use futures_util::io::AsyncWriteExt;
+        let mut prefix_buf = [0; MAX_VARINT_LEN];
+        let prefix_len = self.frame_vectored(parts, &mut prefix_buf)?;
+        let mut gather = gather(&prefix_buf[..prefix_len], parts);
+        while let Some(slices) = gather.remaining() {
+            let result = self.inner.write_vectored(slices).await;
+            gather.advance(result)?;
+        }
+        Ok(())
+    }
+}
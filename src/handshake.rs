@@ -0,0 +1,259 @@
+//! Agreeing on a protocol version and a set of optional features with the other end of a new connection.
+//!
+//! Applications which evolve their IPC protocol over time end up with peers of different ages talking to each other:
+//! a service updated before its clients, or a client started against an older daemon. A [`Handshake`] describes what
+//! one end supports – a range of protocol versions and a set of feature flags – and exchanges that with the other
+//! end, so that both settle on the same [outcome](Negotiated) before any application data is sent.
+//!
+//! ## Preamble
+//! Each end sends a fixed-size preamble as soon as the connection is established, then reads the one of the peer.
+//! Both preambles are sent at once rather than one after the other, and so the handshake takes a single round trip
+//! regardless of which end goes first. The preamble consists of the following, with integers in little-endian:
+//! - the magic number, four bytes which identify the application protocol, so that a peer which speaks a different
+//!   protocol altogether is caught right away instead of having its data misinterpreted;
+//! - the oldest and the newest supported protocol versions, as two `u16`s;
+//! - the supported features, as a `u64` bitmask;
+//! - the features which the end can't do without, as another `u64` bitmask.
+//!
+//! The negotiated version is the newest one supported by both ends, and the negotiated features are those supported
+//! by both. Since both ends see both preambles, they arrive at the same outcome – or fail for the same reason – without
+//! having to exchange a verdict. Nothing else is sent, and so the stream is positioned at the beginning of the
+//! application protocol once the handshake succeeds.
+//!
+//! The handshake works with any type implementing [`Read`] and [`Write`]. With the `async` feature, which is enabled
+//! by the `tokio` and `async-io` features, it also works with the [`futures`](futures_io) flavors of `AsyncRead` and
+//! `AsyncWrite`, which includes all of the asynchronous stream types of this crate.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::{handshake::Handshake, local_socket::LocalSocketStream};
+//!
+//! const COMPRESSION: u64 = 1 << 0;
+//! const BATCHING: u64 = 1 << 1;
+//!
+//! let mut conn = LocalSocketStream::connect("/tmp/example.sock")?;
+//! let negotiated = Handshake::new(*b"EXMP", 3)
+//!     .min_version(2)
+//!     .features(COMPRESSION | BATCHING)
+//!     .require(BATCHING)
+//!     .perform(&mut conn)?;
+//! if negotiated.has(COMPRESSION) {
+//!     // Compress everything sent from here on.
+//! }
+//! println!("Talking version {} of the protocol", negotiated.version());
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{self, prelude::*},
+    ops::RangeInclusive,
+};
+
+const PREAMBLE_LEN: usize = 24;
+
+/// What one end of a connection supports, for [negotiating](self) with the other end.
+///
+/// The features are bit flags, the meaning of which is up to the application. A bit should never be given a different
+/// meaning once it has been used, since that would make new peers misunderstand old ones; a new bit should be used for
+/// every new feature instead, and old bits retired.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Handshake {
+    magic: [u8; 4],
+    min_version: u16,
+    max_version: u16,
+    features: u64,
+    required: u64,
+}
+impl Handshake {
+    /// Creates a handshake for the application protocol identified by the given magic number, supporting only the given
+    /// version of it and no optional features.
+    #[inline]
+    pub const fn new(magic: [u8; 4], version: u16) -> Self {
+        Self {
+            magic,
+            min_version: version,
+            max_version: version,
+            features: 0,
+            required: 0,
+        }
+    }
+    /// Sets the oldest version of the protocol which this end can still speak. The newest one is the version passed to
+    /// [`new()`](Self::new).
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub const fn min_version(mut self, min_version: u16) -> Self {
+        self.min_version = min_version;
+        self
+    }
+    /// Sets the features which this end supports.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub const fn features(mut self, features: u64) -> Self {
+        self.features = features;
+        self
+    }
+    /// Sets the features which this end can't do without, failing the handshake if the peer doesn't support them.
+    /// Those are added to the [supported](Self::features) ones.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub const fn require(mut self, required: u64) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Performs the handshake on a stream on which nothing has been sent or received yet.
+    ///
+    /// The handshake is blocking and has no timeout of its own, so a server which accepts connections from untrusted
+    /// processes should perform it off the thread which accepts connections.
+    ///
+    /// # Errors
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned without sending anything if the
+    /// oldest supported version is newer than the newest one.
+    pub fn perform<S: Read + Write + ?Sized>(&self, stream: &mut S) -> Result<Negotiated, HandshakeError> {
+        let ours = self.encode()?;
+        stream.write_all(&ours)?;
+        let mut theirs = [0; PREAMBLE_LEN];
+        stream.read_exact(&mut theirs)?;
+        self.negotiate(&theirs)
+    }
+    /// Asynchronously performs the handshake on a stream on which nothing has been sent or received yet.
+    ///
+    /// See [`perform()`](Self::perform) for more.
+    #[cfg(feature = "async")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+    pub async fn perform_async<S: futures_io::AsyncRead + futures_io::AsyncWrite + Unpin + ?Sized>(
+        &self,
+        stream: &mut S,
+    ) -> Result<Negotiated, HandshakeError> {
+        use futures_util::io::{AsyncReadExt, AsyncWriteExt};
+        let ours = self.encode()?;
+        stream.write_all(&ours).await?;
+        let mut theirs = [0; PREAMBLE_LEN];
+        stream.read_exact(&mut theirs).await?;
+        self.negotiate(&theirs)
+    }
+
+    fn encode(&self) -> io::Result<[u8; PREAMBLE_LEN]> {
+        if self.min_version > self.max_version {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the oldest supported version is newer than the newest one",
+            ));
+        }
+        let mut buf = [0; PREAMBLE_LEN];
+        buf[0..4].copy_from_slice(&self.magic);
+        buf[4..6].copy_from_slice(&self.min_version.to_le_bytes());
+        buf[6..8].copy_from_slice(&self.max_version.to_le_bytes());
+        buf[8..16].copy_from_slice(&(self.features | self.required).to_le_bytes());
+        buf[16..24].copy_from_slice(&self.required.to_le_bytes());
+        Ok(buf)
+    }
+    fn negotiate(&self, theirs: &[u8; PREAMBLE_LEN]) -> Result<Negotiated, HandshakeError> {
+        if theirs[0..4] != self.magic {
+            return Err(HandshakeError::WrongMagic);
+        }
+        let u16_at = |i: usize| u16::from_le_bytes([theirs[i], theirs[i + 1]]);
+        let u64_at = |i: usize| u64::from_le_bytes(theirs[i..i + 8].try_into().unwrap());
+        let (their_min, their_max) = (u16_at(4), u16_at(6));
+        let (their_features, their_required) = (u64_at(8), u64_at(16));
+
+        let version = self.max_version.min(their_max);
+        if version < self.min_version.max(their_min) {
+            return Err(HandshakeError::NoCommonVersion {
+                ours: self.min_version..=self.max_version,
+                theirs: their_min..=their_max,
+            });
+        }
+        let features = (self.features | self.required) & their_features;
+        let missing = (self.required | their_required) & !features;
+        if missing != 0 {
+            return Err(HandshakeError::MissingFeatures(missing));
+        }
+        Ok(Negotiated { version, features })
+    }
+}
+
+/// The outcome of a successful [handshake](self).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Negotiated {
+    version: u16,
+    features: u64,
+}
+impl Negotiated {
+    /// Returns the version of the protocol to use, which is the newest one supported by both ends.
+    #[inline]
+    pub const fn version(&self) -> u16 {
+        self.version
+    }
+    /// Returns the features supported by both ends.
+    #[inline]
+    pub const fn features(&self) -> u64 {
+        self.features
+    }
+    /// Returns `true` if all of the given features are supported by both ends.
+    #[inline]
+    pub const fn has(&self, features: u64) -> bool {
+        self.features & features == features
+    }
+}
+
+/// Error type of [`Handshake::perform()`].
+#[derive(Debug)]
+pub enum HandshakeError {
+    /// The stream failed during the handshake.
+    Io(io::Error),
+    /// The preamble of the peer doesn't start with the same magic number, which means that it speaks a different
+    /// protocol.
+    WrongMagic,
+    /// The ranges of versions supported by the two ends don't overlap.
+    NoCommonVersion {
+        /// The versions supported by this end.
+        ours: RangeInclusive<u16>,
+        /// The versions supported by the peer.
+        theirs: RangeInclusive<u16>,
+    },
+    /// One of the ends requires features which the other one doesn't support. The bitmask holds those features.
+    MissingFeatures(u64),
+}
+impl Display for HandshakeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => Display::fmt(e, f),
+            Self::WrongMagic => f.write_str("the peer speaks a different protocol"),
+            Self::NoCommonVersion { ours, theirs } => write!(
+                f,
+                "no common protocol version (versions {}–{} supported here, {}–{} by the peer)",
+                ours.start(),
+                ours.end(),
+                theirs.start(),
+                theirs.end()
+            ),
+            Self::MissingFeatures(missing) => write!(f, "required features not supported by both ends: {missing:#x}"),
+        }
+    }
+}
+impl Error for HandshakeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for HandshakeError {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+/// Turns negotiation failures into errors of kind [`InvalidData`](io::ErrorKind::InvalidData).
+impl From<HandshakeError> for io::Error {
+    fn from(e: HandshakeError) -> Self {
+        match e {
+            HandshakeError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
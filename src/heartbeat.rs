@@ -0,0 +1,541 @@
+//! Detecting unresponsive peers by exchanging heartbeats over a stream.
+//!
+//! A peer which hangs without closing its end of the connection – stuck in a deadlock, stopped by a debugger or
+//! swapped out under memory pressure – looks exactly like one which has nothing to say: neither named pipes nor Unix
+//! domain sockets report anything until the process actually exits, which might never happen. A [`HeartbeatStream`]
+//! wraps a stream and exchanges ping and pong frames with the other end in the background, alongside the data, and
+//! fails the stream with a [`PeerUnresponsive`] error once nothing at all has been received from the other end for
+//! longer than a [timeout](HeartbeatOptions::timeout).
+//!
+//! Both ends of the connection have to wrap their streams, since data is sent in frames of its own to keep it apart
+//! from the heartbeats. Other than that, the wrapper is transparent: it implements both the
+//! [`futures`](futures_io::AsyncRead) and the [Tokio](tokio::io::AsyncRead) flavors of `AsyncRead` and `AsyncWrite`,
+//! and can be used in place of the stream it wraps.
+//!
+//! The heartbeats are exchanged by two tasks spawned on the Tokio runtime, and so keep going regardless of whether the
+//! stream is being read from or written to. The timeout is suspended while reading is held up by the application: if
+//! the data received but not read yet reaches 64 KiB, the wrapper stops reading from the connection until some of it
+//! is read, and doesn't count the time spent waiting for that against the other end.
+//!
+//! ## Wire format
+//! Every frame is a little-endian 32-bit length of the rest of the frame, followed by a kind byte and a payload which
+//! depends on the kind of the frame:
+//! - **Data** (0) carries a piece of the byte stream
+//! - **Ping** (1) carries a little-endian 64-bit sequence number, which the other end echoes back in a **pong** (2)
+//! - **Shutdown** (3) has no payload and means that the sender won't send any more data, although heartbeats still
+//!   follow
+//!
+//! # Example
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! use interprocess::{
+//!     heartbeat::{HeartbeatOptions, PeerUnresponsive},
+//!     local_socket::tokio::LocalSocketStream,
+//! };
+//! use std::time::Duration;
+//! use tokio::io::AsyncReadExt;
+//!
+//! let conn = LocalSocketStream::connect("/tmp/example-heartbeat.sock").await?;
+//! let mut conn = HeartbeatOptions::new()
+//!     .interval(Duration::from_secs(1))
+//!     .timeout(Duration::from_secs(3))
+//!     .wrap(conn)?;
+//!
+//! let mut buf = [0; 64];
+//! match conn.read(&mut buf).await {
+//!     Ok(len) => println!("received {:?}", &buf[..len]),
+//!     Err(e) if PeerUnresponsive::is(&e) => println!("the other end has hung"),
+//!     Err(e) => return Err(e),
+//! }
+//! # Ok(()) }
+//! ```
+
+use crate::framing::{LengthPrefix, MessageReader, MessageWriter};
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::io::AsyncReadExt;
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite, ReadBuf as TokioReadBuf},
+    sync::Notify,
+    task::JoinHandle,
+    time::{self, Instant},
+};
+
+const DATA: u8 = 0;
+const PING: u8 = 1;
+const PONG: u8 = 2;
+const SHUTDOWN: u8 = 3;
+
+/// The most data a single data frame carries, which is also how much data written to the stream is buffered before
+/// writes start waiting for it to be sent.
+const MAX_CHUNK: usize = 64 * 1024;
+/// How much data received but not read yet is buffered before reading from the connection is paused.
+const RECV_LIMIT: usize = 64 * 1024;
+
+/// The error reported once the other end of a [`HeartbeatStream`] has been silent for longer than the timeout.
+///
+/// Always emitted with the [`TimedOut`](io::ErrorKind::TimedOut) error kind.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PeerUnresponsive {
+    timeout: Duration,
+}
+impl PeerUnresponsive {
+    /// Returns the timeout which the other end exceeded.
+    #[inline]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    /// Checks whether the I/O error is a `PeerUnresponsive` error.
+    pub fn is(e: &io::Error) -> bool {
+        e.get_ref().is_some_and(|e| e.is::<Self>())
+    }
+}
+impl Display for PeerUnresponsive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "the peer hasn't sent anything in {:?}", self.timeout)
+    }
+}
+impl Error for PeerUnresponsive {}
+
+/// Options for wrapping a stream in a [`HeartbeatStream`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct HeartbeatOptions {
+    /// How long the connection may be idle before a ping is sent. 5 seconds by default.
+    pub interval: Duration,
+    /// How long the other end may be silent before it's considered unresponsive. 15 seconds by default.
+    ///
+    /// Has to be longer than the interval at which the other end sends pings, with some leeway for scheduling delays.
+    pub timeout: Duration,
+}
+impl HeartbeatOptions {
+    /// Creates a new builder with default options.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(15),
+        }
+    }
+    /// Sets how long the connection may be idle before a ping is sent.
+    ///
+    /// See the [associated field](#structfield.interval) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+    /// Sets how long the other end may be silent before it's considered unresponsive.
+    ///
+    /// See the [associated field](#structfield.timeout) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Wraps the stream with these options, starting the exchange of heartbeats.
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the interval is zero or not shorter than the
+    /// timeout.
+    pub fn wrap<S>(&self, stream: S) -> io::Result<HeartbeatStream>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        if self.interval.is_zero() || self.interval >= self.timeout {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "heartbeat interval must be non-zero and shorter than the timeout",
+            ));
+        }
+        Ok(HeartbeatStream::with_options(stream, *self))
+    }
+}
+impl Default for HeartbeatOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Data received but not read yet.
+    recv_buf: VecDeque<u8>,
+    /// Whether the other end has shut down its side of the stream.
+    recv_eof: bool,
+    read_waker: Option<Waker>,
+    /// Data written but not sent yet.
+    send_buf: VecDeque<u8>,
+    /// Whether the writing task is in the middle of sending data taken from the buffer.
+    sending: bool,
+    /// Frames to send ahead of the data, which are pongs.
+    control: Vec<Vec<u8>>,
+    write_waker: Option<Waker>,
+    /// Whether the stream has been shut down for writing, and whether the other end has been told.
+    shutdown_requested: bool,
+    shutdown_sent: bool,
+    /// The sequence number of the last ping sent, and when it was sent, until the pong arrives.
+    ping: Option<(u64, Instant)>,
+    rtt: Option<Duration>,
+    /// Set once the connection is gone, to the error to report.
+    error: Option<(io::ErrorKind, Option<PeerUnresponsive>)>,
+}
+impl State {
+    fn error(&self) -> Option<io::Error> {
+        let (kind, unresponsive) = self.error?;
+        Some(match unresponsive {
+            Some(e) => io::Error::new(kind, e),
+            None => io::Error::new(kind, "the connection was lost"),
+        })
+    }
+    fn wake(&mut self) {
+        if let Some(w) = self.read_waker.take() {
+            w.wake();
+        }
+        if let Some(w) = self.write_waker.take() {
+            w.wake();
+        }
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Wakes up the writing task when there's something to send.
+    to_send: Notify,
+    /// Wakes up the reading task when there's room in the receive buffer.
+    room: Notify,
+    options: HeartbeatOptions,
+}
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+    fn fail(&self, kind: io::ErrorKind, unresponsive: Option<PeerUnresponsive>) {
+        let mut state = self.lock();
+        state.error.get_or_insert((kind, unresponsive));
+        state.wake();
+        drop(state);
+        // Stops the other task.
+        self.to_send.notify_one();
+        self.room.notify_one();
+    }
+}
+
+fn frame(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(1 + payload.len());
+    frame.push(kind);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Sends data, pongs and, whenever the connection has been idle for the interval, pings.
+async fn write_frames<W: AsyncWrite + Unpin>(mut writer: MessageWriter<W>, shared: Arc<Shared>) {
+    let mut next_seq = 0_u64;
+    loop {
+        let idle = time::timeout(shared.options.interval, shared.to_send.notified())
+            .await
+            .is_err();
+        let mut frames = Vec::new();
+        {
+            let mut state = shared.lock();
+            if state.error.is_some() {
+                return;
+            }
+            frames.append(&mut state.control);
+            if !state.send_buf.is_empty() {
+                let len = state.send_buf.len().min(MAX_CHUNK);
+                let mut data = Vec::with_capacity(1 + len);
+                data.push(DATA);
+                data.extend(state.send_buf.drain(..len));
+                frames.push(data);
+                state.sending = true;
+                // There's room for more data now.
+                if let Some(w) = state.write_waker.take() {
+                    w.wake();
+                }
+            }
+            if idle && frames.is_empty() {
+                state.ping = Some((next_seq, Instant::now()));
+                frames.push(frame(PING, &next_seq.to_le_bytes()));
+                next_seq += 1;
+            }
+            if state.shutdown_requested && !state.shutdown_sent && state.send_buf.is_empty() {
+                frames.push(frame(SHUTDOWN, &[]));
+                state.shutdown_sent = true;
+            }
+        }
+        for frame in &frames {
+            if writer.write_message_async(frame).await.is_err() {
+                return shared.fail(io::ErrorKind::ConnectionAborted, None);
+            }
+        }
+        let mut state = shared.lock();
+        state.sending = false;
+        if let Some(w) = state.write_waker.take() {
+            w.wake();
+        }
+        if !state.send_buf.is_empty() {
+            // Sends the rest without waiting for another write to come in.
+            drop(state);
+            shared.to_send.notify_one();
+        }
+    }
+}
+
+/// Receives frames, answering pings, until the connection ends or the other end stays silent for too long.
+async fn read_frames<R: AsyncRead + Unpin>(mut reader: MessageReader<R>, shared: Arc<Shared>) {
+    let timeout = shared.options.timeout;
+    loop {
+        // Doesn't count the time spent waiting for the application to make room against the other end.
+        loop {
+            let room = shared.room.notified();
+            {
+                let state = shared.lock();
+                if state.error.is_some() {
+                    return;
+                }
+                if state.recv_buf.len() < RECV_LIMIT {
+                    break;
+                }
+            }
+            room.await;
+        }
+        let msg = match time::timeout(timeout, reader.read_message_async()).await {
+            Ok(Ok(Some(msg))) => msg,
+            Ok(Ok(None)) => return shared.fail(io::ErrorKind::BrokenPipe, None),
+            Ok(Err(_)) => return shared.fail(io::ErrorKind::ConnectionAborted, None),
+            Err(_) => return shared.fail(io::ErrorKind::TimedOut, Some(PeerUnresponsive { timeout })),
+        };
+        let mut state = shared.lock();
+        match msg.split_first() {
+            Some((&DATA, data)) if !state.recv_eof => {
+                state.recv_buf.extend(data);
+                if let Some(w) = state.read_waker.take() {
+                    w.wake();
+                }
+            }
+            Some((&PING, seq)) if seq.len() == 8 => {
+                state.control.push(frame(PONG, seq));
+                drop(state);
+                shared.to_send.notify_one();
+            }
+            Some((&PONG, seq)) if seq.len() == 8 => {
+                let seq = u64::from_le_bytes(seq.try_into().unwrap());
+                if let Some((sent_seq, sent_at)) = state.ping {
+                    if sent_seq == seq {
+                        state.rtt = Some(sent_at.elapsed());
+                        state.ping = None;
+                    }
+                }
+            }
+            Some((&SHUTDOWN, [])) => {
+                state.recv_eof = true;
+                if let Some(w) = state.read_waker.take() {
+                    w.wake();
+                }
+            }
+            _ => {
+                drop(state);
+                return shared.fail(io::ErrorKind::InvalidData, None);
+            }
+        }
+    }
+}
+
+/// A stream which exchanges heartbeats with the other end in the background, failing once the other end stops
+/// responding.
+///
+/// Data written to the stream is buffered and sent by a background task, and so [`flush()`](futures_util::io::AsyncWriteExt::flush)
+/// has to be called to make sure that all of it has been sent, such as before dropping the stream. Dropping the
+/// stream stops the background tasks, which closes the connection.
+///
+/// Once the connection is gone, reads return the data which had been received before that, and then fail, as do
+/// writes. If the other end has become unresponsive, the error has the [`TimedOut`](io::ErrorKind::TimedOut) kind
+/// and a [`PeerUnresponsive`] inside. If the other end has closed the connection without shutting down its side
+/// first, reads fail with [`BrokenPipe`](io::ErrorKind::BrokenPipe).
+///
+/// See the [module-level documentation](self) for more.
+pub struct HeartbeatStream {
+    shared: Arc<Shared>,
+    tasks: [JoinHandle<()>; 2],
+}
+impl HeartbeatStream {
+    /// Wraps the stream with the default options, starting the exchange of heartbeats.
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        Self::with_options(stream, HeartbeatOptions::new())
+    }
+    fn with_options<S>(stream: S, options: HeartbeatOptions) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = stream.split();
+        let shared = Arc::new(Shared {
+            state: Mutex::default(),
+            to_send: Notify::new(),
+            room: Notify::new(),
+            options,
+        });
+        let writer_task = tokio::spawn(write_frames(
+            MessageWriter::new(writer).with_prefix(LengthPrefix::U32),
+            Arc::clone(&shared),
+        ));
+        let reader_task = tokio::spawn(read_frames(
+            MessageReader::new(reader)
+                .with_prefix(LengthPrefix::U32)
+                .with_max_size(1 + MAX_CHUNK),
+            Arc::clone(&shared),
+        ));
+        Self {
+            shared,
+            tasks: [writer_task, reader_task],
+        }
+    }
+    /// Returns the round-trip time measured with the most recent ping which has been answered, or `None` if none has
+    /// been answered yet.
+    ///
+    /// Pings are only sent while the connection is idle, and so this may be outdated on a busy connection.
+    #[inline]
+    pub fn rtt(&self) -> Option<Duration> {
+        self.shared.lock().rtt
+    }
+
+    fn do_read(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut state = self.shared.lock();
+        if !state.recv_buf.is_empty() {
+            let len = buf.len().min(state.recv_buf.len());
+            for (dst, src) in buf.iter_mut().zip(state.recv_buf.drain(..len)) {
+                *dst = src;
+            }
+            drop(state);
+            self.shared.room.notify_one();
+            return Poll::Ready(Ok(len));
+        }
+        if state.recv_eof {
+            return Poll::Ready(Ok(0));
+        }
+        if let Some(e) = state.error() {
+            return Poll::Ready(Err(e));
+        }
+        state.read_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+    fn do_write(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let mut state = self.shared.lock();
+        if let Some(e) = state.error() {
+            return Poll::Ready(Err(e));
+        }
+        if state.shutdown_requested {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the stream has been shut down for writing",
+            )));
+        }
+        let room = MAX_CHUNK - state.send_buf.len();
+        if room == 0 {
+            state.write_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let len = buf.len().min(room);
+        state.send_buf.extend(&buf[..len]);
+        drop(state);
+        self.shared.to_send.notify_one();
+        Poll::Ready(Ok(len))
+    }
+    fn do_flush(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut state = self.shared.lock();
+        if let Some(e) = state.error() {
+            return Poll::Ready(Err(e));
+        }
+        let shutdown_pending = state.shutdown_requested && !state.shutdown_sent;
+        if state.send_buf.is_empty() && !state.sending && !shutdown_pending {
+            return Poll::Ready(Ok(()));
+        }
+        state.write_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+    fn do_shutdown(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut state = self.shared.lock();
+        if !state.shutdown_requested {
+            state.shutdown_requested = true;
+            drop(state);
+            self.shared.to_send.notify_one();
+        } else {
+            drop(state);
+        }
+        self.do_flush(cx)
+    }
+}
+impl Drop for HeartbeatStream {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+impl AsyncRead for HeartbeatStream {
+    #[inline]
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.do_read(cx, buf)
+    }
+}
+impl AsyncWrite for HeartbeatStream {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.do_write(cx, buf)
+    }
+    /// Waits for all of the data written so far to be sent.
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.do_flush(cx)
+    }
+    /// Shuts the stream down for writing, after sending all of the data written so far. Heartbeats keep being
+    /// exchanged.
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.do_shutdown(cx)
+    }
+}
+impl TokioAsyncRead for HeartbeatStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        let bytes_read = futures_core::ready!(self.do_read(cx, buf.initialize_unfilled()))?;
+        buf.advance(bytes_read);
+        Poll::Ready(Ok(()))
+    }
+}
+impl TokioAsyncWrite for HeartbeatStream {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.do_write(cx, buf)
+    }
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.do_flush(cx)
+    }
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.do_shutdown(cx)
+    }
+}
+impl Debug for HeartbeatStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeartbeatStream")
+            .field("options", &self.shared.options)
+            .field("rtt", &self.rtt())
+            .finish_non_exhaustive()
+    }
+}
@@ -10,23 +10,86 @@
 //! - **Local sockets** – similar to TCP sockets, but use filesystem or namespaced paths instead of ports on
 //! `localhost`, depending on the OS, bypassing the network stack entirely; implemented using named pipes on Windows and
 //! Unix domain sockets on Unix
+//! - **Publish-subscribe broker** – fanning messages out by topic from publishers to subscribers over local sockets,
+//! with a choice of policies for subscribers which fall behind (Tokio only)
+//! - **Nameserver** – a per-user registry mapping logical service names to the local socket names services listen
+//! on, so that the processes of an application don't need to hardcode socket paths or pipe names
+//! - **Peer authentication** – a handshake which checks the credentials of the process on the other end of a local
+//! socket connection, as reported by the system, against a policy before the connection is used
+//! - **Message framing** – sending and receiving whole messages over any byte stream, with varint or 32-bit length
+//! prefixes and a cap on message size, synchronously or asynchronously
+//! - **Typed streams** – sending and receiving serializable Rust values between processes, encoded with bincode or
+//! postcard, over any of the above streams
+//! - **Remote procedure calls** – a request-response layer over local sockets with concurrent in-flight calls matched
+//! by correlation ID, per-call timeouts and a handler trait for servers (Tokio only)
+//! - **Stream multiplexing** – running many independent byte stream channels, each with its own flow control, over
+//! a single local socket connection, sparing a connection per subsystem (Tokio only)
+//! - **Bounded channels** – sender and receiver halves of a message channel over any stream, with credit-based
+//! flow control which makes sending wait while the receiving end is full (Tokio only)
+//! - **Heartbeats** – a stream wrapper which exchanges pings with the other end in the background and reports a peer
+//! which has hung without closing the connection as a timeout (Tokio only)
+//! - **Buffered streams** – read and write buffers for asynchronous streams, with `AsyncBufRead` for line-oriented
+//! protocols, sized to match pipe buffers (Tokio only)
+//! - **Protocol negotiation** – exchanging a magic number, a range of protocol versions and feature flags when a
+//! connection is established, so that peers of different ages settle on a version and feature set they both support
+//! - **Graceful shutdown** – closing a local socket connection without losing the last messages in either direction,
+//! by half-closing it and draining what the peer still sends until it hangs up or a deadline passes
+//! - **Framed local sockets** – local socket connections which preserve message boundaries natively, with
+//! `SOCK_SEQPACKET` sockets on Unix and message-mode named pipes on Windows, falling back to length prefixes where
+//! the system lacks them
+//! - **Datagram traits** – sending and receiving discrete messages through one interface, synchronous or
+//! asynchronous, whether they travel over Ud-socket datagrams or message-mode named pipes
+//! - **Scatter-gather messages** – sending a message made of several separate slices, such as a header and a body
+//! serialized elsewhere, in one vectored write instead of concatenating them first
+//! - **Child process channels** – spawning child processes with unnamed pipes and, on Unix, socket pairs already
+//! connected to them, without the platform-specific inheritance boilerplate
+//! - **Peer liveness watching** – noticing promptly that the process on the other end of a connection has exited,
+//! even if the connection itself stays open, by blocking, awaiting or with a callback
+//! - **Handle transfer** – sending file descriptors and handles over local sockets as compact numeric tokens which
+//! application messages can refer to; `SCM_RIGHTS` on Unix and `DuplicateHandle` into the peer on Windows
 //!
 //! ## Platform-specific, but present on both Unix-like systems and Windows
 //! - **Unnamed pipes** – anonymous file-like objects for communicating privately in one direction, most commonly used
 //! to communicate between a child process and its parent
+//! - **Shared memory** – a region of memory mapped into the address spaces of multiple processes, either identified by
+//! name or anonymous and passed around by file descriptor or handle; the fastest but least structured form of IPC
+//! - **Named mutexes** – locks shared by name between processes, which report when their previous owner died while
+//! holding them
+//! - **Named condition variables** – for waiting on state protected by a named mutex to change, monitor-style
+//! - **Named reader-writer locks** – for sharing data between many reading processes while giving writers exclusivity
+//! - **Named semaphores** – counting semaphores shared by name between processes, for limiting concurrent access to a
+//! resource
+//! - **Events** – set/reset notification objects for waking up other processes without going through a socket
+//! - **Named events** – events shared by name between unrelated processes, for "signal the other service" style
+//! notifications
+//! - **One-time initialization** – running a setup routine in exactly one of several processes while the others
+//! wait, recovering from initializers which crash halfway through
+//! - **File locks** – advisory shared/exclusive locks on files, for coordinating processes around a path on the
+//! filesystem
+//! - **Parking** – futex-style sleeping on a word of shared memory until another process changes it, for building
+//! custom synchronization primitives (not available on some BSDs)
 //!
 //! ## Unix-only
 //! - **FIFO files** – special type of file which is similar to unnamed pipes but exists on the filesystem, often
 //! referred to as "named pipes" but completely different from Windows named pipes
 //! - **Unix domain sockets** – a type of socket which is built around the standard networking APIs but uses filesystem
 //! paths instead of ports on `localhost`, optionally using a spearate namespace on Linux akin to Windows named pipes
+//! - **Signals** – interrupting other processes with a signal number and, with realtime signals, a word-sized payload;
+//! received through a self-pipe instead of in signal handlers (Linux and Android only)
+//! - **launchd socket activation** – adopting listening sockets created by launchd for on-demand daemons (macOS only)
+//! - **Memory and process file descriptors** – sealable anonymous in-memory files to pass to other processes, and
+//! race-free handles to processes for signalling them and waiting for them to exit (Linux and Android only)
 //!
 //! ## Windows-only
 //! - **Named pipes** – closely resembles Unix domain sockets, uses a separate namespace instead of on-drive paths
 //!
 //! # Asynchronous I/O
-//! Currently, only Tokio for local sockets, Unix domain sockets and Windows named pipes is supported. Support for
-//! `async-std` is planned.
+//! Asynchronous I/O is supported through the following features:
+//! - `tokio` – local sockets, Unix domain sockets and Windows named pipes
+//! - `async-std` – Unix domain sockets (Unix only)
+//! - `async-io` – a flavor of Unix domain sockets built directly on `async-io`, and `async_io::Async` support for them
+//! and for unnamed pipes (Unix only)
+//! - `mio` – `mio::event::Source` implementations for Unix domain sockets and unnamed pipes (Unix only)
 //!
 //! # Platform support
 //! Interprocess supports Windows and all generic Unix-like systems. Additionally, platform-specific extensions are
@@ -70,6 +133,32 @@
 //!
 //! # Feature gates
 //! - **`tokio`**, *off* by default – enables support for Tokio-powered efficient asynchronous IPC.
+//! - **`tokio-util`**, *off* by default – adds a `tokio_util::codec` codec for length-prefixed messages, which the
+//!   Tokio-based local socket and named pipe streams can be framed with, and a typed flavor of it with `serde`. Implies
+//!   `tokio` and `bytes`.
+//! - **`bytes`**, *off* by default – lets `MessageReader` hand out messages as reference-counted `bytes::Bytes` cut
+//!   from a shared receive buffer, rather than lending them out until the next read.
+//! - **`serde`**, *off* by default – adds `TypedStream`, which sends and receives values of any type implementing
+//!   Serde's `Serialize` and `Deserialize` over a stream, along with the `Format` trait for plugging in a serialization
+//!   format. Together with `tokio`, also enables the RPC layer for local sockets.
+//! - **`bincode`** and **`postcard`**, *off* by default – provide the respective formats for `TypedStream`. Imply
+//!   `serde`.
+//! - **`async-std`**, *off* by default – enables support for asynchronous Ud-sockets on async-std and other runtimes
+//!   built on the `async-io` reactor.
+//! - **`async-io`**, *off* by default – makes the blocking Ud-socket and unnamed pipe types usable with `async_io::Async`
+//!   on Unix, preserving ancillary data support, and lets shared memory doorbells be waited on by its reactor. Implied
+//!   by `async-std`.
+//! - **`mio`**, *off* by default – implements `mio::event::Source` for Ud-sockets and unnamed pipes on Unix, allowing
+//!   them to be registered in custom poll-based event loops.
+//! - **`io-uring`**, *off* by default – adds an io_uring backend for Ud-socket sends, receives and accepts on Linux,
+//!   including ones with ancillary data, so that busy servers can batch many operations into one system call.
+//! - **`zerocopy`**, *off* by default – adds typed views into shared memory for types implementing `FromBytes` and
+//!   `AsBytes` from the `zerocopy` crate.
+//! - **`bytemuck`**, *off* by default – adds typed views into shared memory for types implementing `Pod` from the
+//!   `bytemuck` crate.
+//! - **`read_buf`**, *off* by default, **requires a nightly compiler** – implements the unstable `Read::read_buf()` on
+//!   Ud-socket, local socket, named pipe and unnamed pipe streams, letting them read into uninitialized memory without
+//!   zeroing it first.
 //!
 //! # License
 //! This crate, along with all community contributions made to it, is dual-licensed under the terms of either the
@@ -84,6 +173,7 @@
 // the network
 
 #![cfg_attr(feature = "doc_cfg", feature(doc_cfg))]
+#![cfg_attr(feature = "read_buf", feature(read_buf, core_io_borrowed_buf))]
 #![deny(rust_2018_idioms)]
 #![warn(missing_docs)]
 #![allow(clippy::nonstandard_macro_braces)]
@@ -105,12 +195,30 @@ if you think that your specific case needs to be accounted for, please open an i
 mod macros;
 
 pub mod local_socket;
+pub mod peer_watcher;
+pub mod shared_memory;
+pub mod spawn;
+pub mod sync;
 pub mod unnamed_pipe;
-//pub mod shared_memory;
 
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+pub mod buffered;
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+pub mod channel;
+pub mod datagram;
 pub mod error;
+pub mod framing;
+pub mod handshake;
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+pub mod heartbeat;
+pub mod message_builder;
 pub mod os;
 
+mod delimited;
+
 mod sealed;
 pub(crate) use sealed::Sealed;
 
@@ -154,3 +262,20 @@ fn weaken_buf_init_mut<T>(r: &mut [T]) -> &mut [MaybeUninit<T>] {
         transmute(r)
     }
 }
+/// Reads into the unfilled part of the cursor with a function that takes a possibly uninitialized buffer and returns
+/// how many bytes it wrote into its beginning.
+#[cfg(feature = "read_buf")]
+fn read_into_cursor(
+    mut cursor: std::io::BorrowedCursor<'_>,
+    read: impl FnOnce(&mut [MaybeUninit<u8>]) -> std::io::Result<usize>,
+) -> std::io::Result<()> {
+    let bytes_read = read(unsafe {
+        // SAFETY: the buffer is only written to, never de-initialized
+        cursor.as_mut()
+    })?;
+    unsafe {
+        // SAFETY: the read function initialized that many bytes
+        cursor.advance(bytes_read);
+    }
+    Ok(())
+}
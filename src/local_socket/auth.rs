@@ -0,0 +1,344 @@
+//! Authenticating the process on the other end of a local socket connection before talking to it.
+//!
+//! Local sockets are reachable by any process which can access their name, and servers which act on behalf of their
+//! clients typically need to know who those clients are. The system knows exactly which process is on the other end of
+//! a connection, which makes its word much harder to forge than anything the peer could claim about itself. An
+//! [`Authenticator`] obtains the [credentials](PeerCredentials) of the peer from the system right after the connection
+//! is established, checks them against a caller-supplied [`Policy`], and only hands the stream over if the policy
+//! accepts them.
+//!
+//! ## Handshake
+//! Both ends of the connection have to go through the handshake, since each of them tells the other whether it
+//! accepted the peer. The verdict is a single byte, which each end sends as soon as it has checked the credentials of
+//! the other, before waiting for the verdict of the other end. A rejected peer is thus told so with
+//! [`AuthError::RejectedByPeer`] rather than seeing the connection get closed for no apparent reason, while the reason
+//! for the rejection stays with the end which made it. Clients which don't care about the identity of the server can
+//! simply use the [`AllowAll`] policy.
+//!
+//! Nothing else is sent, and so the stream is positioned at the beginning of the application protocol once the
+//! handshake succeeds.
+//!
+//! ## Credentials
+//! - On Unix, the credentials are the process ID, the effective user ID and the effective group ID of the peer, as
+//!   recorded by the system when the connection was established. The process ID and the group ID are only available
+//!   on Linux, Android, Fuchsia and Redox, which have `SO_PEERCRED`. Other platforms with `LOCAL_PEERCRED` only provide
+//!   the user ID, and no credentials at all are available on Unix-like systems which have neither.
+//! - On Windows, the credentials are the process ID of the peer, the security identifier of the user it runs as and
+//!   the path to its executable. The latter two are looked up from the process ID, which requires
+//!   `PROCESS_QUERY_LIMITED_INFORMATION` access to the peer process; they're missing if that access is denied, as is
+//!   the case when a process which isn't elevated queries one that is.
+//!
+//! Credentials which aren't available are reported as `None`, and policies should treat those as a reason to reject
+//! the peer, as the built-in [`SameUser`] does.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::local_socket::{
+//!     auth::{AuthError, Authenticator, SameUser},
+//!     LocalSocketListener,
+//! };
+//!
+//! let listener = LocalSocketListener::bind("/tmp/example.sock")?;
+//! let auth = Authenticator::new(SameUser);
+//! loop {
+//!     match auth.accept(&listener) {
+//!         Ok((conn, peer)) => {
+//!             println!("Accepted a connection from process {:?}", peer.pid());
+//!             # let _ = conn;
+//!             // Talk to the client here.
+//!         }
+//!         Err(AuthError::Rejected(rejection)) => eprintln!("Rejected a connection: {rejection}"),
+//!         Err(e) => eprintln!("Handshake failed: {e}"),
+//!     }
+//! }
+//! # #[allow(unreachable_code)] Ok::<(), std::io::Error>(())
+//! ```
+
+impmod! {local_socket,
+    current_credentials,
+}
+use super::{LocalSocketListener, LocalSocketStream, ToLocalSocketName};
+#[cfg(windows)]
+use std::path::{Path, PathBuf};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{self, prelude::*},
+};
+
+const ACCEPTED: u8 = 1;
+const REJECTED: u8 = 0;
+
+/// The identity of a process on one end of a local socket connection, as reported by the system.
+///
+/// See the [module-level documentation](self) for which credentials are available on which platforms.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PeerCredentials {
+    pub(crate) pid: Option<u32>,
+    #[cfg(unix)]
+    pub(crate) uid: Option<u32>,
+    #[cfg(unix)]
+    pub(crate) gid: Option<u32>,
+    #[cfg(windows)]
+    pub(crate) sid: Option<String>,
+    #[cfg(windows)]
+    pub(crate) image_path: Option<PathBuf>,
+}
+impl PeerCredentials {
+    /// Returns the credentials of the current process, in the same form as those of a peer, for comparing against.
+    ///
+    /// # System calls
+    /// - `getpid`, `geteuid` and `getegid` on Unix
+    /// - `OpenProcessToken`, `GetTokenInformation`, `ConvertSidToStringSidW` and `QueryFullProcessImageNameW` on
+    ///   Windows
+    pub fn current() -> io::Result<Self> {
+        current_credentials()
+    }
+    /// Returns the ID of the process.
+    #[inline]
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+    /// Returns the effective user ID of the process.
+    ///
+    /// This method is only available on Unix. On other platforms, it's absent and thus any usage of it will result in
+    /// a compile-time error.
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    #[inline]
+    pub fn uid(&self) -> Option<u32> {
+        self.uid
+    }
+    /// Returns the effective group ID of the process.
+    ///
+    /// This method is only available on Unix. On other platforms, it's absent and thus any usage of it will result in
+    /// a compile-time error.
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    #[inline]
+    pub fn gid(&self) -> Option<u32> {
+        self.gid
+    }
+    /// Returns the security identifier of the user the process runs as, in its string form, such as
+    /// `S-1-5-21-1004336348-1177238915-682003330-512`.
+    ///
+    /// This method is only available on Windows. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    #[inline]
+    pub fn sid(&self) -> Option<&str> {
+        self.sid.as_deref()
+    }
+    /// Returns the full path to the executable of the process.
+    ///
+    /// This method is only available on Windows. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    #[inline]
+    pub fn image_path(&self) -> Option<&Path> {
+        self.image_path.as_deref()
+    }
+}
+
+/// A decision on whether to accept a peer, given its credentials.
+///
+/// Implemented for closures, which makes for the most convenient way of writing ad-hoc policies:
+/// ```
+/// use interprocess::local_socket::auth::{PeerCredentials, Policy, Rejection};
+///
+/// fn only_process(pid: u32) -> impl Policy {
+///     move |peer: &PeerCredentials| {
+///         if peer.pid() == Some(pid) {
+///             Ok(())
+///         } else {
+///             Err(Rejection::new("unexpected process"))
+///         }
+///     }
+/// }
+/// # let _ = only_process(1);
+/// ```
+pub trait Policy {
+    /// Accepts the peer or rejects it with a reason.
+    fn check(&self, peer: &PeerCredentials) -> Result<(), Rejection>;
+}
+impl<F: Fn(&PeerCredentials) -> Result<(), Rejection>> Policy for F {
+    #[inline]
+    fn check(&self, peer: &PeerCredentials) -> Result<(), Rejection> {
+        self(peer)
+    }
+}
+
+/// Accepts every peer, for ends of the connection which only need to go through the handshake for the sake of the
+/// other end.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AllowAll;
+impl Policy for AllowAll {
+    #[inline]
+    fn check(&self, _peer: &PeerCredentials) -> Result<(), Rejection> {
+        Ok(())
+    }
+}
+
+/// Accepts peers which run as the same user as the current process: with the same effective user ID on Unix, or the
+/// same user security identifier on Windows. Peers whose user isn't known are rejected.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SameUser;
+impl Policy for SameUser {
+    fn check(&self, peer: &PeerCredentials) -> Result<(), Rejection> {
+        let current = PeerCredentials::current()
+            .map_err(|e| Rejection::new(format!("failed to query the credentials of the current process: {e}")))?;
+        #[cfg(unix)]
+        let (peer_user, current_user) = (peer.uid, current.uid);
+        #[cfg(windows)]
+        let (peer_user, current_user) = (peer.sid.as_deref(), current.sid.as_deref());
+        match peer_user {
+            None => Err(Rejection::new("the user of the peer is unknown")),
+            Some(user) if Some(user) == current_user => Ok(()),
+            Some(_) => Err(Rejection::new("the peer runs as a different user")),
+        }
+    }
+}
+
+/// The reason for which a [`Policy`] rejected a peer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rejection {
+    reason: Cow<'static, str>,
+    credentials: PeerCredentials,
+}
+impl Rejection {
+    /// Creates a rejection with the given reason.
+    pub fn new(reason: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            reason: reason.into(),
+            credentials: PeerCredentials::default(),
+        }
+    }
+    /// Returns the reason given by the policy.
+    #[inline]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+    /// Returns the credentials of the rejected peer.
+    ///
+    /// Those are filled in by the [`Authenticator`], and are all `None` in rejections which didn't come from one.
+    #[inline]
+    pub fn credentials(&self) -> &PeerCredentials {
+        &self.credentials
+    }
+}
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "peer rejected: {}", self.reason)
+    }
+}
+impl Error for Rejection {}
+
+/// Error type of the handshake performed by an [`Authenticator`].
+#[derive(Debug)]
+pub enum AuthError {
+    /// The connection could not be established, or failed during the handshake.
+    Io(io::Error),
+    /// The policy of this end rejected the peer. The connection has been closed after telling the peer so.
+    Rejected(Rejection),
+    /// The peer rejected this end. The connection has been closed.
+    RejectedByPeer,
+}
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => Display::fmt(e, f),
+            Self::Rejected(rejection) => Display::fmt(rejection, f),
+            Self::RejectedByPeer => f.write_str("rejected by the peer"),
+        }
+    }
+}
+impl Error for AuthError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Rejected(rejection) => Some(rejection),
+            Self::RejectedByPeer => None,
+        }
+    }
+}
+impl From<io::Error> for AuthError {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+/// Turns rejections into errors of kind [`PermissionDenied`](io::ErrorKind::PermissionDenied).
+impl From<AuthError> for io::Error {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::Io(e) => e,
+            AuthError::Rejected(rejection) => io::Error::new(io::ErrorKind::PermissionDenied, rejection),
+            AuthError::RejectedByPeer => io::Error::new(io::ErrorKind::PermissionDenied, "rejected by the peer"),
+        }
+    }
+}
+
+/// Performs the [handshake](self#handshake) on new connections, checking the credentials of the peer against a
+/// [`Policy`].
+///
+/// The handshake is blocking and has no timeout of its own, so a server which accepts connections from untrusted
+/// processes should perform it off the thread which accepts connections.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Authenticator<P> {
+    policy: P,
+}
+impl<P: Policy> Authenticator<P> {
+    /// Creates an authenticator which checks peers against the given policy.
+    #[inline]
+    pub fn new(policy: P) -> Self {
+        Self { policy }
+    }
+    /// Borrows the policy.
+    #[inline]
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+    /// Accepts a connection from the listener and performs the handshake on it, returning the stream and the
+    /// credentials of the client if both ends accept each other.
+    pub fn accept(&self, listener: &LocalSocketListener) -> Result<(LocalSocketStream, PeerCredentials), AuthError> {
+        self.authenticate(listener.accept()?)
+    }
+    /// Connects to a server and performs the handshake, returning the stream and the credentials of the server if both
+    /// ends accept each other.
+    pub fn connect<'a>(
+        &self,
+        name: impl ToLocalSocketName<'a>,
+    ) -> Result<(LocalSocketStream, PeerCredentials), AuthError> {
+        self.authenticate(LocalSocketStream::connect(name)?)
+    }
+    /// Performs the handshake on a stream which was obtained by other means, such as being inherited from the parent
+    /// process, and on which nothing has been sent or received yet.
+    pub fn authenticate(
+        &self,
+        mut stream: LocalSocketStream,
+    ) -> Result<(LocalSocketStream, PeerCredentials), AuthError> {
+        let credentials = stream.peer_credentials()?;
+        if let Err(mut rejection) = self.policy.check(&credentials) {
+            // The peer is owed an explanation, but a failure to deliver it doesn't change the outcome. Its own verdict
+            // is waited for before closing the connection, so that it isn't met with a broken pipe when sending it.
+            let _ = stream.write_all(&[REJECTED]).and_then(|()| stream.read_exact(&mut [0]));
+            rejection.credentials = credentials;
+            return Err(AuthError::Rejected(rejection));
+        }
+        stream.write_all(&[ACCEPTED])?;
+        let mut verdict = [0];
+        stream.read_exact(&mut verdict)?;
+        match verdict[0] {
+            ACCEPTED => Ok((stream, credentials)),
+            REJECTED => Err(AuthError::RejectedByPeer),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the peer doesn't speak the handshake protocol",
+            )
+            .into()),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use super::ToLocalSocketName;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+};
+
+impmod! {local_socket,
+    FramedLocalSocket as FramedLocalSocketImpl,
+    FramedLocalSocketListener as FramedLocalSocketListenerImpl,
+}
+
+/// A local socket server for [`FramedLocalSocket`]s, listening for connections.
+///
+/// Framed local sockets are a different kind of socket from [`LocalSocketListener`](super::LocalSocketListener) and
+/// [`LocalSocketStream`](super::LocalSocketStream), and the two can't connect to each other.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::local_socket::FramedLocalSocketListener;
+///
+/// let listener = FramedLocalSocketListener::bind("/tmp/example.sock")?;
+/// let mut conn = listener.accept()?;
+/// while let Some(msg) = conn.recv_msg()? {
+///     let reply = msg.to_ascii_uppercase();
+///     conn.send_msg(&reply)?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct FramedLocalSocketListener(FramedLocalSocketListenerImpl);
+impl FramedLocalSocketListener {
+    /// Creates a framed local socket server with the specified name.
+    ///
+    /// # System calls
+    /// - `socket` on Unix, twice if `SOCK_SEQPACKET` sockets aren't supported
+    /// - `bind` on Unix
+    /// - `listen` on Unix
+    /// - `CreateNamedPipe` on Windows
+    pub fn bind<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        FramedLocalSocketListenerImpl::bind(name).map(Self)
+    }
+    /// Listens for incoming connections to the socket, blocking until a client is connected.
+    ///
+    /// # System calls
+    /// - `accept` on Unix
+    /// - `setsockopt` on Unix, if `SOCK_SEQPACKET` sockets are supported
+    /// - `ConnectNamedPipe` and `CreateNamedPipe` on Windows
+    pub fn accept(&self) -> io::Result<FramedLocalSocket> {
+        self.0.accept().map(FramedLocalSocket)
+    }
+}
+impl Debug for FramedLocalSocketListener {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+/// A local socket connection which sends and receives whole messages, obtained either from
+/// [`FramedLocalSocketListener`] or by connecting to one.
+///
+/// Message boundaries are preserved with whatever the platform provides natively: `SOCK_SEQPACKET` Ud-sockets on Unix
+/// and message-mode named pipes on Windows. On Unix systems which lack `SOCK_SEQPACKET` for Ud-sockets, such as macOS,
+/// a stream socket is used instead, with every message preceded by its length as a 32-bit little-endian integer, in the
+/// same format as [`LengthPrefix::U32`](crate::framing::LengthPrefix::U32). Both ends always agree on which of the two
+/// is used, since the server decides when it's created and the client adapts to it when connecting.
+/// [`is_native()`](Self::is_native) tells which of the two is in use.
+///
+/// The semantics are the same regardless:
+/// - Every successful [`send_msg()`](Self::send_msg) sends exactly one whole message, and every successful
+///   [`recv_msg()`](Self::recv_msg) receives exactly one whole message, never merged with another or truncated.
+/// - Messages can't be empty, since those are indistinguishable from the end of the connection on `SOCK_SEQPACKET`
+///   sockets, and can't be longer than [`MAX_MSG_SIZE`](Self::MAX_MSG_SIZE).
+/// - The end of the connection is reported by [`recv_msg()`](Self::recv_msg) returning `None` once all messages sent
+///   before it have been received.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::local_socket::FramedLocalSocket;
+///
+/// let mut conn = FramedLocalSocket::connect("/tmp/example.sock")?;
+/// conn.send_msg(b"Hello from client!")?;
+/// if let Some(reply) = conn.recv_msg()? {
+///     println!("Server answered: {}", String::from_utf8_lossy(reply));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct FramedLocalSocket(FramedLocalSocketImpl);
+impl FramedLocalSocket {
+    /// The maximum size of a message, in bytes, which is small enough to fit into the socket buffers of every platform.
+    pub const MAX_MSG_SIZE: usize = 64 * 1024;
+
+    /// Connects to a framed local socket server.
+    ///
+    /// # System calls
+    /// - `socket` on Unix, twice if `SOCK_SEQPACKET` sockets aren't supported by the system or the server
+    /// - `connect` on Unix, twice if `SOCK_SEQPACKET` sockets aren't supported by the server
+    /// - `setsockopt` on Unix, if `SOCK_SEQPACKET` sockets are supported
+    /// - `CreateFile` on Windows
+    pub fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        FramedLocalSocketImpl::connect(name).map(Self)
+    }
+    /// Returns `true` if message boundaries are preserved by the system, and `false` if they're emulated with length
+    /// prefixes over a stream socket.
+    #[inline]
+    pub fn is_native(&self) -> bool {
+        self.0.is_native()
+    }
+    /// Sends one message.
+    ///
+    /// # Errors
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned without sending anything if the
+    /// message is empty or longer than [`MAX_MSG_SIZE`](Self::MAX_MSG_SIZE).
+    ///
+    /// # System calls
+    /// - `write` on Unix
+    /// - `WriteFile` on Windows
+    pub fn send_msg(&mut self, msg: &[u8]) -> io::Result<()> {
+        if msg.is_empty() || msg.len() > Self::MAX_MSG_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "messages must be non-empty and no longer than the maximum size",
+            ));
+        }
+        self.0.send_msg(msg)
+    }
+    /// Receives one message and lends it out until the next call, or returns `None` if the peer has closed its end of
+    /// the connection.
+    ///
+    /// # Errors
+    /// An error of kind [`InvalidData`](io::ErrorKind::InvalidData) is returned if the peer sent a message longer than
+    /// [`MAX_MSG_SIZE`](Self::MAX_MSG_SIZE), which can only happen if it doesn't use this type. The connection should
+    /// be closed after that.
+    ///
+    /// # System calls
+    /// - `recv` on Unix, twice per message if `SOCK_SEQPACKET` sockets are used (once to peek at the size)
+    /// - `read` on Unix otherwise
+    /// - `ReadFile` on Windows
+    pub fn recv_msg(&mut self) -> io::Result<Option<&[u8]>> {
+        match self.0.recv_msg()? {
+            Some(msg) if msg.len() > Self::MAX_MSG_SIZE => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "received message is longer than the maximum size",
+            )),
+            msg => Ok(msg),
+        }
+    }
+}
+impl Debug for FramedLocalSocket {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
@@ -0,0 +1,69 @@
+/// A number standing for a file descriptor or handle sent over a [`LocalSocketStream`](super::LocalSocketStream), which
+/// can be put into application messages to refer to it.
+///
+/// The sender obtains a token from [`send_handle()`](super::LocalSocketStream::send_handle) and writes it into a
+/// message in whatever format the application protocol uses – the raw value is a plain `u64`, available through
+/// [`into_raw()`](Self::into_raw). The receiver reads the message, rebuilds the token with
+/// [`from_raw()`](Self::from_raw) and exchanges it for the file descriptor or handle with
+/// [`recv_handle()`](super::LocalSocketStream::recv_handle).
+///
+/// # Implementation
+/// On Unix, the file descriptor is queued to be sent along with the next write to the stream as `SCM_RIGHTS` ancillary
+/// data, which should thus be the message referring to the token. The receiving end collects file descriptors as they
+/// arrive during reads, up to a [limit](super::LocalSocketStream::set_max_pending_handles) which is zero by default, and
+/// the token is simply the position of the file descriptor in the sequence of all file descriptors sent over the
+/// connection.
+///
+/// On Windows, the handle is duplicated into the process on the other end of the named pipe right away, using its
+/// process ID as reported by the system, and the token is the value of the new handle in that process. This requires
+/// `PROCESS_DUP_HANDLE` access to the peer process, which is normally only granted if it runs as the same user at the
+/// same or a lower integrity level. A handle that is never received is leaked in the peer until it exits.
+///
+/// # Example
+/// ```no_run
+/// # #[cfg(unix)] mod example {
+/// use interprocess::local_socket::{HandleToken, LocalSocketStream};
+/// use std::{fs::File, io::{self, prelude::*}, os::unix::io::AsFd};
+///
+/// fn send_file(conn: &mut LocalSocketStream, file: &File) -> io::Result<()> {
+///     let token = conn.send_handle(file.as_fd())?;
+///     // The file descriptor goes along with this write.
+///     conn.write_all(&token.into_raw().to_le_bytes())
+/// }
+/// fn recv_file(conn: &mut LocalSocketStream) -> io::Result<File> {
+///     // Should be done before anything is read, so as not to miss any file descriptors.
+///     conn.set_max_pending_handles(1);
+///     let mut buf = [0; 8];
+///     conn.read_exact(&mut buf)?;
+///     let token = HandleToken::from_raw(u64::from_le_bytes(buf));
+///     // SAFETY: the token was sent by the other end of this very connection
+///     unsafe { conn.recv_handle(token) }.map(File::from)
+/// }
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandleToken(u64);
+impl HandleToken {
+    /// Rebuilds a token from its raw value, such as one received in a message.
+    #[inline]
+    pub const fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+    /// Returns the raw value of the token, to be sent in a message.
+    #[inline]
+    pub const fn into_raw(self) -> u64 {
+        self.0
+    }
+}
+impl From<u64> for HandleToken {
+    #[inline]
+    fn from(raw: u64) -> Self {
+        Self(raw)
+    }
+}
+impl From<HandleToken> for u64 {
+    #[inline]
+    fn from(token: HandleToken) -> Self {
+        token.0
+    }
+}
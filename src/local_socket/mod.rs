@@ -34,27 +34,40 @@
 //! A few missing features, primarily on Windows, require local sockets to omit some important functionality, because
 //! code relying on it wouldn't be portable. Some notable differences are:
 //! - No `.shutdown()` – your communication protocol must manually negotiate end of transmission. Notably,
-//!   `.read_to_string()` and `.read_all()` will always block indefinitely at some point.
+//!   `.read_to_string()` and `.read_all()` will always block indefinitely at some point. [`graceful_shutdown()`]
+//!   half-closes the stream where the platform allows it, and takes care of the rest of the teardown sequence.
 //! - No datagram sockets – the difference in semantics between connectionless datagram Ud-sockets and connection-based
-//!   named message pipes on Windows does not allow bridging those two into a common API. You can emulate datagrams on
-//!   top of streams anyway, so no big deal, right?
+//!   named message pipes on Windows does not allow bridging those two into a common API. Connection-based message
+//!   sockets do have a common API, [`FramedLocalSocket`], which preserves message boundaries natively where possible.
 
 #[cfg(feature = "tokio")]
 #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
 pub mod tokio;
 
+pub mod auth;
+pub mod nameserver;
+
+mod framed;
+pub use framed::*;
+
 mod listener;
 pub use listener::*;
 
 mod stream;
 pub use stream::*;
 
+mod handle_token;
+pub use handle_token::*;
+
 mod name;
 pub use name::*;
 
 mod name_type_support;
 pub use name_type_support::*;
 
+pub(crate) mod shutdown;
+pub use shutdown::{graceful_shutdown, graceful_shutdown_into};
+
 mod to_name;
 pub use to_name::*;
 
@@ -0,0 +1,327 @@
+//! A nameserver for local sockets, mapping logical service names to the local socket names which the services listen
+//! on.
+//!
+//! Instead of every process of a multi-process application agreeing on hardcoded socket paths or pipe names, a
+//! [`Nameserver`] listens on a single well-known name, services [register](NameserverClient::register) the name they
+//! happen to be listening on under a logical service name, and clients [resolve](NameserverClient::resolve) service
+//! names to connect to them.
+//!
+//! ## Scoping
+//! The [default nameserver name](default_name) includes the identity of the current user – their user ID on Unix and
+//! their user name on Windows – so that every user gets a separate registry, and programs run by different users don't
+//! see each other's services. Nameservers can also be bound to any other name, for registries scoped differently.
+//!
+//! Note that the scoping is done purely by name: the nameserver socket itself is created with the default permissions
+//! of the platform.
+//!
+//! ## Lifetime of registrations
+//! A registration belongs to the connection to the nameserver that it was made over, and is removed once that
+//! connection is closed, which happens when the [`NameserverClient`] is dropped or the process exits. Services should
+//! thus keep their client around for as long as they're accepting connections, so that the registry never refers to
+//! services which are no longer running.
+//!
+//! ## Wire format
+//! Every frame is a little-endian 32-bit length of the rest of the frame, followed by a kind byte, a little-endian
+//! 16-bit length of the service name, the service name itself in UTF-8 and the local socket name in UTF-8, which is
+//! empty if there is none. The nameserver answers every request with exactly one frame, in the order in which the
+//! requests were made.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::local_socket::{
+//!     nameserver::{Nameserver, NameserverClient},
+//!     LocalSocketListener,
+//! };
+//! use std::thread;
+//!
+//! // Typically done by a launcher process, or by whichever process happens to start first.
+//! let nameserver = Nameserver::bind_default()?;
+//! thread::spawn(move || nameserver.run());
+//!
+//! // In the service:
+//! let listener = LocalSocketListener::bind("/tmp/example-service-1234.sock")?;
+//! let mut registration = NameserverClient::connect_default()?;
+//! registration.register("example-service", "/tmp/example-service-1234.sock")?;
+//!
+//! // In a client:
+//! let mut nameserver = NameserverClient::connect_default()?;
+//! let conn = nameserver.connect_service("example-service")?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use super::{LocalSocketListener, LocalSocketStream, NameTypeSupport, ToLocalSocketName};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    io::{self, prelude::*},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+const REGISTER: u8 = 1;
+const UNREGISTER: u8 = 2;
+const RESOLVE: u8 = 3;
+const OK: u8 = 4;
+const NOT_FOUND: u8 = 5;
+const TAKEN: u8 = 6;
+
+/// The longest a local socket name stored in the registry can be, in bytes.
+pub const MAX_NAME_LEN: usize = 4096;
+const MAX_FRAME_SIZE: usize = 1 + 2 + u16::MAX as usize + MAX_NAME_LEN;
+
+/// Returns the name which the nameserver of the current user listens on by default.
+///
+/// This is `@interprocess-nameserver-<user>` if the namespace is supported, and
+/// `/tmp/interprocess-nameserver-<user>.sock` otherwise, where `<user>` is the user ID on Unix and the user name on
+/// Windows.
+///
+/// # System calls
+/// - `getuid` on Unix
+/// - `GetUserNameW` on Windows
+pub fn default_name() -> io::Result<String> {
+    let user = current_user()?;
+    Ok(if NameTypeSupport::query().namespace_supported() {
+        format!("@interprocess-nameserver-{user}")
+    } else {
+        format!("/tmp/interprocess-nameserver-{user}.sock")
+    })
+}
+
+#[cfg(unix)]
+fn current_user() -> io::Result<String> {
+    Ok(unsafe { libc::getuid() }.to_string())
+}
+#[cfg(windows)]
+fn current_user() -> io::Result<String> {
+    use winapi::{shared::minwindef::DWORD, um::winbase::GetUserNameW};
+    // UNLEN + 1, from Lmcons.h.
+    let mut buf = [0_u16; 257];
+    let mut len = buf.len() as DWORD;
+    let success = unsafe { GetUserNameW(buf.as_mut_ptr(), &mut len) } != 0;
+    if !success {
+        return Err(io::Error::last_os_error());
+    }
+    // The length includes the terminating nul.
+    let user = String::from_utf16(&buf[..(len as usize).saturating_sub(1)])
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "user name is not valid UTF-16"))?;
+    // Backslashes would be taken as separators in the pipe name.
+    Ok(user.replace('\\', "-"))
+}
+
+/// Service names and the local socket names they're registered with, alongside the ID of the connection which
+/// registered them.
+type Registry = Mutex<HashMap<String, (String, u64)>>;
+
+/// A nameserver, which keeps the registry of services and answers requests from [`NameserverClient`]s.
+///
+/// The nameserver does nothing until [`run()`](Self::run) is called, which is typically done in a thread of its own.
+pub struct Nameserver {
+    listener: LocalSocketListener,
+    registry: Arc<Registry>,
+}
+impl Nameserver {
+    /// Creates a nameserver listening on the given name.
+    pub fn bind<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        Ok(Self {
+            listener: LocalSocketListener::bind(name)?,
+            registry: Arc::default(),
+        })
+    }
+    /// Creates a nameserver listening on the [default name](default_name) for the current user.
+    ///
+    /// Fails with [`AddrInUse`](io::ErrorKind::AddrInUse) if the user already has a nameserver running, or, where the
+    /// name is a file path, if a previous nameserver exited without removing its socket file.
+    pub fn bind_default() -> io::Result<Self> {
+        Self::bind(default_name()?)
+    }
+    /// Accepts connections and answers requests made over them, spawning a thread for every connection.
+    ///
+    /// Only returns if accepting a connection or spawning a thread fails. Connections which have already been
+    /// accepted keep being served regardless, until they disconnect.
+    pub fn run(self) -> io::Result<()> {
+        for id in 0_u64.. {
+            let conn = self.listener.accept()?;
+            let registry = Arc::clone(&self.registry);
+            thread::Builder::new()
+                .name(String::from("interprocess nameserver connection"))
+                .spawn(move || serve(conn, &registry, id))?;
+        }
+        Ok(())
+    }
+}
+impl Debug for Nameserver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Nameserver")
+            .field("listener", &self.listener)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Serves one connection until it disconnects, then removes everything it registered.
+fn serve(mut conn: LocalSocketStream, registry: &Registry, id: u64) {
+    // Stops at end of file, an error, or a malformed request.
+    while let Ok(Some(frame)) = read_frame(&mut conn) {
+        let reply = match frame.kind {
+            REGISTER => {
+                let mut registry = registry.lock().unwrap();
+                match registry.get(&frame.service) {
+                    Some((_, owner)) if *owner != id => encode(TAKEN, &frame.service, ""),
+                    _ => {
+                        registry.insert(frame.service.clone(), (frame.name, id));
+                        encode(OK, &frame.service, "")
+                    }
+                }
+            }
+            UNREGISTER => {
+                let mut registry = registry.lock().unwrap();
+                match registry.get(&frame.service) {
+                    Some((_, owner)) if *owner == id => {
+                        registry.remove(&frame.service);
+                        encode(OK, &frame.service, "")
+                    }
+                    _ => encode(NOT_FOUND, &frame.service, ""),
+                }
+            }
+            RESOLVE => match registry.lock().unwrap().get(&frame.service) {
+                Some((name, _)) => encode(OK, &frame.service, name),
+                None => encode(NOT_FOUND, &frame.service, ""),
+            },
+            // Not something a client sends.
+            _ => break,
+        };
+        // Can't fail, since names in requests are limited in the same way as in replies.
+        let Ok(reply) = reply else { break };
+        if conn.write_all(&reply).is_err() {
+            break;
+        }
+    }
+    registry.lock().unwrap().retain(|_, (_, owner)| *owner != id);
+}
+
+/// A connection to a [`Nameserver`], for registering services and resolving their names.
+///
+/// Services registered through a client stay registered until they're [unregistered](Self::unregister) or the client
+/// is dropped.
+pub struct NameserverClient(LocalSocketStream);
+impl NameserverClient {
+    /// Connects to the nameserver listening on the given name.
+    pub fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        LocalSocketStream::connect(name).map(Self)
+    }
+    /// Connects to the nameserver listening on the [default name](default_name) for the current user.
+    pub fn connect_default() -> io::Result<Self> {
+        Self::connect(default_name()?)
+    }
+
+    /// Registers the service under the given name as listening on the given local socket name.
+    ///
+    /// Registering a service again through the same client replaces the local socket name. Fails with
+    /// [`AlreadyExists`](io::ErrorKind::AlreadyExists) if the service has been registered through a different client.
+    pub fn register(&mut self, service: &str, name: &str) -> io::Result<()> {
+        match self.request(REGISTER, service, name)? {
+            (OK, _) => Ok(()),
+            (TAKEN, _) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "the service has already been registered by someone else",
+            )),
+            _ => Err(invalid_frame()),
+        }
+    }
+    /// Removes the registration of the service, returning `false` if it wasn't registered through this client.
+    pub fn unregister(&mut self, service: &str) -> io::Result<bool> {
+        match self.request(UNREGISTER, service, "")? {
+            (OK, _) => Ok(true),
+            (NOT_FOUND, _) => Ok(false),
+            _ => Err(invalid_frame()),
+        }
+    }
+    /// Looks up the local socket name which the service has been registered with, returning `None` if it isn't
+    /// registered.
+    pub fn resolve(&mut self, service: &str) -> io::Result<Option<String>> {
+        match self.request(RESOLVE, service, "")? {
+            (OK, name) => Ok(Some(name)),
+            (NOT_FOUND, _) => Ok(None),
+            _ => Err(invalid_frame()),
+        }
+    }
+    /// [Resolves](Self::resolve) the name of the service and connects to it, failing with
+    /// [`NotFound`](io::ErrorKind::NotFound) if it isn't registered.
+    pub fn connect_service(&mut self, service: &str) -> io::Result<LocalSocketStream> {
+        let name = self
+            .resolve(service)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such service is registered"))?;
+        LocalSocketStream::connect(name)
+    }
+
+    fn request(&mut self, kind: u8, service: &str, name: &str) -> io::Result<(u8, String)> {
+        self.0.write_all(&encode(kind, service, name)?)?;
+        let frame = read_frame(&mut self.0)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the nameserver closed the connection"))?;
+        if frame.service != service {
+            return Err(invalid_frame());
+        }
+        Ok((frame.kind, frame.name))
+    }
+}
+impl Debug for NameserverClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NameserverClient").field(&self.0).finish()
+    }
+}
+
+struct Frame {
+    kind: u8,
+    service: String,
+    name: String,
+}
+
+fn encode(kind: u8, service: &str, name: &str) -> io::Result<Vec<u8>> {
+    let service_len = u16::try_from(service.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "service name is longer than 65535 bytes"))?;
+    if name.len() > MAX_NAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "local socket name is longer than the maximum length",
+        ));
+    }
+    let len = 1 + 2 + service.len() + name.len();
+    let mut frame = Vec::with_capacity(4 + len);
+    // Can't overflow, since the frame is no larger than MAX_FRAME_SIZE.
+    frame.extend_from_slice(&(len as u32).to_le_bytes());
+    frame.push(kind);
+    frame.extend_from_slice(&service_len.to_le_bytes());
+    frame.extend_from_slice(service.as_bytes());
+    frame.extend_from_slice(name.as_bytes());
+    Ok(frame)
+}
+
+/// Reads one frame, returning `None` on end of file at a frame boundary.
+fn read_frame(reader: &mut impl Read) -> io::Result<Option<Frame>> {
+    let mut len = [0; 4];
+    if reader.read(&mut len[..1])? == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut len[1..])?;
+    let len = u32::from_le_bytes(len) as usize;
+    if !(3..=MAX_FRAME_SIZE).contains(&len) {
+        return Err(invalid_frame());
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+
+    let kind = body[0];
+    let service_len = usize::from(u16::from_le_bytes([body[1], body[2]]));
+    let rest = body.get(3..).unwrap_or_default();
+    if rest.len() < service_len || rest.len() - service_len > MAX_NAME_LEN {
+        return Err(invalid_frame());
+    }
+    let (service, name) = rest.split_at(service_len);
+    Ok(Some(Frame {
+        kind,
+        service: String::from_utf8(service.to_vec()).map_err(|_| invalid_frame())?,
+        name: String::from_utf8(name.to_vec()).map_err(|_| invalid_frame())?,
+    }))
+}
+fn invalid_frame() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed nameserver frame")
+}
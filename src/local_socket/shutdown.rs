@@ -0,0 +1,70 @@
+use super::LocalSocketStream;
+use std::{
+    io::{self, prelude::*},
+    time::Instant,
+};
+
+/// Closes a local socket stream without losing the last messages exchanged over it.
+///
+/// Dropping a connection right after writing the last message to it is prone to losing data in both directions: on
+/// Unix, closing a socket which still has unread incoming data makes the system reset the connection, which can
+/// discard what was written last before the peer gets to read it, and anything the peer sends in the meantime is lost
+/// either way. This function instead goes through the teardown sequence which avoids that:
+/// 1. The writing half of the stream is shut down, so that the peer reads end-of-file once it has received everything
+///    written before.
+/// 2. Whatever the peer still sends is read and discarded until it closes its end of the connection, or until the
+///    deadline passes.
+/// 3. The stream is closed.
+///
+/// Use [`graceful_shutdown_into()`] to keep the data instead.
+///
+/// Named pipes can't be half-closed, so on Windows, the first step is skipped: the peer doesn't find out that no more
+/// data is coming until the stream is closed, and must close its end on its own accord, typically upon receiving a
+/// goodbye message defined by the protocol, for the draining to end before the deadline. Data written to the stream
+/// is still delivered after it's closed, which is what the peer's reads rely on.
+///
+/// # Errors
+/// An error of kind [`TimedOut`](io::ErrorKind::TimedOut) is returned if the peer hasn't closed its end of the
+/// connection by the deadline. The stream is closed regardless of whether an error occurs.
+///
+/// # System calls
+/// - `shutdown` on Unix
+/// - `poll` on Unix, before every read
+/// - `PeekNamedPipe` on Windows, repeatedly before every read until data arrives or the deadline passes
+/// - `read` on Unix
+/// - `ReadFile` on Windows
+pub fn graceful_shutdown(stream: LocalSocketStream, deadline: Instant) -> io::Result<()> {
+    drain(stream, deadline, |_| {})
+}
+/// Same as [`graceful_shutdown()`], but appends the remaining data from the peer to the given buffer instead of
+/// discarding it.
+///
+/// If an error occurs, the buffer holds whatever was received before it did.
+pub fn graceful_shutdown_into(stream: LocalSocketStream, deadline: Instant, buf: &mut Vec<u8>) -> io::Result<()> {
+    drain(stream, deadline, |data| buf.extend_from_slice(data))
+}
+
+fn drain(mut stream: LocalSocketStream, deadline: Instant, mut sink: impl FnMut(&[u8])) -> io::Result<()> {
+    stream.0.shutdown_write()?;
+    let mut buf = [0; 4096];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || !stream.0.wait_readable(remaining)? {
+            return Err(timed_out());
+        }
+        match stream.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => sink(&buf[..n]),
+            // A nonblocking stream can report readiness that a read then doesn't find.
+            Err(e) if matches!(e.kind(), io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+pub(super) fn timed_out() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        "the peer didn't close its end of the connection before the deadline",
+    )
+}
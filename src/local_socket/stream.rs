@@ -1,5 +1,5 @@
 use {
-    super::ToLocalSocketName,
+    super::{auth::PeerCredentials, HandleToken, ToLocalSocketName},
     std::{
         fmt::{self, Debug, Formatter},
         io::{self, prelude::*, IoSlice, IoSliceMut},
@@ -65,6 +65,27 @@ impl LocalSocketStream {
     pub fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
         Ok(Self(LocalSocketStreamImpl::connect(name)?))
     }
+    /// Adopts a duplicate of the standard input of the process, which has to be a connected local socket: a Unix domain
+    /// stream socket on Unix, or a duplex byte-mode named pipe on Windows.
+    ///
+    /// This supports inetd-style deployment, where a superserver accepts connections and starts a process for each of
+    /// them, as well as parents which talk to their children over a socket or pipe passed as the child's standard
+    /// input. The standard input itself is left untouched.
+    ///
+    /// An error is returned if the standard input is of a different type.
+    pub fn from_stdin() -> io::Result<Self> {
+        LocalSocketStreamImpl::from_stdin().map(Self)
+    }
+    /// Same as [`from_stdin()`](Self::from_stdin), but also checks that the standard output refers to the same socket.
+    /// See [`UdStream::from_stdio()`](crate::os::unix::udsocket::UdStream::from_stdio).
+    ///
+    /// This method is only available on Unix, since Windows provides no way to tell whether two handles refer to the
+    /// same pipe. On other platforms, it's absent and thus any usage of it will result in a compile-time error.
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn from_stdio() -> io::Result<Self> {
+        LocalSocketStreamImpl::from_stdio().map(Self)
+    }
     /// Enables or disables the nonblocking mode for the stream. By default, it is disabled.
     ///
     /// In nonblocking mode, reading and writing will immediately return with the
@@ -76,6 +97,110 @@ impl LocalSocketStream {
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
         self.0.set_nonblocking(nonblocking)
     }
+    /// Reads up to and including the delimiter, or up to end-of-file, appending the data to `buf` and returning how
+    /// many bytes were appended.
+    ///
+    /// Unlike [`BufRead::read_until()`](std::io::BufRead::read_until) on a buffered reader, this never reads past the
+    /// delimiter: the incoming data is peeked at first, and only as much of it as is needed is consumed. Whatever comes
+    /// after the delimiter is thus left in the stream, for code which takes over the stream afterwards – such as
+    /// another process it's passed to – to read. The cost of that is two system calls per read instead of one.
+    ///
+    /// On Windows, named pipes can't be waited on for data without reading it, and so the pipe is polled with a
+    /// gradually increasing interval of up to 16 milliseconds while no data is available.
+    ///
+    /// # System calls
+    /// - `recv` (`MSG_PEEK`) and `recvmsg` on Unix
+    /// - `PeekNamedPipe`, repeatedly until data arrives, and `ReadFile` on Windows
+    pub fn read_until(&mut self, delim: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.0.read_until(delim, buf)
+    }
+    /// Returns the credentials of the process on the other end of the connection, as reported by the system. See
+    /// [`auth`](super::auth) for which credentials are available on which platforms, and for authenticating peers
+    /// based on them.
+    ///
+    /// # System calls
+    /// - `getsockopt` (`SO_PEERCRED` or `LOCAL_PEERCRED`) on Unix
+    /// - `GetNamedPipeClientProcessId` or `GetNamedPipeServerProcessId`, `OpenProcess`, `OpenProcessToken`,
+    ///   `GetTokenInformation`, `ConvertSidToStringSidW` and `QueryFullProcessImageNameW` on Windows
+    pub fn peer_credentials(&self) -> io::Result<PeerCredentials> {
+        self.0.peer_credentials()
+    }
+    /// Sends a file descriptor to the other end of the connection, returning a [token](HandleToken) which stands for
+    /// it and can be put into a message for the other end to [exchange for it](Self::recv_handle).
+    ///
+    /// The file descriptor is duplicated and sent along with the next write to the stream, which should be the message
+    /// referring to the token. See [`HandleToken`] for the details.
+    ///
+    /// This method is only available on Unix; there's a Windows version which takes a handle instead.
+    ///
+    /// # System calls
+    /// - `fcntl` (`F_DUPFD_CLOEXEC`)
+    /// - `sendmsg`, as part of the next write
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn send_handle(&mut self, handle: std::os::unix::io::BorrowedFd<'_>) -> io::Result<HandleToken> {
+        self.0.send_handle(handle)
+    }
+    /// Sends a handle to the other end of the connection, returning a [token](HandleToken) which stands for it and can
+    /// be put into a message for the other end to [exchange for it](Self::recv_handle).
+    ///
+    /// The handle is duplicated into the process on the other end right away. See [`HandleToken`] for the details.
+    ///
+    /// This method is only available on Windows; there's a Unix version which takes a file descriptor instead.
+    ///
+    /// # System calls
+    /// - `GetNamedPipeClientProcessId` or `GetNamedPipeServerProcessId`
+    /// - `OpenProcess` (with `PROCESS_DUP_HANDLE` access)
+    /// - `DuplicateHandle`
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    pub fn send_handle(&mut self, handle: std::os::windows::io::BorrowedHandle<'_>) -> io::Result<HandleToken> {
+        self.0.send_handle(handle)
+    }
+    /// Takes ownership of a file descriptor sent by the other end of the connection with
+    /// [`send_handle()`](Self::send_handle), given the token it returned there.
+    ///
+    /// The file descriptor arrives along with the message it was sent with, so this fails with
+    /// [`NotFound`](io::ErrorKind::NotFound) until the beginning of that message has been read. Each file descriptor can
+    /// only be taken once. File descriptors are only collected by reads after
+    /// [`set_max_pending_handles()`](Self::set_max_pending_handles) has been used to allow for some.
+    ///
+    /// # Safety
+    /// The token must have been returned by `send_handle()` on the other end of this connection. This is only required
+    /// for the sake of Windows, where tokens can't be checked and an invalid one makes for a handle which isn't owned by
+    /// the result; on Unix, invalid tokens merely result in errors.
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub unsafe fn recv_handle(&mut self, token: HandleToken) -> io::Result<std::os::unix::io::OwnedFd> {
+        self.0.recv_handle(token)
+    }
+    /// Sets how many file descriptors received from the other end of the connection can be waiting to be
+    /// [taken](Self::recv_handle) at a time. By default, it's zero.
+    ///
+    /// While the limit is zero, reads don't collect file descriptors at all, and any that the other end sends are closed
+    /// by the system – a peer can't use up this process's file descriptor table unless it's opted into. Past the limit,
+    /// newly arrived file descriptors are closed, and taking them fails with [`NotFound`](io::ErrorKind::NotFound).
+    /// Tokens stay in sync either way as long as the limit is raised above zero before the first file descriptor
+    /// arrives, which is best done right after connecting or accepting.
+    ///
+    /// This method is only available on Unix, since handles are duplicated into the receiving process directly on
+    /// Windows. On other platforms, it's absent and thus any usage of it will result in a compile-time error.
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn set_max_pending_handles(&mut self, max: usize) {
+        self.0.set_max_pending_handles(max)
+    }
+    /// Takes ownership of a handle sent by the other end of the connection with [`send_handle()`](Self::send_handle),
+    /// given the token it returned there.
+    ///
+    /// # Safety
+    /// The token must have been returned by `send_handle()` on the other end of this connection and must not have been
+    /// received already, since it's the raw value of the handle, which can't be checked for who owns it.
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    pub unsafe fn recv_handle(&mut self, token: HandleToken) -> io::Result<std::os::windows::io::OwnedHandle> {
+        unsafe { self.0.recv_handle(token) }
+    }
 }
 impl Read for LocalSocketStream {
     #[inline]
@@ -86,6 +211,11 @@ impl Read for LocalSocketStream {
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
         self.0.read_vectored(bufs)
     }
+    #[cfg(feature = "read_buf")]
+    #[inline]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        self.0.read_buf(cursor)
+    }
 }
 impl Write for LocalSocketStream {
     #[inline]
@@ -107,6 +107,16 @@ impl LocalSocketListener {
     pub fn bind<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
         LocalSocketListenerImpl::bind(name).map(Self::from)
     }
+    /// Adopts the listening socket which launchd created for the calling job under the given name, as described in
+    /// the [`launchd`](crate::os::unix::launchd) module.
+    ///
+    /// This function is only available on macOS. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(target_os = "macos")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "macos")))]
+    pub fn from_launchd(name: &str) -> io::Result<Self> {
+        LocalSocketListenerImpl::from_launchd(name).map(Self::from)
+    }
     /// Listens for incoming connections to the socket, asynchronously waiting until a client is connected.
     #[inline]
     pub async fn accept(&self) -> io::Result<LocalSocketStream> {
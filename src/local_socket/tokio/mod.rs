@@ -13,3 +13,13 @@ pub use listener::*;
 
 mod stream;
 pub use stream::*;
+
+mod shutdown;
+pub use shutdown::*;
+
+pub mod mux;
+pub mod pubsub;
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "serde")))]
+pub mod rpc;
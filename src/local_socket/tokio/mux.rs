@@ -0,0 +1,640 @@
+//! Multiplexing independent logical channels over a single local socket connection.
+//!
+//! Applications made of several subsystems which all talk to the same peer would otherwise open a connection per
+//! subsystem, each of which costs a pipe instance on Windows and a file descriptor on both ends. A [`Mux`] runs any
+//! number of [`MuxChannel`]s over one [`LocalSocketStream`] instead. Either end can [open](Mux::open) a channel, which
+//! the other end then [accepts](Mux::accept), and channels are byte streams which behave much like connections of their
+//! own: they implement both the [`futures`](futures_io::AsyncRead) and the [Tokio](tokio::io::AsyncRead) flavors of
+//! `AsyncRead` and `AsyncWrite`, can be shut down for writing independently of each other, and are closed when dropped.
+//!
+//! ## Flow control
+//! Every channel has a receive window on each end, which is the amount of data the other end may send before it has
+//! to wait for the receiving side to read some of it. Data sent over a channel is buffered by the receiving [`Mux`]
+//! until it's read, and so the window caps the amount of memory a channel can take up there. Because of that, a channel
+//! which isn't being read from only stalls writes to that channel, and never holds up the others. The window is
+//! [`DEFAULT_WINDOW`] unless set otherwise with [`MuxOptions`], and each end picks its own.
+//!
+//! The two ends of the connection have to be on different [sides](Side), which keeps the channels opened by one end
+//! from being confused with those opened by the other.
+//!
+//! ## Wire format
+//! Every frame is a little-endian 32-bit length of the rest of the frame, followed by a kind byte, the ID of the
+//! channel as a little-endian 32-bit integer, and a payload which depends on the kind of the frame:
+//! - **Open** (1) and **accept** (2) carry the receive window of the sender as a little-endian 32-bit integer
+//! - **Refuse** (3) has no payload and is sent in response to an open frame if too many channels are waiting to be
+//!   accepted
+//! - **Data** (4) carries the data itself, of up to 64 KiB
+//! - **Credit** (5) carries the number of bytes by which to widen the send window, as a little-endian 32-bit integer
+//! - **Shutdown** (6) has no payload and means that the sender won't send any more data over the channel
+//! - **Close** (7) has no payload and means that the sender has dropped the channel altogether
+//!
+//! # Example
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! use interprocess::local_socket::tokio::{
+//!     mux::{Mux, Side},
+//!     LocalSocketStream,
+//! };
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//!
+//! let conn = LocalSocketStream::connect("/tmp/example-mux.sock").await?;
+//! let mux = Mux::new(conn, Side::Client);
+//!
+//! let mut control = mux.open().await?;
+//! let mut telemetry = mux.open().await?;
+//! control.write_all(b"start").await?;
+//! let mut sample = [0; 8];
+//! telemetry.read_exact(&mut sample).await?;
+//! # Ok(()) }
+//! ```
+
+use super::LocalSocketStream;
+use crate::framing::{LengthPrefix, MessageReader, MessageWriter};
+use futures_io::{AsyncRead, AsyncWrite};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Debug, Formatter},
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+use tokio::{
+    io::{AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite, ReadBuf as TokioReadBuf},
+    sync::{mpsc, oneshot, Notify},
+    task::JoinHandle,
+};
+
+const OPEN: u8 = 1;
+const ACCEPT: u8 = 2;
+const REFUSE: u8 = 3;
+const DATA: u8 = 4;
+const CREDIT: u8 = 5;
+const SHUTDOWN: u8 = 6;
+const CLOSE: u8 = 7;
+
+/// The most data a single data frame carries. Larger writes are split into several frames, so that one channel can't
+/// hold up the others for long.
+const MAX_CHUNK: usize = 64 * 1024;
+const HEADER_LEN: usize = 1 + 4;
+
+/// The default receive window of a channel, in bytes.
+pub const DEFAULT_WINDOW: u32 = 256 * 1024;
+
+/// Which end of the connection a [`Mux`] is on, which decides the IDs of the channels it opens.
+///
+/// The two ends of a connection have to be on different sides. Typically, the one which connected is the client and
+/// the one which accepted the connection is the server.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// Opens channels with even IDs.
+    Client,
+    /// Opens channels with odd IDs.
+    Server,
+}
+impl Side {
+    fn first_id(self) -> u32 {
+        match self {
+            Self::Client => 0,
+            Self::Server => 1,
+        }
+    }
+}
+
+/// Options for creating a [`Mux`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct MuxOptions {
+    /// The receive window of every channel on this end, in bytes. [`DEFAULT_WINDOW`] by default.
+    pub window: u32,
+    /// The maximum number of channels opened by the other end which are waiting to be [accepted](Mux::accept).
+    /// Channels opened beyond that are refused. 128 by default.
+    pub backlog: usize,
+}
+impl MuxOptions {
+    /// Creates a new builder with default options.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            window: DEFAULT_WINDOW,
+            backlog: 128,
+        }
+    }
+    /// Sets the receive window of every channel on this end.
+    ///
+    /// See the [associated field](#structfield.window) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn window(mut self, window: u32) -> Self {
+        self.window = window;
+        self
+    }
+    /// Sets the maximum number of channels waiting to be accepted.
+    ///
+    /// See the [associated field](#structfield.backlog) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn backlog(mut self, backlog: usize) -> Self {
+        self.backlog = backlog;
+        self
+    }
+    /// Starts multiplexing channels over the given connection with these options.
+    pub fn mux(&self, conn: LocalSocketStream, side: Side) -> io::Result<Mux> {
+        if self.window == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "channel receive window must be non-zero",
+            ));
+        }
+        Ok(Mux::with_options(conn, side, *self))
+    }
+}
+impl Default for MuxOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of one channel on this end.
+#[derive(Default)]
+struct Channel {
+    /// Data received but not read yet.
+    recv_buf: VecDeque<u8>,
+    /// Data read since the last credit frame, which the other end doesn't know has been read.
+    unacked: u32,
+    /// Whether the other end has shut down or closed the channel.
+    eof: bool,
+    read_waker: Option<Waker>,
+    /// How much more data the other end is willing to receive.
+    send_credit: u32,
+    write_waker: Option<Waker>,
+    /// Whether the other end has closed the channel.
+    peer_closed: bool,
+    /// Whether this end has shut down the channel for writing.
+    shut_down: bool,
+    /// Whether the [`MuxChannel`] has been dropped.
+    dropped: bool,
+    /// Waits for the other end to accept or refuse the channel.
+    opening: Option<oneshot::Sender<bool>>,
+}
+impl Channel {
+    fn wake(&mut self) {
+        if let Some(w) = self.read_waker.take() {
+            w.wake();
+        }
+        if let Some(w) = self.write_waker.take() {
+            w.wake();
+        }
+    }
+}
+
+struct State {
+    channels: HashMap<u32, Channel>,
+    next_id: u32,
+    /// Channels opened by the other end which haven't been accepted yet.
+    incoming: VecDeque<u32>,
+    /// Set once the connection is gone.
+    closed: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    outgoing: mpsc::UnboundedSender<Vec<u8>>,
+    incoming_ready: Notify,
+    side: Side,
+    options: MuxOptions,
+}
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+    fn send(&self, kind: u8, id: u32, payload: &[u8]) -> io::Result<()> {
+        let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+        frame.push(kind);
+        frame.extend_from_slice(&id.to_le_bytes());
+        frame.extend_from_slice(payload);
+        self.outgoing.send(frame).map_err(|_| connection_lost())
+    }
+    /// Fails every channel which is still open and wakes up everything waiting on the connection.
+    fn shut_down(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        for channel in state.channels.values_mut() {
+            // Drops the senders, which fails the channels still being opened.
+            channel.opening = None;
+            channel.wake();
+        }
+        drop(state);
+        self.incoming_ready.notify_waiters();
+    }
+    /// Handles one frame from the other end, returning `false` if it's malformed or breaks the protocol.
+    fn handle(&self, frame: &[u8]) -> bool {
+        let (Some(&kind), Some(id)) = (frame.first(), frame.get(1..HEADER_LEN)) else {
+            return false;
+        };
+        let id = u32::from_le_bytes(id.try_into().unwrap());
+        let payload = &frame[HEADER_LEN..];
+        let mut state = self.lock();
+        match kind {
+            OPEN => {
+                let Some(window) = read_u32(payload) else { return false };
+                if id % 2 == self.side.first_id() || state.channels.contains_key(&id) {
+                    return false;
+                }
+                if state.incoming.len() >= self.options.backlog {
+                    return self.send(REFUSE, id, &[]).is_ok();
+                }
+                state.channels.insert(
+                    id,
+                    Channel {
+                        send_credit: window,
+                        ..Channel::default()
+                    },
+                );
+                // Goes out before anything written to the channel once it's accepted.
+                if self.send(ACCEPT, id, &self.options.window.to_le_bytes()).is_err() {
+                    return false;
+                }
+                state.incoming.push_back(id);
+                drop(state);
+                self.incoming_ready.notify_one();
+                true
+            }
+            ACCEPT => {
+                let Some(window) = read_u32(payload) else { return false };
+                if let Some(channel) = state.channels.get_mut(&id) {
+                    channel.send_credit = window;
+                    if let Some(opening) = channel.opening.take() {
+                        let _ = opening.send(true);
+                    }
+                }
+                true
+            }
+            REFUSE => {
+                if let Some(mut channel) = state.channels.remove(&id) {
+                    if let Some(opening) = channel.opening.take() {
+                        let _ = opening.send(false);
+                    }
+                }
+                true
+            }
+            DATA => {
+                let Some(channel) = state.channels.get_mut(&id) else {
+                    return true;
+                };
+                if channel.eof {
+                    return false;
+                }
+                let in_flight = channel.recv_buf.len() + channel.unacked as usize + payload.len();
+                if in_flight > self.options.window as usize {
+                    return false;
+                }
+                if !channel.dropped {
+                    channel.recv_buf.extend(payload);
+                    channel.wake();
+                }
+                true
+            }
+            CREDIT => {
+                let Some(credit) = read_u32(payload) else { return false };
+                if let Some(channel) = state.channels.get_mut(&id) {
+                    channel.send_credit = channel.send_credit.saturating_add(credit);
+                    channel.wake();
+                }
+                true
+            }
+            SHUTDOWN | CLOSE => {
+                let Some(channel) = state.channels.get_mut(&id) else {
+                    return true;
+                };
+                channel.eof = true;
+                if kind == CLOSE {
+                    channel.peer_closed = true;
+                    if channel.dropped {
+                        state.channels.remove(&id);
+                        return true;
+                    }
+                }
+                channel.wake();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn read_u32(payload: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(payload.try_into().ok()?))
+}
+
+/// Multiplexes channels over one local socket connection.
+///
+/// Channels are opened with [`open()`](Self::open) and accepted with [`accept()`](Self::accept), both of which only
+/// need a shared reference, so the multiplexer can be put in an [`Arc`] and used from many tasks at once. Frames are
+/// sent and received by two tasks spawned when the multiplexer is created, which are stopped when it's dropped;
+/// dropping the multiplexer thus closes the connection, and all of the channels with it.
+///
+/// See the [module-level documentation](self) for more.
+pub struct Mux {
+    shared: Arc<Shared>,
+    tasks: [JoinHandle<()>; 2],
+}
+impl Mux {
+    /// Starts multiplexing channels over the given connection with the default options.
+    pub fn new(conn: LocalSocketStream, side: Side) -> Self {
+        Self::with_options(conn, side, MuxOptions::new())
+    }
+    fn with_options(conn: LocalSocketStream, side: Side, options: MuxOptions) -> Self {
+        let (reader, writer) = conn.split();
+        let (outgoing, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                channels: HashMap::new(),
+                next_id: side.first_id(),
+                incoming: VecDeque::new(),
+                closed: false,
+            }),
+            outgoing,
+            incoming_ready: Notify::new(),
+            side,
+            options,
+        });
+        let writer_task = {
+            let shared = Arc::clone(&shared);
+            let mut writer = MessageWriter::new(writer).with_prefix(LengthPrefix::U32);
+            tokio::spawn(async move {
+                while let Some(frame) = rx.recv().await {
+                    if writer.write_message_async(&frame).await.is_err() {
+                        break;
+                    }
+                }
+                shared.shut_down();
+            })
+        };
+        let reader_task = {
+            let shared = Arc::clone(&shared);
+            let mut reader = MessageReader::new(reader)
+                .with_prefix(LengthPrefix::U32)
+                .with_max_size(HEADER_LEN + MAX_CHUNK);
+            tokio::spawn(async move {
+                while let Ok(Some(frame)) = reader.read_message_async().await {
+                    if !shared.handle(frame) {
+                        break;
+                    }
+                }
+                shared.shut_down();
+            })
+        };
+        Self {
+            shared,
+            tasks: [writer_task, reader_task],
+        }
+    }
+    /// Returns the side of the connection this end is on.
+    #[inline]
+    pub fn side(&self) -> Side {
+        self.shared.side
+    }
+
+    /// Opens a new channel and waits for the other end to accept it.
+    ///
+    /// The other end accepts the channel as soon as it's received, without waiting for a call to
+    /// [`accept()`](Self::accept) there, as long as its backlog isn't full.
+    ///
+    /// # Errors
+    /// - [`ConnectionRefused`](io::ErrorKind::ConnectionRefused) if the other end has too many channels waiting to be
+    ///   accepted.
+    /// - [`ConnectionAborted`](io::ErrorKind::ConnectionAborted) if the connection is lost, including if it had been
+    ///   lost before the call.
+    pub async fn open(&self) -> io::Result<MuxChannel> {
+        let (tx, rx) = oneshot::channel();
+        let channel = {
+            let mut state = self.shared.lock();
+            if state.closed {
+                return Err(connection_lost());
+            }
+            let id = state.next_id;
+            state.next_id = state.next_id.wrapping_add(2);
+            state.channels.insert(
+                id,
+                Channel {
+                    opening: Some(tx),
+                    ..Channel::default()
+                },
+            );
+            self.shared.send(OPEN, id, &self.shared.options.window.to_le_bytes())?;
+            // Closes the channel if the call is cancelled.
+            MuxChannel {
+                id,
+                shared: Arc::clone(&self.shared),
+            }
+        };
+        match rx.await {
+            Ok(true) => Ok(channel),
+            Ok(false) => Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "the other end refused to open the channel",
+            )),
+            Err(_) => Err(connection_lost()),
+        }
+    }
+    /// Waits for the other end to open a channel and returns it.
+    ///
+    /// Fails with [`ConnectionAborted`](io::ErrorKind::ConnectionAborted) once the connection is lost and all of the
+    /// channels opened before that have been accepted.
+    pub async fn accept(&self) -> io::Result<MuxChannel> {
+        loop {
+            let notified = self.shared.incoming_ready.notified();
+            {
+                let mut state = self.shared.lock();
+                if let Some(id) = state.incoming.pop_front() {
+                    return Ok(MuxChannel {
+                        id,
+                        shared: Arc::clone(&self.shared),
+                    });
+                }
+                if state.closed {
+                    return Err(connection_lost());
+                }
+            }
+            notified.await;
+        }
+    }
+}
+impl Drop for Mux {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        self.shared.shut_down();
+    }
+}
+impl Debug for Mux {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mux")
+            .field("side", &self.shared.side)
+            .field("options", &self.shared.options)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One logical channel of a [`Mux`], which is a byte stream of its own.
+///
+/// Writes complete as soon as the data is queued for sending, as long as the other end has room for it in the
+/// channel's [receive window](MuxOptions::window), and wait for it to make room otherwise. Shutting the channel down
+/// makes reads on the other end return end of file once they've caught up, while still allowing data to be received
+/// from it. Dropping the channel closes it in both directions, making writes on the other end fail with
+/// [`BrokenPipe`](io::ErrorKind::BrokenPipe).
+pub struct MuxChannel {
+    id: u32,
+    shared: Arc<Shared>,
+}
+impl MuxChannel {
+    /// Returns the ID of the channel, which is the same on both ends.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn do_read(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut state = self.shared.lock();
+        let closed = state.closed;
+        let Some(channel) = state.channels.get_mut(&self.id) else {
+            return Poll::Ready(Ok(0));
+        };
+        if !channel.recv_buf.is_empty() {
+            let len = buf.len().min(channel.recv_buf.len());
+            for (dst, src) in buf.iter_mut().zip(channel.recv_buf.drain(..len)) {
+                *dst = src;
+            }
+            channel.unacked += len as u32;
+            // Batches up credit frames, rather than sending one for every read.
+            if !channel.eof && channel.unacked >= self.shared.options.window / 2 {
+                let credit = std::mem::take(&mut channel.unacked);
+                // If the connection is gone, the next read reports it.
+                let _ = self.shared.send(CREDIT, self.id, &credit.to_le_bytes());
+            }
+            return Poll::Ready(Ok(len));
+        }
+        if channel.eof {
+            return Poll::Ready(Ok(0));
+        }
+        if closed {
+            return Poll::Ready(Err(connection_lost()));
+        }
+        channel.read_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+    fn do_write(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let mut state = self.shared.lock();
+        if state.closed {
+            return Poll::Ready(Err(connection_lost()));
+        }
+        let Some(channel) = state.channels.get_mut(&self.id) else {
+            return Poll::Ready(Err(channel_closed()));
+        };
+        if channel.peer_closed || channel.shut_down {
+            return Poll::Ready(Err(channel_closed()));
+        }
+        if channel.send_credit == 0 {
+            channel.write_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let len = buf.len().min(channel.send_credit as usize).min(MAX_CHUNK);
+        channel.send_credit -= len as u32;
+        Poll::Ready(self.shared.send(DATA, self.id, &buf[..len]).map(|()| len))
+    }
+    fn do_shutdown(&self) -> Poll<io::Result<()>> {
+        let mut state = self.shared.lock();
+        if let Some(channel) = state.channels.get_mut(&self.id) {
+            if !channel.shut_down && !channel.peer_closed {
+                channel.shut_down = true;
+                // There's nothing to shut down if the connection is gone.
+                let _ = self.shared.send(SHUTDOWN, self.id, &[]);
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+impl Drop for MuxChannel {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        let closed = state.closed;
+        let Some(channel) = state.channels.get_mut(&self.id) else {
+            return;
+        };
+        if !closed {
+            let _ = self.shared.send(CLOSE, self.id, &[]);
+        }
+        if channel.peer_closed || closed {
+            state.channels.remove(&self.id);
+        } else {
+            // Kept around until the other end closes the channel too, so that data which is still on its way is
+            // recognized and discarded.
+            channel.dropped = true;
+            channel.recv_buf = VecDeque::new();
+        }
+    }
+}
+impl AsyncRead for MuxChannel {
+    #[inline]
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.do_read(cx, buf)
+    }
+}
+impl AsyncWrite for MuxChannel {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.do_write(cx, buf)
+    }
+    /// Does nothing, since writes hand the data over to the connection right away.
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    /// Shuts the channel down for writing.
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.do_shutdown()
+    }
+}
+impl TokioAsyncRead for MuxChannel {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        let bytes_read = futures_core::ready!(self.do_read(cx, buf.initialize_unfilled()))?;
+        buf.advance(bytes_read);
+        Poll::Ready(Ok(()))
+    }
+}
+impl TokioAsyncWrite for MuxChannel {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.do_write(cx, buf)
+    }
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.do_shutdown()
+    }
+}
+impl Debug for MuxChannel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MuxChannel")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+fn connection_lost() -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionAborted, "the multiplexed connection was lost")
+}
+fn channel_closed() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "the channel has been closed")
+}
@@ -0,0 +1,550 @@
+//! A publish-subscribe broker on top of local sockets, for broadcasting messages between processes by topic.
+//!
+//! A [`Broker`] listens on a local socket name and relays every message sent to it by a [`Publisher`] to all
+//! [`Subscriber`]s of the message's topic. Topics are arbitrary strings of up to 65535 bytes and are matched exactly.
+//! Messages from one publisher are delivered to each subscriber in the order in which they were published.
+//!
+//! ## Slow consumers
+//! Every subscriber has a queue of messages waiting to be sent to it, the length of which is capped by
+//! [`BrokerOptions::queue_capacity`]. What happens once a subscriber falls so far behind that its queue fills up is
+//! decided by the [`SlowConsumerPolicy`]. Publishers are never slowed down by subscribers.
+//!
+//! ## Wire format
+//! Every frame is a little-endian 32-bit length of the rest of the frame, followed by a kind byte, a little-endian
+//! 16-bit length of the topic, the topic itself in UTF-8 and the payload. Frames delivering messages to subscribers
+//! additionally carry the number of messages dropped before them as a little-endian 64-bit integer right after the
+//! topic. Anything which speaks this format can act as a publisher or subscriber, even without this crate.
+//!
+//! # Example
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! use interprocess::local_socket::tokio::pubsub::{Broker, Publisher, Subscriber};
+//!
+//! let broker = Broker::bind("/tmp/example-broker.sock")?;
+//! tokio::spawn(broker.run());
+//!
+//! let mut subscriber = Subscriber::connect("/tmp/example-broker.sock").await?;
+//! subscriber.subscribe("greetings").await?;
+//!
+//! let mut publisher = Publisher::connect("/tmp/example-broker.sock").await?;
+//! publisher.publish("greetings", b"Hello from the publisher!").await?;
+//!
+//! let message = subscriber.recv().await?;
+//! assert_eq!(message.payload(), b"Hello from the publisher!");
+//! # Ok(()) }
+//! ```
+
+use super::{LocalSocketListener, LocalSocketStream, WriteHalf};
+use crate::local_socket::ToLocalSocketName;
+use futures_util::{
+    future::{self, Either},
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Debug, Formatter},
+    io,
+    pin::pin,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{watch, Notify};
+
+const SUBSCRIBE: u8 = 1;
+const UNSUBSCRIBE: u8 = 2;
+const PUBLISH: u8 = 3;
+const DELIVER: u8 = 4;
+const ACK: u8 = 5;
+
+/// The largest payload a message can have. Frames with larger payloads are rejected by the receiving side, so that a
+/// misbehaving peer can't make it allocate arbitrary amounts of memory.
+pub const MAX_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+const MAX_FRAME_SIZE: usize = 1 + 2 + u16::MAX as usize + 8 + MAX_PAYLOAD_SIZE;
+
+/// What the broker does with a message for a subscriber whose queue is full.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SlowConsumerPolicy {
+    /// Drops the oldest message in the queue to make room for the new one. The default.
+    #[default]
+    DropOldest,
+    /// Drops the new message, keeping the queue as it is.
+    DropNewest,
+    /// Disconnects the subscriber.
+    Disconnect,
+}
+
+/// Options for creating a [`Broker`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct BrokerOptions {
+    /// The maximum number of messages waiting to be sent to one subscriber. 64 by default.
+    pub queue_capacity: usize,
+    /// What happens to messages for subscribers whose queue is full. [`DropOldest`](SlowConsumerPolicy::DropOldest)
+    /// by default.
+    pub slow_consumer: SlowConsumerPolicy,
+}
+impl BrokerOptions {
+    /// Creates a new builder with default options.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            queue_capacity: 64,
+            slow_consumer: SlowConsumerPolicy::DropOldest,
+        }
+    }
+    /// Sets the maximum number of messages waiting to be sent to one subscriber.
+    ///
+    /// See the [associated field](#structfield.queue_capacity) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.queue_capacity = queue_capacity;
+        self
+    }
+    /// Sets what happens to messages for subscribers whose queue is full.
+    ///
+    /// See the [associated field](#structfield.slow_consumer) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn slow_consumer(mut self, slow_consumer: SlowConsumerPolicy) -> Self {
+        self.slow_consumer = slow_consumer;
+        self
+    }
+    /// Creates a broker listening on the given name with these options.
+    pub fn bind<'a>(&self, name: impl ToLocalSocketName<'a>) -> io::Result<Broker> {
+        if self.queue_capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "subscriber queue capacity must be non-zero",
+            ));
+        }
+        Ok(Broker {
+            listener: LocalSocketListener::bind(name)?,
+            options: *self,
+            topics: Arc::default(),
+        })
+    }
+}
+impl Default for BrokerOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Subscribers of every topic, by the topic.
+type Topics = Mutex<HashMap<String, Vec<Arc<Outbox>>>>;
+
+/// A publish-subscribe broker, which relays messages from publishers to subscribers.
+///
+/// The broker does nothing until [`run()`](Self::run) is called, which is typically done in a task of its own.
+pub struct Broker {
+    listener: LocalSocketListener,
+    options: BrokerOptions,
+    topics: Arc<Topics>,
+}
+impl Broker {
+    /// Creates a broker listening on the given name with the default options.
+    pub fn bind<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        BrokerOptions::new().bind(name)
+    }
+    /// Accepts connections and relays messages between them, spawning a task for every connection.
+    ///
+    /// Only returns if accepting a connection fails. Connections which have already been accepted keep being served
+    /// regardless, until they disconnect.
+    pub async fn run(self) -> io::Result<()> {
+        loop {
+            let conn = self.listener.accept().await?;
+            tokio::spawn(serve(conn, Arc::clone(&self.topics), self.options));
+        }
+    }
+}
+impl Debug for Broker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Broker")
+            .field("listener", &self.listener)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Serves one connection, which can both publish and subscribe, until it disconnects.
+async fn serve(conn: LocalSocketStream, topics: Arc<Topics>, options: BrokerOptions) {
+    let (mut reader, writer) = conn.split();
+    let outbox = Arc::new(Outbox::new(options));
+    let writer_task = tokio::spawn(Arc::clone(&outbox).drain_into(writer));
+    let mut closed = outbox.closed.subscribe();
+    let mut subscriptions = HashSet::new();
+    loop {
+        // Losing a partially read frame doesn't matter once the connection is being closed anyway.
+        let read = pin!(read_frame(&mut reader));
+        let frame = match future::select(read, pin!(wait_closed(&mut closed))).await {
+            Either::Left((Ok(Some(frame)), _)) => frame,
+            // End of file, an error, or being disconnected for falling behind.
+            _ => break,
+        };
+        match frame.kind {
+            SUBSCRIBE => {
+                if subscriptions.insert(frame.topic.clone()) {
+                    let mut topics = topics.lock().unwrap();
+                    topics.entry(frame.topic.clone()).or_default().push(Arc::clone(&outbox));
+                }
+                outbox.push(Outgoing::Ack(frame.topic));
+            }
+            UNSUBSCRIBE => {
+                if subscriptions.remove(&frame.topic) {
+                    remove_subscriber(&topics, &frame.topic, &outbox);
+                }
+                outbox.push(Outgoing::Ack(frame.topic));
+            }
+            PUBLISH => {
+                let topics = topics.lock().unwrap();
+                if let Some(subscribers) = topics.get(&frame.topic) {
+                    let message = Arc::new((frame.topic, frame.payload));
+                    for subscriber in subscribers {
+                        subscriber.push(Outgoing::Message(Arc::clone(&message)));
+                    }
+                }
+            }
+            // Not something a client sends.
+            _ => break,
+        }
+    }
+    for topic in subscriptions {
+        remove_subscriber(&topics, &topic, &outbox);
+    }
+    outbox.close();
+    let _ = writer_task.await;
+}
+fn remove_subscriber(topics: &Topics, topic: &str, outbox: &Arc<Outbox>) {
+    let mut topics = topics.lock().unwrap();
+    if let Some(subscribers) = topics.get_mut(topic) {
+        subscribers.retain(|s| !Arc::ptr_eq(s, outbox));
+        if subscribers.is_empty() {
+            topics.remove(topic);
+        }
+    }
+}
+async fn wait_closed(closed: &mut watch::Receiver<bool>) {
+    while !*closed.borrow_and_update() {
+        if closed.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+enum Outgoing {
+    /// The topic and the payload, shared by all subscribers.
+    Message(Arc<(String, Vec<u8>)>),
+    /// Confirms a subscription or unsubscription. Never dropped.
+    Ack(String),
+}
+
+/// The queue of frames waiting to be sent to one connection, drained by a task of its own.
+struct Outbox {
+    state: Mutex<OutboxState>,
+    ready: Notify,
+    closed: watch::Sender<bool>,
+    options: BrokerOptions,
+}
+struct OutboxState {
+    queue: VecDeque<Outgoing>,
+    /// The number of messages in the queue, which doesn't count acknowledgements.
+    messages: usize,
+    /// The number of messages dropped since the last one was sent.
+    dropped: u64,
+}
+impl Outbox {
+    fn new(options: BrokerOptions) -> Self {
+        Self {
+            state: Mutex::new(OutboxState {
+                queue: VecDeque::new(),
+                messages: 0,
+                dropped: 0,
+            }),
+            ready: Notify::new(),
+            closed: watch::channel(false).0,
+            options,
+        }
+    }
+    fn push(&self, item: Outgoing) {
+        let mut state = self.state.lock().unwrap();
+        if let Outgoing::Message(..) = item {
+            if state.messages >= self.options.queue_capacity {
+                match self.options.slow_consumer {
+                    SlowConsumerPolicy::DropOldest => {
+                        let oldest = state
+                            .queue
+                            .iter()
+                            .position(|item| matches!(item, Outgoing::Message(..)))
+                            .expect("full queue has no messages");
+                        state.queue.remove(oldest);
+                        state.messages -= 1;
+                        state.dropped += 1;
+                    }
+                    SlowConsumerPolicy::DropNewest => {
+                        state.dropped += 1;
+                        return;
+                    }
+                    SlowConsumerPolicy::Disconnect => {
+                        drop(state);
+                        self.close();
+                        return;
+                    }
+                }
+            }
+            state.messages += 1;
+        }
+        state.queue.push_back(item);
+        drop(state);
+        // Stores a permit if the draining task isn't waiting yet, so the wakeup isn't lost.
+        self.ready.notify_one();
+    }
+    fn close(&self) {
+        self.closed.send_replace(true);
+        self.ready.notify_one();
+    }
+    async fn drain_into(self: Arc<Self>, mut writer: WriteHalf) {
+        loop {
+            if *self.closed.borrow() {
+                return;
+            }
+            let next = {
+                let mut state = self.state.lock().unwrap();
+                let item = state.queue.pop_front();
+                match item {
+                    Some(Outgoing::Message(..)) => {
+                        state.messages -= 1;
+                        item.map(|item| (item, std::mem::take(&mut state.dropped)))
+                    }
+                    _ => item.map(|item| (item, 0)),
+                }
+            };
+            let frame = match next {
+                Some((Outgoing::Message(message), dropped)) => encode(DELIVER, &message.0, Some(dropped), &message.1),
+                Some((Outgoing::Ack(topic), _)) => encode(ACK, &topic, None, &[]),
+                None => {
+                    self.ready.notified().await;
+                    continue;
+                }
+            };
+            // The frame was decoded from a valid one, and so it can be encoded again.
+            let frame = frame.expect("relayed frame failed to encode");
+            if writer.write_all(&frame).await.is_err() {
+                self.close();
+                return;
+            }
+        }
+    }
+}
+
+/// A connection to a [`Broker`] which publishes messages.
+pub struct Publisher(LocalSocketStream);
+impl Publisher {
+    /// Connects to the broker listening on the given name.
+    pub async fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        LocalSocketStream::connect(name).await.map(Self)
+    }
+    /// Publishes a message to all current subscribers of the given topic. If there are none, the message is dropped.
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the topic is longer than 65535 bytes or the payload
+    /// is larger than [`MAX_PAYLOAD_SIZE`].
+    pub async fn publish(&mut self, topic: &str, payload: &[u8]) -> io::Result<()> {
+        let frame = encode(PUBLISH, topic, None, payload)?;
+        self.0.write_all(&frame).await
+    }
+}
+impl Debug for Publisher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Publisher").field(&self.0).finish()
+    }
+}
+
+/// A connection to a [`Broker`] which receives messages on the topics it's subscribed to.
+pub struct Subscriber {
+    conn: LocalSocketStream,
+    /// Messages received while waiting for an acknowledgement.
+    pending: VecDeque<Message>,
+}
+impl Subscriber {
+    /// Connects to the broker listening on the given name. The subscriber starts out subscribed to nothing.
+    pub async fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        Ok(Self {
+            conn: LocalSocketStream::connect(name).await?,
+            pending: VecDeque::new(),
+        })
+    }
+    /// Subscribes to the given topic. Subscribing to a topic twice does nothing.
+    ///
+    /// Returns once the broker has confirmed the subscription, after which every message published to the topic is
+    /// delivered to this subscriber.
+    pub async fn subscribe(&mut self, topic: &str) -> io::Result<()> {
+        self.request(SUBSCRIBE, topic).await
+    }
+    /// Unsubscribes from the given topic. Unsubscribing from a topic the subscriber isn't subscribed to does nothing.
+    ///
+    /// Returns once the broker has confirmed the unsubscription. Messages published to the topic before that might
+    /// still be received afterwards.
+    pub async fn unsubscribe(&mut self, topic: &str) -> io::Result<()> {
+        self.request(UNSUBSCRIBE, topic).await
+    }
+    async fn request(&mut self, kind: u8, topic: &str) -> io::Result<()> {
+        let frame = encode(kind, topic, None, &[])?;
+        self.conn.write_all(&frame).await?;
+        loop {
+            match self.recv_frame().await? {
+                Frame { kind: ACK, .. } => return Ok(()),
+                frame => self.pending.push_back(Message::from(frame)),
+            }
+        }
+    }
+    /// Receives the next message on any of the subscribed topics.
+    ///
+    /// This method is not cancel-safe: if the future is dropped before completing, part of a message may have been
+    /// read, and the connection can't be used anymore.
+    pub async fn recv(&mut self) -> io::Result<Message> {
+        if let Some(message) = self.pending.pop_front() {
+            return Ok(message);
+        }
+        loop {
+            match self.recv_frame().await? {
+                // Stray acknowledgements can't happen, but there's no harm in skipping them.
+                Frame { kind: ACK, .. } => {}
+                frame => return Ok(Message::from(frame)),
+            }
+        }
+    }
+    async fn recv_frame(&mut self) -> io::Result<Frame> {
+        let frame = read_frame(&mut self.conn)
+            .await?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "broker closed the connection"))?;
+        match frame.kind {
+            DELIVER | ACK => Ok(frame),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected frame kind")),
+        }
+    }
+}
+impl Debug for Subscriber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("conn", &self.conn)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+/// A message received by a [`Subscriber`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    topic: String,
+    payload: Vec<u8>,
+    dropped_before: u64,
+}
+impl Message {
+    /// Returns the topic the message was published to.
+    #[inline]
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+    /// Returns the payload of the message.
+    #[inline]
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+    /// Returns the payload of the message, consuming the message.
+    #[inline]
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
+    }
+    /// Returns the number of messages for this subscriber which were dropped by the broker since the previous message
+    /// was delivered, as per the broker's [`SlowConsumerPolicy`].
+    #[inline]
+    pub fn dropped_before(&self) -> u64 {
+        self.dropped_before
+    }
+}
+impl From<Frame> for Message {
+    fn from(frame: Frame) -> Self {
+        Self {
+            topic: frame.topic,
+            payload: frame.payload,
+            dropped_before: frame.dropped,
+        }
+    }
+}
+
+struct Frame {
+    kind: u8,
+    topic: String,
+    dropped: u64,
+    payload: Vec<u8>,
+}
+
+fn encode(kind: u8, topic: &str, dropped: Option<u64>, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let topic_len = u16::try_from(topic.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "topic is longer than 65535 bytes"))?;
+    if payload.len() > MAX_PAYLOAD_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "payload is larger than the maximum size",
+        ));
+    }
+    let len = 1 + 2 + topic.len() + dropped.map_or(0, |_| 8) + payload.len();
+    let mut frame = Vec::with_capacity(4 + len);
+    // Can't overflow, since the frame is no larger than MAX_FRAME_SIZE.
+    frame.extend_from_slice(&(len as u32).to_le_bytes());
+    frame.push(kind);
+    frame.extend_from_slice(&topic_len.to_le_bytes());
+    frame.extend_from_slice(topic.as_bytes());
+    if let Some(dropped) = dropped {
+        frame.extend_from_slice(&dropped.to_le_bytes());
+    }
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+/// Reads one frame, returning `None` on end of file at a frame boundary.
+async fn read_frame(reader: &mut (impl AsyncRead + Unpin)) -> io::Result<Option<Frame>> {
+    let mut len = [0; 4];
+    if reader.read(&mut len[..1]).await? == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut len[1..]).await?;
+    let len = u32::from_le_bytes(len) as usize;
+    if !(3..=MAX_FRAME_SIZE).contains(&len) {
+        return Err(invalid_frame());
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).await?;
+
+    let kind = body[0];
+    let topic_len = usize::from(u16::from_le_bytes([body[1], body[2]]));
+    let rest = body.get(3..).unwrap_or_default();
+    if rest.len() < topic_len {
+        return Err(invalid_frame());
+    }
+    let (topic, rest) = rest.split_at(topic_len);
+    let topic = String::from_utf8(topic.to_vec()).map_err(|_| invalid_frame())?;
+    let (dropped, payload) = if kind == DELIVER {
+        if rest.len() < 8 {
+            return Err(invalid_frame());
+        }
+        let (dropped, payload) = rest.split_at(8);
+        (u64::from_le_bytes(dropped.try_into().unwrap()), payload)
+    } else {
+        (0, rest)
+    };
+    if payload.len() > MAX_PAYLOAD_SIZE {
+        return Err(invalid_frame());
+    }
+    Ok(Some(Frame {
+        kind,
+        topic,
+        dropped,
+        payload: payload.to_vec(),
+    }))
+}
+fn invalid_frame() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed publish-subscribe frame")
+}
@@ -0,0 +1,352 @@
+//! Request-response remote procedure calls on top of local sockets, with requests and responses of any serializable
+//! type.
+//!
+//! An [`RpcServer`] listens on a local socket name and answers every request it receives by passing it to an
+//! [`RpcHandler`], which is typically an async closure. An [`RpcClient`] sends requests to it and waits for the
+//! responses. Any number of calls can be in flight on one client at once: every request carries a correlation ID,
+//! which the server puts on the response, so the server is free to answer requests in whichever order its handler
+//! finishes them in. Calls can be given a timeout, either per client or per call.
+//!
+//! Requests and responses are serialized in a [`Format`] chosen by the user, which both ends have to agree on, as do
+//! they on the request and response types.
+//!
+//! ## Wire format
+//! Every request and response is a [message](crate::framing) with a [32-bit length prefix](LengthPrefix::U32). A
+//! request starts with its correlation ID as a little-endian 64-bit integer, followed by the serialized request. A
+//! response starts with the correlation ID of the request it answers, followed by a status byte, which is 0 if the rest
+//! of the message is the serialized response, or 1 if the request couldn't be handled and the rest is an error message
+//! in UTF-8.
+//!
+//! # Example
+//! ```no_run
+//! # #[cfg(feature = "bincode")]
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! use interprocess::{
+//!     framing::Bincode,
+//!     local_socket::tokio::rpc::{RpcClient, RpcServer},
+//! };
+//!
+//! let server = RpcServer::bind("/tmp/example-rpc.sock", |(a, b): (i32, i32)| async move { a + b }, Bincode)?;
+//! tokio::spawn(server.run());
+//!
+//! let client = RpcClient::<(i32, i32), i32, _>::connect("/tmp/example-rpc.sock", Bincode).await?;
+//! let (two, four) = tokio::try_join!(client.call(&(1, 1)), client.call(&(2, 2)))?;
+//! assert_eq!((two, four), (2, 4));
+//! # Ok(()) }
+//! # #[cfg(not(feature = "bincode"))] fn main() {}
+//! ```
+
+use super::{LocalSocketListener, LocalSocketStream};
+use crate::{
+    framing::{Format, LengthPrefix, MessageReader, MessageWriter},
+    local_socket::ToLocalSocketName,
+};
+use futures_util::future::BoxFuture;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    io,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+
+const STATUS_OK: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+
+/// Handles the requests received by an [`RpcServer`].
+///
+/// Implemented for all closures which take a request and return a future of the response, which is how handlers are
+/// usually written. Every request is handled in a task of its own, and so the handler is shared between them.
+pub trait RpcHandler<Req>: Send + Sync + 'static {
+    /// The type of the responses.
+    type Response: Serialize + Send + 'static;
+    /// Handles one request, producing the response to it.
+    fn handle(&self, request: Req) -> BoxFuture<'_, Self::Response>;
+}
+impl<Req, H, Fut> RpcHandler<Req> for H
+where
+    H: Fn(Req) -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: Serialize + Send + 'static,
+{
+    type Response = Fut::Output;
+    fn handle(&self, request: Req) -> BoxFuture<'_, Self::Response> {
+        Box::pin(self(request))
+    }
+}
+
+/// A server which answers remote procedure calls by passing them to a handler.
+///
+/// The server does nothing until [`run()`](Self::run) is called, which is typically done in a task of its own.
+pub struct RpcServer<Req, H, F> {
+    listener: LocalSocketListener,
+    handler: Arc<H>,
+    format: F,
+    _phantom: PhantomData<fn(Req)>,
+}
+impl<Req, H, F> RpcServer<Req, H, F>
+where
+    Req: DeserializeOwned + Send + 'static,
+    H: RpcHandler<Req>,
+    F: Format + Clone + Send + Sync + 'static,
+{
+    /// Creates a server listening on the given name, which handles requests with the given handler and uses the given
+    /// format for requests and responses.
+    pub fn bind<'a>(name: impl ToLocalSocketName<'a>, handler: H, format: F) -> io::Result<Self> {
+        Ok(Self {
+            listener: LocalSocketListener::bind(name)?,
+            handler: Arc::new(handler),
+            format,
+            _phantom: PhantomData,
+        })
+    }
+    /// Accepts connections and answers the requests received over them, spawning a task for every connection and for
+    /// every request.
+    ///
+    /// Only returns if accepting a connection fails. Connections which have already been accepted keep being served
+    /// regardless, until they disconnect.
+    pub async fn run(self) -> io::Result<()> {
+        loop {
+            let conn = self.listener.accept().await?;
+            tokio::spawn(serve(conn, Arc::clone(&self.handler), self.format.clone()));
+        }
+    }
+}
+impl<Req, H, F: Debug> Debug for RpcServer<Req, H, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcServer")
+            .field("listener", &self.listener)
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Serves one connection until it disconnects or sends something which isn't a request.
+async fn serve<Req, H, F>(conn: LocalSocketStream, handler: Arc<H>, format: F)
+where
+    Req: DeserializeOwned + Send + 'static,
+    H: RpcHandler<Req>,
+    F: Format + Clone + Send + Sync + 'static,
+{
+    let (reader, writer) = conn.split();
+    let mut reader = MessageReader::new(reader).with_prefix(LengthPrefix::U32);
+    let (outgoing, rx) = mpsc::unbounded_channel();
+    tokio::spawn(drain_into(
+        rx,
+        MessageWriter::new(writer).with_prefix(LengthPrefix::U32),
+    ));
+    while let Ok(Some(msg)) = reader.read_message_async().await {
+        let Some((id, payload)) = split_id(msg) else { break };
+        let request = match format.deserialize::<Req>(payload) {
+            Ok(request) => request,
+            Err(e) => {
+                let msg = format!("failed to deserialize request: {e}");
+                let _ = outgoing.send(response(id, STATUS_ERROR, msg.as_bytes()));
+                continue;
+            }
+        };
+        let (handler, format, outgoing) = (Arc::clone(&handler), format.clone(), outgoing.clone());
+        tokio::spawn(async move {
+            let frame = match format.serialize(&handler.handle(request).await) {
+                Ok(bytes) => response(id, STATUS_OK, &bytes),
+                Err(e) => response(
+                    id,
+                    STATUS_ERROR,
+                    format!("failed to serialize response: {e}").as_bytes(),
+                ),
+            };
+            let _ = outgoing.send(frame);
+        });
+    }
+}
+
+fn response(id: u64, status: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(9 + payload.len());
+    frame.extend_from_slice(&id.to_le_bytes());
+    frame.push(status);
+    frame.extend_from_slice(payload);
+    frame
+}
+fn split_id(msg: &[u8]) -> Option<(u64, &[u8])> {
+    let id = msg.get(..8)?;
+    Some((u64::from_le_bytes(id.try_into().unwrap()), &msg[8..]))
+}
+/// Writes messages from the channel to the stream until every sender is gone or writing fails.
+async fn drain_into<W: futures_io::AsyncWrite + Unpin>(
+    mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    mut writer: MessageWriter<W>,
+) {
+    while let Some(frame) = rx.recv().await {
+        if writer.write_message_async(&frame).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Calls waiting for a response, by correlation ID, or `None` once the connection is gone.
+type Pending = Mutex<Option<HashMap<u64, oneshot::Sender<io::Result<Vec<u8>>>>>>;
+
+/// A connection to an [`RpcServer`], over which any number of calls can be made concurrently.
+///
+/// Calls only need a shared reference, so the client can be put in an [`Arc`] and used from many tasks at once.
+/// Responses are received by a task spawned when the client is created, which is stopped when the client is dropped.
+pub struct RpcClient<Req, Resp, F> {
+    outgoing: mpsc::UnboundedSender<Vec<u8>>,
+    pending: Arc<Pending>,
+    next_id: AtomicU64,
+    format: F,
+    timeout: Option<Duration>,
+    tasks: [JoinHandle<()>; 2],
+    _phantom: PhantomData<fn(Req) -> Resp>,
+}
+impl<Req: Serialize, Resp: DeserializeOwned, F: Format> RpcClient<Req, Resp, F> {
+    /// Connects to the server listening on the given name, using the given format for requests and responses.
+    pub async fn connect<'a>(name: impl ToLocalSocketName<'a>, format: F) -> io::Result<Self> {
+        Ok(Self::from_stream(LocalSocketStream::connect(name).await?, format))
+    }
+    /// Makes calls over an already established connection, using the given format for requests and responses.
+    pub fn from_stream(conn: LocalSocketStream, format: F) -> Self {
+        let (reader, writer) = conn.split();
+        let pending = Arc::new(Pending::new(Some(HashMap::new())));
+        let (outgoing, rx) = mpsc::unbounded_channel();
+        let writer_task = {
+            let pending = Arc::clone(&pending);
+            tokio::spawn(async move {
+                drain_into(rx, MessageWriter::new(writer).with_prefix(LengthPrefix::U32)).await;
+                pending.lock().unwrap().take();
+            })
+        };
+        let reader_task = tokio::spawn(receive_responses(
+            MessageReader::new(reader).with_prefix(LengthPrefix::U32),
+            Arc::clone(&pending),
+        ));
+        Self {
+            outgoing,
+            pending,
+            next_id: AtomicU64::new(0),
+            format,
+            timeout: None,
+            tasks: [writer_task, reader_task],
+            _phantom: PhantomData,
+        }
+    }
+    /// Sets the timeout for calls made with [`call()`](Self::call). There's none by default.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Sends the request and waits for the response, for no longer than the [timeout](Self::with_timeout) of the
+    /// client.
+    ///
+    /// # Errors
+    /// - [`TimedOut`](io::ErrorKind::TimedOut) if the timeout elapses first. A response which arrives afterwards is
+    ///   discarded.
+    /// - [`ConnectionAborted`](io::ErrorKind::ConnectionAborted) if the connection is lost before the response
+    ///   arrives, including if it had been lost before the call.
+    /// - [`InvalidInput`](io::ErrorKind::InvalidInput) if the request fails to serialize, and
+    ///   [`InvalidData`](io::ErrorKind::InvalidData) if the response fails to deserialize, or if the server reports
+    ///   that it couldn't handle the request because of either of those.
+    ///
+    /// Dropping the future cancels the call, without affecting other calls.
+    pub async fn call(&self, request: &Req) -> io::Result<Resp> {
+        self.call_with(request, self.timeout).await
+    }
+    /// Same as [`call()`](Self::call), but with the given timeout instead of that of the client.
+    pub async fn call_timeout(&self, request: &Req, timeout: Duration) -> io::Result<Resp> {
+        self.call_with(request, Some(timeout)).await
+    }
+
+    async fn call_with(&self, request: &Req, timeout: Option<Duration>) -> io::Result<Resp> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut frame = id.to_le_bytes().to_vec();
+        frame.extend_from_slice(&self.format.serialize(request)?);
+
+        let (tx, rx) = oneshot::channel();
+        match &mut *self.pending.lock().unwrap() {
+            Some(pending) => pending.insert(id, tx),
+            None => return Err(connection_lost()),
+        };
+        // Removes the entry if the call times out or is cancelled.
+        let _guard = PendingGuard(&self.pending, id);
+        if self.outgoing.send(frame).is_err() {
+            return Err(connection_lost());
+        }
+        let response = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, rx)
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "the call timed out"))?,
+            None => rx.await,
+        };
+        let bytes = response.map_err(|_| connection_lost())??;
+        self.format.deserialize(&bytes)
+    }
+}
+impl<Req, Resp, F> Drop for RpcClient<Req, Resp, F> {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+impl<Req, Resp, F: Debug> Debug for RpcClient<Req, Resp, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcClient")
+            .field("format", &self.format)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+struct PendingGuard<'a>(&'a Pending, u64);
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(pending) = &mut *self.0.lock().unwrap() {
+            pending.remove(&self.1);
+        }
+    }
+}
+
+/// Hands responses over to the calls waiting for them until the connection ends, then fails the remaining calls.
+async fn receive_responses<R: futures_io::AsyncRead + Unpin>(mut reader: MessageReader<R>, pending: Arc<Pending>) {
+    while let Ok(Some(msg)) = reader.read_message_async().await {
+        let Some((id, rest)) = split_id(msg) else { break };
+        let result = match rest.split_first() {
+            Some((&STATUS_OK, payload)) => Ok(payload.to_vec()),
+            Some((&STATUS_ERROR, payload)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the server failed to handle the request: {}",
+                    String::from_utf8_lossy(payload)
+                ),
+            )),
+            _ => break,
+        };
+        // The call might have timed out or been cancelled in the meantime.
+        let waiter = pending.lock().unwrap().as_mut().and_then(|pending| pending.remove(&id));
+        if let Some(waiter) = waiter {
+            let _ = waiter.send(result);
+        }
+    }
+    // Drops the senders, which wakes up the calls still waiting.
+    pending.lock().unwrap().take();
+}
+
+fn connection_lost() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::ConnectionAborted,
+        "the connection to the server was lost",
+    )
+}
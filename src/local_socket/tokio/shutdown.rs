@@ -0,0 +1,42 @@
+use super::LocalSocketStream;
+use crate::local_socket::shutdown::timed_out;
+use std::{io, time::Instant};
+use tokio::{io::AsyncReadExt, time};
+
+/// Asynchronously closes a local socket stream without losing the last messages exchanged over it.
+///
+/// This is the asynchronous counterpart of [`graceful_shutdown()`](crate::local_socket::graceful_shutdown), which
+/// describes the teardown sequence and how it differs on Windows.
+///
+/// # Errors
+/// An error of kind [`TimedOut`](io::ErrorKind::TimedOut) is returned if the peer hasn't closed its end of the
+/// connection by the deadline. The stream is closed regardless of whether an error occurs.
+///
+/// # Panics
+/// Panics if called outside of a Tokio runtime context with the timer enabled.
+pub async fn graceful_shutdown(stream: LocalSocketStream, deadline: Instant) -> io::Result<()> {
+    drain(stream, deadline, |_| {}).await
+}
+/// Same as [`graceful_shutdown()`], but appends the remaining data from the peer to the given buffer instead of
+/// discarding it.
+///
+/// If an error occurs, the buffer holds whatever was received before it did.
+pub async fn graceful_shutdown_into(stream: LocalSocketStream, deadline: Instant, buf: &mut Vec<u8>) -> io::Result<()> {
+    drain(stream, deadline, |data| buf.extend_from_slice(data)).await
+}
+
+async fn drain(mut stream: LocalSocketStream, deadline: Instant, mut sink: impl FnMut(&[u8])) -> io::Result<()> {
+    stream.0.shutdown_write()?;
+    let mut buf = [0; 4096];
+    let reading = async {
+        loop {
+            match stream.read(&mut buf).await? {
+                0 => return Ok(()),
+                n => sink(&buf[..n]),
+            }
+        }
+    };
+    time::timeout_at(deadline.into(), reading)
+        .await
+        .unwrap_or_else(|_| Err(timed_out()))
+}
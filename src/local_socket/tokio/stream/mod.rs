@@ -13,6 +13,7 @@ use {
         pin::Pin,
         task::{Context, Poll},
     },
+    tokio::io::{AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite, ReadBuf as TokioReadBuf},
 };
 
 impmod! {local_socket::tokio,
@@ -22,6 +23,9 @@ impmod! {local_socket::tokio,
 /// A Tokio-based local socket byte stream, obtained eiter from [`LocalSocketListener`](super::LocalSocketListener) or
 /// by connecting to an existing local socket.
 ///
+/// Both the [`futures`](futures_io::AsyncRead) and the [Tokio](tokio::io::AsyncRead) flavors of `AsyncRead` and
+/// `AsyncWrite` are implemented, the latter of which makes the stream usable with the codecs of `tokio-util`.
+///
 /// # Examples
 ///
 /// ## Basic client
@@ -88,6 +92,23 @@ impl LocalSocketStream {
         let (r, w) = self.0.split();
         (ReadHalf(r), WriteHalf(w))
     }
+    /// Asynchronously reads up to and including the delimiter, or up to end-of-file, appending the data to `buf` and
+    /// returning how many bytes were appended.
+    ///
+    /// Like its [synchronous counterpart](crate::local_socket::LocalSocketStream::read_until), this never reads past
+    /// the delimiter, leaving whatever comes after it in the stream.
+    ///
+    /// This method is only available on Unix. Tokio reads from named pipes ahead of time into a buffer of its own, out
+    /// of reach of peeking, and so this can't be done on Windows.
+    ///
+    /// # System calls
+    /// - `recv` (`MSG_PEEK`)
+    /// - `read`
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub async fn read_until(&mut self, delim: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.0.read_until(delim, buf).await
+    }
     #[inline]
     fn pinproj(&mut self) -> Pin<&mut LocalSocketStreamImpl> {
         Pin::new(&mut self.0)
@@ -106,7 +127,7 @@ impl From<LocalSocketStreamImpl> for LocalSocketStream {
 impl AsyncRead for LocalSocketStream {
     #[inline]
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
-        self.pinproj().poll_read(cx, buf)
+        AsyncRead::poll_read(self.pinproj(), cx, buf)
     }
     #[inline]
     fn poll_read_vectored(
@@ -140,6 +161,26 @@ impl AsyncWrite for LocalSocketStream {
         self.pinproj().poll_close(cx)
     }
 }
+impl TokioAsyncRead for LocalSocketStream {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        TokioAsyncRead::poll_read(self.pinproj(), cx, buf)
+    }
+}
+impl TokioAsyncWrite for LocalSocketStream {
+    #[inline]
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.pinproj().poll_write(cx, buf)
+    }
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.pinproj().poll_flush(cx)
+    }
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.pinproj().poll_close(cx)
+    }
+}
 impl Debug for LocalSocketStream {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -6,6 +6,7 @@ use {
         pin::Pin,
         task::{Context, Poll},
     },
+    tokio::io::{AsyncRead as TokioAsyncRead, ReadBuf as TokioReadBuf},
 };
 
 impmod! {local_socket::tokio,
@@ -27,7 +28,7 @@ impl ReadHalf {
 impl AsyncRead for ReadHalf {
     #[inline]
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
-        self.pinproj().poll_read(cx, buf)
+        AsyncRead::poll_read(self.pinproj(), cx, buf)
     }
     #[inline]
     fn poll_read_vectored(
@@ -38,6 +39,12 @@ impl AsyncRead for ReadHalf {
         self.pinproj().poll_read_vectored(cx, bufs)
     }
 }
+impl TokioAsyncRead for ReadHalf {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        TokioAsyncRead::poll_read(self.pinproj(), cx, buf)
+    }
+}
 impl Debug for ReadHalf {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -6,6 +6,7 @@ use {
         pin::Pin,
         task::{Context, Poll},
     },
+    tokio::io::AsyncWrite as TokioAsyncWrite,
 };
 
 impmod! {local_socket::tokio,
@@ -49,6 +50,20 @@ impl AsyncWrite for WriteHalf {
         self.pinproj().poll_close(cx)
     }
 }
+impl TokioAsyncWrite for WriteHalf {
+    #[inline]
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.pinproj().poll_write(cx, buf)
+    }
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.pinproj().poll_flush(cx)
+    }
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.pinproj().poll_close(cx)
+    }
+}
 
 impl Debug for WriteHalf {
     #[inline]
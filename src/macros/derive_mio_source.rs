@@ -0,0 +1,34 @@
+/// Implements [`mio::event::Source`] for a type which implements `AsRawFd`, registering its file descriptor directly.
+macro_rules! derive_mio_source {
+    ($ty:ty) => {
+        #[cfg(all(unix, feature = "mio"))]
+        #[cfg_attr(feature = "doc_cfg", doc(cfg(all(unix, feature = "mio"))))]
+        impl ::mio::event::Source for $ty {
+            #[inline]
+            fn register(
+                &mut self,
+                registry: &::mio::Registry,
+                token: ::mio::Token,
+                interests: ::mio::Interest,
+            ) -> ::std::io::Result<()> {
+                let fd = ::std::os::unix::io::AsRawFd::as_raw_fd(self);
+                ::mio::unix::SourceFd(&fd).register(registry, token, interests)
+            }
+            #[inline]
+            fn reregister(
+                &mut self,
+                registry: &::mio::Registry,
+                token: ::mio::Token,
+                interests: ::mio::Interest,
+            ) -> ::std::io::Result<()> {
+                let fd = ::std::os::unix::io::AsRawFd::as_raw_fd(self);
+                ::mio::unix::SourceFd(&fd).reregister(registry, token, interests)
+            }
+            #[inline]
+            fn deregister(&mut self, registry: &::mio::Registry) -> ::std::io::Result<()> {
+                let fd = ::std::os::unix::io::AsRawFd::as_raw_fd(self);
+                ::mio::unix::SourceFd(&fd).deregister(registry)
+            }
+        }
+    };
+}
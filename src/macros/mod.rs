@@ -10,6 +10,8 @@ mod forward_handle_and_fd;
 mod forward_try_clone;
 #[macro_use]
 mod forward_trait_method;
+#[macro_use]
+mod derive_mio_source;
 
 macro_rules! impmod {
     ($($osmod:ident)::+, $($orig:ident $(as $into:ident)?),* $(,)?) => {
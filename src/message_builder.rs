@@ -0,0 +1,137 @@
+//! Putting outgoing messages together from several separate slices, without copying them into one buffer.
+//!
+//! Serializers which produce their output in pieces, or protocols which wrap a body serialized elsewhere (with rkyv or
+//! Cap'n Proto, for example) in a header and a trailer, would otherwise have to concatenate the pieces before sending
+//! them as one message. A [`MessageBuilder`] collects borrowed slices instead and hands them to the transport as a
+//! whole, which sends them with a single vectored system call where it can:
+//! - [`send()`](MessageBuilder::send) and [`send_async()`](MessageBuilder::send_async) send the parts as one
+//!   [datagram](crate::datagram), with `sendmsg` for Ud-socket datagrams. Message-mode named pipes can't be written to
+//!   from several buffers at once, and so the parts are concatenated into a temporary buffer for those.
+//! - [`write_framed()`](MessageBuilder::write_framed) and [`write_framed_async()`](MessageBuilder::write_framed_async)
+//!   write the parts as one [length-prefixed message](crate::framing) to any byte stream, such as a local socket or an
+//!   unnamed pipe, with the length prefix in the same vectored write as the parts. On Windows, byte streams write
+//!   one part per system call, since `WriteFile` has no vectored counterpart for pipes, but the parts still aren't
+//!   copied.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::{framing::MessageWriter, message_builder::MessageBuilder, unnamed_pipe::pipe};
+//!
+//! let (tx, _rx) = pipe()?;
+//! let mut writer = MessageWriter::new(tx);
+//!
+//! let body = b"body serialized elsewhere";
+//! let header = (body.len() as u32).to_le_bytes();
+//! MessageBuilder::new()
+//!     .part(&header)
+//!     .part(body)
+//!     .part(b"trailer")
+//!     .write_framed(&mut writer)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::{
+    datagram::{total_len, AsyncDatagram, AsyncDatagramExt, Datagram},
+    framing::MessageWriter,
+};
+use std::io::{self, prelude::*, IoSlice};
+
+/// An outgoing message made of borrowed slices, sent without concatenating them.
+///
+/// See the [module-level documentation](self) for more.
+#[derive(Clone, Debug, Default)]
+pub struct MessageBuilder<'a> {
+    parts: Vec<IoSlice<'a>>,
+}
+impl<'a> MessageBuilder<'a> {
+    /// Creates an empty message.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Creates an empty message with room for the given number of parts.
+    #[inline]
+    pub fn with_capacity(parts: usize) -> Self {
+        Self {
+            parts: Vec::with_capacity(parts),
+        }
+    }
+    /// Appends a part to the message.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn part(mut self, part: &'a [u8]) -> Self {
+        self.push(part);
+        self
+    }
+    /// Appends a part to the message in place.
+    #[inline]
+    pub fn push(&mut self, part: &'a [u8]) {
+        self.parts.push(IoSlice::new(part));
+    }
+    /// Removes all of the parts, keeping the allocation for the next message.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.parts.clear();
+    }
+    /// Returns the parts in order, in the form taken by vectored writes.
+    #[inline]
+    pub fn parts(&self) -> &[IoSlice<'a>] {
+        &self.parts
+    }
+    /// Returns the total length of the message, in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        total_len(&self.parts)
+    }
+    /// Returns `true` if the message is empty, which is also the case if all of its parts are.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.parts.iter().all(|p| p.is_empty())
+    }
+    /// Copies the parts into one contiguous buffer.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.len());
+        for part in &self.parts {
+            buf.extend_from_slice(part);
+        }
+        buf
+    }
+
+    /// Sends the message as one datagram, with [`Datagram::send_vectored()`].
+    #[inline]
+    pub fn send(&self, conn: &mut (impl Datagram + ?Sized)) -> io::Result<()> {
+        conn.send_vectored(&self.parts)
+    }
+    /// Asynchronously sends the message as one datagram, with [`AsyncDatagramExt::send_vectored()`].
+    pub async fn send_async(&self, conn: &mut (impl AsyncDatagram + Unpin + ?Sized)) -> io::Result<()> {
+        conn.send_vectored(&self.parts).await
+    }
+    /// Writes the message to a byte stream, preceded by its length, with
+    /// [`MessageWriter::write_message_vectored()`].
+    #[inline]
+    pub fn write_framed<W: Write>(&self, writer: &mut MessageWriter<W>) -> io::Result<()> {
+        writer.write_message_vectored(&self.parts)
+    }
+    /// Asynchronously writes the message to a byte stream, preceded by its length, with
+    /// [`MessageWriter::write_message_vectored_async()`].
+    #[cfg(feature = "async")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async")))]
+    pub async fn write_framed_async<W: futures_io::AsyncWrite + Unpin>(
+        &self,
+        writer: &mut MessageWriter<W>,
+    ) -> io::Result<()> {
+        writer.write_message_vectored_async(&self.parts).await
+    }
+}
+impl<'a> Extend<&'a [u8]> for MessageBuilder<'a> {
+    fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+        self.parts.extend(iter.into_iter().map(IoSlice::new));
+    }
+}
+impl<'a> FromIterator<&'a [u8]> for MessageBuilder<'a> {
+    fn from_iter<I: IntoIterator<Item = &'a [u8]>>(iter: I) -> Self {
+        let mut builder = Self::new();
+        builder.extend(iter);
+        builder
+    }
+}
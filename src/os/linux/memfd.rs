@@ -0,0 +1,207 @@
+use libc::c_int;
+use std::{
+    ffi::{CString, OsStr},
+    fmt::{self, Debug, Formatter},
+    fs::File,
+    io,
+    ops::{BitOr, BitOrAssign},
+    os::unix::prelude::*,
+};
+
+/// Options for creating a [`Memfd`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MemfdOptions {
+    allow_sealing: bool,
+}
+impl MemfdOptions {
+    /// Creates a new set of options with the default values: sealing is not allowed.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { allow_sealing: false }
+    }
+    /// Sets whether [seals](Seals) can be added to the file. If this isn't set, the file is created with
+    /// [`Seals::SEAL`] already in place, which rules out adding any other seals.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub const fn allow_sealing(mut self, allow_sealing: bool) -> Self {
+        self.allow_sealing = allow_sealing;
+        self
+    }
+    /// Creates a memory file with the given name, which is empty.
+    ///
+    /// The name is only used for debugging purposes, showing up as the target of the `/proc/self/fd` symlink, and
+    /// doesn't have to be unique. The file is created with the close-on-exec flag set.
+    ///
+    /// # System calls
+    /// - `memfd_create`
+    pub fn create(self, name: impl AsRef<OsStr>) -> io::Result<Memfd> {
+        let name = CString::new(name.as_ref().as_bytes())?;
+        let mut flags = libc::MFD_CLOEXEC;
+        if self.allow_sealing {
+            flags |= libc::MFD_ALLOW_SEALING;
+        }
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), flags) };
+        ok_or_ret_errno!(fd != -1 => Memfd(unsafe {
+            // SAFETY: we just created this file descriptor
+            File::from_raw_fd(fd)
+        }))
+    }
+}
+
+/// An anonymous file which lives in memory.
+///
+/// Memory files behave like regular files – they can be read, written, resized and mapped – but have no name on the
+/// filesystem, and are destroyed once the last file descriptor referring to them is closed. They're typically used as
+/// shared memory which is shared by passing its file descriptor around rather than by name: the receiver can map it
+/// with [`SharedMemory::from_fd()`](crate::shared_memory::SharedMemory::from_fd), or simply read it.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::os::{
+///     linux::{Memfd, MemfdOptions, Seals},
+///     unix::udsocket::cmsg::ancillary::file_descriptors::FileDescriptors,
+/// };
+/// use std::{io::Write, os::fd::AsFd};
+///
+/// let mut memfd = MemfdOptions::new().allow_sealing(true).create("example-config")?;
+/// memfd.file().write_all(b"verbose = true")?;
+/// // Whoever receives the file can now be sure that its contents won't change.
+/// memfd.add_seals(Seals::SHRINK | Seals::GROW | Seals::WRITE | Seals::SEAL)?;
+///
+/// // Ready to be sent as ancillary data over a Unix domain socket.
+/// let fds = [memfd.as_fd()];
+/// let ancillary = FileDescriptors::new(&fds);
+/// // On the receiving end, file descriptors are taken out of the message and adopted.
+/// let received = ancillary.into_owned_fds()?.into_iter().map(Memfd::from).next();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Memfd(File);
+impl Memfd {
+    /// Creates a memory file with the given name and the default [options](MemfdOptions), which means that seals can't
+    /// be added to it.
+    ///
+    /// # System calls
+    /// - `memfd_create`
+    #[inline]
+    pub fn create(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        MemfdOptions::new().create(name)
+    }
+    /// Borrows the file, for reading, writing and resizing it.
+    ///
+    /// Reading and writing go through a shared file offset, which is shared with every duplicate of the file
+    /// descriptor, including ones sent to other processes; the positional methods of
+    /// [`FileExt`](std::os::unix::fs::FileExt) can be used to avoid depending on it.
+    #[inline]
+    pub fn file(&mut self) -> &mut File {
+        &mut self.0
+    }
+    /// Returns the file, giving up the ability to manage seals through this type.
+    #[inline]
+    pub fn into_file(self) -> File {
+        self.0
+    }
+    /// Returns the seals currently in place on the file.
+    ///
+    /// # System calls
+    /// - `fcntl` (`F_GET_SEALS`)
+    pub fn seals(&self) -> io::Result<Seals> {
+        let seals = unsafe { libc::fcntl(self.0.as_raw_fd(), libc::F_GET_SEALS) };
+        ok_or_ret_errno!(seals != -1 => Seals(seals))
+    }
+    /// Adds seals to the file, which can never be removed. Fails with
+    /// [`PermissionDenied`](io::ErrorKind::PermissionDenied) if [`Seals::SEAL`] is in place, including when the file
+    /// was created without [allowing sealing](MemfdOptions::allow_sealing).
+    ///
+    /// [`Seals::WRITE`] can't be added while the file is mapped as writable anywhere, in which case this fails with
+    /// `EBUSY`.
+    ///
+    /// # System calls
+    /// - `fcntl` (`F_ADD_SEALS`)
+    pub fn add_seals(&self, seals: Seals) -> io::Result<()> {
+        let success = unsafe { libc::fcntl(self.0.as_raw_fd(), libc::F_ADD_SEALS, seals.0) != -1 };
+        ok_or_ret_errno!(success => ())
+    }
+}
+impl Debug for Memfd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Memfd").field(&self.0.as_raw_fd()).finish()
+    }
+}
+impl AsFd for Memfd {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+impl From<Memfd> for OwnedFd {
+    #[inline]
+    fn from(memfd: Memfd) -> Self {
+        memfd.0.into()
+    }
+}
+/// Adopts a file descriptor, such as one received from another process. The file descriptor isn't checked to actually
+/// refer to a memory file; if it doesn't, managing seals fails.
+impl From<OwnedFd> for Memfd {
+    #[inline]
+    fn from(fd: OwnedFd) -> Self {
+        Self(fd.into())
+    }
+}
+impl From<Memfd> for File {
+    #[inline]
+    fn from(memfd: Memfd) -> Self {
+        memfd.0
+    }
+}
+derive_raw!(unix: Memfd);
+
+/// A set of seals, which restrict what can be done with a [`Memfd`], regardless of who does it.
+///
+/// Seals are combined with the `|` operator.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Seals(c_int);
+impl Seals {
+    /// Prevents any further seals from being added.
+    pub const SEAL: Self = Self(libc::F_SEAL_SEAL);
+    /// Prevents the file from being made smaller.
+    pub const SHRINK: Self = Self(libc::F_SEAL_SHRINK);
+    /// Prevents the file from being made larger, including by writing past its end.
+    pub const GROW: Self = Self(libc::F_SEAL_GROW);
+    /// Prevents the contents of the file from being modified, either by writing to it or through a writable mapping.
+    pub const WRITE: Self = Self(libc::F_SEAL_WRITE);
+    /// Like [`WRITE`](Self::WRITE), but allows writable mappings which exist already to keep being written to, so that
+    /// the sealer can keep modifying the file while everyone else can't. Requires Linux 5.1.
+    pub const FUTURE_WRITE: Self = Self(F_SEAL_FUTURE_WRITE);
+
+    /// Returns the empty set of seals.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+    /// Returns the raw value, as used by `fcntl`.
+    #[inline]
+    pub const fn bits(self) -> c_int {
+        self.0
+    }
+    /// Returns `true` if all of the seals in `other` are also in `self`.
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+impl BitOr for Seals {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+impl BitOrAssign for Seals {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Not defined by the oldest version of `libc` that we support.
+const F_SEAL_FUTURE_WRITE: c_int = 0x0010;
@@ -0,0 +1,22 @@
+//! Linux-specific functionality for various interprocess communication primitives.
+//!
+//! ## Memory file descriptors
+//! A [`Memfd`] is an anonymous file which lives in memory and has no name on the filesystem. It's shared with other
+//! processes by passing its file descriptor along, typically over a Unix domain socket as
+//! [`FileDescriptors`](crate::os::unix::udsocket::cmsg::ancillary::file_descriptors::FileDescriptors) ancillary
+//! data. Before doing so, it can be [sealed](Seals) against further modification, allowing the receiver to map it
+//! without worrying about the sender changing its size or contents behind its back.
+//!
+//! ## Process file descriptors
+//! A [`PidFd`] refers to a process in a way which, unlike a process ID, can't be recycled to refer to a different one
+//! once the process exits. It's used to [send signals](PidFd::send_signal) without the risk of hitting an unrelated
+//! process, to [wait for the process to exit](PidFd::wait_for_exit), and to
+//! [duplicate file descriptors](PidFd::get_fd) out of it.
+//!
+//! This module is only available on Linux and Android. On other platforms, it's absent and thus any usage of it will
+//! result in a compile-time error.
+
+mod memfd;
+mod pidfd;
+
+pub use {memfd::*, pidfd::*};
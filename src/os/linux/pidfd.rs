@@ -0,0 +1,157 @@
+use crate::os::unix::c_wrappers;
+use libc::{c_int, pid_t};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    os::unix::prelude::*,
+    process::Child,
+    ptr,
+    time::Duration,
+};
+
+/// A file descriptor referring to a process, which stays tied to that process even after it exits and its process ID
+/// gets reused.
+///
+/// Requires Linux 5.3; [`get_fd()`](Self::get_fd) requires Linux 5.6.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::os::linux::PidFd;
+/// use std::{process::Command, time::Duration};
+///
+/// let child = Command::new("sleep").arg("60").spawn()?;
+/// let pidfd = PidFd::from_child(&child)?;
+/// if !pidfd.wait_for_exit_timeout(Duration::from_secs(1))? {
+///     // Can't hit an unrelated process, even if the child has exited and been reaped since.
+///     pidfd.send_signal(libc::SIGTERM)?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct PidFd(OwnedFd);
+impl PidFd {
+    /// Opens a file descriptor referring to the process with the given ID.
+    ///
+    /// The file descriptor is created with the close-on-exec flag set. Note that a process ID may be reused as soon as
+    /// the process it belonged to is reaped, and so, unless the process is a child of the caller which hasn't been
+    /// waited on yet, there is no telling whether the file descriptor refers to the intended process.
+    ///
+    /// # System calls
+    /// - `pidfd_open`
+    pub fn open(pid: pid_t) -> io::Result<Self> {
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0 as c_int) };
+        ok_or_ret_errno!(fd != -1 => Self(unsafe {
+            // SAFETY: we just created this file descriptor
+            OwnedFd::from_raw_fd(fd as RawFd)
+        }))
+    }
+    /// Opens a file descriptor referring to a child process which hasn't been waited on yet, and thus is sure to refer
+    /// to that child.
+    ///
+    /// # System calls
+    /// - `pidfd_open`
+    #[inline]
+    pub fn from_child(child: &Child) -> io::Result<Self> {
+        Self::open(child.id() as pid_t)
+    }
+
+    /// Sends a signal to the process.
+    ///
+    /// Fails with `ESRCH` if the process has exited, even if it hasn't been waited on yet.
+    ///
+    /// # System calls
+    /// - `pidfd_send_signal`
+    pub fn send_signal(&self, signal: c_int) -> io::Result<()> {
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                self.0.as_raw_fd(),
+                signal,
+                ptr::null::<libc::siginfo_t>(),
+                0 as libc::c_uint,
+            )
+        };
+        ok_or_ret_errno!(ret != -1 => ())
+    }
+    /// Duplicates a file descriptor of the process into the calling one, as if it had been sent over a Unix domain
+    /// socket. `target` is the number of the file descriptor in the process.
+    ///
+    /// The caller needs permission to `ptrace` the process, which normally means that the process must be a child of
+    /// the caller, or belong to the same user, depending on the Yama security module's settings. The new file
+    /// descriptor is created with the close-on-exec flag set.
+    ///
+    /// # System calls
+    /// - `pidfd_getfd`
+    pub fn get_fd(&self, target: RawFd) -> io::Result<OwnedFd> {
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_getfd, self.0.as_raw_fd(), target, 0 as libc::c_uint) };
+        ok_or_ret_errno!(fd != -1 => unsafe {
+            // SAFETY: we just created this file descriptor
+            OwnedFd::from_raw_fd(fd as RawFd)
+        })
+    }
+
+    /// Returns `true` if the process has exited, without blocking.
+    ///
+    /// # System calls
+    /// - `poll`
+    #[inline]
+    pub fn has_exited(&self) -> io::Result<bool> {
+        c_wrappers::poll_readable(self.0.as_fd(), 0)
+    }
+    /// Blocks until the process exits.
+    ///
+    /// This only waits for the process to exit, and doesn't reap it if it's a child of the caller; that's still up to
+    /// [`Child::wait()`] or `waitpid`.
+    ///
+    /// # System calls
+    /// - `poll`
+    pub fn wait_for_exit(&self) -> io::Result<()> {
+        while !c_wrappers::poll_readable(self.0.as_fd(), -1)? {}
+        Ok(())
+    }
+    /// Like [`wait_for_exit()`](Self::wait_for_exit), but gives up once the timeout runs out, returning `false` in
+    /// that case.
+    ///
+    /// # System calls
+    /// - `poll`
+    pub fn wait_for_exit_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        c_wrappers::poll_readable(self.0.as_fd(), c_wrappers::timeout_to_ms(Some(timeout)))
+    }
+    /// Asynchronously waits until the process exits.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn wait_for_exit_async(&self) -> io::Result<()> {
+        let registration = c_wrappers::register_readable(self.0.as_fd())?;
+        // A pidfd stays readable once the process has exited, and so there is nothing to consume.
+        let _guard = registration.readable().await?;
+        Ok(())
+    }
+}
+impl Debug for PidFd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PidFd").field(&self.0.as_raw_fd()).finish()
+    }
+}
+impl AsFd for PidFd {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+impl From<PidFd> for OwnedFd {
+    #[inline]
+    fn from(pidfd: PidFd) -> Self {
+        pidfd.0
+    }
+}
+/// Adopts a file descriptor, such as one received from another process. The file descriptor isn't checked to actually
+/// refer to a process.
+impl From<OwnedFd> for PidFd {
+    #[inline]
+    fn from(fd: OwnedFd) -> Self {
+        Self(fd)
+    }
+}
+derive_raw!(unix: PidFd);
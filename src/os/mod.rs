@@ -6,7 +6,13 @@
 //! Linux and FreeBSD using the Platform menu on the Docs.rs-specific header bar at the top of the page. Docs.rs builds
 //! also have the nightly-only `doc_cfg` feature enabled by default, with which everything platform-specific has a badge
 //! next to it which specifies the `cfg(...)` conditions for that item to be available.
+//!
+//! On Linux and Android, there's also the [`linux`](self::linux) module, which houses functionality specific to those
+//! systems on top of what's in the Unix one.
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(any(target_os = "linux", target_os = "android"))))]
+pub mod linux;
 #[cfg(unix)]
 #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
 pub mod unix;
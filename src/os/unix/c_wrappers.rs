@@ -1,5 +1,8 @@
 use super::unixprelude::*;
-use std::io;
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 pub(super) unsafe fn fcntl_int(fd: BorrowedFd<'_>, cmd: c_int, val: c_int) -> io::Result<c_int> {
     let val = unsafe { libc::fcntl(fd.as_raw_fd(), cmd, val) };
@@ -10,6 +13,37 @@ pub(super) unsafe fn fcntl_noarg(fd: BorrowedFd<'_>, cmd: c_int) -> io::Result<c
     ok_or_ret_errno!(val != -1 => val)
 }
 
+fn get_status_flags(fd: BorrowedFd<'_>) -> io::Result<c_int> {
+    unsafe { fcntl_noarg(fd, libc::F_GETFL) }
+}
+fn set_status_flags(fd: BorrowedFd<'_>, new_flags: c_int) -> io::Result<()> {
+    unsafe {
+        fcntl_int(fd, libc::F_SETFL, new_flags)?;
+    }
+    Ok(())
+}
+pub(super) fn set_nonblocking(fd: BorrowedFd<'_>, nonblocking: bool) -> io::Result<()> {
+    let old_flags = get_status_flags(fd)?;
+    let new_flags = if nonblocking {
+        old_flags | libc::O_NONBLOCK
+    } else {
+        // Inverting the O_NONBLOCK value sets all the bits in the flag set to 1 except for the
+        // nonblocking flag, which clears the flag when ANDed.
+        old_flags & !libc::O_NONBLOCK
+    };
+    set_status_flags(fd, new_flags)
+}
+pub(super) fn get_nonblocking(fd: BorrowedFd<'_>) -> io::Result<bool> {
+    let flags = get_status_flags(fd)?;
+    Ok(flags & libc::O_NONBLOCK != 0)
+}
+
+pub(super) fn bytes_available(fd: BorrowedFd<'_>) -> io::Result<usize> {
+    let mut avail: c_int = 0;
+    let success = unsafe { libc::ioctl(fd.as_raw_fd(), libc::FIONREAD, &mut avail) != -1 };
+    ok_or_ret_errno!(success => avail as usize)
+}
+
 pub(super) fn duplicate_fd(fd: BorrowedFd<'_>) -> io::Result<OwnedFd> {
     #[cfg(target_os = "linux")]
     {
@@ -43,6 +77,72 @@ pub(super) fn set_cloexec(fd: BorrowedFd<'_>) -> io::Result<()> {
     set_fdflags(fd, get_fdflags(fd)? | libc::FD_CLOEXEC)?;
     Ok(())
 }
+/// Registers a duplicate of the file descriptor in the Tokio event loop, waiting for it to become readable.
+///
+/// Registering the same file descriptor twice fails with `EEXIST`, so each concurrent waiter needs its own duplicate.
+/// The registration is only meant to be held for the duration of one wait, so that the object being waited on isn't
+/// tied to a runtime.
+#[cfg(feature = "tokio")]
+pub(crate) fn register_readable(fd: BorrowedFd<'_>) -> io::Result<tokio::io::unix::AsyncFd<OwnedFd>> {
+    tokio::io::unix::AsyncFd::with_interest(duplicate_fd(fd)?, tokio::io::Interest::READABLE)
+}
+/// Converts a timeout to milliseconds for `poll()`, rounding up so that the wait doesn't end early. `None` becomes -1,
+/// which waits indefinitely, and timeouts too long to be represented are clamped.
+pub(crate) fn timeout_to_ms(timeout: Option<Duration>) -> c_int {
+    match timeout {
+        Some(t) => c_int::try_from((t.as_nanos() + 999_999) / 1_000_000).unwrap_or(c_int::MAX),
+        None => -1,
+    }
+}
+/// Returns `false` if the timeout expired before the file descriptor became readable. A negative timeout waits
+/// indefinitely. Interrupted waits are resumed with whatever time is left.
+pub(crate) fn poll_readable(fd: BorrowedFd<'_>, timeout_ms: c_int) -> io::Result<bool> {
+    let mut pfd = libc::pollfd {
+        fd: fd.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let deadline = u64::try_from(timeout_ms)
+        .ok()
+        .and_then(|ms| Instant::now().checked_add(Duration::from_millis(ms)));
+    let mut timeout_ms = timeout_ms;
+    loop {
+        let ret = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+        if ret != -1 {
+            return Ok(ret != 0);
+        }
+        let e = io::Error::last_os_error();
+        if e.kind() != io::ErrorKind::Interrupted {
+            return Err(e);
+        }
+        if let Some(deadline) = deadline {
+            timeout_ms = timeout_to_ms(Some(deadline.saturating_duration_since(Instant::now())));
+        }
+    }
+}
+/// Computes the absolute deadline, measured with the realtime clock, that timed waits on POSIX synchronization
+/// primitives expect. Saturates instead of overflowing.
+pub(super) fn realtime_deadline(timeout: std::time::Duration) -> io::Result<libc::timespec> {
+    let mut now = std::mem::MaybeUninit::<libc::timespec>::uninit();
+    let success = unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, now.as_mut_ptr()) != -1 };
+    let now = ok_or_ret_errno!(success => unsafe { now.assume_init() })?;
+    let nanos = now.tv_nsec as u64 + u64::from(timeout.subsec_nanos());
+    let secs = i64::try_from(timeout.as_secs())
+        .ok()
+        .and_then(|s| s.checked_add(now.tv_sec as i64))
+        .and_then(|s| s.checked_add((nanos / 1_000_000_000) as i64))
+        .unwrap_or(i64::MAX);
+    #[allow(clippy::useless_conversion)] // time_t is narrower than i64 on some platforms
+    let tv_sec = secs.try_into().unwrap_or(libc::time_t::MAX);
+    Ok(libc::timespec {
+        tv_sec,
+        tv_nsec: (nanos % 1_000_000_000) as _,
+    })
+}
+pub(super) fn dup2(fd: BorrowedFd<'_>, target: c_int) -> io::Result<()> {
+    let success = unsafe { libc::dup2(fd.as_raw_fd(), target) != -1 };
+    ok_or_ret_errno!(success => ())
+}
 
 #[cfg(uds_ucred)]
 pub(super) fn get_uid(ruid: bool) -> uid_t {
@@ -68,3 +168,52 @@ pub(super) fn get_gid(rgid: bool) -> gid_t {
 pub(super) fn get_pid() -> pid_t {
     unsafe { libc::getpid() }
 }
+
+#[cfg(target_os = "linux")]
+pub(super) fn splice(fd_in: BorrowedFd<'_>, fd_out: BorrowedFd<'_>, len: usize) -> io::Result<usize> {
+    let (success, bytes) = unsafe {
+        let ret = libc::splice(
+            fd_in.as_raw_fd(),
+            std::ptr::null_mut(),
+            fd_out.as_raw_fd(),
+            std::ptr::null_mut(),
+            len,
+            libc::SPLICE_F_MOVE,
+        );
+        (ret >= 0, ret as usize)
+    };
+    ok_or_ret_errno!(success => bytes)
+}
+#[cfg(target_os = "linux")]
+pub(super) fn sendfile(
+    out_fd: BorrowedFd<'_>,
+    in_fd: BorrowedFd<'_>,
+    offset: &mut libc::off_t,
+    len: usize,
+) -> io::Result<usize> {
+    let (success, bytes) = unsafe {
+        let ret = libc::sendfile(out_fd.as_raw_fd(), in_fd.as_raw_fd(), offset, len);
+        (ret >= 0, ret as usize)
+    };
+    ok_or_ret_errno!(success => bytes)
+}
+#[cfg(target_os = "linux")]
+pub(super) fn vmsplice(fd_out: BorrowedFd<'_>, data: &[u8], flags: libc::c_uint) -> io::Result<usize> {
+    let iov = libc::iovec {
+        iov_base: data.as_ptr() as *mut _,
+        iov_len: data.len(),
+    };
+    let (success, bytes) = unsafe {
+        let ret = libc::vmsplice(fd_out.as_raw_fd(), &iov, 1, flags);
+        (ret >= 0, ret as usize)
+    };
+    ok_or_ret_errno!(success => bytes)
+}
+#[cfg(target_os = "linux")]
+pub(super) fn tee(fd_in: BorrowedFd<'_>, fd_out: BorrowedFd<'_>, len: usize) -> io::Result<usize> {
+    let (success, bytes) = unsafe {
+        let ret = libc::tee(fd_in.as_raw_fd(), fd_out.as_raw_fd(), len, 0);
+        (ret >= 0, ret as usize)
+    };
+    ok_or_ret_errno!(success => bytes)
+}
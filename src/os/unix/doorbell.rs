@@ -0,0 +1,160 @@
+use super::{c_wrappers, unixprelude::*};
+use crate::TryClone;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+/// An auto-reset event, which rings while its file descriptor is readable and is silenced by reading from it.
+///
+/// On Linux and Android, this is an eventfd in non-semaphore mode, which is reset to zero by every successful read.
+/// Elsewhere, it's a self-pipe: a few bytes are written into the pipe to ring the doorbell, and the pipe is drained to
+/// silence it.
+pub(crate) struct Doorbell {
+    rx: OwnedFd,
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    tx: OwnedFd,
+}
+impl Doorbell {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        ok_or_ret_errno!(fd != -1 => Self { rx: unsafe {
+            // SAFETY: we just created this file descriptor
+            OwnedFd::from_raw_fd(fd)
+        } })
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn new() -> io::Result<Self> {
+        use crate::unnamed_pipe::PipeOptions;
+        let [rx, tx] = super::unnamed_pipe::create_fds(&PipeOptions::new().nonblocking(true))?;
+        Ok(Self { rx, tx })
+    }
+
+    #[inline]
+    fn tx(&self) -> BorrowedFd<'_> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            self.rx.as_fd()
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            self.tx.as_fd()
+        }
+    }
+
+    pub fn ring(&self) -> io::Result<()> {
+        // eventfds only accept 8-byte writes, and a pipe is fine with them too.
+        let one = 1_u64.to_ne_bytes();
+        let ret = unsafe { libc::write(self.tx().as_raw_fd(), one.as_ptr().cast(), one.len()) };
+        if ret == -1 {
+            let e = io::Error::last_os_error();
+            // A full pipe or an eventfd counter about to overflow means that the doorbell is already ringing.
+            if e.kind() != io::ErrorKind::WouldBlock {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    pub fn wait(&self) -> io::Result<()> {
+        while !self.wait_timeout(None)? {}
+        Ok(())
+    }
+    /// Returns `false` if the timeout ran out before the doorbell rang.
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        let timeout_ms = c_wrappers::timeout_to_ms(timeout);
+        loop {
+            if !c_wrappers::poll_readable(self.rx.as_fd(), timeout_ms)? {
+                return Ok(false);
+            }
+            match self.try_consume() {
+                // Someone else with a duplicate of the file descriptor got there first.
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                els => return els.map(|()| true),
+            }
+        }
+    }
+    #[cfg(feature = "tokio")]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        let registration = c_wrappers::register_readable(self.rx.as_fd())?;
+        loop {
+            let mut guard = registration.readable().await?;
+            if let Ok(result) = guard.try_io(|_| self.try_consume()) {
+                return result;
+            }
+        }
+    }
+    #[cfg(feature = "async-io")]
+    pub async fn wait_async_io(&self) -> io::Result<()> {
+        // Same as above, a duplicate of the file descriptor is registered in the reactor for the duration of the wait.
+        let registration = async_io::Async::new_nonblocking(c_wrappers::duplicate_fd(self.rx.as_fd())?)?;
+        loop {
+            registration.readable().await?;
+            match self.try_consume() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                els => return els,
+            }
+        }
+    }
+    /// Silences the doorbell, failing with `WouldBlock` if it wasn't ringing.
+    fn try_consume(&self) -> io::Result<()> {
+        // A single read resets an eventfd, while a pipe may take several to drain.
+        let mut buf = [0_u8; 64];
+        let mut consumed = false;
+        loop {
+            let ret = unsafe { libc::read(self.rx.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+            if ret == -1 {
+                let e = io::Error::last_os_error();
+                match e.kind() {
+                    io::ErrorKind::Interrupted => continue,
+                    io::ErrorKind::WouldBlock if consumed => return Ok(()),
+                    _ => return Err(e),
+                }
+            }
+            consumed = true;
+            if cfg!(any(target_os = "linux", target_os = "android")) {
+                return Ok(());
+            }
+        }
+    }
+}
+impl TryClone for Doorbell {
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            rx: self.rx.try_clone()?,
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            tx: self.tx.try_clone()?,
+        })
+    }
+}
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl AsFd for Doorbell {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.rx.as_fd()
+    }
+}
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl From<Doorbell> for OwnedFd {
+    #[inline]
+    fn from(doorbell: Doorbell) -> Self {
+        doorbell.rx
+    }
+}
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl From<OwnedFd> for Doorbell {
+    #[inline]
+    fn from(rx: OwnedFd) -> Self {
+        Self { rx }
+    }
+}
+impl Debug for Doorbell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("Doorbell");
+        dbg.field("rx", &self.rx.as_raw_fd());
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        dbg.field("tx", &self.tx.as_raw_fd());
+        dbg.finish()
+    }
+}
@@ -0,0 +1,136 @@
+use super::{c_wrappers, unixprelude::*};
+use crate::TryClone;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+/// A manual-reset event, which is set while its file descriptor is readable.
+///
+/// On Linux and Android, this is an eventfd, which is both written to and read from. Elsewhere, it's a self-pipe: a
+/// byte is written into the pipe to set the event, and the pipe is drained to reset it.
+pub(crate) struct Event {
+    rx: OwnedFd,
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    tx: OwnedFd,
+}
+impl Event {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        ok_or_ret_errno!(fd != -1 => Self { rx: unsafe {
+            // SAFETY: we just created this file descriptor
+            OwnedFd::from_raw_fd(fd)
+        } })
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn new() -> io::Result<Self> {
+        use crate::unnamed_pipe::PipeOptions;
+        let [rx, tx] = super::unnamed_pipe::create_fds(&PipeOptions::new().nonblocking(true))?;
+        Ok(Self { rx, tx })
+    }
+
+    #[inline]
+    fn tx(&self) -> BorrowedFd<'_> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            self.rx.as_fd()
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            self.tx.as_fd()
+        }
+    }
+
+    pub fn set(&self) -> io::Result<()> {
+        // eventfds only accept 8-byte writes, and a pipe is fine with them too.
+        let one = 1_u64.to_ne_bytes();
+        let ret = unsafe { libc::write(self.tx().as_raw_fd(), one.as_ptr().cast(), one.len()) };
+        if ret == -1 {
+            let e = io::Error::last_os_error();
+            // A full pipe or an eventfd counter about to overflow means that the event is already set.
+            if e.kind() != io::ErrorKind::WouldBlock {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    pub fn reset(&self) -> io::Result<()> {
+        // A single read resets an eventfd, while a pipe may take several to drain.
+        let mut buf = [0_u8; 64];
+        loop {
+            let ret = unsafe { libc::read(self.rx.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+            if ret == -1 {
+                let e = io::Error::last_os_error();
+                match e.kind() {
+                    io::ErrorKind::WouldBlock => return Ok(()),
+                    io::ErrorKind::Interrupted => continue,
+                    _ => return Err(e),
+                }
+            }
+        }
+    }
+    pub fn is_set(&self) -> io::Result<bool> {
+        c_wrappers::poll_readable(self.rx.as_fd(), 0)
+    }
+    pub fn wait(&self) -> io::Result<()> {
+        while !self.wait_timeout(None)? {}
+        Ok(())
+    }
+    /// Returns `false` if the timeout ran out before the event was set.
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        c_wrappers::poll_readable(self.rx.as_fd(), c_wrappers::timeout_to_ms(timeout))
+    }
+    #[cfg(feature = "tokio")]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        let registration = c_wrappers::register_readable(self.rx.as_fd())?;
+        loop {
+            let mut guard = registration.readable().await?;
+            // Someone might have reset the event between it being set and the runtime noticing.
+            if self.is_set()? {
+                return Ok(());
+            }
+            guard.clear_ready();
+        }
+    }
+}
+impl TryClone for Event {
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            rx: self.rx.try_clone()?,
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            tx: self.tx.try_clone()?,
+        })
+    }
+}
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl AsFd for Event {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.rx.as_fd()
+    }
+}
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl From<Event> for OwnedFd {
+    #[inline]
+    fn from(event: Event) -> Self {
+        event.rx
+    }
+}
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl From<OwnedFd> for Event {
+    #[inline]
+    fn from(rx: OwnedFd) -> Self {
+        Self { rx }
+    }
+}
+impl Debug for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("Event");
+        dbg.field("rx", &self.rx.as_raw_fd());
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        dbg.field("tx", &self.tx.as_raw_fd());
+        dbg.finish()
+    }
+}
@@ -3,22 +3,34 @@ use crate::TryClone;
 use std::{
     fmt::{self, Debug, Formatter},
     io::{self, prelude::*, IoSlice, IoSliceMut},
+    mem::MaybeUninit,
     os::fd::OwnedFd,
 };
 use to_method::To;
 
 #[repr(transparent)]
 pub(super) struct FdOps(pub(super) OwnedFd);
-impl Read for &FdOps {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+impl FdOps {
+    /// Reads into a possibly uninitialized buffer, returning how many bytes were written into its beginning.
+    pub(super) fn read_uninit(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
         let length_to_read = buf.len();
 
         let (success, bytes_read) = unsafe {
+            // SAFETY: the kernel never reads from the buffer, so it's fine for it to be uninitialized
             let size_or_err = libc::read(self.0.as_raw_fd(), buf.as_mut_ptr().cast(), length_to_read);
             (size_or_err >= 0, size_or_err as usize)
         };
         ok_or_ret_errno!(success => bytes_read)
     }
+}
+impl Read for &FdOps {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_uninit(crate::weaken_buf_init_mut(buf))
+    }
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        crate::read_into_cursor(cursor, |buf| self.read_uninit(buf))
+    }
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
         let num_bufs = bufs.len().try_to::<c_int>().unwrap_or(c_int::MAX);
 
@@ -62,7 +74,14 @@ impl Debug for FdOps {
     }
 }
 
-// No As/Into because those can be easily done with basic method forwarding.
+// No As/Into because those can be easily done with basic method forwarding. AsRawFd is the exception, since Tokio's
+// AsyncFd requires its contents to implement it.
+impl AsRawFd for FdOps {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
 impl FromRawFd for FdOps {
     #[inline]
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
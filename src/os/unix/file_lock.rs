@@ -0,0 +1,89 @@
+use super::unixprelude::*;
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs::File,
+    io,
+};
+
+/// A file locked with `flock`, or with `fcntl` record locks on Solaris, which lacks `flock`.
+pub(crate) struct FileLock(File);
+impl FileLock {
+    #[inline]
+    pub fn file(&self) -> &File {
+        &self.0
+    }
+    #[inline]
+    pub fn into_file(self) -> File {
+        self.0
+    }
+
+    #[cfg(not(target_os = "solaris"))]
+    pub fn lock(&self, exclusive: bool, nonblocking: bool) -> io::Result<()> {
+        let mut op = if exclusive { libc::LOCK_EX } else { libc::LOCK_SH };
+        if nonblocking {
+            op |= libc::LOCK_NB;
+        }
+        loop {
+            if unsafe { libc::flock(self.0.as_raw_fd(), op) } != -1 {
+                return Ok(());
+            }
+            // EWOULDBLOCK conveniently maps to WouldBlock.
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+    }
+    #[cfg(not(target_os = "solaris"))]
+    pub fn unlock(&self) {
+        unsafe { libc::flock(self.0.as_raw_fd(), libc::LOCK_UN) };
+    }
+
+    /// Record locks belong to the process rather than the open file description, and so two `FileLock`s in the same
+    /// process don't exclude each other, unlike elsewhere.
+    #[cfg(target_os = "solaris")]
+    pub fn lock(&self, exclusive: bool, nonblocking: bool) -> io::Result<()> {
+        let ty = if exclusive { libc::F_WRLCK } else { libc::F_RDLCK };
+        let cmd = if nonblocking { libc::F_SETLK } else { libc::F_SETLKW };
+        loop {
+            match self.fcntl_lock(cmd, ty) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                // F_SETLK reports conflicts with either EAGAIN or EACCES.
+                Err(e) if e.raw_os_error() == Some(libc::EACCES) => {
+                    return Err(io::Error::from(io::ErrorKind::WouldBlock))
+                }
+                els => return els,
+            }
+        }
+    }
+    #[cfg(target_os = "solaris")]
+    pub fn unlock(&self) {
+        let _ = self.fcntl_lock(libc::F_SETLK, libc::F_UNLCK);
+    }
+    #[cfg(target_os = "solaris")]
+    fn fcntl_lock(&self, cmd: c_int, ty: c_int) -> io::Result<()> {
+        let mut fl: libc::flock = unsafe { std::mem::zeroed() };
+        fl.l_type = ty as _;
+        fl.l_whence = libc::SEEK_SET as _;
+        // Zero start and length cover the whole file, however large it grows.
+        let success = unsafe { libc::fcntl(self.0.as_raw_fd(), cmd, &fl) != -1 };
+        ok_or_ret_errno!(success => ())
+    }
+}
+impl From<File> for FileLock {
+    #[inline]
+    fn from(file: File) -> Self {
+        Self(file)
+    }
+}
+impl AsFd for FileLock {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+impl Debug for FileLock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FileLock").field(&self.0.as_raw_fd()).finish()
+    }
+}
@@ -0,0 +1,71 @@
+//! Adopting sockets created by launchd for on-demand activation.
+//!
+//! A launchd job can declare sockets in the `Sockets` dictionary of its property list, which launchd then creates and
+//! listens on itself, starting the job once a client connects. The job receives those sockets with
+//! [`activate_socket()`], passing the key of the socket in the `Sockets` dictionary as the name; the
+//! [`UdStreamListener::from_launchd()`](super::udsocket::UdStreamListener::from_launchd) and
+//! [`LocalSocketListener::from_launchd()`](crate::local_socket::LocalSocketListener::from_launchd) shorthands adopt a
+//! single activated socket as a listener directly.
+//!
+//! This module is only available on macOS. On other platforms, it's absent and thus any usage of it will result in a
+//! compile-time error.
+
+use super::{c_wrappers, unixprelude::*};
+use libc::size_t;
+use std::{ffi::CString, io, ptr, slice};
+
+extern "C" {
+    // From <launch.h>, which is part of libSystem.
+    fn launch_activate_socket(name: *const libc::c_char, fds: *mut *mut c_int, cnt: *mut size_t) -> c_int;
+}
+
+/// Retrieves the sockets which launchd created for the calling job under the given name.
+///
+/// An entry in the `Sockets` dictionary can describe several sockets, which is why there may be more than one. The file
+/// descriptors are set to be closed on `exec`. Each name can only be activated once per process: further attempts fail
+/// with `EALREADY`. If the process wasn't started by launchd, `ESRCH` is returned, and `ENOENT` means that the job has
+/// no sockets under that name.
+///
+/// # System calls
+/// - `launch_activate_socket`
+/// - `fcntl`
+pub fn activate_socket(name: &str) -> io::Result<Vec<OwnedFd>> {
+    let name = CString::new(name)?;
+    let mut fds: *mut c_int = ptr::null_mut();
+    let mut cnt: size_t = 0;
+    // The error code is returned rather than stored in errno.
+    match unsafe { launch_activate_socket(name.as_ptr(), &mut fds, &mut cnt) } {
+        0 => {}
+        e => return Err(io::Error::from_raw_os_error(e)),
+    }
+    let owned = if fds.is_null() {
+        Vec::new()
+    } else {
+        let owned = unsafe {
+            // SAFETY: launchd hands over ownership of cnt file descriptors in a malloc()'d array
+            slice::from_raw_parts(fds, cnt)
+                .iter()
+                .map(|&fd| OwnedFd::from_raw_fd(fd))
+                .collect::<Vec<_>>()
+        };
+        unsafe { libc::free(fds.cast()) };
+        owned
+    };
+    for fd in &owned {
+        c_wrappers::set_cloexec(fd.as_fd())?;
+    }
+    Ok(owned)
+}
+
+/// Activates the sockets under the given name, expecting exactly one, which is what a listening Unix domain socket
+/// entry produces.
+pub(crate) fn activate_listener(name: &str) -> io::Result<OwnedFd> {
+    let mut fds = activate_socket(name)?;
+    if fds.len() != 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected launchd to provide one socket, got {}", fds.len()),
+        ));
+    }
+    Ok(fds.remove(0))
+}
@@ -0,0 +1,86 @@
+use super::local_socket_name_to_ud_socket_path;
+use crate::{
+    framing::{LengthPrefix, MessageReader, MessageWriter},
+    local_socket::ToLocalSocketName,
+    os::unix::udsocket::{is_unsupported, Seqpacket, SeqpacketListener, UdStream, UdStreamListener},
+    TryClone,
+};
+use std::io::{self, BufReader};
+
+/// Uses a `SOCK_SEQPACKET` socket where the system has them, and a stream socket with a length prefix on every message
+/// otherwise.
+#[derive(Debug)]
+pub enum FramedLocalSocketListener {
+    Native(SeqpacketListener),
+    Emulated(UdStreamListener),
+}
+impl FramedLocalSocketListener {
+    pub fn bind<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        let path = local_socket_name_to_ud_socket_path(name.to_local_socket_name()?)?;
+        match SeqpacketListener::bind(path.borrow()) {
+            Err(e) if is_unsupported(&e) => UdStreamListener::bind(path).map(Self::Emulated),
+            result => result.map(Self::Native),
+        }
+    }
+    pub fn accept(&self) -> io::Result<FramedLocalSocket> {
+        match self {
+            Self::Native(listener) => listener.accept().map(FramedLocalSocket::native),
+            Self::Emulated(listener) => FramedLocalSocket::emulated(listener.accept()?),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FramedLocalSocket {
+    Native {
+        socket: Seqpacket,
+        buf: Vec<u8>,
+    },
+    Emulated {
+        // Buffered to spare a system call for the length prefix, which is fine since the reader owns its half.
+        reader: MessageReader<BufReader<UdStream>>,
+        writer: MessageWriter<UdStream>,
+    },
+}
+impl FramedLocalSocket {
+    /// Falls back to a stream socket both if the system doesn't have `SOCK_SEQPACKET` sockets and if the server is a
+    /// stream socket, which is the case when the server's system doesn't have them either.
+    pub fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        let path = local_socket_name_to_ud_socket_path(name.to_local_socket_name()?)?;
+        match Seqpacket::connect(path.borrow()) {
+            Err(e) if is_unsupported(&e) => Self::emulated(UdStream::connect(path)?),
+            result => result.map(Self::native),
+        }
+    }
+    fn native(socket: Seqpacket) -> Self {
+        Self::Native {
+            socket,
+            buf: Vec::new(),
+        }
+    }
+    fn emulated(stream: UdStream) -> io::Result<Self> {
+        let max_size = crate::local_socket::FramedLocalSocket::MAX_MSG_SIZE;
+        let reader = MessageReader::new(BufReader::new(stream.try_clone()?))
+            .with_prefix(LengthPrefix::U32)
+            .with_max_size(max_size);
+        let writer = MessageWriter::new(stream)
+            .with_prefix(LengthPrefix::U32)
+            .with_max_size(max_size);
+        Ok(Self::Emulated { reader, writer })
+    }
+    pub fn is_native(&self) -> bool {
+        matches!(self, Self::Native { .. })
+    }
+    pub fn send_msg(&mut self, msg: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Native { socket, .. } => socket.send(msg),
+            Self::Emulated { writer, .. } => writer.write_message(msg),
+        }
+    }
+    pub fn recv_msg(&mut self) -> io::Result<Option<&[u8]>> {
+        match self {
+            Self::Native { socket, buf } => Ok(socket.recv(buf)?.then_some(&buf[..])),
+            Self::Emulated { reader, .. } => reader.read_message(),
+        }
+    }
+}
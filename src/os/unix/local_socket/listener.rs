@@ -15,9 +15,12 @@ impl LocalSocketListener {
         let inner = UdStreamListener::bind(path)?;
         Ok(Self(inner))
     }
+    #[cfg(target_os = "macos")]
+    pub fn from_launchd(name: &str) -> io::Result<Self> {
+        UdStreamListener::from_launchd(name).map(Self)
+    }
     pub fn accept(&self) -> io::Result<LocalSocketStream> {
-        let inner = self.0.accept()?;
-        Ok(LocalSocketStream(inner))
+        self.0.accept().map(LocalSocketStream::from)
     }
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
         self.0.set_nonblocking(nonblocking)
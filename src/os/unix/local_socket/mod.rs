@@ -3,6 +3,9 @@
 #[cfg(feature = "tokio")]
 pub mod tokio;
 
+mod framed;
+pub use framed::*;
+
 mod listener;
 pub use listener::*;
 
@@ -11,7 +14,7 @@ pub use stream::*;
 
 use {
     crate::{
-        local_socket::{LocalSocketName, NameTypeSupport},
+        local_socket::{auth::PeerCredentials, LocalSocketName, NameTypeSupport},
         os::unix::udsocket::UdSocketPath,
     },
     std::{
@@ -22,6 +25,16 @@ use {
     },
 };
 
+pub fn current_credentials() -> io::Result<PeerCredentials> {
+    Ok(unsafe {
+        PeerCredentials {
+            pid: Some(libc::getpid() as u32),
+            uid: Some(libc::geteuid()),
+            gid: Some(libc::getegid()),
+        }
+    })
+}
+
 fn local_socket_name_to_ud_socket_path(name: LocalSocketName<'_>) -> io::Result<UdSocketPath<'_>> {
     fn cow_osstr_to_cstr(osstr: Cow<'_, OsStr>) -> io::Result<Cow<'_, CStr>> {
         match osstr {
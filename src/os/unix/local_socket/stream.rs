@@ -1,51 +1,245 @@
 use {
     super::local_socket_name_to_ud_socket_path,
     crate::{
-        local_socket::ToLocalSocketName,
-        os::unix::udsocket::{UdSocket, UdStream},
+        local_socket::{auth::PeerCredentials, HandleToken, ToLocalSocketName},
+        os::unix::{
+            c_wrappers,
+            udsocket::{
+                cmsg::{ancillary::file_descriptors::FileDescriptors, Cmsg, CmsgMut, CmsgMutExt, CmsgRef, CmsgVecBuf},
+                ReadAncillary, ReadAncillarySuccess, UdSocket, UdStream, WriteAncillary,
+            },
+        },
     },
+    libc::c_int,
     std::{
+        collections::BTreeMap,
         fmt::{self, Debug, Formatter},
         io::{self, prelude::*, IoSlice, IoSliceMut},
-        os::unix::io::AsRawFd,
+        mem::size_of,
+        net::Shutdown,
+        os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd},
+        time::Duration,
     },
 };
 
-pub struct LocalSocketStream(pub(super) UdStream);
+/// The most file descriptors sent along with a single write, and thus the most that a single read has room for.
+const MAX_FDS_PER_WRITE: usize = 64;
+
+/// Whether `recvmsg` supports `MSG_CMSG_CLOEXEC`, which sets the close-on-exec flag on received file descriptors
+/// atomically. Elsewhere, they're briefly inheritable by processes spawned by other threads.
+const RECVMSG_CLOEXEC: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+));
+
+pub struct LocalSocketStream {
+    inner: UdStream,
+    /// File descriptors queued by `send_handle()`, which go along with the next write.
+    outgoing: Vec<OwnedFd>,
+    /// File descriptors received and not yet taken, keyed by the token they were sent under.
+    incoming: BTreeMap<u64, OwnedFd>,
+    /// The most file descriptors kept in `incoming`. Zero means that reads don't collect file descriptors at all.
+    max_incoming: usize,
+    sent: u64,
+    received: u64,
+    abuf: CmsgVecBuf,
+}
 impl LocalSocketStream {
     pub fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
         let path = local_socket_name_to_ud_socket_path(name.to_local_socket_name()?)?;
-        let inner = UdStream::connect(path)?;
-        Ok(Self(inner))
+        UdStream::connect(path).map(Self::from)
+    }
+    pub fn from_stdin() -> io::Result<Self> {
+        UdStream::from_stdin().map(Self::from)
+    }
+    pub fn from_stdio() -> io::Result<Self> {
+        UdStream::from_stdio().map(Self::from)
     }
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
-        self.0.set_nonblocking(nonblocking)
+        self.inner.set_nonblocking(nonblocking)
+    }
+    pub fn shutdown_write(&self) -> io::Result<()> {
+        self.inner.shutdown(Shutdown::Write)
+    }
+    /// Returns `false` if the timeout ran out before the stream became readable.
+    pub fn wait_readable(&self, timeout: Duration) -> io::Result<bool> {
+        c_wrappers::poll_readable(self.inner.as_fd(), c_wrappers::timeout_to_ms(Some(timeout)))
+    }
+
+    pub fn peer_credentials(&self) -> io::Result<PeerCredentials> {
+        #[cfg(any(uds_ucred, uds_xucred))]
+        {
+            let creds = self.inner.get_peer_credentials()?;
+            Ok(PeerCredentials {
+                pid: creds.pid().and_then(|pid| u32::try_from(pid).ok()),
+                uid: creds.euid(),
+                gid: creds.egid(),
+            })
+        }
+        #[cfg(not(any(uds_ucred, uds_xucred)))]
+        {
+            Ok(PeerCredentials::default())
+        }
+    }
+
+    pub fn send_handle(&mut self, handle: BorrowedFd<'_>) -> io::Result<HandleToken> {
+        // Duplicated so that the caller doesn't have to keep it open until the next write.
+        self.outgoing.push(handle.try_clone_to_owned()?);
+        let token = HandleToken::from_raw(self.sent);
+        self.sent += 1;
+        Ok(token)
+    }
+    pub fn recv_handle(&mut self, token: HandleToken) -> io::Result<OwnedFd> {
+        self.incoming.remove(&token.into_raw()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no file descriptor has been received under this token yet, or it has been taken or discarded already",
+            )
+        })
+    }
+    pub fn set_max_pending_handles(&mut self, max: usize) {
+        if max != 0 && self.abuf.as_bytes().is_empty() {
+            self.abuf = CmsgVecBuf::new(Cmsg::cmsg_len_for_payload_size(
+                (MAX_FDS_PER_WRITE * size_of::<c_int>()) as _,
+            ));
+        }
+        self.max_incoming = max;
+    }
+
+    /// File descriptors which come along with the data are collected like with any other read.
+    pub fn read_until(&mut self, delim: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        crate::delimited::read_until_with(
+            self,
+            delim,
+            buf,
+            |s, b| s.inner.peek(b),
+            |s, b| s.read_with_fds(&mut [IoSliceMut::new(b)]),
+        )
+    }
+
+    /// Writes with the queued file descriptors attached, dropping the ones which made it.
+    fn write_with_fds(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        if self.outgoing.is_empty() {
+            return self.inner.write_vectored(bufs);
+        }
+        let count = self.outgoing.len().min(MAX_FDS_PER_WRITE);
+        let fds = self.outgoing[..count].iter().map(AsFd::as_fd).collect::<Vec<_>>();
+        let mut abuf = CmsgVecBuf::new(Cmsg::cmsg_len_for_payload_size((count * size_of::<c_int>()) as _));
+        abuf.add_message(&FileDescriptors::new(&fds));
+        let written = self.inner.write_ancillary_vectored(bufs, abuf.as_ref())?;
+        // The ancillary data goes along with the first byte, so nothing is sent if nothing is written.
+        if written != 0 {
+            self.outgoing.drain(..count);
+        }
+        Ok(written)
+    }
+    /// Reads while collecting the file descriptors that come along with the data, if enabled.
+    fn read_with_fds(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        if self.max_incoming == 0 {
+            // Without room for ancillary data, any file descriptors that were sent are closed by the system.
+            return self.inner.read_vectored(bufs);
+        }
+        self.read_with_fds_by(|inner, abuf| inner.read_ancillary_vectored(bufs, abuf))
+    }
+    fn read_with_fds_by(
+        &mut self,
+        read: impl FnOnce(&mut UdStream, &mut CmsgVecBuf) -> io::Result<ReadAncillarySuccess>,
+    ) -> io::Result<usize> {
+        self.abuf.clear();
+        let success = read(&mut self.inner, &mut self.abuf)?;
+        let truncated = self.abuf.is_truncated();
+        let abuf: CmsgRef<'_> = self.abuf.as_ref();
+        for fds in abuf.decode::<FileDescriptors<'_>>() {
+            let fds = fds.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            for fd in fds.into_owned_fds()? {
+                if !RECVMSG_CLOEXEC {
+                    c_wrappers::set_cloexec(fd.as_fd())?;
+                }
+                // File descriptors beyond the limit are closed, but still count towards the tokens of later ones.
+                if self.incoming.len() < self.max_incoming {
+                    self.incoming.insert(self.received, fd);
+                }
+                self.received += 1;
+            }
+        }
+        if truncated {
+            // Some file descriptors got dropped by the system, and the tokens no longer match up.
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "more file descriptors were received at once than there was room for",
+            ));
+        }
+        Ok(success.main)
+    }
+}
+impl From<UdStream> for LocalSocketStream {
+    fn from(inner: UdStream) -> Self {
+        Self {
+            inner,
+            outgoing: Vec::new(),
+            incoming: BTreeMap::new(),
+            max_incoming: 0,
+            sent: 0,
+            received: 0,
+            // Only allocated once file descriptors are to be collected.
+            abuf: CmsgVecBuf::default(),
+        }
     }
 }
 impl Read for LocalSocketStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.0.read(buf)
+        self.read_with_fds(&mut [IoSliceMut::new(buf)])
     }
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
-        self.0.read_vectored(bufs)
+        self.read_with_fds(bufs)
+    }
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        if self.max_incoming == 0 {
+            return self.inner.read_buf(cursor);
+        }
+        crate::read_into_cursor(cursor, |buf| {
+            self.read_with_fds_by(|inner, abuf| inner.read_ancillary_uninit(buf, abuf))
+        })
     }
 }
 impl Write for LocalSocketStream {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.0.write(buf)
+        self.write_with_fds(&[IoSlice::new(buf)])
     }
     fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
-        self.0.write_vectored(bufs)
+        self.write_with_fds(bufs)
     }
     fn flush(&mut self) -> io::Result<()> {
-        self.0.flush()
+        self.inner.flush()
     }
 }
 impl Debug for LocalSocketStream {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("LocalSocketStream")
-            .field("fd", &self.0.as_raw_fd())
+            .field("fd", &self.inner.as_raw_fd())
             .finish()
     }
 }
-forward_handle!(unix: LocalSocketStream);
+impl AsFd for LocalSocketStream {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+impl From<LocalSocketStream> for OwnedFd {
+    #[inline]
+    fn from(stream: LocalSocketStream) -> Self {
+        stream.inner.into()
+    }
+}
+impl From<OwnedFd> for LocalSocketStream {
+    #[inline]
+    fn from(fd: OwnedFd) -> Self {
+        UdStream::from(fd).into()
+    }
+}
@@ -15,6 +15,10 @@ impl LocalSocketListener {
         let inner = UdStreamListener::bind(path)?;
         Ok(Self(inner))
     }
+    #[cfg(target_os = "macos")]
+    pub fn from_launchd(name: &str) -> io::Result<Self> {
+        UdStreamListener::from_launchd(name).map(Self)
+    }
     pub async fn accept(&self) -> io::Result<LocalSocketStream> {
         let inner = self.0.accept().await?;
         Ok(LocalSocketStream(inner))
@@ -5,15 +5,20 @@ mod write_half;
 pub use write_half::*;
 
 use super::super::local_socket_name_to_ud_socket_path;
-use crate::{local_socket::ToLocalSocketName, os::unix::udsocket::tokio::UdStream};
+use crate::{
+    local_socket::ToLocalSocketName,
+    os::unix::udsocket::{tokio::UdStream, UdSocket},
+};
 use futures_io::{AsyncRead, AsyncWrite};
 use std::{
     fmt::{self, Debug, Formatter},
     io::{self, IoSlice, IoSliceMut},
+    net::Shutdown,
     os::unix::io::AsRawFd,
     pin::Pin,
     task::{Context, Poll},
 };
+use tokio::io::{AsyncRead as TokioAsyncRead, ReadBuf as TokioReadBuf};
 
 pub struct LocalSocketStream(pub(super) UdStream);
 impl LocalSocketStream {
@@ -25,6 +30,12 @@ impl LocalSocketStream {
         let (r, w) = self.0.split();
         (ReadHalf(r), WriteHalf(w))
     }
+    pub fn shutdown_write(&self) -> io::Result<()> {
+        self.0.shutdown(Shutdown::Write)
+    }
+    pub async fn read_until(&mut self, delim: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.0.read_until(delim, buf).await
+    }
     fn pinproj(&mut self) -> Pin<&mut UdStream> {
         Pin::new(&mut self.0)
     }
@@ -38,7 +49,7 @@ impl From<UdStream> for LocalSocketStream {
 impl AsyncRead for LocalSocketStream {
     #[inline]
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> std::task::Poll<io::Result<usize>> {
-        self.pinproj().poll_read(cx, buf)
+        AsyncRead::poll_read(self.pinproj(), cx, buf)
     }
     #[inline]
     fn poll_read_vectored(
@@ -49,6 +60,12 @@ impl AsyncRead for LocalSocketStream {
         self.pinproj().poll_read_vectored(cx, bufs)
     }
 }
+impl TokioAsyncRead for LocalSocketStream {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        TokioAsyncRead::poll_read(self.pinproj(), cx, buf)
+    }
+}
 impl AsyncWrite for LocalSocketStream {
     #[inline]
     fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
@@ -7,6 +7,7 @@ use {
         pin::Pin,
         task::{Context, Poll},
     },
+    tokio::io::{AsyncRead as TokioAsyncRead, ReadBuf as TokioReadBuf},
 };
 
 pub struct ReadHalf(pub(super) ReadHalfImpl);
@@ -19,7 +20,7 @@ impl ReadHalf {
 impl AsyncRead for ReadHalf {
     #[inline]
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> std::task::Poll<io::Result<usize>> {
-        self.pinproj().poll_read(cx, buf)
+        AsyncRead::poll_read(self.pinproj(), cx, buf)
     }
     #[inline]
     fn poll_read_vectored(
@@ -30,6 +31,12 @@ impl AsyncRead for ReadHalf {
         self.pinproj().poll_read_vectored(cx, bufs)
     }
 }
+impl TokioAsyncRead for ReadHalf {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        TokioAsyncRead::poll_read(self.pinproj(), cx, buf)
+    }
+}
 impl Debug for ReadHalf {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_tuple("local_socket::ReadHalf").field(&self.0).finish()
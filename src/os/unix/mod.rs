@@ -13,6 +13,17 @@
 //! UDP-like interface) and transferring file descriptor ownership.
 //!
 //! Unix domain sockets are not available on ARM Newlib, but are supported on all other Unix-like systems.
+//!
+//! ## Signals
+//! Small integers sent to a process, interrupting it. Realtime signals can additionally carry a word-sized payload and
+//! are queued rather than coalesced. The [`signal`] module receives them through a self-pipe rather than running code
+//! in signal handlers.
+//!
+//! Signals are available on Linux and Android.
+//!
+//! ## launchd socket activation
+//! On macOS, daemons started on demand by launchd receive their listening sockets from it; the `launchd` module
+//! adopts them into this crate's listener types.
 
 pub(crate) mod imports;
 
@@ -22,11 +33,38 @@ use fdops::*;
 
 pub mod fifo_file;
 
-mod c_wrappers;
+pub(crate) mod c_wrappers;
 
 pub mod udsocket;
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(any(target_os = "linux", target_os = "android"))))]
+pub mod signal;
+
+#[cfg(target_os = "macos")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "macos")))]
+pub mod launchd;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) mod doorbell;
+pub(crate) mod event;
+pub(crate) mod file_lock;
 pub(crate) mod local_socket;
+pub(crate) mod named_condvar;
+pub(crate) mod named_event;
+pub(crate) mod named_mutex;
+pub(crate) mod named_rwlock;
+pub(crate) mod named_semaphore;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos"
+))]
+pub(crate) mod park;
+pub(crate) mod peer_watcher;
+pub(crate) mod shared_memory;
+pub(crate) mod spawn;
 pub(crate) mod unnamed_pipe;
 
 mod unixprelude {
@@ -0,0 +1,132 @@
+use super::{
+    named_mutex::{lock_result, NamedMutex},
+    shared_memory::SharedMemory,
+};
+use crate::sync::LockResult;
+use libc::pthread_cond_t;
+use std::{
+    cell::UnsafeCell,
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::{size_of, MaybeUninit},
+    sync::atomic::{
+        AtomicU32,
+        Ordering::{Acquire, Release},
+    },
+    thread,
+    time::Duration,
+};
+
+const READY: u32 = 0x636e6476;
+
+/// The contents of the shared memory object backing the condition variable.
+#[repr(C)]
+struct Shared {
+    /// Set to `READY` once the condition variable is initialized.
+    state: AtomicU32,
+    cond: UnsafeCell<pthread_cond_t>,
+}
+
+/// A process-shared pthread condition variable in a shared memory object of its own.
+pub(crate) struct NamedCondvar {
+    shm: SharedMemory,
+}
+impl NamedCondvar {
+    pub fn create(name: &OsStr, keep_drop_guard: bool) -> io::Result<Self> {
+        // Same as with mutexes, the object is zeroed until the condition variable is initialized.
+        let shm = SharedMemory::create(name, size_of::<Shared>(), keep_drop_guard)?;
+        let condvar = Self { shm };
+        unsafe {
+            // SAFETY: nobody else can use the condition variable until it's marked as ready
+            init_cond(condvar.raw())?;
+        }
+        condvar.shared().state.store(READY, Release);
+        Ok(condvar)
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let shm = SharedMemory::open(name)?;
+        if shm.len() < size_of::<Shared>() {
+            return Err(not_a_condvar());
+        }
+        let condvar = Self { shm };
+        for _ in 0..1000 {
+            if condvar.shared().state.load(Acquire) == READY {
+                return Ok(condvar);
+            }
+            thread::yield_now();
+        }
+        Err(not_a_condvar())
+    }
+
+    #[inline]
+    fn shared(&self) -> &Shared {
+        // SAFETY: as with mutexes
+        unsafe { &*self.shm.as_ptr().cast::<Shared>() }
+    }
+    #[inline]
+    fn raw(&self) -> *mut pthread_cond_t {
+        self.shared().cond.get()
+    }
+
+    /// Must be called with `mutex` locked. On error, the lock is released.
+    pub fn wait(&self, mutex: &NamedMutex) -> io::Result<LockResult<()>> {
+        let ret = unsafe { libc::pthread_cond_wait(self.raw(), mutex.raw()) };
+        relock_result(ret, mutex)
+    }
+    /// Must be called with `mutex` locked. On error, the lock is released. The flag is `true` if the timeout ran out.
+    pub fn wait_timeout(&self, mutex: &NamedMutex, timeout: Duration) -> io::Result<(LockResult<()>, bool)> {
+        let deadline = super::c_wrappers::realtime_deadline(timeout).map_err(|e| {
+            mutex.unlock();
+            e
+        })?;
+        match unsafe { libc::pthread_cond_timedwait(self.raw(), mutex.raw(), &deadline) } {
+            libc::ETIMEDOUT => Ok((LockResult::Acquired(()), true)),
+            ret => relock_result(ret, mutex).map(|r| (r, false)),
+        }
+    }
+    pub fn notify_one(&self) -> io::Result<()> {
+        check(unsafe { libc::pthread_cond_signal(self.raw()) })
+    }
+    pub fn notify_all(&self) -> io::Result<()> {
+        check(unsafe { libc::pthread_cond_broadcast(self.raw()) })
+    }
+}
+impl Debug for NamedCondvar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedCondvar").field("shm", &self.shm).finish()
+    }
+}
+
+/// Interprets the return value of a wait, after which the mutex is held unless the wait failed outright.
+fn relock_result(ret: libc::c_int, mutex: &NamedMutex) -> io::Result<LockResult<()>> {
+    lock_result(ret).map_err(|e| {
+        mutex.unlock();
+        e
+    })
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    match ret {
+        0 => Ok(()),
+        e => Err(io::Error::from_raw_os_error(e)),
+    }
+}
+
+fn not_a_condvar() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "shared memory object does not contain an initialized condition variable",
+    )
+}
+
+/// Initializes a process-shared condition variable.
+unsafe fn init_cond(cond: *mut pthread_cond_t) -> io::Result<()> {
+    let mut attr = MaybeUninit::<libc::pthread_condattr_t>::uninit();
+    check(unsafe { libc::pthread_condattr_init(attr.as_mut_ptr()) })?;
+    let attr = attr.as_mut_ptr();
+    let result = check(unsafe { libc::pthread_condattr_setpshared(attr, libc::PTHREAD_PROCESS_SHARED) })
+        .and_then(|()| check(unsafe { libc::pthread_cond_init(cond, attr) }));
+    unsafe { libc::pthread_condattr_destroy(attr) };
+    result
+}
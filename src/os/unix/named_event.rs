@@ -0,0 +1,190 @@
+use super::shared_memory::SharedMemory;
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::size_of,
+    sync::{
+        atomic::{
+            AtomicU32,
+            Ordering::{Acquire, Release},
+        },
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const READY: u32 = 0x65766e74;
+
+/// The contents of the shared memory object backing the event.
+#[repr(C)]
+struct Shared {
+    /// Set to `READY` once the event is initialized.
+    state: AtomicU32,
+    /// 1 while the event is set, 0 otherwise.
+    set: AtomicU32,
+}
+
+/// A manual-reset event in a shared memory object of its own, waited on by parking on its flag where the platform
+/// allows for it, and by polling it elsewhere.
+pub(crate) struct NamedEvent {
+    // Shared with the thread doing the waiting for asynchronous waits.
+    shm: Arc<SharedMemory>,
+}
+impl NamedEvent {
+    pub fn create(name: &OsStr, keep_drop_guard: bool) -> io::Result<Self> {
+        // The object is zeroed, which is an unset event, and so there's nothing else to initialize.
+        let shm = SharedMemory::create(name, size_of::<Shared>(), keep_drop_guard)?;
+        let event = Self { shm: Arc::new(shm) };
+        event.shared().state.store(READY, Release);
+        Ok(event)
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let shm = SharedMemory::open(name)?;
+        if shm.len() < size_of::<Shared>() {
+            return Err(not_an_event());
+        }
+        let event = Self { shm: Arc::new(shm) };
+        for _ in 0..1000 {
+            if event.shared().state.load(Acquire) == READY {
+                return Ok(event);
+            }
+            thread::yield_now();
+        }
+        Err(not_an_event())
+    }
+
+    #[inline]
+    fn shared(&self) -> &Shared {
+        shared(&self.shm)
+    }
+
+    pub fn set(&self) -> io::Result<()> {
+        if self.shared().set.swap(1, Release) == 0 {
+            wake_all(&self.shared().set)?;
+        }
+        Ok(())
+    }
+    pub fn reset(&self) -> io::Result<()> {
+        self.shared().set.store(0, Release);
+        Ok(())
+    }
+    pub fn is_set(&self) -> io::Result<bool> {
+        Ok(self.shared().set.load(Acquire) != 0)
+    }
+    pub fn wait(&self) -> io::Result<()> {
+        while !self.wait_timeout(None)? {}
+        Ok(())
+    }
+    /// Returns `false` if the timeout ran out before the event was set.
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        wait_on(self.shared(), timeout)
+    }
+    /// Waits on a thread from the blocking pool of the runtime, which gives up shortly after the future is dropped.
+    #[cfg(feature = "tokio")]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+        struct CancelOnDrop(Arc<AtomicBool>);
+        impl Drop for CancelOnDrop {
+            fn drop(&mut self) {
+                self.0.store(true, Relaxed);
+            }
+        }
+
+        if self.is_set()? {
+            return Ok(());
+        }
+        let shm = Arc::clone(&self.shm);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _guard = CancelOnDrop(Arc::clone(&cancelled));
+        tokio::task::spawn_blocking(move || {
+            while !cancelled.load(Relaxed) {
+                // Woken up regularly to check whether anyone still cares.
+                if wait_on(shared(&shm), Some(Duration::from_millis(100)))? {
+                    break;
+                }
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+}
+impl Debug for NamedEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedEvent").field("shm", &self.shm).finish()
+    }
+}
+
+#[inline]
+fn shared(shm: &SharedMemory) -> &Shared {
+    // SAFETY: the mapping is page-aligned and large enough, as checked when it was created or opened, and the fields
+    // are atomics, which are fine with being modified by other processes.
+    unsafe { &*shm.as_ptr().cast::<Shared>() }
+}
+
+fn wait_on(shared: &Shared, timeout: Option<Duration>) -> io::Result<bool> {
+    let deadline = timeout.and_then(|t| Instant::now().checked_add(t));
+    loop {
+        if shared.set.load(Acquire) != 0 {
+            return Ok(true);
+        }
+        let remaining = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) if !remaining.is_zero() => Some(remaining),
+                _ => return Ok(false),
+            },
+            None => None,
+        };
+        wait_while_unset(&shared.set, remaining)?;
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos"
+))]
+fn wait_while_unset(word: &AtomicU32, timeout: Option<Duration>) -> io::Result<()> {
+    super::park::park(word, 0, timeout).map(|_| ())
+}
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos"
+))]
+fn wake_all(word: &AtomicU32) -> io::Result<()> {
+    super::park::unpark(word, true)
+}
+
+/// Without a way to park on a word of shared memory, the flag is polled instead.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos"
+)))]
+fn wait_while_unset(_word: &AtomicU32, timeout: Option<Duration>) -> io::Result<()> {
+    let delay = Duration::from_millis(4);
+    thread::sleep(timeout.map_or(delay, |t| t.min(delay)));
+    Ok(())
+}
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos"
+)))]
+fn wake_all(_word: &AtomicU32) -> io::Result<()> {
+    Ok(())
+}
+
+fn not_an_event() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "shared memory object does not contain an initialized event",
+    )
+}
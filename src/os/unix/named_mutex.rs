@@ -0,0 +1,140 @@
+use super::shared_memory::SharedMemory;
+use crate::sync::LockResult;
+use libc::pthread_mutex_t;
+use std::{
+    cell::UnsafeCell,
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::{size_of, MaybeUninit},
+    sync::atomic::{
+        AtomicU32,
+        Ordering::{Acquire, Release},
+    },
+    thread,
+};
+
+const READY: u32 = 0x6d757478;
+
+/// The contents of the shared memory object backing the mutex.
+#[repr(C)]
+struct Shared {
+    /// Set to `READY` once the mutex is initialized.
+    state: AtomicU32,
+    mutex: UnsafeCell<pthread_mutex_t>,
+}
+
+/// A process-shared pthread mutex in a shared memory object of its own.
+pub(crate) struct NamedMutex {
+    shm: SharedMemory,
+}
+impl NamedMutex {
+    pub fn create(name: &OsStr, keep_drop_guard: bool) -> io::Result<Self> {
+        // The object is new and thus zeroed, so nobody can see the mutex as ready before it's initialized.
+        let shm = SharedMemory::create(name, size_of::<Shared>(), keep_drop_guard)?;
+        let mutex = Self { shm };
+        unsafe {
+            // SAFETY: nobody else can use the mutex until it's marked as ready
+            init_mutex(mutex.shared().mutex.get())?;
+        }
+        mutex.shared().state.store(READY, Release);
+        Ok(mutex)
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let shm = SharedMemory::open(name)?;
+        if shm.len() < size_of::<Shared>() {
+            return Err(not_a_mutex());
+        }
+        let mutex = Self { shm };
+        // The creator might be between creating the object and initializing the mutex, which is a short window.
+        for _ in 0..1000 {
+            if mutex.shared().state.load(Acquire) == READY {
+                return Ok(mutex);
+            }
+            thread::yield_now();
+        }
+        Err(not_a_mutex())
+    }
+
+    #[inline]
+    fn shared(&self) -> &Shared {
+        // SAFETY: the mapping is page-aligned and large enough, and the state is an atomic while the mutex is only
+        // accessed through pthread functions via UnsafeCell
+        unsafe { &*self.shm.as_ptr().cast::<Shared>() }
+    }
+    #[inline]
+    pub(crate) fn raw(&self) -> *mut pthread_mutex_t {
+        self.shared().mutex.get()
+    }
+
+    pub fn lock(&self) -> io::Result<LockResult<()>> {
+        lock_result(unsafe { libc::pthread_mutex_lock(self.raw()) })
+    }
+    pub fn try_lock(&self) -> io::Result<LockResult<()>> {
+        match unsafe { libc::pthread_mutex_trylock(self.raw()) } {
+            libc::EBUSY => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+            ret => lock_result(ret),
+        }
+    }
+    /// Must only be called by the owner of a lock acquired with `EOWNERDEAD`, since `pthread_mutex_consistent` fails
+    /// with `EINVAL` on a mutex that is consistent already.
+    pub fn mark_consistent(&self) -> io::Result<()> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            let ret = unsafe { libc::pthread_mutex_consistent(self.raw()) };
+            if ret != 0 {
+                return Err(io::Error::from_raw_os_error(ret));
+            }
+        }
+        Ok(())
+    }
+    pub fn unlock(&self) {
+        unsafe {
+            // SAFETY: only called by the guard, i.e. on the thread that holds the lock
+            libc::pthread_mutex_unlock(self.raw());
+        }
+    }
+}
+impl Debug for NamedMutex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedMutex").field("shm", &self.shm).finish()
+    }
+}
+
+fn not_a_mutex() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "shared memory object does not contain an initialized mutex",
+    )
+}
+
+/// Interprets the return value of `pthread_mutex_lock` or `pthread_mutex_trylock`, which is an error code rather than
+/// -1 with `errno`.
+pub(crate) fn lock_result(ret: libc::c_int) -> io::Result<LockResult<()>> {
+    match ret {
+        0 => Ok(LockResult::Acquired(())),
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        libc::EOWNERDEAD => Ok(LockResult::OwnerDied(())),
+        e => Err(io::Error::from_raw_os_error(e)),
+    }
+}
+
+/// Initializes a process-shared mutex which errors on relocking and, where supported, is robust.
+unsafe fn init_mutex(mutex: *mut pthread_mutex_t) -> io::Result<()> {
+    let check = |ret| match ret {
+        0 => Ok(()),
+        e => Err(io::Error::from_raw_os_error(e)),
+    };
+    let mut attr = MaybeUninit::<libc::pthread_mutexattr_t>::uninit();
+    check(unsafe { libc::pthread_mutexattr_init(attr.as_mut_ptr()) })?;
+    let attr = attr.as_mut_ptr();
+    let result = (|| {
+        check(unsafe { libc::pthread_mutexattr_setpshared(attr, libc::PTHREAD_PROCESS_SHARED) })?;
+        check(unsafe { libc::pthread_mutexattr_settype(attr, libc::PTHREAD_MUTEX_ERRORCHECK) })?;
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        check(unsafe { libc::pthread_mutexattr_setrobust(attr, libc::PTHREAD_MUTEX_ROBUST) })?;
+        check(unsafe { libc::pthread_mutex_init(mutex, attr) })
+    })();
+    unsafe { libc::pthread_mutexattr_destroy(attr) };
+    result
+}
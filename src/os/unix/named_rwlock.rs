@@ -0,0 +1,128 @@
+use super::shared_memory::SharedMemory;
+use libc::pthread_rwlock_t;
+use std::{
+    cell::UnsafeCell,
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::{size_of, MaybeUninit},
+    sync::atomic::{
+        AtomicU32,
+        Ordering::{Acquire, Release},
+    },
+    thread,
+};
+
+const READY: u32 = 0x72776c6b;
+
+/// The contents of the shared memory object backing the lock.
+#[repr(C)]
+struct Shared {
+    /// Set to `READY` once the lock is initialized.
+    state: AtomicU32,
+    lock: UnsafeCell<pthread_rwlock_t>,
+}
+
+/// A process-shared pthread reader-writer lock in a shared memory object of its own.
+pub(crate) struct NamedRwLock {
+    shm: SharedMemory,
+}
+impl NamedRwLock {
+    pub fn create(name: &OsStr, keep_drop_guard: bool) -> io::Result<Self> {
+        // Same as with mutexes, the object is zeroed until the lock is initialized.
+        let shm = SharedMemory::create(name, size_of::<Shared>(), keep_drop_guard)?;
+        let lock = Self { shm };
+        unsafe {
+            // SAFETY: nobody else can use the lock until it's marked as ready
+            init_rwlock(lock.raw())?;
+        }
+        lock.shared().state.store(READY, Release);
+        Ok(lock)
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let shm = SharedMemory::open(name)?;
+        if shm.len() < size_of::<Shared>() {
+            return Err(not_a_rwlock());
+        }
+        let lock = Self { shm };
+        for _ in 0..1000 {
+            if lock.shared().state.load(Acquire) == READY {
+                return Ok(lock);
+            }
+            thread::yield_now();
+        }
+        Err(not_a_rwlock())
+    }
+
+    #[inline]
+    fn shared(&self) -> &Shared {
+        // SAFETY: as with mutexes
+        unsafe { &*self.shm.as_ptr().cast::<Shared>() }
+    }
+    #[inline]
+    fn raw(&self) -> *mut pthread_rwlock_t {
+        self.shared().lock.get()
+    }
+
+    pub fn read(&self) -> io::Result<()> {
+        check(unsafe { libc::pthread_rwlock_rdlock(self.raw()) })
+    }
+    pub fn try_read(&self) -> io::Result<()> {
+        try_check(unsafe { libc::pthread_rwlock_tryrdlock(self.raw()) })
+    }
+    pub fn write(&self) -> io::Result<()> {
+        check(unsafe { libc::pthread_rwlock_wrlock(self.raw()) })
+    }
+    pub fn try_write(&self) -> io::Result<()> {
+        try_check(unsafe { libc::pthread_rwlock_trywrlock(self.raw()) })
+    }
+    pub fn read_unlock(&self) {
+        self.unlock();
+    }
+    pub fn write_unlock(&self) {
+        self.unlock();
+    }
+    fn unlock(&self) {
+        unsafe {
+            // SAFETY: only called by the guards, i.e. on the thread that holds the lock
+            libc::pthread_rwlock_unlock(self.raw());
+        }
+    }
+}
+impl Debug for NamedRwLock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedRwLock").field("shm", &self.shm).finish()
+    }
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    match ret {
+        0 => Ok(()),
+        e => Err(io::Error::from_raw_os_error(e)),
+    }
+}
+/// Like `check`, but maps `EBUSY` to `WouldBlock`.
+fn try_check(ret: libc::c_int) -> io::Result<()> {
+    match ret {
+        libc::EBUSY => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+        ret => check(ret),
+    }
+}
+
+fn not_a_rwlock() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "shared memory object does not contain an initialized reader-writer lock",
+    )
+}
+
+/// Initializes a process-shared reader-writer lock.
+unsafe fn init_rwlock(lock: *mut pthread_rwlock_t) -> io::Result<()> {
+    let mut attr = MaybeUninit::<libc::pthread_rwlockattr_t>::uninit();
+    check(unsafe { libc::pthread_rwlockattr_init(attr.as_mut_ptr()) })?;
+    let attr = attr.as_mut_ptr();
+    let result = check(unsafe { libc::pthread_rwlockattr_setpshared(attr, libc::PTHREAD_PROCESS_SHARED) })
+        .and_then(|()| check(unsafe { libc::pthread_rwlock_init(lock, attr) }));
+    unsafe { libc::pthread_rwlockattr_destroy(attr) };
+    result
+}
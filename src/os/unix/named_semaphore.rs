@@ -0,0 +1,142 @@
+use super::shared_memory::to_shm_name;
+use libc::{c_uint, sem_t, O_CREAT, O_EXCL, SEM_FAILED};
+use std::{
+    ffi::{CString, OsStr},
+    fmt::{self, Debug, Formatter},
+    io,
+    ptr::NonNull,
+    time::Duration,
+};
+
+/// A POSIX named semaphore.
+pub(crate) struct NamedSemaphore {
+    sem: NonNull<sem_t>,
+    /// The name to unlink when dropped, if any.
+    drop_guard: Option<CString>,
+}
+// SAFETY: semaphores are made for concurrent use, and the handle isn't tied to the thread that opened it.
+unsafe impl Send for NamedSemaphore {}
+unsafe impl Sync for NamedSemaphore {}
+impl NamedSemaphore {
+    pub fn create(name: &OsStr, initial: u32, keep_drop_guard: bool) -> io::Result<Self> {
+        let name = to_shm_name(name)?;
+        // Values above SEM_VALUE_MAX are rejected by the system with EINVAL.
+        let sem = unsafe { libc::sem_open(name.as_ptr(), O_CREAT | O_EXCL, 0o600 as c_uint, initial as c_uint) };
+        let sem = ok_or_ret_errno!(sem != SEM_FAILED => NonNull::new(sem).expect("sem_open() returned null"))?;
+        Ok(Self {
+            sem,
+            drop_guard: keep_drop_guard.then_some(name),
+        })
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let name = to_shm_name(name)?;
+        let sem = unsafe { libc::sem_open(name.as_ptr(), 0) };
+        let sem = ok_or_ret_errno!(sem != SEM_FAILED => NonNull::new(sem).expect("sem_open() returned null"))?;
+        Ok(Self { sem, drop_guard: None })
+    }
+
+    pub fn acquire(&self) -> io::Result<()> {
+        loop {
+            if unsafe { libc::sem_wait(self.sem.as_ptr()) } != -1 {
+                return Ok(());
+            }
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+    }
+    pub fn try_acquire(&self) -> io::Result<()> {
+        loop {
+            if unsafe { libc::sem_trywait(self.sem.as_ptr()) } != -1 {
+                return Ok(());
+            }
+            // EAGAIN conveniently maps to WouldBlock.
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+    }
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn acquire_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        let deadline = super::c_wrappers::realtime_deadline(timeout)?;
+        loop {
+            if unsafe { libc::sem_timedwait(self.sem.as_ptr(), &deadline) } != -1 {
+                return Ok(true);
+            }
+            let e = io::Error::last_os_error();
+            match e.kind() {
+                io::ErrorKind::Interrupted => {}
+                io::ErrorKind::TimedOut => return Ok(false),
+                _ => return Err(e),
+            }
+        }
+    }
+    /// Apple platforms lack `sem_timedwait`, and so the semaphore is polled instead.
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    pub fn acquire_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        use std::{thread, time::Instant};
+        let deadline = Instant::now().checked_add(timeout);
+        let mut delay = Duration::from_millis(1);
+        loop {
+            match self.try_acquire() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return els.map(|()| true),
+            }
+            let now = Instant::now();
+            let remaining = match deadline {
+                Some(deadline) if now >= deadline => return Ok(false),
+                Some(deadline) => deadline - now,
+                None => delay,
+            };
+            thread::sleep(delay.min(remaining));
+            delay = (delay * 2).min(Duration::from_millis(16));
+        }
+    }
+    pub fn release(&self, n: u32) -> io::Result<()> {
+        for _ in 0..n {
+            let success = unsafe { libc::sem_post(self.sem.as_ptr()) != -1 };
+            ok_or_ret_errno!(success => ())?;
+        }
+        Ok(())
+    }
+}
+impl Drop for NamedSemaphore {
+    fn drop(&mut self) {
+        unsafe { libc::sem_close(self.sem.as_ptr()) };
+        if let Some(name) = &self.drop_guard {
+            let _ = sem_unlink(name);
+        }
+    }
+}
+impl Debug for NamedSemaphore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedSemaphore")
+            .field("sem", &self.sem)
+            .field("drop_guard", &self.drop_guard)
+            .finish()
+    }
+}
+
+pub(crate) fn unlink(name: &OsStr) -> io::Result<()> {
+    sem_unlink(&to_shm_name(name)?)
+}
+fn sem_unlink(name: &CString) -> io::Result<()> {
+    let success = unsafe { libc::sem_unlink(name.as_ptr()) != -1 };
+    ok_or_ret_errno!(success => ())
+}
@@ -0,0 +1,138 @@
+use super::unixprelude::*;
+use std::{io, sync::atomic::AtomicU32, time::Duration};
+
+/// Returns `false` if the timeout ran out. Since none of the wait operations are restarted after a signal, an
+/// interruption counts as a spurious wakeup.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn park(word: &AtomicU32, expected: u32, timeout: Option<Duration>) -> io::Result<bool> {
+    let timeout = timeout.map(relative_timespec);
+    let timeout_ptr = timeout
+        .as_ref()
+        .map_or(std::ptr::null(), |t| t as *const libc::timespec);
+    // Without FUTEX_PRIVATE_FLAG, the futex is keyed by the physical page, which is what makes it work across processes.
+    let ret = unsafe { libc::syscall(libc::SYS_futex, word.as_ptr(), libc::FUTEX_WAIT, expected, timeout_ptr) };
+    if ret == -1 {
+        return wait_error(io::Error::last_os_error());
+    }
+    Ok(true)
+}
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn unpark(word: &AtomicU32, all: bool) -> io::Result<()> {
+    let count = if all { c_int::MAX } else { 1 };
+    let success = unsafe { libc::syscall(libc::SYS_futex, word.as_ptr(), libc::FUTEX_WAKE, count) != -1 };
+    ok_or_ret_errno!(success => ())
+}
+
+#[cfg(target_os = "freebsd")]
+pub(crate) fn park(word: &AtomicU32, expected: u32, timeout: Option<Duration>) -> io::Result<bool> {
+    // The size of the timeout structure is passed in place of a pointer, as per _umtx_op(2).
+    let mut timeout = timeout.map(relative_timespec);
+    let (size, timeout_ptr) = match &mut timeout {
+        Some(t) => (std::mem::size_of::<libc::timespec>(), (t as *mut libc::timespec).cast()),
+        None => (0, std::ptr::null_mut()),
+    };
+    // UMTX_OP_WAIT_UINT, unlike its _PRIVATE counterpart, works across processes.
+    let ret = unsafe {
+        libc::_umtx_op(
+            word.as_ptr().cast(),
+            libc::UMTX_OP_WAIT_UINT,
+            expected as libc::c_ulong,
+            size as *mut libc::c_void,
+            timeout_ptr,
+        )
+    };
+    if ret == -1 {
+        return wait_error(io::Error::last_os_error());
+    }
+    Ok(true)
+}
+#[cfg(target_os = "freebsd")]
+pub(crate) fn unpark(word: &AtomicU32, all: bool) -> io::Result<()> {
+    let count = if all { c_int::MAX } else { 1 };
+    let success = unsafe {
+        libc::_umtx_op(
+            word.as_ptr().cast(),
+            libc::UMTX_OP_WAKE,
+            count as libc::c_ulong,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        ) != -1
+    };
+    ok_or_ret_errno!(success => ())
+}
+
+#[cfg(target_os = "macos")]
+mod ulock {
+    use libc::{c_int, c_void};
+    pub const UL_COMPARE_AND_WAIT_SHARED: u32 = 3;
+    pub const ULF_WAKE_ALL: u32 = 0x100;
+    extern "C" {
+        // Not in any public header, but used by libc++ and stable since macOS 10.12.
+        pub fn __ulock_wait(operation: u32, addr: *mut c_void, value: u64, timeout_us: u32) -> c_int;
+        pub fn __ulock_wake(operation: u32, addr: *mut c_void, wake_value: u64) -> c_int;
+    }
+}
+#[cfg(target_os = "macos")]
+pub(crate) fn park(word: &AtomicU32, expected: u32, timeout: Option<Duration>) -> io::Result<bool> {
+    use std::time::Instant;
+    let deadline = timeout.and_then(|t| Instant::now().checked_add(t));
+    loop {
+        // A timeout of zero means no timeout, so the shortest one is a microsecond.
+        let timeout_us = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                u32::try_from(remaining.as_micros()).unwrap_or(u32::MAX).max(1)
+            }
+            // No timeout, or one too far in the future to tell apart from none.
+            None => 0,
+        };
+        let ret = unsafe {
+            ulock::__ulock_wait(
+                ulock::UL_COMPARE_AND_WAIT_SHARED,
+                word.as_ptr().cast(),
+                u64::from(expected),
+                timeout_us,
+            )
+        };
+        if ret != -1 {
+            return Ok(true);
+        }
+        let e = io::Error::last_os_error();
+        match deadline {
+            // The timeout is capped at about 71 minutes, and longer ones are waited out in several steps.
+            Some(deadline) if e.kind() == io::ErrorKind::TimedOut && Instant::now() < deadline => {}
+            _ => return wait_error(e),
+        }
+    }
+}
+#[cfg(target_os = "macos")]
+pub(crate) fn unpark(word: &AtomicU32, all: bool) -> io::Result<()> {
+    let op = ulock::UL_COMPARE_AND_WAIT_SHARED | if all { ulock::ULF_WAKE_ALL } else { 0 };
+    let ret = unsafe { ulock::__ulock_wake(op, word.as_ptr().cast(), 0) };
+    if ret == -1 {
+        let e = io::Error::last_os_error();
+        // ENOENT means that nobody was waiting.
+        if e.raw_os_error() != Some(libc::ENOENT) {
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn relative_timespec(timeout: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: timeout.as_secs().try_into().unwrap_or(libc::time_t::MAX),
+        tv_nsec: timeout.subsec_nanos() as _,
+    }
+}
+
+/// Sorts the errors of the wait operations into timeouts, spurious wakeups and actual errors.
+fn wait_error(e: io::Error) -> io::Result<bool> {
+    match e.kind() {
+        io::ErrorKind::TimedOut => Ok(false),
+        // EAGAIN means that the word didn't hold the expected value to begin with.
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted => Ok(true),
+        _ => Err(e),
+    }
+}
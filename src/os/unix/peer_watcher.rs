@@ -0,0 +1,150 @@
+use super::{c_wrappers, unixprelude::*};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io, thread,
+    time::{Duration, Instant},
+};
+
+/// How often a process which can't be watched through a file descriptor is checked for.
+const PROBE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Watches a process through a file descriptor which becomes readable once it exits where the platform provides one,
+/// and by checking for its existence with `kill` elsewhere.
+pub(crate) enum PeerWatcher {
+    /// A pidfd on Linux and Android, or a kqueue with an `EVFILT_PROC` filter on BSD and Apple platforms.
+    Fd(OwnedFd),
+    Probe(pid_t),
+    /// The process was gone by the time the watcher was created.
+    Exited,
+}
+impl PeerWatcher {
+    pub fn new(pid: u32) -> io::Result<Self> {
+        let pid =
+            pid_t::try_from(pid).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "process ID out of range"))?;
+        match open_exit_fd(pid) {
+            Ok(fd) => Ok(Self::Fd(fd)),
+            Err(e) if e.raw_os_error() == Some(libc::ESRCH) => Ok(Self::Exited),
+            // pidfd_open() on kernels older than 5.3, or a platform without a way to watch a process.
+            Err(e) if e.raw_os_error() == Some(libc::ENOSYS) => {
+                Ok(if is_alive(pid)? { Self::Probe(pid) } else { Self::Exited })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn wait(&self) -> io::Result<()> {
+        while !self.wait_timeout(None)? {}
+        Ok(())
+    }
+    /// Returns `false` if the timeout ran out before the process exited.
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        match self {
+            Self::Fd(fd) => c_wrappers::poll_readable(fd.as_fd(), c_wrappers::timeout_to_ms(timeout)),
+            Self::Probe(pid) => {
+                let deadline = timeout.and_then(|t| Instant::now().checked_add(t));
+                loop {
+                    if !is_alive(*pid)? {
+                        return Ok(true);
+                    }
+                    let delay = match deadline {
+                        Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                            Some(remaining) if !remaining.is_zero() => remaining.min(PROBE_INTERVAL),
+                            _ => return Ok(false),
+                        },
+                        None => PROBE_INTERVAL,
+                    };
+                    thread::sleep(delay);
+                }
+            }
+            Self::Exited => Ok(true),
+        }
+    }
+    #[cfg(feature = "tokio")]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        match self {
+            Self::Fd(fd) => {
+                // The file descriptor stays readable once the process has exited, so nothing is consumed.
+                let registration = c_wrappers::register_readable(fd.as_fd())?;
+                let _guard = registration.readable().await?;
+                Ok(())
+            }
+            Self::Probe(pid) => {
+                while is_alive(*pid)? {
+                    tokio::time::sleep(PROBE_INTERVAL).await;
+                }
+                Ok(())
+            }
+            Self::Exited => Ok(()),
+        }
+    }
+}
+impl Debug for PeerWatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fd(fd) => f.debug_tuple("Fd").field(&fd.as_raw_fd()).finish(),
+            Self::Probe(pid) => f.debug_tuple("Probe").field(pid).finish(),
+            Self::Exited => f.write_str("Exited"),
+        }
+    }
+}
+
+fn is_alive(pid: pid_t) -> io::Result<bool> {
+    if unsafe { libc::kill(pid, 0) } != -1 {
+        return Ok(true);
+    }
+    let e = io::Error::last_os_error();
+    match e.raw_os_error() {
+        Some(libc::ESRCH) => Ok(false),
+        // The process exists, we just aren't allowed to signal it.
+        Some(libc::EPERM) => Ok(true),
+        _ => Err(e),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn open_exit_fd(pid: pid_t) -> io::Result<OwnedFd> {
+    crate::os::linux::PidFd::open(pid).map(OwnedFd::from)
+}
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
+))]
+fn open_exit_fd(pid: pid_t) -> io::Result<OwnedFd> {
+    let kq = unsafe { libc::kqueue() };
+    let kq = ok_or_ret_errno!(kq != -1 => unsafe {
+        // SAFETY: we just created this file descriptor
+        OwnedFd::from_raw_fd(kq)
+    })?;
+    c_wrappers::set_cloexec(kq.as_fd())?;
+    // The event is never retrieved, which keeps the kqueue readable once the process has exited.
+    let mut event = unsafe { std::mem::zeroed::<libc::kevent>() };
+    event.ident = pid as _;
+    event.filter = libc::EVFILT_PROC;
+    event.flags = libc::EV_ADD;
+    event.fflags = libc::NOTE_EXIT;
+    let ret = unsafe { libc::kevent(kq.as_raw_fd(), &event, 1, std::ptr::null_mut(), 0, std::ptr::null()) };
+    ok_or_ret_errno!(ret != -1 => kq)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
+)))]
+fn open_exit_fd(_pid: pid_t) -> io::Result<OwnedFd> {
+    Err(io::Error::from_raw_os_error(libc::ENOSYS))
+}
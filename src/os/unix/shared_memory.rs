@@ -0,0 +1,225 @@
+use super::unixprelude::*;
+use libc::{MAP_FAILED, MAP_SHARED, O_CREAT, O_EXCL, O_RDWR, PROT_READ, PROT_WRITE};
+use std::{
+    ffi::{CString, OsStr, OsString},
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::MaybeUninit,
+    ptr::{self, NonNull},
+};
+
+pub(crate) struct SharedMemory {
+    fd: OwnedFd,
+    ptr: NonNull<u8>,
+    len: usize,
+    _drop_guard: NameDropGuard,
+}
+// SAFETY: the mapping is not tied to the thread it was created on, and the pointer is only handed out in raw form.
+unsafe impl Send for SharedMemory {}
+unsafe impl Sync for SharedMemory {}
+impl SharedMemory {
+    pub fn create(name: &OsStr, size: usize, keep_drop_guard: bool) -> io::Result<Self> {
+        check_size(size)?;
+        let name = to_shm_name(name)?;
+        let fd = shm_open(&name, O_CREAT | O_EXCL | O_RDWR)?;
+        // Armed right away so that a half-created object isn't left behind if anything below fails.
+        let mut drop_guard = NameDropGuard(Some(name));
+        ftruncate(fd.as_fd(), size)?;
+        let mut shm = Self::map(fd, size)?;
+        if !keep_drop_guard {
+            drop_guard.0 = None;
+        }
+        shm._drop_guard = drop_guard;
+        Ok(shm)
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let fd = shm_open(&to_shm_name(name)?, O_RDWR)?;
+        Self::from_fd(fd)
+    }
+    pub fn anonymous(size: usize) -> io::Result<Self> {
+        check_size(size)?;
+        let fd = create_anonymous()?;
+        ftruncate(fd.as_fd(), size)?;
+        Self::map(fd, size)
+    }
+    pub fn from_fd(fd: OwnedFd) -> io::Result<Self> {
+        let size = fstat_size(fd.as_fd())?;
+        check_size(size)?;
+        Self::map(fd, size)
+    }
+    fn map(fd: OwnedFd, len: usize) -> io::Result<Self> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                PROT_READ | PROT_WRITE,
+                MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let ptr = NonNull::new(ptr.cast()).expect("mmap() returned null");
+        Ok(Self {
+            fd,
+            ptr,
+            len,
+            _drop_guard: NameDropGuard(None),
+        })
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: we own the mapping and nothing can borrow from it past this point
+            libc::munmap(self.ptr.as_ptr().cast(), self.len);
+        }
+    }
+}
+impl AsFd for SharedMemory {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+impl Debug for SharedMemory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedMemory")
+            .field("fd", &self.fd.as_raw_fd())
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .field("drop_guard", &self._drop_guard.0)
+            .finish()
+    }
+}
+
+/// Unlinks the name of a shared memory object when dropped, if there is one.
+struct NameDropGuard(Option<CString>);
+impl Drop for NameDropGuard {
+    fn drop(&mut self) {
+        if let Some(name) = &self.0 {
+            let _ = shm_unlink(name);
+        }
+    }
+}
+
+pub(crate) fn unlink(name: &OsStr) -> io::Result<()> {
+    shm_unlink(&to_shm_name(name)?)
+}
+
+pub(crate) fn user_scoped_name(name: &OsStr) -> io::Result<OsString> {
+    let uid = unsafe { libc::geteuid() };
+    let mut scoped = OsString::from(format!("uid{uid}-"));
+    scoped.push(name);
+    Ok(scoped)
+}
+
+fn check_size(size: usize) -> io::Result<()> {
+    if size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "shared memory objects cannot be empty",
+        ));
+    }
+    Ok(())
+}
+
+/// Prepends the slash which POSIX requires shared memory object names to start with.
+pub(super) fn to_shm_name(name: &OsStr) -> io::Result<CString> {
+    let name = name.as_bytes();
+    if name.is_empty() || name.contains(&b'/') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "shared memory object names must be non-empty and cannot contain slashes",
+        ));
+    }
+    let mut buf = Vec::with_capacity(name.len() + 2);
+    buf.push(b'/');
+    buf.extend_from_slice(name);
+    CString::new(buf).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "interior nul byte in name"))
+}
+
+fn shm_open(name: &CString, flags: c_int) -> io::Result<OwnedFd> {
+    // shm_open() always sets FD_CLOEXEC.
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, 0o600) };
+    ok_or_ret_errno!(fd != -1 => unsafe {
+        // SAFETY: we just created this file descriptor
+        OwnedFd::from_raw_fd(fd)
+    })
+}
+fn shm_unlink(name: &CString) -> io::Result<()> {
+    let success = unsafe { libc::shm_unlink(name.as_ptr()) != -1 };
+    ok_or_ret_errno!(success => ())
+}
+fn ftruncate(fd: BorrowedFd<'_>, size: usize) -> io::Result<()> {
+    let size = libc::off_t::try_from(size)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "shared memory object size out of range"))?;
+    let success = unsafe { libc::ftruncate(fd.as_raw_fd(), size) != -1 };
+    ok_or_ret_errno!(success => ())
+}
+fn fstat_size(fd: BorrowedFd<'_>) -> io::Result<usize> {
+    let mut stat = MaybeUninit::<libc::stat>::uninit();
+    let success = unsafe { libc::fstat(fd.as_raw_fd(), stat.as_mut_ptr()) != -1 };
+    let size = ok_or_ret_errno!(success => unsafe {
+        // SAFETY: fstat() succeeded and thus initialized the structure
+        stat.assume_init().st_size
+    })?;
+    usize::try_from(size).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "shared memory object too large to be mapped",
+        )
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn create_anonymous() -> io::Result<OwnedFd> {
+    let fd = unsafe { libc::memfd_create(b"interprocess-shm\0".as_ptr().cast(), libc::MFD_CLOEXEC) };
+    ok_or_ret_errno!(fd != -1 => unsafe {
+        // SAFETY: we just created this file descriptor
+        OwnedFd::from_raw_fd(fd)
+    })
+}
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn create_anonymous() -> io::Result<OwnedFd> {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        process,
+        sync::atomic::{AtomicU32, Ordering::Relaxed},
+    };
+    const MAX_ATTEMPTS: u32 = 16;
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    let mut attempt = 0;
+    loop {
+        // The object is unlinked right away, so the name only needs to be unique for a brief moment. The random part
+        // keeps other processes from predicting it and opening the object before it's unlinked.
+        let name = format!(
+            "interprocess-shm-{}-{}-{:016x}",
+            process::id(),
+            COUNTER.fetch_add(1, Relaxed),
+            RandomState::new().build_hasher().finish(),
+        );
+        let name = to_shm_name(OsStr::new(&name))?;
+        match shm_open(&name, O_CREAT | O_EXCL | O_RDWR) {
+            Ok(fd) => {
+                shm_unlink(&name)?;
+                return Ok(fd);
+            }
+            Err(e) if e.raw_os_error() == Some(libc::EEXIST) && attempt + 1 < MAX_ATTEMPTS => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
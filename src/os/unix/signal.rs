@@ -0,0 +1,315 @@
+//! Sending and receiving signals, including realtime signals with payloads.
+//!
+//! Signals are the oldest form of IPC on Unix: a process can interrupt another with a small integer, and realtime
+//! signals can additionally carry a word-sized value and are queued rather than coalesced. Running arbitrary code in a
+//! signal handler is notoriously unsafe, and so this module never does that – instead, a [`SignalListener`] installs a
+//! handler which writes the details of every signal it catches into a self-pipe, from which they can be received like
+//! messages, be it [in a blocking manner](SignalListener::recv), [without blocking](SignalListener::try_recv) or
+//! [asynchronously](SignalListener::recv_async).
+//!
+//! Signals are sent with [`send()`], and realtime signals with payloads with [`send_queued()`]. The range of realtime
+//! signals is obtained with [`rt_signal()`].
+//!
+//! This module is only available on Linux and Android. On other platforms, it's absent and thus any usage of it will
+//! result in a compile-time error.
+
+use super::{c_wrappers, unixprelude::*};
+use crate::unnamed_pipe::PipeOptions;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::{size_of, MaybeUninit},
+    ptr,
+    sync::atomic::{AtomicI32, AtomicUsize, Ordering::SeqCst},
+};
+
+/// One past the highest signal number that can be listened to.
+const MAX_SIGNAL: usize = 128;
+
+/// For every signal, the writing end of the self-pipe of the listener it's routed to, or -1 if there is none.
+static ROUTES: [AtomicI32; MAX_SIGNAL] = {
+    #[allow(clippy::declare_interior_mutable_const)] // Only used to initialize the array
+    const UNROUTED: AtomicI32 = AtomicI32::new(-1);
+    [UNROUTED; MAX_SIGNAL]
+};
+/// The number of signal handler invocations currently in progress, which listeners wait out before closing their pipes.
+static HANDLERS_RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the `n`-th realtime signal, counting from zero, or `None` if there aren't that many.
+///
+/// Realtime signals have no predefined meaning, are queued instead of being coalesced if the same signal is sent more
+/// than once, and can carry a payload when sent with [`send_queued()`].
+pub fn rt_signal(n: u32) -> Option<c_int> {
+    let n = c_int::try_from(n).ok()?;
+    let signal = libc::SIGRTMIN().checked_add(n)?;
+    (signal <= libc::SIGRTMAX()).then_some(signal)
+}
+
+/// Sends a signal to the process with the given ID.
+///
+/// # System calls
+/// - `kill`
+pub fn send(pid: pid_t, signal: c_int) -> io::Result<()> {
+    let success = unsafe { libc::kill(pid, signal) != -1 };
+    ok_or_ret_errno!(success => ())
+}
+/// Sends a signal with a payload to the process with the given ID, which it can retrieve with
+/// [`SignalInfo::value()`].
+///
+/// The payload is a plain integer, and is not interpreted in any way. If the signal is a [realtime one](rt_signal),
+/// it's queued, and every instance of it is received separately; the number of signals which can be queued is limited
+/// by `RLIMIT_SIGPENDING`, beyond which sending fails with [`WouldBlock`](io::ErrorKind::WouldBlock).
+///
+/// # System calls
+/// - `sigqueue`
+pub fn send_queued(pid: pid_t, signal: c_int, value: usize) -> io::Result<()> {
+    let value = libc::sigval {
+        sival_ptr: value as *mut libc::c_void,
+    };
+    let success = unsafe { libc::sigqueue(pid, signal, value) != -1 };
+    ok_or_ret_errno!(success => ())
+}
+
+/// The details of a signal received by a [`SignalListener`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct SignalInfo {
+    signal: c_int,
+    code: c_int,
+    pid: pid_t,
+    uid: uid_t,
+    value: usize,
+}
+impl SignalInfo {
+    /// Returns the signal number.
+    #[inline]
+    pub fn signal(&self) -> c_int {
+        self.signal
+    }
+    /// Returns the `si_code` of the signal, which describes where it came from – for example, `SI_USER` for signals
+    /// sent with [`send()`] and `SI_QUEUE` for ones sent with [`send_queued()`].
+    #[inline]
+    pub fn code(&self) -> c_int {
+        self.code
+    }
+    /// Returns the ID of the process which sent the signal, if it was sent by a process.
+    #[inline]
+    pub fn sender_pid(&self) -> Option<pid_t> {
+        self.is_from_process().then_some(self.pid)
+    }
+    /// Returns the real user ID of the process which sent the signal, if it was sent by a process.
+    #[inline]
+    pub fn sender_uid(&self) -> Option<uid_t> {
+        self.is_from_process().then_some(self.uid)
+    }
+    /// Returns the payload of the signal, if it was sent with [`send_queued()`].
+    #[inline]
+    pub fn value(&self) -> Option<usize> {
+        (self.code == libc::SI_QUEUE).then_some(self.value)
+    }
+    #[inline]
+    fn is_from_process(&self) -> bool {
+        matches!(self.code, libc::SI_USER | libc::SI_QUEUE | libc::SI_TKILL)
+    }
+}
+
+/// Catches signals and makes them available to be received like messages.
+///
+/// Creating a listener installs a handler for each of the given signals, which routes them into the listener's
+/// self-pipe; dropping it restores the previous dispositions of the signals. A signal can only be routed to one
+/// listener at a time.
+///
+/// Signals which arrive while the pipe is full are dropped, which only happens if a large number of signals is left
+/// unreceived. Signals caused by faults, like `SIGSEGV`, can't be listened to, since returning from their handlers
+/// would just repeat the fault, and neither can `SIGKILL` and `SIGSTOP`.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::os::unix::signal::{rt_signal, SignalListener};
+///
+/// let signal = rt_signal(0).unwrap();
+/// let listener = SignalListener::new(&[signal])?;
+/// let info = listener.recv()?;
+/// println!("received {:?} from {:?}", info.value(), info.sender_pid());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SignalListener {
+    /// The signals routed to this listener, along with their previous dispositions.
+    signals: Vec<(c_int, libc::sigaction)>,
+    rx: OwnedFd,
+    tx: OwnedFd,
+}
+impl SignalListener {
+    /// Creates a listener for the given signals.
+    ///
+    /// An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if one of the signals is already
+    /// routed to a different listener, and one of kind [`InvalidInput`](io::ErrorKind::InvalidInput) if it can't be
+    /// listened to.
+    ///
+    /// # System calls
+    /// - `pipe2`
+    /// - `sigaction`
+    pub fn new(signals: &[c_int]) -> io::Result<Self> {
+        let [rx, tx] = super::unnamed_pipe::create_fds(&PipeOptions::new().nonblocking(true))?;
+        let mut listener = Self {
+            signals: Vec::with_capacity(signals.len()),
+            rx,
+            tx,
+        };
+        // On failure, the listener is dropped, unrouting the signals routed so far.
+        for &signal in signals {
+            listener.route(signal)?;
+        }
+        Ok(listener)
+    }
+    fn route(&mut self, signal: c_int) -> io::Result<()> {
+        if matches!(signal, libc::SIGSEGV | libc::SIGBUS | libc::SIGILL | libc::SIGFPE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "signals caused by faults cannot be listened to",
+            ));
+        }
+        let route = usize::try_from(signal)
+            .ok()
+            .and_then(|s| ROUTES.get(s))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid signal number"))?;
+        route
+            .compare_exchange(-1, self.tx.as_raw_fd(), SeqCst, SeqCst)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "signal is already routed to a different listener",
+                )
+            })?;
+
+        let mut action = unsafe { MaybeUninit::<libc::sigaction>::zeroed().assume_init() };
+        action.sa_sigaction = handler as *const () as usize;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+        let mut old = MaybeUninit::<libc::sigaction>::uninit();
+        if unsafe { libc::sigaction(signal, &action, old.as_mut_ptr()) } == -1 {
+            let e = io::Error::last_os_error();
+            route.store(-1, SeqCst);
+            return Err(e);
+        }
+        self.signals.push((signal, unsafe { old.assume_init() }));
+        Ok(())
+    }
+
+    /// Receives a signal if one has arrived, failing with [`WouldBlock`](io::ErrorKind::WouldBlock) otherwise.
+    ///
+    /// # System calls
+    /// - `read`
+    pub fn try_recv(&self) -> io::Result<SignalInfo> {
+        let mut info = MaybeUninit::<SignalInfo>::uninit();
+        loop {
+            // Records are written atomically and are always read whole.
+            let ret = unsafe { libc::read(self.rx.as_raw_fd(), info.as_mut_ptr().cast(), size_of::<SignalInfo>()) };
+            if ret == -1 {
+                let e = io::Error::last_os_error();
+                if e.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(e);
+            }
+            debug_assert_eq!(ret as usize, size_of::<SignalInfo>());
+            // SAFETY: the handler writes whole records, and every bit pattern is a valid SignalInfo
+            return Ok(unsafe { info.assume_init() });
+        }
+    }
+    /// Blocks until a signal arrives, then receives it.
+    ///
+    /// # System calls
+    /// - `poll`
+    /// - `read`
+    pub fn recv(&self) -> io::Result<SignalInfo> {
+        loop {
+            c_wrappers::poll_readable(self.rx.as_fd(), -1)?;
+            match self.try_recv() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                els => return els,
+            }
+        }
+    }
+    /// Asynchronously waits until a signal arrives, then receives it. The self-pipe is registered in the Tokio event
+    /// loop for the duration of the wait.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn recv_async(&self) -> io::Result<SignalInfo> {
+        let registration = c_wrappers::register_readable(self.rx.as_fd())?;
+        loop {
+            let mut guard = registration.readable().await?;
+            if let Ok(result) = guard.try_io(|_| self.try_recv()) {
+                return result;
+            }
+        }
+    }
+}
+impl Drop for SignalListener {
+    fn drop(&mut self) {
+        for (signal, old) in self.signals.drain(..) {
+            unsafe { libc::sigaction(signal, &old, ptr::null_mut()) };
+            ROUTES[signal as usize].store(-1, SeqCst);
+        }
+        // A handler which picked up the route before it was cleared might still be about to write into the pipe. This
+        // and the handler form a Dekker-style handshake – the handler increments the count before loading the route,
+        // and the route is cleared before the count is loaded – which only works if both sides use SeqCst, since a
+        // Release store followed by an Acquire load may be reordered.
+        while HANDLERS_RUNNING.load(SeqCst) != 0 {
+            std::hint::spin_loop();
+        }
+    }
+}
+/// Borrows the reading end of the self-pipe, which becomes readable when a signal arrives.
+impl AsFd for SignalListener {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.rx.as_fd()
+    }
+}
+impl Debug for SignalListener {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignalListener")
+            .field("signals", &self.signals.iter().map(|(s, _)| s).collect::<Vec<_>>())
+            .field("rx", &self.rx.as_raw_fd())
+            .field("tx", &self.tx.as_raw_fd())
+            .finish()
+    }
+}
+
+/// Writes the details of the signal into the self-pipe it's routed to. Only performs async-signal-safe operations.
+extern "C" fn handler(signal: c_int, info: *mut libc::siginfo_t, _context: *mut libc::c_void) {
+    HANDLERS_RUNNING.fetch_add(1, SeqCst);
+    let errno = errno_location();
+    let saved_errno = unsafe { *errno };
+    let fd = ROUTES.get(signal as usize).map_or(-1, |r| r.load(SeqCst));
+    if fd != -1 && !info.is_null() {
+        let info = unsafe { &*info };
+        let record = unsafe {
+            // SAFETY: the union fields are read as integers regardless of which ones the sender filled in
+            SignalInfo {
+                signal,
+                code: info.si_code,
+                pid: info.si_pid(),
+                uid: info.si_uid(),
+                value: info.si_value().sival_ptr as usize,
+            }
+        };
+        // A full pipe drops the signal.
+        unsafe { libc::write(fd, (&record as *const SignalInfo).cast(), size_of::<SignalInfo>()) };
+    }
+    unsafe { *errno = saved_errno };
+    HANDLERS_RUNNING.fetch_sub(1, SeqCst);
+}
+
+#[cfg(target_os = "linux")]
+fn errno_location() -> *mut c_int {
+    unsafe { libc::__errno_location() }
+}
+#[cfg(target_os = "android")]
+fn errno_location() -> *mut c_int {
+    unsafe { libc::__errno() }
+}
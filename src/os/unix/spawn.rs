@@ -0,0 +1,45 @@
+use super::{c_wrappers, unixprelude::*};
+use std::{io, mem, os::unix::process::CommandExt, process::Command};
+
+pub(crate) type ChildEnd = OwnedFd;
+
+/// The file descriptor at which the first channel is inherited, right after standard input, output and error.
+const FIRST_FD: c_int = 3;
+
+/// Arranges for the child ends to be inherited as consecutive file descriptors starting at `FIRST_FD`, and returns
+/// their values to be listed in the environment of the child. The ends are moved into the command, which keeps them open
+/// until it's dropped.
+pub(crate) fn inherit_all(command: &mut Command, ends: &mut Vec<ChildEnd>) -> io::Result<Vec<u64>> {
+    let count = c_int::try_from(ends.len())
+        .ok()
+        .and_then(|c| c.checked_add(FIRST_FD))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "too many channels"))?;
+    let ends = mem::take(ends);
+    let mut scratch = vec![-1; ends.len()];
+    let hook = move || {
+        // Every end is first moved out of the target range, since a child end might happen to occupy the target of
+        // another one and would then be overwritten by dup2() before getting to its own target.
+        for (end, slot) in ends.iter().zip(&mut scratch) {
+            *slot = unsafe { c_wrappers::fcntl_int(end.as_fd(), libc::F_DUPFD_CLOEXEC, count)? };
+        }
+        for (target, &fd) in (FIRST_FD..).zip(&scratch) {
+            // The duplicate doesn't inherit the close-on-exec flag, while the one made above is closed by exec.
+            c_wrappers::dup2(unsafe { BorrowedFd::borrow_raw(fd) }, target)?;
+        }
+        Ok(())
+    };
+    // SAFETY: the hook only performs async-signal-safe system calls and doesn't allocate
+    unsafe { command.pre_exec(hook) };
+    Ok((FIRST_FD..count).map(|fd| fd as u64).collect())
+}
+
+/// Takes ownership of an inherited channel and makes sure that it isn't passed further down to grandchildren.
+///
+/// # Safety
+/// `raw` must be a file descriptor inherited from the parent which isn't owned by anything else.
+pub(crate) unsafe fn adopt(raw: u64) -> io::Result<ChildEnd> {
+    let fd = RawFd::try_from(raw).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    c_wrappers::set_cloexec(fd.as_fd())?;
+    Ok(fd)
+}
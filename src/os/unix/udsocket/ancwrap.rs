@@ -2,7 +2,7 @@ use super::{
     super::unixprelude::*,
     c_wrappers,
     cmsg::{read::buf_to_msghdr, CmsgMut, CmsgMutExt, CmsgRef},
-    util::{make_msghdr, to_msghdr_iovlen},
+    util::{make_msghdr, to_msghdr_iovlen, MsghdrIovlen},
     ReadAncillarySuccess, UdSocketPath,
 };
 use libc::{c_void, iovec, sockaddr_un};
@@ -19,6 +19,29 @@ pub(super) fn recvmsg<AB: CmsgMut + ?Sized>(
 ) -> io::Result<ReadAncillarySuccess> {
     let iov = bufs.as_mut_ptr().cast::<iovec>();
     let iovlen = to_msghdr_iovlen(bufs.len())?;
+    recvmsg_iov(fd, iov, iovlen, ancbuf, addrbuf)
+}
+/// Like [`recvmsg()`], but receives the main data into a single possibly uninitialized buffer.
+#[cfg(feature = "read_buf")]
+pub(super) fn recvmsg_uninit<AB: CmsgMut + ?Sized>(
+    fd: BorrowedFd<'_>,
+    buf: &mut [std::mem::MaybeUninit<u8>],
+    ancbuf: &mut AB,
+) -> io::Result<ReadAncillarySuccess> {
+    // The kernel never reads from the buffer, so it's fine for it to be uninitialized.
+    let mut iov = iovec {
+        iov_base: buf.as_mut_ptr().cast(),
+        iov_len: buf.len(),
+    };
+    recvmsg_iov(fd, &mut iov, 1, ancbuf, None)
+}
+fn recvmsg_iov<AB: CmsgMut + ?Sized>(
+    fd: BorrowedFd<'_>,
+    iov: *mut iovec,
+    iovlen: MsghdrIovlen,
+    ancbuf: &mut AB,
+    addrbuf: Option<&mut UdSocketPath<'_>>,
+) -> io::Result<ReadAncillarySuccess> {
     let mut hdr = make_msghdr(iov, iovlen);
     buf_to_msghdr(ancbuf, &mut hdr)?;
 
@@ -0,0 +1,153 @@
+//! Integration with the [`async-io`](https://docs.rs/async-io) reactor for the blocking Ud-socket types.
+//!
+//! With the `async-io` feature enabled, [`UdStream`], [`UdStreamListener`] and [`UdDatagram`] implement `IoSafe`,
+//! which means that wrapping them in `async_io::Async` yields objects which implement the `futures` I/O traits out of
+//! the box. On top of that, `Async<UdStream>` implements [`AsyncReadAncillary`] and [`AsyncWriteAncillary`], so that
+//! programs built on `smol` or other runtimes using the same reactor don't lose access to ancillary data.
+//!
+//! If you'd rather have a dedicated set of types with an API similar to that of the Tokio types, see the
+//! [`async_std`](super::async_std) module, which is built on the same foundation.
+//!
+//! # Example
+//! ```no_run
+//! # futures::executor::block_on(async {
+//! use async_io::Async;
+//! use futures::prelude::*;
+//! use interprocess::os::unix::udsocket::UdStream;
+//!
+//! let mut conn = Async::new(UdStream::connect("/tmp/example.sock")?)?;
+//! conn.write_all(b"Hello from client!\n").await?;
+//! # Ok::<(), Box<dyn std::error::Error>>(()) }).unwrap();
+//! ```
+
+use super::{
+    ancwrap,
+    cmsg::{CmsgMut, CmsgRef},
+    poll::{read_in_terms_of_vectored, write_in_terms_of_vectored},
+    AsyncReadAncillary, AsyncWriteAncillary, ReadAncillarySuccess, UdDatagram, UdStream, UdStreamListener,
+};
+use ::async_io::{Async, IoSafe};
+use futures_core::ready;
+use std::{
+    io::{self, IoSlice, IoSliceMut},
+    os::fd::AsFd,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+// SAFETY: none of those types provide a way to replace or close their file descriptor through a mutable reference.
+unsafe impl IoSafe for UdStream {}
+unsafe impl IoSafe for UdStreamListener {}
+unsafe impl IoSafe for UdDatagram {}
+
+/// Performs a read-like operation on the inner object, waiting for readability if it would block.
+pub(super) fn poll_read_with<T, R>(
+    slf: &Async<T>,
+    cx: &mut Context<'_>,
+    mut f: impl FnMut(&T) -> io::Result<R>,
+) -> Poll<io::Result<R>> {
+    loop {
+        match f(slf.get_ref()) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            els => return Poll::Ready(els),
+        }
+        ready!(slf.poll_readable(cx))?;
+    }
+}
+
+/// Performs a write-like operation on the inner object, waiting for writability if it would block.
+pub(super) fn poll_write_with<T, R>(
+    slf: &Async<T>,
+    cx: &mut Context<'_>,
+    mut f: impl FnMut(&T) -> io::Result<R>,
+) -> Poll<io::Result<R>> {
+    loop {
+        match f(slf.get_ref()) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            els => return Poll::Ready(els),
+        }
+        ready!(slf.poll_writable(cx))?;
+    }
+}
+
+impl<AB: CmsgMut + ?Sized> AsyncReadAncillary<AB> for &Async<UdStream> {
+    #[inline]
+    fn poll_read_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        read_in_terms_of_vectored(self, cx, buf, abuf)
+    }
+    #[inline]
+    fn poll_read_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        poll_read_with(*self, cx, |s| ancwrap::recvmsg(s.as_fd(), bufs, abuf, None))
+    }
+}
+impl<AB: CmsgMut + ?Sized> AsyncReadAncillary<AB> for Async<UdStream> {
+    #[inline(always)]
+    fn poll_read_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        Pin::new(&mut &*self).poll_read_ancillary(cx, buf, abuf)
+    }
+    #[inline(always)]
+    fn poll_read_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        Pin::new(&mut &*self).poll_read_ancillary_vectored(cx, bufs, abuf)
+    }
+}
+
+impl AsyncWriteAncillary for &Async<UdStream> {
+    #[inline]
+    fn poll_write_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        write_in_terms_of_vectored(self, cx, buf, abuf)
+    }
+    #[inline]
+    fn poll_write_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        poll_write_with(*self, cx, |s| ancwrap::sendmsg(s.as_fd(), bufs, abuf))
+    }
+}
+impl AsyncWriteAncillary for Async<UdStream> {
+    #[inline(always)]
+    fn poll_write_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_write_ancillary(cx, buf, abuf)
+    }
+    #[inline(always)]
+    fn poll_write_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_write_ancillary_vectored(cx, bufs, abuf)
+    }
+}
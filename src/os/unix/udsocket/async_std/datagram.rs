@@ -0,0 +1,244 @@
+use super::super::async_io::{poll_read_with, poll_write_with};
+use crate::{
+    datagram::{self, AsyncDatagram},
+    os::unix::{
+        udsocket::{
+            ancwrap, c_wrappers,
+            cmsg::{CmsgMut, CmsgRef},
+            datagram::try_recv_msg,
+            path::to_sockaddr_un,
+            ReadAncillarySuccess, ToUdSocketPath, UdDatagram as SyncUdDatagram, UdSocketPath,
+        },
+        unixprelude::*,
+    },
+    reliable_recv_msg::{AsyncReliableRecvMsg, RecvResult, TryRecvResult},
+};
+use async_io::Async;
+use futures_core::ready;
+use futures_util::future::poll_fn;
+use libc::sockaddr_un;
+use std::{
+    io::{self, IoSlice, IoSliceMut},
+    mem::MaybeUninit,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use to_method::To;
+
+/// A Unix domain datagram socket, obtained either by binding it to a path or by creating an unnamed one.
+///
+/// # Examples
+///
+/// ## Basic packet exchange
+/// ```no_run
+/// # futures::executor::block_on(async {
+/// use futures::try_join;
+/// use interprocess::os::unix::udsocket::async_std::*;
+///
+/// // Socket creation happens immediately, no futures here.
+/// let socket = UdDatagram::bound("/tmp/example_side_a.sock")?;
+///
+/// // This is the part where you tell the other side
+/// // that you've spun up a socket, if you need to.
+///
+/// // So does destination assignment.
+/// socket.set_destination("/tmp/example_side_b.sock")?;
+///
+/// // Allocate a stack buffer for reading at a later moment.
+/// let mut buffer = [0; 128];
+///
+/// // Describe the send operation, but don't run it yet.
+/// // We'll launch it concurrently with the read operation.
+/// let send = socket.send(b"Hello from side A!");
+///
+/// // Describe the receive operation, and also don't run it yet.
+/// let recv = socket.recv(&mut buffer);
+///
+/// // Perform both operations concurrently: the send and the receive.
+/// let (_, received) = try_join!(send, recv)?;
+///
+/// // Clean up early. Good riddance!
+/// drop(socket);
+///
+/// let received_string = String::from_utf8_lossy(&buffer[..received]);
+/// println!("Other side answered: {}", &received_string);
+/// # Ok::<(), Box<dyn std::error::Error>>(()) }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct UdDatagram(Async<SyncUdDatagram>);
+impl UdDatagram {
+    /// Creates an unnamed datagram socket.
+    pub fn unbound() -> io::Result<Self> {
+        Self::try_from(SyncUdDatagram::unbound()?).map_err(Into::into)
+    }
+    /// Creates a named datagram socket assigned to the specified path. This will be the "home" of this socket. Then,
+    /// packets from somewhere else directed to this socket with [`.send_to()`](Self::send_to) or
+    /// [`.set_destination()`](Self::set_destination) will go here.
+    ///
+    /// See [`ToUdSocketPath`] for an example of using various string types to specify socket paths.
+    pub fn bound<'a>(path: impl ToUdSocketPath<'a>) -> io::Result<Self> {
+        Self::try_from(SyncUdDatagram::bound(path)?).map_err(Into::into)
+    }
+    /// Selects the Unix domain socket to send packets to. You can also just use [`.send_to()`](Self::send_to) instead,
+    /// but supplying the address to the kernel once is more efficient.
+    ///
+    /// See [`ToUdSocketPath`] for an example of using various string types to specify socket paths.
+    pub fn set_destination<'a>(&self, path: impl ToUdSocketPath<'a>) -> io::Result<()> {
+        self.0.get_ref().set_destination(path)
+    }
+
+    /// Receives a single datagram from the socket, returning the amount of bytes received.
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        poll_fn(|cx| self.poll_recv(cx, buf)).await
+    }
+    /// Same as [`.recv()`](Self::recv), but accepts a possibly uninitialized buffer. The first that many bytes of the
+    /// buffer as the returned value are guaranteed to be initialized after the call.
+    pub async fn recv_uninit(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        poll_fn(|cx| self.poll_recv_uninit(cx, buf)).await
+    }
+    /// Receives a single datagram from the socket, making use of [scatter input].
+    ///
+    /// [scatter input]: https://en.wikipedia.org/wiki/Vectored_I/O " "
+    pub async fn recv_vectored(&self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        poll_fn(|cx| poll_read_with(&self.0, cx, |s| s.recv_vectored(bufs))).await
+    }
+    /// Receives a single datagram from the socket along with the control messages attached to it.
+    pub async fn recv_ancillary(&self, buf: &mut [u8], abuf: &mut impl CmsgMut) -> io::Result<ReadAncillarySuccess> {
+        self.recv_ancillary_vectored(&mut [IoSliceMut::new(buf)], abuf).await
+    }
+    /// Receives a single datagram from the socket along with the control messages attached to it, making use of
+    /// [scatter input].
+    ///
+    /// [scatter input]: https://en.wikipedia.org/wiki/Vectored_I/O " "
+    pub async fn recv_ancillary_vectored(
+        &self,
+        bufs: &mut [IoSliceMut<'_>],
+        abuf: &mut impl CmsgMut,
+    ) -> io::Result<ReadAncillarySuccess> {
+        poll_fn(|cx| poll_read_with(&self.0, cx, |s| ancwrap::recvmsg(s.as_fd(), bufs, abuf, None))).await
+    }
+    /// Asynchronously waits until readable data arrives to the socket.
+    ///
+    /// May finish spuriously – *do not* perform a blocking read when this future finishes and *do* handle a
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) or [`Poll::Pending`].
+    pub async fn recv_ready(&self) -> io::Result<()> {
+        self.0.readable().await
+    }
+
+    /// Sends a single datagram into the socket, returning how many bytes were actually sent.
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        poll_fn(|cx| self.poll_send(cx, buf)).await
+    }
+    /// Sends a single datagram into the socket, making use of [gather output].
+    ///
+    /// [gather output]: https://en.wikipedia.org/wiki/Vectored_I/O " "
+    pub async fn send_vectored(&self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        poll_fn(|cx| poll_write_with(&self.0, cx, |s| s.send_vectored(bufs))).await
+    }
+    /// Sends a single datagram into the socket along with the specified control messages.
+    pub async fn send_ancillary(&self, buf: &[u8], abuf: CmsgRef<'_>) -> io::Result<usize> {
+        self.send_ancillary_vectored(&[IoSlice::new(buf)], abuf).await
+    }
+    /// Sends a single datagram into the socket along with the specified control messages, making use of
+    /// [gather output].
+    ///
+    /// [gather output]: https://en.wikipedia.org/wiki/Vectored_I/O " "
+    pub async fn send_ancillary_vectored(&self, bufs: &[IoSlice<'_>], abuf: CmsgRef<'_>) -> io::Result<usize> {
+        poll_fn(|cx| poll_write_with(&self.0, cx, |s| ancwrap::sendmsg(s.as_fd(), bufs, abuf))).await
+    }
+    /// Sends a single datagram to the given address, returning how many bytes were actually sent.
+    pub async fn send_to(&self, buf: &[u8], path: impl ToUdSocketPath<'_>) -> io::Result<usize> {
+        let addr = to_sockaddr_un(path)?;
+        poll_fn(|cx| self.poll_send_to_addr(cx, buf, &addr)).await
+    }
+    /// Asynchronously waits until the socket becomes writable due to the other side freeing up space in its OS receive
+    /// buffer.
+    ///
+    /// May finish spuriously – *do not* perform a blocking write when this future finishes and *do* handle a
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) or [`Poll::Pending`].
+    pub async fn send_ready(&self) -> io::Result<()> {
+        self.0.writable().await
+    }
+
+    /// Raw polling interface for receiving datagrams. You probably want `.recv()` instead.
+    pub fn poll_recv(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        poll_read_with(&self.0, cx, |s| s.recv(buf))
+    }
+    /// Raw polling interface for receiving datagrams into a possibly uninitialized buffer. You probably want
+    /// `.recv_uninit()` instead.
+    pub fn poll_recv_uninit(&self, cx: &mut Context<'_>, buf: &mut [MaybeUninit<u8>]) -> Poll<io::Result<usize>> {
+        poll_read_with(&self.0, cx, |s| c_wrappers::recv(s.as_fd(), buf, 0))
+    }
+    /// Raw polling interface for sending datagrams. You probably want `.send()` instead.
+    pub fn poll_send(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        poll_write_with(&self.0, cx, |s| s.send(buf))
+    }
+    /// Raw polling interface for sending datagrams. You probably want `.send_to()` instead.
+    pub fn poll_send_to<'a>(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        path: impl ToUdSocketPath<'a>,
+    ) -> Poll<io::Result<usize>> {
+        let addr = to_sockaddr_un(path)?;
+        self.poll_send_to_addr(cx, buf, &addr)
+    }
+    fn poll_send_to_addr(&self, cx: &mut Context<'_>, buf: &[u8], addr: &sockaddr_un) -> Poll<io::Result<usize>> {
+        poll_write_with(&self.0, cx, |s| unsafe {
+            // SAFETY: addr is well-constructed
+            c_wrappers::sendto(s.as_fd(), buf, addr)
+        })
+    }
+}
+impl AsyncDatagram for UdDatagram {
+    type Address = UdSocketPath<'static>;
+    fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, msg: &[u8]) -> Poll<io::Result<()>> {
+        let sent = ready!(UdDatagram::poll_send(&self, cx, msg))?;
+        Poll::Ready(datagram::check_sent(sent, msg.len()))
+    }
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        UdDatagram::poll_recv(&self, cx, buf)
+    }
+    fn poll_send_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, parts: &[IoSlice<'_>]) -> Poll<io::Result<()>> {
+        // `sendmsg` rather than `writev`, which doesn't send empty datagrams.
+        let sent = ready!(poll_write_with(&self.0, cx, |s| s.send_ancillary_vectored(parts, CmsgRef::empty())))?;
+        Poll::Ready(datagram::check_sent(sent, datagram::total_len(parts)))
+    }
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        msg: &[u8],
+        addr: &Self::Address,
+    ) -> Poll<io::Result<()>> {
+        let addr = addr.borrow().try_to::<sockaddr_un>()?;
+        let sent = ready!(self.poll_send_to_addr(cx, msg, &addr))?;
+        Poll::Ready(datagram::check_sent(sent, msg.len()))
+    }
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Option<Self::Address>)>> {
+        let mut addr = UdSocketPath::buffer();
+        let size = ready!(poll_read_with(&self.0, cx, |s| s.recv_from(buf, &mut addr)))?;
+        Poll::Ready(Ok((size, addr.named())))
+    }
+}
+
+impl AsyncReliableRecvMsg for &UdDatagram {
+    fn poll_try_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<TryRecvResult>> {
+        poll_read_with(&self.0, cx, |s| try_recv_msg(s.as_fd(), buf))
+    }
+}
+impl AsyncReliableRecvMsg for UdDatagram {
+    #[inline]
+    fn poll_try_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<TryRecvResult>> {
+        Pin::new(&mut &*self).poll_try_recv(cx, buf)
+    }
+    #[inline]
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<RecvResult>> {
+        Pin::new(&mut &*self).poll_recv(cx, buf)
+    }
+}
+
+async_io_wrapper_trait_impls!(for UdDatagram, sync SyncUdDatagram);
@@ -0,0 +1,68 @@
+use super::{super::async_io::poll_read_with, UdStream};
+use crate::os::unix::udsocket::{ToUdSocketPath, UdSocketPath, UdStreamListener as SyncUdStreamListener};
+use async_io::Async;
+use futures_util::future::poll_fn;
+use std::io;
+
+/// A Unix domain byte stream socket server, listening for connections.
+///
+/// All such sockets have the `SOCK_STREAM` socket type; in other words, this is the Unix domain version of a TCP
+/// server.
+///
+/// # Examples
+///
+/// ## Basic server
+/// ```no_run
+/// # futures::executor::block_on(async {
+/// use futures::prelude::*;
+/// use interprocess::os::unix::udsocket::async_std::*;
+///
+/// let listener = UdStreamListener::bind("/tmp/example.sock")?;
+/// loop {
+///     let mut conn = match listener.accept().await {
+///         Ok(c) => c,
+///         Err(e) => {
+///             eprintln!("There was an error with an incoming connection: {e}");
+///             continue;
+///         }
+///     };
+///     let mut buffer = String::with_capacity(128);
+///     conn.read_to_string(&mut buffer).await?;
+///     conn.write_all(b"Hello from server!\n").await?;
+///     println!("Client answered: {}", buffer.trim());
+/// }
+/// # #[allow(unreachable_code)]
+/// # Ok::<(), Box<dyn std::error::Error>>(()) }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct UdStreamListener(Async<SyncUdStreamListener>);
+impl UdStreamListener {
+    /// Creates a new listener socket at the specified address.
+    ///
+    /// If the socket path exceeds the [maximum socket path length] (which includes the first 0 byte when using the
+    /// [socket namespace]), an error is returned. Errors can also be produced for different reasons, i.e. errors should
+    /// always be handled regardless of whether the path is known to be short enough or not.
+    ///
+    /// # Example
+    /// See [`ToUdSocketPath`].
+    ///
+    /// # System calls
+    /// - `socket`
+    /// - `bind`
+    ///
+    /// [maximum socket path length]: super::super::MAX_UDSOCKET_PATH_LEN
+    /// [socket namespace]: super::super::UdSocketPath::Namespaced
+    pub fn bind<'a>(path: impl ToUdSocketPath<'a>) -> io::Result<Self> {
+        Self::_bind(path.to_socket_path()?)
+    }
+    fn _bind(path: UdSocketPath<'_>) -> io::Result<Self> {
+        let listener = SyncUdStreamListener::_bind(path, false, true)?;
+        Self::try_from(listener).map_err(Into::into)
+    }
+    /// Listens for incoming connections to the socket, asynchronously waiting until a client is connected.
+    pub async fn accept(&self) -> io::Result<UdStream> {
+        let stream = poll_fn(|cx| poll_read_with(&self.0, cx, SyncUdStreamListener::accept)).await?;
+        UdStream::try_from(stream).map_err(Into::into)
+    }
+}
+async_io_wrapper_trait_impls!(for UdStreamListener, sync SyncUdStreamListener);
@@ -0,0 +1,18 @@
+//! Asynchronous Ud-sockets which work with the async-std runtime and event loop.
+//!
+//! async-std drives I/O using the reactor from the [`async-io`](https://docs.rs/async-io) crate, which is what the
+//! types in this module register themselves with. As a consequence, they work with any other runtime built on top of
+//! that reactor (such as `smol`) as well, and even with executors that don't provide a reactor of their own, since
+//! `async-io` spins up a fallback thread to drive it when needed.
+//!
+//! Unlike the socket types from async-std itself, the types in this module are built on top of the Ud-socket types
+//! from this crate, which means that they support ancillary data just like their [Tokio](super::tokio) counterparts.
+
+// contains macros, has to go before the other modules
+#[macro_use]
+mod util;
+
+mod datagram;
+mod listener;
+mod stream;
+pub use {datagram::*, listener::*, stream::*};
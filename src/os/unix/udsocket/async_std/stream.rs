@@ -0,0 +1,257 @@
+use super::super::async_io::{poll_read_with, poll_write_with};
+use crate::os::unix::udsocket::{
+    ancwrap,
+    cmsg::{CmsgMut, CmsgRef},
+    connect_future::ConnectFuture,
+    poll::{read_in_terms_of_vectored, write_in_terms_of_vectored},
+    AsyncReadAncillary, AsyncWriteAncillary, ReadAncillarySuccess, ToUdSocketPath, UdSocket, UdSocketPath,
+    UdStream as SyncUdStream,
+};
+use async_io::Async;
+use futures_io::{AsyncRead, AsyncWrite};
+use std::{
+    io::{self, prelude::*, IoSlice, IoSliceMut},
+    net::Shutdown,
+    os::fd::AsFd,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A Unix domain socket byte stream, obtained either from [`UdStreamListener`](super::UdStreamListener) or by
+/// connecting to an existing server.
+///
+/// # Examples
+///
+/// ## Basic client
+/// ```no_run
+/// # futures::executor::block_on(async {
+/// use futures::{prelude::*, try_join};
+/// use interprocess::os::unix::udsocket::{async_std::*, UdSocket};
+/// use std::net::Shutdown;
+///
+/// // Await this here since we can't do a whole lot without a connection.
+/// let conn = UdStream::connect("/tmp/example.sock").await?;
+///
+/// // Streams can be read from and written to by reference, which allows us to
+/// // perform both operations concurrently without splitting the stream.
+/// let (mut reader, mut writer) = (&conn, &conn);
+///
+/// // Allocate a sizeable buffer for reading.
+/// // This size should be enough and should be easy to find for the allocator.
+/// let mut buffer = String::with_capacity(128);
+///
+/// // Describe the write operation as writing our whole string, waiting for
+/// // that to complete, and then shutting down the write half, which sends
+/// // an EOF to the other end to help it determine where the message ends.
+/// let write = async {
+///     writer.write_all(b"Hello from client!\n").await?;
+///     conn.shutdown(Shutdown::Write)?;
+///     Ok(())
+/// };
+///
+/// // Describe the read operation as reading until EOF into our big buffer.
+/// let read = reader.read_to_string(&mut buffer);
+///
+/// // Concurrently perform both operations: write-and-send-EOF and read.
+/// try_join!(write, read)?;
+///
+/// // Close the connection a bit earlier than you'd think we would. Nice practice!
+/// drop(conn);
+///
+/// // Display the results when we're done!
+/// println!("Server answered: {}", buffer.trim());
+/// # Ok::<(), Box<dyn std::error::Error>>(()) }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct UdStream(Async<SyncUdStream>);
+impl UdStream {
+    /// Connects to a Unix domain socket server at the specified path.
+    ///
+    /// See [`ToUdSocketPath`] for an example of using various string types to specify socket paths.
+    pub async fn connect(path: impl ToUdSocketPath<'_>) -> io::Result<Self> {
+        let path = path.to_socket_path()?;
+        Self::_connect(&path).await
+    }
+    async fn _connect(path: &UdSocketPath<'_>) -> io::Result<Self> {
+        let stream = ConnectFuture { path }.await?;
+        Self::try_from(stream).map_err(|e| e.cause.unwrap())
+    }
+    /// Asynchronously waits until readable data arrives to the socket.
+    ///
+    /// May finish spuriously – *do not* perform a blocking read when this future finishes and *do* handle a
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) or [`Poll::Pending`].
+    pub async fn read_ready(&self) -> io::Result<()> {
+        self.0.readable().await
+    }
+    /// Asynchronously waits until the socket becomes writable due to the other side freeing up space in its OS receive
+    /// buffer.
+    ///
+    /// May finish spuriously – *do not* perform a blocking write when this future finishes and *do* handle a
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) or [`Poll::Pending`].
+    pub async fn write_ready(&self) -> io::Result<()> {
+        self.0.writable().await
+    }
+}
+async_io_wrapper_trait_impls!(for UdStream, sync SyncUdStream);
+
+impl AsyncRead for &UdStream {
+    #[inline]
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        poll_read_with(&self.0, cx, |s| (&*s).read(buf))
+    }
+    #[inline]
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        poll_read_with(&self.0, cx, |s| (&*s).read_vectored(bufs))
+    }
+}
+impl AsyncRead for UdStream {
+    #[inline(always)]
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_read(cx, buf)
+    }
+    #[inline(always)]
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<AB: CmsgMut + ?Sized> AsyncReadAncillary<AB> for &UdStream {
+    #[inline]
+    fn poll_read_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        read_in_terms_of_vectored(self, cx, buf, abuf)
+    }
+    #[inline]
+    fn poll_read_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        poll_read_with(&self.0, cx, |s| ancwrap::recvmsg(s.as_fd(), bufs, abuf, None))
+    }
+}
+impl<AB: CmsgMut + ?Sized> AsyncReadAncillary<AB> for UdStream {
+    #[inline(always)]
+    fn poll_read_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        Pin::new(&mut &*self).poll_read_ancillary(cx, buf, abuf)
+    }
+    #[inline(always)]
+    fn poll_read_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+        abuf: &mut AB,
+    ) -> Poll<io::Result<ReadAncillarySuccess>> {
+        Pin::new(&mut &*self).poll_read_ancillary_vectored(cx, bufs, abuf)
+    }
+}
+
+impl AsyncWrite for &UdStream {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        poll_write_with(&self.0, cx, |s| (&*s).write(buf))
+    }
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        poll_write_with(&self.0, cx, |s| (&*s).write_vectored(bufs))
+    }
+    /// Does nothing and finishes immediately, as sockets cannot be flushed.
+    #[inline(always)]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    /// Finishes immediately. See the `.shutdown()` method.
+    #[inline(always)]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.shutdown(Shutdown::Both)?;
+        Poll::Ready(Ok(()))
+    }
+}
+impl AsyncWrite for UdStream {
+    #[inline(always)]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_write(cx, buf)
+    }
+    #[inline(always)]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_write_vectored(cx, bufs)
+    }
+    /// Does nothing and finishes immediately, as sockets cannot be flushed.
+    #[inline(always)]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    /// Finishes immediately. See the `.shutdown()` method.
+    #[inline(always)]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.shutdown(Shutdown::Both)?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWriteAncillary for &UdStream {
+    #[inline]
+    fn poll_write_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        write_in_terms_of_vectored(self, cx, buf, abuf)
+    }
+    #[inline]
+    fn poll_write_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        poll_write_with(&self.0, cx, |s| ancwrap::sendmsg(s.as_fd(), bufs, abuf))
+    }
+}
+impl AsyncWriteAncillary for UdStream {
+    #[inline(always)]
+    fn poll_write_ancillary(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_write_ancillary(cx, buf, abuf)
+    }
+    #[inline(always)]
+    fn poll_write_ancillary_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+        abuf: CmsgRef<'_>,
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &*self).poll_write_ancillary_vectored(cx, bufs, abuf)
+    }
+}
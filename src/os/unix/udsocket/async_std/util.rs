@@ -0,0 +1,65 @@
+macro_rules! async_io_wrapper_trait_impls {
+    (for $slf:ty, sync $sync:ty) => {
+        impl ::std::os::unix::io::AsFd for $slf {
+            #[inline]
+            fn as_fd(&self) -> ::std::os::unix::io::BorrowedFd<'_> {
+                ::std::os::unix::io::AsFd::as_fd(&self.0)
+            }
+        }
+        /// Unwraps into the `async-io` adapter around the corresponding blocking type. This is a zero-cost operation.
+        impl From<$slf> for ::async_io::Async<$sync> {
+            #[inline]
+            fn from(x: $slf) -> Self {
+                x.0
+            }
+        }
+        /// Wraps the `async-io` adapter around the corresponding blocking type. This is a zero-cost operation.
+        impl From<::async_io::Async<$sync>> for $slf {
+            #[inline]
+            fn from(a: ::async_io::Async<$sync>) -> Self {
+                Self(a)
+            }
+        }
+        /// Deregisters the async object from the reactor and converts it to a blocking one.
+        ///
+        /// The object stays in nonblocking mode after the conversion.
+        impl ::std::convert::TryFrom<$slf> for $sync {
+            type Error = crate::error::ConversionError<$slf>;
+            #[inline]
+            fn try_from(x: $slf) -> Result<Self, Self::Error> {
+                x.0.into_inner().map_err(crate::error::ConversionError::from_cause)
+            }
+        }
+        /// Registers a blocking object in the reactor, putting it in nonblocking mode in the process.
+        impl ::std::convert::TryFrom<$sync> for $slf {
+            type Error = crate::error::ConversionError<$sync>;
+            #[inline]
+            fn try_from(sync: $sync) -> Result<Self, Self::Error> {
+                ::async_io::Async::new(sync)
+                    .map(Self)
+                    .map_err(crate::error::ConversionError::from_cause)
+            }
+        }
+        /// Deregisters the async object from the reactor and returns its file descriptor as an
+        /// [`OwnedFd`](::std::os::unix::io::OwnedFd).
+        impl ::std::convert::TryFrom<$slf> for ::std::os::unix::io::OwnedFd {
+            type Error = crate::error::ConversionError<$slf>;
+            #[inline]
+            fn try_from(x: $slf) -> Result<Self, Self::Error> {
+                <$sync>::try_from(x).map(::std::convert::From::from)
+            }
+        }
+        /// Creates an async object from a given owned file descriptor, registering it in the reactor and putting it in
+        /// nonblocking mode in the process.
+        impl ::std::convert::TryFrom<::std::os::unix::io::OwnedFd> for $slf {
+            type Error = crate::error::FromFdError;
+            #[inline]
+            fn try_from(fd: ::std::os::unix::io::OwnedFd) -> Result<Self, Self::Error> {
+                ::async_io::Async::new(<$sync>::from(fd))
+                    .map(Self)
+                    .map_err(crate::error::ConversionError::from_cause)
+            }
+        }
+        derive_asraw!(unix: $slf);
+    };
+}
@@ -1,10 +1,11 @@
 use crate::os::unix::{unixprelude::*, FdOps};
-use libc::{msghdr, sockaddr, sockaddr_un, socklen_t, AF_UNIX, O_NONBLOCK, SHUT_RD, SHUT_RDWR, SHUT_WR};
+use libc::{msghdr, sockaddr, sockaddr_un, socklen_t, AF_UNIX, SHUT_RD, SHUT_RDWR, SHUT_WR};
 use std::{
     ffi::c_void,
     io,
-    mem::{size_of, size_of_val},
+    mem::{size_of, size_of_val, MaybeUninit},
     net::Shutdown,
+    ptr,
 };
 
 #[cfg_attr(target_os = "linux", allow(unused))]
@@ -57,7 +58,14 @@ fn create_uds_raw(ty: c_int) -> io::Result<FdOps> {
 /// Pointers in `hdr` must not dangle, and ancillary data must be correct.
 #[allow(unused_mut)]
 pub(super) unsafe fn recvmsg(fd: BorrowedFd<'_>, hdr: &mut msghdr, mut flags: c_int) -> io::Result<usize> {
-    #[cfg(target_os = "linux")]
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
     {
         flags |= libc::MSG_CMSG_CLOEXEC;
     }
@@ -69,6 +77,57 @@ pub(super) unsafe fn recvmsg(fd: BorrowedFd<'_>, hdr: &mut msghdr, mut flags: c_
 
     ok_or_ret_errno!(success => bytes_read)
 }
+/// Receives data from the given socket into a possibly uninitialized buffer, returning how many bytes were written
+/// into the beginning of the buffer.
+pub(super) fn recv(fd: BorrowedFd<'_>, buf: &mut [MaybeUninit<u8>], flags: c_int) -> io::Result<usize> {
+    let (success, bytes_read) = unsafe {
+        // SAFETY: the kernel never reads from the buffer, so it's fine for it to be uninitialized
+        let result = libc::recv(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), flags);
+        (result != -1, result as usize)
+    };
+    ok_or_ret_errno!(success => bytes_read)
+}
+/// Copies the data available on the given stream socket into the buffer without consuming it, waiting for some to
+/// arrive first unless the socket is in nonblocking mode.
+pub(super) fn peek(fd: BorrowedFd<'_>, buf: &mut [u8]) -> io::Result<usize> {
+    recv(fd, crate::weaken_buf_init_mut(buf), libc::MSG_PEEK)
+}
+/// Returns the size of the next datagram available on the given socket without discarding it.
+///
+/// Linux reports the full size of a datagram peeked at with `MSG_TRUNC` regardless of the size of the buffer. Other
+/// systems only report whether it was truncated, and so the datagram is peeked at with a buffer which keeps doubling
+/// in size until the whole datagram fits.
+pub(super) fn peek_msg_size(fd: BorrowedFd<'_>) -> io::Result<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        recv(fd, &mut [], libc::MSG_PEEK | libc::MSG_TRUNC)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let mut buf = vec![0_u8; 1024];
+        loop {
+            let mut iov = libc::iovec {
+                iov_base: buf.as_mut_ptr().cast(),
+                iov_len: buf.len(),
+            };
+            let mut hdr = unsafe {
+                // SAFETY: msghdr is POD
+                std::mem::zeroed::<msghdr>()
+            };
+            hdr.msg_iov = &mut iov;
+            hdr.msg_iovlen = 1;
+            let size = unsafe {
+                // SAFETY: the only pointer in the header is to the buffer, which outlives the call
+                recvmsg(fd, &mut hdr, libc::MSG_PEEK)
+            }?;
+            if hdr.msg_flags & libc::MSG_TRUNC == 0 {
+                return Ok(size);
+            }
+            let new_len = buf.len() * 2;
+            buf.resize(new_len, 0);
+        }
+    }
+}
 /// Writes stream data and ancillary data from the given socket. Pointers are supplied directly via the `msghdr`.
 ///
 /// # Safety
@@ -81,6 +140,25 @@ pub(super) unsafe fn sendmsg(fd: BorrowedFd<'_>, hdr: &msghdr, flags: c_int) ->
     ok_or_ret_errno!(success => bytes_written)
 }
 
+/// Sends a datagram from the given socket to the specified address.
+///
+/// # Safety
+/// `addr` must be properly null-terminated.
+pub(super) unsafe fn sendto(fd: BorrowedFd<'_>, buf: &[u8], addr: &sockaddr_un) -> io::Result<usize> {
+    let (success, bytes_written) = unsafe {
+        let result = libc::sendto(
+            fd.as_raw_fd(),
+            buf.as_ptr().cast(),
+            buf.len(),
+            0,
+            addr as *const _ as *const sockaddr,
+            size_of::<sockaddr_un>() as u32,
+        );
+        (result != -1, result as usize)
+    };
+    ok_or_ret_errno!(success => bytes_written)
+}
+
 /// Binds the specified Ud-socket file descriptor to the given address.
 ///
 /// # Safety
@@ -106,13 +184,51 @@ pub(super) unsafe fn connect(fd: BorrowedFd<'_>, addr: &sockaddr_un) -> io::Resu
     let success = unsafe {
         libc::connect(
             fd.as_raw_fd(),
-            (addr as *const sockaddr_un).cast(),
+            addr as *const _ as *const sockaddr,
             size_of::<sockaddr_un>() as _,
         ) != -1
     };
     ok_or_ret_errno!(success => ())
 }
 
+/// Accepts a connection on the given listening socket, setting the close-on-exec flag on it in the same system call on
+/// platforms which have `accept4`.
+pub(super) fn accept(fd: BorrowedFd<'_>) -> io::Result<OwnedFd> {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    let result = unsafe { libc::accept4(fd.as_raw_fd(), ptr::null_mut(), ptr::null_mut(), libc::SOCK_CLOEXEC) };
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    )))]
+    let result = unsafe { libc::accept(fd.as_raw_fd(), ptr::null_mut(), ptr::null_mut()) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: we just created the file descriptor
+    let fd = unsafe { OwnedFd::from_raw_fd(result) };
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    )))]
+    set_cloexec(fd.as_fd())?;
+    Ok(fd)
+}
+
 pub(super) fn listen(fd: BorrowedFd<'_>, backlog: c_int) -> io::Result<()> {
     let success = unsafe { libc::listen(fd.as_raw_fd(), backlog) != -1 };
     ok_or_ret_errno!(success => ())
@@ -165,30 +281,49 @@ pub(super) fn get_peer_xucred(fd: BorrowedFd<'_>) -> io::Result<libc::xucred> {
     Ok(cred)
 }
 
-fn get_status_flags(fd: BorrowedFd<'_>) -> io::Result<c_int> {
-    unsafe { fcntl_noarg(fd, libc::F_GETFL) }
+#[cfg(feature = "tokio")]
+pub(super) fn get_peer_addr(fd: BorrowedFd<'_>) -> io::Result<super::UdSocketPath<'static>> {
+    // SAFETY: sockaddr_un is POD
+    let mut addr = unsafe { std::mem::zeroed::<sockaddr_un>() };
+    let mut addrlen = size_of_val(&addr) as socklen_t;
+    let success =
+        unsafe { libc::getpeername(fd.as_raw_fd(), &mut addr as *mut _ as *mut sockaddr, &mut addrlen) != -1 };
+    ok_or_ret_errno!(success => ())?;
+    let mut path = super::UdSocketPath::buffer();
+    path.write_sockaddr_un_to_self(&addr, addrlen as _);
+    Ok(path)
 }
-fn set_status_flags(fd: BorrowedFd<'_>, new_flags: c_int) -> io::Result<()> {
-    unsafe {
-        fcntl_int(fd, libc::F_SETFL, new_flags)?;
+
+/// Returns whether the file descriptor is a Unix domain socket of the given type, as opposed to some other socket or not
+/// a socket at all.
+pub(super) fn is_uds_of_type(fd: BorrowedFd<'_>, ty: c_int) -> io::Result<bool> {
+    let mut actual_ty: c_int = 0;
+    match get_socket_option(fd, libc::SOL_SOCKET, libc::SO_TYPE, &mut actual_ty) {
+        Err(e) if e.raw_os_error() == Some(libc::ENOTSOCK) => return Ok(false),
+        els => els?,
+    };
+    if actual_ty != ty {
+        return Ok(false);
     }
-    Ok(())
+    // SAFETY: sockaddr_un is POD
+    let mut addr = unsafe { std::mem::zeroed::<sockaddr_un>() };
+    let mut addrlen = size_of_val(&addr) as socklen_t;
+    let success =
+        unsafe { libc::getsockname(fd.as_raw_fd(), &mut addr as *mut _ as *mut sockaddr, &mut addrlen) != -1 };
+    ok_or_ret_errno!(success => c_int::from(addr.sun_family) == AF_UNIX)
 }
-pub(super) fn set_nonblocking(fd: BorrowedFd<'_>, nonblocking: bool) -> io::Result<()> {
-    let old_flags = get_status_flags(fd)?;
-    let new_flags = if nonblocking {
-        old_flags | O_NONBLOCK
-    } else {
-        // Inverting the O_NONBLOCK value sets all the bits in the flag set to 1 except for the
-        // nonblocking flag, which clears the flag when ANDed.
-        old_flags & !O_NONBLOCK
+
+/// Returns whether both file descriptors refer to the same open file, such as when one is a duplicate of the other.
+pub(super) fn is_same_file(a: BorrowedFd<'_>, b: BorrowedFd<'_>) -> io::Result<bool> {
+    let stat = |fd: BorrowedFd<'_>| {
+        let mut stat = MaybeUninit::<libc::stat>::uninit();
+        let success = unsafe { libc::fstat(fd.as_raw_fd(), stat.as_mut_ptr()) != -1 };
+        ok_or_ret_errno!(success => unsafe { stat.assume_init() })
     };
-    set_status_flags(fd, new_flags)
-}
-pub(super) fn get_nonblocking(fd: BorrowedFd<'_>) -> io::Result<bool> {
-    let flags = get_status_flags(fd)?;
-    Ok(flags & O_NONBLOCK != 0)
+    let (a, b) = (stat(a)?, stat(b)?);
+    Ok(a.st_dev == b.st_dev && a.st_ino == b.st_ino)
 }
+
 pub(super) fn shutdown(fd: BorrowedFd<'_>, how: Shutdown) -> io::Result<()> {
     let how = match how {
         Shutdown::Read => SHUT_RD,
@@ -1,7 +1,8 @@
 //! [`FileDescriptors`] and associated helper types.
 use super::*;
 use std::{
-    mem::{align_of, size_of, transmute},
+    io,
+    mem::{self, size_of, transmute},
     os::fd::{BorrowedFd, FromRawFd, OwnedFd, RawFd},
     slice,
 };
@@ -30,6 +31,39 @@ impl<'a> FileDescriptors<'a> {
     pub const unsafe fn new_raw(descriptors: &'a [RawFd], owned: bool) -> Self {
         unsafe { Self(UnalignedFdSlice::from_raw_fd_slice(descriptors, owned)) }
     }
+    /// Returns the number of file descriptors in the message.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.0.fds.len()
+    }
+    /// Returns `true` if the message contains no file descriptors.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0.fds.is_empty()
+    }
+    /// Takes ownership of the file descriptors, typically ones received from another process, so that they can be
+    /// turned into the types they stand for via `From<OwnedFd>`.
+    ///
+    /// File descriptors that the message only borrows are duplicated instead.
+    ///
+    /// # System calls
+    /// - `fcntl` (`F_DUPFD_CLOEXEC`), for borrowed file descriptors
+    pub fn into_owned_fds(mut self) -> io::Result<Vec<OwnedFd>> {
+        let mut slice = mem::take(&mut self.0);
+        let owned = mem::replace(&mut slice.owned, false);
+        if owned {
+            // SAFETY: the owned flag doesn't lie, and it's been cleared so that the slice won't close them itself
+            return Ok(slice.fds.iter().map(|fd| unsafe { fd.into_owned_fd() }).collect());
+        }
+        slice
+            .fds
+            .iter()
+            .map(|fd| {
+                // SAFETY: borrowed file descriptors are valid for the lifetime of the message
+                unsafe { BorrowedFd::borrow_raw(fd.to_raw()) }.try_clone_to_owned()
+            })
+            .collect()
+    }
 }
 impl ToCmsg for FileDescriptors<'_> {
     #[inline]
@@ -46,11 +80,11 @@ impl<'a> FromCmsg<'a> for FileDescriptors<'a> {
 
     fn try_parse(mut cmsg: Cmsg<'a>) -> ParseResult<'a, Self, Self::MalformedPayloadError> {
         cmsg = check_level_and_type(cmsg, Self::ANCTYPE)?;
-        let unalign_mask = (1_usize << align_of::<c_int>()) - 1;
+        let unalign_mask = size_of::<c_int>() - 1;
         let len = cmsg.data().len();
         if len & unalign_mask != 0 {
             return Err(ParseErrorKind::MalformedPayload(SizeMismatch {
-                expected: len + 1,
+                expected: (len | unalign_mask) + 1,
                 got: len,
             })
             .wrap(cmsg));
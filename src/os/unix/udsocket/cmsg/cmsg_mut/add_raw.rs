@@ -23,7 +23,7 @@ fn locate_next_cmsghdr_idx(buf: &[MUu8]) -> Option<usize> {
     }
     let base_idx = unsafe {
         // SAFETY: CMSG_NXTHDR never returns a pointer outside the buffer if the return value is non-null
-        base.offset_from(cur)
+        base.cast::<u8>().offset_from(cur.cast::<u8>())
     };
     debug_assert!(base_idx >= 0);
     Some(base_idx as usize)
@@ -105,7 +105,8 @@ pub(super) fn add_raw_message(buf: &mut (impl CmsgMut + ?Sized), cmsg: Cmsg<'_>)
     valid_incr += data_range.len();
 
     // Get an offset to the end of the buffer if another control message wouldn't fit.
-    let next_cmsghdr_base_offset = locate_next_cmsghdr_idx(buf.uninit_part()).unwrap_or_else(|| buf.capacity());
+    let next_cmsghdr_base_offset =
+        locate_next_cmsghdr_idx(buf.uninit_part()).unwrap_or_else(|| buf.uninit_part().len());
 
     // The spacer between the end of the control message body and the next cmsghdr.
     let post_data_spacer = &mut buf.uninit_part()[end_of_data_range..next_cmsghdr_base_offset];
@@ -1,16 +1,14 @@
 use super::{
     ancwrap, c_wrappers,
     cmsg::{CmsgMut, CmsgMutBuf, CmsgRef},
+    path::to_sockaddr_un,
     PathDropGuard, ReadAncillarySuccess, ToUdSocketPath, UdSocketPath,
 };
 use crate::{
+    datagram::{self, Datagram},
     os::unix::{unixprelude::*, FdOps},
-    TryClone,
-};
-#[cfg(target_os = "linux")]
-use crate::{
     reliable_recv_msg::{ReliableRecvMsg, TryRecvResult},
-    Sealed,
+    weaken_buf_init_mut, Sealed, TryClone,
 };
 use libc::sockaddr_un;
 use std::io::{self, prelude::*, IoSlice, IoSliceMut};
@@ -139,11 +137,7 @@ impl UdDatagram {
     /// # System calls
     /// - `connect`
     pub fn set_destination<'a>(&self, path: impl ToUdSocketPath<'a>) -> io::Result<()> {
-        let path = path.to_socket_path()?;
-        self._set_destination(&path)
-    }
-    fn _set_destination(&self, path: &UdSocketPath<'_>) -> io::Result<()> {
-        let addr = path.borrow().try_to::<sockaddr_un>()?;
+        let addr = to_sockaddr_un(path)?;
         unsafe {
             // SAFETY: addr is well-constructed
             c_wrappers::connect(self.fd.0.as_fd(), &addr)
@@ -264,25 +258,15 @@ impl UdDatagram {
 
     /// Returns the size of the next datagram available on the socket without discarding it.
     ///
-    /// This method is only available on Linux.2. On other platforms, it's absent and thus any usage of it will result
-    /// in a compile-time error.
+    /// On Linux, the size is reported by the system directly. Elsewhere, the datagram is peeked at with a buffer that
+    /// grows until the whole datagram fits into it, which takes several system calls and allocations for large
+    /// datagrams.
     ///
     /// # System calls
-    /// - `recv`
-    #[cfg(target_os = "linux")]
-    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    /// - `recv` on Linux
+    /// - `recvmsg`, potentially repeatedly, on other platforms
     pub fn peek_msg_size(&self) -> io::Result<usize> {
-        let mut buffer = [0_u8; 0];
-        let (success, size) = unsafe {
-            let size = libc::recv(
-                self.as_raw_fd(),
-                buffer.as_mut_ptr().cast(),
-                buffer.len(),
-                libc::MSG_TRUNC | libc::MSG_PEEK,
-            );
-            (size != -1, size as usize)
-        };
-        ok_or_ret_errno!(success => size)
+        c_wrappers::peek_msg_size(self.as_fd())
     }
 
     /// Sends a datagram into the socket.
@@ -293,7 +277,18 @@ impl UdDatagram {
     pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
         (&self.fd).write(buf)
     }
-    // TODO sendto
+    /// Sends a datagram to the socket at the given path, regardless of the [destination](Self::set_destination), if
+    /// any.
+    ///
+    /// # System calls
+    /// - `sendto`
+    pub fn send_to<'a>(&self, buf: &[u8], path: impl ToUdSocketPath<'a>) -> io::Result<usize> {
+        let addr = to_sockaddr_un(path)?;
+        unsafe {
+            // SAFETY: addr is well-constructed
+            c_wrappers::sendto(self.fd.0.as_fd(), buf, &addr)
+        }
+    }
     /// Sends a datagram into the socket, making use of [gather output] for the main data.
     ///
     ///
@@ -325,21 +320,45 @@ impl UdDatagram {
     }
 }
 
-#[cfg(target_os = "linux")]
-#[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
 impl ReliableRecvMsg for UdDatagram {
     fn try_recv(&mut self, buf: &mut [u8]) -> io::Result<TryRecvResult> {
-        let mut size = self.peek_msg_size()?;
-        let fit = size > buf.len();
-        if fit {
-            size = UdDatagram::recv(self, buf)?;
-        }
-        Ok(TryRecvResult { size, fit })
+        try_recv_msg(self.as_fd(), buf)
+    }
+}
+/// Receives the next datagram if it fits into the buffer, also used by the asynchronous datagram sockets.
+pub(super) fn try_recv_msg(fd: BorrowedFd<'_>, buf: &mut [u8]) -> io::Result<TryRecvResult> {
+    let mut size = c_wrappers::peek_msg_size(fd)?;
+    let fit = size <= buf.len();
+    if fit {
+        size = c_wrappers::recv(fd, weaken_buf_init_mut(buf), 0)?;
     }
+    Ok(TryRecvResult { size, fit })
 }
-#[cfg(target_os = "linux")]
 impl Sealed for UdDatagram {}
 
+impl Datagram for UdDatagram {
+    type Address = UdSocketPath<'static>;
+    fn send(&mut self, msg: &[u8]) -> io::Result<()> {
+        datagram::check_sent(UdDatagram::send(self, msg)?, msg.len())
+    }
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        UdDatagram::recv(self, buf)
+    }
+    fn send_vectored(&mut self, parts: &[IoSlice<'_>]) -> io::Result<()> {
+        // Unlike `sendmsg`, `writev` doesn't send anything if all of the slices are empty.
+        let sent = self.send_ancillary_vectored(parts, CmsgRef::empty())?;
+        datagram::check_sent(sent, datagram::total_len(parts))
+    }
+    fn send_to(&mut self, msg: &[u8], addr: &Self::Address) -> io::Result<()> {
+        datagram::check_sent(UdDatagram::send_to(self, msg, addr)?, msg.len())
+    }
+    fn recv_from(&mut self, buf: &mut [u8]) -> io::Result<(usize, Option<Self::Address>)> {
+        let mut addr = UdSocketPath::buffer();
+        let size = UdDatagram::recv_from(self, buf, &mut addr)?;
+        Ok((size, addr.named()))
+    }
+}
+
 impl TryClone for UdDatagram {
     fn try_clone(&self) -> io::Result<Self> {
         Ok(Self {
@@ -370,3 +389,4 @@ impl From<OwnedFd> for UdDatagram {
     }
 }
 derive_raw!(unix: UdDatagram);
+derive_mio_source!(UdDatagram);
@@ -1,7 +1,7 @@
 use super::{c_wrappers, PathDropGuard, ToUdSocketPath, UdSocketPath, UdStream};
 use crate::{
     os::unix::{unixprelude::*, FdOps},
-    TryClone,
+    Sealed, TryClone,
 };
 use libc::{sockaddr_un, SOCK_STREAM};
 use std::{
@@ -86,6 +86,20 @@ impl UdStreamListener {
     pub fn bind_with_drop_guard<'a>(path: impl ToUdSocketPath<'a>) -> io::Result<Self> {
         Self::_bind(path.to_socket_path()?, true, false)
     }
+    /// Adopts the listening socket which launchd created for the calling job under the given name, as described in
+    /// the [`launchd`](crate::os::unix::launchd) module.
+    ///
+    /// This function is only available on macOS. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `launch_activate_socket`
+    /// - `fcntl`
+    #[cfg(target_os = "macos")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "macos")))]
+    pub fn from_launchd(name: &str) -> io::Result<Self> {
+        crate::os::unix::launchd::activate_listener(name).map(Self::from)
+    }
     pub(crate) fn _bind(path: UdSocketPath<'_>, keep_drop_guard: bool, nonblocking: bool) -> io::Result<Self> {
         let addr = path.borrow().try_to::<sockaddr_un>()?;
 
@@ -159,6 +173,47 @@ impl UdStreamListener {
         }
     }
 
+    /// Accepts up to `max` connections, appending them to `conns` and returning how many were accepted.
+    ///
+    /// In nonblocking mode, this accepts all connections which are already waiting to be accepted, up to `max`, so that
+    /// servers woken up by a storm of connections can take them all in one go instead of waiting for a wakeup for each
+    /// one. If no connections are waiting, an error of kind [`WouldBlock`](io::ErrorKind::WouldBlock) is returned. In
+    /// blocking mode, this waits until `max` connections have been accepted.
+    ///
+    /// An error is only returned if no connection has been accepted. Otherwise, the connections accepted before it are
+    /// kept, and the error, if it's persistent, is returned by the next call.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use interprocess::os::unix::udsocket::UdStreamListener;
+    ///
+    /// let listener = UdStreamListener::bind("/tmp/example.sock")?;
+    /// listener.set_nonblocking(true)?;
+    /// let mut conns = Vec::new();
+    /// // Typically called when a poller reports the listener as readable.
+    /// match listener.accept_many(&mut conns, 64) {
+    ///     Ok(n) => println!("{n} new clients!"),
+    ///     Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # System calls
+    /// - `accept4` (`accept` and `fcntl` on platforms which don't have it), repeatedly
+    pub fn accept_many(&self, conns: &mut Vec<UdStream>, max: usize) -> io::Result<usize> {
+        let mut accepted = 0;
+        while accepted < max {
+            match c_wrappers::accept(self.fd.0.as_fd()) {
+                Ok(fd) => conns.push(UdStream::from(fd)),
+                Err(e) if accepted == 0 => return Err(e),
+                Err(..) => break,
+            }
+            accepted += 1;
+        }
+        Ok(accepted)
+    }
+
     /// Creates an infinite iterator which calls `accept()` with each iteration. Used together with `for` loops to
     /// conveniently create a main loop for a socket server.
     ///
@@ -227,6 +282,7 @@ impl From<OwnedFd> for UdStreamListener {
         }
     }
 }
+impl Sealed for UdStreamListener {}
 impl TryClone for UdStreamListener {
     fn try_clone(&self) -> io::Result<Self> {
         let s = Self {
@@ -237,6 +293,7 @@ impl TryClone for UdStreamListener {
     }
 }
 derive_raw!(unix: UdStreamListener);
+derive_mio_source!(UdStreamListener);
 
 /// An infinite iterator over incoming client connections of a [`UdStreamListener`].
 ///
@@ -38,6 +38,18 @@ pub mod credentials;
 #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
 pub mod tokio;
 
+#[cfg(feature = "async-std")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async-std")))]
+pub mod async_std;
+
+#[cfg(feature = "async-io")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "async-io")))]
+pub mod async_io;
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(all(target_os = "linux", feature = "io-uring"))))]
+pub mod uring;
+
 #[macro_use]
 mod util;
 
@@ -45,9 +57,11 @@ mod ancillary_io;
 mod datagram;
 mod listener;
 mod path;
+mod seqpacket;
 mod socket_trait;
 mod stream;
 
+pub(crate) use seqpacket::*;
 pub use {ancillary_io::*, datagram::*, listener::*, path::*, socket_trait::*, stream::*};
 
 mod path_drop_guard;
@@ -56,6 +70,9 @@ use path_drop_guard::*;
 mod ancwrap;
 mod c_wrappers;
 
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+mod connect_future;
+
 /// The maximum path length for Unix domain sockets. [`UdStreamListener::bind()`] panics if the length of the specified
 /// path exceeds this value.
 ///
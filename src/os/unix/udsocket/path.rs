@@ -161,11 +161,18 @@ impl<'a> UdSocketPath<'a> {
         }
     }
 
+    /// Returns the path unless it's [`Unnamed`](Self::Unnamed), as received from a socket that isn't bound to one.
+    pub(super) fn named(self) -> Option<Self> {
+        match self {
+            Self::Unnamed => None,
+            els => Some(els),
+        }
+    }
     pub(super) fn write_sockaddr_un_to_self(&mut self, addr: &sockaddr_un, addrlen: usize) {
         let sun_path_length = (addrlen as isize) - (size_of_val(&addr.sun_family) as isize);
         let sun_path_length = match usize::try_from(sun_path_length) {
-            Ok(val) => val,
-            Err(..) => {
+            Ok(val) if val > 0 => val,
+            _ => {
                 *self = Self::Unnamed;
                 return;
             }
@@ -188,10 +195,9 @@ impl<'a> UdSocketPath<'a> {
                 vec.resize(path_length, 0);
                 ptr::copy_nonoverlapping(src_ptr, vec.as_mut_ptr(), path_length);
             };
-            // If the system added a nul byte as part of the length, remove the one we added ourselves.
-            if vec.last() == Some(&0) && vec[vec.len() - 2] == 0 {
-                vec.pop();
-            }
+            // The address may be padded with nul bytes past the end of the name, either by the system or because the
+            // peer bound its socket with the full length of `sockaddr_un`. Cut off everything past the first one.
+            truncate_at_nul(&mut vec);
             let new_cstring = CString::new(vec).unwrap_or_else(eunreachable);
             #[cfg(uds_linux_namespace)]
             let path_to_write = if _namespaced {
@@ -207,6 +213,7 @@ impl<'a> UdSocketPath<'a> {
             let mut _namespaced = false;
             let mut vec = unsafe {
                 let (src_ptr, path_length) = if addr.sun_path[0] == 0 {
+                    _namespaced = true;
                     (addr.sun_path.as_ptr().offset(1) as *const u8, sun_path_length - 1)
                 } else {
                     (addr.sun_path.as_ptr() as *const u8, sun_path_length)
@@ -215,10 +222,8 @@ impl<'a> UdSocketPath<'a> {
                 ptr::copy_nonoverlapping(src_ptr, vec.as_mut_ptr(), path_length);
                 vec
             };
-            // If the system added a nul byte as part of the length, remove it.
-            if vec.last() == Some(&0) {
-                vec.pop();
-            }
+            // Same as above.
+            truncate_at_nul(&mut vec);
             let cstring = CString::new(vec).unwrap_or_else(eunreachable);
             #[cfg(uds_linux_namespace)]
             let path_to_write = if _namespaced {
@@ -345,6 +350,12 @@ impl AsRef<OsStr> for UdSocketPath<'_> {
         self.as_osstr()
     }
 }
+fn truncate_at_nul(vec: &mut Vec<u8>) {
+    if let Some(nul_idx) = vec.iter().position(|&b| b == 0) {
+        vec.truncate(nul_idx);
+    }
+}
+
 impl TryFrom<UdSocketPath<'_>> for sockaddr_un {
     type Error = io::Error;
     fn try_from(path: UdSocketPath<'_>) -> io::Result<Self> {
@@ -357,6 +368,14 @@ impl TryFrom<UdSocketPath<'_>> for sockaddr_un {
     }
 }
 
+/// Converts a path straight to a socket address, for functions which only need it to make a system call.
+///
+/// The address stores the path inline, but the conversion to [`UdSocketPath`] still allocates if the path isn't
+/// already a nul-terminated string. Avoiding that would require `UdSocketPath` itself to store owned paths inline.
+pub(super) fn to_sockaddr_un<'a>(path: impl ToUdSocketPath<'a>) -> io::Result<sockaddr_un> {
+    sockaddr_un::try_from(path.to_socket_path()?)
+}
+
 /// Trait for types which can be converted to a [path to a Unix domain socket][`UdSocketPath`].
 ///
 /// The difference between this trait and [`TryInto`]`<`[`UdSocketPath`]`>` is that the latter does not constrain the
@@ -0,0 +1,109 @@
+//! Ud-sockets of type `SOCK_SEQPACKET`, which are connection-oriented like stream sockets but preserve message
+//! boundaries like datagram sockets. Only used internally, by framed local sockets.
+
+use super::{c_wrappers, UdSocketPath};
+use crate::os::unix::{unixprelude::*, FdOps};
+use libc::{sockaddr_un, SOCK_SEQPACKET};
+use std::{
+    io::{self, prelude::*},
+    mem::zeroed,
+};
+use to_method::To;
+
+/// A listening `SOCK_SEQPACKET` Ud-socket.
+#[derive(Debug)]
+pub(crate) struct SeqpacketListener(FdOps);
+impl SeqpacketListener {
+    /// Fails with an error for which [`is_unsupported()`] returns `true` if the system doesn't support the socket type.
+    pub fn bind(path: UdSocketPath<'_>) -> io::Result<Self> {
+        let addr = path.try_to::<sockaddr_un>()?;
+        let fd = c_wrappers::create_uds(SOCK_SEQPACKET, false)?;
+        unsafe {
+            // SAFETY: addr is well-constructed
+            c_wrappers::bind(fd.0.as_fd(), &addr)?;
+        }
+        c_wrappers::listen(fd.0.as_fd(), 128)?;
+        Ok(Self(fd))
+    }
+    pub fn accept(&self) -> io::Result<Seqpacket> {
+        let (success, fd) = unsafe {
+            let result = libc::accept(self.0 .0.as_raw_fd(), zeroed(), zeroed());
+            (result != -1, result)
+        };
+        if !success {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe {
+            // SAFETY: we just created the file descriptor
+            FdOps::from_raw_fd(fd)
+        };
+        Seqpacket::new(fd)
+    }
+}
+impl AsFd for SeqpacketListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0 .0.as_fd()
+    }
+}
+
+/// A connected `SOCK_SEQPACKET` Ud-socket.
+#[derive(Debug)]
+pub(crate) struct Seqpacket(FdOps);
+impl Seqpacket {
+    /// The size of the send and receive buffers requested for every socket, so that messages of up to half this size
+    /// can be sent on systems with smaller defaults, such as the BSDs.
+    const BUFFER_SIZE: c_int = 256 * 1024;
+
+    /// Fails with an error for which [`is_unsupported()`] returns `true` if the system doesn't support the socket type,
+    /// or if the server at the given path is of a different type.
+    pub fn connect(path: UdSocketPath<'_>) -> io::Result<Self> {
+        let addr = path.try_to::<sockaddr_un>()?;
+        let fd = c_wrappers::create_uds(SOCK_SEQPACKET, false)?;
+        unsafe {
+            // SAFETY: addr is well-constructed
+            c_wrappers::connect(fd.0.as_fd(), &addr)?;
+        }
+        Self::new(fd)
+    }
+    fn new(fd: FdOps) -> io::Result<Self> {
+        for option in [libc::SO_SNDBUF, libc::SO_RCVBUF] {
+            unsafe {
+                // SAFETY: both options take a c_int
+                c_wrappers::set_socket_option(fd.0.as_fd(), libc::SOL_SOCKET, option, &Self::BUFFER_SIZE)?;
+            }
+        }
+        Ok(Self(fd))
+    }
+    /// Sends the whole message at once, or fails.
+    pub fn send(&self, msg: &[u8]) -> io::Result<()> {
+        let sent = (&self.0).write(msg)?;
+        crate::datagram::check_sent(sent, msg.len())
+    }
+    /// Replaces the contents of the buffer with the next message, returning `false` instead if the peer has closed its
+    /// end of the connection. Zero-sized messages are indistinguishable from the end of the connection.
+    pub fn recv(&self, buf: &mut Vec<u8>) -> io::Result<bool> {
+        let size = c_wrappers::peek_msg_size(self.0 .0.as_fd())?;
+        buf.clear();
+        buf.reserve(size);
+        let received = c_wrappers::recv(self.0 .0.as_fd(), buf.spare_capacity_mut(), 0)?;
+        unsafe {
+            // SAFETY: the kernel has initialized that many bytes
+            buf.set_len(received);
+        }
+        Ok(received != 0)
+    }
+}
+impl AsFd for Seqpacket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0 .0.as_fd()
+    }
+}
+
+/// Whether the error means that `SOCK_SEQPACKET` sockets aren't available, either on this system or at the address
+/// being connected to, and a stream socket should be used instead.
+pub(crate) fn is_unsupported(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(libc::EPROTONOSUPPORT | libc::ESOCKTNOSUPPORT | libc::EPROTOTYPE | libc::EOPNOTSUPP)
+    )
+}
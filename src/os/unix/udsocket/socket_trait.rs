@@ -114,3 +114,7 @@ impl UdSocket for UdDatagram {}
 impl UdSocket for super::tokio::UdStream {}
 #[cfg(feature = "tokio")]
 impl UdSocket for super::tokio::UdDatagram {}
+#[cfg(feature = "async-std")]
+impl UdSocket for super::async_std::UdStream {}
+#[cfg(feature = "async-std")]
+impl UdSocket for super::async_std::UdDatagram {}
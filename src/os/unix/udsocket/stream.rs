@@ -2,15 +2,15 @@ use super::{
     ancillary_io::sync::{read_in_terms_of_vectored, write_in_terms_of_vectored},
     ancwrap, c_wrappers,
     cmsg::{CmsgMut, CmsgRef},
-    ReadAncillary, ReadAncillarySuccess, ToUdSocketPath, UdSocketPath, WriteAncillary,
+    path::to_sockaddr_un,
+    ReadAncillary, ReadAncillarySuccess, ToUdSocketPath, WriteAncillary,
 };
 use crate::{
     os::unix::{unixprelude::*, FdOps},
-    TryClone,
+    Sealed, TryClone,
 };
 use libc::{sockaddr_un, SOCK_STREAM};
 use std::io::{self, IoSlice, IoSliceMut, Read, Write};
-use to_method::To;
 
 /// A Unix domain socket byte stream, obtained either from [`UdStreamListener`](super::UdStreamListener) or by
 /// connecting to an existing server.
@@ -41,23 +41,112 @@ impl UdStream {
     /// - `socket`
     /// - `connect`
     pub fn connect<'a>(path: impl ToUdSocketPath<'a>) -> io::Result<Self> {
-        Self::_connect(path.to_socket_path()?, false)
+        Self::_connect(&to_sockaddr_un(path)?, false)
     }
-    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
     pub(crate) fn connect_nonblocking<'a>(path: impl ToUdSocketPath<'a>) -> io::Result<Self> {
-        Self::_connect(path.to_socket_path()?, true)
+        Self::_connect(&to_sockaddr_un(path)?, true)
     }
-    fn _connect(path: UdSocketPath<'_>, nonblocking: bool) -> io::Result<Self> {
-        let addr = path.try_to::<sockaddr_un>()?;
-
+    fn _connect(addr: &sockaddr_un, nonblocking: bool) -> io::Result<Self> {
         let fd = c_wrappers::create_uds(SOCK_STREAM, nonblocking)?;
         unsafe {
             // SAFETY: addr is well-constructed
-            c_wrappers::connect(fd.0.as_fd(), &addr)?;
+            c_wrappers::connect(fd.0.as_fd(), addr)?;
         }
 
         Ok(Self(fd))
     }
+    /// Adopts a duplicate of the standard input of the process, which has to be a connected Unix domain stream socket.
+    ///
+    /// This is how inetd-style superservers and socket-activating service managers hand connections to the services
+    /// they start, and how a parent can give its child one end of a socket pair to talk over. The standard input itself
+    /// is left untouched; the returned stream owns a close-on-exec duplicate of it.
+    ///
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if the standard input isn't a Unix
+    /// domain stream socket.
+    ///
+    /// # System calls
+    /// - `getsockopt`
+    /// - `getsockname`
+    /// - `fcntl` (`F_DUPFD_CLOEXEC`)
+    pub fn from_stdin() -> io::Result<Self> {
+        let stdin = io::stdin();
+        if !c_wrappers::is_uds_of_type(stdin.as_fd(), SOCK_STREAM)? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "standard input is not a Unix domain stream socket",
+            ));
+        }
+        stdin.as_fd().try_clone_to_owned().map(Self::from)
+    }
+    /// Same as [`from_stdin()`](Self::from_stdin), but also checks that the standard output refers to the same socket,
+    /// as is the case when inetd starts a service or a parent passes one end of a socket pair as both.
+    ///
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if the standard output is a
+    /// different file.
+    ///
+    /// # System calls
+    /// - `fstat`
+    /// - `getsockopt`
+    /// - `getsockname`
+    /// - `fcntl` (`F_DUPFD_CLOEXEC`)
+    pub fn from_stdio() -> io::Result<Self> {
+        if !c_wrappers::is_same_file(io::stdin().as_fd(), io::stdout().as_fd())? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "standard input and output refer to different files",
+            ));
+        }
+        Self::from_stdin()
+    }
+    /// Copies the data which has been received but not read yet into the buffer without consuming it, returning how many
+    /// bytes were copied. If there's none, waits for some to arrive first, unless the stream is in nonblocking mode.
+    /// Zero is returned if the peer has shut down its writing half.
+    ///
+    /// # System calls
+    /// - `recv` (`MSG_PEEK`)
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        c_wrappers::peek(self.as_fd(), buf)
+    }
+    /// Reads up to and including the delimiter, or up to end-of-file, appending the data to `buf` and returning how
+    /// many bytes were appended.
+    ///
+    /// Unlike [`BufRead::read_until()`](io::BufRead::read_until) on a buffered reader, this never reads past the
+    /// delimiter: the incoming data is peeked at first, and only as much of it as is needed is consumed. Whatever comes
+    /// after the delimiter is thus left in the socket, for code which takes over the stream afterwards – such as
+    /// another process it's passed to – to read. The cost of that is two system calls per read instead of one.
+    ///
+    /// # System calls
+    /// - `recv` (`MSG_PEEK`)
+    /// - `read`
+    pub fn read_until(&self, delim: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        crate::delimited::read_until_with(&mut &*self, delim, buf, |s, b| s.peek(b), |s, b| s.read(b))
+    }
+    /// Moves up to `len` bytes received from the socket into the given pipe without copying them through userspace,
+    /// returning the amount of bytes moved. Zero is returned if the peer has shut down its writing half.
+    ///
+    /// Combined with [`UnnamedPipeReader::splice_to()`](crate::unnamed_pipe::UnnamedPipeReader::splice_to), this
+    /// allows relaying data between sockets and files with zero copies.
+    ///
+    /// This method is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `splice`
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    pub fn splice_to_pipe(&self, pipe: &crate::unnamed_pipe::UnnamedPipeWriter, len: usize) -> io::Result<usize> {
+        c_wrappers::splice(self.as_fd(), pipe.as_fd(), len)
+    }
+    /// Like [`read_ancillary()`](ReadAncillary::read_ancillary), but receives into a possibly uninitialized buffer.
+    #[cfg(feature = "read_buf")]
+    pub(crate) fn read_ancillary_uninit<AB: CmsgMut + ?Sized>(
+        &self,
+        buf: &mut [std::mem::MaybeUninit<u8>],
+        abuf: &mut AB,
+    ) -> io::Result<ReadAncillarySuccess> {
+        ancwrap::recvmsg_uninit(self.as_fd(), buf, abuf)
+    }
 }
 
 /// A list of used system calls is available.
@@ -68,6 +157,12 @@ impl Read for &UdStream {
         (&self.0).read(buf)
     }
     /// # System calls
+    /// - `read`
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        (&self.0).read_buf(cursor)
+    }
+    /// # System calls
     /// - `readv`
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
         (&self.0).read_vectored(bufs)
@@ -82,6 +177,13 @@ impl Read for UdStream {
         (&*self).read(buf)
     }
     /// # System calls
+    /// - `read`
+    #[cfg(feature = "read_buf")]
+    #[inline(always)]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        (&*self).read_buf(cursor)
+    }
+    /// # System calls
     /// - `readv`
     #[inline(always)]
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
@@ -212,6 +314,7 @@ impl WriteAncillary for UdStream {
     }
 }
 
+impl Sealed for UdStream {}
 impl TryClone for UdStream {
     fn try_clone(&self) -> io::Result<Self> {
         self.0.try_clone().map(Self)
@@ -238,3 +341,4 @@ impl From<OwnedFd> for UdStream {
 }
 
 derive_raw!(unix: UdStream);
+derive_mio_source!(UdStream);
@@ -1,12 +1,30 @@
-use crate::os::unix::udsocket::{ToUdSocketPath, UdDatagram as SyncUdDatagram, UdSocketPath};
+use crate::{
+    datagram::{self, AsyncDatagram},
+    os::unix::{
+        udsocket::{
+            ancwrap, c_wrappers,
+            cmsg::{CmsgMutBuf, CmsgRef},
+            datagram::try_recv_msg,
+            ToUdSocketPath, UdDatagram as SyncUdDatagram, UdSocketPath,
+        },
+        unixprelude::*,
+    },
+    reliable_recv_msg::{AsyncReliableRecvMsg, RecvResult, TryRecvResult},
+};
+use futures_core::ready;
+use futures_util::future::poll_fn;
 use std::{
     future::Future,
-    io,
+    io::{self, IoSlice, IoSliceMut},
+    mem::MaybeUninit,
     os::unix::net::UnixDatagram as StdUdDatagram,
     pin::Pin,
     task::{Context, Poll},
 };
-use tokio::{io::ReadBuf as TokioReadBuf, net::UnixDatagram as TokioUdDatagram};
+use tokio::{
+    io::{Interest, ReadBuf as TokioReadBuf},
+    net::UnixDatagram as TokioUdDatagram,
+};
 
 /// A Unix domain datagram socket, obtained either from [`UdSocketListener`](super::UdSocketListener) or by connecting
 /// to an existing server.
@@ -110,6 +128,43 @@ impl UdDatagram {
     pub async fn recv_stdbuf(&self, buf: &mut [u8]) -> io::Result<usize> {
         self.0.recv(buf).await
     }
+    /// Receives a single datagram from the socket into a possibly uninitialized buffer, returning the amount of bytes
+    /// received. The first that many bytes of the buffer are guaranteed to be initialized after the call.
+    ///
+    /// Unlike `.recv()`, this does not go through a [`ReadBuf`](TokioReadBuf) and thus doesn't track the initialized
+    /// part of the buffer separately from the filled part.
+    ///
+    /// # System calls
+    /// - `recv`
+    pub async fn recv_uninit(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        let fd = self.0.as_fd();
+        self.0
+            .async_io(Interest::READABLE, || c_wrappers::recv(fd, buf, 0))
+            .await
+    }
+    /// Receives a single datagram and the source address from the socket, advancing the `ReadBuf` cursor by the
+    /// datagram length.
+    ///
+    /// Uses Tokio's [`ReadBuf`](TokioReadBuf) interface. See `.recv_from_stdbuf()` for a `&mut [u8]` version.
+    ///
+    /// # System calls
+    /// - `recvmsg`
+    pub async fn recv_from(&self, buf: &mut TokioReadBuf<'_>, addr_buf: &mut UdSocketPath<'_>) -> io::Result<()> {
+        poll_fn(|cx| self.poll_recv_from(cx, buf, addr_buf)).await
+    }
+    /// Receives a single datagram and the source address from the socket, returning the amount of bytes received.
+    ///
+    /// Uses an `std`-like `&mut [u8]` interface. See `.recv_from()` for a version which uses Tokio's
+    /// [`ReadBuf`](TokioReadBuf) instead.
+    ///
+    /// # System calls
+    /// - `recvmsg`
+    pub async fn recv_from_stdbuf(&self, buf: &mut [u8], addr_buf: &mut UdSocketPath<'_>) -> io::Result<usize> {
+        let fd = self.0.as_fd();
+        self.0
+            .async_io(Interest::READABLE, || recv_from_nonblocking(fd, buf, addr_buf))
+            .await
+    }
     /// Asynchronously waits until readable data arrives to the socket.
     ///
     /// May finish spuriously – *do not* perform a blocking read when this future finishes and *do* handle a
@@ -129,6 +184,14 @@ impl UdDatagram {
     async fn _send_to(&self, buf: &[u8], path: &UdSocketPath<'_>) -> io::Result<usize> {
         self.0.send_to(buf, path.as_osstr()).await
     }
+    /// Sends a single datagram, gathered from the given buffers, into the socket, returning how many bytes were
+    /// actually sent.
+    ///
+    /// # System calls
+    /// - `sendmsg`
+    pub async fn send_vectored(&self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        poll_fn(|cx| self.poll_send_vectored(cx, bufs)).await
+    }
     /// Asynchronously waits until the socket becomes writable due to the other side freeing up space in its OS receive
     /// buffer.
     ///
@@ -147,10 +210,70 @@ impl UdDatagram {
         let mut readbuf = TokioReadBuf::new(buf);
         self.0.poll_recv(cx, &mut readbuf)
     }
+    /// Raw polling interface for receiving datagrams into a possibly uninitialized buffer. You probably want
+    /// `.recv_uninit()` instead.
+    pub fn poll_recv_uninit(&self, cx: &mut Context<'_>, buf: &mut [MaybeUninit<u8>]) -> Poll<io::Result<usize>> {
+        loop {
+            match self
+                .0
+                .try_io(Interest::READABLE, || c_wrappers::recv(self.0.as_fd(), buf, 0))
+            {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return Poll::Ready(els),
+            }
+            ready!(self.0.poll_recv_ready(cx))?;
+        }
+    }
+    /// Raw polling interface for receiving datagrams along with their source address. You probably want `.recv_from()`
+    /// instead.
+    pub fn poll_recv_from(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut TokioReadBuf<'_>,
+        addr_buf: &mut UdSocketPath<'_>,
+    ) -> Poll<io::Result<()>> {
+        let bytes_read = ready!(self.poll_recv_from_stdbuf(cx, buf.initialize_unfilled(), addr_buf))?;
+        buf.advance(bytes_read);
+        Poll::Ready(Ok(()))
+    }
+    /// Raw polling interface for receiving datagrams along with their source address with an `std`-like receive
+    /// buffer. You probably want `.recv_from_stdbuf()` instead.
+    pub fn poll_recv_from_stdbuf(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        addr_buf: &mut UdSocketPath<'_>,
+    ) -> Poll<io::Result<usize>> {
+        let fd = self.0.as_fd();
+        loop {
+            match self
+                .0
+                .try_io(Interest::READABLE, || recv_from_nonblocking(fd, buf, addr_buf))
+            {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return Poll::Ready(els),
+            }
+            ready!(self.0.poll_recv_ready(cx))?;
+        }
+    }
     /// Raw polling interface for sending datagrams. You probably want `.send()` instead.
     pub fn poll_send(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
         self.0.poll_send(cx, buf)
     }
+    /// Raw polling interface for sending datagrams from several buffers. You probably want `.send_vectored()` instead.
+    pub fn poll_send_vectored(&self, cx: &mut Context<'_>, bufs: &[IoSlice<'_>]) -> Poll<io::Result<usize>> {
+        let fd = self.0.as_fd();
+        loop {
+            match self
+                .0
+                .try_io(Interest::WRITABLE, || ancwrap::sendmsg(fd, bufs, CmsgRef::empty()))
+            {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return Poll::Ready(els),
+            }
+            ready!(self.0.poll_send_ready(cx))?;
+        }
+    }
     /// Raw polling interface for sending datagrams. You probably want `.send_to()` instead.
     pub fn poll_send_to<'a>(
         &self,
@@ -166,6 +289,74 @@ impl UdDatagram {
     }
 }
 
+impl AsyncDatagram for UdDatagram {
+    type Address = UdSocketPath<'static>;
+    fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, msg: &[u8]) -> Poll<io::Result<()>> {
+        let sent = ready!(UdDatagram::poll_send(&self, cx, msg))?;
+        Poll::Ready(datagram::check_sent(sent, msg.len()))
+    }
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut readbuf = TokioReadBuf::new(buf);
+        ready!(UdDatagram::poll_recv(&self, cx, &mut readbuf))?;
+        Poll::Ready(Ok(readbuf.filled().len()))
+    }
+    fn poll_send_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, parts: &[IoSlice<'_>]) -> Poll<io::Result<()>> {
+        let sent = ready!(UdDatagram::poll_send_vectored(&self, cx, parts))?;
+        Poll::Ready(datagram::check_sent(sent, datagram::total_len(parts)))
+    }
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        msg: &[u8],
+        addr: &Self::Address,
+    ) -> Poll<io::Result<()>> {
+        let sent = ready!(self._poll_send_to(cx, msg, addr))?;
+        Poll::Ready(datagram::check_sent(sent, msg.len()))
+    }
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Option<Self::Address>)>> {
+        let mut addr = UdSocketPath::buffer();
+        let size = ready!(self.poll_recv_from_stdbuf(cx, buf, &mut addr))?;
+        Poll::Ready(Ok((size, addr.named())))
+    }
+}
+
+impl AsyncReliableRecvMsg for &UdDatagram {
+    fn poll_try_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<TryRecvResult>> {
+        let fd = self.0.as_fd();
+        loop {
+            match self.0.try_io(Interest::READABLE, || try_recv_msg(fd, buf)) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return Poll::Ready(els),
+            }
+            ready!(self.0.poll_recv_ready(cx))?;
+        }
+    }
+}
+impl AsyncReliableRecvMsg for UdDatagram {
+    #[inline]
+    fn poll_try_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<TryRecvResult>> {
+        Pin::new(&mut &*self).poll_try_recv(cx, buf)
+    }
+    #[inline]
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<RecvResult>> {
+        Pin::new(&mut &*self).poll_recv(cx, buf)
+    }
+}
+
+fn recv_from_nonblocking(fd: BorrowedFd<'_>, buf: &mut [u8], addr_buf: &mut UdSocketPath<'_>) -> io::Result<usize> {
+    ancwrap::recvmsg(
+        fd,
+        &mut [IoSliceMut::new(buf)],
+        &mut CmsgMutBuf::new(&mut []),
+        Some(addr_buf),
+    )
+    .map(|s| s.main)
+}
+
 tokio_wrapper_trait_impls!(
     for UdDatagram,
     sync SyncUdDatagram,
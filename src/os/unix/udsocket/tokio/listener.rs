@@ -1,5 +1,6 @@
-use crate::os::unix::udsocket::{
-    tokio::UdStream, ToUdSocketPath, UdSocketPath, UdStreamListener as SyncUdStreamListener,
+use crate::os::unix::{
+    udsocket::{c_wrappers, tokio::UdStream, ToUdSocketPath, UdSocketPath, UdStreamListener as SyncUdStreamListener},
+    unixprelude::*,
 };
 use std::{io, os::unix::net::UnixListener as StdUdStreamListener};
 use tokio::net::UnixListener as TokioUdStreamListener;
@@ -114,6 +115,22 @@ impl UdStreamListener {
     pub fn bind<'a>(path: impl ToUdSocketPath<'a>) -> io::Result<Self> {
         Self::_bind(path.to_socket_path()?)
     }
+    /// Adopts the listening socket which launchd created for the calling job under the given name, as described in
+    /// the [`launchd`](crate::os::unix::launchd) module, and attaches it to the Tokio runtime.
+    ///
+    /// This function is only available on macOS. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `launch_activate_socket`
+    /// - `fcntl`
+    #[cfg(target_os = "macos")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "macos")))]
+    pub fn from_launchd(name: &str) -> io::Result<Self> {
+        let listener = SyncUdStreamListener::from_launchd(name)?;
+        listener.set_nonblocking(true)?;
+        Self::try_from(listener).map_err(Into::into)
+    }
     fn _bind(path: UdSocketPath<'_>) -> io::Result<Self> {
         let listener = SyncUdStreamListener::_bind(path, false, true)?;
         Self::try_from(listener).map_err(Into::into)
@@ -122,6 +139,16 @@ impl UdStreamListener {
     pub async fn accept(&self) -> io::Result<UdStream> {
         Ok(self.0.accept().await?.0.into())
     }
+    /// Same as [`.accept()`](Self::accept), but also returns the address of the connecting client.
+    ///
+    /// The address is typically [unnamed](UdSocketPath::Unnamed), since clients rarely bind their sockets before
+    /// connecting, but clients that do so will be reported with their filesystem path or, on Linux, abstract namespace
+    /// name.
+    pub async fn accept_with_addr(&self) -> io::Result<(UdStream, UdSocketPath<'static>)> {
+        let stream = self.accept().await?;
+        let addr = c_wrappers::get_peer_addr(stream.as_fd())?;
+        Ok((stream, addr))
+    }
 }
 tokio_wrapper_trait_impls!(
     for UdStreamListener,
@@ -1,9 +1,13 @@
-use crate::os::unix::udsocket::{
-    ancwrap, c_wrappers,
-    cmsg::{CmsgMut, CmsgMutBuf, CmsgRef},
-    poll::{read_in_terms_of_vectored, write_in_terms_of_vectored},
-    AsyncReadAncillary, AsyncWriteAncillary, ReadAncillarySuccess, ToUdSocketPath, UdSocket, UdSocketPath,
-    UdStream as SyncUdStream,
+use crate::{
+    delimited::{delimited_len, PEEK_CHUNK},
+    os::unix::udsocket::{
+        ancwrap, c_wrappers,
+        cmsg::{CmsgMut, CmsgMutBuf, CmsgRef},
+        connect_future::ConnectFuture,
+        poll::{read_in_terms_of_vectored, write_in_terms_of_vectored},
+        AsyncReadAncillary, AsyncWriteAncillary, ReadAncillarySuccess, ToUdSocketPath, UdSocket, UdSocketPath,
+        UdStream as SyncUdStream,
+    },
 };
 use futures_core::ready;
 use futures_io::{AsyncRead, AsyncWrite};
@@ -15,17 +19,20 @@ use std::{
     os::{fd::AsFd, unix::net::UnixStream as StdUdStream},
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::{
-    io::{AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite, ReadBuf as TokioReadBuf},
+    io::{
+        AsyncRead as TokioAsyncRead, AsyncReadExt as _, AsyncWrite as TokioAsyncWrite, Interest,
+        ReadBuf as TokioReadBuf,
+    },
     net::{unix::ReuniteError as TokioReuniteError, UnixStream as TokioUdStream},
 };
 
-mod connect_future;
+mod msg;
 mod read_half;
 mod write_half;
-use connect_future::*;
-pub use {read_half::*, write_half::*};
+pub use {msg::*, read_half::*, write_half::*};
 
 /// A Unix domain socket byte stream, obtained either from [`UdStreamListener`](super::UdStreamListener) or by
 /// connecting to an existing server.
@@ -110,6 +117,76 @@ impl UdStream {
         Ok(Self::from(stream_tok))
     }
 
+    /// Gracefully closes the connection.
+    ///
+    /// The write half of the socket is shut down first, which sends an EOF to the other end. If `drain_timeout` is
+    /// `Some`, the stream is then read from until the other side closes its own write half, discarding all data that
+    /// arrives in the meantime – this ensures that the peer has seen everything that was sent and has finished using
+    /// the connection before the file descriptor is closed. If the deadline expires before that happens, an error of
+    /// kind [`TimedOut`](io::ErrorKind::TimedOut) is returned. The stream is closed in either case.
+    ///
+    /// Simply dropping the stream does none of this, meaning that the peer may observe a connection reset instead of
+    /// an orderly shutdown if it still had data in flight.
+    pub async fn close(self, drain_timeout: Option<Duration>) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)?;
+        if let Some(drain_timeout) = drain_timeout {
+            let drain = async {
+                let mut buf = [0; 512];
+                while (&self).read(&mut buf).await? != 0 {}
+                Ok::<_, io::Error>(())
+            };
+            tokio::time::timeout(drain_timeout, drain)
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "peer did not close the connection in time"))??;
+        }
+        Ok(())
+    }
+
+    /// Asynchronously copies the data which has been received but not read yet into the buffer without consuming it,
+    /// returning how many bytes were copied. If there's none, waits for some to arrive first. Zero is returned if the
+    /// peer has shut down its writing half.
+    ///
+    /// # System calls
+    /// - `recv` (`MSG_PEEK`)
+    pub async fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            self.0.readable().await?;
+            match self
+                .0
+                .try_io(Interest::READABLE, || c_wrappers::peek(self.0.as_fd(), buf))
+            {
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted) => {}
+                result => return result,
+            }
+        }
+    }
+    /// Asynchronously reads up to and including the delimiter, or up to end-of-file, appending the data to `buf` and
+    /// returning how many bytes were appended.
+    ///
+    /// Like its [synchronous counterpart](SyncUdStream::read_until), this never reads past the delimiter, leaving
+    /// whatever comes after it in the socket.
+    ///
+    /// # System calls
+    /// - `recv` (`MSG_PEEK`)
+    /// - `read`
+    pub async fn read_until(&self, delim: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let start = buf.len();
+        let mut chunk = [0; PEEK_CHUNK];
+        loop {
+            let peeked = self.peek(&mut chunk).await?;
+            if peeked == 0 {
+                break;
+            }
+            let (len, found) = delimited_len(&chunk[..peeked], delim);
+            (&*self).read_exact(&mut chunk[..len]).await?;
+            buf.extend_from_slice(&chunk[..len]);
+            if found {
+                break;
+            }
+        }
+        Ok(buf.len() - start)
+    }
+
     fn pinproject(self: Pin<&mut Self>) -> Pin<&mut TokioUdStream> {
         Pin::new(&mut self.get_mut().0)
     }
@@ -0,0 +1,166 @@
+use super::{poll_read_ref, poll_write_ref, ReadHalf, UdStream, WriteHalf};
+use futures_core::ready;
+use std::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{io::ReadBuf as TokioReadBuf, net::UnixStream as TokioUdStream};
+
+impl UdStream {
+    /// Writes the entirety of `msg` to the stream, returning a future which is safe to cancel and resume.
+    ///
+    /// See [`SendAllMsg`] for the details on cancellation safety.
+    pub fn send_all_msg<'s, 'b>(&'s self, msg: &'b [u8]) -> SendAllMsg<'s, 'b> {
+        SendAllMsg::new(&self.0, msg)
+    }
+    /// Fills the entirety of `buf` with data read from the stream, returning a future which is safe to cancel and
+    /// resume.
+    ///
+    /// See [`RecvExactMsg`] for the details on cancellation safety.
+    pub fn recv_exact_msg<'s, 'b>(&'s self, buf: &'b mut [u8]) -> RecvExactMsg<'s, 'b> {
+        RecvExactMsg::new(&self.0, buf)
+    }
+}
+impl ReadHalf {
+    /// Fills the entirety of `buf` with data read from the stream, returning a future which is safe to cancel and
+    /// resume.
+    ///
+    /// See [`RecvExactMsg`] for the details on cancellation safety.
+    pub fn recv_exact_msg<'s, 'b>(&'s self, buf: &'b mut [u8]) -> RecvExactMsg<'s, 'b> {
+        RecvExactMsg::new(self.0.as_ref(), buf)
+    }
+}
+impl WriteHalf {
+    /// Writes the entirety of `msg` to the stream, returning a future which is safe to cancel and resume.
+    ///
+    /// See [`SendAllMsg`] for the details on cancellation safety.
+    pub fn send_all_msg<'s, 'b>(&'s self, msg: &'b [u8]) -> SendAllMsg<'s, 'b> {
+        SendAllMsg::new(self.0.as_ref(), msg)
+    }
+}
+
+/// Future returned by [`UdStream::send_all_msg()`] and [`WriteHalf::send_all_msg()`].
+///
+/// # Cancellation safety
+/// Unlike `write_all`, this future keeps track of how much of the message has already been written inside of itself
+/// rather than in a local variable of an `async` block. As such, it can be polled by mutable reference (as in
+/// `select!(&mut fut, ...)`) and, if another branch completes first, polled again later to resume sending exactly where
+/// it left off, without ever writing any part of the message twice.
+///
+/// Dropping the future before it completes still leaves a partially written message in the stream, since the bytes
+/// which have already been handed off to the kernel cannot be taken back. [`.progress()`](Self::progress) reports how
+/// many bytes that is, which allows the caller to either finish the job later or consider the connection broken.
+#[must_use = "futures do nothing unless polled"]
+pub struct SendAllMsg<'s, 'b> {
+    stream: &'s TokioUdStream,
+    msg: &'b [u8],
+    written: usize,
+}
+impl<'s, 'b> SendAllMsg<'s, 'b> {
+    fn new(stream: &'s TokioUdStream, msg: &'b [u8]) -> Self {
+        Self {
+            stream,
+            msg,
+            written: 0,
+        }
+    }
+    /// Returns the amount of bytes of the message which have been written so far.
+    #[inline]
+    pub fn progress(&self) -> usize {
+        self.written
+    }
+    /// Returns `true` if the whole message has been written.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.written == self.msg.len()
+    }
+}
+impl Future for SendAllMsg<'_, '_> {
+    type Output = io::Result<()>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let slf = self.get_mut();
+        while !slf.is_finished() {
+            let written = ready!(poll_write_ref(slf.stream, cx, &slf.msg[slf.written..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write the whole message",
+                )));
+            }
+            slf.written += written;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+impl Debug for SendAllMsg<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendAllMsg")
+            .field("stream", &self.stream)
+            .field("len", &self.msg.len())
+            .field("written", &self.written)
+            .finish()
+    }
+}
+
+/// Future returned by [`UdStream::recv_exact_msg()`] and [`ReadHalf::recv_exact_msg()`].
+///
+/// # Cancellation safety
+/// Unlike `read_exact`, this future keeps track of how much of the buffer has already been filled inside of itself. As
+/// such, it can be polled by mutable reference (as in `select!(&mut fut, ...)`) and, if another branch completes first,
+/// polled again later to resume receiving exactly where it left off, without losing any of the data that has already
+/// been consumed from the stream.
+///
+/// Dropping the future before it completes discards the knowledge of where the message boundary is, but not the data
+/// itself: the first [`.progress()`](Self::progress) bytes of the buffer hold what has been received so far.
+#[must_use = "futures do nothing unless polled"]
+pub struct RecvExactMsg<'s, 'b> {
+    stream: &'s TokioUdStream,
+    buf: &'b mut [u8],
+    filled: usize,
+}
+impl<'s, 'b> RecvExactMsg<'s, 'b> {
+    fn new(stream: &'s TokioUdStream, buf: &'b mut [u8]) -> Self {
+        Self { stream, buf, filled: 0 }
+    }
+    /// Returns the amount of bytes at the start of the buffer which have been received so far.
+    #[inline]
+    pub fn progress(&self) -> usize {
+        self.filled
+    }
+    /// Returns `true` if the whole buffer has been filled.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.filled == self.buf.len()
+    }
+}
+impl Future for RecvExactMsg<'_, '_> {
+    type Output = io::Result<()>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let slf = self.get_mut();
+        while !slf.is_finished() {
+            let mut buf = TokioReadBuf::new(&mut slf.buf[slf.filled..]);
+            ready!(poll_read_ref(slf.stream, cx, &mut buf))?;
+            let received = buf.filled().len();
+            if received == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "stream ended before the whole message was received",
+                )));
+            }
+            slf.filled += received;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+impl Debug for RecvExactMsg<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecvExactMsg")
+            .field("stream", &self.stream)
+            .field("len", &self.buf.len())
+            .field("filled", &self.filled)
+            .finish()
+    }
+}
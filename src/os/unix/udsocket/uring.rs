@@ -0,0 +1,425 @@
+//! An [io_uring](https://man7.org/linux/man-pages/man7/io_uring.7.html) backend for Ud-sockets, for Linux servers which
+//! handle enough traffic for the cost of a system call per operation to matter.
+//!
+//! Sockets are [registered](UdRing::register) with a [`UdRing`] as fixed files, which spares the kernel from looking
+//! up the file descriptor for every operation, and the returned [`Registered`] handle is then used to start
+//! operations. Any number of operations can be started before a single call to
+//! [`submit_and_wait()`](UdRing::submit_and_wait) hands them all to the kernel, after which their results are
+//! collected from [`completions()`](UdRing::completions).
+//!
+//! Since the kernel accesses the buffers of an operation while it's in flight, the ring takes ownership of them when
+//! the operation is started and gives them back in its [`Completion`], whether or not it succeeded.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::os::unix::udsocket::{uring::{Completion, UdRing}, UdStream};
+//!
+//! let conn = UdStream::connect("/tmp/example.sock")?;
+//! let mut ring = UdRing::new(64, 16)?;
+//! let reg = ring.register(&conn)?;
+//! ring.send(reg, b"Hello from client!\n".to_vec())?;
+//! ring.recv(reg, Vec::with_capacity(128))?;
+//! ring.submit_and_wait(2)?;
+//! for (_, completion) in ring.completions() {
+//!     if let Completion::Received { buf, result } = completion {
+//!         result?;
+//!         println!("Server answered: {}", String::from_utf8_lossy(&buf));
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use super::{
+    cmsg::{read::buf_to_msghdr, CmsgMut, CmsgMutExt, CmsgVecBuf},
+    util::{make_msghdr, to_msghdr_iovlen},
+    UdDatagram, UdStream, UdStreamListener,
+};
+use crate::{os::unix::unixprelude::*, Sealed};
+use io_uring::{cqueue, opcode, squeue, types::Fixed, IoUring};
+use libc::{iovec, msghdr};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    io,
+    marker::PhantomData,
+    mem,
+    ptr::null_mut,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+};
+
+/// Ud-sockets which can be [registered](UdRing::register) with a [`UdRing`].
+///
+/// This trait is sealed and implemented for [`UdStream`], [`UdDatagram`] and [`UdStreamListener`].
+pub trait UringSocket: AsFd + Sealed {}
+impl UringSocket for UdStream {}
+impl UringSocket for UdDatagram {}
+impl UringSocket for UdStreamListener {}
+
+/// Registered Ud-sockets which data can be sent to and received from, which excludes listeners.
+///
+/// This trait is sealed and implemented for [`UdStream`] and [`UdDatagram`].
+pub trait UringData: UringSocket {}
+impl UringData for UdStream {}
+impl UringData for UdDatagram {}
+
+/// A handle to a socket [registered](UdRing::register) with a [`UdRing`], used to start operations on it.
+///
+/// The handle stays valid until the socket is [unregistered](UdRing::unregister), even if the socket object itself is
+/// dropped in the meantime, since the ring keeps a reference to the socket of its own. Using the handle with another
+/// ring or after unregistration fails with [`InvalidInput`](io::ErrorKind::InvalidInput).
+pub struct Registered<S> {
+    ring: u64,
+    index: u32,
+    generation: u32,
+    _phantom: PhantomData<fn() -> S>,
+}
+impl<S> Clone for Registered<S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<S> Copy for Registered<S> {}
+impl<S> Debug for Registered<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registered")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// Identifies an operation started on a [`UdRing`], to be matched against the one reported by
+/// [`completions()`](UdRing::completions).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Token(u64);
+
+/// The outcome of an operation, together with the buffers it was started with.
+#[derive(Debug)]
+#[allow(missing_docs)] // The fields are described by the variants
+pub enum Completion {
+    /// A [`send()`](UdRing::send) finished, having sent the given number of bytes from the beginning of the buffer.
+    Sent { buf: Vec<u8>, result: io::Result<usize> },
+    /// A [`recv()`](UdRing::recv) finished, having appended the given number of bytes to the buffer.
+    Received { buf: Vec<u8>, result: io::Result<usize> },
+    /// An [`accept()`](UdRing::accept) finished. The new connection is not registered with the ring.
+    Accepted(io::Result<UdStream>),
+    /// A [`send_msg()`](UdRing::send_msg) finished, having sent the given number of bytes from the beginning of the
+    /// buffer.
+    SentMsg {
+        buf: Vec<u8>,
+        abuf: CmsgVecBuf,
+        result: io::Result<usize>,
+    },
+    /// A [`recv_msg()`](UdRing::recv_msg) finished, having appended the given number of bytes to the buffer and the
+    /// received control messages to the ancillary data buffer.
+    ReceivedMsg {
+        buf: Vec<u8>,
+        abuf: CmsgVecBuf,
+        result: io::Result<usize>,
+    },
+}
+
+/// An io_uring instance with a table of registered Ud-sockets. See the [module-level documentation](self) for more.
+pub struct UdRing {
+    ring: IoUring,
+    id: u64,
+    /// Generation of every slot in the fixed file table, and whether it's occupied.
+    slots: Vec<(u32, bool)>,
+    ops: HashMap<u64, Op>,
+    next_token: u64,
+}
+impl UdRing {
+    /// Creates a ring with room for `entries` operations to be started between submissions and a table for up to
+    /// `max_sockets` registered sockets.
+    ///
+    /// # System calls
+    /// - `io_uring_setup`
+    /// - `io_uring_register` (`IORING_REGISTER_FILES`)
+    pub fn new(entries: u32, max_sockets: u32) -> io::Result<Self> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let ring = IoUring::new(entries)?;
+        ring.submitter().register_files(&vec![-1; max_sockets as usize])?;
+        Ok(Self {
+            ring,
+            id: NEXT_ID.fetch_add(1, Relaxed),
+            slots: vec![(0, false); max_sockets as usize],
+            ops: HashMap::new(),
+            next_token: 0,
+        })
+    }
+
+    /// Registers a socket in a free slot of the fixed file table, failing with
+    /// [`OutOfMemory`](io::ErrorKind::OutOfMemory) if there are none left.
+    ///
+    /// # System calls
+    /// - `io_uring_register` (`IORING_REGISTER_FILES_UPDATE`)
+    pub fn register<S: UringSocket>(&mut self, socket: &S) -> io::Result<Registered<S>> {
+        let index = self
+            .slots
+            .iter()
+            .position(|&(_, occupied)| !occupied)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::OutOfMemory, "the fixed file table is full"))?;
+        let index = index as u32;
+        self.ring
+            .submitter()
+            .register_files_update(index, &[socket.as_fd().as_raw_fd()])?;
+        let slot = &mut self.slots[index as usize];
+        slot.1 = true;
+        Ok(Registered {
+            ring: self.id,
+            index,
+            generation: slot.0,
+            _phantom: PhantomData,
+        })
+    }
+    /// Removes a socket from the fixed file table, after which the handle and any copies of it are no longer valid.
+    /// Operations already in flight are unaffected.
+    ///
+    /// # System calls
+    /// - `io_uring_register` (`IORING_REGISTER_FILES_UPDATE`)
+    pub fn unregister<S>(&mut self, reg: Registered<S>) -> io::Result<()> {
+        let index = self.check(reg)?;
+        self.ring.submitter().register_files_update(index.0, &[-1])?;
+        let slot = &mut self.slots[index.0 as usize];
+        *slot = (slot.0.wrapping_add(1), false);
+        Ok(())
+    }
+
+    /// Starts sending the contents of the buffer.
+    pub fn send<S: UringData>(&mut self, reg: Registered<S>, buf: Vec<u8>) -> io::Result<Token> {
+        let fd = self.check(reg)?;
+        let entry = opcode::Send::new(fd, buf.as_ptr(), clamp_len(buf.len()))
+            .flags(libc::MSG_NOSIGNAL)
+            .build();
+        self.push(entry, Op::Send(buf))
+    }
+    /// Starts receiving data into the spare capacity of the buffer, which is left as is if the buffer doesn't have
+    /// any.
+    pub fn recv<S: UringData>(&mut self, reg: Registered<S>, mut buf: Vec<u8>) -> io::Result<Token> {
+        let fd = self.check(reg)?;
+        let spare = buf.spare_capacity_mut();
+        let entry = opcode::Recv::new(fd, spare.as_mut_ptr().cast(), clamp_len(spare.len())).build();
+        self.push(entry, Op::Recv(buf))
+    }
+    /// Starts accepting a connection.
+    pub fn accept(&mut self, reg: Registered<UdStreamListener>) -> io::Result<Token> {
+        let fd = self.check(reg)?;
+        let entry = opcode::Accept::new(fd, null_mut(), null_mut())
+            .flags(libc::SOCK_CLOEXEC)
+            .build();
+        self.push(entry, Op::Accept)
+    }
+    /// Starts sending the contents of the buffer together with the ancillary data in `abuf`.
+    pub fn send_msg<S: UringData>(&mut self, reg: Registered<S>, buf: Vec<u8>, abuf: CmsgVecBuf) -> io::Result<Token> {
+        let fd = self.check(reg)?;
+        let mut msg = Msg::new(buf, abuf, false)?;
+        msg.abuf.as_ref().fill_msghdr(&mut msg.hdr)?;
+        let entry = opcode::SendMsg::new(fd, &msg.hdr)
+            .flags(libc::MSG_NOSIGNAL as u32)
+            .build();
+        self.push(entry, Op::SendMsg(msg))
+    }
+    /// Starts receiving data into the spare capacity of the buffer and control messages into that of `abuf`.
+    ///
+    /// File descriptors received this way have the close-on-exec flag set.
+    pub fn recv_msg<S: UringData>(&mut self, reg: Registered<S>, buf: Vec<u8>, abuf: CmsgVecBuf) -> io::Result<Token> {
+        let fd = self.check(reg)?;
+        let mut msg = Msg::new(buf, abuf, true)?;
+        buf_to_msghdr(&mut msg.abuf, &mut msg.hdr)?;
+        let entry = opcode::RecvMsg::new(fd, &mut msg.hdr)
+            .flags(libc::MSG_CMSG_CLOEXEC as u32)
+            .build();
+        self.push(entry, Op::RecvMsg(msg))
+    }
+
+    /// Submits the operations started since the last submission and waits for at least `want` operations to finish,
+    /// returning the number of operations submitted.
+    ///
+    /// # System calls
+    /// - `io_uring_enter`
+    pub fn submit_and_wait(&mut self, want: usize) -> io::Result<usize> {
+        self.ring.submit_and_wait(want)
+    }
+    /// Returns an iterator over the operations which have finished, consuming their completion entries.
+    pub fn completions(&mut self) -> Completions<'_> {
+        Completions {
+            cq: self.ring.completion(),
+            ops: &mut self.ops,
+        }
+    }
+    /// Returns the number of operations which have been started but whose completion hasn't been collected yet.
+    #[inline]
+    pub fn in_flight(&self) -> usize {
+        self.ops.len()
+    }
+
+    fn check<S>(&self, reg: Registered<S>) -> io::Result<Fixed> {
+        match self.slots.get(reg.index as usize) {
+            Some(&(generation, true)) if reg.ring == self.id && generation == reg.generation => Ok(Fixed(reg.index)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the socket is not registered with this ring",
+            )),
+        }
+    }
+    /// Queues the entry, submitting the queue first if it's full.
+    fn push(&mut self, entry: squeue::Entry, op: Op) -> io::Result<Token> {
+        let token = self.next_token;
+        self.next_token += 1;
+        let entry = entry.user_data(token);
+        if self.ring.submission().is_full() {
+            self.ring.submit()?;
+        }
+        unsafe {
+            // SAFETY: the buffers the entry points to are owned by `op`, which is kept in `ops` until the operation
+            // completes and leaked if the ring is dropped before that.
+            self.ring
+                .submission()
+                .push(&entry)
+                .map_err(|_| io::Error::new(io::ErrorKind::WouldBlock, "the submission queue is full"))?;
+        }
+        self.ops.insert(token, op);
+        Ok(Token(token))
+    }
+}
+impl Debug for UdRing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UdRing")
+            .field("fd", &self.ring.as_raw_fd())
+            .field("in_flight", &self.ops.len())
+            .finish()
+    }
+}
+impl AsFd for UdRing {
+    /// Returns the file descriptor of the ring, which becomes readable when there are completions to collect.
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        unsafe {
+            // SAFETY: the ring owns the file descriptor
+            BorrowedFd::borrow_raw(self.ring.as_raw_fd())
+        }
+    }
+}
+impl Drop for UdRing {
+    fn drop(&mut self) {
+        // The kernel may still be accessing the buffers of operations which haven't completed.
+        mem::forget(mem::take(&mut self.ops));
+    }
+}
+
+/// Iterator over finished operations, returned by [`UdRing::completions()`].
+pub struct Completions<'a> {
+    cq: cqueue::CompletionQueue<'a>,
+    ops: &'a mut HashMap<u64, Op>,
+}
+impl Iterator for Completions<'_> {
+    type Item = (Token, Completion);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = self.cq.next()?;
+            // Completions of operations this ring didn't start, if any, are skipped.
+            if let Some(op) = self.ops.remove(&entry.user_data()) {
+                return Some((Token(entry.user_data()), op.complete(entry.result())));
+            }
+        }
+    }
+}
+impl Debug for Completions<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Completions").field("ready", &self.cq.len()).finish()
+    }
+}
+
+enum Op {
+    Send(Vec<u8>),
+    Recv(Vec<u8>),
+    Accept,
+    SendMsg(Box<Msg>),
+    RecvMsg(Box<Msg>),
+}
+impl Op {
+    fn complete(self, result: i32) -> Completion {
+        let result = if result < 0 {
+            Err(io::Error::from_raw_os_error(-result))
+        } else {
+            Ok(result as usize)
+        };
+        match self {
+            Self::Send(buf) => Completion::Sent { buf, result },
+            Self::Recv(mut buf) => {
+                if let Ok(received) = result {
+                    unsafe {
+                        // SAFETY: the kernel initialized that many bytes of the spare capacity
+                        buf.set_len(buf.len() + received);
+                    }
+                }
+                Completion::Received { buf, result }
+            }
+            Self::Accept => Completion::Accepted(result.map(|fd| {
+                UdStream::from(unsafe {
+                    // SAFETY: the kernel just created the file descriptor for us
+                    OwnedFd::from_raw_fd(fd as c_int)
+                })
+            })),
+            Self::SendMsg(msg) => {
+                let Msg { buf, abuf, .. } = *msg;
+                Completion::SentMsg { buf, abuf, result }
+            }
+            Self::RecvMsg(msg) => {
+                let Msg {
+                    hdr, mut buf, mut abuf, ..
+                } = *msg;
+                if let Ok(received) = result {
+                    abuf.set_truncation_flag(hdr.msg_flags & libc::MSG_CTRUNC != 0);
+                    unsafe {
+                        // SAFETY: the kernel initialized that many bytes of both buffers' spare capacity
+                        buf.set_len(buf.len() + received);
+                        abuf.add_len(hdr.msg_controllen as _);
+                    }
+                }
+                Completion::ReceivedMsg { buf, abuf, result }
+            }
+        }
+    }
+}
+
+/// The state of a `sendmsg`/`recvmsg` operation, boxed so that the header and the I/O vector don't move while the
+/// kernel holds pointers to them.
+struct Msg {
+    hdr: msghdr,
+    iov: iovec,
+    buf: Vec<u8>,
+    abuf: CmsgVecBuf,
+}
+// SAFETY: the pointers in the header and the I/O vector only ever point into the struct itself and the buffers it owns.
+unsafe impl Send for Msg {}
+impl Msg {
+    /// Points the I/O vector at the contents of the buffer, or at its spare capacity if `spare` is `true`.
+    fn new(mut buf: Vec<u8>, abuf: CmsgVecBuf, spare: bool) -> io::Result<Box<Self>> {
+        let iov = if spare {
+            let spare = buf.spare_capacity_mut();
+            iovec {
+                iov_base: spare.as_mut_ptr().cast(),
+                iov_len: spare.len(),
+            }
+        } else {
+            iovec {
+                iov_base: buf.as_mut_ptr().cast(),
+                iov_len: buf.len(),
+            }
+        };
+        let mut msg = Box::new(Self {
+            hdr: make_msghdr(null_mut(), to_msghdr_iovlen(1)?),
+            iov,
+            buf,
+            abuf,
+        });
+        msg.hdr.msg_iov = &mut msg.iov;
+        Ok(msg)
+    }
+}
+
+fn clamp_len(len: usize) -> u32 {
+    len.try_into().unwrap_or(u32::MAX)
+}
@@ -1,52 +1,175 @@
+#[cfg(feature = "tokio")]
+pub(crate) mod tokio;
+
 use super::FdOps;
 use crate::{
-    unnamed_pipe::{UnnamedPipeReader as PubReader, UnnamedPipeWriter as PubWriter},
-    Sealed,
+    unnamed_pipe::{
+        send_file_chunked, PipeOptions, ReadTimeout, UnnamedPipeReader as PubReader, UnnamedPipeWriter as PubWriter,
+    },
+    Sealed, TryClone,
 };
 use libc::c_int;
 use std::{
     fmt::{self, Debug, Formatter},
-    io::{self, Read, Write},
+    fs::File,
+    io::{self, IoSlice, Read, Write},
+    ops::Range,
     os::{
         fd::{AsFd, BorrowedFd, OwnedFd},
-        unix::io::{AsRawFd, FromRawFd},
+        unix::{
+            fs::FileExt,
+            io::{AsRawFd, FromRawFd, RawFd},
+        },
     },
+    sync::atomic::{AtomicI32, Ordering::Relaxed},
+    time::{Duration, Instant},
 };
 
 pub(crate) fn pipe() -> io::Result<(PubWriter, PubReader)> {
-    let (success, fds) = unsafe {
-        let mut fds: [c_int; 2] = [0; 2];
-        let result = libc::pipe(fds.as_mut_ptr());
-        (result == 0, fds)
+    // Plain pipe() leaves both file descriptors inheritable and in blocking mode.
+    pipe_with_options(&PipeOptions::new().inheritable(true))
+}
+
+pub(crate) fn pipe_with_options(opts: &PipeOptions) -> io::Result<(PubWriter, PubReader)> {
+    let [r, w] = create_fds(opts)?;
+    let w = PubWriter(UnnamedPipeWriter(FdOps(w)));
+    let r = PubReader(UnnamedPipeReader::from(r));
+    Ok((w, r))
+}
+
+/// The highest file descriptor that a child end was ever arranged to be inherited as, or standard error if none was.
+static HIGHEST_TARGET: AtomicI32 = AtomicI32::new(2);
+
+/// Returns a `pre_exec` hook which makes the child process inherit `fd` as the file descriptor `target`. The hook only
+/// performs async-signal-safe system calls.
+///
+/// Every call registers a hook of its own, and the hooks run in the order they were registered in. If the target of
+/// an earlier hook happened to be the file descriptor of a later hook's child end, that end would be overwritten by
+/// `dup2()` before getting to its own target. To rule this out, `fd` is first moved above every target that any hook
+/// was ever created for, including its own, and above standard input, output and error, which are set up by the
+/// command before any hook runs.
+pub(crate) fn inherit_as_fd(
+    fd: OwnedFd,
+    target: RawFd,
+) -> io::Result<impl FnMut() -> io::Result<()> + Send + Sync + 'static> {
+    let floor = HIGHEST_TARGET.load(Relaxed).max(target).saturating_add(1);
+    let fd = unsafe {
+        // SAFETY: F_DUPFD_CLOEXEC returns a new file descriptor that nothing else owns
+        OwnedFd::from_raw_fd(super::c_wrappers::fcntl_int(fd.as_fd(), libc::F_DUPFD_CLOEXEC, floor)?)
     };
-    if success {
-        let (w, r) = unsafe {
-            // SAFETY: we just created both of those file descriptors, which means that neither of
-            // them can be in use elsewhere.
-            let w = OwnedFd::from_raw_fd(fds[1]);
-            let r = OwnedFd::from_raw_fd(fds[0]);
-            (w, r)
-        };
-        let w = PubWriter(UnnamedPipeWriter(FdOps(w)));
-        let r = PubReader(UnnamedPipeReader(FdOps(r)));
-        Ok((w, r))
-    } else {
-        Err(io::Error::last_os_error())
+    HIGHEST_TARGET.fetch_max(target, Relaxed);
+    // The duplicate doesn't inherit the close-on-exec flag, and the two file descriptors are never the same.
+    Ok(move || super::c_wrappers::dup2(fd.as_fd(), target))
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "illumos",
+    target_os = "solaris",
+    target_os = "redox",
+))]
+pub(super) fn create_fds(opts: &PipeOptions) -> io::Result<[OwnedFd; 2]> {
+    let mut flags = 0;
+    if !opts.inheritable {
+        flags |= libc::O_CLOEXEC;
+    }
+    if opts.nonblocking {
+        flags |= libc::O_NONBLOCK;
+    }
+    #[cfg(target_os = "linux")]
+    if opts.packet_mode {
+        flags |= libc::O_DIRECT;
     }
+    let mut fds: [c_int; 2] = [0; 2];
+    let success = unsafe { libc::pipe2(fds.as_mut_ptr(), flags) == 0 };
+    ok_or_ret_errno!(success => unsafe {
+        // SAFETY: we just created both of those file descriptors, which means that neither of
+        // them can be in use elsewhere.
+        fds.map(|fd| OwnedFd::from_raw_fd(fd))
+    })
+}
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "illumos",
+    target_os = "solaris",
+    target_os = "redox",
+)))]
+pub(super) fn create_fds(opts: &PipeOptions) -> io::Result<[OwnedFd; 2]> {
+    let mut fds: [c_int; 2] = [0; 2];
+    let success = unsafe { libc::pipe(fds.as_mut_ptr()) == 0 };
+    let fds = ok_or_ret_errno!(success => unsafe {
+        // SAFETY: as above.
+        fds.map(|fd| OwnedFd::from_raw_fd(fd))
+    })?;
+    // Without pipe2(), there's a window during which another thread can fork and leak the file descriptors into the
+    // child process, which can't be helped.
+    for fd in &fds {
+        if !opts.inheritable {
+            super::c_wrappers::set_cloexec(fd.as_fd())?;
+        }
+        if opts.nonblocking {
+            super::c_wrappers::set_nonblocking(fd.as_fd(), true)?;
+        }
+    }
+    Ok(fds)
 }
 
-pub(crate) struct UnnamedPipeReader(FdOps);
+pub(crate) struct UnnamedPipeReader(FdOps, ReadTimeout);
+impl UnnamedPipeReader {
+    pub fn read_timeout(&self) -> &ReadTimeout {
+        &self.1
+    }
+    pub fn bytes_available(&self) -> io::Result<usize> {
+        super::c_wrappers::bytes_available(self.as_fd())
+    }
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        super::c_wrappers::set_nonblocking(self.as_fd(), nonblocking)
+    }
+}
+#[cfg(target_os = "linux")]
+impl UnnamedPipeReader {
+    pub fn splice_to(&self, fd: BorrowedFd<'_>, len: usize) -> io::Result<usize> {
+        super::c_wrappers::splice(self.as_fd(), fd, len)
+    }
+    pub fn tee(&self, writer: &UnnamedPipeWriter, len: usize) -> io::Result<usize> {
+        super::c_wrappers::tee(self.as_fd(), writer.as_fd(), len)
+    }
+}
 impl Read for &UnnamedPipeReader {
-    #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(timeout) = self.1.get() {
+            wait_for_data(self.as_fd(), timeout)?;
+        }
         (&self.0).read(buf)
     }
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        if let Some(timeout) = self.1.get() {
+            wait_for_data(self.as_fd(), timeout)?;
+        }
+        (&self.0).read_buf(cursor)
+    }
 }
 impl Read for UnnamedPipeReader {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         (self as &Self).read(buf)
     }
+    #[cfg(feature = "read_buf")]
+    #[inline]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        (self as &Self).read_buf(cursor)
+    }
 }
 impl Sealed for UnnamedPipeReader {}
 impl AsFd for UnnamedPipeReader {
@@ -64,25 +187,101 @@ impl From<UnnamedPipeReader> for OwnedFd {
 impl From<OwnedFd> for UnnamedPipeReader {
     #[inline]
     fn from(fd: OwnedFd) -> Self {
-        Self(FdOps(fd))
+        Self(FdOps(fd), ReadTimeout::default())
     }
 }
 impl Debug for UnnamedPipeReader {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("UnnamedPipeReader")
             .field("fd", &self.0 .0.as_raw_fd())
+            .field("read_timeout", &self.1.get())
             .finish()
     }
 }
-forward_try_clone!(UnnamedPipeReader);
+impl TryClone for UnnamedPipeReader {
+    #[inline]
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self(self.0.try_clone()?, self.1.clone()))
+    }
+}
+
+/// Waits until the pipe has data or its writing end is closed, failing with `TimedOut` if that doesn't happen in time.
+fn wait_for_data(fd: BorrowedFd<'_>, timeout: Duration) -> io::Result<()> {
+    let Some(deadline) = Instant::now().checked_add(timeout) else {
+        // Too far into the future to ever happen.
+        return Ok(());
+    };
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "read timed out"));
+        }
+        // Rounded up, so the deadline is guaranteed to have passed if poll() times out, unless the timeout was too long
+        // to be represented and got clamped.
+        let millis = super::c_wrappers::timeout_to_ms(Some(remaining));
+        if super::c_wrappers::poll_readable(fd, millis)? {
+            return Ok(());
+        }
+    }
+}
 
 pub(crate) struct UnnamedPipeWriter(FdOps);
+impl UnnamedPipeWriter {
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        super::c_wrappers::set_nonblocking(self.as_fd(), nonblocking)
+    }
+    #[cfg(not(target_os = "linux"))]
+    pub fn send_file(&self, file: &File, range: Range<u64>) -> io::Result<u64> {
+        send_file_chunked(|buf, offset| file.read_at(buf, offset), self, range)
+    }
+    #[cfg(target_os = "linux")]
+    pub fn send_file(&self, file: &File, range: Range<u64>) -> io::Result<u64> {
+        // The largest amount of bytes Linux will transfer in one call.
+        const MAX_SENDFILE: u64 = 0x7fff_f000;
+        let mut offset = libc::off_t::try_from(range.start)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file offset out of range"))?;
+        let mut sent = 0;
+        while range.start + sent < range.end {
+            let len = (range.end - range.start - sent).min(MAX_SENDFILE) as usize;
+            match super::c_wrappers::sendfile(self.as_fd(), file.as_fd(), &mut offset, len) {
+                Ok(0) => break,
+                Ok(n) => sent += n as u64,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock && sent > 0 => break,
+                // Some files, such as those in procfs, can't be used with sendfile().
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) && sent == 0 => {
+                    return send_file_chunked(|buf, offset| file.read_at(buf, offset), self, range);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(sent)
+    }
+}
+#[cfg(target_os = "linux")]
+impl UnnamedPipeWriter {
+    pub fn splice_from(&self, fd: BorrowedFd<'_>, len: usize) -> io::Result<usize> {
+        super::c_wrappers::splice(fd, self.as_fd(), len)
+    }
+    /// Maps `data` into the pipe with `SPLICE_F_GIFT`, returning the amount of bytes written.
+    ///
+    /// # Safety
+    /// The memory of `data` must never be modified or freed afterwards, since the kernel may keep referring to it for
+    /// an unbounded amount of time.
+    pub unsafe fn write_gifted(&self, data: &[u8]) -> io::Result<usize> {
+        super::c_wrappers::vmsplice(self.as_fd(), data, libc::SPLICE_F_GIFT)
+    }
+}
 impl Write for &UnnamedPipeWriter {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         (&self.0).write(buf)
     }
     #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (&self.0).write_vectored(bufs)
+    }
+    #[inline]
     fn flush(&mut self) -> io::Result<()> {
         (&self.0).flush()
     }
@@ -93,6 +292,10 @@ impl Write for UnnamedPipeWriter {
         (self as &Self).write(buf)
     }
     #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (self as &Self).write_vectored(bufs)
+    }
+    #[inline]
     fn flush(&mut self) -> io::Result<()> {
         (self as &Self).flush()
     }
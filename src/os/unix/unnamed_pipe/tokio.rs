@@ -0,0 +1,120 @@
+use super::{
+    super::{c_wrappers, FdOps},
+    UnnamedPipeReader as SyncReader, UnnamedPipeWriter as SyncWriter,
+};
+use crate::unnamed_pipe::tokio::{UnnamedPipeReader as PubReader, UnnamedPipeWriter as PubWriter};
+use futures_core::ready;
+use std::{
+    convert::TryFrom,
+    fmt::{self, Debug, Formatter},
+    io::{self, Read, Write},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd},
+    task::{Context, Poll},
+};
+use tokio::io::{unix::AsyncFd, ReadBuf};
+
+pub(crate) fn pipe() -> io::Result<(PubWriter, PubReader)> {
+    let (w, r) = super::pipe()?;
+    Ok((
+        PubWriter(UnnamedPipeWriter::try_from(w.0)?),
+        PubReader(UnnamedPipeReader::try_from(r.0)?),
+    ))
+}
+
+fn register(fd: FdOps) -> io::Result<AsyncFd<FdOps>> {
+    c_wrappers::set_nonblocking(fd.0.as_fd(), true)?;
+    AsyncFd::new(fd)
+}
+
+pub(crate) struct UnnamedPipeReader(AsyncFd<FdOps>);
+impl UnnamedPipeReader {
+    pub fn poll_read(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = ready!(self.0.poll_read_ready(cx))?;
+            if let Ok(rslt) = guard.try_io(|fd| Read::read(&mut fd.get_ref(), buf)) {
+                return Poll::Ready(rslt);
+            }
+        }
+    }
+    /// Reads into the unfilled part of the buffer without initializing it first.
+    pub fn poll_read_readbuf(&self, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = ready!(self.0.poll_read_ready(cx))?;
+            let unfilled = unsafe {
+                // SAFETY: the buffer is only written to, never de-initialized
+                buf.unfilled_mut()
+            };
+            if let Ok(rslt) = guard.try_io(|fd| fd.get_ref().read_uninit(unfilled)) {
+                let bytes_read = rslt?;
+                unsafe {
+                    // SAFETY: the kernel initialized that many bytes
+                    buf.assume_init(bytes_read);
+                }
+                buf.advance(bytes_read);
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
+impl TryFrom<SyncReader> for UnnamedPipeReader {
+    type Error = io::Error;
+    fn try_from(sync: SyncReader) -> io::Result<Self> {
+        register(sync.0).map(Self)
+    }
+}
+impl AsFd for UnnamedPipeReader {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.get_ref().0.as_fd()
+    }
+}
+impl From<UnnamedPipeReader> for OwnedFd {
+    #[inline]
+    fn from(x: UnnamedPipeReader) -> Self {
+        x.0.into_inner().0
+    }
+}
+impl Debug for UnnamedPipeReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnnamedPipeReader")
+            .field("fd", &self.as_fd().as_raw_fd())
+            .finish()
+    }
+}
+
+pub(crate) struct UnnamedPipeWriter(AsyncFd<FdOps>);
+impl UnnamedPipeWriter {
+    pub fn poll_write(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = ready!(self.0.poll_write_ready(cx))?;
+            if let Ok(rslt) = guard.try_io(|fd| Write::write(&mut fd.get_ref(), buf)) {
+                return Poll::Ready(rslt);
+            }
+        }
+    }
+}
+impl TryFrom<SyncWriter> for UnnamedPipeWriter {
+    type Error = io::Error;
+    fn try_from(sync: SyncWriter) -> io::Result<Self> {
+        register(sync.0).map(Self)
+    }
+}
+impl AsFd for UnnamedPipeWriter {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.get_ref().0.as_fd()
+    }
+}
+impl From<UnnamedPipeWriter> for OwnedFd {
+    #[inline]
+    fn from(x: UnnamedPipeWriter) -> Self {
+        x.0.into_inner().0
+    }
+}
+impl Debug for UnnamedPipeWriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnnamedPipeWriter")
+            .field("fd", &self.as_fd().as_raw_fd())
+            .finish()
+    }
+}
@@ -1,13 +1,40 @@
 use super::winprelude::*;
 use std::{
+    ffi::OsString,
     io,
     mem::{size_of, zeroed},
+    path::PathBuf,
+    ptr, slice,
+    time::Duration,
 };
-use winapi::um::{
-    handleapi::DuplicateHandle, minwinbase::SECURITY_ATTRIBUTES, processthreadsapi::GetCurrentProcess,
-    winnt::DUPLICATE_SAME_ACCESS,
+use winapi::{
+    shared::winerror::ERROR_INSUFFICIENT_BUFFER,
+    um::{
+        handleapi::{DuplicateHandle, GetHandleInformation, SetHandleInformation},
+        minwinbase::SECURITY_ATTRIBUTES,
+        namedpipeapi::PeekNamedPipe,
+        processthreadsapi::{GetCurrentProcess, OpenProcess, OpenProcessToken},
+        sddl::ConvertSidToStringSidW,
+        securitybaseapi::GetTokenInformation,
+        winbase::{LocalFree, QueryFullProcessImageNameW, HANDLE_FLAG_INHERIT, INFINITE},
+        winnt::{
+            TokenUser, DUPLICATE_SAME_ACCESS, PROCESS_DUP_HANDLE, PROCESS_QUERY_LIMITED_INFORMATION, TOKEN_QUERY,
+            TOKEN_USER,
+        },
+    },
 };
 
+/// Converts a timeout to milliseconds for the wait functions, rounding up so that the wait doesn't end early. `None`
+/// becomes `INFINITE`, and timeouts too long to be represented are kept short of it.
+pub fn timeout_to_ms(timeout: Option<Duration>) -> DWORD {
+    match timeout {
+        Some(t) => DWORD::try_from((t.as_nanos() + 999_999) / 1_000_000)
+            .unwrap_or(INFINITE - 1)
+            .min(INFINITE - 1),
+        None => INFINITE,
+    }
+}
+
 pub fn duplicate_handle(handle: BorrowedHandle<'_>) -> io::Result<OwnedHandle> {
     let raw = duplicate_handle_inner(handle, None)?;
     unsafe { Ok(OwnedHandle::from_raw_handle(raw)) }
@@ -39,6 +66,117 @@ fn duplicate_handle_inner(
     ok_or_ret_errno!(success => new_handle)
 }
 
+pub fn open_process_for_duplication(pid: DWORD) -> io::Result<OwnedHandle> {
+    let handle = unsafe { OpenProcess(PROCESS_DUP_HANDLE, 0, pid) };
+    ok_or_ret_errno!(!handle.is_null() => unsafe {
+        // SAFETY: we just opened this handle
+        OwnedHandle::from_raw_handle(handle)
+    })
+}
+
+fn open_process_for_query(pid: DWORD) -> io::Result<OwnedHandle> {
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    ok_or_ret_errno!(!handle.is_null() => unsafe {
+        // SAFETY: we just opened this handle
+        OwnedHandle::from_raw_handle(handle)
+    })
+}
+pub fn process_image_path(pid: DWORD) -> io::Result<PathBuf> {
+    let process = open_process_for_query(pid)?;
+    // Paths can be longer than MAX_PATH, up to the 32767 characters of the \\?\ syntax.
+    let mut buf = vec![0_u16; 1024];
+    loop {
+        let mut len = buf.len() as DWORD;
+        let success =
+            unsafe { QueryFullProcessImageNameW(process.as_raw_handle(), 0, buf.as_mut_ptr(), &mut len) != 0 };
+        if success {
+            buf.truncate(len as usize);
+            return Ok(OsString::from_wide(&buf).into());
+        }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as _) || buf.len() > i16::MAX as usize {
+            return Err(e);
+        }
+        buf.resize(buf.len() * 2, 0);
+    }
+}
+pub fn process_user_sid(pid: DWORD) -> io::Result<String> {
+    let process = open_process_for_query(pid)?;
+    let mut token = ptr::null_mut();
+    let success = unsafe { OpenProcessToken(process.as_raw_handle(), TOKEN_QUERY, &mut token) != 0 };
+    let token = ok_or_ret_errno!(success => unsafe {
+        // SAFETY: we just opened this handle
+        OwnedHandle::from_raw_handle(token)
+    })?;
+
+    // The first call fails, but reports the size of the buffer.
+    let mut len: DWORD = 0;
+    unsafe { GetTokenInformation(token.as_raw_handle(), TokenUser, ptr::null_mut(), 0, &mut len) };
+    if len == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Made of u64s so that it's aligned for TOKEN_USER.
+    let mut buf = vec![0_u64; (len as usize + 7) / 8];
+    let success =
+        unsafe { GetTokenInformation(token.as_raw_handle(), TokenUser, buf.as_mut_ptr().cast(), len, &mut len) != 0 };
+    ok_or_ret_errno!(success => ())?;
+    let sid = unsafe { (*buf.as_ptr().cast::<TOKEN_USER>()).User.Sid };
+
+    let mut wide = ptr::null_mut();
+    let success = unsafe { ConvertSidToStringSidW(sid, &mut wide) != 0 };
+    ok_or_ret_errno!(success => ())?;
+    let sid = unsafe {
+        // SAFETY: ConvertSidToStringSidW returns a nul-terminated string, which we free right after copying it
+        let len = (0..).take_while(|&i| *wide.add(i) != 0).count();
+        let sid = String::from_utf16_lossy(slice::from_raw_parts(wide, len));
+        LocalFree(wide.cast());
+        sid
+    };
+    Ok(sid)
+}
+
+pub fn set_inheritable(handle: BorrowedHandle<'_>, inheritable: bool) -> io::Result<()> {
+    let flags = if inheritable { HANDLE_FLAG_INHERIT } else { 0 };
+    let success = unsafe { SetHandleInformation(handle.as_raw_handle(), HANDLE_FLAG_INHERIT, flags) != 0 };
+    ok_or_ret_errno!(success => ())
+}
+pub fn is_inheritable(handle: BorrowedHandle<'_>) -> io::Result<bool> {
+    let mut flags: DWORD = 0;
+    let success = unsafe { GetHandleInformation(handle.as_raw_handle(), &mut flags) != 0 };
+    ok_or_ret_errno!(success => flags & HANDLE_FLAG_INHERIT != 0)
+}
+
+/// Copies the data available in the given pipe into the buffer without consuming it. Doesn't wait for data to arrive.
+pub fn peek(handle: BorrowedHandle<'_>, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read: DWORD = 0;
+    let len = DWORD::try_from(buf.len()).unwrap_or(DWORD::MAX);
+    let success = unsafe {
+        PeekNamedPipe(
+            handle.as_raw_handle(),
+            buf.as_mut_ptr().cast(),
+            len,
+            &mut read,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        ) != 0
+    };
+    ok_or_ret_errno!(success => read as usize)
+}
+pub fn bytes_available(handle: BorrowedHandle<'_>) -> io::Result<usize> {
+    let mut avail: DWORD = 0;
+    let success = unsafe {
+        PeekNamedPipe(
+            handle.as_raw_handle(),
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+            &mut avail,
+            ptr::null_mut(),
+        ) != 0
+    };
+    ok_or_ret_errno!(success => avail as usize)
+}
+
 pub fn init_security_attributes() -> SECURITY_ATTRIBUTES {
     let mut a: SECURITY_ATTRIBUTES = unsafe { zeroed() };
     a.nLength = size_of::<SECURITY_ATTRIBUTES>() as _;
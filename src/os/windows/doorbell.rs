@@ -0,0 +1,78 @@
+use super::{c_wrappers, winprelude::*};
+use crate::TryClone;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io, ptr,
+    time::Duration,
+};
+use winapi::{
+    shared::winerror::WAIT_TIMEOUT,
+    um::{
+        synchapi::{CreateEventW, SetEvent, WaitForSingleObject},
+        winbase::WAIT_OBJECT_0,
+    },
+};
+
+/// An unnamed auto-reset event, which is unsignaled again as soon as a wait on it succeeds.
+pub(crate) struct Doorbell(OwnedHandle);
+impl Doorbell {
+    pub fn new() -> io::Result<Self> {
+        let handle = unsafe { CreateEventW(ptr::null_mut(), 0, 0, ptr::null()) };
+        ok_or_ret_errno!(!handle.is_null() => Self(unsafe {
+            // SAFETY: we just created this handle
+            OwnedHandle::from_raw_handle(handle)
+        }))
+    }
+    pub fn ring(&self) -> io::Result<()> {
+        let success = unsafe { SetEvent(self.0.as_raw_handle()) != 0 };
+        ok_or_ret_errno!(success => ())
+    }
+    pub fn wait(&self) -> io::Result<()> {
+        while !self.wait_timeout(None)? {}
+        Ok(())
+    }
+    /// Returns `false` if the timeout ran out before the doorbell rang.
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        match unsafe { WaitForSingleObject(self.0.as_raw_handle(), c_wrappers::timeout_to_ms(timeout)) } {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+    /// Waits on a thread from the blocking pool of the runtime, since events can't be registered in its event loop.
+    #[cfg(feature = "tokio")]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        let doorbell = self.try_clone()?;
+        tokio::task::spawn_blocking(move || doorbell.wait())
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+}
+impl TryClone for Doorbell {
+    fn try_clone(&self) -> io::Result<Self> {
+        c_wrappers::duplicate_handle(self.0.as_handle()).map(Self)
+    }
+}
+impl AsHandle for Doorbell {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl From<Doorbell> for OwnedHandle {
+    #[inline]
+    fn from(doorbell: Doorbell) -> Self {
+        doorbell.0
+    }
+}
+impl From<OwnedHandle> for Doorbell {
+    #[inline]
+    fn from(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+}
+impl Debug for Doorbell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Doorbell").field(&self.0.as_raw_handle()).finish()
+    }
+}
@@ -0,0 +1,88 @@
+use super::{c_wrappers, winprelude::*};
+use crate::TryClone;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io, ptr,
+    time::Duration,
+};
+use winapi::{
+    shared::winerror::WAIT_TIMEOUT,
+    um::{
+        synchapi::{CreateEventW, ResetEvent, SetEvent, WaitForSingleObject},
+        winbase::WAIT_OBJECT_0,
+    },
+};
+
+/// An unnamed manual-reset event, which stays signaled until it's explicitly reset.
+pub(crate) struct Event(OwnedHandle);
+impl Event {
+    pub fn new() -> io::Result<Self> {
+        let handle = unsafe { CreateEventW(ptr::null_mut(), 1, 0, ptr::null()) };
+        ok_or_ret_errno!(!handle.is_null() => Self(unsafe {
+            // SAFETY: we just created this handle
+            OwnedHandle::from_raw_handle(handle)
+        }))
+    }
+    pub fn set(&self) -> io::Result<()> {
+        let success = unsafe { SetEvent(self.0.as_raw_handle()) != 0 };
+        ok_or_ret_errno!(success => ())
+    }
+    pub fn reset(&self) -> io::Result<()> {
+        let success = unsafe { ResetEvent(self.0.as_raw_handle()) != 0 };
+        ok_or_ret_errno!(success => ())
+    }
+    pub fn is_set(&self) -> io::Result<bool> {
+        self.wait_ms(0)
+    }
+    pub fn wait(&self) -> io::Result<()> {
+        while !self.wait_timeout(None)? {}
+        Ok(())
+    }
+    /// Returns `false` if the timeout ran out before the event was set.
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        self.wait_ms(c_wrappers::timeout_to_ms(timeout))
+    }
+    fn wait_ms(&self, timeout_ms: DWORD) -> io::Result<bool> {
+        match unsafe { WaitForSingleObject(self.0.as_raw_handle(), timeout_ms) } {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+    /// Waits on a thread from the blocking pool of the runtime, since events can't be registered in its event loop.
+    #[cfg(feature = "tokio")]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        let event = self.try_clone()?;
+        tokio::task::spawn_blocking(move || event.wait())
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+}
+impl TryClone for Event {
+    fn try_clone(&self) -> io::Result<Self> {
+        c_wrappers::duplicate_handle(self.0.as_handle()).map(Self)
+    }
+}
+impl AsHandle for Event {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl From<Event> for OwnedHandle {
+    #[inline]
+    fn from(event: Event) -> Self {
+        event.0
+    }
+}
+impl From<OwnedHandle> for Event {
+    #[inline]
+    fn from(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+}
+impl Debug for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Event").field(&self.0.as_raw_handle()).finish()
+    }
+}
@@ -1,7 +1,10 @@
 use super::{c_wrappers, downgrade_eof, winprelude::*};
 use crate::TryClone;
 use std::{io, mem::MaybeUninit, ptr};
-use winapi::um::fileapi::{FlushFileBuffers, ReadFile, WriteFile};
+use winapi::{
+    shared::winerror::ERROR_NO_DATA,
+    um::fileapi::{FlushFileBuffers, ReadFile, WriteFile},
+};
 
 /// Newtype wrapper which defines file I/O operations on a `HANDLE` to a file.
 #[repr(transparent)]
@@ -27,7 +30,13 @@ impl FileHandle {
             );
             (result != 0, num_bytes_read as usize)
         };
-        downgrade_eof(ok_or_ret_errno!(success => num_bytes_read))
+        // Reading from a named pipe in nonblocking mode fails with ERROR_NO_DATA if there's nothing to read. The
+        // standard library maps that to BrokenPipe, which would otherwise be mistaken for EOF below.
+        let r = match ok_or_ret_errno!(success => num_bytes_read) {
+            Err(e) if e.raw_os_error() == Some(ERROR_NO_DATA as _) => Err(io::ErrorKind::WouldBlock.into()),
+            els => els,
+        };
+        downgrade_eof(r)
     }
     pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
         let len = DWORD::try_from(buf.len()).map_err(|_| {
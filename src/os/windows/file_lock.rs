@@ -0,0 +1,69 @@
+use super::winprelude::*;
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs::File,
+    io, mem,
+};
+use winapi::{
+    shared::{minwindef::MAXDWORD, winerror::ERROR_LOCK_VIOLATION},
+    um::{
+        fileapi::{LockFileEx, UnlockFileEx},
+        minwinbase::{LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY, OVERLAPPED},
+    },
+};
+
+/// A file locked with `LockFileEx`, over the largest possible byte range so as to cover the whole file.
+pub(crate) struct FileLock(File);
+impl FileLock {
+    #[inline]
+    pub fn file(&self) -> &File {
+        &self.0
+    }
+    #[inline]
+    pub fn into_file(self) -> File {
+        self.0
+    }
+
+    pub fn lock(&self, exclusive: bool, nonblocking: bool) -> io::Result<()> {
+        let mut flags = 0;
+        if exclusive {
+            flags |= LOCKFILE_EXCLUSIVE_LOCK;
+        }
+        if nonblocking {
+            flags |= LOCKFILE_FAIL_IMMEDIATELY;
+        }
+        // The offset of the range lives in the OVERLAPPED structure, and is zero here. For a file opened without
+        // FILE_FLAG_OVERLAPPED, which is what std opens files as, the call completes synchronously.
+        let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+        let success = unsafe { LockFileEx(self.0.as_raw_handle(), flags, 0, MAXDWORD, MAXDWORD, &mut overlapped) != 0 };
+        if success {
+            return Ok(());
+        }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() == Some(ERROR_LOCK_VIOLATION as _) {
+            return Err(io::Error::from(io::ErrorKind::WouldBlock));
+        }
+        Err(e)
+    }
+    pub fn unlock(&self) {
+        let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+        unsafe { UnlockFileEx(self.0.as_raw_handle(), 0, MAXDWORD, MAXDWORD, &mut overlapped) };
+    }
+}
+impl From<File> for FileLock {
+    #[inline]
+    fn from(file: File) -> Self {
+        Self(file)
+    }
+}
+impl AsHandle for FileLock {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl Debug for FileLock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FileLock").field(&self.0.as_raw_handle()).finish()
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    local_socket::ToLocalSocketName,
+    os::windows::named_pipe::{
+        pipe_mode, DuplexPipeStream, PipeListener as GenericPipeListener, PipeListenerOptions, PipeMode,
+    },
+    reliable_recv_msg::{RecvResult, ReliableRecvMsg},
+};
+use std::io;
+
+type PipeListener = GenericPipeListener<pipe_mode::Messages, pipe_mode::Messages>;
+type PipeStream = DuplexPipeStream<pipe_mode::Messages>;
+
+/// Message-mode named pipes are available on every version of Windows, and so there's no fallback.
+#[derive(Debug)]
+pub struct FramedLocalSocketListener(PipeListener);
+impl FramedLocalSocketListener {
+    pub fn bind<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        let name = name.to_local_socket_name()?;
+        let inner = PipeListenerOptions::new()
+            .name(name.into_inner())
+            .mode(PipeMode::Messages)
+            .create()?;
+        Ok(Self(inner))
+    }
+    pub fn accept(&self) -> io::Result<FramedLocalSocket> {
+        let inner = self.0.accept()?;
+        Ok(FramedLocalSocket {
+            pipe: inner,
+            buf: Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FramedLocalSocket {
+    pipe: PipeStream,
+    buf: Vec<u8>,
+}
+impl FramedLocalSocket {
+    pub fn connect<'a>(name: impl ToLocalSocketName<'a>) -> io::Result<Self> {
+        let name = name.to_local_socket_name()?;
+        let pipe = PipeStream::connect(name.inner())?;
+        Ok(Self { pipe, buf: Vec::new() })
+    }
+    pub fn is_native(&self) -> bool {
+        true
+    }
+    pub fn send_msg(&mut self, msg: &[u8]) -> io::Result<()> {
+        crate::datagram::check_sent(self.pipe.send(msg)?, msg.len())
+    }
+    /// Receives into the capacity left over from previous messages, which the pipe replaces with a larger allocation
+    /// whenever a message doesn't fit.
+    pub fn recv_msg(&mut self) -> io::Result<Option<&[u8]>> {
+        let capacity = self.buf.capacity().max(1024);
+        self.buf.resize(capacity, 0);
+        match ReliableRecvMsg::recv(&mut self.pipe, &mut self.buf) {
+            Ok(RecvResult::Fit(len)) => self.buf.truncate(len),
+            Ok(RecvResult::Alloc(msg)) => self.buf = msg,
+            Err(e) if super::super::is_eof_like(&e) => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        Ok(Some(&self.buf))
+    }
+}
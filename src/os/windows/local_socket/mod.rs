@@ -1,14 +1,21 @@
 //! Adapter module, implements local sockets under Windows.
 
-use crate::local_socket::{LocalSocketName, NameTypeSupport};
+use crate::{
+    local_socket::{auth::PeerCredentials, LocalSocketName, NameTypeSupport},
+    os::windows::c_wrappers,
+};
 use std::{
     borrow::Cow,
     ffi::{OsStr, OsString},
+    io,
 };
 
 #[cfg(feature = "tokio")]
 pub mod tokio;
 
+mod framed;
+pub use framed::*;
+
 mod listener;
 pub use listener::*;
 
@@ -17,6 +24,17 @@ pub use stream::*;
 
 pub const NAME_TYPE_ALWAYS_SUPPORTED: NameTypeSupport = NameTypeSupport::OnlyNamespaced;
 
+pub fn current_credentials() -> io::Result<PeerCredentials> {
+    Ok(process_credentials(std::process::id()))
+}
+fn process_credentials(pid: u32) -> PeerCredentials {
+    PeerCredentials {
+        pid: Some(pid),
+        sid: c_wrappers::process_user_sid(pid).ok(),
+        image_path: c_wrappers::process_image_path(pid).ok(),
+    }
+}
+
 pub fn name_type_support_query() -> NameTypeSupport {
     NAME_TYPE_ALWAYS_SUPPORTED
 }
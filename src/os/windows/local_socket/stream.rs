@@ -1,12 +1,18 @@
 use crate::{
     error::FromHandleError,
-    local_socket::ToLocalSocketName,
-    os::windows::named_pipe::{pipe_mode, DuplexPipeStream},
+    local_socket::{auth::PeerCredentials, HandleToken, ToLocalSocketName},
+    os::windows::{
+        c_wrappers,
+        named_pipe::{pipe_mode, DuplexPipeStream},
+    },
 };
 use std::{
     io::{self, prelude::*, IoSlice, IoSliceMut},
     os::windows::prelude::*,
+    thread,
+    time::{Duration, Instant},
 };
+use winapi::um::handleapi::INVALID_HANDLE_VALUE;
 
 type PipeStream = DuplexPipeStream<pipe_mode::Bytes>;
 #[derive(Debug)]
@@ -17,10 +23,97 @@ impl LocalSocketStream {
         let inner = PipeStream::connect(name.inner())?;
         Ok(Self(inner))
     }
+    /// Named pipe streams validate the handle by querying the pipe, which fails for anything that isn't one.
+    pub fn from_stdin() -> io::Result<Self> {
+        let handle = io::stdin().as_handle().try_clone_to_owned()?;
+        Self::try_from(handle).map_err(io::Error::from)
+    }
     #[inline]
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
         self.0.set_nonblocking(nonblocking)
     }
+    /// Named pipes can't be half-closed, and so there's nothing to do.
+    #[inline]
+    pub fn shutdown_write(&self) -> io::Result<()> {
+        Ok(())
+    }
+    /// Named pipes can only be peeked at without waiting, and so the pipe is polled for data before every peek in the
+    /// same way as by [`wait_readable()`](Self::wait_readable).
+    pub fn read_until(&mut self, delim: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        crate::delimited::read_until_with(
+            self,
+            delim,
+            buf,
+            |s, b| {
+                s.wait_readable(Duration::MAX)?;
+                match c_wrappers::peek(s.as_handle(), b) {
+                    Err(e) if super::super::is_eof_like(&e) => Ok(0),
+                    r => r,
+                }
+            },
+            |s, b| s.0.read(b),
+        )
+    }
+    /// Returns `false` if the timeout ran out before the stream became readable.
+    ///
+    /// Named pipe handles opened for synchronous I/O can't be waited on for readiness, so the pipe is polled with a
+    /// gradually increasing interval.
+    pub fn wait_readable(&self, timeout: Duration) -> io::Result<bool> {
+        const MAX_INTERVAL: Duration = Duration::from_millis(16);
+        let deadline = Instant::now().checked_add(timeout);
+        let mut interval = Duration::from_millis(1);
+        loop {
+            match c_wrappers::bytes_available(self.as_handle()) {
+                Ok(0) => {}
+                // The read will report the data or the closure of the pipe.
+                Ok(_) => return Ok(true),
+                Err(e) if super::super::is_eof_like(&e) => return Ok(true),
+                Err(e) => return Err(e),
+            }
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => MAX_INTERVAL,
+            };
+            if remaining.is_zero() {
+                return Ok(false);
+            }
+            thread::sleep(interval.min(remaining));
+            interval = (interval * 2).min(MAX_INTERVAL);
+        }
+    }
+
+    fn peer_pid(&self) -> io::Result<u32> {
+        if self.0.is_server() {
+            self.0.client_process_id()
+        } else {
+            self.0.server_process_id()
+        }
+    }
+    /// The user and the executable are looked up from the process ID, and are left out if the peer can't be queried.
+    pub fn peer_credentials(&self) -> io::Result<PeerCredentials> {
+        Ok(super::process_credentials(self.peer_pid()?))
+    }
+
+    /// The handle is duplicated into the peer right away, and the token is its value there.
+    pub fn send_handle(&mut self, handle: BorrowedHandle<'_>) -> io::Result<HandleToken> {
+        let peer = c_wrappers::open_process_for_duplication(self.peer_pid()?)?;
+        let raw = c_wrappers::duplicate_handle_to_foreign(handle, peer.as_handle())?;
+        Ok(HandleToken::from_raw(raw as usize as u64))
+    }
+    /// # Safety
+    /// The token must stand for a handle which was duplicated into this process and hasn't been taken yet.
+    pub unsafe fn recv_handle(&mut self, token: HandleToken) -> io::Result<OwnedHandle> {
+        let raw = usize::try_from(token.into_raw())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "handle token out of range"))?
+            as RawHandle;
+        if raw.is_null() || raw == INVALID_HANDLE_VALUE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid handle token"));
+        }
+        Ok(unsafe {
+            // SAFETY: upheld by the caller
+            OwnedHandle::from_raw_handle(raw)
+        })
+    }
 }
 
 // The thunking already happens inside.
@@ -33,6 +126,11 @@ impl Read for LocalSocketStream {
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
         self.0.read_vectored(bufs)
     }
+    #[cfg(feature = "read_buf")]
+    #[inline]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        self.0.read_buf(cursor)
+    }
 }
 impl Write for LocalSocketStream {
     #[inline]
@@ -17,6 +17,7 @@ use std::{
     pin::Pin,
     task::{Context, Poll},
 };
+use tokio::io::{AsyncRead as TokioAsyncRead, ReadBuf as TokioReadBuf};
 
 type StreamImpl = DuplexPipeStream<pipe_mode::Bytes>;
 
@@ -28,6 +29,11 @@ impl LocalSocketStream {
         let inner = DuplexPipeStream::connect(name.inner()).await?;
         Ok(Self(inner))
     }
+    /// Named pipes can't be half-closed, and so there's nothing to do.
+    #[inline]
+    pub fn shutdown_write(&self) -> io::Result<()> {
+        Ok(())
+    }
     #[inline]
     pub fn split(self) -> (ReadHalf, WriteHalf) {
         let (r, w) = self.0.split();
@@ -50,7 +56,13 @@ impl LocalSocketStream {
 impl AsyncRead for LocalSocketStream {
     #[inline]
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
-        self.pinproj().poll_read(cx, buf)
+        AsyncRead::poll_read(self.pinproj(), cx, buf)
+    }
+}
+impl TokioAsyncRead for LocalSocketStream {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        TokioAsyncRead::poll_read(self.pinproj(), cx, buf)
     }
 }
 impl AsyncWrite for LocalSocketStream {
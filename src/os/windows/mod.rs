@@ -5,11 +5,26 @@ pub mod named_pipe;
 pub mod unnamed_pipe;
 // TODO mailslots
 //pub mod mailslot;
+pub(crate) mod doorbell;
+pub(crate) mod event;
+pub(crate) mod file_lock;
 pub(crate) mod local_socket;
+pub(crate) mod named_condvar;
+pub(crate) mod named_event;
+pub(crate) mod named_mutex;
+pub(crate) mod named_rwlock;
+pub(crate) mod named_semaphore;
+pub(crate) mod park;
+pub(crate) mod peer_watcher;
+pub(crate) mod shared_memory;
+pub(crate) mod spawn;
 
 mod file_handle;
 pub(crate) use file_handle::*;
 
+mod security_descriptor;
+pub use security_descriptor::*;
+
 use std::{io, task::Poll};
 mod winprelude {
     pub use std::os::windows::prelude::*;
@@ -51,9 +66,46 @@ pub trait ShareHandle: AsHandle {
     fn share(&self, receiver: BorrowedHandle<'_>) -> io::Result<HANDLE> {
         c_wrappers::duplicate_handle_to_foreign(self.as_handle(), receiver)
     }
+    /// Like [`.share()`](Self::share), but takes the ID of the receiving process instead of a handle to it, which is
+    /// more convenient when the process was spawned with [`std::process::Command`] – see
+    /// [`Child::id()`](std::process::Child::id).
+    ///
+    /// # System calls
+    /// - `OpenProcess` (with `PROCESS_DUP_HANDLE` access)
+    /// - `DuplicateHandle`
+    fn share_with_pid(&self, pid: u32) -> io::Result<HANDLE> {
+        let receiver = c_wrappers::open_process_for_duplication(pid)?;
+        c_wrappers::duplicate_handle_to_foreign(self.as_handle(), receiver.as_handle())
+    }
+    /// Sets whether the handle is inherited by child processes spawned afterwards. Handles created by this crate are not
+    /// inheritable by default.
+    ///
+    /// Inheritance applies to all child processes which are spawned with handle inheritance enabled, which is the case
+    /// for every process spawned by [`std::process::Command`], and not just the one the handle is meant for. Consider
+    /// disabling inheritance again right after spawning the intended child, or using
+    /// [`.share_with_pid()`](Self::share_with_pid) instead.
+    ///
+    /// # System calls
+    /// - `SetHandleInformation`
+    fn set_inheritable(&self, inheritable: bool) -> io::Result<()> {
+        c_wrappers::set_inheritable(self.as_handle(), inheritable)
+    }
+    /// Returns whether the handle is inherited by child processes. See [`.set_inheritable()`](Self::set_inheritable).
+    ///
+    /// # System calls
+    /// - `GetHandleInformation`
+    fn is_inheritable(&self) -> io::Result<bool> {
+        c_wrappers::is_inheritable(self.as_handle())
+    }
 }
 impl ShareHandle for crate::unnamed_pipe::UnnamedPipeReader {}
 impl ShareHandle for crate::unnamed_pipe::UnnamedPipeWriter {}
+impl ShareHandle for crate::shared_memory::SharedMemory {}
+impl ShareHandle for crate::shared_memory::Doorbell {}
+impl ShareHandle for crate::sync::Event {}
+impl<Rm: named_pipe::PipeModeTag, Sm: named_pipe::PipeModeTag> ShareHandle for named_pipe::PipeStream<Rm, Sm> {}
+#[cfg(feature = "tokio")]
+impl<Rm: named_pipe::PipeModeTag, Sm: named_pipe::PipeModeTag> ShareHandle for named_pipe::tokio::PipeStream<Rm, Sm> {}
 
 fn is_eof_like(e: &io::Error) -> bool {
     e.kind() == io::ErrorKind::BrokenPipe
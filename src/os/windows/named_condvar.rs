@@ -0,0 +1,106 @@
+use super::{named_mutex::NamedMutex, named_semaphore::NamedSemaphore, shared_memory::SharedMemory};
+use crate::sync::LockResult;
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::size_of,
+    sync::atomic::{AtomicU32, Ordering::SeqCst},
+    time::Duration,
+};
+
+/// A condition variable emulated with a waiter count in shared memory and a semaphore to wake the waiters up with.
+///
+/// Windows condition variables and `WaitOnAddress` only work within a single process, and keyed events aren't part of
+/// the documented API, hence the emulation. Notifying a condition variable takes waiters off the count and releases
+/// one semaphore permit for each of them. A thread which starts waiting afterwards can take a permit meant for an
+/// earlier waiter, which is indistinguishable from a spurious wakeup of the former and a late notification of the
+/// latter.
+pub(crate) struct NamedCondvar {
+    /// Holds the number of waiters which haven't been notified yet, and is zeroed upon creation.
+    shm: SharedMemory,
+    sem: NamedSemaphore,
+}
+impl NamedCondvar {
+    /// The semaphore is created first, so that it exists by the time the shared memory object can be opened.
+    pub fn create(name: &OsStr, _keep_drop_guard: bool) -> io::Result<Self> {
+        let sem = NamedSemaphore::create(&semaphore_name(name), 0, false)?;
+        let shm = SharedMemory::create(name, size_of::<AtomicU32>(), false)?;
+        Ok(Self { shm, sem })
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let shm = SharedMemory::open(name)?;
+        if shm.len() < size_of::<AtomicU32>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory object is too small to contain a condition variable",
+            ));
+        }
+        let sem = NamedSemaphore::open(&semaphore_name(name))?;
+        Ok(Self { shm, sem })
+    }
+
+    #[inline]
+    fn waiters(&self) -> &AtomicU32 {
+        // SAFETY: the mapping is page-aligned and large enough
+        unsafe { &*self.shm.as_ptr().cast::<AtomicU32>() }
+    }
+    /// Takes a waiter off the count, returning `false` if there were none left.
+    fn take_waiter(&self) -> bool {
+        self.waiters()
+            .fetch_update(SeqCst, SeqCst, |w| w.checked_sub(1))
+            .is_ok()
+    }
+
+    /// Must be called with `mutex` locked. On error, the lock is released.
+    pub fn wait(&self, mutex: &NamedMutex) -> io::Result<LockResult<()>> {
+        self.waiters().fetch_add(1, SeqCst);
+        mutex.unlock();
+        // A failed wait leaves a stale waiter on the count, which only costs a future notification.
+        self.sem.acquire()?;
+        mutex.lock()
+    }
+    /// Must be called with `mutex` locked. On error, the lock is released. The flag is `true` if the timeout ran out.
+    pub fn wait_timeout(&self, mutex: &NamedMutex, timeout: Duration) -> io::Result<(LockResult<()>, bool)> {
+        self.waiters().fetch_add(1, SeqCst);
+        mutex.unlock();
+        let timed_out = match self.sem.acquire_timeout(timeout)? {
+            true => false,
+            // Taking ourselves off the count means that nobody will release a permit for us.
+            false if self.take_waiter() => true,
+            // Otherwise, a notifier has already counted us in, and its permit is on its way.
+            false => {
+                self.sem.acquire()?;
+                false
+            }
+        };
+        Ok((mutex.lock()?, timed_out))
+    }
+    pub fn notify_one(&self) -> io::Result<()> {
+        if self.take_waiter() {
+            self.sem.release(1)?;
+        }
+        Ok(())
+    }
+    pub fn notify_all(&self) -> io::Result<()> {
+        match self.waiters().swap(0, SeqCst) {
+            0 => Ok(()),
+            n => self.sem.release(n),
+        }
+    }
+}
+impl Debug for NamedCondvar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedCondvar")
+            .field("shm", &self.shm)
+            .field("sem", &self.sem)
+            .finish()
+    }
+}
+
+/// Section and semaphore objects share a namespace, so the semaphore needs a name of its own.
+fn semaphore_name(name: &OsStr) -> OsString {
+    let mut sem_name = name.to_owned();
+    sem_name.push(".condvar");
+    sem_name
+}
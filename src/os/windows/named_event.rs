@@ -0,0 +1,88 @@
+use super::{event::Event, winprelude::*};
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io, iter, ptr,
+    time::Duration,
+};
+use winapi::{
+    shared::winerror::ERROR_ALREADY_EXISTS,
+    um::{
+        synchapi::{CreateEventW, OpenEventW},
+        winnt::{EVENT_MODIFY_STATE, SYNCHRONIZE},
+    },
+};
+
+/// A named manual-reset event object, which is otherwise the same as an unnamed one.
+pub(crate) struct NamedEvent(Event);
+impl NamedEvent {
+    /// The event object is destroyed along with its last handle, and so there is no drop guard to keep.
+    pub fn create(name: &OsStr, _keep_drop_guard: bool) -> io::Result<Self> {
+        let name = to_wide(name)?;
+        let handle = unsafe { CreateEventW(ptr::null_mut(), 1, 0, name.as_ptr()) };
+        let handle = ok_or_ret_errno!(!handle.is_null() => unsafe {
+            // SAFETY: we just created this handle
+            OwnedHandle::from_raw_handle(handle)
+        })?;
+        // CreateEventW() opens the existing object instead of failing, leaving a note for us to find.
+        if io::Error::last_os_error().raw_os_error() == Some(ERROR_ALREADY_EXISTS as _) {
+            return Err(io::Error::from_raw_os_error(ERROR_ALREADY_EXISTS as _));
+        }
+        Ok(Self(Event::from(handle)))
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let name = to_wide(name)?;
+        let handle = unsafe { OpenEventW(SYNCHRONIZE | EVENT_MODIFY_STATE, 0, name.as_ptr()) };
+        ok_or_ret_errno!(!handle.is_null() => Self(Event::from(unsafe {
+            // SAFETY: we just opened this handle
+            OwnedHandle::from_raw_handle(handle)
+        })))
+    }
+
+    #[inline]
+    pub fn set(&self) -> io::Result<()> {
+        self.0.set()
+    }
+    #[inline]
+    pub fn reset(&self) -> io::Result<()> {
+        self.0.reset()
+    }
+    #[inline]
+    pub fn is_set(&self) -> io::Result<bool> {
+        self.0.is_set()
+    }
+    #[inline]
+    pub fn wait(&self) -> io::Result<()> {
+        self.0.wait()
+    }
+    #[inline]
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        self.0.wait_timeout(timeout)
+    }
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        self.0.wait_async().await
+    }
+}
+impl AsHandle for NamedEvent {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl Debug for NamedEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NamedEvent").field(&self.0).finish()
+    }
+}
+
+fn to_wide(name: &OsStr) -> io::Result<Vec<u16>> {
+    if name.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "event names must be non-empty",
+        ));
+    }
+    Ok(name.encode_wide().chain(iter::once(0)).collect())
+}
@@ -0,0 +1,88 @@
+use super::winprelude::*;
+use crate::sync::LockResult;
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io, iter, ptr,
+};
+use winapi::{
+    shared::winerror::{ERROR_ALREADY_EXISTS, WAIT_TIMEOUT},
+    um::{
+        synchapi::{CreateMutexW, OpenMutexW, ReleaseMutex, WaitForSingleObject},
+        winbase::{INFINITE, WAIT_ABANDONED, WAIT_OBJECT_0},
+        winnt::{MUTEX_MODIFY_STATE, SYNCHRONIZE},
+    },
+};
+
+/// A named mutex object, which the system marks as abandoned if its owner exits without releasing it.
+pub(crate) struct NamedMutex(OwnedHandle);
+impl NamedMutex {
+    /// The mutex object is destroyed along with its last handle, and so there is no drop guard to keep.
+    pub fn create(name: &OsStr, _keep_drop_guard: bool) -> io::Result<Self> {
+        let name = to_wide(name)?;
+        let handle = unsafe { CreateMutexW(ptr::null_mut(), 0, name.as_ptr()) };
+        let handle = ok_or_ret_errno!(!handle.is_null() => unsafe {
+            // SAFETY: we just created this handle
+            OwnedHandle::from_raw_handle(handle)
+        })?;
+        // CreateMutexW() opens the existing object instead of failing, leaving a note for us to find.
+        if io::Error::last_os_error().raw_os_error() == Some(ERROR_ALREADY_EXISTS as _) {
+            return Err(io::Error::from_raw_os_error(ERROR_ALREADY_EXISTS as _));
+        }
+        Ok(Self(handle))
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let name = to_wide(name)?;
+        let handle = unsafe { OpenMutexW(SYNCHRONIZE | MUTEX_MODIFY_STATE, 0, name.as_ptr()) };
+        ok_or_ret_errno!(!handle.is_null() => Self(unsafe {
+            // SAFETY: we just opened this handle
+            OwnedHandle::from_raw_handle(handle)
+        }))
+    }
+
+    pub fn lock(&self) -> io::Result<LockResult<()>> {
+        self.wait(INFINITE)
+    }
+    pub fn try_lock(&self) -> io::Result<LockResult<()>> {
+        self.wait(0)
+    }
+    fn wait(&self, timeout_ms: DWORD) -> io::Result<LockResult<()>> {
+        match unsafe { WaitForSingleObject(self.0.as_raw_handle(), timeout_ms) } {
+            WAIT_OBJECT_0 => Ok(LockResult::Acquired(())),
+            WAIT_ABANDONED => Ok(LockResult::OwnerDied(())),
+            WAIT_TIMEOUT => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+    /// Abandoned mutexes are usable as soon as they're acquired again, and so there's nothing to do.
+    pub fn mark_consistent(&self) -> io::Result<()> {
+        Ok(())
+    }
+    pub fn unlock(&self) {
+        unsafe {
+            // SAFETY: only called by the guard, i.e. on the thread that holds the lock
+            ReleaseMutex(self.0.as_raw_handle());
+        }
+    }
+}
+impl AsHandle for NamedMutex {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl Debug for NamedMutex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NamedMutex").field(&self.0.as_raw_handle()).finish()
+    }
+}
+
+fn to_wide(name: &OsStr) -> io::Result<Vec<u16>> {
+    if name.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "mutex names must be non-empty",
+        ));
+    }
+    Ok(name.encode_wide().chain(iter::once(0)).collect())
+}
@@ -0,0 +1,61 @@
+use super::{path_conversion, WaitTimeout};
+use crate::os::windows::winprelude::*;
+use std::{ffi::OsStr, io, time::Duration};
+use winapi::um::namedpipeapi::CallNamedPipeW;
+
+/// Connects to a message-mode named pipe (the `\\.\pipe\` prefix is added automatically), sends a request message,
+/// receives the response message into the given buffer and closes the connection, returning the size of the response.
+///
+/// This is a convenience function for clients which only ever perform one request-response exchange per connection
+/// and thus have no use for a persistent [`PipeStream`](super::PipeStream). If all instances of the pipe are busy,
+/// waits for one to become available for at most `timeout`, or for the default timeout specified by the server if
+/// `timeout` is `None`.
+///
+/// # Errors
+/// If the response doesn't fit into `response`, the system returns the `ERROR_MORE_DATA` error and the rest of the
+/// response is discarded. Make sure to provide a buffer big enough for the largest possible response.
+///
+/// The pipe must be created with the [`Messages`](super::PipeMode::Messages) mode.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::os::windows::named_pipe;
+/// use std::time::Duration;
+///
+/// let mut buffer = [0; 128];
+/// let len = named_pipe::call("Example", b"Hello from client!", &mut buffer, Some(Duration::from_secs(1)))?;
+/// println!("Server answered: {}", String::from_utf8_lossy(&buffer[..len]));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// # System calls
+/// - `CallNamedPipeW`
+pub fn call(
+    pipename: impl AsRef<OsStr>,
+    request: &[u8],
+    response: &mut [u8],
+    timeout: Option<Duration>,
+) -> io::Result<usize> {
+    _call(pipename.as_ref(), request, response, timeout)
+}
+fn _call(pipename: &OsStr, request: &[u8], response: &mut [u8], timeout: Option<Duration>) -> io::Result<usize> {
+    let path = path_conversion::convert_and_encode_path(pipename, None);
+    let request_len = DWORD::try_from(request.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "request is too big"))?;
+    let response_len = DWORD::try_from(response.len()).unwrap_or(DWORD::MAX);
+    let timeout = timeout.map_or(WaitTimeout::DEFAULT, WaitTimeout::from_duration);
+
+    let mut received: DWORD = 0;
+    let success = unsafe {
+        CallNamedPipeW(
+            path.as_ptr(),
+            request.as_ptr() as *mut _,
+            request_len,
+            response.as_mut_ptr().cast(),
+            response_len,
+            &mut received,
+            timeout.into(),
+        ) != 0
+    };
+    ok_or_ret_errno!(success => received as usize)
+}
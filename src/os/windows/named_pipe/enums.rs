@@ -3,7 +3,8 @@ use crate::os::windows::winprelude::*;
 use std::{convert::TryFrom, mem};
 use winapi::um::winbase::{
     PIPE_ACCESS_DUPLEX, PIPE_ACCESS_INBOUND, PIPE_ACCESS_OUTBOUND, PIPE_READMODE_BYTE, PIPE_READMODE_MESSAGE,
-    PIPE_TYPE_BYTE, PIPE_TYPE_MESSAGE,
+    PIPE_TYPE_BYTE, PIPE_TYPE_MESSAGE, SECURITY_ANONYMOUS, SECURITY_DELEGATION, SECURITY_IDENTIFICATION,
+    SECURITY_IMPERSONATION,
 };
 
 /// The direction of a named pipe connection, designating who can read data and who can write it. This describes the
@@ -211,3 +212,26 @@ impl TryFrom<DWORD> for PipeMode {
         }
     }
 }
+
+/// The extent to which a named pipe server is allowed to impersonate a client, chosen by the client when connecting.
+///
+/// See [`ConnectOptions::impersonation_level`](super::ConnectOptions::impersonation_level).
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ImpersonationLevel {
+    /// The server can neither identify nor impersonate the client.
+    Anonymous = SECURITY_ANONYMOUS,
+    /// The server can obtain the identity and privileges of the client to perform access checks, but cannot act as
+    /// the client.
+    Identification = SECURITY_IDENTIFICATION,
+    /// The server can act as the client on the local computer. This is what the system allows if no level is
+    /// specified.
+    Impersonation = SECURITY_IMPERSONATION,
+    /// The server can act as the client on the local computer and on remote computers.
+    Delegation = SECURITY_DELEGATION,
+}
+impl From<ImpersonationLevel> for DWORD {
+    fn from(op: ImpersonationLevel) -> Self {
+        op as _
+    }
+}
@@ -0,0 +1,72 @@
+use super::PipeMode;
+use crate::os::windows::winprelude::*;
+use std::{io, ptr};
+use winapi::um::{
+    namedpipeapi::{GetNamedPipeHandleStateW, SetNamedPipeHandleState},
+    winbase::{PIPE_NOWAIT, PIPE_READMODE_MESSAGE},
+};
+
+/// The current state of a named pipe handle, as returned by
+/// [`PipeStream::handle_state()`](super::PipeStream::handle_state).
+///
+/// Unlike [`PipeInfo`](super::PipeInfo), which describes the pipe itself, this describes settings that can be changed
+/// at runtime, some of them on a per-handle basis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct PipeHandleState {
+    /// The mode in which data is read from the handle. Can be changed with [`set_read_mode()`].
+    pub read_mode: PipeMode,
+    /// Whether the handle is in nonblocking mode.
+    pub nonblocking: bool,
+    /// The amount of currently existing instances of the pipe.
+    pub current_instances: u32,
+}
+impl PipeHandleState {
+    /// Queries the state of the given named pipe handle.
+    ///
+    /// # System calls
+    /// - `GetNamedPipeHandleStateW`
+    pub fn query(handle: BorrowedHandle<'_>) -> io::Result<Self> {
+        let (mut state, mut current_instances) = (0, 0);
+        let success = unsafe {
+            // The collection parameters and the user name are only valid on one side of the pipe and would cause the
+            // call to fail on the other one, so they're not requested.
+            GetNamedPipeHandleStateW(
+                handle.as_raw_handle(),
+                &mut state,
+                &mut current_instances,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+            ) != 0
+        };
+        ok_or_ret_errno!(success => Self {
+            read_mode: if state & PIPE_READMODE_MESSAGE != 0 {
+                PipeMode::Messages
+            } else {
+                PipeMode::Bytes
+            },
+            nonblocking: state & PIPE_NOWAIT != 0,
+            current_instances,
+        })
+    }
+}
+
+/// Switches the given named pipe handle between byte and message read mode, leaving its nonblocking mode as is.
+///
+/// Handles opened with `CreateFileW` start out in byte read mode regardless of the pipe's mode, so externally created
+/// client handles to message-mode pipes need to be switched to message read mode before being wrapped into a
+/// [`PipeStream`](super::PipeStream) which receives messages. Message read mode cannot be enabled on pipes which don't
+/// preserve message boundaries.
+///
+/// # System calls
+/// - `GetNamedPipeHandleStateW`
+/// - `SetNamedPipeHandleState`
+pub fn set_read_mode(handle: BorrowedHandle<'_>, read_mode: PipeMode) -> io::Result<()> {
+    let nonblocking = PipeHandleState::query(handle)?.nonblocking;
+    let mut mode = read_mode.to_readmode() | if nonblocking { PIPE_NOWAIT } else { 0 };
+    let success =
+        unsafe { SetNamedPipeHandleState(handle.as_raw_handle(), &mut mode, ptr::null_mut(), ptr::null_mut()) != 0 };
+    ok_or_ret_errno!(success => ())
+}
@@ -1,27 +1,34 @@
-use super::{path_conversion, pipe_mode, PipeMode, PipeModeTag, PipeStream, PipeStreamRole, RawPipeStream};
-use crate::os::windows::{c_wrappers::init_security_attributes, winprelude::*, FileHandle};
+use super::{
+    path_conversion, pipe_mode, PipeMode, PipeModeTag, PipeStream, PipeStreamRole, RawPipeStream, WaitTimeout,
+};
+use crate::os::windows::{c_wrappers::init_security_attributes, winprelude::*, FileHandle, SecurityDescriptor};
 use std::{
     borrow::Cow,
+    collections::VecDeque,
+    error::Error,
     ffi::OsStr,
-    fmt::{self, Debug, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     io,
     marker::PhantomData,
-    mem::replace,
-    num::{NonZeroU32, NonZeroU8},
+    num::NonZeroU8,
     ptr,
     sync::{
         atomic::{AtomicBool, Ordering::Relaxed},
         Mutex,
     },
+    thread,
+    time::{Duration, Instant},
 };
 use to_method::To;
 use winapi::{
-    shared::winerror::ERROR_PIPE_CONNECTED,
+    shared::winerror::{
+        ERROR_ACCESS_DENIED, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, ERROR_PIPE_LISTENING, ERROR_PIPE_NOT_CONNECTED,
+    },
     um::{
         namedpipeapi::{ConnectNamedPipe, CreateNamedPipeW},
         winbase::{
-            FILE_FLAG_FIRST_PIPE_INSTANCE, FILE_FLAG_OVERLAPPED, FILE_FLAG_WRITE_THROUGH, PIPE_NOWAIT,
-            PIPE_REJECT_REMOTE_CLIENTS,
+            FILE_FLAG_FIRST_PIPE_INSTANCE, FILE_FLAG_OVERLAPPED, FILE_FLAG_WRITE_THROUGH, PIPE_ACCEPT_REMOTE_CLIENTS,
+            PIPE_NOWAIT, PIPE_REJECT_REMOTE_CLIENTS,
         },
     },
 };
@@ -33,7 +40,9 @@ use winapi::{
 pub struct PipeListener<Rm: PipeModeTag, Sm: PipeModeTag> {
     config: PipeListenerOptions<'static>, // We need the options to create new instances
     nonblocking: AtomicBool,
-    stored_instance: Mutex<FileHandle>,
+    // Instances which clients can connect to, oldest first. Can hold less than `pending_instances` if the instance
+    // limit was reached when trying to replace instances that were handed out.
+    stored_instances: Mutex<VecDeque<FileHandle>>,
     _phantom: PhantomData<(Rm, Sm)>,
 }
 /// An iterator that infinitely [`accept`]s connections on a [`PipeListener`].
@@ -64,21 +73,132 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeListener<Rm, Sm> {
     /// Blocks until a client connects to the named pipe, creating a `Stream` to communicate with the pipe.
     ///
     /// See `incoming` for an iterator version of this.
+    ///
+    /// # Errors
+    /// If the [instance limit] has been reached, i.e. all instances of the pipe are in use by previously accepted
+    /// streams, an error wrapping [`InstanceLimitReached`] is returned. This allows servers to apply backpressure by
+    /// waiting for some of the existing connections to be dropped before calling this method again.
+    ///
+    /// [instance limit]: PipeListenerOptions::instance_limit
     pub fn accept(&self) -> io::Result<PipeStream<Rm, Sm>> {
+        self.accept_until(None)
+    }
+    /// Like [`.accept()`](Self::accept), but gives up and returns an error of kind
+    /// [`TimedOut`](io::ErrorKind::TimedOut) if no client connects within the specified amount of time. This allows
+    /// servers to periodically check whether they should shut down instead of being stuck in `accept` forever.
+    ///
+    /// The instance which was being waited on stays with the listener and is used by the next call, so a client that
+    /// connects right after the timeout expires is not lost. If the listener is in nonblocking mode, this behaves
+    /// exactly like `accept`.
+    ///
+    /// Since synchronous pipe handles can't have connection operations pending on them without a thread being blocked,
+    /// waiting is done by switching the instance to nonblocking mode and periodically checking whether a client has
+    /// connected, which means that the connection may be noticed up to a few milliseconds late.
+    pub fn accept_timeout(&self, timeout: Duration) -> io::Result<PipeStream<Rm, Sm>> {
+        self.accept_until(Some(Instant::now() + timeout))
+    }
+    fn accept_until(&self, deadline: Option<Instant>) -> io::Result<PipeStream<Rm, Sm>> {
         let instance_to_hand_out = {
-            let mut stored_instance = self.stored_instance.lock().expect("unexpected lock poison");
+            let mut stored_instances = self.stored_instances.lock().expect("unexpected lock poison");
             // Doesn't actually even need to be atomic to begin with, but it's simpler and more
             // convenient to do this instead. The mutex takes care of ordering.
             let nonblocking = self.nonblocking.load(Relaxed);
-            block_on_connect(stored_instance.as_handle())?;
-            let new_instance = self.create_instance(nonblocking)?;
-            replace(&mut *stored_instance, new_instance)
+            // Clients get connected to the oldest instance which is available, so waiting on the front one is enough
+            // even if there are several. The ones behind it only get clients after it does, and those clients are then
+            // picked up by subsequent calls without blocking.
+            let instance = match stored_instances.pop_front() {
+                Some(instance) => instance,
+                None => self
+                    .create_instance(nonblocking)
+                    .map_err(InstanceLimitReached::convert)?,
+            };
+            let connected = match deadline {
+                Some(deadline) if !nonblocking => connect_until(instance.as_handle(), Rm::MODE, deadline),
+                _ => block_on_connect(instance.as_handle()),
+            };
+            if let Err(e) = connected {
+                stored_instances.push_front(instance);
+                return Err(e);
+            }
+            if let Err(e) = self.replenish(&mut stored_instances, nonblocking) {
+                stored_instances.push_front(instance);
+                return Err(e);
+            }
+            instance
         };
 
         let raw = RawPipeStream::new_server(instance_to_hand_out);
 
         Ok(PipeStream::new(raw))
     }
+    /// Accepts up to `max` connections, appending them to `conns` and returning how many were accepted.
+    ///
+    /// In nonblocking mode, this drains the pool of [pending instances] of those which clients have already connected
+    /// to, up to `max`, so that servers woken up by a storm of connections can take them all in one go. If no client
+    /// has connected, an error of kind [`WouldBlock`](io::ErrorKind::WouldBlock) is returned. In blocking mode, this
+    /// waits until `max` connections have been accepted.
+    ///
+    /// An error is only returned if no connection has been accepted. Otherwise, the connections accepted before it are
+    /// kept, and the error, if it's persistent, is returned by the next call. This includes errors wrapping
+    /// [`InstanceLimitReached`].
+    ///
+    /// [pending instances]: PipeListenerOptions::pending_instances
+    pub fn accept_many(&self, conns: &mut Vec<PipeStream<Rm, Sm>>, max: usize) -> io::Result<usize> {
+        let mut accepted = 0;
+        while accepted < max {
+            match self.accept() {
+                Ok(conn) => conns.push(conn),
+                Err(e) if accepted == 0 => return Err(e),
+                Err(..) => break,
+            }
+            accepted += 1;
+        }
+        Ok(accepted)
+    }
+    /// Returns an instance of the pipe that was previously handed out by this listener, allowing it to be used for
+    /// another client. This is cheaper than creating a new instance, which makes a difference for servers with a high
+    /// rate of short-lived connections.
+    ///
+    /// The stream is [disconnected](PipeStream::disconnect) first unless it already has been, discarding any data which
+    /// the client hasn't received yet.
+    ///
+    /// # Errors
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if the stream is client-side or
+    /// split, in which case the stream is dropped. Passing a stream which was accepted by a different listener is a
+    /// logic error, but not UB; the instance will be handed out to clients of this listener as usual, but will be
+    /// waiting for clients of the other named pipe.
+    ///
+    /// # System calls
+    /// - `DisconnectNamedPipe`
+    /// - `SetNamedPipeHandleState`
+    pub fn reuse(&self, stream: PipeStream<Rm, Sm>) -> io::Result<()> {
+        if !stream.is_server() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot reuse a client-side pipe stream as a listener instance",
+            ));
+        }
+        match stream.disconnect() {
+            Err(e) if e.raw_os_error() != Some(ERROR_PIPE_NOT_CONNECTED as _) => return Err(e),
+            _ => {}
+        }
+        let instance = OwnedHandle::try_from(stream).map(FileHandle).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot reuse a split pipe stream as a listener instance",
+            )
+        })?;
+
+        let mut stored_instances = self.stored_instances.lock().expect("unexpected lock poison");
+        // Restore the nonblocking mode of the listener in case it was changed for the stream.
+        unsafe {
+            super::set_nonblocking_for_stream(instance.as_handle(), Rm::MODE, self.nonblocking.load(Relaxed))?;
+        }
+        // Disconnected instances don't take clients until they are waited on, so this one has to go to the back. If it
+        // went to the front, the next call to accept would be stuck on it while clients connect to other instances.
+        stored_instances.push_back(instance);
+        Ok(())
+    }
     /// Creates an iterator which accepts connections from clients, blocking each time `next()` is called until one
     /// connects.
     pub fn incoming(&self) -> Incoming<'_, Rm, Sm> {
@@ -94,15 +214,31 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeListener<Rm, Sm> {
     ///
     /// [`nonblocking` field]: struct.PipeListenerOptions.html#structfield.nonblocking " "
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
-        let instance = self.stored_instance.lock().expect("unexpected lock poison");
+        let instances = self.stored_instances.lock().expect("unexpected lock poison");
         // Doesn't actually even need to be atomic to begin with, but it's simpler and more
         // convenient to do this instead. The mutex takes care of ordering.
         self.nonblocking.store(nonblocking, Relaxed);
-        unsafe {
-            super::set_nonblocking_for_stream(instance.as_handle(), Rm::MODE, nonblocking)?;
+        for instance in &*instances {
+            unsafe {
+                super::set_nonblocking_for_stream(instance.as_handle(), Rm::MODE, nonblocking)?;
+            }
         }
         // Make it clear that the lock survives until this moment.
-        drop(instance);
+        drop(instances);
+        Ok(())
+    }
+
+    /// Creates new instances until there are as many as the pool size specifies, stopping early without an error if
+    /// the instance limit is reached – the connected instances can still be handed out, and the next call to `accept`
+    /// will report the error if the limit is still reached by then.
+    fn replenish(&self, instances: &mut VecDeque<FileHandle>, nonblocking: bool) -> io::Result<()> {
+        while instances.len() < usize::from(self.config.pending_instances.get()) {
+            match self.create_instance(nonblocking) {
+                Ok(instance) => instances.push_back(instance),
+                Err(e) if InstanceLimitReached::is_busy(&e) => break,
+                Err(e) => return Err(e),
+            }
+        }
         Ok(())
     }
 
@@ -116,12 +252,80 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> Debug for PipeListener<Rm, Sm> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("PipeListener")
             .field("config", &self.config)
-            .field("instance", &self.stored_instance)
+            .field("instances", &self.stored_instances)
             .field("nonblocking", &self.nonblocking.load(Relaxed))
             .finish()
     }
 }
 
+/// Error payload returned by [`PipeListener::accept()`] when all instances of the pipe allowed by the
+/// [instance limit] are in use.
+///
+/// Since `accept()` returns [`io::Error`], this type is wrapped in one. Use [`InstanceLimitReached::is_cause_of()`] to
+/// tell it apart from other errors.
+///
+/// [instance limit]: PipeListenerOptions::instance_limit
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InstanceLimitReached;
+impl InstanceLimitReached {
+    /// Returns `true` if the given I/O error was produced because the instance limit was reached.
+    pub fn is_cause_of(e: &io::Error) -> bool {
+        matches!(e.get_ref(), Some(inner) if inner.is::<Self>())
+    }
+    fn is_busy(e: &io::Error) -> bool {
+        e.raw_os_error() == Some(ERROR_PIPE_BUSY as _)
+    }
+    fn convert(e: io::Error) -> io::Error {
+        if Self::is_busy(&e) {
+            io::Error::new(io::ErrorKind::Other, Self)
+        } else {
+            e
+        }
+    }
+}
+impl Display for InstanceLimitReached {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("all instances of the named pipe are in use")
+    }
+}
+impl Error for InstanceLimitReached {}
+
+/// Error payload returned when creating a [`PipeListener`] if a named pipe with the same name already exists, i.e. if
+/// the name is owned by another listener, possibly in another process.
+///
+/// Since listener creation returns [`io::Error`], this type is wrapped in one, with an error kind of
+/// [`AddrInUse`](io::ErrorKind::AddrInUse). Use [`NameOccupied::is_cause_of()`] to tell it apart from other errors,
+/// such as the ones caused by an invalid configuration.
+///
+/// If the previous owner of the name is expected to go away soon, such as when restarting a server, the
+/// [`name_takeover_timeout`](PipeListenerOptions::name_takeover_timeout) creation option can be used to wait for it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NameOccupied;
+impl NameOccupied {
+    /// Returns `true` if the given I/O error was produced because the name of the pipe is already in use.
+    pub fn is_cause_of(e: &io::Error) -> bool {
+        matches!(e.get_ref(), Some(inner) if inner.is::<Self>())
+    }
+    // Creating the first instance of a pipe whose name is taken fails with ERROR_ACCESS_DENIED, regardless of whether
+    // the security descriptor of the existing pipe would have allowed us to create another instance of it.
+    fn is_occupied(e: &io::Error) -> bool {
+        e.raw_os_error() == Some(ERROR_ACCESS_DENIED as _)
+    }
+    fn convert(e: io::Error) -> io::Error {
+        if Self::is_occupied(&e) {
+            io::Error::new(io::ErrorKind::AddrInUse, Self)
+        } else {
+            e
+        }
+    }
+}
+impl Display for NameOccupied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("the name of the named pipe is already owned by another listener")
+    }
+}
+impl Error for NameOccupied {}
+
 /// Allows for thorough customization of [`PipeListener`]s during creation.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[non_exhaustive]
@@ -149,9 +353,23 @@ pub struct PipeListenerOptions<'a> {
     /// [`WouldBlock`]: io::ErrorKind::WouldBlock
     pub nonblocking: bool,
     /// Specifies the maximum amount of instances of the pipe which can be created, i.e. how many clients can be
-    /// communicated with at once. If set to 1, trying to create multiple instances at the same time will return an
-    /// error. If set to `None`, no limit is applied. The value 255 is not allowed because of Windows limitations.
+    /// communicated with at once. Once the limit is reached, [`accept`] returns an error wrapping
+    /// [`InstanceLimitReached`] until some of the previously accepted streams are dropped. If set to `None`, no limit is
+    /// applied. The value 255 is not allowed because of Windows limitations.
+    ///
+    /// [`accept`]: PipeListener::accept
     pub instance_limit: Option<NonZeroU8>,
+    /// Specifies how many idle instances the listener keeps around for clients to connect to. By default, it is 1.
+    ///
+    /// With only one idle instance, there's a window between a client connecting to it and [`accept`] creating a
+    /// replacement, during which other clients fail to connect or have to wait for the pipe to become available. Keeping
+    /// several instances ready allows bursts of clients to connect without running into that window. Idle instances
+    /// count towards the [instance limit](#structfield.instance_limit).
+    ///
+    /// Only the synchronous [`PipeListener`] maintains a pool; the Tokio one ignores this option.
+    ///
+    /// [`accept`]: PipeListener::accept
+    pub pending_instances: NonZeroU8,
     /// Enables write-through mode, which applies only to network connections to the pipe. If enabled, writing to the
     /// pipe would always block until all data is delivered to the other end instead of piling up in the kernel's
     /// network buffer until a certain amount of data accamulates or a certain period of time passes, which is when the
@@ -160,8 +378,17 @@ pub struct PipeListenerOptions<'a> {
     /// Not required for pipes which are restricted to local connections only. If debug assertions are enabled, setting
     /// this parameter on a local-only pipe will cause a panic when the pipe is created; in release builds, creation
     /// will successfully complete without any errors and the flag will be completely ignored.
+    ///
+    /// Clients can enable write-through mode for their end of the connection with
+    /// [`ConnectOptions::write_through`](super::ConnectOptions::write_through). Regardless of this option, streams can
+    /// opt out of blocking on [`.flush()`](PipeStream::flush) with
+    /// [`.set_flush_enabled()`](PipeStream::set_flush_enabled).
     pub write_through: bool,
-    /// Enables remote machines to connect to the named pipe over the network.
+    /// Enables remote machines to connect to the named pipe over the network. Disabled by default, so that pipe servers
+    /// can't accidentally be reached from other computers.
+    ///
+    /// Maps to `PIPE_ACCEPT_REMOTE_CLIENTS` if enabled and to `PIPE_REJECT_REMOTE_CLIENTS` otherwise. Remote clients are
+    /// still subject to the [security descriptor](#structfield.security_descriptor) of the pipe.
     pub accept_remote: bool,
     /// Specifies how big the input buffer should be. The system will automatically adjust this size to align it as
     /// required or clip it by the minimum or maximum buffer size.
@@ -169,28 +396,27 @@ pub struct PipeListenerOptions<'a> {
     /// Specifies how big the output buffer should be. The system will automatically adjust this size to align it as
     /// required or clip it by the minimum or maximum buffer size.
     pub output_buffer_size_hint: DWORD,
-    /// The default timeout clients use when connecting. Used unless another timeout is specified when waiting by a
-    /// client.
-    // TODO use WaitTimeout struct
-    pub wait_timeout: NonZeroU32,
-}
-macro_rules! genset {
-    ($name:ident : $ty:ty) => {
-        #[doc = concat!(
-            "Sets the [`",
-            stringify!($name),
-            "`](#structfield.", stringify!($name),
-            ") parameter to the specified value."
-        )]
-        #[must_use = "builder setters take the entire structure and return the result"]
-        pub fn $name(mut self, $name: impl Into<$ty>) -> Self {
-            self.$name = $name.into();
-            self
-        }
-    };
-    ($($name:ident : $ty:ty),+ $(,)?) => {
-        $(genset!($name: $ty);)+
-    };
+    /// Specifies the default amount of time clients wait for an instance of the pipe to become available when all of
+    /// them are busy. Used by clients which don't specify a timeout of their own, which includes [`PipeStream::connect()`]
+    /// and [`ConnectOptions`](super::ConnectOptions) without a [`timeout`](super::ConnectOptions::timeout). By default,
+    /// it is 50 milliseconds.
+    ///
+    /// This is the `nDefaultTimeOut` parameter of `CreateNamedPipeW`, and so the value is rounded up to whole
+    /// milliseconds and clamped to the range between 1 millisecond and about 49 days.
+    pub wait_timeout: Duration,
+    /// Specifies the security descriptor to apply to all instances of the pipe, controlling which users can connect
+    /// to it. If set to `None`, the default security descriptor is used, which grants full control to the LocalSystem
+    /// account, administrators and the creator owner, and read access to members of the Everyone group and the
+    /// anonymous account.
+    ///
+    /// Use [`SecurityDescriptor::from_sddl()`] to restrict the pipe to a specific user or SID.
+    pub security_descriptor: Option<SecurityDescriptor>,
+    /// Specifies how long to keep retrying if the name of the pipe is already owned by another listener, which is the
+    /// case when a previous instance of the server is still shutting down. If set to `None`, which is the default, an
+    /// error wrapping [`NameOccupied`] is returned right away.
+    ///
+    /// If the name is still occupied once the timeout expires, the `NameOccupied` error is returned as usual.
+    pub name_takeover_timeout: Option<Duration>,
 }
 impl<'a> PipeListenerOptions<'a> {
     /// Creates a new builder with default options.
@@ -200,11 +426,14 @@ impl<'a> PipeListenerOptions<'a> {
             mode: PipeMode::Bytes,
             nonblocking: false,
             instance_limit: None,
+            pending_instances: NonZeroU8::MIN,
             write_through: false,
             accept_remote: false,
             input_buffer_size_hint: 512,
             output_buffer_size_hint: 512,
-            wait_timeout: NonZeroU32::new(50).unwrap(),
+            wait_timeout: Duration::from_millis(50),
+            security_descriptor: None,
+            name_takeover_timeout: None,
         }
     }
     /// Clones configuration options which are not owned by value and returns a copy of the original option table which
@@ -220,11 +449,14 @@ impl<'a> PipeListenerOptions<'a> {
             mode: self.mode,
             nonblocking: self.nonblocking,
             instance_limit: self.instance_limit,
+            pending_instances: self.pending_instances,
             write_through: self.write_through,
             accept_remote: self.accept_remote,
             input_buffer_size_hint: self.input_buffer_size_hint,
             output_buffer_size_hint: self.output_buffer_size_hint,
             wait_timeout: self.wait_timeout,
+            security_descriptor: self.security_descriptor.clone(),
+            name_takeover_timeout: self.name_takeover_timeout,
         }
     }
     genset!(
@@ -232,11 +464,14 @@ impl<'a> PipeListenerOptions<'a> {
         mode: PipeMode,
         nonblocking: bool,
         instance_limit: Option<NonZeroU8>,
+        pending_instances: NonZeroU8,
         write_through: bool,
         accept_remote: bool,
         input_buffer_size_hint: DWORD,
         output_buffer_size_hint: DWORD,
-        wait_timeout: NonZeroU32,
+        wait_timeout: Duration,
+        security_descriptor: Option<SecurityDescriptor>,
+        name_takeover_timeout: Option<Duration>,
     );
     /// Creates an instance of a pipe for a listener with the specified stream type and with the first-instance flag set
     /// to the specified value.
@@ -257,13 +492,20 @@ cannot create pipe server that has byte type but reads messages – have you for
             ));
         }
 
+        debug_assert!(
+            !self.write_through || self.accept_remote,
+            "write-through mode has no effect on pipes which only accept local connections"
+        );
+
         let path = path_conversion::convert_and_encode_path(&self.name, None);
         let open_mode = self.open_mode(first, role, overlapped);
         let pipe_mode = self.pipe_mode(read_mode, nonblocking);
 
         let mut sa = init_security_attributes();
         sa.bInheritHandle = 0;
-        // TODO security descriptor
+        if let Some(sd) = &self.security_descriptor {
+            sa.lpSecurityDescriptor = sd.as_ptr();
+        }
 
         let max_instances = match self.instance_limit.map(NonZeroU8::get) {
             Some(255) => {
@@ -284,7 +526,7 @@ cannot create pipe server that has byte type but reads messages – have you for
                 max_instances,
                 self.output_buffer_size_hint,
                 self.input_buffer_size_hint,
-                self.wait_timeout.get(),
+                WaitTimeout::from_duration(self.wait_timeout).into(),
                 &mut sa as *mut _,
             );
             (handle, handle != INVALID_HANDLE_VALUE)
@@ -294,21 +536,55 @@ cannot create pipe server that has byte type but reads messages – have you for
             OwnedHandle::from_raw_handle(handle)
         })
     }
+    /// Creates the first instance of a pipe for a listener, waiting for the name to become available if so configured.
+    pub(super) fn create_first_instance(
+        &self,
+        nonblocking: bool,
+        overlapped: bool,
+        role: PipeStreamRole,
+        read_mode: Option<PipeMode>,
+    ) -> io::Result<OwnedHandle> {
+        const MAX_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+        let deadline = self.name_takeover_timeout.map(|t| Instant::now() + t);
+        let mut interval = Duration::from_millis(1);
+        loop {
+            match self.create_instance(true, nonblocking, overlapped, role, read_mode) {
+                Err(e) if NameOccupied::is_occupied(&e) => {
+                    let remaining = deadline.map_or(Duration::ZERO, |d| d.saturating_duration_since(Instant::now()));
+                    if remaining.is_zero() {
+                        return Err(NameOccupied::convert(e));
+                    }
+                    thread::sleep(interval.min(remaining));
+                    interval = (interval * 2).min(MAX_POLL_INTERVAL);
+                }
+                els => return els,
+            }
+        }
+    }
     /// Creates the pipe listener from the builder. The `Rm` and `Sm` generic arguments specify the type of pipe stream
     /// that the listener will create, thus determining the direction of the pipe and its mode.
     ///
     /// # Errors
     /// In addition to regular OS errors, an error will be returned if the given `Rm` is [`pipe_mode::Messages`], but
     /// the `mode` field isn't also [`pipe_mode::Messages`].
+    ///
+    /// If the name of the pipe is already owned by another listener, an error of kind
+    /// [`AddrInUse`](io::ErrorKind::AddrInUse) wrapping [`NameOccupied`] is returned, unless the
+    /// [`name_takeover_timeout`](Self::name_takeover_timeout) option allows waiting for the name to be released.
     pub fn create<Rm: PipeModeTag, Sm: PipeModeTag>(&self) -> io::Result<PipeListener<Rm, Sm>> {
         let (owned_config, instance) = self._create(PipeListener::<Rm, Sm>::STREAM_ROLE, Rm::MODE)?;
-        let nonblocking = owned_config.nonblocking.into();
-        Ok(PipeListener {
+        let listener = PipeListener {
+            nonblocking: owned_config.nonblocking.into(),
             config: owned_config,
-            nonblocking,
-            stored_instance: Mutex::new(instance),
+            stored_instances: Mutex::new(VecDeque::from([instance])),
             _phantom: PhantomData,
-        })
+        };
+        listener.replenish(
+            &mut listener.stored_instances.lock().expect("unexpected lock poison"),
+            listener.config.nonblocking,
+        )?;
+        Ok(listener)
     }
     /// Alias for [`.create()`](Self::create) with the same `Rm` and `Sm`.
     #[inline]
@@ -333,7 +609,7 @@ cannot create pipe server that has byte type but reads messages – have you for
         let owned_config = self.to_owned();
 
         let instance = self
-            .create_instance(true, self.nonblocking, false, role, read_mode)
+            .create_first_instance(self.nonblocking, false, role, read_mode)
             .map(FileHandle)?;
         Ok((owned_config, instance))
     }
@@ -359,9 +635,11 @@ cannot create pipe server that has byte type but reads messages – have you for
         if nonblocking {
             pipe_mode |= PIPE_NOWAIT;
         }
-        if !self.accept_remote {
-            pipe_mode |= PIPE_REJECT_REMOTE_CLIENTS;
-        }
+        pipe_mode |= if self.accept_remote {
+            PIPE_ACCEPT_REMOTE_CLIENTS
+        } else {
+            PIPE_REJECT_REMOTE_CLIENTS
+        };
         pipe_mode
     }
 }
@@ -372,16 +650,42 @@ impl Default for PipeListenerOptions<'_> {
     }
 }
 
+fn connect_until(handle: BorrowedHandle<'_>, read_mode: Option<PipeMode>, deadline: Instant) -> io::Result<()> {
+    const MAX_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    unsafe { super::set_nonblocking_for_stream(handle, read_mode, true)? };
+    let mut interval = Duration::from_millis(1);
+    let rslt = loop {
+        match block_on_connect(handle) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    break Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "no client connected to the named pipe before the timeout expired",
+                    ));
+                }
+                thread::sleep(interval.min(remaining));
+                interval = (interval * 2).min(MAX_POLL_INTERVAL);
+            }
+            els => break els,
+        }
+    };
+    unsafe { super::set_nonblocking_for_stream(handle, read_mode, false)? };
+    rslt
+}
+
 fn block_on_connect(handle: BorrowedHandle<'_>) -> io::Result<()> {
     let success = unsafe { ConnectNamedPipe(handle.as_raw_handle(), ptr::null_mut()) != 0 };
     if success {
         Ok(())
     } else {
         let last_error = io::Error::last_os_error();
-        if last_error.raw_os_error() == Some(ERROR_PIPE_CONNECTED as i32) {
-            Ok(())
-        } else {
-            Err(last_error)
+        match last_error.raw_os_error() {
+            Some(c) if c == ERROR_PIPE_CONNECTED as i32 => Ok(()),
+            // Returned in nonblocking mode if no client is trying to connect.
+            Some(c) if c == ERROR_PIPE_LISTENING as i32 => Err(io::ErrorKind::WouldBlock.into()),
+            _ => Err(last_error),
         }
     }
 }
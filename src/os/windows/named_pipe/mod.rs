@@ -27,16 +27,39 @@
 // TODO add examples
 // TODO document limbo
 // TODO sync split
-// TODO client impersonation
 
+// Builder setter generator for option tables, has to go before the modules that use it
+macro_rules! genset {
+    ($name:ident : $ty:ty) => {
+        #[doc = concat!(
+            "Sets the [`",
+            stringify!($name),
+            "`](#structfield.", stringify!($name),
+            ") parameter to the specified value."
+        )]
+        #[must_use = "builder setters take the entire structure and return the result"]
+        pub fn $name(mut self, $name: impl Into<$ty>) -> Self {
+            self.$name = $name.into();
+            self
+        }
+    };
+    ($($name:ident : $ty:ty),+ $(,)?) => {
+        $(genset!($name: $ty);)+
+    };
+}
+
+mod call;
 mod enums;
+mod handle_state;
 mod listener;
+mod peer_identity;
+mod pipe_info;
 mod stream;
-pub use {enums::*, listener::*, stream::*};
+pub use {call::*, enums::*, handle_state::*, listener::*, peer_identity::*, pipe_info::*, stream::*};
 
 mod limbo_pool;
 mod maybe_arc;
-mod path_conversion;
+pub(super) mod path_conversion;
 
 #[cfg(feature = "tokio")]
 #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
@@ -46,7 +69,7 @@ use super::winprelude::*;
 use std::{io, ptr};
 use winapi::um::namedpipeapi::SetNamedPipeHandleState;
 
-unsafe fn set_nonblocking_for_stream(
+pub(super) unsafe fn set_nonblocking_for_stream(
     handle: BorrowedHandle<'_>,
     read_mode: Option<PipeMode>,
     nonblocking: bool,
@@ -0,0 +1,118 @@
+use crate::os::windows::winprelude::*;
+use std::{ffi::OsString, io, os::windows::prelude::*, path::PathBuf, ptr, slice};
+use winapi::{
+    shared::{sddl::ConvertSidToStringSidW, winerror::ERROR_INSUFFICIENT_BUFFER},
+    um::{
+        processthreadsapi::{OpenProcess, OpenProcessToken},
+        securitybaseapi::GetTokenInformation,
+        winbase::{LocalFree, QueryFullProcessImageNameW},
+        winnt::{TokenUser, PROCESS_QUERY_LIMITED_INFORMATION, TOKEN_QUERY, TOKEN_USER},
+    },
+};
+
+/// The identity of a process on the other end of a named pipe connection, as returned by
+/// [`PipeStream::client_identity()`](super::PipeStream::client_identity).
+///
+/// Servers can use this to reject connections from unexpected executables or users. Note that the process ID, and
+/// hence everything derived from it, is only meaningful as long as the process is alive and the connection is open:
+/// once the client exits, its process ID may be reused by an unrelated process.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct PeerIdentity {
+    /// The process identifier of the peer.
+    pub process_id: u32,
+    /// The full path to the executable file of the peer process, in Win32 format (e.g. `C:\Windows\notepad.exe`).
+    pub executable_path: PathBuf,
+    /// The security identifier of the user account the peer process runs as, in string format (e.g.
+    /// `S-1-5-21-…-1001`).
+    pub user_sid: OsString,
+}
+impl PeerIdentity {
+    /// Retrieves the identity of the process with the given process identifier.
+    ///
+    /// The calling process needs to be able to open the process with `PROCESS_QUERY_LIMITED_INFORMATION` access and
+    /// its primary token with `TOKEN_QUERY` access, which might not be the case for processes running as other users
+    /// or with a higher integrity level.
+    ///
+    /// # System calls
+    /// - `OpenProcess`
+    /// - `QueryFullProcessImageNameW`
+    /// - `OpenProcessToken`
+    /// - `GetTokenInformation`
+    /// - `ConvertSidToStringSidW`
+    pub fn query(process_id: u32) -> io::Result<Self> {
+        let process = open_process(process_id)?;
+        Ok(Self {
+            process_id,
+            executable_path: executable_path(process.as_handle())?,
+            user_sid: user_sid(process.as_handle())?,
+        })
+    }
+}
+
+fn open_process(pid: u32) -> io::Result<OwnedHandle> {
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    ok_or_ret_errno!(!handle.is_null() => unsafe {
+        // SAFETY: we just opened this handle
+        OwnedHandle::from_raw_handle(handle)
+    })
+}
+
+fn executable_path(process: BorrowedHandle<'_>) -> io::Result<PathBuf> {
+    // Start with MAX_PATH and grow up to the maximum length of an extended-length path.
+    let mut buf = Vec::<u16>::with_capacity(260);
+    loop {
+        let mut len = buf.capacity() as DWORD;
+        let success =
+            unsafe { QueryFullProcessImageNameW(process.as_raw_handle(), 0, buf.as_mut_ptr(), &mut len) != 0 };
+        if success {
+            // SAFETY: the system wrote `len` characters, not including the nul terminator
+            unsafe { buf.set_len(len as usize) };
+            return Ok(OsString::from_wide(&buf).into());
+        }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) || buf.capacity() >= 32768 {
+            return Err(e);
+        }
+        buf.reserve_exact(buf.capacity() * 2);
+    }
+}
+
+pub(crate) fn user_sid(process: BorrowedHandle<'_>) -> io::Result<OsString> {
+    let token = {
+        let mut token = ptr::null_mut();
+        let success = unsafe { OpenProcessToken(process.as_raw_handle(), TOKEN_QUERY, &mut token) != 0 };
+        ok_or_ret_errno!(success => unsafe {
+            // SAFETY: we just received ownership of the token
+            OwnedHandle::from_raw_handle(token)
+        })?
+    };
+
+    // The size of TOKEN_USER depends on the length of the SID that follows it, so the size has to be queried first.
+    let mut len: DWORD = 0;
+    unsafe { GetTokenInformation(token.as_raw_handle(), TokenUser, ptr::null_mut(), 0, &mut len) };
+    // Allocate in units of usize so that the buffer is sufficiently aligned for TOKEN_USER.
+    let mut buf = vec![0_usize; (len as usize + std::mem::size_of::<usize>() - 1) / std::mem::size_of::<usize>()];
+    let success = unsafe {
+        GetTokenInformation(
+            token.as_raw_handle(),
+            TokenUser,
+            buf.as_mut_ptr().cast(),
+            (buf.len() * std::mem::size_of::<usize>()) as DWORD,
+            &mut len,
+        ) != 0
+    };
+    ok_or_ret_errno!(success => ())?;
+    let sid = unsafe { (*buf.as_ptr().cast::<TOKEN_USER>()).User.Sid };
+
+    let mut string = ptr::null_mut();
+    let success = unsafe { ConvertSidToStringSidW(sid, &mut string) != 0 };
+    ok_or_ret_errno!(success => ())?;
+    let sid = unsafe {
+        let len = (0..).take_while(|&i| *string.add(i) != 0).count();
+        let sid = OsString::from_wide(slice::from_raw_parts(string, len));
+        LocalFree(string.cast());
+        sid
+    };
+    Ok(sid)
+}
@@ -0,0 +1,57 @@
+use super::PipeMode;
+use crate::os::windows::winprelude::*;
+use std::{io, num::NonZeroU8};
+use winapi::um::{
+    namedpipeapi::GetNamedPipeInfo,
+    winbase::{PIPE_SERVER_END, PIPE_TYPE_MESSAGE},
+};
+
+/// Information about a named pipe, as returned by [`PipeStream::pipe_info()`](super::PipeStream::pipe_info).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct PipeInfo {
+    /// Whether the handle is the server end of the pipe (`true`) or the client end (`false`).
+    pub is_server: bool,
+    /// The mode in which data is written into the pipe, i.e. whether it has message boundaries or not.
+    pub mode: PipeMode,
+    /// The size of the buffer for incoming data, in bytes. Zero if the buffer is allocated as needed.
+    pub input_buffer_size: u32,
+    /// The size of the buffer for outgoing data, in bytes. Zero if the buffer is allocated as needed.
+    pub output_buffer_size: u32,
+    /// The maximum amount of instances of the pipe which can be created, or `None` if it is unlimited. Has the same
+    /// meaning as the [`instance_limit`](super::PipeListenerOptions::instance_limit) creation option.
+    pub instance_limit: Option<NonZeroU8>,
+}
+impl PipeInfo {
+    /// Queries information about the named pipe that the given handle belongs to.
+    ///
+    /// # System calls
+    /// - `GetNamedPipeInfo`
+    pub fn query(handle: BorrowedHandle<'_>) -> io::Result<Self> {
+        let (mut flags, mut output_buffer_size, mut input_buffer_size, mut max_instances) = (0, 0, 0, 0);
+        let success = unsafe {
+            GetNamedPipeInfo(
+                handle.as_raw_handle(),
+                &mut flags,
+                &mut output_buffer_size,
+                &mut input_buffer_size,
+                &mut max_instances,
+            ) != 0
+        };
+        ok_or_ret_errno!(success => Self {
+            is_server: flags & PIPE_SERVER_END != 0,
+            mode: if flags & PIPE_TYPE_MESSAGE != 0 {
+                PipeMode::Messages
+            } else {
+                PipeMode::Bytes
+            },
+            input_buffer_size,
+            output_buffer_size,
+            // PIPE_UNLIMITED_INSTANCES is 255, and the limit can't be higher than that.
+            instance_limit: u8::try_from(max_instances)
+                .ok()
+                .filter(|&n| n != 255)
+                .and_then(NonZeroU8::new),
+        })
+    }
+}
@@ -0,0 +1,144 @@
+use super::{
+    super::{set_read_mode, ImpersonationLevel, PipeMode},
+    pipe_mode, PipeModeTag, PipeStream, RawPipeStream,
+};
+use crate::os::windows::winprelude::*;
+use std::{borrow::Cow, ffi::OsStr, io, time::Duration};
+
+/// Allows for customization of how [`PipeStream`]s connect to servers.
+///
+/// The default options are what [`PipeStream::connect()`] uses.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::os::windows::named_pipe::*;
+/// use std::{ffi::OsStr, time::Duration};
+///
+/// let conn = ConnectOptions::new()
+///     .name(OsStr::new("Example"))
+///     .max_busy_retries(100_u32)
+///     .timeout(Duration::from_secs(5))
+///     .connect_duplex::<pipe_mode::Bytes>()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ConnectOptions<'a> {
+    /// Specifies the name of the named pipe to connect to. The `\\<hostname>\pipe\` prefix is added automatically.
+    pub name: Cow<'a, OsStr>,
+    /// Specifies the computer on which the named pipe is located. If set to `None`, the local computer is used.
+    pub hostname: Option<Cow<'a, OsStr>>,
+    /// Specifies whether the connection attempt should wait for an instance of the pipe to become available and retry
+    /// when all instances are busy serving other clients. Enabled by default.
+    ///
+    /// If disabled, the `ERROR_PIPE_BUSY` error is returned immediately in that situation.
+    pub retry_if_busy: bool,
+    /// Specifies how many times the connection attempt will be retried if all instances of the pipe keep being busy.
+    /// Once exhausted, the `ERROR_PIPE_BUSY` error is returned.
+    ///
+    /// Retrying more than once is only necessary if other clients manage to claim the instance that has just become
+    /// available before this one does, which tends to happen with heavily loaded servers.
+    pub max_busy_retries: u32,
+    /// Specifies the maximum amount of time to spend waiting for an instance of the pipe to become available. If set to
+    /// `None`, each wait lasts for the default timeout specified by the server. If the deadline passes, an error of kind
+    /// [`TimedOut`](io::ErrorKind::TimedOut) is returned.
+    pub timeout: Option<Duration>,
+    /// Enables write-through mode, which only has an effect when connecting to a pipe on a remote computer. If
+    /// enabled, writes to the pipe block until the data is transmitted over the network instead of being buffered by
+    /// the system. Maps to `FILE_FLAG_WRITE_THROUGH`.
+    pub write_through: bool,
+    /// Specifies how far the server is allowed to go when impersonating the client. If set to `None`, no security
+    /// quality-of-service information is supplied and the server may fully impersonate the client on the local
+    /// computer. Maps to `SECURITY_SQOS_PRESENT` combined with one of the `SECURITY_*` impersonation level flags.
+    pub impersonation_level: Option<ImpersonationLevel>,
+    /// Specifies whether the server is only able to use the privileges and groups that are enabled in the client's
+    /// security context at the time of connection, preventing it from enabling the rest. Maps to
+    /// `SECURITY_EFFECTIVE_ONLY`.
+    ///
+    /// Only has an effect if [`impersonation_level`](#structfield.impersonation_level) is set.
+    pub effective_only: bool,
+    /// Specifies whether the server sees changes made to the client's security context after the connection is
+    /// established (dynamic tracking), instead of a snapshot taken at connection time (static tracking). Maps to
+    /// `SECURITY_CONTEXT_TRACKING`.
+    ///
+    /// Only has an effect if [`impersonation_level`](#structfield.impersonation_level) is set.
+    pub context_tracking: bool,
+    /// Additional flags to pass to `CreateFileW` in its `dwFlagsAndAttributes` parameter, on top of the ones implied
+    /// by the other options. Empty by default.
+    ///
+    /// This allows for flags such as `FILE_FLAG_NO_BUFFERING` and `FILE_FLAG_OPEN_NO_RECALL` which do not have a
+    /// dedicated option. `FILE_FLAG_OVERLAPPED` can also be specified to open the handle for use with an external
+    /// reactor, but such a stream must not be read from or written to through its own methods, which perform
+    /// non-overlapped I/O – extract the handle by converting the stream into an [`OwnedHandle`] instead.
+    pub custom_flags: u32,
+}
+impl<'a> ConnectOptions<'a> {
+    /// The default value of the [`max_busy_retries`](#structfield.max_busy_retries) field.
+    pub const DEFAULT_MAX_BUSY_RETRIES: u32 = 8;
+
+    /// Creates a new builder with default options.
+    pub fn new() -> Self {
+        Self {
+            name: Cow::Borrowed(OsStr::new("")),
+            hostname: None,
+            retry_if_busy: true,
+            max_busy_retries: Self::DEFAULT_MAX_BUSY_RETRIES,
+            timeout: None,
+            write_through: false,
+            impersonation_level: None,
+            effective_only: false,
+            context_tracking: false,
+            custom_flags: 0,
+        }
+    }
+    genset!(
+        name: Cow<'a, OsStr>,
+        hostname: Option<Cow<'a, OsStr>>,
+        retry_if_busy: bool,
+        max_busy_retries: u32,
+        timeout: Option<Duration>,
+        write_through: bool,
+        impersonation_level: Option<ImpersonationLevel>,
+        effective_only: bool,
+        context_tracking: bool,
+        custom_flags: u32,
+    );
+    /// Connects to the named pipe with the specified options. The `Rm` and `Sm` generic arguments specify the type of
+    /// pipe stream that will be created, thus determining the direction of the pipe and its mode.
+    ///
+    /// # System calls
+    /// - `CreateFileW`
+    /// - `WaitNamedPipeW`
+    /// - `GetNamedPipeHandleStateW` (if receiving messages on a duplex stream)
+    /// - `SetNamedPipeHandleState` (if receiving messages on a duplex stream)
+    pub fn connect<Rm: PipeModeTag, Sm: PipeModeTag>(&self) -> io::Result<PipeStream<Rm, Sm>> {
+        let raw = RawPipeStream::connect(self, Rm::MODE.is_some(), Sm::MODE.is_some())?;
+        // Client handles start out in byte read mode. Switching requires write access, which receive-only streams
+        // lack, but they do fine without message read mode since message lengths are peeked before reading anyway.
+        if Rm::MODE == Some(PipeMode::Messages) && Sm::MODE.is_some() {
+            set_read_mode(raw.as_handle(), PipeMode::Messages)?;
+        }
+        Ok(PipeStream::new(raw))
+    }
+    /// Alias for [`.connect()`](Self::connect) with the same `Rm` and `Sm`.
+    #[inline]
+    pub fn connect_duplex<M: PipeModeTag>(&self) -> io::Result<PipeStream<M, M>> {
+        self.connect::<M, M>()
+    }
+    /// Alias for [`.connect()`](Self::connect) with an `Sm` of [`pipe_mode::None`].
+    #[inline]
+    pub fn connect_recv_only<Rm: PipeModeTag>(&self) -> io::Result<PipeStream<Rm, pipe_mode::None>> {
+        self.connect::<Rm, pipe_mode::None>()
+    }
+    /// Alias for [`.connect()`](Self::connect) with an `Rm` of [`pipe_mode::None`].
+    #[inline]
+    pub fn connect_send_only<Sm: PipeModeTag>(&self) -> io::Result<PipeStream<pipe_mode::None, Sm>> {
+        self.connect::<pipe_mode::None, Sm>()
+    }
+}
+impl Default for ConnectOptions<'_> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
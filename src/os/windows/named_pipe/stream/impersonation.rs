@@ -0,0 +1,110 @@
+//! Client impersonation for server-side `PipeStream`s.
+
+use super::{PipeModeTag, PipeStream};
+use std::{io, marker::PhantomData, os::windows::prelude::*, process, ptr};
+use winapi::um::{
+    namedpipeapi::ImpersonateNamedPipeClient,
+    processthreadsapi::{GetCurrentThread, OpenThreadToken},
+    securitybaseapi::RevertToSelf,
+    winnt::{TOKEN_DUPLICATE, TOKEN_IMPERSONATE, TOKEN_QUERY},
+};
+
+impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeStream<Rm, Sm> {
+    /// Makes the calling thread impersonate the client of the named pipe connection, returning a guard which reverts
+    /// the thread to its own security context when dropped.
+    ///
+    /// This allows a privileged server to perform access checks and open securable objects as the client would. The
+    /// client must have allowed impersonation when connecting, which is the case by default. The server must also
+    /// have read data from the pipe before it can impersonate the client.
+    ///
+    /// Impersonation is a property of the calling thread, which is why the guard can't be sent to other threads.
+    ///
+    /// # Errors
+    /// In addition to regular OS errors, an error is returned if the stream is client-side.
+    ///
+    /// # System calls
+    /// - `ImpersonateNamedPipeClient`
+    /// - `RevertToSelf` (when the guard is dropped)
+    pub fn impersonate_client(&self) -> io::Result<ImpersonationGuard<'_>> {
+        if !self.is_server() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot impersonate the client of a client-side pipe stream",
+            ));
+        }
+        let success = unsafe { ImpersonateNamedPipeClient(self.as_raw_handle()) != 0 };
+        ok_or_ret_errno!(success => ImpersonationGuard {
+            _phantom: PhantomData,
+        })
+    }
+    /// Retrieves an impersonation token of the client of the named pipe connection, which can then be passed to
+    /// functions like `AccessCheck` or `CreateProcessAsUserW`.
+    ///
+    /// The token is opened with `TOKEN_QUERY`, `TOKEN_IMPERSONATE` and `TOKEN_DUPLICATE` access rights. The calling
+    /// thread impersonates the client for the duration of the call.
+    ///
+    /// # System calls
+    /// - `ImpersonateNamedPipeClient`
+    /// - `OpenThreadToken`
+    /// - `RevertToSelf`
+    pub fn get_client_token(&self) -> io::Result<OwnedHandle> {
+        let guard = self.impersonate_client()?;
+        let token = guard.token();
+        guard.revert()?;
+        token
+    }
+}
+
+/// Guard which keeps the calling thread impersonating the client of a named pipe connection, created by
+/// [`PipeStream::impersonate_client()`].
+///
+/// When dropped, the thread reverts to its own security context. If that fails, the process is aborted, since
+/// continuing to run with the security context of the client is a security hazard. Use [`.revert()`](Self::revert) to
+/// handle the error instead.
+#[derive(Debug)]
+#[must_use = "the client is only impersonated until the guard is dropped"]
+pub struct ImpersonationGuard<'a> {
+    // Impersonation is per-thread, so the guard must stay on the thread which created it.
+    _phantom: PhantomData<(&'a (), *const ())>,
+}
+impl ImpersonationGuard<'_> {
+    /// Opens the impersonation token of the calling thread, which is that of the client.
+    ///
+    /// The token is opened with `TOKEN_QUERY`, `TOKEN_IMPERSONATE` and `TOKEN_DUPLICATE` access rights.
+    ///
+    /// # System calls
+    /// - `OpenThreadToken`
+    pub fn token(&self) -> io::Result<OwnedHandle> {
+        let mut token = ptr::null_mut();
+        let success = unsafe {
+            // Open as self, since the client may not have the right to open its own token with the requested access.
+            OpenThreadToken(
+                GetCurrentThread(),
+                TOKEN_QUERY | TOKEN_IMPERSONATE | TOKEN_DUPLICATE,
+                1,
+                &mut token,
+            ) != 0
+        };
+        ok_or_ret_errno!(success => unsafe {
+            // SAFETY: we just received ownership of the token
+            OwnedHandle::from_raw_handle(token)
+        })
+    }
+    /// Reverts the calling thread to its own security context, reporting errors instead of aborting the process.
+    ///
+    /// # System calls
+    /// - `RevertToSelf`
+    pub fn revert(self) -> io::Result<()> {
+        let success = unsafe { RevertToSelf() != 0 };
+        // Even if the call failed, there's no point in trying again in the destructor.
+        std::mem::forget(self);
+        ok_or_ret_errno!(success => ())
+    }
+}
+impl Drop for ImpersonationGuard<'_> {
+    fn drop(&mut self) {
+        if unsafe { RevertToSelf() } == 0 {
+            process::abort();
+        }
+    }
+}
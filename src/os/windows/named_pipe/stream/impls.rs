@@ -5,28 +5,35 @@ use super::{
     *,
 };
 use crate::{
+    datagram::{self, Datagram},
     os::windows::{
-        named_pipe::{path_conversion, set_nonblocking_for_stream, PipeMode},
+        named_pipe::{path_conversion, set_nonblocking_for_stream, PeerIdentity, PipeHandleState, PipeInfo, PipeMode},
         FileHandle,
     },
     reliable_recv_msg::{RecvResult, ReliableRecvMsg, TryRecvResult},
     weaken_buf_init_mut,
 };
 use std::{
-    ffi::OsStr,
+    borrow::Cow,
+    convert::Infallible,
+    ffi::{OsStr, OsString},
     fmt::{self, Debug, DebugStruct, Formatter},
     io::{self, prelude::*},
     marker::PhantomData,
-    mem::MaybeUninit,
+    mem::{ManuallyDrop, MaybeUninit},
     os::windows::prelude::*,
-    slice,
+    ptr, slice,
     sync::atomic::Ordering,
+    time::Duration,
 };
 use winapi::{
-    shared::winerror::ERROR_MORE_DATA,
-    um::winbase::{
-        GetNamedPipeClientProcessId, GetNamedPipeClientSessionId, GetNamedPipeServerProcessId,
-        GetNamedPipeServerSessionId,
+    shared::{minwindef::DWORD, winerror::ERROR_MORE_DATA},
+    um::{
+        namedpipeapi::{DisconnectNamedPipe, TransactNamedPipe},
+        winbase::{
+            GetNamedPipeClientProcessId, GetNamedPipeClientSessionId, GetNamedPipeServerProcessId,
+            GetNamedPipeServerSessionId,
+        },
     },
 };
 
@@ -46,6 +53,7 @@ impl RawPipeStream {
             handle: Some(handle),
             is_server,
             needs_flush: AtomicBool::new(false),
+            noop_flush: AtomicBool::new(false),
         }
     }
     pub(crate) fn new_server(handle: FileHandle) -> Self {
@@ -66,9 +74,9 @@ impl RawPipeStream {
         }
     }
 
-    fn connect(pipename: &OsStr, hostname: Option<&OsStr>, read: bool, write: bool) -> io::Result<Self> {
-        let path = path_conversion::convert_and_encode_path(pipename, hostname);
-        let handle = _connect(&path, read, write, WaitTimeout::DEFAULT)?;
+    pub(super) fn connect(opts: &ConnectOptions<'_>, read: bool, write: bool) -> io::Result<Self> {
+        let path = path_conversion::convert_and_encode_path(&opts.name, opts.hostname.as_deref());
+        let handle = _connect(&path, read, write, opts)?;
         Ok(Self::new_client(handle))
     }
 
@@ -79,7 +87,11 @@ impl RawPipeStream {
         self.file_handle().read(buf)
     }
     fn write(&self, buf: &[u8]) -> io::Result<usize> {
-        let r = self.file_handle().write(buf);
+        let r = match self.file_handle().write(buf) {
+            // In nonblocking mode, writing into a full pipe succeeds without writing anything.
+            Ok(0) if !buf.is_empty() => Err(io::ErrorKind::WouldBlock.into()),
+            els => els,
+        };
         if r.is_ok() {
             self.needs_flush.store(true, Ordering::Release);
         }
@@ -87,6 +99,9 @@ impl RawPipeStream {
     }
 
     fn flush(&self) -> io::Result<()> {
+        if self.noop_flush.load(Ordering::Acquire) {
+            return Ok(());
+        }
         if self
             .needs_flush
             .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
@@ -145,10 +160,64 @@ impl RawPipeStream {
         }
     }
 
+    fn transact(&self, request: &[u8], buf: &mut [MaybeUninit<u8>]) -> io::Result<RecvResult> {
+        let request_len = DWORD::try_from(request.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "request is too big"))?;
+        let buf_len = DWORD::try_from(buf.len()).unwrap_or(DWORD::MAX);
+        let mut received: DWORD = 0;
+        let success = unsafe {
+            TransactNamedPipe(
+                self.as_handle().as_raw_handle(),
+                request.as_ptr() as *mut _,
+                request_len,
+                buf.as_mut_ptr().cast(),
+                buf_len,
+                &mut received,
+                ptr::null_mut(),
+            ) != 0
+        };
+        let received = received as usize;
+        if success {
+            return Ok(RecvResult::Fit(received));
+        }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() != Some(ERROR_MORE_DATA as _) {
+            return Err(e);
+        }
+
+        // The response didn't fit, so collect what we've got and read the rest of the message.
+        let rest = peek_msg_len(self.as_handle())?;
+        let mut msg = Vec::with_capacity(received + rest);
+        msg.extend_from_slice(unsafe {
+            // SAFETY: Win32 guarantees that this much is initialized.
+            slice::from_raw_parts(buf.as_ptr().cast::<u8>(), received)
+        });
+        let tail_len = self.file_handle().read(&mut vec_as_uninit(&mut msg)[received..])?;
+        unsafe {
+            // SAFETY: Win32 guarantees that this much is initialized.
+            msg.set_len(received + tail_len)
+        };
+        Ok(RecvResult::Alloc(msg))
+    }
+
     fn set_nonblocking(&self, readmode: Option<PipeMode>, nonblocking: bool) -> io::Result<()> {
         unsafe { set_nonblocking_for_stream(self.as_handle(), readmode, nonblocking) }
     }
 
+    fn disconnect(&self) -> io::Result<()> {
+        if !self.is_server {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot disconnect a client-side pipe stream from the server side",
+            ));
+        }
+        let success = unsafe { DisconnectNamedPipe(self.as_raw_handle()) != 0 };
+        ok_or_ret_errno!(success => ())?;
+        // Whatever hasn't been received by now has been discarded, so there's nothing left for limbo to do.
+        self.assume_flushed();
+        Ok(())
+    }
+
     fn fill_fields<'a, 'b, 'c>(
         &self,
         dbst: &'a mut DebugStruct<'b, 'c>,
@@ -182,7 +251,9 @@ derive_asraw!(RawPipeStream);
 impl From<RawPipeStream> for OwnedHandle {
     #[inline]
     fn from(x: RawPipeStream) -> Self {
-        x.into()
+        // Bypass the destructor, which would otherwise try to bury the handle we're taking.
+        let mut x = ManuallyDrop::new(x);
+        x.handle.take().expect(LIMBO_ERR).0
     }
 }
 impl TryFrom<OwnedHandle> for RawPipeStream {
@@ -223,6 +294,32 @@ impl<Rm: PipeModeTag> PipeStream<Rm, pipe_mode::Messages> {
         self.raw.write(buf)
     }
 }
+impl PipeStream<pipe_mode::Messages, pipe_mode::Messages> {
+    /// Sends a request message and receives the response message in a single system call, which makes for an
+    /// efficient implementation of simple RPC clients.
+    ///
+    /// The response is received into the given buffer if it fits; otherwise, the part of it that didn't fit is
+    /// received separately and the whole message is returned in a new buffer, just like with
+    /// [`.recv()`](ReliableRecvMsg::recv).
+    ///
+    /// # Errors
+    /// In addition to regular OS errors, the system fails the call if there is unread data in the pipe or if the stream
+    /// is in nonblocking mode.
+    ///
+    /// # System calls
+    /// - `TransactNamedPipe`
+    /// - `PeekNamedPipe` (if the response doesn't fit)
+    /// - `ReadFile` (if the response doesn't fit)
+    #[inline]
+    pub fn transact(&self, request: &[u8], response: &mut [u8]) -> io::Result<RecvResult> {
+        self.raw.transact(request, weaken_buf_init_mut(response))
+    }
+    /// Same as [`.transact()`](Self::transact), but accepts an uninitialized buffer.
+    #[inline]
+    pub fn transact_to_uninit(&self, request: &[u8], response: &mut [MaybeUninit<u8>]) -> io::Result<RecvResult> {
+        self.raw.transact(request, response)
+    }
+}
 impl<Sm: PipeModeTag> PipeStream<pipe_mode::Bytes, Sm> {
     /// Same as `.read()` from the [`Read`] trait, but accepts an uninitialized buffer.
     #[inline]
@@ -233,20 +330,37 @@ impl<Sm: PipeModeTag> PipeStream<pipe_mode::Bytes, Sm> {
 impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeStream<Rm, Sm> {
     /// Connects to the specified named pipe (the `\\.\pipe\` prefix is added automatically), blocking until a server
     /// instance is dispatched.
+    ///
+    /// If all instances of the pipe are busy, the connection attempt is retried a bounded number of times as they free
+    /// up. Use [`ConnectOptions`] to customize this behavior.
     pub fn connect(pipename: impl AsRef<OsStr>) -> io::Result<Self> {
-        let raw = RawPipeStream::connect(pipename.as_ref(), None, Rm::MODE.is_some(), Sm::MODE.is_some())?;
-        Ok(Self::new(raw))
+        ConnectOptions::new().name(pipename.as_ref()).connect()
+    }
+    /// Connects to the specified named pipe (the `\\.\pipe\` prefix is added automatically), waiting for a server
+    /// instance to become available for at most the specified amount of time.
+    ///
+    /// If all instances of the pipe are busy serving other clients, the connection attempt is retried whenever one of
+    /// them frees up, until `timeout` expires, at which point an error of kind
+    /// [`TimedOut`](io::ErrorKind::TimedOut) is returned. If the pipe does not exist at all, the error is returned
+    /// immediately, just like with [`.connect()`](Self::connect).
+    ///
+    /// # System calls
+    /// - `CreateFileW`
+    /// - `WaitNamedPipeW`
+    pub fn connect_with_timeout(pipename: impl AsRef<OsStr>, timeout: Duration) -> io::Result<Self> {
+        ConnectOptions::new()
+            .name(pipename.as_ref())
+            .max_busy_retries(u32::MAX)
+            .timeout(timeout)
+            .connect()
     }
     /// Connects to the specified named pipe at a remote computer (the `\\<hostname>\pipe\` prefix is added
     /// automatically), blocking until a server instance is dispatched.
     pub fn connect_to_remote(pipename: impl AsRef<OsStr>, hostname: impl AsRef<OsStr>) -> io::Result<Self> {
-        let raw = RawPipeStream::connect(
-            pipename.as_ref(),
-            Some(hostname.as_ref()),
-            Rm::MODE.is_some(),
-            Sm::MODE.is_some(),
-        )?;
-        Ok(Self::new(raw))
+        ConnectOptions::new()
+            .name(pipename.as_ref())
+            .hostname(Cow::Borrowed(hostname.as_ref()))
+            .connect()
     }
     /// Splits the pipe stream by value, returning a receive half and a send half. The stream is closed when both are
     /// dropped, kind of like an `Arc` (which is how it's implemented under the hood).
@@ -290,10 +404,35 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeStream<Rm, Sm> {
         unsafe { hget(self.as_handle(), GetNamedPipeClientProcessId) }
     }
     /// Retrieves the session identifier of the client side of the named pipe connection.
+    ///
+    /// On systems with multiple interactive sessions, such as Terminal Server or Remote Desktop hosts, this identifies
+    /// the session the client process is running in, which services can use to tell apart clients from different
+    /// logged-on users.
     #[inline]
     pub fn client_session_id(&self) -> io::Result<u32> {
         unsafe { hget(self.as_handle(), GetNamedPipeClientSessionId) }
     }
+    /// Retrieves the name of the computer on which the client side of the named pipe connection is located, or `None`
+    /// if the client is local.
+    ///
+    /// # System calls
+    /// - `GetNamedPipeClientComputerNameW`
+    #[inline]
+    pub fn client_computer_name(&self) -> io::Result<Option<OsString>> {
+        client_computer_name(self.as_handle())
+    }
+    /// Retrieves the identity of the client process – its executable path and the user account it runs as – so that
+    /// servers can reject connections from unexpected binaries or users.
+    ///
+    /// Only local clients can be identified this way, since the process identifier of a remote client is meaningless
+    /// on the local machine. See [`PeerIdentity::query()`] for the access rights required.
+    ///
+    /// # System calls
+    /// - `GetNamedPipeClientProcessId`
+    /// - All of the system calls performed by [`PeerIdentity::query()`]
+    pub fn client_identity(&self) -> io::Result<PeerIdentity> {
+        PeerIdentity::query(self.client_process_id()?)
+    }
     /// Retrieves the process identifier of the server side of the named pipe connection.
     #[inline]
     pub fn server_process_id(&self) -> io::Result<u32> {
@@ -304,6 +443,18 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeStream<Rm, Sm> {
     pub fn server_session_id(&self) -> io::Result<u32> {
         unsafe { hget(self.as_handle(), GetNamedPipeServerSessionId) }
     }
+    /// Retrieves information about the named pipe the stream belongs to, such as the sizes of its buffers and whether
+    /// it has message boundaries.
+    #[inline]
+    pub fn pipe_info(&self) -> io::Result<PipeInfo> {
+        PipeInfo::query(self.as_handle())
+    }
+    /// Retrieves the current state of the stream's handle, such as its read mode and the amount of instances of the
+    /// pipe that currently exist.
+    #[inline]
+    pub fn handle_state(&self) -> io::Result<PipeHandleState> {
+        PipeHandleState::query(self.as_handle())
+    }
     /// Returns `true` if the stream was created by a listener (server-side), `false` if it was created by connecting to
     /// a server (server-side).
     #[inline]
@@ -316,6 +467,21 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeStream<Rm, Sm> {
     pub fn is_client(&self) -> bool {
         !self.raw.is_server
     }
+    /// Forcibly closes the client's end of the connection, discarding any data which the client hasn't received yet.
+    /// Only available on server-side streams; an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is
+    /// returned if called on a client-side stream.
+    ///
+    /// Use [`.flush()`](Self::flush) beforehand if the client has to receive everything that has been sent. Any
+    /// subsequent operations on the stream behave as if the client has closed the connection. A disconnected stream can
+    /// be handed back to the listener it came from with [`PipeListener::reuse()`](super::super::PipeListener::reuse)
+    /// to serve another client, avoiding the creation of a new instance of the pipe.
+    ///
+    /// # System calls
+    /// - `DisconnectNamedPipe`
+    #[inline]
+    pub fn disconnect(&self) -> io::Result<()> {
+        self.raw.disconnect()
+    }
     /// Sets whether the nonblocking mode for the pipe stream is enabled. By default, it is disabled.
     ///
     /// In nonblocking mode, attempts to read from the pipe when there is no data available or to write when the buffer
@@ -348,11 +514,29 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag + PmtNotNone> PipeStream<Rm, Sm> {
     /// Flushes the stream, blocking until the send buffer is empty (has been received by the other end in its
     /// entirety).
     ///
-    /// Only available on streams that have a send mode.
+    /// Only available on streams that have a send mode. Does nothing if flushing has been disabled with
+    /// [`.set_flush_enabled()`](Self::set_flush_enabled).
     #[inline]
     pub fn flush(&self) -> io::Result<()> {
         self.raw.flush()
     }
+    /// Sets whether [`.flush()`](Self::flush) actually waits for the other end to receive everything that's been sent
+    /// or immediately returns without doing anything. Enabled by default.
+    ///
+    /// Latency-sensitive applications, which can't afford to block until the client catches up, can disable flushing.
+    /// Data which hasn't been flushed when the stream is dropped is still delivered by limbo, unless
+    /// [`.assume_flushed()`](Self::assume_flushed) or [`.evade_limbo()`](Self::evade_limbo) is used.
+    ///
+    /// This setting is shared between the halves of a split stream.
+    #[inline]
+    pub fn set_flush_enabled(&self, enabled: bool) {
+        self.raw.noop_flush.store(!enabled, Ordering::Release);
+    }
+    /// Returns whether [`.flush()`](Self::flush) is enabled. See [`.set_flush_enabled()`](Self::set_flush_enabled).
+    #[inline]
+    pub fn is_flush_enabled(&self) -> bool {
+        !self.raw.noop_flush.load(Ordering::Acquire)
+    }
     /// Assumes that the other side has consumed everything that's been written so far. This will turn the next flush
     /// into a no-op, but will cause the send buffer to be cleared when the stream is closed, since it won't be sent to
     /// limbo.
@@ -371,12 +555,22 @@ impl<Sm: PipeModeTag> Read for &PipeStream<pipe_mode::Bytes, Sm> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.raw.read(buf)
     }
+    #[cfg(feature = "read_buf")]
+    #[inline]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        crate::read_into_cursor(cursor, |buf| self.raw.read_to_uninit(buf))
+    }
 }
 impl<Sm: PipeModeTag> Read for PipeStream<pipe_mode::Bytes, Sm> {
     #[inline(always)]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         (self as &PipeStream<_, _>).read(buf)
     }
+    #[cfg(feature = "read_buf")]
+    #[inline(always)]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        (self as &PipeStream<_, _>).read_buf(cursor)
+    }
 }
 impl<Rm: PipeModeTag> Write for &PipeStream<Rm, pipe_mode::Bytes> {
     #[inline]
@@ -414,6 +608,18 @@ impl<Sm: PipeModeTag> ReliableRecvMsg for PipeStream<pipe_mode::Messages, Sm> {
         (self as &PipeStream<_, _>).try_recv(buf)
     }
 }
+impl Datagram for PipeStream<pipe_mode::Messages, pipe_mode::Messages> {
+    type Address = Infallible;
+    fn send(&mut self, msg: &[u8]) -> io::Result<()> {
+        datagram::check_sent(PipeStream::send(self, msg)?, msg.len())
+    }
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(match ReliableRecvMsg::recv(&mut &*self, buf)? {
+            RecvResult::Fit(size) => size,
+            RecvResult::Alloc(msg) => datagram::truncate_into(&msg, buf),
+        })
+    }
+}
 impl<Rm: PipeModeTag, Sm: PipeModeTag> Debug for PipeStream<Rm, Sm> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut dbst = f.debug_struct("PipeStream");
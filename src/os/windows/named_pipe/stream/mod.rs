@@ -1,9 +1,12 @@
+mod connect_options;
 mod enums;
-pub use enums::*;
+pub use {connect_options::*, enums::*};
 
+mod impersonation;
 mod impls;
 mod limbo;
 mod wrapper_fns;
+pub use impersonation::ImpersonationGuard;
 pub(super) use impls::{LIMBO_ERR, REBURY_ERR};
 pub(crate) use wrapper_fns::*;
 
@@ -125,6 +128,7 @@ pub(crate) struct RawPipeStream {
     handle: Option<FileHandle>,
     is_server: bool,
     needs_flush: AtomicBool,
+    noop_flush: AtomicBool,
 }
 
 /// Additional contextual information for conversions from a raw handle to a named pipe stream.
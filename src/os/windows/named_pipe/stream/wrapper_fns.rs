@@ -1,11 +1,22 @@
-use crate::os::windows::{winprelude::*, FileHandle};
-use std::{io, os::windows::prelude::*, ptr};
+use super::{
+    super::{PipeInfo, PipeMode},
+    ConnectOptions,
+};
+use crate::os::windows::{c_wrappers, winprelude::*, FileHandle};
+use std::{
+    ffi::OsString,
+    io, mem,
+    os::windows::prelude::*,
+    ptr,
+    time::{Duration, Instant},
+};
 use winapi::{
-    shared::winerror::ERROR_PIPE_BUSY,
+    shared::winerror::{ERROR_PIPE_BUSY, ERROR_PIPE_LOCAL, ERROR_SEM_TIMEOUT},
     um::{
         fileapi::{CreateFileW, OPEN_EXISTING},
         handleapi::INVALID_HANDLE_VALUE,
-        namedpipeapi::{GetNamedPipeInfo, PeekNamedPipe, WaitNamedPipeW},
+        namedpipeapi::{GetNamedPipeClientComputerNameW, PeekNamedPipe, WaitNamedPipeW},
+        winbase::{FILE_FLAG_WRITE_THROUGH, SECURITY_CONTEXT_TRACKING, SECURITY_EFFECTIVE_ONLY, SECURITY_SQOS_PRESENT},
         winnt::{FILE_SHARE_READ, FILE_SHARE_WRITE, GENERIC_READ, GENERIC_WRITE},
     },
 };
@@ -20,32 +31,27 @@ pub(crate) unsafe fn hget(
     ok_or_ret_errno!(ok => x)
 }
 
-pub(crate) fn get_flags(handle: BorrowedHandle<'_>) -> io::Result<DWORD> {
-    let mut flags: u32 = 0;
-    let success = unsafe {
-        GetNamedPipeInfo(
-            handle.as_raw_handle(),
-            &mut flags as *mut _,
-            ptr::null_mut(),
-            ptr::null_mut(),
-            ptr::null_mut(),
-        ) != 0
-    };
-    ok_or_ret_errno!(success => flags)
-}
 pub(crate) fn is_server_from_sys(handle: BorrowedHandle<'_>) -> io::Result<bool> {
-    // Source: https://docs.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-getnamedpipeinfo
-    const PIPE_IS_SERVER_BIT: u32 = 0x00000001;
-
-    let flags = get_flags(handle)?;
-    Ok(flags & PIPE_IS_SERVER_BIT != 0)
+    PipeInfo::query(handle).map(|info| info.is_server)
 }
 pub(crate) fn has_msg_boundaries_from_sys(handle: BorrowedHandle<'_>) -> io::Result<bool> {
-    // Same source.
-    const PIPE_IS_MESSAGE_BIT: u32 = 0x00000004;
-
-    let flags = get_flags(handle)?;
-    Ok((flags & PIPE_IS_MESSAGE_BIT) != 0)
+    PipeInfo::query(handle).map(|info| info.mode == PipeMode::Messages)
+}
+pub(crate) fn client_computer_name(handle: BorrowedHandle<'_>) -> io::Result<Option<OsString>> {
+    // NetBIOS names are at most 15 characters long, but the name might be a DNS name, which can be up to 255.
+    let mut buf = [0_u16; 256];
+    let success = unsafe {
+        GetNamedPipeClientComputerNameW(handle.as_raw_handle(), buf.as_mut_ptr(), mem::size_of_val(&buf) as _) != 0
+    };
+    if !success {
+        let e = io::Error::last_os_error();
+        return match e.raw_os_error() {
+            Some(c) if c == ERROR_PIPE_LOCAL as i32 => Ok(None),
+            _ => Err(e),
+        };
+    }
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    Ok(Some(OsString::from_wide(&buf[..len])))
 }
 pub(crate) fn peek_msg_len(handle: BorrowedHandle<'_>) -> io::Result<usize> {
     let mut len: DWORD = 0;
@@ -62,19 +68,62 @@ pub(crate) fn peek_msg_len(handle: BorrowedHandle<'_>) -> io::Result<usize> {
     ok_or_ret_errno!(ok => len as usize)
 }
 
-pub(crate) fn _connect(path: &[u16], read: bool, write: bool, timeout: WaitTimeout) -> io::Result<FileHandle> {
+pub(crate) fn _connect(path: &[u16], read: bool, write: bool, opts: &ConnectOptions<'_>) -> io::Result<FileHandle> {
+    let flags = open_flags(opts);
+    let deadline = opts.timeout.map(|t| Instant::now() + t);
+    let mut retries = 0_u32;
     loop {
-        match connect_without_waiting(path, read, write) {
+        match connect_without_waiting(path, read, write, flags) {
             Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) => {
-                block_for_server(path, timeout)?;
-                continue;
+                if !opts.retry_if_busy || retries >= opts.max_busy_retries {
+                    return Err(e);
+                }
+                retries += 1;
+                let timeout = match deadline {
+                    Some(deadline) => {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero() {
+                            return Err(timed_out());
+                        }
+                        WaitTimeout::from_duration(remaining)
+                    }
+                    None => WaitTimeout::DEFAULT,
+                };
+                match block_for_server(path, timeout) {
+                    Err(e) if deadline.is_some() && e.raw_os_error() == Some(ERROR_SEM_TIMEOUT as i32) => {
+                        return Err(timed_out())
+                    }
+                    els => els?,
+                }
             }
             els => return els,
         }
     }
 }
+fn open_flags(opts: &ConnectOptions<'_>) -> DWORD {
+    let mut flags = opts.custom_flags;
+    if opts.write_through {
+        flags |= FILE_FLAG_WRITE_THROUGH;
+    }
+    if let Some(level) = opts.impersonation_level {
+        flags |= SECURITY_SQOS_PRESENT | DWORD::from(level);
+        if opts.effective_only {
+            flags |= SECURITY_EFFECTIVE_ONLY;
+        }
+        if opts.context_tracking {
+            flags |= SECURITY_CONTEXT_TRACKING;
+        }
+    }
+    flags
+}
+fn timed_out() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        "all instances of the named pipe remained busy until the timeout expired",
+    )
+}
 
-fn connect_without_waiting(path: &[u16], read: bool, write: bool) -> io::Result<FileHandle> {
+fn connect_without_waiting(path: &[u16], read: bool, write: bool, flags: DWORD) -> io::Result<FileHandle> {
     assert_eq!(path[path.len() - 1], 0, "nul terminator not found");
     let (success, handle) = unsafe {
         let handle = CreateFileW(
@@ -92,7 +141,7 @@ fn connect_without_waiting(path: &[u16], read: bool, write: bool) -> io::Result<
             FILE_SHARE_READ | FILE_SHARE_WRITE,
             ptr::null_mut(),
             OPEN_EXISTING,
-            0,
+            flags,
             ptr::null_mut(),
         );
         (handle != INVALID_HANDLE_VALUE, handle)
@@ -109,6 +158,12 @@ pub(crate) struct WaitTimeout(u32);
 impl WaitTimeout {
     pub(crate) const DEFAULT: Self = Self(0x00000000);
     //pub(crate) const FOREVER: Self = Self(0xffffffff);
+
+    /// Converts a duration to a timeout in milliseconds, rounding up to the nearest millisecond so as to never produce
+    /// `DEFAULT` and clamping to the largest value which isn't `FOREVER`.
+    pub(crate) fn from_duration(duration: Duration) -> Self {
+        Self(c_wrappers::timeout_to_ms(Some(duration)).max(1))
+    }
 }
 impl From<WaitTimeout> for u32 {
     fn from(x: WaitTimeout) -> Self {
@@ -10,11 +10,15 @@ use crate::{
     },
     Sealed,
 };
+use futures_core::{ready, Stream};
 use std::{
     fmt::{self, Debug, Formatter},
+    future::Future,
     io,
     marker::PhantomData,
     mem::replace,
+    pin::Pin,
+    task::{Context, Poll},
 };
 use tokio::{net::windows::named_pipe::NamedPipeServer as TokioNPServer, sync::Mutex};
 
@@ -111,6 +115,12 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeListener<Rm, Sm> {
 
     /// Asynchronously waits until a client connects to the named pipe, creating a `Stream` to communicate with the
     /// pipe.
+    ///
+    /// # Cancel safety
+    /// This method is cancel-safe: if the future is dropped before a client connects, the instance that was being
+    /// waited on stays with the listener, and a client which connects to it in the meantime is handed out by the next
+    /// call. This makes it suitable for use with `select!` or `tokio::time::timeout()`, e.g. for graceful shutdown.
+    /// The pending connection operation is cancelled when the listener is dropped.
     pub async fn accept(&self) -> io::Result<PipeStream<Rm, Sm>> {
         let instance_to_hand_out = {
             let mut stored_instance = self.stored_instance.lock().await;
@@ -122,6 +132,17 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeListener<Rm, Sm> {
         let raw = RawPipeStream::new_server(instance_to_hand_out);
         Ok(PipeStream::new(raw))
     }
+    /// Creates a [`Stream`] which [`accept`](Self::accept)s connections from clients, yielding each stream once a client
+    /// connects. The stream never ends.
+    ///
+    /// This is the asynchronous counterpart of the [synchronous `incoming`](super::super::PipeListener::incoming) and
+    /// allows using stream combinators on incoming connections.
+    pub fn incoming(&self) -> Incoming<'_, Rm, Sm> {
+        Incoming {
+            listener: self,
+            accept: None,
+        }
+    }
 
     fn create_instance(&self) -> io::Result<TokioNPServer> {
         self.config
@@ -129,6 +150,39 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeListener<Rm, Sm> {
             .and_then(npserver_from_handle)
     }
 }
+
+type AcceptFuture<'a, Rm, Sm> = Pin<Box<dyn Future<Output = io::Result<PipeStream<Rm, Sm>>> + Send + 'a>>;
+
+/// A [`Stream`] that infinitely [`accept`]s connections on a [`PipeListener`].
+///
+/// This stream is created by the [`incoming`] method on [`PipeListener`]. See its documentation for more.
+///
+/// [`accept`]: PipeListener::accept
+/// [`incoming`]: PipeListener::incoming
+pub struct Incoming<'a, Rm: PipeModeTag, Sm: PipeModeTag> {
+    listener: &'a PipeListener<Rm, Sm>,
+    accept: Option<AcceptFuture<'a, Rm, Sm>>,
+}
+impl<Rm: PipeModeTag, Sm: PipeModeTag> Stream for Incoming<'_, Rm, Sm> {
+    type Item = io::Result<PipeStream<Rm, Sm>>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let slf = self.get_mut();
+        let listener = slf.listener;
+        let accept = slf.accept.get_or_insert_with(|| Box::pin(listener.accept()));
+        let rslt = ready!(accept.as_mut().poll(cx));
+        slf.accept = None;
+        Poll::Ready(Some(rslt))
+    }
+}
+impl<Rm: PipeModeTag, Sm: PipeModeTag> Debug for Incoming<'_, Rm, Sm> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Incoming")
+            .field("listener", &self.listener)
+            .field("accepting", &self.accept.is_some())
+            .finish()
+    }
+}
+
 impl<Rm: PipeModeTag, Sm: PipeModeTag> Debug for PipeListener<Rm, Sm> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("PipeListener")
@@ -184,7 +238,7 @@ fn _create_tokio(
     config.nonblocking = false;
 
     let instance = config
-        .create_instance(true, config.nonblocking, true, role, read_mode)
+        .create_first_instance(config.nonblocking, true, role, read_mode)
         .and_then(npserver_from_handle)?;
 
     Ok((config, instance))
@@ -2,18 +2,20 @@
 
 use super::{
     limbo::{send_off, Corpse},
-    *,
+    pool, *,
 };
 use crate::{
+    datagram::{self, AsyncDatagram},
     os::windows::{
         downgrade_eof, downgrade_poll_eof,
         named_pipe::{
             maybe_arc::MaybeArc,
             path_conversion,
             stream::{
-                block_for_server, has_msg_boundaries_from_sys, hget, is_server_from_sys, peek_msg_len, WaitTimeout,
+                block_for_server, client_computer_name, has_msg_boundaries_from_sys, hget, is_server_from_sys,
+                peek_msg_len, WaitTimeout,
             },
-            PipeMode, PmtNotNone, LIMBO_ERR, REBURY_ERR,
+            PeerIdentity, PipeHandleState, PipeInfo, PipeMode, PmtNotNone, LIMBO_ERR, REBURY_ERR,
         },
         winprelude::*,
         FileHandle,
@@ -22,11 +24,14 @@ use crate::{
 };
 use futures_core::ready;
 use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::future::poll_fn;
 use std::{
-    ffi::OsStr,
+    convert::Infallible,
+    ffi::{OsStr, OsString},
     fmt::{self, Debug, DebugStruct, Formatter},
     future::Future,
-    mem::{ManuallyDrop, MaybeUninit},
+    io::{IoSlice, IoSliceMut},
+    mem::{self, ManuallyDrop, MaybeUninit},
     ops::Deref,
     pin::Pin,
     sync::atomic::Ordering,
@@ -54,6 +59,15 @@ macro_rules! same_clsrv {
     };
 }
 
+/// The most data that vectored writes copy into a buffer of their own to be written all at once. Mio copies whatever is
+/// written into a buffer for the overlapped write anyway, so slices bigger than this are better off written on their
+/// own than copied twice.
+///
+/// That copy, and the one Mio makes out of its own buffer on reads, can't be avoided from here: Tokio's named pipes
+/// don't allow submitting overlapped operations against caller buffers, and doing so would take replacing them with an
+/// I/O completion port integration of our own. Only the allocation made for every vectored write is saved on.
+const COALESCE_LIMIT: usize = 64 * 1024;
+
 #[repr(transparent)]
 struct AssertHandleSyncSend(HANDLE);
 unsafe impl Sync for AssertHandleSyncSend {}
@@ -64,6 +78,8 @@ impl RawPipeStream {
         Self {
             inner: Some(inner),
             needs_flush: AtomicBool::new(false),
+            noop_flush: AtomicBool::new(false),
+            coalesce_buf: Mutex::new(Vec::new()),
         }
     }
     pub(crate) fn new_server(server: TokioNPServer) -> Self {
@@ -80,6 +96,13 @@ impl RawPipeStream {
     fn reap(&mut self) -> Corpse {
         self.inner.take().map(Corpse).expect(REBURY_ERR)
     }
+    fn into_inner(self) -> InnerTokio {
+        // Bypass the destructor, which would otherwise try to bury what we're taking.
+        let mut slf = ManuallyDrop::new(self);
+        // The coalescing buffer is the only other field which owns anything.
+        drop(mem::take(slf.coalesce_buf.get_mut().unwrap_or_else(|e| e.into_inner())));
+        slf.inner.take().expect(LIMBO_ERR)
+    }
 
     async fn wait_for_server(path: Vec<u16>) -> io::Result<Vec<u16>> {
         tokio::task::spawn_blocking(move || {
@@ -140,6 +163,30 @@ impl RawPipeStream {
         }
     }
 
+    fn poll_read_vectored(&self, cx: &mut Context<'_>, bufs: &mut [IoSliceMut<'_>]) -> Poll<io::Result<usize>> {
+        let mut total = 0;
+        for buf in bufs.iter_mut().filter(|b| !b.is_empty()) {
+            // Only the first read waits for data; the rest only pick up what has already arrived.
+            let rslt = if total == 0 {
+                ready!(self.poll_read_init(cx, buf))
+            } else {
+                downgrade_eof(same_clsrv!(x in self.inner() => x.try_read(buf)))
+            };
+            match rslt {
+                Ok(n) => {
+                    total += n;
+                    if n < buf.len() {
+                        break;
+                    }
+                }
+                Err(e) if total == 0 => return Poll::Ready(Err(e)),
+                // The error will be reported by the next read, if it's persistent.
+                Err(..) => break,
+            }
+        }
+        Poll::Ready(Ok(total))
+    }
+
     fn poll_write(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
         loop {
             ready!(same_clsrv!(x in self.inner() => x.poll_write_ready(cx)))?;
@@ -152,13 +199,44 @@ impl RawPipeStream {
             }
         }
     }
+    fn poll_write_vectored(&self, cx: &mut Context<'_>, bufs: &[IoSlice<'_>]) -> Poll<io::Result<usize>> {
+        let mut nonempty = bufs.iter().filter(|b| !b.is_empty());
+        let first = match (nonempty.next(), nonempty.next()) {
+            (None, _) => return Poll::Ready(Ok(0)),
+            (Some(buf), None) => return self.poll_write(cx, buf),
+            (Some(first), Some(..)) => first,
+        };
+        if first.len() >= COALESCE_LIMIT {
+            // Big enough to be worth an overlapped write of its own, leaving the rest to the next call.
+            return self.poll_write(cx, first);
+        }
+        // Mio's named pipes write only the first slice when given several, so they have to be coalesced for everything
+        // to end up in a single overlapped write. Waiting for readiness first avoids doing that on every poll.
+        ready!(same_clsrv!(x in self.inner() => x.poll_write_ready(cx)))?;
+        // Concurrent writes through shared references each get a buffer, though only one of them is kept afterwards.
+        let mut coalesced = mem::take(&mut *self.coalesce_buf.lock().unwrap());
+        for buf in bufs {
+            if coalesced.len() + buf.len() > COALESCE_LIMIT {
+                break;
+            }
+            coalesced.extend_from_slice(buf);
+        }
+        let rslt = self.poll_write(cx, &coalesced);
+        coalesced.clear();
+        *self.coalesce_buf.lock().unwrap() = coalesced;
+        rslt
+    }
     #[inline]
     fn write<'a>(&'a self, buf: &'a [u8]) -> Write<'a> {
         Write(self, buf)
     }
 
-    /// Removes the needs-flush flag if it is set, returning its previous value.
+    /// Removes the needs-flush flag if it is set, returning its previous value. Always returns `false` without touching
+    /// the flag if flushing is disabled, so that limbo still gets to flush the stream.
     fn cas_flush(&self) -> bool {
+        if self.noop_flush.load(Ordering::Acquire) {
+            return false;
+        }
         self.needs_flush
             .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
             .is_ok()
@@ -313,6 +391,22 @@ impl<Rm: PipeModeTag> PipeStream<Rm, pipe_mode::Messages> {
         self.raw.write(buf).await
     }
 }
+impl PipeStream<pipe_mode::Messages, pipe_mode::Messages> {
+    /// Sends a request message and receives the response message, which makes for a convenient implementation of
+    /// simple RPC clients.
+    ///
+    /// Unlike the [synchronous version](crate::os::windows::named_pipe::PipeStream::transact), this doesn't use
+    /// `TransactNamedPipe` and instead sends the request and then receives the response, so as to go through Tokio's
+    /// reactor. The result is the same as long as the caller doesn't concurrently send or receive other messages
+    /// through the same stream.
+    ///
+    /// The response is received into the given buffer if it fits; otherwise, a new buffer of sufficient size is
+    /// allocated, just like with [`.recv()`](crate::reliable_recv_msg::AsyncReliableRecvMsgExt::recv).
+    pub async fn transact(&self, request: &[u8], response: &mut [u8]) -> io::Result<RecvResult> {
+        self.send(request).await?;
+        poll_fn(|cx| Pin::new(&mut &*self).poll_recv(cx, response)).await
+    }
+}
 
 impl<Sm: PipeModeTag> PipeStream<pipe_mode::Bytes, Sm> {
     /// Same as `.read()` from the [`Read`] trait, but accepts an uninitialized buffer.
@@ -386,10 +480,35 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeStream<Rm, Sm> {
         unsafe { hget(self.as_handle(), GetNamedPipeClientProcessId) }
     }
     /// Retrieves the session identifier of the client side of the named pipe connection.
+    ///
+    /// On systems with multiple interactive sessions, such as Terminal Server or Remote Desktop hosts, this identifies
+    /// the session the client process is running in, which services can use to tell apart clients from different
+    /// logged-on users.
     #[inline]
     pub fn client_session_id(&self) -> io::Result<u32> {
         unsafe { hget(self.as_handle(), GetNamedPipeClientSessionId) }
     }
+    /// Retrieves the name of the computer on which the client side of the named pipe connection is located, or `None`
+    /// if the client is local.
+    ///
+    /// # System calls
+    /// - `GetNamedPipeClientComputerNameW`
+    #[inline]
+    pub fn client_computer_name(&self) -> io::Result<Option<OsString>> {
+        client_computer_name(self.as_handle())
+    }
+    /// Retrieves the identity of the client process – its executable path and the user account it runs as – so that
+    /// servers can reject connections from unexpected binaries or users.
+    ///
+    /// Only local clients can be identified this way, since the process identifier of a remote client is meaningless
+    /// on the local machine. See [`PeerIdentity::query()`] for the access rights required.
+    ///
+    /// # System calls
+    /// - `GetNamedPipeClientProcessId`
+    /// - All of the system calls performed by [`PeerIdentity::query()`]
+    pub fn client_identity(&self) -> io::Result<PeerIdentity> {
+        PeerIdentity::query(self.client_process_id()?)
+    }
     /// Retrieves the process identifier of the server side of the named pipe connection.
     #[inline]
     pub fn server_process_id(&self) -> io::Result<u32> {
@@ -400,6 +519,18 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> PipeStream<Rm, Sm> {
     pub fn server_session_id(&self) -> io::Result<u32> {
         unsafe { hget(self.as_handle(), GetNamedPipeServerSessionId) }
     }
+    /// Retrieves information about the named pipe the stream belongs to, such as the sizes of its buffers and whether
+    /// it has message boundaries.
+    #[inline]
+    pub fn pipe_info(&self) -> io::Result<PipeInfo> {
+        PipeInfo::query(self.as_handle())
+    }
+    /// Retrieves the current state of the stream's handle, such as its read mode and the amount of instances of the
+    /// pipe that currently exist.
+    #[inline]
+    pub fn handle_state(&self) -> io::Result<PipeHandleState> {
+        PipeHandleState::query(self.as_handle())
+    }
     /// Returns `true` if the stream was created by a listener (server-side), `false` if it was created by connecting to
     /// a server (server-side).
     #[inline]
@@ -440,7 +571,8 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag + PmtNotNone> PipeStream<Rm, Sm> {
     }
     /// Flushes the stream, waiting until the send buffer is empty (has been received by the other end in its entirety).
     ///
-    /// Only available on streams that have a send mode.
+    /// Only available on streams that have a send mode. Does nothing if flushing has been disabled with
+    /// [`.set_flush_enabled()`](Self::set_flush_enabled).
     pub async fn flush(&self) -> io::Result<()> {
         if !self.raw.cas_flush() {
             // No flush required.
@@ -466,6 +598,24 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag + PmtNotNone> PipeStream<Rm, Sm> {
         }
         rslt
     }
+    /// Sets whether [`.flush()`](Self::flush) actually waits for the other end to receive everything that's been sent
+    /// or immediately returns without doing anything. Enabled by default.
+    ///
+    /// Latency-sensitive applications, which can't afford to wait until the client catches up, can disable flushing.
+    /// Data which hasn't been flushed when the stream is dropped is still delivered by limbo, unless
+    /// [`.assume_flushed()`](Self::assume_flushed) is used.
+    ///
+    /// This setting is shared between the halves of a split stream. If there's already an outstanding `.flush()`
+    /// operation, it won't be affected by this call.
+    #[inline]
+    pub fn set_flush_enabled(&self, enabled: bool) {
+        self.raw.noop_flush.store(!enabled, Ordering::Release);
+    }
+    /// Returns whether [`.flush()`](Self::flush) is enabled. See [`.set_flush_enabled()`](Self::set_flush_enabled).
+    #[inline]
+    pub fn is_flush_enabled(&self) -> bool {
+        !self.raw.noop_flush.load(Ordering::Acquire)
+    }
     /// Assumes that the other side has consumed everything that's been written so far. This will turn the next flush
     /// into a no-op, but will cause the send buffer to be cleared when the stream is closed, since it won't be sent to
     /// limbo.
@@ -489,12 +639,28 @@ impl<Sm: PipeModeTag> AsyncRead for &PipeStream<pipe_mode::Bytes, Sm> {
     fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
         self.raw.poll_read_init(cx, buf)
     }
+    #[inline]
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.raw.poll_read_vectored(cx, bufs)
+    }
 }
 impl<Sm: PipeModeTag> AsyncRead for PipeStream<pipe_mode::Bytes, Sm> {
     #[inline]
     fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
         Pin::new(&mut self.deref()).poll_read(cx, buf)
     }
+    #[inline]
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.deref()).poll_read_vectored(cx, bufs)
+    }
 }
 // TODO TokioAsyncRead on ref
 impl<Sm: PipeModeTag> TokioAsyncRead for PipeStream<pipe_mode::Bytes, Sm> {
@@ -508,6 +674,14 @@ impl<Rm: PipeModeTag> AsyncWrite for &PipeStream<Rm, pipe_mode::Bytes> {
     fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
         self.raw.poll_write(cx, buf)
     }
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.raw.poll_write_vectored(cx, bufs)
+    }
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         if !self.raw.cas_flush() {
             // No flush required.
@@ -543,6 +717,14 @@ impl<Rm: PipeModeTag> AsyncWrite for PipeStream<Rm, pipe_mode::Bytes> {
         Pin::new(&mut self.deref()).poll_write(cx, buf)
     }
     #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.deref()).poll_write_vectored(cx, bufs)
+    }
+    #[inline]
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         Pin::new(&mut self.deref()).poll_flush(cx)
     }
@@ -558,6 +740,18 @@ impl<Rm: PipeModeTag> TokioAsyncWrite for PipeStream<Rm, pipe_mode::Bytes> {
         self.get_mut().raw.poll_write(cx, buf)
     }
     #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize, io::Error>> {
+        self.get_mut().raw.poll_write_vectored(cx, bufs)
+    }
+    #[inline(always)]
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+    #[inline]
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
         <&Self as AsyncWrite>::poll_flush(Pin::new(&mut &*self), cx)
     }
@@ -582,6 +776,27 @@ impl<Sm: PipeModeTag> AsyncReliableRecvMsg for PipeStream<pipe_mode::Messages, S
         Pin::new(&mut self.deref()).poll_recv(cx, buf)
     }
 }
+impl AsyncDatagram for PipeStream<pipe_mode::Messages, pipe_mode::Messages> {
+    type Address = Infallible;
+    fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, msg: &[u8]) -> Poll<io::Result<()>> {
+        let sent = ready!(self.raw.poll_write(cx, msg))?;
+        Poll::Ready(datagram::check_sent(sent, msg.len()))
+    }
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let TryRecvResult { size, fit } = ready!(self.raw.poll_try_recv_msg(cx, buf))?;
+        if fit {
+            return Poll::Ready(Ok(size));
+        }
+        // The intermediate buffer goes back to the pool even if the message somehow isn't there anymore.
+        let mut msg = pool::take(size);
+        let rslt = self
+            .raw
+            .poll_try_recv_msg(cx, &mut msg)
+            .map_ok(|TryRecvResult { size, .. }| datagram::truncate_into(&msg[..size.min(msg.len())], buf));
+        pool::give_back(msg);
+        rslt
+    }
+}
 impl<Rm: PipeModeTag, Sm: PipeModeTag> Debug for PipeStream<Rm, Sm> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut dbst = f.debug_struct("PipeStream");
@@ -608,30 +823,107 @@ impl<Rm: PipeModeTag, Sm: PipeModeTag> TryFrom<OwnedHandle> for PipeStream<Rm, S
     type Error = FromHandleError;
 
     fn try_from(handle: OwnedHandle) -> Result<Self, Self::Error> {
-        // If the wrapper type tries to read incoming data as messages, that might break if
-        // the underlying pipe has no message boundaries. Let's check for that.
-        if Rm::MODE == Some(PipeMode::Messages) {
-            let msg_bnd = match has_msg_boundaries_from_sys(handle.as_handle()) {
-                Ok(b) => b,
-                Err(e) => {
-                    return Err(FromHandleError {
-                        details: FromHandleErrorKind::MessageBoundariesCheckFailed,
-                        cause: Some(e),
-                        source: Some(handle),
-                    })
-                }
-            };
-            if !msg_bnd {
-                return Err(FromHandleError {
-                    details: FromHandleErrorKind::NoMessageBoundaries,
-                    cause: None,
-                    source: Some(handle),
-                });
-            }
+        if let Err((details, cause)) = check_msg_boundaries::<Rm>(handle.as_handle()) {
+            return Err(FromHandleError {
+                details,
+                cause,
+                source: Some(handle),
+            });
         }
         let raw = RawPipeStream::try_from(handle)?;
         Ok(Self::new(raw))
     }
 }
+/// Wraps the given Tokio named pipe server into the high-level pipe stream type, allowing code which uses Tokio's pipe
+/// types to gradually migrate to this crate. Fails if `Rm` receives messages but the pipe does not preserve message
+/// boundaries.
+impl<Rm: PipeModeTag, Sm: PipeModeTag> TryFrom<TokioNPServer> for PipeStream<Rm, Sm> {
+    type Error = ConversionError<TokioNPServer, FromHandleErrorKind>;
+
+    fn try_from(server: TokioNPServer) -> Result<Self, Self::Error> {
+        if let Err((details, cause)) = check_msg_boundaries::<Rm>(server.as_handle()) {
+            return Err(ConversionError {
+                details,
+                cause,
+                source: Some(server),
+            });
+        }
+        Ok(Self::new(RawPipeStream::new_server(server)))
+    }
+}
+/// Wraps the given Tokio named pipe client into the high-level pipe stream type, allowing code which uses Tokio's pipe
+/// types to gradually migrate to this crate. Fails if `Rm` receives messages but the pipe does not preserve message
+/// boundaries.
+impl<Rm: PipeModeTag, Sm: PipeModeTag> TryFrom<TokioNPClient> for PipeStream<Rm, Sm> {
+    type Error = ConversionError<TokioNPClient, FromHandleErrorKind>;
+
+    fn try_from(client: TokioNPClient) -> Result<Self, Self::Error> {
+        if let Err((details, cause)) = check_msg_boundaries::<Rm>(client.as_handle()) {
+            return Err(ConversionError {
+                details,
+                cause,
+                source: Some(client),
+            });
+        }
+        Ok(Self::new(RawPipeStream::new_client(client)))
+    }
+}
+/// Attempts to unwrap the given stream into the Tokio named pipe server type, returning itself back if it's a
+/// client-side stream or if no ownership over the handle is available, as is the case when the stream is split.
+///
+/// Data which hasn't been flushed yet is not waited for.
+impl<Rm: PipeModeTag, Sm: PipeModeTag> TryFrom<PipeStream<Rm, Sm>> for TokioNPServer {
+    type Error = PipeStream<Rm, Sm>;
+
+    fn try_from(stream: PipeStream<Rm, Sm>) -> Result<Self, Self::Error> {
+        match stream.raw {
+            MaybeArc::Inline(raw) if matches!(raw.inner(), InnerTokio::Server(..)) => match raw.into_inner() {
+                InnerTokio::Server(server) => Ok(server),
+                InnerTokio::Client(..) => unreachable!(),
+            },
+            raw => Err(PipeStream {
+                raw,
+                flush: stream.flush,
+                _phantom: PhantomData,
+            }),
+        }
+    }
+}
+/// Attempts to unwrap the given stream into the Tokio named pipe client type, returning itself back if it's a
+/// server-side stream or if no ownership over the handle is available, as is the case when the stream is split.
+///
+/// Data which hasn't been flushed yet is not waited for.
+impl<Rm: PipeModeTag, Sm: PipeModeTag> TryFrom<PipeStream<Rm, Sm>> for TokioNPClient {
+    type Error = PipeStream<Rm, Sm>;
+
+    fn try_from(stream: PipeStream<Rm, Sm>) -> Result<Self, Self::Error> {
+        match stream.raw {
+            MaybeArc::Inline(raw) if matches!(raw.inner(), InnerTokio::Client(..)) => match raw.into_inner() {
+                InnerTokio::Client(client) => Ok(client),
+                InnerTokio::Server(..) => unreachable!(),
+            },
+            raw => Err(PipeStream {
+                raw,
+                flush: stream.flush,
+                _phantom: PhantomData,
+            }),
+        }
+    }
+}
+
+/// If the wrapper type tries to read incoming data as messages, that might break if the underlying pipe has no message
+/// boundaries. This checks for that.
+fn check_msg_boundaries<Rm: PipeModeTag>(
+    handle: BorrowedHandle<'_>,
+) -> Result<(), (FromHandleErrorKind, Option<io::Error>)> {
+    if Rm::MODE != Some(PipeMode::Messages) {
+        return Ok(());
+    }
+    match has_msg_boundaries_from_sys(handle) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err((FromHandleErrorKind::NoMessageBoundaries, None)),
+        Err(e) => Err((FromHandleErrorKind::MessageBoundariesCheckFailed, Some(e))),
+    }
+}
 
 derive_asraw!(windows: {Rm: PipeModeTag, Sm: PipeModeTag} PipeStream<Rm, Sm>);
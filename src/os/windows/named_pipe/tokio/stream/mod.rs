@@ -1,6 +1,8 @@
 mod impls;
 mod limbo;
+mod pool;
 mod wrapper_fns;
+pub use pool::{buffer_pool_limits, set_buffer_pool_limits, BufferPoolLimits};
 pub(crate) use wrapper_fns::*;
 
 use crate::{
@@ -19,7 +21,7 @@ use std::{
     fmt::{self, Display, Formatter},
     io,
     marker::PhantomData,
-    sync::atomic::AtomicBool,
+    sync::{atomic::AtomicBool, Mutex},
 };
 use tokio::{
     net::windows::named_pipe::{NamedPipeClient as TokioNPClient, NamedPipeServer as TokioNPServer},
@@ -104,6 +106,10 @@ pub(crate) struct RawPipeStream {
     inner: Option<InnerTokio>,
     // Cleared by the generic pipes rather than the raw pipe stream unlike in sync land.
     needs_flush: AtomicBool,
+    noop_flush: AtomicBool,
+    /// Buffer into which vectored writes are coalesced, taken out for the duration of a write and put back after it, so
+    /// that it's only allocated once per stream.
+    coalesce_buf: Mutex<Vec<u8>>,
 }
 enum InnerTokio {
     Server(TokioNPServer),
@@ -0,0 +1,71 @@
+//! The pool of intermediate buffers which messages that don't fit into the buffer given to a receive operation are
+//! received into before being truncated.
+
+use std::sync::Mutex;
+
+/// Limits on the pool of intermediate buffers used by Tokio-based pipe streams, set with
+/// [`set_buffer_pool_limits()`].
+///
+/// When a message is received with [`AsyncDatagram::recv()`](crate::datagram::AsyncDatagramExt::recv) into a buffer
+/// that's too small for it, the whole message has to be received into a buffer of sufficient size first, since named
+/// pipes would otherwise leave the rest of it to the next receive operation. Those intermediate buffers are taken from
+/// a process-wide pool and put back after use, so that programs which do this with every message don't allocate a
+/// buffer each time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BufferPoolLimits {
+    /// The most buffers kept in the pool between uses. Setting this to zero disables pooling.
+    pub max_buffers: usize,
+    /// The capacity of the biggest buffer kept in the pool. Bigger ones are freed after use, so that a single huge
+    /// message doesn't keep its memory allocated for the lifetime of the process.
+    pub max_buffer_size: usize,
+}
+impl BufferPoolLimits {
+    /// The limits used unless set otherwise: 16 buffers of up to 64 KiB each.
+    pub const DEFAULT: Self = Self {
+        max_buffers: 16,
+        max_buffer_size: 64 * 1024,
+    };
+}
+impl Default for BufferPoolLimits {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+struct Pool {
+    buffers: Vec<Vec<u8>>,
+    limits: BufferPoolLimits,
+}
+static POOL: Mutex<Pool> = Mutex::new(Pool {
+    buffers: Vec::new(),
+    limits: BufferPoolLimits::DEFAULT,
+});
+
+/// Sets the limits on the pool of intermediate buffers used by Tokio-based pipe streams, freeing the buffers which go
+/// over the new limits. See [`BufferPoolLimits`].
+pub fn set_buffer_pool_limits(limits: BufferPoolLimits) {
+    let mut pool = POOL.lock().unwrap();
+    pool.limits = limits;
+    pool.buffers.retain(|buf| buf.capacity() <= limits.max_buffer_size);
+    pool.buffers.truncate(limits.max_buffers);
+}
+/// Returns the limits on the pool of intermediate buffers used by Tokio-based pipe streams. See [`BufferPoolLimits`].
+pub fn buffer_pool_limits() -> BufferPoolLimits {
+    POOL.lock().unwrap().limits
+}
+
+/// Takes a buffer from the pool, or allocates one if it's empty, and fills it with `size` zeroes.
+pub(super) fn take(size: usize) -> Vec<u8> {
+    let mut buf = POOL.lock().unwrap().buffers.pop().unwrap_or_default();
+    buf.clear();
+    buf.resize(size, 0);
+    buf
+}
+/// Puts a buffer back into the pool, unless that would go over its limits.
+pub(super) fn give_back(buf: Vec<u8>) {
+    let mut pool = POOL.lock().unwrap();
+    if pool.buffers.len() < pool.limits.max_buffers && buf.capacity() <= pool.limits.max_buffer_size {
+        pool.buffers.push(buf);
+    }
+}
@@ -0,0 +1,129 @@
+use super::{named_semaphore::NamedSemaphore, shared_memory::SharedMemory};
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::size_of,
+    sync::atomic::{AtomicU32, Ordering::SeqCst},
+};
+
+/// Set in the state while a writer holds the lock. The rest of the bits count the readers.
+const WRITER: u32 = 1 << 31;
+
+/// The contents of the shared memory object backing the lock, which are zeroed upon creation.
+#[repr(C)]
+struct Shared {
+    state: AtomicU32,
+    /// The number of threads which are about to block on the semaphore.
+    waiters: AtomicU32,
+}
+
+/// A reader-writer lock built from an atomic state word in shared memory and a semaphore to block on, in the spirit of
+/// SRW locks.
+///
+/// SRW locks and `WaitOnAddress` only work within a single process, hence the emulation. Threads which fail to take
+/// the lock register as waiters and block on the semaphore; releasing the lock wakes all of them up to compete for it
+/// again. A thread that wins the lock without blocking can leave a permit behind, which only causes a spurious wakeup
+/// of some later waiter.
+pub(crate) struct NamedRwLock {
+    shm: SharedMemory,
+    sem: NamedSemaphore,
+}
+impl NamedRwLock {
+    /// The semaphore is created first, so that it exists by the time the shared memory object can be opened.
+    pub fn create(name: &OsStr, _keep_drop_guard: bool) -> io::Result<Self> {
+        let sem = NamedSemaphore::create(&semaphore_name(name), 0, false)?;
+        let shm = SharedMemory::create(name, size_of::<Shared>(), false)?;
+        Ok(Self { shm, sem })
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let shm = SharedMemory::open(name)?;
+        if shm.len() < size_of::<Shared>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory object is too small to contain a reader-writer lock",
+            ));
+        }
+        let sem = NamedSemaphore::open(&semaphore_name(name))?;
+        Ok(Self { shm, sem })
+    }
+
+    #[inline]
+    fn shared(&self) -> &Shared {
+        // SAFETY: the mapping is page-aligned and large enough, and only contains atomics
+        unsafe { &*self.shm.as_ptr().cast::<Shared>() }
+    }
+
+    pub fn read(&self) -> io::Result<()> {
+        self.block_on(Self::try_read)
+    }
+    pub fn try_read(&self) -> io::Result<()> {
+        self.shared()
+            .state
+            // The reader count stops short of the writer bit, which puts the state above the limit while it's set.
+            .fetch_update(SeqCst, SeqCst, |s| s.checked_add(1).filter(|&n| n < WRITER))
+            .map(drop)
+            .map_err(|_| io::Error::from(io::ErrorKind::WouldBlock))
+    }
+    pub fn write(&self) -> io::Result<()> {
+        self.block_on(Self::try_write)
+    }
+    pub fn try_write(&self) -> io::Result<()> {
+        self.shared()
+            .state
+            .compare_exchange(0, WRITER, SeqCst, SeqCst)
+            .map(drop)
+            .map_err(|_| io::Error::from(io::ErrorKind::WouldBlock))
+    }
+    pub fn read_unlock(&self) {
+        if self.shared().state.fetch_sub(1, SeqCst) == 1 {
+            self.wake_all();
+        }
+    }
+    pub fn write_unlock(&self) {
+        self.shared().state.store(0, SeqCst);
+        self.wake_all();
+    }
+
+    fn block_on(&self, mut try_lock: impl FnMut(&Self) -> io::Result<()>) -> io::Result<()> {
+        loop {
+            match try_lock(self) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return els,
+            }
+            // Registering before checking again means that an unlock either lets the check through or sees us.
+            self.shared().waiters.fetch_add(1, SeqCst);
+            match try_lock(self) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => self.sem.acquire()?,
+                els => {
+                    let _ = self.shared().waiters.fetch_update(SeqCst, SeqCst, |w| w.checked_sub(1));
+                    return els;
+                }
+            }
+        }
+    }
+    fn wake_all(&self) {
+        match self.shared().waiters.swap(0, SeqCst) {
+            0 => {}
+            n => {
+                // The only way for this to fail is for the count to be absurdly high, which can't happen.
+                let _ = self.sem.release(n);
+            }
+        }
+    }
+}
+impl Debug for NamedRwLock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedRwLock")
+            .field("shm", &self.shm)
+            .field("sem", &self.sem)
+            .finish()
+    }
+}
+
+/// Section and semaphore objects share a namespace, so the semaphore needs a name of its own.
+fn semaphore_name(name: &OsStr) -> OsString {
+    let mut sem_name = name.to_owned();
+    sem_name.push(".rwlock");
+    sem_name
+}
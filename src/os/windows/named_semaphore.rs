@@ -0,0 +1,98 @@
+use super::{c_wrappers, winprelude::*};
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io, iter, ptr,
+    time::Duration,
+};
+use winapi::{
+    shared::{
+        ntdef::LONG,
+        winerror::{ERROR_ALREADY_EXISTS, WAIT_TIMEOUT},
+    },
+    um::{
+        synchapi::{CreateSemaphoreW, OpenSemaphoreW, ReleaseSemaphore, WaitForSingleObject},
+        winbase::{INFINITE, WAIT_OBJECT_0},
+        winnt::{SEMAPHORE_MODIFY_STATE, SYNCHRONIZE},
+    },
+};
+
+/// A named semaphore object.
+pub(crate) struct NamedSemaphore(OwnedHandle);
+impl NamedSemaphore {
+    /// The semaphore object is destroyed along with its last handle, and so there is no drop guard to keep.
+    pub fn create(name: &OsStr, initial: u32, _keep_drop_guard: bool) -> io::Result<Self> {
+        let name = to_wide(name)?;
+        let initial = LONG::try_from(initial)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "initial semaphore value out of range"))?;
+        let handle = unsafe { CreateSemaphoreW(ptr::null_mut(), initial, LONG::MAX, name.as_ptr()) };
+        let handle = ok_or_ret_errno!(!handle.is_null() => unsafe {
+            // SAFETY: we just created this handle
+            OwnedHandle::from_raw_handle(handle)
+        })?;
+        // CreateSemaphoreW() opens the existing object instead of failing, leaving a note for us to find.
+        if io::Error::last_os_error().raw_os_error() == Some(ERROR_ALREADY_EXISTS as _) {
+            return Err(io::Error::from_raw_os_error(ERROR_ALREADY_EXISTS as _));
+        }
+        Ok(Self(handle))
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let name = to_wide(name)?;
+        let handle = unsafe { OpenSemaphoreW(SYNCHRONIZE | SEMAPHORE_MODIFY_STATE, 0, name.as_ptr()) };
+        ok_or_ret_errno!(!handle.is_null() => Self(unsafe {
+            // SAFETY: we just opened this handle
+            OwnedHandle::from_raw_handle(handle)
+        }))
+    }
+
+    pub fn acquire(&self) -> io::Result<()> {
+        while !self.wait(INFINITE)? {}
+        Ok(())
+    }
+    pub fn try_acquire(&self) -> io::Result<()> {
+        match self.wait(0)? {
+            true => Ok(()),
+            false => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+        }
+    }
+    pub fn acquire_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        self.wait(c_wrappers::timeout_to_ms(Some(timeout)))
+    }
+    fn wait(&self, timeout_ms: DWORD) -> io::Result<bool> {
+        match unsafe { WaitForSingleObject(self.0.as_raw_handle(), timeout_ms) } {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+    pub fn release(&self, n: u32) -> io::Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+        let n = LONG::try_from(n)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "semaphore release count out of range"))?;
+        let success = unsafe { ReleaseSemaphore(self.0.as_raw_handle(), n, ptr::null_mut()) != 0 };
+        ok_or_ret_errno!(success => ())
+    }
+}
+impl AsHandle for NamedSemaphore {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl Debug for NamedSemaphore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NamedSemaphore").field(&self.0.as_raw_handle()).finish()
+    }
+}
+
+fn to_wide(name: &OsStr) -> io::Result<Vec<u16>> {
+    if name.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "semaphore names must be non-empty",
+        ));
+    }
+    Ok(name.encode_wide().chain(iter::once(0)).collect())
+}
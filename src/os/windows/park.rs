@@ -0,0 +1,64 @@
+use super::{c_wrappers, winprelude::*};
+use std::{
+    io,
+    mem::size_of,
+    sync::atomic::{AtomicU32, Ordering::Acquire},
+    time::{Duration, Instant},
+};
+use winapi::{
+    shared::winerror::ERROR_TIMEOUT,
+    um::synchapi::{WaitOnAddress, WakeByAddressAll, WakeByAddressSingle},
+};
+
+/// The longest stretch of time a waiter sleeps in `WaitOnAddress` before looking at the word again.
+const MAX_SLICE: Duration = Duration::from_millis(16);
+
+/// `WaitOnAddress` only ever gets woken up by threads of the same process, and so the wait is split into short slices
+/// with the word being checked after each one, which bounds the latency of wakeups coming from other processes.
+pub(crate) fn park(word: &AtomicU32, expected: u32, timeout: Option<Duration>) -> io::Result<bool> {
+    let deadline = timeout.and_then(|t| Instant::now().checked_add(t));
+    let mut slice = Duration::from_millis(1);
+    loop {
+        if word.load(Acquire) != expected {
+            return Ok(true);
+        }
+        let slice_ms = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(false);
+                }
+                c_wrappers::timeout_to_ms(Some((deadline - now).min(slice)))
+            }
+            None => slice.as_millis() as DWORD,
+        };
+        let mut expected = expected;
+        let woken = unsafe {
+            WaitOnAddress(
+                word.as_ptr().cast(),
+                (&mut expected as *mut u32).cast(),
+                size_of::<u32>(),
+                slice_ms,
+            ) != 0
+        };
+        if woken {
+            return Ok(true);
+        }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() != Some(ERROR_TIMEOUT as _) {
+            return Err(e);
+        }
+        slice = (slice * 2).min(MAX_SLICE);
+    }
+}
+pub(crate) fn unpark(word: &AtomicU32, all: bool) -> io::Result<()> {
+    // Waiters in other processes don't need to be woken up, since they'll notice the change on their own.
+    unsafe {
+        if all {
+            WakeByAddressAll(word.as_ptr().cast());
+        } else {
+            WakeByAddressSingle(word.as_ptr().cast());
+        }
+    }
+    Ok(())
+}
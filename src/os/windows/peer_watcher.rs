@@ -0,0 +1,62 @@
+use super::{c_wrappers, winprelude::*};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+use winapi::{
+    shared::winerror::{ERROR_INVALID_PARAMETER, WAIT_TIMEOUT},
+    um::{processthreadsapi::OpenProcess, synchapi::WaitForSingleObject, winbase::WAIT_OBJECT_0, winnt::SYNCHRONIZE},
+};
+
+/// A handle to the process, which is signaled once it exits, or nothing if the process was gone by the time the
+/// watcher was created.
+pub(crate) struct PeerWatcher(Option<OwnedHandle>);
+impl PeerWatcher {
+    pub fn new(pid: u32) -> io::Result<Self> {
+        let handle = unsafe { OpenProcess(SYNCHRONIZE, 0, pid) };
+        if handle.is_null() {
+            let e = io::Error::last_os_error();
+            // That's what OpenProcess() fails with if there is no process with that ID.
+            if e.raw_os_error() == Some(ERROR_INVALID_PARAMETER as _) {
+                return Ok(Self(None));
+            }
+            return Err(e);
+        }
+        Ok(Self(Some(unsafe {
+            // SAFETY: we just opened this handle
+            OwnedHandle::from_raw_handle(handle)
+        })))
+    }
+
+    pub fn wait(&self) -> io::Result<()> {
+        while !self.wait_timeout(None)? {}
+        Ok(())
+    }
+    /// Returns `false` if the timeout ran out before the process exited.
+    pub fn wait_timeout(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        let Some(handle) = &self.0 else { return Ok(true) };
+        match unsafe { WaitForSingleObject(handle.as_raw_handle(), c_wrappers::timeout_to_ms(timeout)) } {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+    /// Waits on a thread from the blocking pool of the runtime, since process handles can't be registered in its
+    /// event loop.
+    #[cfg(feature = "tokio")]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        let Some(handle) = &self.0 else { return Ok(()) };
+        let watcher = Self(Some(c_wrappers::duplicate_handle(handle.as_handle())?));
+        tokio::task::spawn_blocking(move || watcher.wait())
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+}
+impl Debug for PeerWatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PeerWatcher")
+            .field(&self.0.as_ref().map(AsRawHandle::as_raw_handle))
+            .finish()
+    }
+}
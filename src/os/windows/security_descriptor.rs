@@ -0,0 +1,182 @@
+use super::winprelude::*;
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    io, iter, ptr,
+    ptr::NonNull,
+    slice,
+};
+use winapi::{
+    shared::{
+        sddl::{ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1},
+        winerror::ERROR_INSUFFICIENT_BUFFER,
+    },
+    um::{
+        minwinbase::LMEM_FIXED,
+        securitybaseapi::{
+            GetSecurityDescriptorControl, GetSecurityDescriptorLength, IsValidSecurityDescriptor, MakeSelfRelativeSD,
+        },
+        winbase::{LocalAlloc, LocalFree},
+        winnt::{PSECURITY_DESCRIPTOR, SE_SELF_RELATIVE},
+    },
+};
+
+/// An owned security descriptor, used to control who can access an object created by this crate.
+///
+/// The descriptor is always stored in the self-relative format, which means that it occupies one contiguous block of
+/// memory and can be freely copied around. It's allocated with `LocalAlloc`, which is what
+/// `ConvertStringSecurityDescriptorToSecurityDescriptorW` uses as well.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::os::windows::{named_pipe::*, SecurityDescriptor};
+/// use std::ffi::OsStr;
+///
+/// // Grant full access to the owner of the pipe and nobody else.
+/// let sd = SecurityDescriptor::from_sddl("D:P(A;;GA;;;OW)")?;
+/// let listener = PipeListenerOptions::new()
+///     .name(OsStr::new("Example"))
+///     .security_descriptor(sd)
+///     .create_duplex::<pipe_mode::Bytes>()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SecurityDescriptor(NonNull<u8>);
+// SAFETY: the descriptor is owned and is never mutated after creation.
+unsafe impl Send for SecurityDescriptor {}
+unsafe impl Sync for SecurityDescriptor {}
+impl SecurityDescriptor {
+    /// Parses a security descriptor from a string in the [security descriptor definition language].
+    ///
+    /// # System calls
+    /// - `ConvertStringSecurityDescriptorToSecurityDescriptorW`
+    ///
+    /// [security descriptor definition language]: https://learn.microsoft.com/en-us/windows/win32/secauthz/security-descriptor-definition-language
+    pub fn from_sddl(sddl: impl AsRef<OsStr>) -> io::Result<Self> {
+        Self::_from_sddl(sddl.as_ref())
+    }
+    fn _from_sddl(sddl: &OsStr) -> io::Result<Self> {
+        let sddl = sddl.encode_wide().chain(iter::once(0)).collect::<Vec<u16>>();
+        let mut sd: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        let success = unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                sddl.as_ptr(),
+                SDDL_REVISION_1.into(),
+                &mut sd,
+                ptr::null_mut(),
+            ) != 0
+        };
+        ok_or_ret_errno!(success => unsafe {
+            // SAFETY: the function succeeded and returned a self-relative descriptor allocated with LocalAlloc,
+            // ownership of which is transferred to us
+            Self::from_local_alloc(sd)
+        })
+    }
+    /// Copies the given security descriptor, which can be either in the absolute or the self-relative format, into a
+    /// new owned self-relative one.
+    ///
+    /// # Safety
+    /// `sd` must point to a valid security descriptor, and all the structures it references (in the case of the
+    /// absolute format) must be valid for the duration of the call.
+    ///
+    /// # System calls
+    /// - `IsValidSecurityDescriptor`
+    /// - `GetSecurityDescriptorControl`
+    /// - `GetSecurityDescriptorLength`
+    /// - `MakeSelfRelativeSD`
+    pub unsafe fn copy_from_raw(sd: PSECURITY_DESCRIPTOR) -> io::Result<Self> {
+        if sd.is_null() || unsafe { IsValidSecurityDescriptor(sd) } == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid security descriptor",
+            ));
+        }
+        let (mut control, mut revision) = (0, 0);
+        if unsafe { GetSecurityDescriptorControl(sd, &mut control, &mut revision) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if control & SE_SELF_RELATIVE != 0 {
+            let len = unsafe { GetSecurityDescriptorLength(sd) } as usize;
+            let new = local_alloc(len)?;
+            unsafe {
+                // SAFETY: self-relative descriptors are exactly GetSecurityDescriptorLength bytes long
+                ptr::copy_nonoverlapping(sd.cast::<u8>(), new.as_ptr(), len);
+            }
+            return Ok(Self(new));
+        }
+
+        let mut len: DWORD = 0;
+        let success = unsafe { MakeSelfRelativeSD(sd, ptr::null_mut(), &mut len) != 0 };
+        if !success {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as _) {
+                return Err(e);
+            }
+        }
+        let new = Self(local_alloc(len as usize)?);
+        let success = unsafe { MakeSelfRelativeSD(sd, new.as_ptr(), &mut len) != 0 };
+        ok_or_ret_errno!(success => new)
+    }
+    /// Takes ownership of a self-relative security descriptor allocated with `LocalAlloc`.
+    unsafe fn from_local_alloc(sd: PSECURITY_DESCRIPTOR) -> Self {
+        Self(NonNull::new(sd.cast()).expect("null security descriptor"))
+    }
+
+    /// Returns a pointer to the security descriptor, suitable for use in `SECURITY_ATTRIBUTES`.
+    ///
+    /// The descriptor must not be modified through the pointer.
+    #[inline]
+    pub fn as_ptr(&self) -> PSECURITY_DESCRIPTOR {
+        self.0.as_ptr().cast()
+    }
+    /// Returns the contents of the descriptor as a byte slice in the self-relative format.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            // SAFETY: self-relative descriptors are exactly GetSecurityDescriptorLength bytes long and are never
+            // mutated after creation
+            let len = GetSecurityDescriptorLength(self.as_ptr()) as usize;
+            slice::from_raw_parts(self.0.as_ptr(), len)
+        }
+    }
+}
+impl Clone for SecurityDescriptor {
+    fn clone(&self) -> Self {
+        let bytes = self.as_bytes();
+        let new = local_alloc(bytes.len()).expect("failed to allocate memory for security descriptor");
+        unsafe {
+            // SAFETY: the allocation is of the same size
+            ptr::copy_nonoverlapping(bytes.as_ptr(), new.as_ptr(), bytes.len());
+        }
+        Self(new)
+    }
+}
+impl Drop for SecurityDescriptor {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: we own the allocation
+            LocalFree(self.0.as_ptr().cast());
+        }
+    }
+}
+impl PartialEq for SecurityDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+impl Eq for SecurityDescriptor {}
+impl Hash for SecurityDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+impl Debug for SecurityDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SecurityDescriptor").field(&self.0).finish()
+    }
+}
+
+fn local_alloc(len: usize) -> io::Result<NonNull<u8>> {
+    let mem = unsafe { LocalAlloc(LMEM_FIXED, len) };
+    NonNull::new(mem.cast()).ok_or_else(io::Error::last_os_error)
+}
@@ -0,0 +1,144 @@
+use super::winprelude::*;
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::{self, Debug, Formatter},
+    io, iter,
+    mem::{size_of, zeroed},
+    ptr::{self, NonNull},
+};
+use winapi::{
+    shared::winerror::ERROR_ALREADY_EXISTS,
+    um::{
+        memoryapi::{
+            CreateFileMappingW, MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, VirtualQuery, FILE_MAP_ALL_ACCESS,
+        },
+        processthreadsapi::GetCurrentProcess,
+        winnt::{MEMORY_BASIC_INFORMATION, PAGE_READWRITE},
+    },
+};
+
+pub(crate) struct SharedMemory {
+    handle: OwnedHandle,
+    ptr: NonNull<u8>,
+    len: usize,
+}
+// SAFETY: the view is not tied to the thread it was created on, and the pointer is only handed out in raw form.
+unsafe impl Send for SharedMemory {}
+unsafe impl Sync for SharedMemory {}
+impl SharedMemory {
+    /// The object is destroyed along with its last handle, and so there is no drop guard to keep.
+    pub fn create(name: &OsStr, size: usize, _keep_drop_guard: bool) -> io::Result<Self> {
+        let name = name.encode_wide().chain(iter::once(0)).collect::<Vec<u16>>();
+        if name.len() == 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "shared memory object names must be non-empty",
+            ));
+        }
+        let handle = create_mapping(name.as_ptr(), size)?;
+        // CreateFileMappingW() opens the existing object instead of failing, leaving a note for us to find.
+        if io::Error::last_os_error().raw_os_error() == Some(ERROR_ALREADY_EXISTS as _) {
+            return Err(io::Error::from_raw_os_error(ERROR_ALREADY_EXISTS as _));
+        }
+        Self::map(handle, size)
+    }
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let name = name.encode_wide().chain(iter::once(0)).collect::<Vec<u16>>();
+        let handle = unsafe { OpenFileMappingW(FILE_MAP_ALL_ACCESS, 0, name.as_ptr()) };
+        let handle = ok_or_ret_errno!(!handle.is_null() => unsafe {
+            // SAFETY: we just opened this handle
+            OwnedHandle::from_raw_handle(handle)
+        })?;
+        Self::from_handle(handle)
+    }
+    pub fn anonymous(size: usize) -> io::Result<Self> {
+        let handle = create_mapping(ptr::null(), size)?;
+        Self::map(handle, size)
+    }
+    pub fn from_handle(handle: OwnedHandle) -> io::Result<Self> {
+        let mut mapping = Self::map(handle, 0)?;
+        let mut info: MEMORY_BASIC_INFORMATION = unsafe { zeroed() };
+        let success = unsafe {
+            VirtualQuery(
+                mapping.ptr.as_ptr().cast(),
+                &mut info,
+                size_of::<MEMORY_BASIC_INFORMATION>(),
+            ) != 0
+        };
+        mapping.len = ok_or_ret_errno!(success => info.RegionSize)?;
+        Ok(mapping)
+    }
+    /// Maps the view. A size of zero maps the whole object, the size of which is then to be queried separately.
+    fn map(handle: OwnedHandle, len: usize) -> io::Result<Self> {
+        let ptr = unsafe { MapViewOfFile(handle.as_raw_handle(), FILE_MAP_ALL_ACCESS, 0, 0, len) };
+        let ptr = ok_or_ret_errno!(!ptr.is_null() => NonNull::new(ptr.cast()).unwrap())?;
+        Ok(Self { handle, ptr, len })
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: we own the view and nothing can borrow from it past this point
+            UnmapViewOfFile(self.ptr.as_ptr().cast());
+        }
+    }
+}
+impl AsHandle for SharedMemory {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.handle.as_handle()
+    }
+}
+impl Debug for SharedMemory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedMemory")
+            .field("handle", &self.handle.as_raw_handle())
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+fn create_mapping(name: *const u16, size: usize) -> io::Result<OwnedHandle> {
+    if size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "shared memory objects cannot be empty",
+        ));
+    }
+    let size = size as u64;
+    let handle = unsafe {
+        CreateFileMappingW(
+            INVALID_HANDLE_VALUE,
+            ptr::null_mut(),
+            PAGE_READWRITE,
+            (size >> 32) as DWORD,
+            size as DWORD,
+            name,
+        )
+    };
+    ok_or_ret_errno!(!handle.is_null() => unsafe {
+        // SAFETY: we just created this handle
+        OwnedHandle::from_raw_handle(handle)
+    })
+}
+
+pub(crate) fn user_scoped_name(name: &OsStr) -> io::Result<OsString> {
+    let process = unsafe {
+        // SAFETY: the pseudo-handle of the current process is always valid and needn't be closed
+        BorrowedHandle::borrow_raw(GetCurrentProcess())
+    };
+    let mut scoped = crate::os::windows::named_pipe::user_sid(process)?;
+    scoped.push("-");
+    scoped.push(name);
+    Ok(scoped)
+}
@@ -0,0 +1,27 @@
+use super::{c_wrappers, winprelude::*};
+use std::{io, process::Command};
+
+pub(crate) type ChildEnd = OwnedHandle;
+
+/// Marks the child ends as inheritable and returns their values to be listed in the environment of the child. Handles
+/// keep their values when inherited, and so there is nothing to remap; the ends stay where they are, to be closed after
+/// the child is spawned.
+pub(crate) fn inherit_all(_command: &mut Command, ends: &mut Vec<ChildEnd>) -> io::Result<Vec<u64>> {
+    ends.iter()
+        .map(|end| {
+            c_wrappers::set_inheritable(end.as_handle(), true)?;
+            Ok(end.as_raw_handle() as usize as u64)
+        })
+        .collect()
+}
+
+/// Takes ownership of an inherited channel and makes sure that it isn't passed further down to grandchildren.
+///
+/// # Safety
+/// `raw` must be a handle inherited from the parent which isn't owned by anything else.
+pub(crate) unsafe fn adopt(raw: u64) -> io::Result<ChildEnd> {
+    let handle = usize::try_from(raw).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    let handle = unsafe { OwnedHandle::from_raw_handle(handle as RawHandle) };
+    c_wrappers::set_inheritable(handle.as_handle(), false)?;
+    Ok(handle)
+}
@@ -1,44 +1,92 @@
 //! Platform-specific functionality for unnamed pipes.
 //!
 //! Currently, this consists of only the [`UnnamedPipeCreationOptions`] builder, but more might be added.
-//!
-//! [`UnnamedPipeCreationOptions`]: struct.UnnamedPipeCreationOptions.html " "
 
 // TODO add examples
 
-use super::{c_wrappers::init_security_attributes, winprelude::*, FileHandle};
+#[cfg(feature = "tokio")]
+pub(crate) mod tokio;
+
+use super::{
+    c_wrappers::init_security_attributes,
+    named_pipe::{path_conversion, set_nonblocking_for_stream},
+    winprelude::*,
+    FileHandle, SecurityDescriptor,
+};
 use crate::{
-    unnamed_pipe::{UnnamedPipeReader as PubReader, UnnamedPipeWriter as PubWriter},
-    weaken_buf_init_mut,
+    unnamed_pipe::{
+        send_file_chunked, PipeOptions, ReadTimeout, UnnamedPipeReader as PubReader, UnnamedPipeWriter as PubWriter,
+    },
+    weaken_buf_init_mut, TryClone,
 };
 use std::{
+    collections::hash_map::RandomState,
+    ffi::OsString,
     fmt::{self, Debug, Formatter},
+    fs::File,
+    hash::{BuildHasher, Hasher},
     io::{self, Read, Write},
     num::NonZeroUsize,
-    ptr,
+    ops::Range,
+    os::windows::fs::FileExt,
+    process, ptr,
+    sync::atomic::{AtomicU32, Ordering::Relaxed},
+    thread,
+    time::{Duration, Instant},
+};
+use winapi::{
+    shared::winerror::{ERROR_ACCESS_DENIED, ERROR_BROKEN_PIPE},
+    um::{
+        fileapi::{CreateFileW, OPEN_EXISTING},
+        minwinbase::SECURITY_ATTRIBUTES,
+        namedpipeapi::{CreateNamedPipeW, CreatePipe},
+        winbase::{
+            FILE_FLAG_FIRST_PIPE_INSTANCE, FILE_FLAG_OVERLAPPED, PIPE_ACCESS_INBOUND, PIPE_READMODE_BYTE,
+            PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_WAIT,
+        },
+        winnt::{FILE_READ_ATTRIBUTES, GENERIC_WRITE},
+    },
 };
-use winapi::um::{minwinbase::SECURITY_ATTRIBUTES, namedpipeapi::CreatePipe};
 
 /// Builder used to create unnamed pipes while supplying additional options.
 ///
 /// You can use this instead of the simple [`pipe` function](crate::unnamed_pipe::pipe) to supply additional
-/// Windows-specific parameters to a pipe.
+/// Windows-specific parameters to a pipe, which are otherwise left at the defaults of `CreatePipe`.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::os::windows::{unnamed_pipe::UnnamedPipeCreationOptions, SecurityDescriptor};
+/// use std::num::NonZeroUsize;
+///
+/// // Grant full access to the owner of the pipe and nobody else.
+/// let sd = SecurityDescriptor::from_sddl("D:P(A;;GA;;;OW)")?;
+/// let (writer, reader) = UnnamedPipeCreationOptions::new()
+///     .security_descriptor(Some(sd))
+///     .buffer_size_hint(NonZeroUsize::new(64 * 1024))
+///     .build()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
 #[non_exhaustive]
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct UnnamedPipeCreationOptions {
     /// Specifies whether the resulting pipe can be inherited by child processes.
     ///
     /// The default value is `false` and you probably shouldn't modify this, unless you want all child processes to
     /// explicitly be able to use the pipe using various fishy methods to find the handle in the parent process.
     pub inheritable: bool,
-    /// A pointer to the [security descriptor] for the pipe. Leave this at the default `NULL` unless you want something
-    /// specific.
+    /// Specifies the [security descriptor] for the pipe, controlling which users can access it through its handles.
+    /// If set to `None`, the default security descriptor is used, the access control list of which comes from the
+    /// primary or impersonation token of the creator.
+    ///
+    /// Use [`SecurityDescriptor::from_sddl()`] to restrict the pipe to a specific user or SID.
     ///
     /// [security descriptor]: https://docs.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-security_descriptor " "
-    pub security_descriptor: LPVOID,
+    pub security_descriptor: Option<SecurityDescriptor>,
     /// A hint on the buffer size for the pipe. There is no way to ensure or check that the system actually uses this
     /// exact size, since it's only a hint. Set to `None` to disable the hint and rely entirely on the system's default
     /// buffer size.
+    ///
+    /// Values which don't fit into a `DWORD` are clamped to the maximum value of one.
     pub buffer_size_hint: Option<NonZeroUsize>,
 }
 impl UnnamedPipeCreationOptions {
@@ -46,7 +94,7 @@ impl UnnamedPipeCreationOptions {
     pub const fn new() -> Self {
         Self {
             inheritable: false,
-            security_descriptor: ptr::null_mut(),
+            security_descriptor: None,
             buffer_size_hint: None,
         }
     }
@@ -60,13 +108,13 @@ impl UnnamedPipeCreationOptions {
         self.inheritable = inheritable;
         self
     }
-    /// Specifies the pointer to the security descriptor for the pipe.
+    /// Specifies the security descriptor for the pipe.
     ///
     /// See the [associated field] for more.
     ///
     /// [associated field]: #structfield.security_descriptor " "
     #[must_use = "this is not an in-place operation"]
-    pub fn security_descriptor(mut self, security_descriptor: LPVOID) -> Self {
+    pub fn security_descriptor(mut self, security_descriptor: Option<SecurityDescriptor>) -> Self {
         self.security_descriptor = security_descriptor;
         self
     }
@@ -84,65 +132,124 @@ impl UnnamedPipeCreationOptions {
     /// Extracts the [`SECURITY_ATTRIBUTES`][sa] from the builder. Primarily an implementation detail, but has other
     /// uses.
     ///
+    /// The returned structure borrows the security descriptor of the builder, if there is one, and thus must not
+    /// outlive it.
+    ///
     /// [sa]: https://learn.microsoft.com/en-us/windows/win32/api/wtypesbase/ns-wtypesbase-security_attributes
-    pub fn extract_security_attributes(self) -> SECURITY_ATTRIBUTES {
+    pub fn extract_security_attributes(&self) -> SECURITY_ATTRIBUTES {
         let mut attrs = init_security_attributes();
-        attrs.lpSecurityDescriptor = self.security_descriptor;
+        if let Some(sd) = &self.security_descriptor {
+            attrs.lpSecurityDescriptor = sd.as_ptr();
+        }
         attrs.bInheritHandle = self.inheritable as i32;
         attrs
     }
 
     /// Creates the pipe and returns its writing and reading ends, or the error if one occurred.
     ///
-    /// This will fail if the [`security_descriptor`](Self.security_descriptor) field is non-null. See
-    /// [`.build_with_security_descriptor()`](Self::build_with_security_descriptor) for an unsafe version that allows
-    /// the pointer to be passed.
-    pub fn build(self) -> io::Result<(PubWriter, PubReader)> {
-        if !self.security_descriptor.is_null() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "cannot use safe .build() with security descriptor pointer",
+    /// # System calls
+    /// - `CreatePipe`
+    pub fn build(&self) -> io::Result<(PubWriter, PubReader)> {
+        let hint_raw = self.hint_raw();
+        let mut sa = self.extract_security_attributes();
+        let [mut w, mut r] = [INVALID_HANDLE_VALUE; 2];
+        let success = unsafe { CreatePipe(&mut r as *mut _, &mut w as *mut _, &mut sa as *mut _, hint_raw) } != 0;
+        ok_or_ret_errno!(success => unsafe {
+            // SAFETY: we just created those handles which means that we own them
+            let w = OwnedHandle::from_raw_handle(w);
+            let r = OwnedHandle::from_raw_handle(r);
+            (
+                PubWriter(UnnamedPipeWriter(FileHandle(w))),
+                PubReader(UnnamedPipeReader(FileHandle(r), ReadTimeout::default())),
+            )
+        })
+    }
+    /// Creates a pipe with both of its ends opened for overlapped I/O and returns the handles to its writing and
+    /// reading ends, or the error if one occurred.
+    ///
+    /// Anonymous pipes created by `CreatePipe` don't support overlapped I/O, which rules them out for use with
+    /// completion ports and the async runtimes built on top of them. This emulates them instead with a local-only
+    /// named pipe with a unique, unpredictable name, which is created with `FILE_FLAG_FIRST_PIPE_INSTANCE` and
+    /// immediately connected to; this is also how the system implements anonymous pipes to begin with. No other
+    /// process can connect to the pipe afterwards, since it has only one instance.
+    ///
+    /// Since the handles are opened with `FILE_FLAG_OVERLAPPED`, they are returned as raw owned handles rather than
+    /// as [`UnnamedPipeWriter`](PubWriter) and [`UnnamedPipeReader`](PubReader), which perform synchronous I/O.
+    ///
+    /// # System calls
+    /// - `CreateNamedPipeW`
+    /// - `CreateFileW`
+    pub fn build_overlapped(&self) -> io::Result<(OwnedHandle, OwnedHandle)> {
+        let (server, path) = self.create_overlapped_server()?;
+        let client = self.open_overlapped_client(&path)?;
+        Ok((client, server))
+    }
+    fn create_overlapped_server(&self) -> io::Result<(OwnedHandle, Vec<u16>)> {
+        const MAX_ATTEMPTS: u32 = 16;
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+        let buffer_size = self.hint_raw();
+        let mut sa = self.extract_security_attributes();
+        let mut attempt = 0;
+        loop {
+            // The random part keeps other processes from predicting the name and squatting on it in advance.
+            let name = OsString::from(format!(
+                "interprocess-unnamed-{}-{}-{:016x}",
+                process::id(),
+                COUNTER.fetch_add(1, Relaxed),
+                RandomState::new().build_hasher().finish(),
             ));
-        }
-        unsafe {
-            // SAFETY: we just checked for null
-            self.build_with_security_descriptor()
+            let path = path_conversion::convert_and_encode_path(&name, None);
+
+            let handle = unsafe {
+                CreateNamedPipeW(
+                    path.as_ptr(),
+                    PIPE_ACCESS_INBOUND | FILE_FLAG_OVERLAPPED | FILE_FLAG_FIRST_PIPE_INSTANCE,
+                    PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                    1,
+                    0,
+                    buffer_size,
+                    0,
+                    &mut sa,
+                )
+            };
+            if handle != INVALID_HANDLE_VALUE {
+                // SAFETY: we just created this handle
+                return Ok((unsafe { OwnedHandle::from_raw_handle(handle) }, path));
+            }
+            let e = io::Error::last_os_error();
+            attempt += 1;
+            // A name collision manifests as ERROR_ACCESS_DENIED due to FILE_FLAG_FIRST_PIPE_INSTANCE.
+            if e.raw_os_error() != Some(ERROR_ACCESS_DENIED as _) || attempt >= MAX_ATTEMPTS {
+                return Err(e);
+            }
         }
     }
-
-    /// Creates the pipe and returns its writing and reading ends, or the error if one occurred. Allows for a security
-    /// descriptor pointer to be passed.
-    ///
-    /// # Safety
-    /// The [`security_descriptor`](Self.security_descriptor) field is passed directly to Win32 which is then
-    /// dereferenced there, resulting in undefined behavior if it was an invalid non-null pointer. For the default
-    /// configuration, this should never be a concern.
-    pub unsafe fn build_with_security_descriptor(self) -> io::Result<(PubWriter, PubReader)> {
-        let hint_raw = match self.buffer_size_hint {
-            Some(num) => num.get(),
-            None => 0,
-        } as u32;
-        let [mut w, mut r] = [INVALID_HANDLE_VALUE; 2];
-        let success = unsafe {
-            CreatePipe(
-                &mut r as *mut _,
-                &mut w as *mut _,
-                &mut self.extract_security_attributes() as *mut _,
-                hint_raw,
+    fn open_overlapped_client(&self, path: &[u16]) -> io::Result<OwnedHandle> {
+        // The security descriptor only matters when creating the pipe, not when opening it.
+        let mut sa = init_security_attributes();
+        sa.bInheritHandle = self.inheritable as i32;
+        let handle = unsafe {
+            CreateFileW(
+                path.as_ptr(),
+                // FILE_READ_ATTRIBUTES is needed by async runtimes to query information about the pipe.
+                GENERIC_WRITE | FILE_READ_ATTRIBUTES,
+                0,
+                &mut sa,
+                OPEN_EXISTING,
+                FILE_FLAG_OVERLAPPED,
+                ptr::null_mut(),
             )
-        } != 0;
-        if success {
-            let (w, r) = unsafe {
-                // SAFETY: we just created those handles which means that we own them
-                let w = OwnedHandle::from_raw_handle(w);
-                let r = OwnedHandle::from_raw_handle(r);
-                (w, r)
-            };
-            let w = PubWriter(UnnamedPipeWriter(FileHandle(w)));
-            let r = PubReader(UnnamedPipeReader(FileHandle(r)));
-            Ok((w, r))
-        } else {
-            Err(io::Error::last_os_error())
+        };
+        ok_or_ret_errno!(handle != INVALID_HANDLE_VALUE => unsafe {
+            // SAFETY: we just created this handle
+            OwnedHandle::from_raw_handle(handle)
+        })
+    }
+    fn hint_raw(&self) -> DWORD {
+        match self.buffer_size_hint {
+            Some(num) => num.get().try_into().unwrap_or(DWORD::MAX),
+            None => 0,
         }
     }
 }
@@ -151,38 +258,149 @@ impl Default for UnnamedPipeCreationOptions {
         Self::new()
     }
 }
-unsafe impl Send for UnnamedPipeCreationOptions {}
-unsafe impl Sync for UnnamedPipeCreationOptions {}
 
 pub(crate) fn pipe() -> io::Result<(PubWriter, PubReader)> {
     UnnamedPipeCreationOptions::default().build()
 }
 
-pub(crate) struct UnnamedPipeReader(FileHandle);
-impl Read for UnnamedPipeReader {
+pub(crate) fn pipe_with_options(opts: &PipeOptions) -> io::Result<(PubWriter, PubReader)> {
+    let (w, r) = UnnamedPipeCreationOptions::new()
+        .inheritable(opts.inheritable)
+        .build()?;
+    if opts.nonblocking {
+        for handle in [w.as_handle(), r.as_handle()] {
+            // SAFETY: anonymous pipes are named pipes in byte mode under the hood
+            unsafe { set_nonblocking_for_stream(handle, None, true)? };
+        }
+    }
+    Ok((w, r))
+}
+
+pub(crate) struct UnnamedPipeReader(FileHandle, ReadTimeout);
+impl UnnamedPipeReader {
+    pub fn read_timeout(&self) -> &ReadTimeout {
+        &self.1
+    }
+    pub fn bytes_available(&self) -> io::Result<usize> {
+        match super::c_wrappers::bytes_available(self.as_handle()) {
+            // The writing end has been closed, which is reported as zero bytes on Unix.
+            Err(e) if e.raw_os_error() == Some(ERROR_BROKEN_PIPE as _) => Ok(0),
+            els => els,
+        }
+    }
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        // SAFETY: anonymous pipes are named pipes in byte mode under the hood
+        unsafe { set_nonblocking_for_stream(self.as_handle(), None, nonblocking) }
+    }
+}
+impl Read for &UnnamedPipeReader {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(timeout) = self.1.get() {
+            wait_for_data(self.as_handle(), timeout)?;
+        }
         self.0.read(weaken_buf_init_mut(buf))
     }
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        if let Some(timeout) = self.1.get() {
+            wait_for_data(self.as_handle(), timeout)?;
+        }
+        crate::read_into_cursor(cursor, |buf| self.0.read(buf))
+    }
+}
+impl Read for UnnamedPipeReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (self as &Self).read(buf)
+    }
+    #[cfg(feature = "read_buf")]
+    #[inline]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        (self as &Self).read_buf(cursor)
+    }
 }
 impl Debug for UnnamedPipeReader {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_tuple("UnnamedPipeReader")
             .field(&self.0 .0.as_raw_handle())
+            .field(&self.1.get())
             .finish()
     }
 }
-forward_handle!(UnnamedPipeReader);
-forward_try_clone!(UnnamedPipeReader);
+forward_as_handle!(windows: UnnamedPipeReader);
+forward_into_handle!(windows: UnnamedPipeReader);
+impl From<OwnedHandle> for UnnamedPipeReader {
+    #[inline]
+    fn from(handle: OwnedHandle) -> Self {
+        Self(FileHandle(handle), ReadTimeout::default())
+    }
+}
+impl TryClone for UnnamedPipeReader {
+    #[inline]
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self(self.0.try_clone()?, self.1.clone()))
+    }
+}
+
+/// Waits until the pipe has data or its writing end is closed, failing with `TimedOut` if that doesn't happen in time.
+///
+/// Anonymous pipes can't be waited on for readiness, so the pipe is polled with a gradually increasing interval.
+fn wait_for_data(handle: BorrowedHandle<'_>, timeout: Duration) -> io::Result<()> {
+    const MAX_INTERVAL: Duration = Duration::from_millis(16);
+    let Some(deadline) = Instant::now().checked_add(timeout) else {
+        // Too far into the future to ever happen.
+        return Ok(());
+    };
+    let mut interval = Duration::from_millis(1);
+    loop {
+        match super::c_wrappers::bytes_available(handle) {
+            Ok(0) => {}
+            // The read will report the data or the closure of the pipe.
+            Ok(_) => return Ok(()),
+            Err(e) if e.raw_os_error() == Some(ERROR_BROKEN_PIPE as _) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "read timed out"));
+        }
+        thread::sleep(interval.min(remaining));
+        interval = (interval * 2).min(MAX_INTERVAL);
+    }
+}
 
 pub(crate) struct UnnamedPipeWriter(FileHandle);
-impl Write for UnnamedPipeWriter {
+impl UnnamedPipeWriter {
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        // SAFETY: as above
+        unsafe { set_nonblocking_for_stream(self.as_handle(), None, nonblocking) }
+    }
+    pub fn send_file(&self, file: &File, range: Range<u64>) -> io::Result<u64> {
+        send_file_chunked(|buf, offset| file.seek_read(buf, offset), self, range)
+    }
+}
+impl Write for &UnnamedPipeWriter {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.0.write(buf)
+        match self.0.write(buf)? {
+            // In nonblocking mode, a full pipe accepts no data instead of failing.
+            0 if !buf.is_empty() => Err(io::ErrorKind::WouldBlock.into()),
+            n => Ok(n),
+        }
     }
     fn flush(&mut self) -> io::Result<()> {
         self.0.flush()
     }
 }
+impl Write for UnnamedPipeWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (self as &Self).write(buf)
+    }
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        (self as &Self).flush()
+    }
+}
 impl Debug for UnnamedPipeWriter {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_tuple("UnnamedPipeWriter")
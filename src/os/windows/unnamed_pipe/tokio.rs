@@ -0,0 +1,83 @@
+use super::{
+    super::{downgrade_poll_eof, winprelude::*},
+    UnnamedPipeCreationOptions,
+};
+use crate::unnamed_pipe::tokio::{UnnamedPipeReader as PubReader, UnnamedPipeWriter as PubWriter};
+use futures_core::ready;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::net::windows::named_pipe::{NamedPipeClient as TokioNPClient, NamedPipeServer as TokioNPServer};
+
+// Anonymous pipes don't support overlapped I/O, which is what Tokio needs to drive them, so they're emulated with a
+// named pipe instead.
+pub(crate) fn pipe() -> io::Result<(PubWriter, PubReader)> {
+    let (client, server) = UnnamedPipeCreationOptions::new().build_overlapped()?;
+    // SAFETY: both handles were just opened for overlapped I/O and are not registered anywhere else
+    let reader = unsafe { TokioNPServer::from_raw_handle(server.into_raw_handle())? };
+    let writer = unsafe { TokioNPClient::from_raw_handle(client.into_raw_handle())? };
+    Ok((
+        PubWriter(UnnamedPipeWriter(writer)),
+        PubReader(UnnamedPipeReader(reader)),
+    ))
+}
+
+pub(crate) struct UnnamedPipeReader(TokioNPServer);
+impl UnnamedPipeReader {
+    pub fn poll_read(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        downgrade_poll_eof(loop {
+            match self.0.try_read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => break Poll::Ready(els),
+            }
+            ready!(self.0.poll_read_ready(cx))?;
+        })
+    }
+    /// Reads into the unfilled part of the buffer without initializing it first.
+    pub fn poll_read_readbuf(&mut self, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        downgrade_poll_eof(Pin::new(&mut self.0).poll_read(cx, buf))
+    }
+}
+impl AsHandle for UnnamedPipeReader {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl Debug for UnnamedPipeReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UnnamedPipeReader")
+            .field(&self.0.as_raw_handle())
+            .finish()
+    }
+}
+
+pub(crate) struct UnnamedPipeWriter(TokioNPClient);
+impl UnnamedPipeWriter {
+    pub fn poll_write(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        loop {
+            match self.0.try_write(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return Poll::Ready(els),
+            }
+            ready!(self.0.poll_write_ready(cx))?;
+        }
+    }
+}
+impl AsHandle for UnnamedPipeWriter {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.0.as_handle()
+    }
+}
+impl Debug for UnnamedPipeWriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UnnamedPipeWriter")
+            .field(&self.0.as_raw_handle())
+            .finish()
+    }
+}
@@ -0,0 +1,141 @@
+//! Watching for the process on the other end of a connection to exit.
+//!
+//! A server normally learns that a client is gone when reading from its connection hits end-of-file. That doesn't
+//! happen if the client has handed a duplicate of its end of the connection to another process, such as a child it
+//! spawned, or if the server doesn't read from the connection for a long time. A [`PeerWatcher`] instead watches the
+//! peer process itself, so that per-client state can be cleaned up as soon as the client exits, regardless of what
+//! happens to the connection.
+//!
+//! The process ID of the peer is obtained from the connection:
+//! - on Unix, with [`get_peer_credentials()`](crate::os::unix::udsocket::UdSocket::get_peer_credentials) on a Unix
+//!   domain socket, which can be passed to [`PeerWatcher::from_credentials()`] directly;
+//! - on Windows, with [`client_process_id()`](crate::os::windows::named_pipe::PipeStream::client_process_id) on a
+//!   named pipe stream.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::peer_watcher::PeerWatcher;
+//! use std::{collections::HashMap, sync::{Arc, Mutex}};
+//!
+//! let sessions = Arc::new(Mutex::new(HashMap::<u32, String>::new()));
+//! # let client_pid = std::process::id();
+//! // Once a client has connected and its process ID is known:
+//! sessions.lock().unwrap().insert(client_pid, String::from("session state"));
+//! let watcher = PeerWatcher::new(client_pid)?;
+//! let sessions = Arc::clone(&sessions);
+//! watcher.on_exit(move || {
+//!     sessions.lock().unwrap().remove(&client_pid);
+//! })?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+impmod! {peer_watcher,
+    PeerWatcher as PeerWatcherImpl,
+}
+use std::{
+    fmt::{self, Debug, Formatter},
+    io, thread,
+    time::Duration,
+};
+
+/// Watches a process for it to exit.
+///
+/// # Implementation
+/// - On Linux and Android, this is a pidfd, or, on kernels older than 5.3, which lack those, a periodic check with
+///   `kill` for whether the process still exists.
+/// - On FreeBSD, DragonFly BSD, OpenBSD, NetBSD and Apple platforms, this is a kqueue with an `EVFILT_PROC` filter for
+///   the process exiting.
+/// - On other Unix-like systems, the process is checked for with `kill` every few dozen milliseconds. Since process IDs
+///   are recycled, this will miss the exit of a process if another one is given its ID in between two checks.
+/// - On Windows, this is a handle to the process, which is waited on with `WaitForSingleObject`.
+///
+/// If the process doesn't exist anymore by the time the watcher is created, the watcher reports it as having exited
+/// right away. If the process ID has already been given to a different process by then, that process is watched
+/// instead, which is why a watcher should be created while the connection to the peer is still open.
+pub struct PeerWatcher(pub(crate) PeerWatcherImpl);
+impl PeerWatcher {
+    /// Starts watching the process with the given ID.
+    ///
+    /// # System calls
+    /// - `pidfd_open` on Linux and Android
+    /// - `kqueue` and `kevent` on BSD and Apple platforms
+    /// - `kill` on other Unix-like systems
+    /// - `OpenProcess` on Windows
+    pub fn new(pid: u32) -> io::Result<Self> {
+        PeerWatcherImpl::new(pid).map(Self)
+    }
+    /// Starts watching the process which the given credentials came from, which fails with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if they don't include a process ID.
+    ///
+    /// This method is only available on platforms where Unix domain sockets can provide credentials. On other
+    /// platforms, it's absent and thus any usage of it will result in a compile-time error.
+    #[cfg_attr( // uds_credentials template
+        feature = "doc_cfg",
+        doc(cfg(any(
+            target_os = "linux",
+            target_os = "redox",
+            target_os = "android",
+            target_os = "fuchsia",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+        )))
+    )]
+    #[cfg(uds_credentials)]
+    pub fn from_credentials(credentials: &crate::os::unix::udsocket::credentials::Credentials<'_>) -> io::Result<Self> {
+        let pid = credentials
+            .pid()
+            .and_then(|pid| u32::try_from(pid).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "credentials don't include a process ID"))?;
+        Self::new(pid)
+    }
+
+    /// Returns `true` if the process has exited, without blocking.
+    pub fn has_exited(&self) -> io::Result<bool> {
+        self.0.wait_timeout(Some(Duration::ZERO))
+    }
+    /// Blocks until the process exits.
+    ///
+    /// # System calls
+    /// - `poll` on Linux, Android, BSD and Apple platforms
+    /// - `kill` on other Unix-like systems
+    /// - `WaitForSingleObject` on Windows
+    pub fn wait(&self) -> io::Result<()> {
+        self.0.wait()
+    }
+    /// Like [`wait()`](Self::wait), but gives up once the timeout runs out, returning `false` in that case.
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        self.0.wait_timeout(Some(timeout))
+    }
+    /// Asynchronously waits until the process exits.
+    ///
+    /// On Windows, the waiting is done by a thread from the blocking pool of the runtime, which keeps waiting until the
+    /// process exits even if the future is dropped.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        self.0.wait_async().await
+    }
+    /// Invokes the callback on a dedicated thread once the process exits.
+    ///
+    /// The thread lives until the process exits and there is no way to cancel it, so this is best suited for peers
+    /// which are expected to exit eventually. If waiting fails, the callback is invoked regardless, since the peer
+    /// can no longer be watched.
+    pub fn on_exit(self, callback: impl FnOnce() + Send + 'static) -> io::Result<()> {
+        thread::Builder::new()
+            .name(String::from("interprocess peer watcher"))
+            .spawn(move || {
+                let _ = self.wait();
+                callback();
+            })
+            .map(drop)
+    }
+}
+impl Debug for PeerWatcher {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
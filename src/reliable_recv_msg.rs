@@ -43,12 +43,25 @@
 //! The inner [`TryRecvResult`] reports both the size of the message and whether it fit into the buffer or not. If it
 //! didn't fit, the buffer is unaffected (unlike with `RecvResult`).
 //!
+//! ## Asynchronous receiving
+//! [`AsyncReliableRecvMsg`] is the asynchronous counterpart of [`ReliableRecvMsg`], with the same guarantees: it's
+//! implemented in terms of `poll_try_recv`, which never takes a message off the queue unless it fits into the buffer,
+//! and `poll_recv` is provided on top of it. [`AsyncReliableRecvMsgExt`], which is implemented for every type that
+//! implements the trait, turns those into the `recv` and `try_recv` futures.
+//!
+//! Both futures are cancel-safe: a message is either received in full by the poll that completes the future or left
+//! in the queue, and so dropping a future before it completes never loses a message.
+//!
 //! ## Platform support
-//! The traits are implemented for:
-//! - Named pipes on Windows (module `interprocess::os::windows::named_pipe`)
-//! - Unix domain pipes, but only on Linux (module `interprocess::os::unix::udsocket`)
-//!     - This is because only Linux provides a special flag for `recv` which returns the amount of bytes in the message
-//!       regardless of the provided buffer size when peeking.
+//! The traits are implemented on every platform, which makes whole-message receipt available to portable code:
+//! - Message-mode named pipes on Windows (module `interprocess::os::windows::named_pipe`), synchronous and Tokio-based,
+//!   both by value and by shared reference
+//! - Ud-socket datagrams on all Unix platforms (module `interprocess::os::unix::udsocket`), synchronous, Tokio-based and
+//!   async-std-based, with the asynchronous ones also implemented by shared reference
+//!     - Linux provides a special flag for `recv` which returns the amount of bytes in the message regardless of the
+//!       provided buffer size when peeking. Other Unix systems only report that a peeked-at message was truncated, and
+//!       so the message is peeked at with a buffer which grows until it fits, which costs additional system calls for
+//!       large messages.
 
 use std::{
     error::Error,
@@ -99,7 +112,7 @@ pub trait AsyncReliableRecvMsg {
     /// from the outermost `Result` is returned.
     fn poll_try_recv(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<TryRecvResult>>;
 
-    /// Polls a future that aeceives one message from the stream into the specified buffer, returning either the size of
+    /// Polls a future that receives one message from the stream into the specified buffer, returning either the size of
     /// the message written, a bigger buffer if the one provided was too small, or an error in the outermost `Result` if
     /// the operation could not be completed for OS reasons.
     fn poll_recv(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<RecvResult>> {
@@ -151,7 +164,7 @@ pub trait AsyncReliableRecvMsgExt: AsyncReliableRecvMsg {
         TryRecv(self, buf)
     }
 }
-impl<T: AsyncReliableRecvMsg> AsyncReliableRecvMsgExt for T {}
+impl<T: AsyncReliableRecvMsg + ?Sized> AsyncReliableRecvMsgExt for T {}
 
 /// Future type returned by [`.recv()`](AsyncReliableRecvMsgExt::recv).
 #[derive(Debug)]
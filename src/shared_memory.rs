@@ -0,0 +1,232 @@
+//! Shared memory, which maps the same region of physical memory into the address spaces of multiple processes.
+//!
+//! Unlike every other IPC primitive in this crate, shared memory doesn't involve the kernel in the transfer of data:
+//! once the memory is mapped, reads and writes are just as fast as with private memory. The flip side is that there is
+//! no built-in synchronization – the processes have to coordinate access to the memory on their own, typically with
+//! atomics placed inside of it, or by signalling each other through some other channel.
+//!
+//! ## Named and anonymous shared memory
+//! Named shared memory objects are created with [`SharedMemory::create()`] and can be opened by any process which knows
+//! the name with [`SharedMemory::open()`]. On Unix, the object is created with `shm_open` and persists until it's
+//! removed with [`unlink()`], even if no process has it mapped; on Windows, it's a named file mapping object, which is
+//! destroyed when the last handle to it is closed.
+//!
+//! Since the namespace of shared memory objects is shared by all users of the system, [`user_scoped_name()`] can be
+//! used to keep the names picked by different users from colliding. On Unix, named objects outlive the process which
+//! created them unless they're unlinked, which [`SharedMemory::create_with_drop_guard()`] automates.
+//!
+//! Anonymous shared memory objects, created with [`SharedMemory::anonymous()`], have no name and can only be shared by
+//! transferring their file descriptor or handle to another process – by inheritance, by sending it over a Unix domain
+//! socket or with [`ShareHandle`](crate::os::windows::ShareHandle) on Windows. The receiving process then maps it with
+//! `SharedMemory::from_fd()` on Unix or `SharedMemory::from_handle()` on Windows.
+//!
+//! ## Ring buffers
+//! [`RingSender`] and [`RingReceiver`] implement a lock-free single-producer single-consumer byte stream on top of a
+//! shared memory object, which is a high-throughput alternative to sockets and pipes for streaming data between
+//! processes on the same machine. The ends can wake each other up with [doorbells](Doorbell) rather than polling.
+//!
+//! ## Message queues
+//! [`MessageQueue`] is a bounded multi-producer multi-consumer queue of messages of up to a fixed length, which any
+//! number of processes can send to and receive from concurrently.
+//!
+//! ## Allocation
+//! [`SharedArena`] divides a shared memory object into allocations of arbitrary size, which multiple processes can make
+//! and free. Allocations are identified by [offsets](ArenaOffset) from the start of the object rather than pointers,
+//! so that they can be referred to by processes which have the object mapped at different addresses, and the amount
+//! of memory each process can hold can be capped with quotas.
+//!
+//! ## Typed views
+//! With the `zerocopy` or `bytemuck` feature enabled, [`SharedMemory`] gains methods which borrow parts of the mapping
+//! as values or slices of plain-old-data types, checking their bounds and alignment instead of leaving pointer casts to
+//! the user. Which processes get to access the memory at a given time still has to be coordinated by other means.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::shared_memory::SharedMemory;
+//!
+//! let shm = SharedMemory::create("Example", 4096)?;
+//! // The data might be changed by another process at any moment, and so it's only read through raw pointers.
+//! unsafe { shm.as_ptr().write(42) };
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+mod arena;
+mod doorbell;
+mod queue;
+mod ring;
+#[cfg(any(feature = "zerocopy", feature = "bytemuck"))]
+mod typed;
+mod util;
+pub use {arena::*, doorbell::*, queue::*, ring::*};
+
+impmod! {shared_memory,
+    SharedMemory as SharedMemoryImpl,
+    user_scoped_name as user_scoped_name_impl,
+}
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::{self, Debug, Formatter},
+    io, slice,
+};
+
+/// A shared memory object mapped into the address space of the current process.
+///
+/// The mapping is removed when the value is dropped, and so is the object itself if it's anonymous or, on Windows, if
+/// this was the last handle to it.
+///
+/// Since other processes can modify the memory at any time, it's exposed through a raw pointer, and turning it into a
+/// reference is left to the user, who knows which synchronization protocol is in use.
+pub struct SharedMemory(pub(crate) SharedMemoryImpl);
+impl SharedMemory {
+    /// Creates a new named shared memory object of the given size, filled with zeroes, and maps it.
+    ///
+    /// The name must not contain slashes (on Unix) or backslashes (on Windows), save for the namespace prefix on
+    /// Windows, such as `Global\`. An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if an
+    /// object with the same name exists already. Sizes of zero are rejected with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput).
+    ///
+    /// # System calls
+    /// - `shm_open`, `ftruncate` and `mmap` on Unix
+    /// - `CreateFileMappingW` and `MapViewOfFile` on Windows
+    ///
+    /// On Unix, the object will be left over after the value is dropped. Use
+    /// [`create_with_drop_guard()`](Self::create_with_drop_guard) to mitigate this automatically, even during panics
+    /// (if unwinding is enabled).
+    pub fn create(name: impl AsRef<OsStr>, size: usize) -> io::Result<Self> {
+        SharedMemoryImpl::create(name.as_ref(), size, false).map(Self)
+    }
+    /// Creates a new named shared memory object like [`create()`](Self::create), and installs a drop guard that will
+    /// unlink the object once the value is dropped.
+    ///
+    /// Processes which have the object mapped at that point can keep using it, but it can no longer be opened by name.
+    /// On Windows, the object is destroyed once its last handle is closed regardless, making this the same as
+    /// `create()`.
+    ///
+    /// # System calls
+    /// - `shm_open`, `ftruncate` and `mmap` on Unix, as well as `shm_unlink` during drop
+    /// - `CreateFileMappingW` and `MapViewOfFile` on Windows
+    pub fn create_with_drop_guard(name: impl AsRef<OsStr>, size: usize) -> io::Result<Self> {
+        SharedMemoryImpl::create(name.as_ref(), size, true).map(Self)
+    }
+    /// Opens an existing named shared memory object and maps all of it.
+    ///
+    /// On Windows, the size of the object can't be retrieved precisely, and so the [length](Self::len) of the mapping
+    /// is the size of the object rounded up to the page size.
+    ///
+    /// # System calls
+    /// - `shm_open`, `fstat` and `mmap` on Unix
+    /// - `OpenFileMappingW`, `MapViewOfFile` and `VirtualQuery` on Windows
+    pub fn open(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        SharedMemoryImpl::open(name.as_ref()).map(Self)
+    }
+    /// Creates a new anonymous shared memory object of the given size, filled with zeroes, and maps it.
+    ///
+    /// The file descriptor or handle of the object is not inheritable. Sizes of zero are rejected with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput).
+    ///
+    /// # System calls
+    /// - `memfd_create` on Linux and Android, `shm_open` and `shm_unlink` on other Unix platforms
+    /// - `ftruncate` and `mmap` on Unix
+    /// - `CreateFileMappingW` and `MapViewOfFile` on Windows
+    pub fn anonymous(size: usize) -> io::Result<Self> {
+        SharedMemoryImpl::anonymous(size).map(Self)
+    }
+    /// Maps a shared memory object, the file descriptor of which was received from another process, in its entirety.
+    ///
+    /// This method is only available on Unix. On other platforms, it's absent and thus any usage of it will result in a
+    /// compile-time error.
+    ///
+    /// # System calls
+    /// - `fstat`
+    /// - `mmap`
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn from_fd(fd: std::os::unix::io::OwnedFd) -> io::Result<Self> {
+        SharedMemoryImpl::from_fd(fd).map(Self)
+    }
+    /// Maps a file mapping object, the handle of which was received from another process, in its entirety.
+    ///
+    /// The [length](Self::len) of the mapping is the size of the object rounded up to the page size.
+    ///
+    /// This method is only available on Windows. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `MapViewOfFile`
+    /// - `VirtualQuery`
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    pub fn from_handle(handle: std::os::windows::io::OwnedHandle) -> io::Result<Self> {
+        SharedMemoryImpl::from_handle(handle).map(Self)
+    }
+
+    /// Returns a pointer to the start of the mapping, which is aligned to the page size of the system.
+    #[inline]
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.0.as_ptr()
+    }
+    /// Returns the size of the mapping in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Always returns `false`, since shared memory objects can't be empty. Exists only to satisfy a Clippy lint.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+    /// Borrows the contents of the mapping as a byte slice.
+    ///
+    /// # Safety
+    /// No process may modify the memory for as long as the slice is alive.
+    #[inline]
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len()) }
+    }
+    /// Mutably borrows the contents of the mapping as a byte slice.
+    ///
+    /// # Safety
+    /// No other process may access the memory for as long as the slice is alive.
+    #[inline]
+    pub unsafe fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.as_ptr(), self.len()) }
+    }
+}
+impl Debug for SharedMemory {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_as_handle!(SharedMemory);
+
+/// Prefixes the given name with an identifier of the user the current process runs as, so that processes of different
+/// users picking the same name don't end up sharing, or fighting over, the same object.
+///
+/// The identifier is the effective user ID on Unix and the security identifier of the user in string form on Windows.
+/// The result can be passed to [`SharedMemory::create()`] and [`SharedMemory::open()`]; on Windows, a namespace
+/// prefix such as `Global\` has to be prepended to the result rather than included in `name`.
+///
+/// Note that this is only a naming convention and not an access restriction – on Unix, objects are created
+/// accessible only to their owner, while on Windows, the default security descriptor of the process applies.
+///
+/// # System calls
+/// - `geteuid` on Unix
+/// - `OpenProcessToken`, `GetTokenInformation` and `ConvertSidToStringSidW` on Windows
+pub fn user_scoped_name(name: impl AsRef<OsStr>) -> io::Result<OsString> {
+    user_scoped_name_impl(name.as_ref())
+}
+
+/// Removes the name of a named shared memory object, so that it can no longer be opened. The object itself is destroyed
+/// once no process has it mapped.
+///
+/// This function is only available on Unix. On other platforms, it's absent and thus any usage of it will result in a
+/// compile-time error.
+///
+/// # System calls
+/// - `shm_unlink`
+#[cfg(unix)]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+pub fn unlink(name: impl AsRef<OsStr>) -> io::Result<()> {
+    crate::os::unix::shared_memory::unlink(name.as_ref())
+}
@@ -0,0 +1,488 @@
+use super::{util::Backoff, SharedMemory};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::size_of,
+    process,
+    sync::atomic::{
+        AtomicU32, AtomicU64,
+        Ordering::{Acquire, Relaxed, Release},
+    },
+};
+
+const MAGIC: u64 = u64::from_ne_bytes(*b"ipcarna1");
+const FREE: u32 = u32::from_ne_bytes(*b"free");
+const USED: u32 = u32::from_ne_bytes(*b"used");
+
+/// The number of processes which can have a quota at the same time.
+pub const MAX_QUOTAS: usize = 32;
+
+#[repr(C)]
+struct Header {
+    /// Set to `MAGIC` once the rest of the header and the heap are initialized.
+    magic: AtomicU64,
+    /// Held while the free list or the quotas are being looked at or modified.
+    lock: AtomicU32,
+    _pad: u32,
+    heap_end: AtomicU64,
+    /// The offset of the free block with the lowest offset, or zero if there are no free blocks.
+    free_head: AtomicU64,
+    quotas: [Quota; MAX_QUOTAS],
+}
+/// Heap blocks start here, which is the size of the header rounded up to the alignment.
+const HEAP_START: usize = (size_of::<Header>() + ALIGN - 1) & !(ALIGN - 1);
+
+#[repr(C)]
+struct Quota {
+    /// The ID of the process the quota applies to, or zero if the entry is unused.
+    pid: AtomicU32,
+    _pad: u32,
+    limit: AtomicU64,
+    used: AtomicU64,
+}
+
+/// Precedes every block of the heap, be it free or allocated.
+#[repr(C)]
+struct BlockHeader {
+    /// The size of the whole block, including the header, in bytes.
+    size: AtomicU64,
+    /// Either `FREE` or `USED`.
+    state: AtomicU32,
+    /// The ID of the process which allocated the block, if it's allocated.
+    owner: AtomicU32,
+}
+const BLOCK_HEADER_SIZE: usize = size_of::<BlockHeader>();
+/// A free block also stores the offset of the next free block, or zero if it's the last one, right after its header.
+const MIN_BLOCK_SIZE: usize = BLOCK_HEADER_SIZE + ALIGN;
+
+/// The alignment of every allocation made by a [`SharedArena`], in bytes.
+pub const ALIGN: usize = 16;
+
+/// The location of an allocation made by a [`SharedArena`], relative to the start of the shared memory object.
+///
+/// Since every process may map the same shared memory object at a different address, pointers into it are meaningless
+/// to other processes. Offsets, on the other hand, can be stored in the shared memory itself or sent to other processes
+/// and then turned back into pointers with [`SharedArena::ptr()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArenaOffset(u64);
+impl ArenaOffset {
+    /// Rebuilds an offset from its raw value, such as one read from shared memory.
+    #[inline]
+    pub const fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+    /// Returns the raw value of the offset, to be stored in shared memory or sent to another process.
+    #[inline]
+    pub const fn into_raw(self) -> u64 {
+        self.0
+    }
+}
+
+/// A memory allocator in shared memory, handing out blocks of the shared memory object to multiple processes.
+///
+/// Allocations are identified by [offsets](ArenaOffset) from the start of the shared memory object, which every
+/// process can turn into a pointer into its own mapping. Any process attached to the arena can free any allocation.
+///
+/// Free memory is kept in a list of blocks sorted by their location, from which allocations are made on a first-fit
+/// basis, and adjacent free blocks are merged together when an allocation is freed. Every allocation is aligned to
+/// [`ALIGN`] bytes and carries a header of 16 bytes.
+///
+/// The allocator state is protected by a spinlock in the shared memory object, which is held only for as long as it
+/// takes to walk the free list. A process which dies while holding it stalls the arena for all others.
+///
+/// # Quotas
+/// The amount of memory any one process can have allocated at a time can be limited with
+/// [`set_quota()`](Self::set_quota), so that one process can't starve the others. Quotas are enforced by the
+/// allocator rather than the system, and so they only stop processes which use the allocator properly.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::shared_memory::{ArenaOffset, SharedArena, SharedMemory};
+///
+/// // In one process:
+/// let shm = SharedMemory::create_with_drop_guard("Example", 1024 * 1024)?;
+/// let arena = SharedArena::create(shm)?;
+/// let offset = arena.alloc(256)?;
+/// unsafe { arena.ptr(offset).write_bytes(42, 256) };
+/// // Send offset.into_raw() to another process somehow...
+///
+/// // In another:
+/// let arena = SharedArena::open(SharedMemory::open("Example")?)?;
+/// # let raw = 0;
+/// let offset = ArenaOffset::from_raw(raw);
+/// assert_eq!(unsafe { arena.ptr(offset).read() }, 42);
+/// arena.free(offset)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SharedArena {
+    shm: SharedMemory,
+    heap_end: usize,
+}
+impl SharedArena {
+    /// Returns the size of the shared memory object needed for an arena from which at least `capacity` bytes can be
+    /// allocated at once, assuming a single allocation.
+    ///
+    /// # Panics
+    /// Panics on arithmetic overflow.
+    pub fn required_size(capacity: usize) -> usize {
+        capacity
+            .checked_add(ALIGN - 1)
+            .map(|c| c & !(ALIGN - 1))
+            .and_then(|c| c.max(ALIGN).checked_add(HEAP_START + BLOCK_HEADER_SIZE))
+            .expect("shared arena size overflow")
+    }
+    /// Initializes a new arena, with all of the shared memory object past the allocator state available for
+    /// allocation, overwriting its previous contents.
+    ///
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if the object is too small to hold
+    /// even a single allocation.
+    pub fn create(shm: SharedMemory) -> io::Result<Self> {
+        // Blocks can't straddle the end of the heap, which is thus rounded down.
+        let heap_end = shm.len() & !(ALIGN - 1);
+        if heap_end < HEAP_START + MIN_BLOCK_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "shared memory object too small to hold the arena",
+            ));
+        }
+        let arena = Self { shm, heap_end };
+        let header = arena.header();
+        header.lock.store(0, Relaxed);
+        header.heap_end.store(heap_end as u64, Relaxed);
+        header.free_head.store(HEAP_START as u64, Relaxed);
+        for quota in &header.quotas {
+            quota.pid.store(0, Relaxed);
+            quota.limit.store(0, Relaxed);
+            quota.used.store(0, Relaxed);
+        }
+        let block = arena.block(HEAP_START);
+        block.size.store((heap_end - HEAP_START) as u64, Relaxed);
+        block.state.store(FREE, Relaxed);
+        block.owner.store(0, Relaxed);
+        arena.next_free(HEAP_START).store(0, Relaxed);
+        header.magic.store(MAGIC, Release);
+        Ok(arena)
+    }
+    /// Attaches to an arena which has been initialized with [`create()`](Self::create), possibly by another process.
+    ///
+    /// An error of kind [`InvalidData`](io::ErrorKind::InvalidData) is returned if the shared memory object doesn't
+    /// contain an initialized arena.
+    pub fn open(shm: SharedMemory) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory object does not contain an initialized arena",
+            )
+        };
+        if shm.len() < HEAP_START + MIN_BLOCK_SIZE {
+            return Err(invalid());
+        }
+        let header = unsafe { &*shm.as_ptr().cast::<Header>() };
+        if header.magic.load(Acquire) != MAGIC {
+            return Err(invalid());
+        }
+        // The other process could be malicious, and so nothing read from the header can be trusted.
+        let heap_end = usize::try_from(header.heap_end.load(Relaxed)).map_err(|_| invalid())?;
+        if heap_end > shm.len() || heap_end < HEAP_START + MIN_BLOCK_SIZE || heap_end % ALIGN != 0 {
+            return Err(invalid());
+        }
+        Ok(Self { shm, heap_end })
+    }
+
+    /// Returns the number of bytes in the heap, which is an upper bound for the total size of all allocations.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.heap_end - HEAP_START
+    }
+    /// Borrows the shared memory object the arena resides in, in order to share it with another process.
+    #[inline]
+    pub fn shared_memory(&self) -> &SharedMemory {
+        &self.shm
+    }
+    /// Returns a pointer to the allocation at the given offset in the mapping of the current process, which is aligned
+    /// to [`ALIGN`] bytes.
+    ///
+    /// The offset isn't checked to be that of an allocation that hasn't been freed yet, and so the pointer is only
+    /// valid to dereference if that's known by other means.
+    ///
+    /// # Panics
+    /// Panics if the offset is outside of the shared memory object.
+    #[inline]
+    pub fn ptr(&self, offset: ArenaOffset) -> *mut u8 {
+        let offset = usize::try_from(offset.0)
+            .ok()
+            .filter(|&o| o < self.heap_end)
+            .expect("arena offset out of bounds");
+        unsafe { self.shm.as_ptr().add(offset) }
+    }
+    /// Returns the offset of the given pointer into the mapping of the current process, or `None` if it points outside
+    /// of the heap.
+    pub fn offset_of(&self, ptr: *const u8) -> Option<ArenaOffset> {
+        let offset = (ptr as usize).checked_sub(self.shm.as_ptr() as usize)?;
+        (HEAP_START..self.heap_end)
+            .contains(&offset)
+            .then_some(ArenaOffset(offset as u64))
+    }
+
+    /// Allocates `size` bytes and returns the offset of the allocation, charging it to the quota of the current process
+    /// if it has one.
+    ///
+    /// An error of kind [`OutOfMemory`](io::ErrorKind::OutOfMemory) is returned if there is no free block large enough
+    /// or if the allocation would exceed the quota. The contents of the allocation are unspecified.
+    pub fn alloc(&self, size: usize) -> io::Result<ArenaOffset> {
+        let needed = size
+            .checked_add(BLOCK_HEADER_SIZE + ALIGN - 1)
+            .map(|s| (s & !(ALIGN - 1)).max(MIN_BLOCK_SIZE))
+            .filter(|&s| s <= self.capacity())
+            .ok_or_else(out_of_memory)?;
+        let pid = process::id();
+        let _guard = self.lock();
+
+        let header = self.header();
+        let mut prev = None;
+        let mut cur = self.checked_block_offset(header.free_head.load(Relaxed))?;
+        let mut steps = 0;
+        let (offset, block_size, next) = loop {
+            let Some(offset) = cur else { return Err(out_of_memory()) };
+            let block_size = self.checked_block_size(offset)?;
+            let next = self.checked_block_offset(self.next_free(offset).load(Relaxed))?;
+            if block_size >= needed {
+                break (offset, block_size, next);
+            }
+            steps += 1;
+            if steps > self.capacity() / MIN_BLOCK_SIZE {
+                return Err(corrupted());
+            }
+            prev = Some(offset);
+            cur = next;
+        };
+        // Blocks which aren't worth splitting are given to the allocation whole.
+        let split = block_size - needed >= MIN_BLOCK_SIZE;
+        let size = if split { needed } else { block_size };
+
+        let quota = self.quota(pid);
+        if let Some(quota) = quota {
+            if quota.used.load(Relaxed).saturating_add(size as u64) > quota.limit.load(Relaxed) {
+                return Err(io::Error::new(
+                    io::ErrorKind::OutOfMemory,
+                    "allocation would exceed the quota of the process",
+                ));
+            }
+            quota.used.fetch_add(size as u64, Relaxed);
+        }
+        let block = if split {
+            // Carved from the end, so that the free block stays where it is in the list.
+            self.block(offset).size.store((block_size - needed) as u64, Relaxed);
+            offset + block_size - needed
+        } else {
+            let link = next.unwrap_or(0) as u64;
+            match prev {
+                Some(prev) => self.next_free(prev).store(link, Relaxed),
+                None => header.free_head.store(link, Relaxed),
+            }
+            offset
+        };
+        let allocated = self.block(block);
+        allocated.size.store(size as u64, Relaxed);
+        allocated.state.store(USED, Relaxed);
+        allocated.owner.store(pid, Relaxed);
+        Ok(ArenaOffset((block + BLOCK_HEADER_SIZE) as u64))
+    }
+    /// Frees the allocation at the given offset, which may have been made by any process, crediting its size back to
+    /// the quota of the process which made it.
+    ///
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if the offset isn't that of an
+    /// allocation, including if it has been freed already.
+    pub fn free(&self, offset: ArenaOffset) -> io::Result<()> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "offset is not that of an allocation");
+        let offset = usize::try_from(offset.0)
+            .ok()
+            .and_then(|o| o.checked_sub(BLOCK_HEADER_SIZE))
+            .filter(|&o| o >= HEAP_START && o % ALIGN == 0 && o + MIN_BLOCK_SIZE <= self.heap_end)
+            .ok_or_else(invalid)?;
+        let _guard = self.lock();
+        let block = self.block(offset);
+        if block.state.load(Relaxed) != USED {
+            return Err(invalid());
+        }
+        let size = self.checked_block_size(offset)?;
+        if let Some(quota) = self.quota(block.owner.load(Relaxed)) {
+            let used = quota.used.load(Relaxed);
+            quota.used.store(used.saturating_sub(size as u64), Relaxed);
+        }
+        block.state.store(FREE, Relaxed);
+        block.owner.store(0, Relaxed);
+
+        // Finds the free blocks right before and right after this one.
+        let header = self.header();
+        let mut prev = None;
+        let mut next = self.checked_block_offset(header.free_head.load(Relaxed))?;
+        let mut steps = 0;
+        while let Some(cur) = next.filter(|&cur| cur < offset) {
+            steps += 1;
+            if steps > self.capacity() / MIN_BLOCK_SIZE {
+                return Err(corrupted());
+            }
+            prev = Some(cur);
+            next = self.checked_block_offset(self.next_free(cur).load(Relaxed))?;
+        }
+
+        // Merges with the next block if they're adjacent, then links into the list.
+        let mut size = size;
+        let mut link = next.unwrap_or(0) as u64;
+        if let Some(next) = next.filter(|&next| next == offset + size) {
+            size += self.checked_block_size(next)?;
+            link = self.next_free(next).load(Relaxed);
+        }
+        match prev {
+            Some(prev) if prev + self.checked_block_size(prev)? == offset => {
+                // Merges into the previous block instead, which is already linked.
+                self.block(prev).size.fetch_add(size as u64, Relaxed);
+                self.next_free(prev).store(link, Relaxed);
+            }
+            _ => {
+                block.size.store(size as u64, Relaxed);
+                self.next_free(offset).store(link, Relaxed);
+                match prev {
+                    Some(prev) => self.next_free(prev).store(offset as u64, Relaxed),
+                    None => header.free_head.store(offset as u64, Relaxed),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Limits the amount of memory that the process with the given ID can have allocated at a time, or lifts the limit
+    /// if `limit` is `None`.
+    ///
+    /// The amount counts the headers of the allocations, and so is slightly larger than the sum of their sizes. If the
+    /// process already has more memory allocated than the new limit, it can't allocate any more until it frees enough,
+    /// but none of its allocations are taken away.
+    ///
+    /// Up to [`MAX_QUOTAS`] processes can have a quota at once; an error of kind [`Other`](io::ErrorKind::Other) is
+    /// returned if that many already do.
+    pub fn set_quota(&self, pid: u32, limit: Option<usize>) -> io::Result<()> {
+        if pid == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "process ID of zero"));
+        }
+        let _guard = self.lock();
+        let Some(limit) = limit else {
+            if let Some(quota) = self.quota(pid) {
+                quota.pid.store(0, Relaxed);
+            }
+            return Ok(());
+        };
+        let quota = match self.quota(pid) {
+            Some(quota) => quota,
+            None => {
+                let quota = self
+                    .header()
+                    .quotas
+                    .iter()
+                    .find(|q| q.pid.load(Relaxed) == 0)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "too many processes have a quota"))?;
+                // Allocations made before the quota was set count towards it all the same.
+                quota.used.store(self.allocated_by(pid)? as u64, Relaxed);
+                quota.pid.store(pid, Relaxed);
+                quota
+            }
+        };
+        quota.limit.store(limit as u64, Relaxed);
+        Ok(())
+    }
+    /// Returns the amount of memory that the process with the given ID has allocated, counting the headers of the
+    /// allocations.
+    ///
+    /// This walks the whole heap, unless the process has a quota.
+    pub fn usage(&self, pid: u32) -> io::Result<usize> {
+        let _guard = self.lock();
+        match self.quota(pid) {
+            Some(quota) => Ok(quota.used.load(Relaxed) as usize),
+            None => self.allocated_by(pid),
+        }
+    }
+
+    #[inline]
+    fn header(&self) -> &Header {
+        // SAFETY: the mapping is page-aligned and large enough, and the header consists of atomics only, which makes
+        // it fine for other processes to modify it
+        unsafe { &*self.shm.as_ptr().cast::<Header>() }
+    }
+    #[inline]
+    fn block(&self, offset: usize) -> &BlockHeader {
+        // SAFETY: as with the header, and offsets are checked to be aligned and within the heap before getting here
+        unsafe { &*self.shm.as_ptr().add(offset).cast::<BlockHeader>() }
+    }
+    #[inline]
+    fn next_free(&self, offset: usize) -> &AtomicU64 {
+        unsafe { &*self.shm.as_ptr().add(offset + BLOCK_HEADER_SIZE).cast::<AtomicU64>() }
+    }
+    fn quota(&self, pid: u32) -> Option<&Quota> {
+        self.header().quotas.iter().find(|q| q.pid.load(Relaxed) == pid)
+    }
+    /// Sums up the sizes of the blocks allocated by the given process by walking the heap.
+    fn allocated_by(&self, pid: u32) -> io::Result<usize> {
+        let mut total = 0;
+        let mut offset = HEAP_START;
+        while offset < self.heap_end {
+            let size = self.checked_block_size(offset)?;
+            let block = self.block(offset);
+            if block.state.load(Relaxed) == USED && block.owner.load(Relaxed) == pid {
+                total += size;
+            }
+            offset += size;
+        }
+        Ok(total)
+    }
+
+    /// Checks an offset read from the free list, turning zero into `None`.
+    fn checked_block_offset(&self, offset: u64) -> io::Result<Option<usize>> {
+        if offset == 0 {
+            return Ok(None);
+        }
+        usize::try_from(offset)
+            .ok()
+            .filter(|&o| o >= HEAP_START && o % ALIGN == 0 && o + MIN_BLOCK_SIZE <= self.heap_end)
+            .map(Some)
+            .ok_or_else(corrupted)
+    }
+    /// Reads the size of a block, checking that it doesn't extend past the end of the heap.
+    fn checked_block_size(&self, offset: usize) -> io::Result<usize> {
+        usize::try_from(self.block(offset).size.load(Relaxed))
+            .ok()
+            .filter(|&s| s >= MIN_BLOCK_SIZE && s % ALIGN == 0 && s <= self.heap_end - offset)
+            .ok_or_else(corrupted)
+    }
+
+    fn lock(&self) -> LockGuard<'_> {
+        let lock = &self.header().lock;
+        let mut backoff = Backoff::default();
+        while lock.compare_exchange_weak(0, 1, Acquire, Relaxed).is_err() {
+            backoff.snooze();
+        }
+        LockGuard(lock)
+    }
+}
+impl Debug for SharedArena {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedArena")
+            .field("shm", &self.shm)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+struct LockGuard<'a>(&'a AtomicU32);
+impl Drop for LockGuard<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.store(0, Release);
+    }
+}
+
+fn out_of_memory() -> io::Error {
+    io::Error::new(io::ErrorKind::OutOfMemory, "no free block in the arena is large enough")
+}
+fn corrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "shared arena is corrupted")
+}
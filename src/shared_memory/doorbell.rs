@@ -0,0 +1,98 @@
+impmod! {doorbell,
+    Doorbell as DoorbellImpl,
+}
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+/// A cross-process wakeup primitive, used to put a [ring buffer](super::RingSender) end to sleep until the other end
+/// makes progress.
+///
+/// A doorbell is either ringing or silent. Ringing it when it's already ringing does nothing, and a successful wait
+/// silences it again, so that every ring wakes up at most one waiter. Rings are never lost: ringing a doorbell nobody
+/// is waiting on makes the next wait return immediately.
+///
+/// On Linux and Android, doorbells are eventfds, and other Unix-like systems use a self-pipe instead; on Windows, they're
+/// auto-reset event objects. None of those have a name, so doorbells are shared with other processes the same way as
+/// [anonymous shared memory](super::SharedMemory::anonymous). The two file descriptors of a self-pipe survive `fork()`,
+/// but there's no way to extract them, and thus doorbells can't be converted to or from file descriptors on those
+/// platforms.
+///
+/// # Asynchronous waiting
+/// With the `tokio` feature enabled, [`wait_async()`](Self::wait_async) waits for the doorbell within the Tokio
+/// runtime. With the `async-io` feature enabled, [`wait_async_io()`](Self::wait_async_io) does the same on the reactor
+/// of `async-io`, which makes doorbells usable with async-std, smol and other runtimes built on it.
+pub struct Doorbell(pub(crate) DoorbellImpl);
+impl Doorbell {
+    /// Creates a new silent doorbell. Its file descriptor or handle is not inheritable.
+    ///
+    /// # System calls
+    /// - `eventfd` on Linux and Android
+    /// - `pipe2` or `pipe` on other Unix-like systems
+    /// - `CreateEventW` on Windows
+    pub fn new() -> io::Result<Self> {
+        DoorbellImpl::new().map(Self)
+    }
+    /// Rings the doorbell, waking up a thread waiting on it, if there is one.
+    ///
+    /// # System calls
+    /// - `write` on Unix
+    /// - `SetEvent` on Windows
+    pub fn ring(&self) -> io::Result<()> {
+        self.0.ring()
+    }
+    /// Blocks until the doorbell rings, then silences it.
+    ///
+    /// # System calls
+    /// - `poll` and `read` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn wait(&self) -> io::Result<()> {
+        self.0.wait()
+    }
+    /// Like [`wait()`](Self::wait), but gives up once the timeout runs out, returning `false` in that case.
+    ///
+    /// # System calls
+    /// - `poll` and `read` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        self.0.wait_timeout(Some(timeout))
+    }
+    /// Asynchronously waits until the doorbell rings, then silences it.
+    ///
+    /// On Linux and Android, the doorbell is registered in the Tokio event loop for the duration of the wait. On
+    /// Windows, where event objects can't be waited on by the event loop, a thread from the blocking pool of the runtime
+    /// does the waiting; if the future is dropped before the doorbell rings, that thread keeps waiting until it does.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        self.0.wait_async().await
+    }
+    /// Asynchronously waits until the doorbell rings on the reactor of `async-io`, then silences it.
+    ///
+    /// The doorbell is registered in the reactor for the duration of the wait, and any executor can drive the future.
+    ///
+    /// This method is only available on Unix with the `async-io` feature enabled. On Windows, where event objects can't
+    /// be waited on by the reactor, it's absent and thus any usage of it will result in a compile-time error.
+    #[cfg(all(unix, feature = "async-io"))]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(all(unix, feature = "async-io"))))]
+    pub async fn wait_async_io(&self) -> io::Result<()> {
+        self.0.wait_async_io().await
+    }
+}
+impl Debug for Doorbell {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_handle!(windows: Doorbell);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+forward_handle!(unix: Doorbell);
+forward_try_clone!(Doorbell);
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+derive_raw!(Doorbell);
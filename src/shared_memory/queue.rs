@@ -0,0 +1,463 @@
+use super::Doorbell;
+use super::{
+    util::{Backoff, CachePadded},
+    SharedMemory,
+};
+use std::sync::atomic::{fence, Ordering::SeqCst};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::size_of,
+    ptr,
+    sync::atomic::{
+        AtomicU32, AtomicU64,
+        Ordering::{Acquire, Relaxed, Release},
+    },
+};
+
+const MAGIC: u64 = u64::from_ne_bytes(*b"ipcmpmc1");
+
+#[repr(C)]
+struct Header {
+    /// Set to `MAGIC` once the rest of the header and the slots are initialized.
+    magic: AtomicU64,
+    capacity: AtomicU64,
+    max_message_len: AtomicU64,
+    /// The number of senders waiting on the doorbell for a slot to free up.
+    senders_waiting: AtomicU32,
+    /// The number of receivers waiting on the doorbell for a message to arrive.
+    receivers_waiting: AtomicU32,
+    /// The position of the next slot to be claimed by a sender.
+    enqueue_pos: CachePadded<AtomicU64>,
+    /// The position of the next slot to be claimed by a receiver.
+    dequeue_pos: CachePadded<AtomicU64>,
+}
+const HEADER_SIZE: usize = size_of::<Header>();
+
+/// Precedes the message in every slot.
+#[repr(C)]
+struct SlotHeader {
+    /// Equal to the position of the slot when it's free for a sender to claim, and to the position plus one when it
+    /// holds a message for a receiver to claim.
+    seq: AtomicU64,
+    len: AtomicU64,
+}
+
+/// A bounded multi-producer multi-consumer message queue in shared memory.
+///
+/// The queue consists of a fixed number of slots, each of which holds one message of up to a fixed maximum length. Any
+/// number of threads and processes can send and receive messages concurrently through handles attached to the same
+/// shared memory object. Messages are received in the order in which they were sent, and each message is received
+/// exactly once, by whichever receiver claims it first.
+///
+/// The queue is lock-free: senders and receivers claim slots by advancing atomic positions, and every slot carries a
+/// sequence counter that tells whether it's free or full. One consequence of this is that a process which dies between
+/// claiming a slot and filling or emptying it stalls the queue once the positions wrap around to that slot.
+///
+/// The queue has no notion of disconnection, since there's no single owner of either end. A [`SharedMemory`] object
+/// with a drop guard, or a message agreed upon as a shutdown signal, can be used for that instead.
+///
+/// # Waiting
+/// [`send()`](Self::send) blocks while the queue is full, and [`recv()`](Self::recv) blocks while it's empty. By
+/// default, this is done by spinning and then sleeping for progressively longer, up to 16 milliseconds. Alternatively,
+/// all handles can be given the same pair of [doorbells](Doorbell) with `with_doorbells()`, which they then sleep on
+/// after a brief period of spinning, and use to wake each other up.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::shared_memory::{MessageQueue, SharedMemory};
+///
+/// // In one process:
+/// let shm = SharedMemory::create_with_drop_guard("Example", MessageQueue::required_size(64, 256))?;
+/// let queue = MessageQueue::create(shm, 64, 256)?;
+/// queue.send(b"Hello from one process!")?;
+///
+/// // In any number of others:
+/// let queue = MessageQueue::open(SharedMemory::open("Example")?)?;
+/// let mut buf = [0; 256];
+/// let len = queue.recv(&mut buf)?;
+/// assert_eq!(&buf[..len], b"Hello from one process!");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct MessageQueue {
+    shm: SharedMemory,
+    capacity: usize,
+    max_message_len: usize,
+    slot_size: usize,
+    /// The doorbell rung when a message is sent, and the one rung when a message is received.
+    doorbells: Option<(Doorbell, Doorbell)>,
+}
+impl MessageQueue {
+    /// Returns the size of the shared memory object needed to hold a queue of `capacity` messages, each up to
+    /// `max_message_len` bytes long.
+    ///
+    /// # Panics
+    /// Panics on arithmetic overflow.
+    pub fn required_size(capacity: usize, max_message_len: usize) -> usize {
+        checked_slot_size(max_message_len)
+            .and_then(|slot| slot.checked_mul(capacity))
+            .and_then(|slots| slots.checked_add(HEADER_SIZE))
+            .expect("message queue size overflow")
+    }
+    /// Initializes a new, empty queue of `capacity` messages, each up to `max_message_len` bytes long, in the given
+    /// shared memory object, overwriting its previous contents.
+    ///
+    /// The capacity must be a power of two, and the object must be at least
+    /// [`required_size(capacity, max_message_len)`](Self::required_size) bytes in size; otherwise, an error of kind
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) is returned.
+    pub fn create(shm: SharedMemory, capacity: usize, max_message_len: usize) -> io::Result<Self> {
+        if !capacity.is_power_of_two() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "message queue capacity must be a power of two",
+            ));
+        }
+        if max_message_len == 0 || fits(shm.len(), capacity, max_message_len) != Some(true) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "shared memory object too small to hold the message queue",
+            ));
+        }
+        let queue = Self::new(shm, capacity, max_message_len);
+        let header = queue.header();
+        header.capacity.store(capacity as u64, Relaxed);
+        header.max_message_len.store(max_message_len as u64, Relaxed);
+        header.senders_waiting.store(0, Relaxed);
+        header.receivers_waiting.store(0, Relaxed);
+        header.enqueue_pos.0.store(0, Relaxed);
+        header.dequeue_pos.0.store(0, Relaxed);
+        for i in 0..capacity {
+            let slot = queue.slot(i as u64);
+            slot.seq.store(i as u64, Relaxed);
+            slot.len.store(0, Relaxed);
+        }
+        header.magic.store(MAGIC, Release);
+        Ok(queue)
+    }
+    /// Attaches to a queue which has been initialized with [`create()`](Self::create), possibly by another process.
+    ///
+    /// An error of kind [`InvalidData`](io::ErrorKind::InvalidData) is returned if the shared memory object doesn't
+    /// contain an initialized queue.
+    pub fn open(shm: SharedMemory) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory object does not contain an initialized message queue",
+            )
+        };
+        if shm.len() < HEADER_SIZE {
+            return Err(invalid());
+        }
+        let header = unsafe { &*shm.as_ptr().cast::<Header>() };
+        if header.magic.load(Acquire) != MAGIC {
+            return Err(invalid());
+        }
+        // The other process could be malicious, and so nothing read from the header can be trusted.
+        let capacity = usize::try_from(header.capacity.load(Relaxed)).map_err(|_| invalid())?;
+        let max_message_len = usize::try_from(header.max_message_len.load(Relaxed)).map_err(|_| invalid())?;
+        if !capacity.is_power_of_two()
+            || max_message_len == 0
+            || fits(shm.len(), capacity, max_message_len) != Some(true)
+        {
+            return Err(invalid());
+        }
+        Ok(Self::new(shm, capacity, max_message_len))
+    }
+    fn new(shm: SharedMemory, capacity: usize, max_message_len: usize) -> Self {
+        Self {
+            shm,
+            capacity,
+            max_message_len,
+            slot_size: slot_size(max_message_len),
+            doorbells: None,
+        }
+    }
+    /// Makes the handle ring `message_sent` after sending a message and `message_received` after receiving one, while
+    /// waiting on the latter when the queue is full and on the former when it's empty.
+    ///
+    /// All handles attached to the queue have to be given the same doorbells (or duplicates of their file descriptors
+    /// or handles) in the same order, or they will miss each other's wakeups.
+    pub fn with_doorbells(mut self, message_sent: Doorbell, message_received: Doorbell) -> Self {
+        self.doorbells = Some((message_sent, message_received));
+        self
+    }
+
+    /// Returns the number of messages the queue can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns the maximum length of a message in bytes.
+    #[inline]
+    pub fn max_message_len(&self) -> usize {
+        self.max_message_len
+    }
+    /// Borrows the shared memory object the queue resides in, in order to share it with another process.
+    #[inline]
+    pub fn shared_memory(&self) -> &SharedMemory {
+        &self.shm
+    }
+
+    /// Sends a message without waiting, failing with [`WouldBlock`](io::ErrorKind::WouldBlock) if the queue is full.
+    ///
+    /// Messages longer than the [maximum length](Self::max_message_len) are rejected with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput).
+    pub fn try_send(&self, msg: &[u8]) -> io::Result<()> {
+        if msg.len() > self.max_message_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "message exceeds the maximum length",
+            ));
+        }
+        let header = self.header();
+        let mut pos = header.enqueue_pos.0.load(Relaxed);
+        let slot = loop {
+            let slot = self.slot(pos);
+            match (slot.seq.load(Acquire) as i64).wrapping_sub(pos as i64) {
+                0 => match header
+                    .enqueue_pos
+                    .0
+                    .compare_exchange_weak(pos, pos.wrapping_add(1), Relaxed, Relaxed)
+                {
+                    Ok(..) => break slot,
+                    Err(actual) => pos = actual,
+                },
+                // The slot still holds the message from the previous lap.
+                d if d < 0 => return Err(io::Error::from(io::ErrorKind::WouldBlock)),
+                // Another sender claimed the slot in the meantime.
+                _ => pos = header.enqueue_pos.0.load(Relaxed),
+            }
+        };
+        unsafe {
+            // SAFETY: the slot was claimed by us, and the message fits into it
+            ptr::copy_nonoverlapping(msg.as_ptr(), self.payload(pos), msg.len());
+        }
+        slot.len.store(msg.len() as u64, Relaxed);
+        slot.seq.store(pos.wrapping_add(1), Release);
+        self.notify(true)
+    }
+    /// Receives a message into `buf` without waiting and returns its length, failing with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) if the queue is empty.
+    ///
+    /// `buf` must be able to hold a message of the [maximum length](Self::max_message_len), or an error of kind
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) is returned without receiving anything.
+    pub fn try_recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.len() < self.max_message_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "buffer is smaller than the maximum message length",
+            ));
+        }
+        let header = self.header();
+        let mut pos = header.dequeue_pos.0.load(Relaxed);
+        let slot = loop {
+            let slot = self.slot(pos);
+            match (slot.seq.load(Acquire) as i64).wrapping_sub(pos.wrapping_add(1) as i64) {
+                0 => match header
+                    .dequeue_pos
+                    .0
+                    .compare_exchange_weak(pos, pos.wrapping_add(1), Relaxed, Relaxed)
+                {
+                    Ok(..) => break slot,
+                    Err(actual) => pos = actual,
+                },
+                // The slot hasn't been filled yet.
+                d if d < 0 => return Err(io::Error::from(io::ErrorKind::WouldBlock)),
+                // Another receiver claimed the slot in the meantime.
+                _ => pos = header.dequeue_pos.0.load(Relaxed),
+            }
+        };
+        let len = slot.len.load(Relaxed);
+        let result = if len <= self.max_message_len as u64 {
+            let len = len as usize;
+            unsafe {
+                // SAFETY: the slot was claimed by us, and the length was checked
+                ptr::copy_nonoverlapping(self.payload(pos), buf.as_mut_ptr(), len);
+            }
+            Ok(len)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "message queue slot is corrupted",
+            ))
+        };
+        // The slot is released either way, so that a corrupted message doesn't stall the queue.
+        slot.seq.store(pos.wrapping_add(self.capacity as u64), Release);
+        self.notify(false)?;
+        result
+    }
+    /// Sends a message, waiting for a slot to free up if the queue is full.
+    ///
+    /// See [`try_send()`](Self::try_send) for more.
+    pub fn send(&self, msg: &[u8]) -> io::Result<()> {
+        self.block_on(true, || self.try_send(msg))
+    }
+    /// Receives a message into `buf` and returns its length, waiting for one to arrive if the queue is empty.
+    ///
+    /// See [`try_recv()`](Self::try_recv) for more.
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.block_on(false, || self.try_recv(buf))
+    }
+    /// Asynchronously sends a message, waiting for a slot to free up if the queue is full.
+    ///
+    /// If the handle has doorbells, the wait is done with [`Doorbell::wait_async()`]; otherwise, the task yields to the
+    /// runtime and then sleeps for progressively longer.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn send_async(&self, msg: &[u8]) -> io::Result<()> {
+        self.block_on_async(true, || self.try_send(msg)).await
+    }
+    /// Asynchronously receives a message into `buf` and returns its length, waiting for one to arrive if the queue is
+    /// empty.
+    ///
+    /// See [`send_async()`](Self::send_async) for how the waiting is done.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn recv_async(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.block_on_async(false, || self.try_recv(buf)).await
+    }
+
+    #[inline]
+    fn header(&self) -> &Header {
+        // SAFETY: the mapping is page-aligned and large enough, and the header consists of atomics only, which makes
+        // it fine for other processes to modify it
+        unsafe { &*self.shm.as_ptr().cast::<Header>() }
+    }
+    #[inline]
+    fn slot_ptr(&self, pos: u64) -> *mut u8 {
+        let index = pos as usize & (self.capacity - 1);
+        unsafe { self.shm.as_ptr().add(HEADER_SIZE + index * self.slot_size) }
+    }
+    #[inline]
+    fn slot(&self, pos: u64) -> &SlotHeader {
+        // SAFETY: as with the header, and slots are aligned to the size of the cache line
+        unsafe { &*self.slot_ptr(pos).cast::<SlotHeader>() }
+    }
+    #[inline]
+    fn payload(&self, pos: u64) -> *mut u8 {
+        unsafe { self.slot_ptr(pos).add(size_of::<SlotHeader>()) }
+    }
+
+    /// Returns the number of handles waiting for a message to arrive (if `sent`) or for a slot to free up, and the
+    /// doorbell to ring to wake them up.
+    fn waiters(&self, sent: bool) -> Option<(&AtomicU32, &Doorbell)> {
+        let (message_sent, message_received) = self.doorbells.as_ref()?;
+        let header = self.header();
+        Some(if sent {
+            (&header.receivers_waiting, message_sent)
+        } else {
+            (&header.senders_waiting, message_received)
+        })
+    }
+    /// Wakes up a waiter after a message was sent (if `sent`) or received.
+    fn notify(&self, sent: bool) -> io::Result<()> {
+        if let Some((waiting, doorbell)) = self.waiters(sent) {
+            // Pairs with the SeqCst increment of the waiter count in block_on() and the Acquire load of the slot
+            // sequence number in the retry after it: either the waiter sees our change before going to sleep, or we
+            // see that it's waiting and wake it up.
+            fence(SeqCst);
+            if waiting.load(Relaxed) != 0 {
+                doorbell.ring()?;
+            }
+        }
+        Ok(())
+    }
+    /// Retries `op` until it stops failing with `WouldBlock`, backing off in between and eventually sleeping on the
+    /// doorbell if there is one. `sending` selects which doorbell is waited on.
+    fn block_on<T>(&self, sending: bool, mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut backoff = Backoff::default();
+        loop {
+            match op() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return els,
+            }
+            if let Some((waiting, doorbell)) = self.waiters(!sending).filter(|_| backoff.should_park()) {
+                let waiter = Waiter::new(waiting);
+                match op() {
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    els => return els,
+                }
+                let result = doorbell.wait();
+                drop(waiter);
+                result?;
+                // Several rings in a row only wake up one waiter, so the others are woken up in a chain.
+                if waiting.load(Relaxed) != 0 {
+                    doorbell.ring()?;
+                }
+                continue;
+            }
+            backoff.snooze();
+        }
+    }
+    #[cfg(feature = "tokio")]
+    async fn block_on_async<T>(&self, sending: bool, mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut backoff = Backoff::default();
+        loop {
+            match op() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return els,
+            }
+            if let Some((waiting, doorbell)) = self.waiters(!sending).filter(|_| backoff.should_park()) {
+                // Also taken off the count if the future is dropped during the wait.
+                let waiter = Waiter::new(waiting);
+                match op() {
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    els => return els,
+                }
+                let result = doorbell.wait_async().await;
+                drop(waiter);
+                result?;
+                if waiting.load(Relaxed) != 0 {
+                    doorbell.ring()?;
+                }
+                continue;
+            }
+            backoff.snooze_async().await;
+        }
+    }
+}
+/// Counts a handle among the waiters for as long as it's alive, so that a wait which ends in any way – including by
+/// its future being dropped – takes it off the count.
+struct Waiter<'a>(&'a AtomicU32);
+impl<'a> Waiter<'a> {
+    fn new(waiting: &'a AtomicU32) -> Self {
+        waiting.fetch_add(1, SeqCst);
+        Self(waiting)
+    }
+}
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, SeqCst);
+    }
+}
+
+impl Debug for MessageQueue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dbs = f.debug_struct("MessageQueue");
+        dbs.field("shm", &self.shm)
+            .field("capacity", &self.capacity)
+            .field("max_message_len", &self.max_message_len);
+        dbs.field("doorbells", &self.doorbells);
+        dbs.finish()
+    }
+}
+
+/// Slots are rounded up to the size of the cache line, so that neighboring slots don't contend with each other.
+fn slot_size(max_message_len: usize) -> usize {
+    checked_slot_size(max_message_len).expect("message queue size overflow")
+}
+fn checked_slot_size(max_message_len: usize) -> Option<usize> {
+    Some(size_of::<SlotHeader>().checked_add(max_message_len)?.checked_add(63)? & !63)
+}
+fn fits(shm_len: usize, capacity: usize, max_message_len: usize) -> Option<bool> {
+    let needed = checked_slot_size(max_message_len)?
+        .checked_mul(capacity)?
+        .checked_add(HEADER_SIZE)?;
+    Some(needed <= shm_len)
+}
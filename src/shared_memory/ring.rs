@@ -0,0 +1,498 @@
+use super::Doorbell;
+use super::{
+    util::{Backoff, CachePadded},
+    SharedMemory,
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, prelude::*},
+    mem::size_of,
+    ptr,
+    sync::atomic::{
+        fence, AtomicU32, AtomicU64,
+        Ordering::{Acquire, Relaxed, Release, SeqCst},
+    },
+};
+
+/// The size of the header which precedes the data of a ring buffer in shared memory.
+///
+/// The capacity of a ring buffer is the largest power of two that fits into the shared memory object after the header,
+/// and so the object should be `RING_HEADER_SIZE` bytes larger than a power of two in order not to waste any space.
+pub const RING_HEADER_SIZE: usize = size_of::<Header>();
+
+const MAGIC: u64 = u64::from_ne_bytes(*b"ipcring1");
+
+#[repr(C)]
+struct Header {
+    /// Set to `MAGIC` once the rest of the header is initialized.
+    magic: AtomicU64,
+    capacity: AtomicU64,
+    sender_closed: AtomicU32,
+    receiver_closed: AtomicU32,
+    /// Set by the sender before it waits on its doorbell, so that the receiver knows to ring it.
+    sender_waiting: AtomicU32,
+    /// Ditto for the receiver.
+    receiver_waiting: AtomicU32,
+    /// The total number of bytes ever written, advanced by the sender.
+    head: CachePadded<AtomicU64>,
+    /// The total number of bytes ever read, advanced by the receiver.
+    tail: CachePadded<AtomicU64>,
+}
+
+/// The part shared by both ends of a ring buffer.
+struct RingEnd {
+    shm: SharedMemory,
+    capacity: usize,
+    /// The position advanced by this end, which only it ever writes to.
+    pos: u64,
+    /// The doorbell this end rings after advancing its position, and the one it waits on when it can't make progress.
+    doorbells: Option<(Doorbell, Doorbell)>,
+}
+impl RingEnd {
+    fn create(shm: SharedMemory) -> io::Result<Self> {
+        let usable = shm
+            .len()
+            .checked_sub(RING_HEADER_SIZE)
+            .filter(|&u| u > 0)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "shared memory object too small to hold a ring buffer",
+                )
+            })?;
+        // The largest power of two that fits, so that positions can be wrapped with a mask.
+        let capacity = 1 << (usize::BITS - 1 - usable.leading_zeros());
+
+        let end = Self::new(shm, capacity, 0);
+        let header = end.header();
+        header.capacity.store(capacity as u64, Relaxed);
+        for flag in [
+            &header.sender_closed,
+            &header.receiver_closed,
+            &header.sender_waiting,
+            &header.receiver_waiting,
+        ] {
+            flag.store(0, Relaxed);
+        }
+        header.head.0.store(0, Relaxed);
+        header.tail.0.store(0, Relaxed);
+        header.magic.store(MAGIC, Release);
+        Ok(end)
+    }
+    fn open(shm: SharedMemory, sender: bool) -> io::Result<Self> {
+        if shm.len() <= RING_HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "shared memory object too small to hold a ring buffer",
+            ));
+        }
+        let header = unsafe { &*shm.as_ptr().cast::<Header>() };
+        if header.magic.load(Acquire) != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory object does not contain an initialized ring buffer",
+            ));
+        }
+        let capacity = header.capacity.load(Relaxed);
+        // The other process could be malicious, and so nothing read from the header can be trusted.
+        if !capacity.is_power_of_two() || capacity > (shm.len() - RING_HEADER_SIZE) as u64 {
+            return Err(corrupted());
+        }
+        let pos = if sender {
+            header.head.0.load(Relaxed)
+        } else {
+            header.tail.0.load(Relaxed)
+        };
+        Ok(Self::new(shm, capacity as usize, pos))
+    }
+    fn new(shm: SharedMemory, capacity: usize, pos: u64) -> Self {
+        Self {
+            shm,
+            capacity,
+            pos,
+            doorbells: None,
+        }
+    }
+
+    #[inline]
+    fn header(&self) -> &Header {
+        // SAFETY: the mapping is page-aligned and large enough, and the header consists of atomics only, which makes
+        // it fine for other processes to modify it
+        unsafe { &*self.shm.as_ptr().cast::<Header>() }
+    }
+    /// Returns the offset into the data area that corresponds to the given position, and the number of bytes from there
+    /// to the end of the data area.
+    #[inline]
+    fn offset(&self, pos: u64) -> (usize, usize) {
+        let offset = pos as usize & (self.capacity - 1);
+        (offset, self.capacity - offset)
+    }
+    #[inline]
+    fn data(&self) -> *mut u8 {
+        unsafe { self.shm.as_ptr().add(RING_HEADER_SIZE) }
+    }
+
+    /// Writes as much of `buf` as fits without waiting.
+    fn try_send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let header = self.header();
+        if header.receiver_closed.load(Acquire) != 0 {
+            return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+        }
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let used = self.pos.wrapping_sub(header.tail.0.load(Acquire));
+        if used > self.capacity as u64 {
+            return Err(corrupted());
+        }
+        let len = buf.len().min(self.capacity - used as usize);
+        if len == 0 {
+            return Err(io::Error::from(io::ErrorKind::WouldBlock));
+        }
+        let (offset, until_end) = self.offset(self.pos);
+        let first = len.min(until_end);
+        unsafe {
+            // SAFETY: both parts are within the data area, the free part of which the receiver doesn't touch
+            ptr::copy_nonoverlapping(buf.as_ptr(), self.data().add(offset), first);
+            ptr::copy_nonoverlapping(buf.as_ptr().add(first), self.data(), len - first);
+        }
+        self.advance(len, |h| &h.head.0, |h| &h.receiver_waiting)?;
+        Ok(len)
+    }
+    /// Reads as much as is available into `buf` without waiting.
+    fn try_recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let header = self.header();
+        // Checked before the head, so that all data sent before closing is seen.
+        let closed = header.sender_closed.load(Acquire) != 0;
+        let available = header.head.0.load(Acquire).wrapping_sub(self.pos);
+        if available > self.capacity as u64 {
+            return Err(corrupted());
+        }
+        let len = buf.len().min(available as usize);
+        if len == 0 {
+            return match (buf.is_empty(), closed) {
+                (false, false) => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+                _ => Ok(0),
+            };
+        }
+        let (offset, until_end) = self.offset(self.pos);
+        let first = len.min(until_end);
+        unsafe {
+            // SAFETY: as above, with the sender not touching the occupied part of the data area
+            ptr::copy_nonoverlapping(self.data().add(offset), buf.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(self.data(), buf.as_mut_ptr().add(first), len - first);
+        }
+        self.advance(len, |h| &h.tail.0, |h| &h.sender_waiting)?;
+        Ok(len)
+    }
+    /// Publishes the new position and rings the doorbell of the other end if it's waiting on it.
+    fn advance(
+        &mut self,
+        by: usize,
+        pos: fn(&Header) -> &AtomicU64,
+        peer_waiting: fn(&Header) -> &AtomicU32,
+    ) -> io::Result<()> {
+        self.pos = self.pos.wrapping_add(by as u64);
+        pos(self.header()).store(self.pos, Release);
+        if let Some((notify, _)) = &self.doorbells {
+            // Pairs with the fence in block_on(): either the other end sees the new position before going to sleep, or
+            // we see its flag and wake it up.
+            fence(SeqCst);
+            if peer_waiting(self.header()).load(Relaxed) != 0 {
+                notify.ring()?;
+            }
+        }
+        Ok(())
+    }
+    /// Marks this end as closed and wakes up the other end so that it can notice.
+    fn close(&self, closed: fn(&Header) -> &AtomicU32) {
+        closed(self.header()).store(1, Release);
+        if let Some((notify, _)) = &self.doorbells {
+            let _ = notify.ring();
+        }
+    }
+
+    /// Retries `op` until it stops failing with `WouldBlock`, backing off in between and eventually sleeping on the
+    /// doorbell if there is one.
+    fn block_on<T>(
+        &mut self,
+        waiting: fn(&Header) -> &AtomicU32,
+        mut op: impl FnMut(&mut Self) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut backoff = Backoff::default();
+        loop {
+            match op(self) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return els,
+            }
+            if self.doorbells.is_some() && backoff.should_park() {
+                waiting(self.header()).store(1, Relaxed);
+                fence(SeqCst);
+                let result = match op(self) {
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => self.doorbells.as_ref().unwrap().1.wait(),
+                    els => {
+                        waiting(self.header()).store(0, Relaxed);
+                        return els;
+                    }
+                };
+                waiting(self.header()).store(0, Relaxed);
+                result?;
+                continue;
+            }
+            backoff.snooze();
+        }
+    }
+    #[cfg(feature = "tokio")]
+    async fn block_on_async<T>(
+        &mut self,
+        waiting: fn(&Header) -> &AtomicU32,
+        mut op: impl FnMut(&mut Self) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut backoff = Backoff::default();
+        loop {
+            match op(self) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return els,
+            }
+            if self.doorbells.is_some() && backoff.should_park() {
+                waiting(self.header()).store(1, Relaxed);
+                fence(SeqCst);
+                match op(self) {
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    els => {
+                        waiting(self.header()).store(0, Relaxed);
+                        return els;
+                    }
+                }
+                // Also cleared if the future is dropped during the wait.
+                let flag = WaitingFlag(waiting(self.header()));
+                let result = self.doorbells.as_ref().unwrap().1.wait_async().await;
+                drop(flag);
+                result?;
+                continue;
+            }
+            backoff.snooze_async().await;
+        }
+    }
+}
+/// Clears the flag of an end which is waiting on its doorbell when dropped, so that a wait which ends in any way –
+/// including by its future being dropped – doesn't leave it set.
+#[cfg(feature = "tokio")]
+struct WaitingFlag<'a>(&'a AtomicU32);
+#[cfg(feature = "tokio")]
+impl Drop for WaitingFlag<'_> {
+    fn drop(&mut self) {
+        self.0.store(0, Relaxed);
+    }
+}
+
+impl Debug for RingEnd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dbs = f.debug_struct("RingEnd");
+        dbs.field("shm", &self.shm)
+            .field("capacity", &self.capacity)
+            .field("pos", &self.pos);
+        dbs.field("doorbells", &self.doorbells);
+        dbs.finish()
+    }
+}
+
+fn corrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "ring buffer header is corrupted")
+}
+
+/// The writing end of a single-producer single-consumer byte ring buffer in shared memory, the reading end of which is a
+/// [`RingReceiver`].
+///
+/// The ring buffer is lock-free: data is copied straight into the shared memory, and the only synchronization between
+/// the ends is a pair of atomic positions. This makes it a lot faster than sockets or pipes for streaming data between
+/// processes on the same machine, in exchange for the data being copied in and out of a fixed-size buffer which is
+/// visible to the other process.
+///
+/// Exactly one of the ends initializes the ring buffer with `create()` before the shared memory is handed to the other
+/// process, which then attaches to it with `open()`. It makes no difference which of the ends does which.
+///
+/// # Waiting
+/// When the buffer is full, [writes](Write::write) block until the receiver frees up some space. By default, this is
+/// done by spinning and then sleeping for progressively longer; this is very fast when the data flows continuously,
+/// but makes idle ends sleep for up to 16 milliseconds before noticing new data. For lower latency when idle, both ends
+/// can be given a pair of [doorbells](Doorbell) with `with_doorbells()`, which they then sleep on after a brief period
+/// of spinning, and use to wake each other up.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::shared_memory::{RingReceiver, RingSender, SharedMemory, RING_HEADER_SIZE};
+/// use std::io::prelude::*;
+///
+/// // In one process:
+/// let shm = SharedMemory::create_with_drop_guard("Example", RING_HEADER_SIZE + 65536)?;
+/// let mut sender = RingSender::create(shm)?;
+/// sender.write_all(b"Hello from the other process!")?;
+///
+/// // In the other process:
+/// let mut receiver = RingReceiver::open(SharedMemory::open("Example")?)?;
+/// let mut buf = [0; 29];
+/// receiver.read_exact(&mut buf)?;
+/// assert_eq!(&buf, b"Hello from the other process!");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct RingSender(RingEnd);
+impl RingSender {
+    /// Initializes a new, empty ring buffer in the given shared memory object, overwriting its previous contents, and
+    /// returns its writing end.
+    ///
+    /// The capacity of the buffer is the largest power of two that fits after the [header](RING_HEADER_SIZE). An error
+    /// of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if the object isn't larger than the header.
+    pub fn create(shm: SharedMemory) -> io::Result<Self> {
+        RingEnd::create(shm).map(Self)
+    }
+    /// Attaches to a ring buffer which another process has initialized with [`RingReceiver::create()`] and returns its
+    /// writing end.
+    ///
+    /// An error of kind [`InvalidData`](io::ErrorKind::InvalidData) is returned if the shared memory object doesn't
+    /// contain an initialized ring buffer.
+    pub fn open(shm: SharedMemory) -> io::Result<Self> {
+        RingEnd::open(shm, true).map(Self)
+    }
+    /// Makes the sender ring `data_ready` whenever it writes data which the receiver is waiting for, and wait on
+    /// `space_ready` when the buffer is full.
+    ///
+    /// The receiver has to be given the same doorbells (or duplicates of their file descriptors or handles) in the same
+    /// order, or the ends will miss each other's wakeups.
+    pub fn with_doorbells(mut self, data_ready: Doorbell, space_ready: Doorbell) -> Self {
+        self.0.doorbells = Some((data_ready, space_ready));
+        self
+    }
+    /// Returns the capacity of the ring buffer in bytes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity
+    }
+    /// Borrows the shared memory object the ring buffer resides in, in order to share it with another process.
+    #[inline]
+    pub fn shared_memory(&self) -> &SharedMemory {
+        &self.0.shm
+    }
+    /// Writes as much of `buf` as fits into the buffer without waiting, failing with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) if the buffer is full.
+    ///
+    /// An error of kind [`BrokenPipe`](io::ErrorKind::BrokenPipe) is returned if the receiver has been dropped.
+    pub fn try_send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.try_send(buf)
+    }
+    /// Asynchronously writes as much of `buf` as fits into the buffer, waiting for the receiver to free up some space
+    /// if it's full.
+    ///
+    /// If the sender has doorbells, the wait is done with [`Doorbell::wait_async()`]; otherwise, the task yields to the
+    /// runtime and then sleeps for progressively longer.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn send_async(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .block_on_async(|h| &h.sender_waiting, |end| end.try_send(buf))
+            .await
+    }
+}
+/// Blocks until at least one byte can be written, in the way described in the [type-level documentation](RingSender).
+impl Write for RingSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.block_on(|h| &h.sender_waiting, |end| end.try_send(buf))
+    }
+    /// Does nothing, since the data is visible to the receiver as soon as it's written.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+/// Marks the ring buffer as closed, which makes the receiver see end of file once it reads everything that was written.
+impl Drop for RingSender {
+    fn drop(&mut self) {
+        self.0.close(|h| &h.sender_closed);
+    }
+}
+impl Debug for RingSender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RingSender").field(&self.0).finish()
+    }
+}
+
+/// The reading end of a single-producer single-consumer byte ring buffer in shared memory, the writing end of which is
+/// a [`RingSender`].
+///
+/// See the documentation of the latter for more.
+pub struct RingReceiver(RingEnd);
+impl RingReceiver {
+    /// Initializes a new, empty ring buffer in the given shared memory object, overwriting its previous contents, and
+    /// returns its reading end.
+    ///
+    /// See [`RingSender::create()`] for more.
+    pub fn create(shm: SharedMemory) -> io::Result<Self> {
+        RingEnd::create(shm).map(Self)
+    }
+    /// Attaches to a ring buffer which another process has initialized with [`RingSender::create()`] and returns its
+    /// reading end.
+    ///
+    /// See [`RingSender::open()`] for more.
+    pub fn open(shm: SharedMemory) -> io::Result<Self> {
+        RingEnd::open(shm, false).map(Self)
+    }
+    /// Makes the receiver wait on `data_ready` when the buffer is empty, and ring `space_ready` whenever it frees up
+    /// space which the sender is waiting for.
+    ///
+    /// See [`RingSender::with_doorbells()`] for more.
+    pub fn with_doorbells(mut self, data_ready: Doorbell, space_ready: Doorbell) -> Self {
+        self.0.doorbells = Some((space_ready, data_ready));
+        self
+    }
+    /// Returns the capacity of the ring buffer in bytes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity
+    }
+    /// Borrows the shared memory object the ring buffer resides in, in order to share it with another process.
+    #[inline]
+    pub fn shared_memory(&self) -> &SharedMemory {
+        &self.0.shm
+    }
+    /// Reads as much data as is available into `buf` without waiting, failing with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) if the buffer is empty.
+    ///
+    /// Zero bytes are read if the buffer is empty and the sender has been dropped.
+    pub fn try_recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.try_recv(buf)
+    }
+    /// Asynchronously reads as much data as is available into `buf`, waiting for the sender to write some if the
+    /// buffer is empty.
+    ///
+    /// See [`RingSender::send_async()`] for how the waiting is done.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn recv_async(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0
+            .block_on_async(|h| &h.receiver_waiting, |end| end.try_recv(buf))
+            .await
+    }
+}
+/// Blocks until at least one byte can be read, in the way described in the documentation of [`RingSender`].
+impl Read for RingReceiver {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.block_on(|h| &h.receiver_waiting, |end| end.try_recv(buf))
+    }
+}
+/// Marks the ring buffer as closed, which makes the sender fail with [`BrokenPipe`](io::ErrorKind::BrokenPipe).
+impl Drop for RingReceiver {
+    fn drop(&mut self) {
+        self.0.close(|h| &h.receiver_closed);
+    }
+}
+impl Debug for RingReceiver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RingReceiver").field(&self.0).finish()
+    }
+}
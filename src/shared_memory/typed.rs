@@ -0,0 +1,156 @@
+//! Typed views into shared memory, for types which can be safely reinterpreted from and to raw bytes.
+
+use super::SharedMemory;
+use std::{
+    io,
+    mem::{align_of, size_of},
+    slice,
+};
+
+impl SharedMemory {
+    /// Returns a pointer to `count` values of type `T` at `offset` bytes into the mapping, checking that they fit and
+    /// are properly aligned.
+    fn typed_ptr<T>(&self, offset: usize, count: usize) -> io::Result<*mut T> {
+        let end = size_of::<T>()
+            .checked_mul(count)
+            .and_then(|size| size.checked_add(offset))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "typed view size overflows usize"))?;
+        if end > self.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "typed view extends past the end of the shared memory object",
+            ));
+        }
+        // The mapping itself is page-aligned, but the offset might not be.
+        let ptr = self.as_ptr().wrapping_add(offset);
+        if ptr as usize % align_of::<T>() != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "typed view is not aligned to the alignment of the type",
+            ));
+        }
+        Ok(ptr.cast())
+    }
+}
+
+#[cfg(feature = "zerocopy")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "zerocopy")))]
+impl SharedMemory {
+    /// Borrows a value of type `T` located at `offset` bytes into the mapping.
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the value doesn't fit into the mapping or `offset`
+    /// isn't suitably aligned for `T`. Since the mapping is page-aligned, an offset which is a multiple of the alignment
+    /// of `T` always is.
+    ///
+    /// This method is only available with the `zerocopy` feature enabled.
+    ///
+    /// # Safety
+    /// No process may modify the memory covered by the value for as long as the reference is alive.
+    pub unsafe fn view<T: zerocopy::FromBytes + zerocopy::AsBytes>(&self, offset: usize) -> io::Result<&T> {
+        let ptr = self.typed_ptr::<T>(offset, 1)?;
+        // SAFETY: in bounds and aligned as per the check above, and any bytes are a valid T
+        Ok(unsafe { &*ptr })
+    }
+    /// Mutably borrows a value of type `T` located at `offset` bytes into the mapping.
+    ///
+    /// See [`view()`](Self::view) for the conditions under which this fails.
+    ///
+    /// This method is only available with the `zerocopy` feature enabled.
+    ///
+    /// # Safety
+    /// No other process may access the memory covered by the value for as long as the reference is alive.
+    pub unsafe fn view_mut<T: zerocopy::FromBytes + zerocopy::AsBytes>(&mut self, offset: usize) -> io::Result<&mut T> {
+        let ptr = self.typed_ptr::<T>(offset, 1)?;
+        // SAFETY: as above, and the mutable borrow of self rules out other references in this process
+        Ok(unsafe { &mut *ptr })
+    }
+    /// Borrows a slice of `count` values of type `T` starting at `offset` bytes into the mapping.
+    ///
+    /// See [`view()`](Self::view) for the conditions under which this fails.
+    ///
+    /// This method is only available with the `zerocopy` feature enabled.
+    ///
+    /// # Safety
+    /// No process may modify the memory covered by the slice for as long as it is alive.
+    pub unsafe fn view_slice<T: zerocopy::FromBytes + zerocopy::AsBytes>(
+        &self,
+        offset: usize,
+        count: usize,
+    ) -> io::Result<&[T]> {
+        let ptr = self.typed_ptr::<T>(offset, count)?;
+        Ok(unsafe { slice::from_raw_parts(ptr, count) })
+    }
+    /// Mutably borrows a slice of `count` values of type `T` starting at `offset` bytes into the mapping.
+    ///
+    /// See [`view()`](Self::view) for the conditions under which this fails.
+    ///
+    /// This method is only available with the `zerocopy` feature enabled.
+    ///
+    /// # Safety
+    /// No other process may access the memory covered by the slice for as long as it is alive.
+    pub unsafe fn view_slice_mut<T: zerocopy::FromBytes + zerocopy::AsBytes>(
+        &mut self,
+        offset: usize,
+        count: usize,
+    ) -> io::Result<&mut [T]> {
+        let ptr = self.typed_ptr::<T>(offset, count)?;
+        Ok(unsafe { slice::from_raw_parts_mut(ptr, count) })
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "bytemuck")))]
+impl SharedMemory {
+    /// Borrows a value of type `T` located at `offset` bytes into the mapping.
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the value doesn't fit into the mapping or `offset`
+    /// isn't suitably aligned for `T`. Since the mapping is page-aligned, an offset which is a multiple of the alignment
+    /// of `T` always is.
+    ///
+    /// This method is only available with the `bytemuck` feature enabled.
+    ///
+    /// # Safety
+    /// No process may modify the memory covered by the value for as long as the reference is alive.
+    pub unsafe fn view_pod<T: bytemuck::Pod>(&self, offset: usize) -> io::Result<&T> {
+        let ptr = self.typed_ptr::<T>(offset, 1)?;
+        // SAFETY: in bounds and aligned as per the check above, and any bytes are a valid T
+        Ok(unsafe { &*ptr })
+    }
+    /// Mutably borrows a value of type `T` located at `offset` bytes into the mapping.
+    ///
+    /// See [`view_pod()`](Self::view_pod) for the conditions under which this fails.
+    ///
+    /// This method is only available with the `bytemuck` feature enabled.
+    ///
+    /// # Safety
+    /// No other process may access the memory covered by the value for as long as the reference is alive.
+    pub unsafe fn view_pod_mut<T: bytemuck::Pod>(&mut self, offset: usize) -> io::Result<&mut T> {
+        let ptr = self.typed_ptr::<T>(offset, 1)?;
+        // SAFETY: as above, and the mutable borrow of self rules out other references in this process
+        Ok(unsafe { &mut *ptr })
+    }
+    /// Borrows a slice of `count` values of type `T` starting at `offset` bytes into the mapping.
+    ///
+    /// See [`view_pod()`](Self::view_pod) for the conditions under which this fails.
+    ///
+    /// This method is only available with the `bytemuck` feature enabled.
+    ///
+    /// # Safety
+    /// No process may modify the memory covered by the slice for as long as it is alive.
+    pub unsafe fn view_pod_slice<T: bytemuck::Pod>(&self, offset: usize, count: usize) -> io::Result<&[T]> {
+        let ptr = self.typed_ptr::<T>(offset, count)?;
+        Ok(unsafe { slice::from_raw_parts(ptr, count) })
+    }
+    /// Mutably borrows a slice of `count` values of type `T` starting at `offset` bytes into the mapping.
+    ///
+    /// See [`view_pod()`](Self::view_pod) for the conditions under which this fails.
+    ///
+    /// This method is only available with the `bytemuck` feature enabled.
+    ///
+    /// # Safety
+    /// No other process may access the memory covered by the slice for as long as it is alive.
+    pub unsafe fn view_pod_slice_mut<T: bytemuck::Pod>(&mut self, offset: usize, count: usize) -> io::Result<&mut [T]> {
+        let ptr = self.typed_ptr::<T>(offset, count)?;
+        Ok(unsafe { slice::from_raw_parts_mut(ptr, count) })
+    }
+}
@@ -0,0 +1,43 @@
+//! Helpers shared by the data structures that live in shared memory.
+
+use std::{hint, thread, time::Duration};
+
+/// Keeps a value which is written by one process from sharing a cache line with ones written by others.
+#[repr(C, align(64))]
+pub(super) struct CachePadded<T>(pub T);
+
+/// Spins, then yields, then sleeps for progressively longer, up to 16 milliseconds.
+#[derive(Default)]
+pub(super) struct Backoff(u32);
+impl Backoff {
+    const SPIN_LIMIT: u32 = 6;
+    const YIELD_LIMIT: u32 = 10;
+    pub fn snooze(&mut self) {
+        if self.0 <= Self::SPIN_LIMIT {
+            for _ in 0..1 << self.0 {
+                hint::spin_loop();
+            }
+        } else if self.0 <= Self::YIELD_LIMIT {
+            thread::yield_now();
+        } else {
+            thread::sleep(self.sleep_duration());
+        }
+        self.0 = (self.0 + 1).min(Self::YIELD_LIMIT + 5);
+    }
+    #[cfg(feature = "tokio")]
+    pub async fn snooze_async(&mut self) {
+        if self.0 <= Self::YIELD_LIMIT {
+            tokio::task::yield_now().await;
+        } else {
+            tokio::time::sleep(self.sleep_duration()).await;
+        }
+        self.0 = (self.0 + 1).min(Self::YIELD_LIMIT + 5);
+    }
+    /// Returns `true` once spinning and yielding have been tried, and it's time to go to sleep on a doorbell instead.
+    pub fn should_park(&self) -> bool {
+        self.0 > Self::YIELD_LIMIT
+    }
+    fn sleep_duration(&self) -> Duration {
+        Duration::from_millis(1 << (self.0 - Self::YIELD_LIMIT - 1))
+    }
+}
@@ -0,0 +1,203 @@
+//! Spawning child processes with interprocess communication channels already connected to them.
+//!
+//! Passing an unnamed pipe or a socket to a child process takes a different dance on every platform: on Unix, the file
+//! descriptor has to be moved to a number the child knows about in between `fork` and `exec`, while on Windows, the
+//! handle has to be made inheritable and its value has to be communicated to the child by some other means. A
+//! [`Spawner`] does all of that for any number of channels, and the functions in this module pick them up on the
+//! child's side.
+//!
+//! Channels are identified by their index, which is the order in which they were added to the spawner, starting from
+//! zero. The child learns where to find them from the `INTERPROCESS_CHANNELS` environment variable, which holds a
+//! comma-separated list of file descriptor or handle values. On Unix, those are always consecutive file descriptors
+//! starting from 3, right after standard input, output and error, so that programs which don't use this crate can
+//! rely on them too.
+//!
+//! # Example
+//! ```no_run
+//! use interprocess::spawn::{self, Spawner};
+//! use std::{env, io::prelude::*, process::Command};
+//!
+//! if env::var_os("INTERPROCESS_CHANNELS").is_none() {
+//!     // Parent process
+//!     let mut spawner = Spawner::new(Command::new(env::current_exe()?));
+//!     let mut to_child = spawner.pipe_to_child()?; // Channel 0
+//!     let mut from_child = spawner.pipe_from_child()?; // Channel 1
+//!     let mut child = spawner.spawn()?;
+//!
+//!     to_child.write_all(b"ping")?;
+//!     let mut buf = [0; 4];
+//!     from_child.read_exact(&mut buf)?;
+//!     assert_eq!(&buf, b"pong");
+//!     child.wait()?;
+//! } else {
+//!     // Child process
+//!     // SAFETY: this process was spawned with a spawner which set up those channels, and they're only taken once
+//!     let (mut from_parent, mut to_parent) =
+//!         unsafe { (spawn::inherited_pipe_reader(0)?, spawn::inherited_pipe_writer(1)?) };
+//!     let mut buf = [0; 4];
+//!     from_parent.read_exact(&mut buf)?;
+//!     to_parent.write_all(b"pong")?;
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+impmod! {spawn,
+    ChildEnd,
+    inherit_all,
+    adopt,
+}
+use crate::unnamed_pipe::{PipeOptions, UnnamedPipeReader, UnnamedPipeWriter};
+use std::{
+    env,
+    ffi::OsString,
+    fmt::{self, Debug, Formatter},
+    io,
+    process::{Child, Command},
+};
+
+/// The name of the environment variable which lists the inherited channels in the child process.
+const CHANNELS_ENV: &str = "INTERPROCESS_CHANNELS";
+
+/// Builder for a child process with interprocess communication channels connected to it.
+///
+/// Every method which adds a channel returns the parent's end of it, while the child's end is kept by the spawner until
+/// the child is spawned, after which it's closed in the parent process.
+///
+/// See the [module-level documentation](self) for how the child picks up the channels.
+pub struct Spawner {
+    command: Command,
+    ends: Vec<ChildEnd>,
+}
+impl Spawner {
+    /// Creates a spawner for the given command, with no channels yet.
+    pub fn new(command: Command) -> Self {
+        Self {
+            command,
+            ends: Vec::new(),
+        }
+    }
+    /// Returns a mutable reference to the command, for configuring it further.
+    #[inline]
+    pub fn command_mut(&mut self) -> &mut Command {
+        &mut self.command
+    }
+    /// Returns the number of channels added so far, which is also the index of the next one.
+    #[inline]
+    pub fn channel_count(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Adds a pipe which the child reads from as the next channel, and returns its writing end.
+    pub fn pipe_to_child(&mut self) -> io::Result<UnnamedPipeWriter> {
+        let (writer, reader) = PipeOptions::new().create()?;
+        self.ends.push(reader.into());
+        Ok(writer)
+    }
+    /// Adds a pipe which the child writes into as the next channel, and returns its reading end.
+    pub fn pipe_from_child(&mut self) -> io::Result<UnnamedPipeReader> {
+        let (writer, reader) = PipeOptions::new().create()?;
+        self.ends.push(writer.into());
+        Ok(reader)
+    }
+    /// Adds one end of a connected pair of Unix domain stream sockets as the next channel, and returns the other one.
+    ///
+    /// This method is only available on Unix. On other platforms, it's absent and thus any usage of it will result in a
+    /// compile-time error.
+    ///
+    /// # System calls
+    /// - `socketpair`
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn socket_pair(&mut self) -> io::Result<crate::os::unix::udsocket::UdStream> {
+        let (ours, theirs) = std::os::unix::net::UnixStream::pair()?;
+        self.ends.push(theirs.into());
+        Ok(std::os::unix::io::OwnedFd::from(ours).into())
+    }
+
+    /// Spawns the child process, closing the child's ends of the channels in the parent.
+    ///
+    /// On Windows, the child's ends are inheritable until this method returns, and so any other process spawned in the
+    /// meantime by another thread will inherit them too.
+    ///
+    /// # System calls
+    /// - `fcntl` and `dup2` (on Unix, in the child process)
+    /// - `SetHandleInformation` (on Windows)
+    pub fn spawn(mut self) -> io::Result<Child> {
+        let values = inherit_all(&mut self.command, &mut self.ends)?;
+        let mut list = OsString::new();
+        for (i, value) in values.iter().enumerate() {
+            if i != 0 {
+                list.push(",");
+            }
+            list.push(value.to_string());
+        }
+        self.command.env(CHANNELS_ENV, list);
+        self.command.spawn()
+    }
+}
+impl From<Command> for Spawner {
+    #[inline]
+    fn from(command: Command) -> Self {
+        Self::new(command)
+    }
+}
+impl Debug for Spawner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spawner")
+            .field("command", &self.command)
+            .field("channels", &self.ends.len())
+            .finish()
+    }
+}
+
+/// Takes ownership of the reading end of the channel at the given index, added by the parent process with
+/// [`Spawner::pipe_to_child()`].
+///
+/// Fails with [`NotFound`](io::ErrorKind::NotFound) if the process has no channel with that index.
+///
+/// # Safety
+/// The process must have been spawned by a [`Spawner`] (or by something else which provides channels the same way),
+/// and the channel must not have been taken before.
+pub unsafe fn inherited_pipe_reader(index: usize) -> io::Result<UnnamedPipeReader> {
+    unsafe { take(index) }.map(UnnamedPipeReader::from)
+}
+/// Takes ownership of the writing end of the channel at the given index, added by the parent process with
+/// [`Spawner::pipe_from_child()`].
+///
+/// Fails with [`NotFound`](io::ErrorKind::NotFound) if the process has no channel with that index.
+///
+/// # Safety
+/// Same as [`inherited_pipe_reader()`].
+pub unsafe fn inherited_pipe_writer(index: usize) -> io::Result<UnnamedPipeWriter> {
+    unsafe { take(index) }.map(UnnamedPipeWriter::from)
+}
+/// Takes ownership of the socket of the channel at the given index, added by the parent process with
+/// [`Spawner::socket_pair()`].
+///
+/// Fails with [`NotFound`](io::ErrorKind::NotFound) if the process has no channel with that index.
+///
+/// This function is only available on Unix. On other platforms, it's absent and thus any usage of it will result in a
+/// compile-time error.
+///
+/// # Safety
+/// Same as [`inherited_pipe_reader()`].
+#[cfg(unix)]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+pub unsafe fn inherited_socket(index: usize) -> io::Result<crate::os::unix::udsocket::UdStream> {
+    unsafe { take(index) }.map(crate::os::unix::udsocket::UdStream::from)
+}
+
+unsafe fn take(index: usize) -> io::Result<ChildEnd> {
+    let list = env::var(CHANNELS_ENV).map_err(|_| no_such_channel())?;
+    let raw = list
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .nth(index)
+        .ok_or_else(no_such_channel)?
+        .parse::<u64>()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "malformed channel list in the environment"))?;
+    unsafe { adopt(raw) }
+}
+fn no_such_channel() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "no channel with this index was inherited")
+}
@@ -0,0 +1,61 @@
+//! Synchronization primitives which work across process boundaries.
+//!
+//! Unlike their counterparts in [`std::sync`], these are identified by name, so that unrelated processes can find
+//! them. On Unix, mutexes, condition variables and reader-writer locks are built on top of
+//! [shared memory](crate::shared_memory) and share its namespace, while semaphores are POSIX named semaphores; on
+//! Windows, mutexes and semaphores are named kernel objects, and condition variables and reader-writer locks are
+//! emulated with shared memory and a semaphore. [Events](Event) are the exception, being unnamed and shared the same way
+//! as file descriptors and handles, while [file locks](FileLock) are identified by a path on the filesystem instead.
+//! [Named events](NamedEvent) bridge the gap, being named event objects on Windows and a flag in shared memory on
+//! Unix.
+//!
+//! ## One-time initialization
+//! [`SharedOnce`] builds on named mutexes and shared memory to make sure that exactly one process runs an
+//! initialization routine, such as setting up a shared memory segment, with the others waiting for it to finish. A
+//! process crashing halfway through initialization is detected the same way as [owner death](#owner-death).
+//!
+//! ## Parking
+//! For building synchronization primitives of one's own, [`park()`] puts the current thread to sleep for as long as a
+//! 32-bit word in shared memory holds a given value, and [`unpark_one()`] and [`unpark_all()`] wake up the threads
+//! parked on it, in this or any other process. This is available on Linux, Android, FreeBSD, macOS and Windows.
+//!
+//! ## Owner death
+//! A process can crash while holding a lock, leaving whatever the lock protects in an inconsistent state. Instead of
+//! making every other process deadlock waiting for a lock that will never be released, the next process to acquire it
+//! is told about it with [`LockResult::OwnerDied`], and can then repair the shared state before carrying on.
+
+mod condvar;
+mod event;
+mod file_lock;
+mod mutex;
+mod named_event;
+mod once;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos",
+    windows
+))]
+#[cfg_attr(
+    feature = "doc_cfg",
+    doc(cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "macos",
+        windows
+    )))
+)]
+mod park;
+mod rwlock;
+mod semaphore;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos",
+    windows
+))]
+pub use park::*;
+pub use {condvar::*, event::*, file_lock::*, mutex::*, named_event::*, once::*, rwlock::*, semaphore::*};
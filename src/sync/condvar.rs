@@ -0,0 +1,135 @@
+impmod! {named_condvar,
+    NamedCondvar as NamedCondvarImpl,
+}
+use super::{LockResult, NamedMutex, NamedMutexGuard};
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+/// A condition variable shared by name between processes, used together with a [`NamedMutex`](super::NamedMutex) to
+/// wait for the state it protects to change.
+///
+/// Like its [`std::sync`] counterpart, the condition variable is subject to spurious wakeups, and thus waits should be
+/// performed in a loop which checks the actual condition. Any mutex may be used with it, but all processes waiting on
+/// the condition variable at the same time should use the same one.
+///
+/// On Unix, this is a process-shared pthread condition variable, stored in a shared memory object of its own which
+/// persists until it's [unlinked](crate::shared_memory::unlink), unless it was created with
+/// [`create_with_drop_guard()`](Self::create_with_drop_guard). On Windows, where the system's condition variables
+/// don't work across processes, it's emulated with a waiter count in a shared memory object and a named semaphore, both
+/// of which are destroyed once the last handle to them is closed.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::{NamedCondvar, NamedMutex};
+///
+/// let mutex = NamedMutex::open("Example-mutex")?;
+/// let condvar = NamedCondvar::open("Example-condvar")?;
+/// let mut guard = mutex.lock()?.into_guard();
+/// # let ready = || true;
+/// while !ready() {
+///     guard = condvar.wait(guard)?.into_guard();
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct NamedCondvar(pub(crate) NamedCondvarImpl);
+impl NamedCondvar {
+    /// Creates a new condition variable with the given name.
+    ///
+    /// An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if an object with the same name
+    /// exists already.
+    ///
+    /// # System calls
+    /// - `shm_open`, `ftruncate`, `mmap` and `pthread_cond_init` on Unix
+    /// - `CreateSemaphoreW`, `CreateFileMappingW` and `MapViewOfFile` on Windows
+    pub fn create(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedCondvarImpl::create(name.as_ref(), false).map(Self)
+    }
+    /// Creates a new condition variable like [`create()`](Self::create), and installs a drop guard that will unlink its
+    /// name once the value is dropped.
+    ///
+    /// On Windows, the condition variable is destroyed once its last handle is closed regardless, making this the same
+    /// as `create()`.
+    pub fn create_with_drop_guard(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedCondvarImpl::create(name.as_ref(), true).map(Self)
+    }
+    /// Opens an existing condition variable with the given name.
+    ///
+    /// # System calls
+    /// - `shm_open`, `fstat` and `mmap` on Unix
+    /// - `OpenFileMappingW`, `MapViewOfFile` and `OpenSemaphoreW` on Windows
+    pub fn open(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedCondvarImpl::open(name.as_ref()).map(Self)
+    }
+
+    /// Atomically releases the lock held by the guard and blocks until the condition variable is notified, then
+    /// reacquires the lock.
+    ///
+    /// If the lock's owner died while the current thread was waiting, [`LockResult::OwnerDied`] is returned, same as
+    /// with [`NamedMutex::lock()`](super::NamedMutex::lock). The same goes for a guard which was itself obtained that way
+    /// and wasn't [marked as consistent](NamedMutexGuard::mark_consistent), since the state still needs repairing – on
+    /// Unix, however, the mutex becomes unusable once it's released by the wait in that case, and so it should be marked
+    /// as consistent before waiting. If an error is returned, the lock has been released.
+    ///
+    /// # System calls
+    /// - `pthread_cond_wait` on Unix
+    /// - `ReleaseMutex` and `WaitForSingleObject` on Windows
+    pub fn wait<'a>(&self, guard: NamedMutexGuard<'a>) -> io::Result<LockResult<NamedMutexGuard<'a>>> {
+        let mutex = guard.mutex();
+        // The lock changes hands inside the wait, and a guard for it is created anew afterwards.
+        let inconsistent = guard.forget();
+        self.0.wait(&mutex.0).map(|r| rebuild_guard(mutex, r, inconsistent))
+    }
+    /// Like [`wait()`](Self::wait), but gives up once the timeout runs out. The returned flag is `true` in that case,
+    /// and the lock is reacquired either way.
+    ///
+    /// # System calls
+    /// - `clock_gettime` and `pthread_cond_timedwait` on Unix
+    /// - `ReleaseMutex` and `WaitForSingleObject` on Windows
+    pub fn wait_timeout<'a>(
+        &self,
+        guard: NamedMutexGuard<'a>,
+        timeout: Duration,
+    ) -> io::Result<(LockResult<NamedMutexGuard<'a>>, bool)> {
+        let mutex = guard.mutex();
+        let inconsistent = guard.forget();
+        self.0
+            .wait_timeout(&mutex.0, timeout)
+            .map(|(r, timed_out)| (rebuild_guard(mutex, r, inconsistent), timed_out))
+    }
+    /// Wakes up one thread waiting on the condition variable, if there is one.
+    ///
+    /// # System calls
+    /// - `pthread_cond_signal` on Unix
+    /// - `ReleaseSemaphore` on Windows
+    pub fn notify_one(&self) -> io::Result<()> {
+        self.0.notify_one()
+    }
+    /// Wakes up all threads waiting on the condition variable.
+    ///
+    /// # System calls
+    /// - `pthread_cond_broadcast` on Unix
+    /// - `ReleaseSemaphore` on Windows
+    pub fn notify_all(&self) -> io::Result<()> {
+        self.0.notify_all()
+    }
+}
+impl Debug for NamedCondvar {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+/// Creates the guard for a lock reacquired by a wait, which reports owner death if the guard given to the wait did and
+/// the mutex wasn't marked as consistent since.
+fn rebuild_guard(mutex: &NamedMutex, result: LockResult<()>, inconsistent: bool) -> LockResult<NamedMutexGuard<'_>> {
+    let result = match result {
+        LockResult::Acquired(()) if inconsistent => LockResult::OwnerDied(()),
+        els => els,
+    };
+    NamedMutexGuard::from_lock_result(mutex, result)
+}
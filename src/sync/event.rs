@@ -0,0 +1,112 @@
+impmod! {event,
+    Event as EventImpl,
+}
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+/// A cross-process event, which lets a process cheaply notify others that something has happened.
+///
+/// An event is either set or unset. Once [set](Self::set), it stays that way, releasing every current and future
+/// waiter, until it's explicitly [reset](Self::reset). Setting an event that's already set does nothing. For a
+/// primitive which wakes up one waiter per notification, see [`Doorbell`](crate::shared_memory::Doorbell).
+///
+/// Events have no name, and are shared with other processes the same way as
+/// [anonymous shared memory](crate::shared_memory::SharedMemory::anonymous): by inheritance, by passing a duplicate
+/// of the file descriptor or handle over IPC, or, on Windows, with [`ShareHandle`](crate::os::windows::ShareHandle).
+///
+/// On Linux and Android, events are eventfds. Other Unix-like systems lack eventfds and use a self-pipe instead; the
+/// two file descriptors of the pipe survive `fork()`, but there's no way to extract them, and thus events can't be
+/// converted to or from file descriptors on those platforms. On Windows, events are manual-reset event objects.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::Event;
+/// use std::time::Duration;
+///
+/// let event = Event::new()?;
+/// // Share the event with another process here, which calls `event.set()` once it's ready...
+/// if !event.wait_timeout(Duration::from_secs(5))? {
+///     eprintln!("the other process is taking too long");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Event(pub(crate) EventImpl);
+impl Event {
+    /// Creates a new event which is not set. Its file descriptors or handle are not inheritable.
+    ///
+    /// # System calls
+    /// - `eventfd` on Linux and Android
+    /// - `pipe2` or `pipe` on other Unix-like systems
+    /// - `CreateEventW` on Windows
+    pub fn new() -> io::Result<Self> {
+        EventImpl::new().map(Self)
+    }
+    /// Sets the event, releasing all threads waiting on it.
+    ///
+    /// # System calls
+    /// - `write` on Unix
+    /// - `SetEvent` on Windows
+    pub fn set(&self) -> io::Result<()> {
+        self.0.set()
+    }
+    /// Resets the event, so that waits block until it's set again.
+    ///
+    /// # System calls
+    /// - `read` on Unix
+    /// - `ResetEvent` on Windows
+    pub fn reset(&self) -> io::Result<()> {
+        self.0.reset()
+    }
+    /// Returns whether the event is currently set, without blocking.
+    ///
+    /// # System calls
+    /// - `poll` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn is_set(&self) -> io::Result<bool> {
+        self.0.is_set()
+    }
+    /// Blocks until the event is set. Returns immediately if it's set already.
+    ///
+    /// # System calls
+    /// - `poll` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn wait(&self) -> io::Result<()> {
+        self.0.wait()
+    }
+    /// Like [`wait()`](Self::wait), but gives up once the timeout runs out, returning `false` in that case.
+    ///
+    /// # System calls
+    /// - `poll` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        self.0.wait_timeout(Some(timeout))
+    }
+    /// Asynchronously waits until the event is set.
+    ///
+    /// On Unix, the event is registered in the Tokio event loop for the duration of the wait. On Windows, where event
+    /// objects can't be waited on by the event loop, a thread from the blocking pool of the runtime does the waiting;
+    /// if the future is dropped before the event is set, that thread keeps waiting until it is.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        self.0.wait_async().await
+    }
+}
+impl Debug for Event {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_handle!(windows: Event);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+forward_handle!(unix: Event);
+forward_try_clone!(Event);
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+derive_raw!(Event);
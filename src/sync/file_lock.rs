@@ -0,0 +1,191 @@
+impmod! {file_lock,
+    FileLock as FileLockImpl,
+}
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs::{File, OpenOptions},
+    io,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// An advisory lock on a file, which can be held either exclusively by one owner or shared by many.
+///
+/// Unlike the other primitives in this module, file locks are identified by a path on the filesystem, which makes them
+/// a natural fit for guarding other files, such as the ones Unix domain sockets are bound to. The lock is released when
+/// its guard is dropped, and by the system when the process holding it exits, even if it crashes.
+///
+/// The lock belongs to the open file rather than the path, and so two `FileLock`s opened separately from the same path
+/// exclude each other even within one process. The exception is Solaris, where locks are taken with `fcntl` and belong
+/// to the process instead. Locking is not reentrant, and converting a held lock between shared and exclusive isn't
+/// supported – release it and lock it again instead.
+///
+/// # Advisory and mandatory locking
+/// On Unix, file locks are advisory: they only exclude other lock attempts, and don't prevent anyone from reading or
+/// writing the file. On Windows, they're mandatory: while the lock is held exclusively, other handles to the file can't
+/// read from or write to it, and while it's shared, nobody can write to it, including the owners of the lock.
+/// Portable programs should thus not use the locked file itself to store data, but lock a dedicated lock file.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::FileLock;
+///
+/// let lock = FileLock::open("/tmp/example.lock")?;
+/// let guard = lock.lock_exclusive()?;
+/// // Only one process at a time gets here...
+/// drop(guard);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct FileLock(pub(crate) FileLockImpl);
+impl FileLock {
+    /// Opens the file at the given path for locking, creating it if it doesn't exist. The file is not locked yet.
+    ///
+    /// The file is opened for both reading and writing, which some platforms require for some kinds of locks.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map(Self::from)
+    }
+    /// Returns a reference to the underlying file.
+    #[inline]
+    pub fn file(&self) -> &File {
+        self.0.file()
+    }
+    /// Returns the underlying file.
+    #[inline]
+    pub fn into_file(self) -> File {
+        self.0.into_file()
+    }
+
+    /// Acquires the lock exclusively, blocking until nobody else holds it.
+    ///
+    /// # System calls
+    /// - `flock` on Unix, or `fcntl` on Solaris
+    /// - `LockFileEx` on Windows
+    pub fn lock_exclusive(&self) -> io::Result<FileLockGuard<'_>> {
+        self.lock(true, false)
+    }
+    /// Acquires the lock exclusively if nobody else holds it, failing with [`WouldBlock`](io::ErrorKind::WouldBlock)
+    /// otherwise.
+    ///
+    /// # System calls
+    /// - `flock` on Unix, or `fcntl` on Solaris
+    /// - `LockFileEx` on Windows
+    pub fn try_lock_exclusive(&self) -> io::Result<FileLockGuard<'_>> {
+        self.lock(true, true)
+    }
+    /// Acquires the lock exclusively, blocking until nobody else holds it or the timeout runs out, returning `None` in
+    /// the latter case.
+    ///
+    /// Since neither platform has a timed wait on file locks, the lock is polled instead.
+    ///
+    /// # System calls
+    /// - `flock` on Unix, or `fcntl` on Solaris
+    /// - `LockFileEx` on Windows
+    pub fn lock_exclusive_timeout(&self, timeout: Duration) -> io::Result<Option<FileLockGuard<'_>>> {
+        self.lock_timeout(true, timeout)
+    }
+    /// Acquires the lock in shared mode, blocking until nobody holds it exclusively.
+    ///
+    /// # System calls
+    /// - `flock` on Unix, or `fcntl` on Solaris
+    /// - `LockFileEx` on Windows
+    pub fn lock_shared(&self) -> io::Result<FileLockGuard<'_>> {
+        self.lock(false, false)
+    }
+    /// Acquires the lock in shared mode if nobody holds it exclusively, failing with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) otherwise.
+    ///
+    /// # System calls
+    /// - `flock` on Unix, or `fcntl` on Solaris
+    /// - `LockFileEx` on Windows
+    pub fn try_lock_shared(&self) -> io::Result<FileLockGuard<'_>> {
+        self.lock(false, true)
+    }
+    /// Acquires the lock in shared mode, blocking until nobody holds it exclusively or the timeout runs out, returning
+    /// `None` in the latter case.
+    ///
+    /// See [`lock_exclusive_timeout()`](Self::lock_exclusive_timeout) for more.
+    ///
+    /// # System calls
+    /// - `flock` on Unix, or `fcntl` on Solaris
+    /// - `LockFileEx` on Windows
+    pub fn lock_shared_timeout(&self, timeout: Duration) -> io::Result<Option<FileLockGuard<'_>>> {
+        self.lock_timeout(false, timeout)
+    }
+
+    fn lock(&self, exclusive: bool, nonblocking: bool) -> io::Result<FileLockGuard<'_>> {
+        self.0.lock(exclusive, nonblocking)?;
+        Ok(FileLockGuard { lock: self, exclusive })
+    }
+    fn lock_timeout(&self, exclusive: bool, timeout: Duration) -> io::Result<Option<FileLockGuard<'_>>> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut delay = Duration::from_millis(1);
+        loop {
+            match self.lock(exclusive, true) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                els => return els.map(Some),
+            }
+            let now = Instant::now();
+            let remaining = match deadline {
+                Some(deadline) if now >= deadline => return Ok(None),
+                Some(deadline) => deadline - now,
+                None => delay,
+            };
+            thread::sleep(delay.min(remaining));
+            delay = (delay * 2).min(Duration::from_millis(16));
+        }
+    }
+}
+impl From<File> for FileLock {
+    /// Wraps an open file for locking. Shared and exclusive locks may require the file to be open for reading and
+    /// writing respectively, depending on the platform.
+    #[inline]
+    fn from(file: File) -> Self {
+        Self(FileLockImpl::from(file))
+    }
+}
+impl Debug for FileLock {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_as_handle!(FileLock);
+derive_asraw!(FileLock);
+
+/// Holds a [`FileLock`] and releases it when dropped.
+pub struct FileLockGuard<'a> {
+    lock: &'a FileLock,
+    exclusive: bool,
+}
+impl<'a> FileLockGuard<'a> {
+    /// Returns the lock this guard holds.
+    #[inline]
+    pub fn lock(&self) -> &'a FileLock {
+        self.lock
+    }
+    /// Returns `true` if the lock is held exclusively, or `false` if it's shared.
+    #[inline]
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+}
+impl Drop for FileLockGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.0.unlock();
+    }
+}
+impl Debug for FileLockGuard<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileLockGuard")
+            .field("lock", &self.lock)
+            .field("exclusive", &self.exclusive)
+            .finish()
+    }
+}
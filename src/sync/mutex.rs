@@ -0,0 +1,196 @@
+impmod! {named_mutex,
+    NamedMutex as NamedMutexImpl,
+}
+use std::{
+    cell::Cell,
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    marker::PhantomData,
+    mem,
+};
+
+/// The outcome of acquiring a lock which is robust against its owner dying while holding it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[must_use = "the lock is released when the guard is dropped, and owner death should be handled"]
+pub enum LockResult<G> {
+    /// The lock was acquired normally.
+    Acquired(G),
+    /// The lock was acquired, but only because its previous owner exited without releasing it. The state protected by
+    /// the lock might have been left half-modified and should be repaired.
+    OwnerDied(G),
+}
+impl<G> LockResult<G> {
+    /// Returns the guard, ignoring owner death.
+    #[inline]
+    pub fn into_guard(self) -> G {
+        match self {
+            Self::Acquired(g) | Self::OwnerDied(g) => g,
+        }
+    }
+    /// Returns `true` if the previous owner of the lock died while holding it.
+    #[inline]
+    pub fn owner_died(&self) -> bool {
+        matches!(self, Self::OwnerDied(..))
+    }
+    /// Applies a function to the guard, keeping the outcome.
+    #[inline]
+    pub fn map<T>(self, f: impl FnOnce(G) -> T) -> LockResult<T> {
+        match self {
+            Self::Acquired(g) => LockResult::Acquired(f(g)),
+            Self::OwnerDied(g) => LockResult::OwnerDied(f(g)),
+        }
+    }
+}
+
+/// A mutual exclusion lock shared by name between processes.
+///
+/// The mutex doesn't contain the data it protects, which typically lives in [shared
+/// memory](crate::shared_memory::SharedMemory) next to it. Locking is *not* reentrant: trying to lock a mutex that the
+/// current thread already holds fails with an error on Unix, and succeeds, requiring one more unlock, on Windows.
+///
+/// # Owner death
+/// If the process holding the lock exits without releasing it, the next lock attempt returns
+/// [`LockResult::OwnerDied`]. On Unix, the new owner then has to call
+/// [`mark_consistent()`](NamedMutexGuard::mark_consistent) after repairing the protected state – otherwise, the mutex
+/// becomes permanently unusable once the guard is dropped, and all further attempts to lock it fail with an error. On
+/// Windows, the mutex is usable again regardless.
+///
+/// Owner death is detected on Linux, FreeBSD and Windows, which support robust mutexes. On other platforms, the mutex
+/// stays locked forever if its owner dies while holding it.
+///
+/// # Lifetime
+/// On Unix, the mutex is stored in a shared memory object of its own, which persists until it's
+/// [unlinked](crate::shared_memory::unlink), unless it was created with
+/// [`create_with_drop_guard()`](Self::create_with_drop_guard). On Windows, the mutex is destroyed once the last handle
+/// to it is closed.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::{LockResult, NamedMutex};
+///
+/// let mutex = NamedMutex::open("Example")?;
+/// let guard = match mutex.lock()? {
+///     LockResult::Acquired(guard) => guard,
+///     LockResult::OwnerDied(guard) => {
+///         // Repair the shared state here...
+///         guard.mark_consistent()?;
+///         guard
+///     }
+/// };
+/// // Access the shared state here...
+/// drop(guard);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct NamedMutex(pub(crate) NamedMutexImpl);
+impl NamedMutex {
+    /// Creates a new unlocked mutex with the given name.
+    ///
+    /// An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if an object with the same name
+    /// exists already.
+    ///
+    /// # System calls
+    /// - `shm_open`, `ftruncate`, `mmap` and `pthread_mutex_init` on Unix
+    /// - `CreateMutexW` on Windows
+    pub fn create(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedMutexImpl::create(name.as_ref(), false).map(Self)
+    }
+    /// Creates a new mutex like [`create()`](Self::create), and installs a drop guard that will unlink its name once
+    /// the value is dropped.
+    ///
+    /// On Windows, the mutex is destroyed once its last handle is closed regardless, making this the same as
+    /// `create()`.
+    pub fn create_with_drop_guard(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedMutexImpl::create(name.as_ref(), true).map(Self)
+    }
+    /// Opens an existing mutex with the given name.
+    ///
+    /// # System calls
+    /// - `shm_open`, `fstat` and `mmap` on Unix
+    /// - `OpenMutexW` on Windows
+    pub fn open(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedMutexImpl::open(name.as_ref()).map(Self)
+    }
+    /// Acquires the lock, blocking until it becomes available.
+    ///
+    /// # System calls
+    /// - `pthread_mutex_lock` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn lock(&self) -> io::Result<LockResult<NamedMutexGuard<'_>>> {
+        self.0.lock().map(|r| NamedMutexGuard::from_lock_result(self, r))
+    }
+    /// Acquires the lock if it's available, failing with [`WouldBlock`](io::ErrorKind::WouldBlock) otherwise.
+    ///
+    /// # System calls
+    /// - `pthread_mutex_trylock` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn try_lock(&self) -> io::Result<LockResult<NamedMutexGuard<'_>>> {
+        self.0.try_lock().map(|r| NamedMutexGuard::from_lock_result(self, r))
+    }
+}
+impl Debug for NamedMutex {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_as_handle!(windows: NamedMutex);
+
+/// Holds the lock of a [`NamedMutex`] and releases it when dropped.
+///
+/// Since mutexes have to be released by the thread that acquired them, guards can't be sent to other threads.
+pub struct NamedMutexGuard<'a> {
+    mutex: &'a NamedMutex,
+    /// Whether the lock was acquired with [`LockResult::OwnerDied`] and the mutex hasn't been marked as consistent
+    /// since.
+    inconsistent: Cell<bool>,
+    _not_send: PhantomData<*const ()>,
+}
+impl<'a> NamedMutexGuard<'a> {
+    /// Creates a guard for a lock that was just acquired with the given outcome.
+    pub(super) fn from_lock_result(mutex: &'a NamedMutex, result: LockResult<()>) -> LockResult<Self> {
+        let inconsistent = result.owner_died();
+        result.map(|()| Self {
+            mutex,
+            inconsistent: Cell::new(inconsistent),
+            _not_send: PhantomData,
+        })
+    }
+    /// Gives up the guard without unlocking the mutex, for the lock to change hands inside a condition variable wait,
+    /// and returns whether the mutex has yet to be marked as consistent.
+    pub(super) fn forget(self) -> bool {
+        let inconsistent = self.inconsistent.get();
+        mem::forget(self);
+        inconsistent
+    }
+    /// Returns the mutex this guard holds the lock of.
+    #[inline]
+    pub fn mutex(&self) -> &'a NamedMutex {
+        self.mutex
+    }
+    /// Marks the state protected by the mutex as repaired after the previous owner died while holding the lock, which
+    /// keeps the mutex usable once this guard is dropped.
+    ///
+    /// Does nothing if the lock was acquired normally or the mutex has already been marked as consistent, as well as on
+    /// Windows and on platforms without robust mutexes.
+    ///
+    /// # System calls
+    /// - `pthread_mutex_consistent` on Linux and FreeBSD
+    pub fn mark_consistent(&self) -> io::Result<()> {
+        if self.inconsistent.get() {
+            self.mutex.0.mark_consistent()?;
+            self.inconsistent.set(false);
+        }
+        Ok(())
+    }
+}
+impl Drop for NamedMutexGuard<'_> {
+    fn drop(&mut self) {
+        self.mutex.0.unlock();
+    }
+}
+impl Debug for NamedMutexGuard<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedMutexGuard").field("mutex", &self.mutex).finish()
+    }
+}
@@ -0,0 +1,146 @@
+impmod! {named_event,
+    NamedEvent as NamedEventImpl,
+}
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+/// An [event](super::Event) shared by name between processes, for notifying unrelated processes that something has
+/// happened – for example, telling a running service to reload its configuration.
+///
+/// Like an unnamed event, a named event is either set or unset, and once [set](Self::set), it stays that way, releasing
+/// every current and future waiter, until it's explicitly [reset](Self::reset).
+///
+/// # Implementation
+/// On Windows, this is a named manual-reset event object, which is destroyed once the last handle to it is closed.
+///
+/// On Unix, the event is a flag in a shared memory object of its own, sharing the namespace of
+/// [shared memory](crate::shared_memory), which persists until it's [unlinked](Self::unlink), unless it was created
+/// with [`create_with_drop_guard()`](Self::create_with_drop_guard). Waiters [park](super::park) on the flag on
+/// platforms which support it, and poll it every few milliseconds elsewhere. Asynchronous waits are done by a thread
+/// from the blocking pool of the Tokio runtime on both Unix and Windows.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::NamedEvent;
+/// use std::{io, time::Duration};
+///
+/// // In the service:
+/// fn serve() -> io::Result<()> {
+///     let reload = NamedEvent::create_with_drop_guard("example-service-reload")?;
+///     loop {
+///         if reload.wait_timeout(Duration::from_secs(1))? {
+///             reload.reset()?;
+///             // Reload the configuration here...
+///         }
+///         // Do the actual work here...
+///     }
+/// }
+/// // In the tool which tells the service to reload:
+/// fn request_reload() -> io::Result<()> {
+///     NamedEvent::open("example-service-reload")?.set()
+/// }
+/// ```
+pub struct NamedEvent(pub(crate) NamedEventImpl);
+impl NamedEvent {
+    /// Creates a new event with the given name, which is not set.
+    ///
+    /// An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if an event with the same name
+    /// exists already.
+    ///
+    /// # System calls
+    /// - `shm_open`, `ftruncate` and `mmap` on Unix
+    /// - `CreateEventW` on Windows
+    pub fn create(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedEventImpl::create(name.as_ref(), false).map(Self)
+    }
+    /// Creates a new event like [`create()`](Self::create), and installs a drop guard that will unlink its name once
+    /// the value is dropped.
+    ///
+    /// On Windows, the event is destroyed once its last handle is closed regardless, making this the same as
+    /// `create()`.
+    pub fn create_with_drop_guard(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedEventImpl::create(name.as_ref(), true).map(Self)
+    }
+    /// Opens an existing event with the given name.
+    ///
+    /// # System calls
+    /// - `shm_open`, `fstat` and `mmap` on Unix
+    /// - `OpenEventW` on Windows
+    pub fn open(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedEventImpl::open(name.as_ref()).map(Self)
+    }
+    /// Sets the event, releasing all threads waiting on it in any process.
+    ///
+    /// # System calls
+    /// - `futex`, `_umtx_op` or `__ulock_wake` on Linux and Android, FreeBSD and macOS respectively
+    /// - `SetEvent` on Windows
+    pub fn set(&self) -> io::Result<()> {
+        self.0.set()
+    }
+    /// Resets the event, so that waits block until it's set again.
+    ///
+    /// # System calls
+    /// - `ResetEvent` on Windows
+    pub fn reset(&self) -> io::Result<()> {
+        self.0.reset()
+    }
+    /// Returns whether the event is currently set, without blocking.
+    ///
+    /// # System calls
+    /// - `WaitForSingleObject` on Windows
+    pub fn is_set(&self) -> io::Result<bool> {
+        self.0.is_set()
+    }
+    /// Blocks until the event is set. Returns immediately if it's set already.
+    ///
+    /// # System calls
+    /// - `futex`, `_umtx_op` or `__ulock_wait` on Linux and Android, FreeBSD and macOS respectively
+    /// - `WaitForSingleObject` on Windows
+    pub fn wait(&self) -> io::Result<()> {
+        self.0.wait()
+    }
+    /// Like [`wait()`](Self::wait), but gives up once the timeout runs out, returning `false` in that case.
+    ///
+    /// # System calls
+    /// - `futex`, `_umtx_op` or `__ulock_wait` on Linux and Android, FreeBSD and macOS respectively
+    /// - `WaitForSingleObject` on Windows
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        self.0.wait_timeout(Some(timeout))
+    }
+    /// Asynchronously waits until the event is set.
+    ///
+    /// The waiting is done by a thread from the blocking pool of the runtime. If the future is dropped before the event
+    /// is set, that thread keeps waiting for up to 100 milliseconds on Unix, and until the event is set on Windows.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime context.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+    pub async fn wait_async(&self) -> io::Result<()> {
+        self.0.wait_async().await
+    }
+    /// Removes the name of an event, so that it can no longer be opened. The event itself is destroyed once no process
+    /// has it open.
+    ///
+    /// This function is only available on Unix. On other platforms, it's absent and thus any usage of it will result in
+    /// a compile-time error.
+    ///
+    /// # System calls
+    /// - `shm_unlink`
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn unlink(name: impl AsRef<OsStr>) -> io::Result<()> {
+        crate::os::unix::shared_memory::unlink(name.as_ref())
+    }
+}
+impl Debug for NamedEvent {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_as_handle!(windows: NamedEvent);
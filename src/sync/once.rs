@@ -0,0 +1,205 @@
+use super::NamedMutex;
+use crate::shared_memory::SharedMemory;
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::{self, Debug, Formatter},
+    io,
+    mem::size_of,
+    sync::atomic::{
+        AtomicU32,
+        Ordering::{Acquire, Relaxed, Release},
+    },
+};
+
+const INCOMPLETE: u32 = 0;
+/// An initializer has started running, and either still is, or has failed.
+const RUNNING: u32 = 1;
+const COMPLETE: u32 = 2;
+
+/// A one-time initialization shared by name between processes, which makes sure that exactly one process successfully
+/// runs an initialization routine, such as setting up a shared cache, while the others wait for it to finish.
+///
+/// The first process to [call](Self::call_once) it runs its closure, and all other calls, from any process, block until
+/// that closure has returned, after which they return without running theirs. Once complete, calls return right away
+/// without any synchronization beyond an atomic load.
+///
+/// # Failed initializers
+/// If the closure panics, or the process running it crashes or gets killed, the initialization is not complete, and the
+/// next call runs its closure instead. That closure is told about the failed attempt through
+/// [`OnceState::is_poisoned()`], and should then be prepared to find the shared state half-initialized.
+///
+/// Crashes are detected with the help of [`NamedMutex`]'s owner death detection, which is only available on Linux,
+/// FreeBSD and Windows. On other platforms, a crashed initializer makes all other calls block forever; panics are
+/// handled everywhere as long as unwinding is enabled.
+///
+/// # Lifetime
+/// The once is made of a [`NamedMutex`] with the given name and a [`SharedMemory`] object with `.once` appended to it.
+/// On Unix, both persist until they're [unlinked](Self::unlink), unless the once was created with
+/// [`create_with_drop_guard()`](Self::create_with_drop_guard). On Windows, they're destroyed once the last handle to them
+/// is closed.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::SharedOnce;
+///
+/// let once = SharedOnce::open_or_create("example-cache-setup")?;
+/// once.call_once(|state| {
+///     if state.is_poisoned() {
+///         // Clean up after the initializer which crashed here...
+///     }
+///     // Set up the shared cache here...
+/// })?;
+/// // The cache is set up by now, whichever process did it.
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SharedOnce {
+    mutex: NamedMutex,
+    state: SharedMemory,
+}
+impl SharedOnce {
+    /// Creates a new once with the given name, which is not complete.
+    ///
+    /// An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if an object with the same name
+    /// exists already.
+    ///
+    /// # System calls
+    /// See [`SharedMemory::create()`] and [`NamedMutex::create()`].
+    pub fn create(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        let name = name.as_ref();
+        // The state is created first, so that it exists by the time the mutex can be opened.
+        let state = SharedMemory::create(state_name(name), size_of::<AtomicU32>())?;
+        let mutex = NamedMutex::create(name)?;
+        Ok(Self { mutex, state })
+    }
+    /// Creates a new once like [`create()`](Self::create), and installs drop guards that will unlink the names of the
+    /// objects it's made of once the value is dropped.
+    ///
+    /// On Windows, the objects are destroyed once their last handle is closed regardless, making this the same as
+    /// `create()`.
+    pub fn create_with_drop_guard(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        let name = name.as_ref();
+        let state = SharedMemory::create_with_drop_guard(state_name(name), size_of::<AtomicU32>())?;
+        let mutex = NamedMutex::create_with_drop_guard(name)?;
+        Ok(Self { mutex, state })
+    }
+    /// Opens an existing once with the given name.
+    ///
+    /// # System calls
+    /// See [`SharedMemory::open()`] and [`NamedMutex::open()`].
+    pub fn open(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        let name = name.as_ref();
+        let mutex = NamedMutex::open(name)?;
+        let state = SharedMemory::open(state_name(name))?;
+        if state.len() < size_of::<AtomicU32>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared memory object is too small to contain the state of a once",
+            ));
+        }
+        Ok(Self { mutex, state })
+    }
+    /// Opens the once with the given name, creating it if it doesn't exist yet.
+    ///
+    /// Since it's typically unknown which of the processes taking part in the initialization gets there first, this is
+    /// the usual way of obtaining a once. The once is created without drop guards.
+    pub fn open_or_create(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        let name = name.as_ref();
+        loop {
+            match Self::create(name) {
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                els => return els,
+            }
+            // Whoever created it might still be halfway through, or might have unlinked it again in the meantime.
+            match Self::open(name) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                els => return els,
+            }
+        }
+    }
+
+    #[inline]
+    fn word(&self) -> &AtomicU32 {
+        // SAFETY: the mapping is page-aligned and large enough, and only contains an atomic
+        unsafe { &*self.state.as_ptr().cast::<AtomicU32>() }
+    }
+
+    /// Returns `true` if some call to [`call_once()`](Self::call_once) has completed successfully.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.word().load(Acquire) == COMPLETE
+    }
+    /// Runs the closure if the initialization hasn't been completed yet, blocking while another thread or process is
+    /// running its own.
+    ///
+    /// Returns once the initialization is complete, either by this closure or another one. If this closure panics, the
+    /// panic is propagated after letting the next caller try instead.
+    ///
+    /// # System calls
+    /// See [`NamedMutex::lock()`]. Once the initialization is complete, there are none.
+    pub fn call_once(&self, f: impl FnOnce(&OnceState)) -> io::Result<()> {
+        if self.is_completed() {
+            return Ok(());
+        }
+        let result = self.mutex.lock()?;
+        let owner_died = result.owner_died();
+        let guard = result.into_guard();
+        if owner_died {
+            // The state word tells us everything we need to know, so the mutex is made usable again right away.
+            guard.mark_consistent()?;
+        }
+        let poisoned = match self.word().load(Acquire) {
+            COMPLETE => return Ok(()),
+            INCOMPLETE => false,
+            _ => true,
+        };
+        self.word().store(RUNNING, Relaxed);
+        // If this panics, the guard is dropped while the state still says RUNNING, which poisons the once.
+        f(&OnceState { poisoned });
+        self.word().store(COMPLETE, Release);
+        drop(guard);
+        Ok(())
+    }
+
+    /// Removes the names of the objects making up the once with the given name, so that it can no longer be opened.
+    ///
+    /// This function is only available on Unix. On other platforms, it's absent and thus any usage of it will result in
+    /// a compile-time error.
+    ///
+    /// # System calls
+    /// - `shm_unlink`
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn unlink(name: impl AsRef<OsStr>) -> io::Result<()> {
+        let name = name.as_ref();
+        crate::shared_memory::unlink(name)?;
+        crate::shared_memory::unlink(state_name(name))
+    }
+}
+impl Debug for SharedOnce {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedOnce")
+            .field("mutex", &self.mutex)
+            .field("completed", &self.is_completed())
+            .finish()
+    }
+}
+
+/// The state of a [`SharedOnce`], as seen by the closure passed to [`call_once()`](SharedOnce::call_once).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OnceState {
+    poisoned: bool,
+}
+impl OnceState {
+    /// Returns `true` if an earlier initializer panicked or its process died while running it, which might have left
+    /// the shared state half-initialized.
+    #[inline]
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+}
+
+fn state_name(name: &OsStr) -> OsString {
+    let mut state_name = name.to_owned();
+    state_name.push(".once");
+    state_name
+}
@@ -0,0 +1,82 @@
+impmod! {park,
+    park as park_impl,
+    unpark as unpark_impl,
+}
+use std::{io, sync::atomic::AtomicU32, time::Duration};
+
+/// Blocks the current thread for as long as `word` holds the value `expected`, until another thread or process wakes
+/// it up with [`unpark_one()`] or [`unpark_all()`].
+///
+/// The comparison and the going to sleep happen atomically with respect to unparking, and so a thread which changes the
+/// word and then unparks its waiters can't miss one that's about to go to sleep. If the word holds a different value
+/// to begin with, this returns immediately.
+///
+/// This is the futex pattern: `word` is typically located in [shared memory](crate::shared_memory::SharedMemory), and
+/// a process wanting to wait for a condition checks the word, parks if it isn't met yet, and checks it again once
+/// woken up. Wakeups can be spurious, and so the word has to be checked again in a loop regardless.
+///
+/// On Windows, `WaitOnAddress` can only be woken up from within the same process, and so waits are split into slices of
+/// up to 16 milliseconds, after each of which the value of the word is checked again. Wakeups from other processes are
+/// thus delayed by up to that much, and only happen if the word was changed, rather than just unparked.
+///
+/// This function is only available on Linux, Android, FreeBSD, macOS and Windows. On other platforms, it's absent and
+/// thus any usage of it will result in a compile-time error.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::{park, unpark_all};
+/// use std::sync::atomic::{AtomicU32, Ordering::*};
+///
+/// fn wait_for_flag(flag: &AtomicU32) -> std::io::Result<()> {
+///     while flag.load(Acquire) == 0 {
+///         park(flag, 0)?;
+///     }
+///     Ok(())
+/// }
+/// fn set_flag(flag: &AtomicU32) -> std::io::Result<()> {
+///     flag.store(1, Release);
+///     unpark_all(flag)
+/// }
+/// ```
+///
+/// # System calls
+/// - `futex` (`FUTEX_WAIT`) on Linux and Android
+/// - `_umtx_op` (`UMTX_OP_WAIT_UINT`) on FreeBSD
+/// - `__ulock_wait` (`UL_COMPARE_AND_WAIT_SHARED`) on macOS
+/// - `WaitOnAddress` on Windows
+pub fn park(word: &AtomicU32, expected: u32) -> io::Result<()> {
+    park_impl(word, expected, None).map(|_| ())
+}
+/// Like [`park()`], but gives up once the timeout runs out, returning `false` in that case.
+///
+/// # System calls
+/// - `futex` (`FUTEX_WAIT`) on Linux and Android
+/// - `_umtx_op` (`UMTX_OP_WAIT_UINT`) on FreeBSD
+/// - `__ulock_wait` (`UL_COMPARE_AND_WAIT_SHARED`) on macOS
+/// - `WaitOnAddress` on Windows
+pub fn park_timeout(word: &AtomicU32, expected: u32, timeout: Duration) -> io::Result<bool> {
+    park_impl(word, expected, Some(timeout))
+}
+/// Wakes up one of the threads [parked](park) on `word`, if there are any.
+///
+/// On Windows, waiters in other processes which notice the word being changed wake up regardless, and so more than one
+/// of them may end up being woken up.
+///
+/// # System calls
+/// - `futex` (`FUTEX_WAKE`) on Linux and Android
+/// - `_umtx_op` (`UMTX_OP_WAKE`) on FreeBSD
+/// - `__ulock_wake` on macOS
+/// - `WakeByAddressSingle` on Windows
+pub fn unpark_one(word: &AtomicU32) -> io::Result<()> {
+    unpark_impl(word, false)
+}
+/// Wakes up all threads [parked](park) on `word`.
+///
+/// # System calls
+/// - `futex` (`FUTEX_WAKE`) on Linux and Android
+/// - `_umtx_op` (`UMTX_OP_WAKE`) on FreeBSD
+/// - `__ulock_wake` (`ULF_WAKE_ALL`) on macOS
+/// - `WakeByAddressAll` on Windows
+pub fn unpark_all(word: &AtomicU32) -> io::Result<()> {
+    unpark_impl(word, true)
+}
@@ -0,0 +1,178 @@
+impmod! {named_rwlock,
+    NamedRwLock as NamedRwLockImpl,
+}
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    marker::PhantomData,
+};
+
+/// A reader-writer lock shared by name between processes, which lets many readers share data while giving writers
+/// exclusive access to it.
+///
+/// Like [`NamedMutex`](super::NamedMutex), the lock doesn't contain the data it protects, which typically lives in
+/// [shared memory](crate::shared_memory::SharedMemory). Whether readers or writers are preferred when both are waiting
+/// is unspecified, and a steady stream of readers may starve writers. Locking is not reentrant: trying to take the lock
+/// for writing while the current thread already holds it either fails with an error or deadlocks.
+///
+/// Unlike mutexes, reader-writer locks are *not* robust: if a process dies while holding the lock, the lock is never
+/// released.
+///
+/// # Lifetime
+/// On Unix, this is a process-shared pthread reader-writer lock, stored in a shared memory object of its own which
+/// persists until it's [unlinked](crate::shared_memory::unlink), unless it was created with
+/// [`create_with_drop_guard()`](Self::create_with_drop_guard). On Windows, where SRW locks don't work across
+/// processes, it's emulated with an atomic state word in a shared memory object and a named semaphore to block on, both
+/// of which are destroyed once the last handle to them is closed.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::NamedRwLock;
+///
+/// let lock = NamedRwLock::open("Example")?;
+/// {
+///     let _guard = lock.read()?;
+///     // Read the shared data here...
+/// }
+/// {
+///     let _guard = lock.write()?;
+///     // Modify the shared data here...
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct NamedRwLock(pub(crate) NamedRwLockImpl);
+impl NamedRwLock {
+    /// Creates a new unlocked reader-writer lock with the given name.
+    ///
+    /// An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if an object with the same name
+    /// exists already.
+    ///
+    /// # System calls
+    /// - `shm_open`, `ftruncate`, `mmap` and `pthread_rwlock_init` on Unix
+    /// - `CreateSemaphoreW`, `CreateFileMappingW` and `MapViewOfFile` on Windows
+    pub fn create(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedRwLockImpl::create(name.as_ref(), false).map(Self)
+    }
+    /// Creates a new reader-writer lock like [`create()`](Self::create), and installs a drop guard that will unlink
+    /// its name once the value is dropped.
+    ///
+    /// On Windows, the lock is destroyed once its last handle is closed regardless, making this the same as
+    /// `create()`.
+    pub fn create_with_drop_guard(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedRwLockImpl::create(name.as_ref(), true).map(Self)
+    }
+    /// Opens an existing reader-writer lock with the given name.
+    ///
+    /// # System calls
+    /// - `shm_open`, `fstat` and `mmap` on Unix
+    /// - `OpenFileMappingW`, `MapViewOfFile` and `OpenSemaphoreW` on Windows
+    pub fn open(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedRwLockImpl::open(name.as_ref()).map(Self)
+    }
+    /// Acquires the lock for reading, blocking until no writer holds it.
+    ///
+    /// # System calls
+    /// - `pthread_rwlock_rdlock` on Unix
+    /// - `WaitForSingleObject` on Windows, if the lock is contended
+    pub fn read(&self) -> io::Result<NamedRwLockReadGuard<'_>> {
+        self.0.read().map(|()| NamedRwLockReadGuard::new(self))
+    }
+    /// Acquires the lock for reading if no writer holds it, failing with [`WouldBlock`](io::ErrorKind::WouldBlock)
+    /// otherwise.
+    ///
+    /// # System calls
+    /// - `pthread_rwlock_tryrdlock` on Unix
+    pub fn try_read(&self) -> io::Result<NamedRwLockReadGuard<'_>> {
+        self.0.try_read().map(|()| NamedRwLockReadGuard::new(self))
+    }
+    /// Acquires the lock for writing, blocking until nobody else holds it.
+    ///
+    /// # System calls
+    /// - `pthread_rwlock_wrlock` on Unix
+    /// - `WaitForSingleObject` on Windows, if the lock is contended
+    pub fn write(&self) -> io::Result<NamedRwLockWriteGuard<'_>> {
+        self.0.write().map(|()| NamedRwLockWriteGuard::new(self))
+    }
+    /// Acquires the lock for writing if nobody else holds it, failing with [`WouldBlock`](io::ErrorKind::WouldBlock)
+    /// otherwise.
+    ///
+    /// # System calls
+    /// - `pthread_rwlock_trywrlock` on Unix
+    pub fn try_write(&self) -> io::Result<NamedRwLockWriteGuard<'_>> {
+        self.0.try_write().map(|()| NamedRwLockWriteGuard::new(self))
+    }
+}
+impl Debug for NamedRwLock {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+/// Holds a [`NamedRwLock`] for reading and releases it when dropped.
+///
+/// Since pthread reader-writer locks have to be released by the thread that acquired them, guards can't be sent to
+/// other threads.
+pub struct NamedRwLockReadGuard<'a> {
+    lock: &'a NamedRwLock,
+    _not_send: PhantomData<*const ()>,
+}
+impl<'a> NamedRwLockReadGuard<'a> {
+    fn new(lock: &'a NamedRwLock) -> Self {
+        Self {
+            lock,
+            _not_send: PhantomData,
+        }
+    }
+    /// Returns the lock this guard holds.
+    #[inline]
+    pub fn lock(&self) -> &'a NamedRwLock {
+        self.lock
+    }
+}
+impl Drop for NamedRwLockReadGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.0.read_unlock();
+    }
+}
+impl Debug for NamedRwLockReadGuard<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedRwLockReadGuard")
+            .field("lock", &self.lock)
+            .finish()
+    }
+}
+
+/// Holds a [`NamedRwLock`] for writing and releases it when dropped.
+///
+/// Same as with read guards, write guards can't be sent to other threads.
+pub struct NamedRwLockWriteGuard<'a> {
+    lock: &'a NamedRwLock,
+    _not_send: PhantomData<*const ()>,
+}
+impl<'a> NamedRwLockWriteGuard<'a> {
+    fn new(lock: &'a NamedRwLock) -> Self {
+        Self {
+            lock,
+            _not_send: PhantomData,
+        }
+    }
+    /// Returns the lock this guard holds.
+    #[inline]
+    pub fn lock(&self) -> &'a NamedRwLock {
+        self.lock
+    }
+}
+impl Drop for NamedRwLockWriteGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.0.write_unlock();
+    }
+}
+impl Debug for NamedRwLockWriteGuard<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedRwLockWriteGuard")
+            .field("lock", &self.lock)
+            .finish()
+    }
+}
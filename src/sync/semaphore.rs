@@ -0,0 +1,119 @@
+impmod! {named_semaphore,
+    NamedSemaphore as NamedSemaphoreImpl,
+}
+use std::{
+    ffi::OsStr,
+    fmt::{self, Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+/// A counting semaphore shared by name between processes, typically used to limit the number of processes that can use
+/// a resource at the same time.
+///
+/// The semaphore holds a number of permits. [Acquiring](Self::acquire) a permit decrements the count, blocking while
+/// it's zero, and [releasing](Self::release) permits increments it, waking up the processes waiting for them. Unlike
+/// with a mutex, permits aren't tied to the thread or process that acquired them and can be released by anyone.
+///
+/// Permits held by a process that exits are *not* released automatically.
+///
+/// On Unix, this is a POSIX named semaphore, which persists until it's [unlinked](Self::unlink), unless it was created
+/// with [`create_with_drop_guard()`](Self::create_with_drop_guard). On Windows, it's a named semaphore object, which is
+/// destroyed once the last handle to it is closed.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::sync::NamedSemaphore;
+///
+/// // At most four processes at a time get to do the expensive thing.
+/// let semaphore = NamedSemaphore::open("Example")?;
+/// semaphore.acquire()?;
+/// // Do the expensive thing here...
+/// semaphore.release(1)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct NamedSemaphore(pub(crate) NamedSemaphoreImpl);
+impl NamedSemaphore {
+    /// Creates a new semaphore with the given name and initial number of permits.
+    ///
+    /// An error of kind [`AlreadyExists`](io::ErrorKind::AlreadyExists) is returned if a semaphore with the same name
+    /// exists already. The maximum number of permits is platform-dependent, but no less than 32767.
+    ///
+    /// # System calls
+    /// - `sem_open` on Unix
+    /// - `CreateSemaphoreW` on Windows
+    pub fn create(name: impl AsRef<OsStr>, initial: u32) -> io::Result<Self> {
+        NamedSemaphoreImpl::create(name.as_ref(), initial, false).map(Self)
+    }
+    /// Creates a new semaphore like [`create()`](Self::create), and installs a drop guard that will unlink its name
+    /// once the value is dropped.
+    ///
+    /// On Windows, the semaphore is destroyed once its last handle is closed regardless, making this the same as
+    /// `create()`.
+    pub fn create_with_drop_guard(name: impl AsRef<OsStr>, initial: u32) -> io::Result<Self> {
+        NamedSemaphoreImpl::create(name.as_ref(), initial, true).map(Self)
+    }
+    /// Opens an existing semaphore with the given name.
+    ///
+    /// # System calls
+    /// - `sem_open` on Unix
+    /// - `OpenSemaphoreW` on Windows
+    pub fn open(name: impl AsRef<OsStr>) -> io::Result<Self> {
+        NamedSemaphoreImpl::open(name.as_ref()).map(Self)
+    }
+    /// Acquires a permit, blocking until one becomes available.
+    ///
+    /// # System calls
+    /// - `sem_wait` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn acquire(&self) -> io::Result<()> {
+        self.0.acquire()
+    }
+    /// Acquires a permit if one is available, failing with [`WouldBlock`](io::ErrorKind::WouldBlock) otherwise.
+    ///
+    /// # System calls
+    /// - `sem_trywait` on Unix
+    /// - `WaitForSingleObject` on Windows
+    pub fn try_acquire(&self) -> io::Result<()> {
+        self.0.try_acquire()
+    }
+    /// Acquires a permit, blocking until one becomes available or the timeout runs out, returning `false` in the
+    /// latter case.
+    ///
+    /// On Apple platforms, which lack a timed wait on semaphores, the semaphore is polled instead.
+    ///
+    /// # System calls
+    /// - `sem_timedwait` on Unix, or `sem_trywait` on Apple platforms
+    /// - `WaitForSingleObject` on Windows
+    pub fn acquire_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        self.0.acquire_timeout(timeout)
+    }
+    /// Releases `n` permits, waking up to `n` waiters.
+    ///
+    /// # System calls
+    /// - `sem_post` (`n` times) on Unix
+    /// - `ReleaseSemaphore` on Windows
+    pub fn release(&self, n: u32) -> io::Result<()> {
+        self.0.release(n)
+    }
+    /// Removes the name of a semaphore, so that it can no longer be opened. The semaphore itself is destroyed once no
+    /// process has it open.
+    ///
+    /// This function is only available on Unix. On other platforms, it's absent and thus any usage of it will result in
+    /// a compile-time error.
+    ///
+    /// # System calls
+    /// - `sem_unlink`
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn unlink(name: impl AsRef<OsStr>) -> io::Result<()> {
+        crate::os::unix::named_semaphore::unlink(name.as_ref())
+    }
+}
+impl Debug for NamedSemaphore {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_as_handle!(windows: NamedSemaphore);
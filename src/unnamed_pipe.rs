@@ -9,14 +9,26 @@
 //! Another way to use unnamed pipes is to use a named pipe or a Unix domain socket to establish an unnamed pipe
 //! connection. It just so happens that this crate supports all three.
 
+#[cfg(feature = "tokio")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "tokio")))]
+pub mod tokio;
+
+mod command;
+pub use command::*;
+
 impmod! {unnamed_pipe,
     UnnamedPipeReader as UnnamedPipeReaderImpl,
     UnnamedPipeWriter as UnnamedPipeWriterImpl,
     pipe as pipe_impl,
+    pipe_with_options as pipe_with_options_impl,
 }
 use std::{
     fmt::{self, Formatter},
-    io::{self, Read, Write},
+    fs::File,
+    io::{self, IoSlice, Read, Write},
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    time::Duration,
 };
 
 /// Creates a new pipe with the default creation settings and returns the handles to its writing end and reading end.
@@ -27,10 +39,117 @@ pub fn pipe() -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)> {
     pipe_impl()
 }
 
+/// The maximum size of a packet in a pipe created in [packet mode](PipeOptions::packet_mode), which is `PIPE_BUF`.
+///
+/// This constant is only available on Linux. On other platforms, it's absent and thus any usage of it will result in
+/// a compile-time error.
+#[cfg(target_os = "linux")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+pub const MAX_PACKET_SIZE: usize = libc::PIPE_BUF;
+
+/// Cross-platform options for creating unnamed pipes.
+///
+/// Unlike [`pipe()`], which uses the platform's defaults – inheritable file descriptors on Unix, non-inheritable
+/// handles on Windows – this builder behaves the same way everywhere, with both ends of the pipe being
+/// non-inheritable and in blocking mode by default.
+///
+/// # Example
+/// ```
+/// use interprocess::unnamed_pipe::PipeOptions;
+///
+/// let (writer, reader) = PipeOptions::new()
+///     .inheritable(false)
+///     .nonblocking(true)
+///     .create()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct PipeOptions {
+    /// Specifies whether both ends of the pipe can be inherited by child processes. Disabled by default.
+    ///
+    /// On Unix, this controls the close-on-exec flag (`O_CLOEXEC`), which is set if inheritance is disabled. On
+    /// Windows, this is the `bInheritHandle` field of the security attributes the pipe is created with.
+    pub inheritable: bool,
+    /// Specifies whether both ends of the pipe are put in nonblocking mode upon creation. Disabled by default.
+    ///
+    /// In nonblocking mode, reading from an empty pipe or writing into a full one fails with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) instead of waiting. On Unix, this is `O_NONBLOCK`; on Windows,
+    /// `PIPE_NOWAIT` is set with `SetNamedPipeHandleState`.
+    pub nonblocking: bool,
+    /// Specifies whether the pipe operates in packet mode, in which every write is delivered as a discrete packet
+    /// instead of being merged with adjacent writes into a byte stream. Disabled by default. Maps to `O_DIRECT`.
+    ///
+    /// See [`UnnamedPipeWriter::write_packet()`] and [`UnnamedPipeReader::read_packet()`] for how packets are sent and
+    /// received.
+    ///
+    /// This option is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    pub packet_mode: bool,
+}
+impl PipeOptions {
+    /// Creates a new builder with default options.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inheritable: false,
+            nonblocking: false,
+            #[cfg(target_os = "linux")]
+            packet_mode: false,
+        }
+    }
+    /// Specifies whether both ends of the pipe can be inherited by child processes.
+    ///
+    /// See the [associated field](#structfield.inheritable) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn inheritable(mut self, inheritable: bool) -> Self {
+        self.inheritable = inheritable;
+        self
+    }
+    /// Specifies whether both ends of the pipe are put in nonblocking mode upon creation.
+    ///
+    /// See the [associated field](#structfield.nonblocking) for more.
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn nonblocking(mut self, nonblocking: bool) -> Self {
+        self.nonblocking = nonblocking;
+        self
+    }
+    /// Specifies whether the pipe operates in packet mode.
+    ///
+    /// See the [associated field](#structfield.packet_mode) for more.
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    #[must_use = "this is not an in-place operation"]
+    #[inline]
+    pub fn packet_mode(mut self, packet_mode: bool) -> Self {
+        self.packet_mode = packet_mode;
+        self
+    }
+    /// Creates the pipe and returns its writing and reading ends.
+    ///
+    /// # System calls
+    /// - `pipe2` on platforms that have it, `pipe` followed by `fcntl` on other Unix systems
+    /// - `CreatePipe` on Windows, followed by `SetNamedPipeHandleState` in nonblocking mode
+    pub fn create(&self) -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)> {
+        pipe_with_options_impl(self)
+    }
+}
+impl Default for PipeOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// A handle to the reading end of an unnamed pipe, created by the [`pipe()`] function together with the
 /// [writing end](UnnamedPipeWriter).
 ///
-/// The core functionality is exposed in a file-like [`Read`] interface. On Windows, the
+/// The core functionality is exposed in a file-like [`Read`] interface, which is also implemented for shared
+/// references, allowing the reader to be used concurrently from behind an [`Arc`](std::sync::Arc). On Windows, the
 /// [`ShareHandle`](crate::os::windows::ShareHandle) and [`As-`][ARH]/[`Into-`][IRH]/[`FromRawHandle`][FRH] traits are
 /// also implemented, along with [`As-`][ARF]/[`Into-`][IRF]/[`FromRawFd`][FRF] on Unix.
 ///
@@ -42,9 +161,136 @@ pub fn pipe() -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)> {
 /// [FRF]: https://doc.rust-lang.org/std/os/unix/io/trait.FromRawFd.html
 // field is pub(crate) to allow the platform specific builders to create the public-facing pipe types
 pub struct UnnamedPipeReader(pub(crate) UnnamedPipeReaderImpl);
+impl UnnamedPipeReader {
+    /// Returns the amount of bytes which are currently in the pipe and can be read without blocking.
+    ///
+    /// A nonzero value means that the next read will not block. Zero, however, can mean either that the pipe is empty
+    /// and a read would block, or that the writing end has been closed and a read would return end of file.
+    ///
+    /// # System calls
+    /// - `ioctl` with `FIONREAD` on Unix
+    /// - `PeekNamedPipe` on Windows
+    #[inline]
+    pub fn bytes_available(&self) -> io::Result<usize> {
+        self.0.bytes_available()
+    }
+    /// Sets the read timeout to the specified value, or disables it if `None` is passed. By default, reads wait
+    /// indefinitely.
+    ///
+    /// If the timeout is set, a read which finds the pipe empty waits for data for up to the specified duration and
+    /// then fails with an error of kind [`TimedOut`](io::ErrorKind::TimedOut). The timeout applies to every individual
+    /// read, not to a sequence of them, such as the one performed by [`read_exact()`](Read::read_exact).
+    ///
+    /// # Errors
+    /// An error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if the duration is zero. Use
+    /// [nonblocking mode](Self::set_nonblocking) instead.
+    ///
+    /// # System calls
+    /// - `poll` on Unix, before every read
+    /// - `PeekNamedPipe` on Windows, repeatedly before every read until data arrives or the timeout expires
+    #[inline]
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.0.read_timeout().set(timeout)
+    }
+    /// Returns the read timeout. See [`set_read_timeout()`](Self::set_read_timeout).
+    #[inline]
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.0.read_timeout().get()
+    }
+    /// Enables or disables the nonblocking mode for the reading end of the pipe. This is what [`PipeOptions::nonblocking`]
+    /// sets at creation time.
+    ///
+    /// In nonblocking mode, reading from an empty pipe immediately returns with the [`WouldBlock`](io::ErrorKind::WouldBlock) error
+    /// instead of waiting. This is required when the pipe is registered with a readiness-based event loop, such as one
+    /// built with `mio` or `async-io`.
+    ///
+    /// # System calls
+    /// - `fcntl` on Unix
+    /// - `SetNamedPipeHandleState` on Windows
+    #[inline]
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.0.set_nonblocking(nonblocking)
+    }
+    /// Returns a handle which can be passed to `WaitForSingleObject`, `WaitForMultipleObjects` or
+    /// `RegisterWaitForSingleObject` to multiplex the pipe with other waitable objects in a custom event loop.
+    ///
+    /// Windows does not track readiness for anonymous pipes: the handle is only signaled once an I/O operation on it
+    /// completes. Pair this with nonblocking mode and [`bytes_available()`](Self::bytes_available()) to find out whether
+    /// data can be read without blocking.
+    ///
+    /// This method is only available on Windows. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    #[inline]
+    pub fn waitable_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+        std::os::windows::io::AsHandle::as_handle(self)
+    }
+    /// Receives one packet from a pipe created in [packet mode](PipeOptions::packet_mode), returning its size.
+    ///
+    /// If the packet is larger than the buffer, the rest of it is discarded. Since packets can be at most
+    /// [`MAX_PACKET_SIZE`] bytes long, a buffer of that size is always sufficient.
+    ///
+    /// On pipes not created in packet mode, this is equivalent to [`read()`](Read::read).
+    ///
+    /// This method is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    #[inline]
+    pub fn read_packet(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // In packet mode, every read consumes exactly one packet.
+        self.read(buf)
+    }
+    /// Moves up to `len` bytes from the pipe into the given file descriptor without copying them through userspace,
+    /// returning the amount of bytes moved. Zero is returned if the writing end of the pipe has been closed.
+    ///
+    /// The file descriptor can refer to any file, socket or pipe which the data is to be written into.
+    ///
+    /// This method is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `splice`
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    #[inline]
+    pub fn splice_to(&mut self, fd: impl std::os::fd::AsFd, len: usize) -> io::Result<usize> {
+        self.0.splice_to(fd.as_fd(), len)
+    }
+    /// Duplicates up to `len` bytes from this pipe into the pipe of the given writer without consuming them, returning
+    /// the amount of bytes duplicated. The data remains available for reading from this pipe afterwards.
+    ///
+    /// This method is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `tee`
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    #[inline]
+    pub fn tee(&self, writer: &UnnamedPipeWriter, len: usize) -> io::Result<usize> {
+        self.0.tee(&writer.0, len)
+    }
+}
 impl Read for UnnamedPipeReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (self as &Self).read(buf)
+    }
+    #[cfg(feature = "read_buf")]
+    #[inline]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        (self as &Self).read_buf(cursor)
+    }
+}
+impl Read for &UnnamedPipeReader {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.0.read(buf)
+        (&self.0).read(buf)
+    }
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        (&self.0).read_buf(cursor)
     }
 }
 impl fmt::Debug for UnnamedPipeReader {
@@ -55,11 +301,16 @@ impl fmt::Debug for UnnamedPipeReader {
 forward_handle!(UnnamedPipeReader);
 forward_try_clone!(UnnamedPipeReader);
 derive_raw!(UnnamedPipeReader);
+derive_mio_source!(UnnamedPipeReader);
+// SAFETY: the file descriptor cannot be replaced or closed through a mutable reference.
+#[cfg(all(unix, feature = "async-io"))]
+unsafe impl async_io::IoSafe for UnnamedPipeReader {}
 
 /// A handle to the writing end of an unnamed pipe, created by the [`pipe()`] function together with the
 /// [reading end](UnnamedPipeReader).
 ///
-/// The core functionality is exposed in a file-like [`Write`] interface. On Windows, the
+/// The core functionality is exposed in a file-like [`Write`] interface, which is also implemented for shared
+/// references, allowing the writer to be used concurrently from behind an [`Arc`](std::sync::Arc). On Windows, the
 /// [`ShareHandle`](crate::os::windows::ShareHandle) and [`As-`][ARH]/[`Into-`][IRH]/[`FromRawHandle`][FRH] traits are
 /// also implemented, along with [`As-`][ARF]/[`Into-`][IRF]/[`FromRawFd`][FRF] on Unix.
 ///
@@ -71,12 +322,145 @@ derive_raw!(UnnamedPipeReader);
 /// [IntoRawFd]: https://doc.rust-lang.org/std/os/unix/io/trait.IntoRawFd.html
 /// [FromRawFd]: https://doc.rust-lang.org/std/os/unix/io/trait.FromRawFd.html
 pub struct UnnamedPipeWriter(pub(crate) UnnamedPipeWriterImpl);
+impl UnnamedPipeWriter {
+    /// Enables or disables the nonblocking mode for the writing end of the pipe. This is what [`PipeOptions::nonblocking`]
+    /// sets at creation time.
+    ///
+    /// In nonblocking mode, writing into a full pipe immediately returns with the [`WouldBlock`](io::ErrorKind::WouldBlock) error
+    /// instead of waiting. This is required when the pipe is registered with a readiness-based event loop, such as one
+    /// built with `mio` or `async-io`.
+    ///
+    /// # System calls
+    /// - `fcntl` on Unix
+    /// - `SetNamedPipeHandleState` on Windows
+    #[inline]
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.0.set_nonblocking(nonblocking)
+    }
+    /// Returns a handle which can be passed to `WaitForSingleObject`, `WaitForMultipleObjects` or
+    /// `RegisterWaitForSingleObject` to multiplex the pipe with other waitable objects in a custom event loop.
+    ///
+    /// Windows does not track readiness for anonymous pipes: the handle is only signaled once an I/O operation on it
+    /// completes. Pair this with nonblocking mode and [`write()`](Self::write()) to find out whether
+    /// X without blocking.
+    ///
+    /// This method is only available on Windows. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    #[inline]
+    pub fn waitable_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+        std::os::windows::io::AsHandle::as_handle(self)
+    }
+    /// Sends `data` as one packet through a pipe created in [packet mode](PipeOptions::packet_mode).
+    ///
+    /// # Errors
+    /// In addition to regular OS errors, an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if
+    /// `data` is longer than [`MAX_PACKET_SIZE`], since the system would split it into multiple packets.
+    ///
+    /// This method is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    pub fn write_packet(&mut self, data: &[u8]) -> io::Result<()> {
+        if data.len() > MAX_PACKET_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "packet exceeds the maximum packet size of the pipe",
+            ));
+        }
+        // Writes of up to PIPE_BUF bytes are atomic, so there's no such thing as a partial packet.
+        self.write(data).map(drop)
+    }
+    /// Moves up to `len` bytes from the given file descriptor into the pipe without copying them through userspace,
+    /// returning the amount of bytes moved. Zero is returned if the file descriptor is at end of file.
+    ///
+    /// The file descriptor can refer to any file, socket or pipe which the data is to be read from.
+    ///
+    /// This method is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `splice`
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    #[inline]
+    pub fn splice_from(&mut self, fd: impl std::os::fd::AsFd, len: usize) -> io::Result<usize> {
+        self.0.splice_from(fd.as_fd(), len)
+    }
+    /// Maps the memory of `data` into the pipe instead of copying it, returning the amount of bytes written. Like with
+    /// [`write()`](Write::write), fewer bytes than requested may be written if the pipe fills up.
+    ///
+    /// This speeds up large transfers, such as from a parent process to a child, by letting the kernel refer to the
+    /// pages of the buffer directly. The pages are gifted to the kernel, which lets a reader using
+    /// [`splice_to()`](UnnamedPipeReader::splice_to) move them on without copying them either; only page-aligned
+    /// buffers spanning whole pages benefit from this, but others work just as well otherwise.
+    ///
+    /// This method is only available on Linux. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # Safety
+    /// The data is not copied when this method returns, but when the reader reads it out of the pipe, which may be
+    /// arbitrarily later, and the whole pages it spans are handed over to the kernel, which may keep referring to them
+    /// even after that if the reader splices them into a file. The memory of `data` must thus never be modified or
+    /// freed again, which is best ensured by using buffers which are leaked or `static` and immutable. Otherwise, the
+    /// reader may receive whatever was written there in the meantime instead, and files the data was spliced into may
+    /// end up with contents which differ from what was read.
+    ///
+    /// # System calls
+    /// - `vmsplice`
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(target_os = "linux")))]
+    #[inline]
+    pub unsafe fn write_gifted(&mut self, data: &[u8]) -> io::Result<usize> {
+        unsafe { self.0.write_gifted(data) }
+    }
+    /// Sends the given byte range of a file through the pipe, returning the amount of bytes sent. Fewer bytes than
+    /// requested are sent if the file ends before the end of the range.
+    ///
+    /// On Linux, the data is copied by the kernel without passing through userspace. On other platforms, the file is
+    /// read in chunks into an intermediate buffer, which are then written into the pipe.
+    ///
+    /// The file is read at the offsets specified by the range, which means that its cursor is not used. On Unix, the
+    /// cursor is left untouched; on Windows, it's moved to the end of the last chunk that was read.
+    ///
+    /// # Errors
+    /// If the pipe is in nonblocking mode and fills up after some of the data has been sent, the amount of bytes sent
+    /// up to that point is returned instead of a [`WouldBlock`](io::ErrorKind::WouldBlock) error. Any other error is
+    /// returned as-is, with the amount of bytes sent before it being lost.
+    ///
+    /// # System calls
+    /// - `sendfile` on Linux
+    /// - `pread` on other Unix platforms
+    /// - `ReadFile` on Windows
+    /// - `write`/`WriteFile` on platforms other than Linux
+    pub fn send_file(&mut self, file: &File, range: Range<u64>) -> io::Result<u64> {
+        self.0.send_file(file, range)
+    }
+}
 impl Write for UnnamedPipeWriter {
+    #[inline]
     fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-        self.0.write(data)
+        (self as &Self).write(data)
     }
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (self as &Self).write_vectored(bufs)
+    }
+    #[inline]
     fn flush(&mut self) -> io::Result<()> {
-        self.0.flush()
+        (self as &Self).flush()
+    }
+}
+impl Write for &UnnamedPipeWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        (&self.0).write(data)
+    }
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (&self.0).write_vectored(bufs)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        (&self.0).flush()
     }
 }
 impl fmt::Debug for UnnamedPipeWriter {
@@ -87,3 +471,78 @@ impl fmt::Debug for UnnamedPipeWriter {
 forward_handle!(UnnamedPipeWriter);
 forward_try_clone!(UnnamedPipeWriter);
 derive_raw!(UnnamedPipeWriter);
+derive_mio_source!(UnnamedPipeWriter);
+// SAFETY: as above.
+#[cfg(all(unix, feature = "async-io"))]
+unsafe impl async_io::IoSafe for UnnamedPipeWriter {}
+
+/// The read timeout of an unnamed pipe reader, which can be changed through a shared reference.
+#[derive(Debug, Default)]
+pub(crate) struct ReadTimeout(
+    // In nanoseconds. Zero means that there is no timeout, which is unambiguous since zero timeouts are rejected.
+    AtomicU64,
+);
+impl ReadTimeout {
+    pub fn get(&self) -> Option<Duration> {
+        match self.0.load(Relaxed) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+    pub fn set(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let nanos = match timeout {
+            Some(Duration::ZERO) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "cannot set a zero duration timeout",
+                ))
+            }
+            // Hundreds of years are as good as infinity.
+            Some(timeout) => u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX),
+            None => 0,
+        };
+        self.0.store(nanos, Relaxed);
+        Ok(())
+    }
+}
+impl Clone for ReadTimeout {
+    fn clone(&self) -> Self {
+        Self(AtomicU64::new(self.0.load(Relaxed)))
+    }
+}
+
+/// Sends a byte range of a file into a pipe through an intermediate buffer, for platforms which can't copy the data
+/// inside the kernel. `read_at` reads from the file at the given offset.
+pub(crate) fn send_file_chunked(
+    mut read_at: impl FnMut(&mut [u8], u64) -> io::Result<usize>,
+    mut pipe: impl Write,
+    range: Range<u64>,
+) -> io::Result<u64> {
+    const CHUNK_SIZE: usize = 64 * 1024;
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut offset = range.start;
+    while offset < range.end {
+        let want = usize::try_from(range.end - offset).map_or(CHUNK_SIZE, |rem| rem.min(CHUNK_SIZE));
+        let read = match read_at(&mut buf[..want], offset) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let mut chunk = &buf[..read];
+        while !chunk.is_empty() {
+            match pipe.write(chunk) {
+                Ok(written) => {
+                    chunk = &chunk[written..];
+                    offset += written as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock && offset > range.start => {
+                    return Ok(offset - range.start)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(offset - range.start)
+}
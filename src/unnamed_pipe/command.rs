@@ -0,0 +1,180 @@
+use super::{PipeOptions, UnnamedPipeReader, UnnamedPipeWriter};
+use crate::Sealed;
+use std::{io, process::Stdio};
+
+/// Extends [`Command`](std::process::Command) (and, with the `tokio` feature, `tokio::process::Command`) with methods
+/// that connect unnamed pipes to a child process before it's spawned.
+///
+/// Every method creates a new pipe, hands one of its ends to the command and returns the other one, which is to be kept
+/// in the parent process. The parent-side end is never inheritable, so that it doesn't leak into this or any other child
+/// process and keep the pipe open after the parent is done with it.
+///
+/// The child-side end is owned by the command and is only closed when the command is dropped. Until then, reading from
+/// a pipe connected to the child's output won't end with end of file even after the child exits, so the command should
+/// be dropped right after spawning the child.
+///
+/// # Example
+/// ```no_run
+/// use interprocess::unnamed_pipe::CommandPipeExt;
+/// use std::{io::prelude::*, process::Command};
+///
+/// let mut command = Command::new("cat");
+/// let mut stdin = command.pipe_stdin()?;
+/// let mut stdout = command.pipe_stdout()?;
+/// let mut child = command.spawn()?;
+/// drop(command);
+///
+/// stdin.write_all(b"Hello from the parent!")?;
+/// drop(stdin);
+/// let mut output = String::new();
+/// stdout.read_to_string(&mut output)?;
+/// child.wait()?;
+/// assert_eq!(output, "Hello from the parent!");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait CommandPipeExt: Sealed {
+    /// Connects a pipe to the standard input of the child process and returns its writing end.
+    fn pipe_stdin(&mut self) -> io::Result<UnnamedPipeWriter>;
+    /// Connects a pipe to the standard output of the child process and returns its reading end.
+    fn pipe_stdout(&mut self) -> io::Result<UnnamedPipeReader>;
+    /// Connects a pipe to the standard error of the child process and returns its reading end.
+    fn pipe_stderr(&mut self) -> io::Result<UnnamedPipeReader>;
+
+    /// Makes the child process inherit the reading end of a pipe as the file descriptor `fd`, and returns the writing
+    /// end.
+    ///
+    /// If `fd` is already open in the child, it's replaced by the pipe. Standard input, output and error can be
+    /// targeted this way as well, although the methods dedicated to them are preferable, since they combine correctly
+    /// with [`stdin()`](std::process::Command::stdin) and friends. This method and
+    /// [`pipe_writer_to_fd()`](Self::pipe_writer_to_fd) can be called any number of times on the same command, with
+    /// each call to either one wiring up a pipe of its own.
+    ///
+    /// This method is only available on Unix. On other platforms, it's absent and thus any usage of it will result in a
+    /// compile-time error.
+    ///
+    /// # System calls
+    /// - `pipe2` or `pipe`, and `fcntl`
+    /// - `dup2` (in the child process)
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    fn pipe_reader_to_fd(&mut self, fd: std::os::unix::io::RawFd) -> io::Result<UnnamedPipeWriter>;
+    /// Makes the child process inherit the writing end of a pipe as the file descriptor `fd`, and returns the reading
+    /// end.
+    ///
+    /// See [`pipe_reader_to_fd()`](Self::pipe_reader_to_fd) for more.
+    ///
+    /// This method is only available on Unix. On other platforms, it's absent and thus any usage of it will result in a
+    /// compile-time error.
+    ///
+    /// # System calls
+    /// - `pipe2` or `pipe`, and `fcntl`
+    /// - `dup2` (in the child process)
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    fn pipe_writer_to_fd(&mut self, fd: std::os::unix::io::RawFd) -> io::Result<UnnamedPipeReader>;
+
+    /// Creates a pipe the reading end of which will be inherited by the child process, and returns both ends, the
+    /// writing one being kept by the parent.
+    ///
+    /// Unlike file descriptors on Unix, inherited handles keep their values in the child process, which has to be told
+    /// the value of the [raw handle](std::os::windows::io::AsRawHandle) by some other means, typically a command-line
+    /// argument. The returned reading end should be dropped after spawning the child.
+    ///
+    /// Since handle inheritance can't be restricted to one child process with
+    /// [`Command`](std::process::Command), any other child spawned while the reading end is alive will inherit it as
+    /// well.
+    ///
+    /// This method is only available on Windows. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `SetHandleInformation`
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    fn inherit_pipe_reader(&mut self) -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)>;
+    /// Creates a pipe the writing end of which will be inherited by the child process, and returns both ends, the
+    /// reading one being kept by the parent.
+    ///
+    /// See [`inherit_pipe_reader()`](Self::inherit_pipe_reader) for more.
+    ///
+    /// This method is only available on Windows. On other platforms, it's absent and thus any usage of it will result
+    /// in a compile-time error.
+    ///
+    /// # System calls
+    /// - `SetHandleInformation`
+    #[cfg(windows)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(windows)))]
+    fn inherit_pipe_writer(&mut self) -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)>;
+}
+
+macro_rules! impl_command_pipe_ext {
+    ($ty:ty) => {
+        impl CommandPipeExt for $ty {
+            fn pipe_stdin(&mut self) -> io::Result<UnnamedPipeWriter> {
+                let (writer, reader) = PipeOptions::new().create()?;
+                self.stdin(into_stdio(reader));
+                Ok(writer)
+            }
+            fn pipe_stdout(&mut self) -> io::Result<UnnamedPipeReader> {
+                let (writer, reader) = PipeOptions::new().create()?;
+                self.stdout(into_stdio(writer));
+                Ok(reader)
+            }
+            fn pipe_stderr(&mut self) -> io::Result<UnnamedPipeReader> {
+                let (writer, reader) = PipeOptions::new().create()?;
+                self.stderr(into_stdio(writer));
+                Ok(reader)
+            }
+
+            #[cfg(unix)]
+            fn pipe_reader_to_fd(&mut self, fd: std::os::unix::io::RawFd) -> io::Result<UnnamedPipeWriter> {
+                let (writer, reader) = PipeOptions::new().create()?;
+                let hook = crate::os::unix::unnamed_pipe::inherit_as_fd(reader.into(), fd)?;
+                // SAFETY: the hook only performs async-signal-safe system calls
+                unsafe { self.pre_exec(hook) };
+                Ok(writer)
+            }
+            #[cfg(unix)]
+            fn pipe_writer_to_fd(&mut self, fd: std::os::unix::io::RawFd) -> io::Result<UnnamedPipeReader> {
+                let (writer, reader) = PipeOptions::new().create()?;
+                let hook = crate::os::unix::unnamed_pipe::inherit_as_fd(writer.into(), fd)?;
+                // SAFETY: as above
+                unsafe { self.pre_exec(hook) };
+                Ok(reader)
+            }
+
+            #[cfg(windows)]
+            fn inherit_pipe_reader(&mut self) -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)> {
+                use crate::os::windows::ShareHandle;
+                let (writer, reader) = PipeOptions::new().create()?;
+                reader.set_inheritable(true)?;
+                Ok((writer, reader))
+            }
+            #[cfg(windows)]
+            fn inherit_pipe_writer(&mut self) -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)> {
+                use crate::os::windows::ShareHandle;
+                let (writer, reader) = PipeOptions::new().create()?;
+                writer.set_inheritable(true)?;
+                Ok((writer, reader))
+            }
+        }
+        impl Sealed for $ty {}
+    };
+}
+
+#[cfg(unix)]
+fn into_stdio(end: impl Into<std::os::unix::io::OwnedFd>) -> Stdio {
+    Stdio::from(end.into())
+}
+#[cfg(windows)]
+fn into_stdio(end: impl Into<std::os::windows::io::OwnedHandle>) -> Stdio {
+    Stdio::from(end.into())
+}
+
+// pre_exec() is an inherent method of the Tokio command, but comes from an extension trait for the standard one.
+#[cfg(unix)]
+use std::os::unix::process::CommandExt as _;
+
+impl_command_pipe_ext!(std::process::Command);
+#[cfg(feature = "tokio")]
+impl_command_pipe_ext!(tokio::process::Command);
@@ -0,0 +1,104 @@
+//! Asynchronous unnamed pipes which work with the Tokio runtime and event loop.
+//!
+//! On Unix, the pipe is created just like with the synchronous [`pipe()`](super::pipe) function and its ends are
+//! switched to nonblocking mode and registered in the runtime. Windows does not support overlapped I/O on anonymous
+//! pipes, so a local-only named pipe with a randomly generated name is used instead, with the same semantics.
+//!
+//! Types from this module will *not* work with other async runtimes, such as `async-std` or `smol`, since the Tokio
+//! types' methods will panic whenever they're called outside of a Tokio runtime context.
+
+impmod! {unnamed_pipe::tokio,
+    UnnamedPipeReader as UnnamedPipeReaderImpl,
+    UnnamedPipeWriter as UnnamedPipeWriterImpl,
+    pipe as pipe_impl,
+}
+use futures_io::{AsyncRead, AsyncWrite};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite, ReadBuf as TokioReadBuf};
+
+/// Creates a new pipe with the default creation settings and returns Tokio-based handles to its writing end and
+/// reading end.
+///
+/// # Panics
+/// Panics if called outside of a Tokio runtime context.
+pub fn pipe() -> io::Result<(UnnamedPipeWriter, UnnamedPipeReader)> {
+    pipe_impl()
+}
+
+/// A Tokio-based handle to the reading end of an unnamed pipe, created by the [`pipe()`] function together with the
+/// [writing end](UnnamedPipeWriter).
+///
+/// Both the [`futures`](futures_io::AsyncRead) and the [Tokio](tokio::io::AsyncRead) flavors of `AsyncRead` are
+/// implemented.
+pub struct UnnamedPipeReader(pub(crate) UnnamedPipeReaderImpl);
+impl AsyncRead for UnnamedPipeReader {
+    #[inline]
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.0.poll_read(cx, buf)
+    }
+}
+impl TokioAsyncRead for UnnamedPipeReader {
+    #[inline]
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut TokioReadBuf<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().0.poll_read_readbuf(cx, buf)
+    }
+}
+impl Debug for UnnamedPipeReader {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_as_handle!(UnnamedPipeReader);
+forward_into_handle!(unix: UnnamedPipeReader);
+derive_asraw!(UnnamedPipeReader);
+
+/// A Tokio-based handle to the writing end of an unnamed pipe, created by the [`pipe()`] function together with the
+/// [reading end](UnnamedPipeReader).
+///
+/// Both the [`futures`](futures_io::AsyncWrite) and the [Tokio](tokio::io::AsyncWrite) flavors of `AsyncWrite` are
+/// implemented. Flushing and closing do nothing, since unnamed pipes have no buffering of their own; the pipe is closed
+/// when the writer is dropped.
+pub struct UnnamedPipeWriter(pub(crate) UnnamedPipeWriterImpl);
+impl AsyncWrite for UnnamedPipeWriter {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+impl TokioAsyncWrite for UnnamedPipeWriter {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+impl Debug for UnnamedPipeWriter {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+forward_as_handle!(UnnamedPipeWriter);
+forward_into_handle!(unix: UnnamedPipeWriter);
+derive_asraw!(UnnamedPipeWriter);
@@ -0,0 +1,113 @@
+#![cfg(all(unix, feature = "async-std"))]
+
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+use color_eyre::eyre::Context;
+use futures::{executor::block_on, prelude::*, try_join};
+use interprocess::os::unix::udsocket::{async_std::*, UdSocket};
+use std::net::Shutdown;
+
+fn run_stream(mut namegen: NameGen) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut namegen, |nm| UdStreamListener::bind(nm))?;
+    let server = async {
+        let conn = listener.accept().await.context("accept failed")?;
+        let mut buf = String::new();
+        (&conn)
+            .read_to_string(&mut buf)
+            .await
+            .context("server receive failed")?;
+        ensure_eq!(buf, "Hello from client!");
+        (&conn)
+            .write_all(b"Hello from server!")
+            .await
+            .context("server send failed")?;
+        conn.shutdown(Shutdown::Write)?;
+        TestResult::Ok(())
+    };
+    let client = async {
+        let conn = UdStream::connect(&*name).await.context("connect failed")?;
+        (&conn)
+            .write_all(b"Hello from client!")
+            .await
+            .context("client send failed")?;
+        conn.shutdown(Shutdown::Write)?;
+        let mut buf = String::new();
+        (&conn)
+            .read_to_string(&mut buf)
+            .await
+            .context("client receive failed")?;
+        ensure_eq!(buf, "Hello from server!");
+        TestResult::Ok(())
+    };
+    block_on(async { try_join!(server, client) }).map(|((), ())| ())
+}
+
+fn run_async_io(mut namegen: NameGen) -> TestResult {
+    use async_io::Async;
+    use interprocess::os::unix::udsocket::{UdStream as SyncUdStream, UdStreamListener as SyncUdStreamListener};
+
+    let (name, listener) = listen_and_pick_name(&mut namegen, |nm| SyncUdStreamListener::bind(nm))?;
+    let listener = Async::new(listener)?;
+    block_on(async {
+        let client = async {
+            let mut conn = Async::new(SyncUdStream::connect(&*name)?)?;
+            conn.write_all(b"Hello from client!").await?;
+            TestResult::Ok(())
+        };
+        let server = async {
+            let conn = listener.read_with(|l| l.accept()).await?;
+            let mut conn = Async::new(conn)?;
+            let mut buf = String::new();
+            conn.read_to_string(&mut buf).await?;
+            ensure_eq!(buf, "Hello from client!");
+            TestResult::Ok(())
+        };
+        try_join!(server, client).map(|((), ())| ())
+    })
+}
+
+fn run_datagram(mut namegen: NameGen) -> TestResult {
+    let (a_name, a_socket) = listen_and_pick_name(&mut namegen, |nm| UdDatagram::bound(nm))?;
+    let (b_name, b_socket) = listen_and_pick_name(&mut namegen, |nm| UdDatagram::bound(nm))?;
+    a_socket.set_destination(&*b_name).context("set destination failed")?;
+    block_on(async {
+        a_socket.send(b"Message from side A").await?;
+        b_socket.send_to(b"Message from side B", &*a_name).await?;
+
+        let mut buf = [0; 64];
+        let read = b_socket.recv(&mut buf).await?;
+        ensure_eq!(&buf[..read], b"Message from side A");
+        let read = a_socket.recv(&mut buf).await?;
+        ensure_eq!(&buf[..read], b"Message from side B");
+        Ok(())
+    })
+}
+
+#[test]
+fn async_std_udsocket_stream() -> TestResult {
+    install_color_eyre();
+    run_stream(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        run_stream(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn async_std_udsocket_datagram() -> TestResult {
+    install_color_eyre();
+    run_datagram(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        run_datagram(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn async_io_udsocket_stream() -> TestResult {
+    install_color_eyre();
+    run_async_io(NameGen::new(make_id!(), false))
+}
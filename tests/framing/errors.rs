@@ -0,0 +1,48 @@
+use super::util::*;
+use color_eyre::eyre::ensure;
+use interprocess::framing::{LengthPrefix, MessageReader, MessageWriter};
+use std::io::{self, Cursor};
+
+fn read_kind(bytes: &[u8], prefix: LengthPrefix, max_size: usize) -> Option<io::ErrorKind> {
+    let mut reader = MessageReader::new(Cursor::new(bytes))
+        .with_prefix(prefix)
+        .with_max_size(max_size);
+    reader.read_message().err().map(|e| e.kind())
+}
+
+pub fn run() -> TestResult {
+    let mut writer = MessageWriter::new(Vec::new()).with_max_size(4);
+    writer.write_message(b"1234")?;
+    let e = writer.write_message(b"12345").unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    // 300 is 0b10_0101100, which is split into 0b0101100 and 0b10.
+    let mut writer = MessageWriter::new(Vec::new());
+    writer.write_message(&[0; 300])?;
+    ensure_eq!(writer.get_ref()[..2], [0b1010_1100, 0b10]);
+    ensure_eq!(writer.get_ref().len(), 302);
+
+    use LengthPrefix::*;
+    // Over the maximum size.
+    ensure_eq!(
+        read_kind(&[5, 1, 2, 3, 4, 5], Varint, 4),
+        Some(io::ErrorKind::InvalidData)
+    );
+    ensure_eq!(
+        read_kind(&[5, 0, 0, 0, 1, 2, 3, 4, 5], U32, 4),
+        Some(io::ErrorKind::InvalidData)
+    );
+    // A varint which doesn't fit into 64 bits.
+    ensure_eq!(
+        read_kind(&[0xFF; 11], Varint, usize::MAX),
+        Some(io::ErrorKind::InvalidData)
+    );
+    // End of file in the middle of the prefix and of the message.
+    ensure_eq!(read_kind(&[0x80], Varint, 16), Some(io::ErrorKind::UnexpectedEof));
+    ensure_eq!(read_kind(&[4, 0], U32, 16), Some(io::ErrorKind::UnexpectedEof));
+    ensure_eq!(read_kind(&[4, 1, 2], Varint, 16), Some(io::ErrorKind::UnexpectedEof));
+    // End of file at a message boundary is not an error.
+    let mut reader = MessageReader::new(Cursor::new(&[2, 1, 2][..]));
+    ensure_eq!(reader.read_message()?, Some(&[1, 2][..]));
+    ensure!(reader.read_message()?.is_none(), "message read past the end");
+    Ok(())
+}
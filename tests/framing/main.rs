@@ -0,0 +1,58 @@
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+mod errors;
+mod pipe;
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+mod typed;
+mod vectored;
+
+use interprocess::framing::LengthPrefix;
+
+#[test]
+fn framing_pipe_varint() -> TestResult {
+    install_color_eyre();
+    pipe::run(LengthPrefix::Varint)
+}
+#[test]
+fn framing_pipe_u32() -> TestResult {
+    install_color_eyre();
+    pipe::run(LengthPrefix::U32)
+}
+#[cfg(feature = "bytes")]
+#[test]
+fn framing_pipe_bytes() -> TestResult {
+    install_color_eyre();
+    pipe::run_bytes(LengthPrefix::Varint)?;
+    pipe::run_bytes(LengthPrefix::U32)
+}
+#[test]
+fn framing_vectored() -> TestResult {
+    install_color_eyre();
+    vectored::run(LengthPrefix::Varint)?;
+    vectored::run(LengthPrefix::U32)
+}
+#[test]
+fn framing_vectored_partial() -> TestResult {
+    install_color_eyre();
+    vectored::run_partial()
+}
+#[test]
+fn framing_errors() -> TestResult {
+    install_color_eyre();
+    errors::run()
+}
+#[cfg(feature = "bincode")]
+#[test]
+fn framing_typed_bincode() -> TestResult {
+    install_color_eyre();
+    typed::run(interprocess::framing::Bincode)
+}
+#[cfg(feature = "postcard")]
+#[test]
+fn framing_typed_postcard() -> TestResult {
+    install_color_eyre();
+    typed::run(interprocess::framing::Postcard)
+}
@@ -0,0 +1,64 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    framing::{LengthPrefix, MessageReader, MessageWriter},
+    unnamed_pipe::pipe,
+};
+use std::{io::BufReader, thread};
+
+/// Message lengths around the boundaries of varint prefix lengths.
+const LENS: &[usize] = &[0, 1, 127, 128, 300, 16383, 16384, 100_000];
+
+pub fn run(prefix: LengthPrefix) -> TestResult {
+    let (tx, rx) = pipe().context("pipe creation failed")?;
+    let writer = thread::spawn(move || {
+        let mut writer = MessageWriter::new(tx).with_prefix(prefix);
+        for (i, &len) in LENS.iter().enumerate() {
+            writer.write_message(&vec![i as u8; len])?;
+        }
+        std::io::Result::Ok(())
+    });
+
+    let mut reader = MessageReader::new(BufReader::new(rx)).with_prefix(prefix);
+    for (i, &len) in LENS.iter().enumerate() {
+        let msg = reader
+            .read_message()
+            .context("read failed")?
+            .ok_or_else(|| color_eyre::eyre::eyre!("unexpected end of file"))?;
+        ensure_eq!(msg.len(), len);
+        ensure_eq!(msg.iter().all(|&b| b == i as u8), true);
+    }
+    writer.join().unwrap().context("write failed")?;
+    ensure_eq!(reader.read_message()?, None);
+    Ok(())
+}
+
+/// Same as `run()`, but keeps all of the messages around until the end.
+#[cfg(feature = "bytes")]
+pub fn run_bytes(prefix: LengthPrefix) -> TestResult {
+    let (tx, rx) = pipe().context("pipe creation failed")?;
+    let writer = thread::spawn(move || {
+        let mut writer = MessageWriter::new(tx).with_prefix(prefix);
+        for (i, &len) in LENS.iter().enumerate() {
+            writer.write_message(&vec![i as u8; len])?;
+        }
+        std::io::Result::Ok(())
+    });
+
+    let mut reader = MessageReader::new(BufReader::new(rx)).with_prefix(prefix);
+    let mut msgs = Vec::new();
+    for _ in LENS {
+        let msg = reader
+            .read_message_bytes()
+            .context("read failed")?
+            .ok_or_else(|| color_eyre::eyre::eyre!("unexpected end of file"))?;
+        msgs.push(msg);
+    }
+    for ((i, &len), msg) in LENS.iter().enumerate().zip(&msgs) {
+        ensure_eq!(msg.len(), len);
+        ensure_eq!(msg.iter().all(|&b| b == i as u8), true);
+    }
+    writer.join().unwrap().context("write failed")?;
+    ensure_eq!(reader.read_message_bytes()?, None);
+    Ok(())
+}
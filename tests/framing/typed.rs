@@ -0,0 +1,55 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    framing::{Format, MessageWriter, TypedStream},
+    local_socket::{LocalSocketListener, LocalSocketStream},
+};
+use serde::{Deserialize, Serialize};
+use std::{io, thread};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Msg {
+    Greeting(String),
+    Numbers { list: Vec<u64>, negative: i32 },
+    Bye,
+}
+
+pub fn msgs() -> Vec<Msg> {
+    vec![
+        Msg::Greeting("Hello from the client!".to_owned()),
+        Msg::Numbers {
+            list: (0..1000).map(|i| i * 7919).collect(),
+            negative: -42,
+        },
+        Msg::Bye,
+    ]
+}
+
+/// Sends values to a server which echoes them back, then checks how bad data and end of file are reported.
+pub fn run<F: Format + Copy + Send + 'static>(format: F) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let server = thread::spawn(move || -> io::Result<()> {
+        let mut conn = TypedStream::<Msg, _, _>::new(listener.accept()?, format);
+        for _ in 0..msgs().len() {
+            let msg = conn.recv()?;
+            conn.send(&msg)?;
+        }
+        // Something that isn't a valid value, followed by end of file.
+        MessageWriter::new(conn.into_inner())
+            .with_prefix(interprocess::framing::LengthPrefix::U32)
+            .write_message(&[0xFF; 8])
+    });
+
+    let conn = LocalSocketStream::connect(&*name).context("connect failed")?;
+    let mut conn = TypedStream::<Msg, _, _>::new(conn, format);
+    for msg in msgs() {
+        conn.send(&msg).context("send failed")?;
+        ensure_eq!(conn.recv().context("receive failed")?, msg);
+    }
+    server.join().unwrap().context("server failed")?;
+    ensure_eq!(conn.recv().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    ensure_eq!(conn.recv().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    Ok(())
+}
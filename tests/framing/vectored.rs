@@ -0,0 +1,86 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    framing::{LengthPrefix, MessageReader, MessageWriter},
+    message_builder::MessageBuilder,
+    unnamed_pipe::pipe,
+};
+use std::{
+    io::{self, prelude::*, BufReader, IoSlice},
+    thread,
+};
+
+pub fn run(prefix: LengthPrefix) -> TestResult {
+    let (tx, rx) = pipe().context("pipe creation failed")?;
+    let body = vec![0xAB; 100_000];
+    let writer = thread::spawn({
+        let body = body.clone();
+        move || {
+            let mut writer = MessageWriter::new(tx).with_prefix(prefix);
+            MessageBuilder::new()
+                .part(b"head")
+                .part(&body)
+                .part(b"")
+                .part(b"tail")
+                .write_framed(&mut writer)?;
+            MessageBuilder::new().write_framed(&mut writer)
+        }
+    });
+
+    let mut reader = MessageReader::new(BufReader::new(rx)).with_prefix(prefix);
+    let msg = reader
+        .read_message()
+        .context("read failed")?
+        .ok_or_else(|| color_eyre::eyre::eyre!("unexpected end of file"))?;
+    ensure_eq!(msg.len(), body.len() + 8);
+    ensure_eq!(&msg[..4], b"head");
+    ensure_eq!(&msg[4..msg.len() - 4], &body[..]);
+    ensure_eq!(&msg[msg.len() - 4..], b"tail");
+    ensure_eq!(reader.read_message()?, Some(&[][..]));
+    writer.join().unwrap().context("write failed")?;
+    ensure_eq!(reader.read_message()?, None);
+    Ok(())
+}
+
+/// A writer which only takes a few bytes per call, to exercise the resumption of partial vectored writes.
+struct Trickle(Vec<u8>);
+impl Write for Trickle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(3);
+        self.0.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let mut budget = 3;
+        for buf in bufs {
+            let len = buf.len().min(budget);
+            self.0.extend_from_slice(&buf[..len]);
+            budget -= len;
+            if budget == 0 {
+                break;
+            }
+        }
+        Ok(3 - budget)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn run_partial() -> TestResult {
+    let parts = [&b"a"[..], b"", b"bcdefg", b"hi", b"", b"jklmnop"];
+    let slices = parts.iter().map(|p| IoSlice::new(p)).collect::<Vec<_>>();
+    let mut writer = MessageWriter::new(Trickle(Vec::new())).with_prefix(LengthPrefix::U32);
+    writer.write_message_vectored(&slices).context("write failed")?;
+    let out = writer.into_inner().0;
+    ensure_eq!(&out[..4], &16_u32.to_le_bytes());
+    ensure_eq!(&out[4..], b"abcdefghijklmnop");
+
+    let mut writer = MessageWriter::new(Trickle(Vec::new())).with_max_size(15);
+    ensure_eq!(
+        writer.write_message_vectored(&slices).map_err(|e| e.kind()),
+        Err(io::ErrorKind::InvalidInput)
+    );
+    ensure_eq!(writer.get_ref().0.len(), 0);
+    Ok(())
+}
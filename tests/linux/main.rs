@@ -0,0 +1,36 @@
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, TestResult};
+
+mod memfd;
+mod pidfd;
+
+#[test]
+fn linux_memfd() -> TestResult {
+    install_color_eyre();
+    memfd::run()
+}
+#[test]
+fn linux_memfd_shared_memory() -> TestResult {
+    install_color_eyre();
+    memfd::run_shared_memory()
+}
+#[test]
+fn linux_pidfd() -> TestResult {
+    install_color_eyre();
+    pidfd::run()
+}
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn linux_pidfd_tokio() -> TestResult {
+    install_color_eyre();
+    pidfd::run_tokio().await
+}
+#[test]
+fn linux_pidfd_get_fd() -> TestResult {
+    install_color_eyre();
+    pidfd::run_get_fd()
+}
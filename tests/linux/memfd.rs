@@ -0,0 +1,74 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::{
+    os::{
+        linux::{Memfd, MemfdOptions, Seals},
+        unix::udsocket::cmsg::ancillary::file_descriptors::FileDescriptors,
+    },
+    shared_memory::SharedMemory,
+};
+use std::{
+    io::{self, Write},
+    os::{fd::AsFd, unix::fs::FileExt},
+};
+
+static MSG: &[u8] = b"Hello from a memory file!";
+
+pub fn run() -> TestResult {
+    let mut memfd = MemfdOptions::new()
+        .allow_sealing(true)
+        .create("interprocess-test")
+        .context("creation failed")?;
+    ensure!(memfd.seals()? == Seals::empty(), "fresh memory file has seals");
+    memfd.file().write_all(MSG).context("write failed")?;
+    memfd
+        .add_seals(Seals::SHRINK | Seals::GROW | Seals::WRITE)
+        .context("sealing failed")?;
+    let seals = memfd.seals().context("getting seals failed")?;
+    ensure!(
+        seals.contains(Seals::WRITE | Seals::GROW) && !seals.contains(Seals::SEAL),
+        "wrong seals: {seals:?}"
+    );
+    match memfd.file().write_all(b"overwritten") {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {}
+        els => bail!("unexpected result of writing to a sealed file: {els:?}"),
+    }
+
+    // Round trip through the ancillary data type used to send file descriptors.
+    let fds = [memfd.as_fd()];
+    let received = FileDescriptors::new(&fds)
+        .into_owned_fds()
+        .context("taking ownership of file descriptors failed")?;
+    ensure!(
+        received.len() == 1,
+        "wrong number of file descriptors: {}",
+        received.len()
+    );
+    let received = received.into_iter().map(Memfd::from).next().unwrap();
+    ensure!(received.seals()? == seals, "seals differ between duplicates");
+    let mut buf = [0; MSG.len()];
+    received.into_file().read_exact_at(&mut buf, 0).context("read failed")?;
+    ensure_eq!(&buf[..], MSG);
+
+    // Without allowing sealing, the file is sealed shut from the start.
+    let unsealable = Memfd::create("interprocess-test").context("creation failed")?;
+    ensure!(unsealable.seals()? == Seals::SEAL, "unsealable file isn't sealed");
+    match unsealable.add_seals(Seals::WRITE) {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {}
+        els => bail!("unexpected result of sealing an unsealable file: {els:?}"),
+    }
+    Ok(())
+}
+
+pub fn run_shared_memory() -> TestResult {
+    let mut memfd = Memfd::create("interprocess-test").context("creation failed")?;
+    memfd.file().set_len(4096).context("resizing failed")?;
+    memfd.file().write_all(MSG).context("write failed")?;
+    let file = memfd.into_file();
+    let dup = file.try_clone().context("duplication failed")?;
+    let shm = SharedMemory::from_fd(dup.into()).context("mapping failed")?;
+    ensure!(shm.len() >= 4096, "mapping is too small: {}", shm.len());
+    let mapped = unsafe { std::slice::from_raw_parts(shm.as_ptr(), MSG.len()) };
+    ensure_eq!(mapped, MSG);
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::linux::{Memfd, PidFd};
+use std::{
+    fs::File,
+    io::Write,
+    os::unix::{fs::FileExt, prelude::*, process::ExitStatusExt},
+    process::{self, Command},
+    time::Duration,
+};
+
+pub fn run() -> TestResult {
+    let mut child = Command::new("sleep")
+        .arg("60")
+        .spawn()
+        .context("spawning child failed")?;
+    let pidfd = PidFd::from_child(&child).context("opening pidfd failed")?;
+    ensure!(!pidfd.has_exited()?, "child exited early");
+    ensure!(
+        !pidfd.wait_for_exit_timeout(Duration::from_millis(10))?,
+        "wait didn't time out"
+    );
+
+    pidfd.send_signal(libc::SIGKILL).context("sending signal failed")?;
+    ensure!(
+        pidfd.wait_for_exit_timeout(Duration::from_secs(10))?,
+        "child didn't exit after being killed"
+    );
+    ensure!(pidfd.has_exited()?, "exited child isn't reported as such");
+    pidfd.wait_for_exit().context("wait failed")?;
+    // Waiting on the pidfd doesn't reap the child.
+    let status = child.wait().context("reaping child failed")?;
+    ensure_eq!(status.signal(), Some(libc::SIGKILL));
+    ensure!(pidfd.send_signal(libc::SIGKILL).is_err(), "signalled a reaped child");
+    Ok(())
+}
+
+#[cfg(feature = "tokio")]
+pub async fn run_tokio() -> TestResult {
+    let mut child = Command::new("sleep")
+        .arg("60")
+        .spawn()
+        .context("spawning child failed")?;
+    let pidfd = PidFd::from_child(&child).context("opening pidfd failed")?;
+    // Both of these waits are in progress at once.
+    let kill = async {
+        ::tokio::time::sleep(Duration::from_millis(10)).await;
+        pidfd.send_signal(libc::SIGKILL)
+    };
+    let waits = async { ::tokio::try_join!(pidfd.wait_for_exit_async(), pidfd.wait_for_exit_async(), kill) };
+    ::tokio::time::timeout(Duration::from_secs(10), waits)
+        .await
+        .context("child didn't exit after being killed")?
+        .context("wait failed")?;
+    child.wait().context("reaping child failed")?;
+    Ok(())
+}
+
+pub fn run_get_fd() -> TestResult {
+    let mut memfd = Memfd::create("interprocess-test").context("creation failed")?;
+    memfd.file().write_all(b"duplicated").context("write failed")?;
+    let pidfd = PidFd::open(process::id() as libc::pid_t).context("opening pidfd failed")?;
+    let dup = File::from(
+        pidfd
+            .get_fd(memfd.as_raw_fd())
+            .context("getting file descriptor failed")?,
+    );
+    ensure!(
+        dup.as_raw_fd() != memfd.as_raw_fd(),
+        "file descriptor wasn't duplicated"
+    );
+    let mut buf = [0; 10];
+    dup.read_exact_at(&mut buf, 0).context("read failed")?;
+    ensure_eq!(&buf, b"duplicated");
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::local_socket::{
+    auth::{AllowAll, AuthError, Authenticator, PeerCredentials, Rejection, SameUser},
+    LocalSocketListener,
+};
+use std::{
+    io::{self, prelude::*},
+    thread,
+};
+
+static MSG: &[u8] = b"Hello from the authenticated client!";
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let server = thread::spawn(move || -> io::Result<()> {
+        let auth = Authenticator::new(SameUser);
+        let (mut conn, peer) = auth.accept(&listener)?;
+        check_self(&peer);
+        let mut buf = [0; MSG.len()];
+        conn.read_exact(&mut buf)?;
+        assert_eq!(&buf[..], MSG);
+
+        let picky = Authenticator::new(|_: &PeerCredentials| Err(Rejection::new("not today")));
+        match picky.accept(&listener) {
+            Err(AuthError::Rejected(rejection)) => {
+                assert_eq!(rejection.reason(), "not today");
+                check_self(rejection.credentials());
+            }
+            els => panic!("expected a rejection, got {els:?}"),
+        }
+        Ok(())
+    });
+
+    let auth = Authenticator::new(AllowAll);
+    let (mut conn, peer) = auth.connect(&*name).context("accepted connect failed")?;
+    check_self(&peer);
+    conn.write_all(MSG).context("write failed")?;
+
+    match auth.connect(&*name) {
+        Err(AuthError::RejectedByPeer) => {}
+        els => bail!("expected to be rejected, got {els:?}"),
+    }
+    server.join().unwrap().context("server failed")?;
+    Ok(())
+}
+
+/// Both ends are this very process.
+fn check_self(peer: &PeerCredentials) {
+    let current = PeerCredentials::current().unwrap();
+    if cfg!(any(target_os = "linux", target_os = "android", windows)) {
+        assert_eq!(peer.pid(), Some(std::process::id()));
+    }
+    #[cfg(unix)]
+    assert_eq!(peer.uid(), current.uid());
+    #[cfg(windows)]
+    assert_eq!(peer.sid(), current.sid());
+}
@@ -0,0 +1,81 @@
+//! Tests message boundaries, size limits and the end of the connection on framed local sockets.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::local_socket::{FramedLocalSocket, FramedLocalSocketListener};
+use std::{io, thread};
+
+fn messages() -> Vec<Vec<u8>> {
+    vec![
+        b"first".to_vec(),
+        b"x".to_vec(),
+        vec![0xA5; FramedLocalSocket::MAX_MSG_SIZE],
+        b"last".to_vec(),
+    ]
+}
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        FramedLocalSocketListener::bind(nm)
+    })?;
+    let client = thread::spawn(move || -> TestResult {
+        let mut conn = FramedLocalSocket::connect(&*name).context("connect failed")?;
+        // All messages are sent before any is received, so that they queue up back to back.
+        for msg in messages() {
+            conn.send_msg(&msg).context("send failed")?;
+        }
+        ensure_eq!(
+            conn.send_msg(b"").map_err(|e| e.kind()),
+            Err(io::ErrorKind::InvalidInput)
+        );
+        let oversized = vec![0; FramedLocalSocket::MAX_MSG_SIZE + 1];
+        ensure_eq!(
+            conn.send_msg(&oversized).map_err(|e| e.kind()),
+            Err(io::ErrorKind::InvalidInput)
+        );
+        let reply = conn.recv_msg().context("receive failed")?.map(<[u8]>::to_vec);
+        ensure_eq!(reply.as_deref(), Some(&b"done"[..]));
+        Ok(())
+    });
+    let mut conn = listener.accept().context("accept failed")?;
+    #[cfg(any(target_os = "linux", windows))]
+    ensure_eq!(conn.is_native(), true);
+    for expected in messages() {
+        let msg = conn.recv_msg().context("receive failed")?;
+        ensure_eq!(msg, Some(&expected[..]));
+    }
+    conn.send_msg(b"done").context("send failed")?;
+    client.join().unwrap()?;
+    ensure_eq!(conn.recv_msg().context("receive at end of connection failed")?, None);
+    Ok(())
+}
+
+/// A client connecting to a plain local socket server falls back to length prefixes, which is also what happens against
+/// a framed server on systems without `SOCK_SEQPACKET`.
+#[cfg(unix)]
+pub fn run_fallback() -> TestResult {
+    use interprocess::{
+        framing::{LengthPrefix, MessageReader, MessageWriter},
+        local_socket::LocalSocketListener,
+    };
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let client = thread::spawn(move || -> TestResult {
+        let mut conn = FramedLocalSocket::connect(&*name).context("connect failed")?;
+        ensure_eq!(conn.is_native(), false);
+        conn.send_msg(b"ping").context("send failed")?;
+        ensure_eq!(conn.recv_msg().context("receive failed")?, Some(&b"pong"[..]));
+        Ok(())
+    });
+    let mut conn = listener.accept().context("accept failed")?;
+    let mut reader = MessageReader::new(&mut conn).with_prefix(LengthPrefix::U32);
+    ensure_eq!(reader.read_message().context("read failed")?, Some(&b"ping"[..]));
+    MessageWriter::new(&mut conn)
+        .with_prefix(LengthPrefix::U32)
+        .write_message(b"pong")
+        .context("write failed")?;
+    client.join().unwrap()?;
+    Ok(())
+}
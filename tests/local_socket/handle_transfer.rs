@@ -0,0 +1,67 @@
+use super::{util::*, NameGen};
+use color_eyre::eyre::Context;
+use interprocess::{
+    local_socket::{HandleToken, LocalSocketListener, LocalSocketStream},
+    unnamed_pipe::{pipe, UnnamedPipeWriter},
+};
+use std::{
+    io::{Read, Write},
+    sync::{mpsc::Sender, Arc},
+};
+
+static MSG: &[u8] = b"Hello through a transferred pipe!";
+
+pub fn server(name_sender: Sender<Arc<str>>, num_clients: u32) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let _ = name_sender.send(name);
+
+    for _ in 0..num_clients {
+        let mut conn = listener.accept().context("accept failed")?;
+        #[cfg(unix)]
+        {
+            // The client sends one more file descriptor than this, which must be closed on arrival.
+            conn.set_max_pending_handles(1);
+            // Nothing has been read yet, and so nothing has been received either.
+            let e = unsafe { conn.recv_handle(HandleToken::from_raw(0)) }.unwrap_err();
+            ensure_eq!(e.kind(), std::io::ErrorKind::NotFound);
+        }
+        let mut buf = [0; 8];
+        conn.read_exact(&mut buf).context("token receive failed")?;
+        let token = HandleToken::from_raw(u64::from_le_bytes(buf));
+        let handle = unsafe { conn.recv_handle(token) }.context("handle receive failed")?;
+        #[cfg(unix)]
+        {
+            let e = unsafe { conn.recv_handle(HandleToken::from_raw(token.into_raw() + 1)) }.unwrap_err();
+            ensure_eq!(e.kind(), std::io::ErrorKind::NotFound);
+        }
+        let mut writer = UnnamedPipeWriter::from(handle);
+        writer.write_all(MSG).context("pipe write failed")?;
+        conn.write_all(b"\n").context("acknowledgement send failed")?;
+    }
+    Ok(())
+}
+
+pub fn client(name: &str) -> TestResult {
+    let mut conn = LocalSocketStream::connect(name).context("connect failed")?;
+    let (writer, mut reader) = pipe().context("pipe creation failed")?;
+    #[cfg(unix)]
+    let handle = std::os::unix::io::AsFd::as_fd(&writer);
+    #[cfg(windows)]
+    let handle = std::os::windows::io::AsHandle::as_handle(&writer);
+    let token = conn.send_handle(handle).context("handle send failed")?;
+    #[cfg(unix)]
+    conn.send_handle(handle).context("excess handle send failed")?;
+    conn.write_all(&token.into_raw().to_le_bytes())
+        .context("token send failed")?;
+    // The server now has the only writer.
+    drop(writer);
+
+    let mut ack = [0];
+    conn.read_exact(&mut ack).context("acknowledgement receive failed")?;
+    let mut buf = [0; MSG.len()];
+    reader.read_exact(&mut buf).context("pipe read failed")?;
+    ensure_eq!(&buf[..], MSG);
+    Ok(())
+}
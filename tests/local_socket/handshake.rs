@@ -0,0 +1,61 @@
+//! Tests protocol negotiation: picking the newest common version and the common features, and both ends failing the
+//! same way when they can't agree.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    handshake::{Handshake, HandshakeError, Negotiated},
+    local_socket::{LocalSocketListener, LocalSocketStream},
+};
+use std::thread;
+
+const MAGIC: [u8; 4] = *b"TEST";
+const COMPRESSION: u64 = 1 << 0;
+const BATCHING: u64 = 1 << 1;
+const TRACING: u64 = 1 << 2;
+
+type Outcome = Result<Negotiated, HandshakeError>;
+
+/// Performs the handshake with the given parameters on both ends of a fresh connection.
+fn negotiate(server: Handshake, client: Handshake) -> TestResult<(Outcome, Outcome)> {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let server = thread::spawn(move || server.perform(&mut listener.accept()?));
+    let mut conn = LocalSocketStream::connect(&*name).context("connect failed")?;
+    let client = client.perform(&mut conn);
+    Ok((server.join().unwrap(), client))
+}
+
+pub fn run() -> TestResult {
+    // A newer server talking to an older client.
+    let (server, client) = negotiate(
+        Handshake::new(MAGIC, 3).min_version(1).features(COMPRESSION | TRACING),
+        Handshake::new(MAGIC, 2).features(COMPRESSION | BATCHING),
+    )?;
+    let (server, client) = (server.context("server failed")?, client.context("client failed")?);
+    ensure_eq!(server, client);
+    ensure_eq!(server.version(), 2);
+    ensure_eq!(server.features(), COMPRESSION);
+    ensure_eq!(server.has(COMPRESSION), true);
+    ensure_eq!(server.has(COMPRESSION | BATCHING), false);
+
+    let (server, client) = negotiate(Handshake::new(MAGIC, 3).min_version(3), Handshake::new(MAGIC, 2))?;
+    for outcome in [server, client] {
+        ensure_eq!(matches!(outcome, Err(HandshakeError::NoCommonVersion { .. })), true);
+    }
+
+    let (server, client) = negotiate(
+        Handshake::new(MAGIC, 1).require(BATCHING),
+        Handshake::new(MAGIC, 1).features(COMPRESSION),
+    )?;
+    for outcome in [server, client] {
+        ensure_eq!(matches!(outcome, Err(HandshakeError::MissingFeatures(BATCHING))), true);
+    }
+
+    let (server, client) = negotiate(Handshake::new(MAGIC, 1), Handshake::new(*b"ELSE", 1))?;
+    for outcome in [server, client] {
+        ensure_eq!(matches!(outcome, Err(HandshakeError::WrongMagic)), true);
+    }
+    Ok(())
+}
@@ -3,7 +3,14 @@
 mod util;
 use util::*;
 
+mod auth;
+mod framed;
+mod handle_transfer;
+mod handshake;
+mod nameserver;
 mod no_server;
+mod read_until;
+mod shutdown;
 mod stream;
 
 use interprocess::local_socket::NameTypeSupport;
@@ -22,6 +29,38 @@ fn local_socket_stream() -> TestResult {
     Ok(())
 }
 #[test]
+fn local_socket_auth() -> TestResult {
+    install_color_eyre();
+    auth::run()
+}
+#[test]
+fn local_socket_framed() -> TestResult {
+    install_color_eyre();
+    framed::run()
+}
+#[cfg(unix)]
+#[test]
+fn local_socket_framed_fallback() -> TestResult {
+    install_color_eyre();
+    framed::run_fallback()
+}
+#[test]
+fn local_socket_handle_transfer() -> TestResult {
+    use handle_transfer::*;
+    install_color_eyre();
+    util::drive_server_and_multiple_clients(server, client)
+}
+#[test]
+fn local_socket_handshake() -> TestResult {
+    install_color_eyre();
+    handshake::run()
+}
+#[test]
+fn local_socket_nameserver() -> TestResult {
+    install_color_eyre();
+    nameserver::run()
+}
+#[test]
 fn local_socket_no_server() -> TestResult {
     install_color_eyre();
     // Same as above.
@@ -31,3 +70,18 @@ fn local_socket_no_server() -> TestResult {
     }
     Ok(())
 }
+#[test]
+fn local_socket_read_until() -> TestResult {
+    install_color_eyre();
+    read_until::run()
+}
+#[test]
+fn local_socket_shutdown() -> TestResult {
+    install_color_eyre();
+    shutdown::run()
+}
+#[test]
+fn local_socket_shutdown_timeout() -> TestResult {
+    install_color_eyre();
+    shutdown::run_timeout()
+}
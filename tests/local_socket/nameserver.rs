@@ -0,0 +1,60 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::local_socket::{
+    nameserver::{Nameserver, NameserverClient},
+    LocalSocketListener,
+};
+use std::{
+    io::{self, prelude::*},
+    thread,
+    time::{Duration, Instant},
+};
+
+static MSG: &[u8] = b"Hello from the service!";
+
+pub fn run() -> TestResult {
+    let (ns_name, nameserver) =
+        listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| Nameserver::bind(nm))?;
+    thread::spawn(move || nameserver.run());
+
+    let (service_name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let service = thread::spawn(move || -> io::Result<()> { listener.accept()?.write_all(MSG) });
+
+    let mut registrar = NameserverClient::connect(&*ns_name).context("registrar connect failed")?;
+    registrar
+        .register("test-service", &service_name)
+        .context("register failed")?;
+
+    let mut client = NameserverClient::connect(&*ns_name).context("client connect failed")?;
+    ensure_eq!(client.resolve("test-service")?.as_deref(), Some(&*service_name));
+    ensure_eq!(client.resolve("no-such-service")?, None);
+    let mut conn = client
+        .connect_service("test-service")
+        .context("service connect failed")?;
+    let mut buf = [0; MSG.len()];
+    conn.read_exact(&mut buf).context("service read failed")?;
+    ensure_eq!(&buf[..], MSG);
+    service.join().unwrap().context("service failed")?;
+
+    let e = client.register("test-service", "@elsewhere").unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::AlreadyExists);
+    ensure_eq!(client.unregister("test-service")?, false);
+    ensure_eq!(registrar.unregister("test-service")?, true);
+    ensure_eq!(client.resolve("test-service")?, None);
+    let e = client.connect_service("test-service").unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::NotFound);
+
+    // Registrations go away together with the connection that made them.
+    registrar.register("test-service", &service_name)?;
+    drop(registrar);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while client.resolve("test-service")?.is_some() {
+        if Instant::now() > deadline {
+            bail!("registration outlived the connection");
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
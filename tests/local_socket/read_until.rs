@@ -0,0 +1,39 @@
+//! Tests reading up to a delimiter without consuming anything past it.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::local_socket::{LocalSocketListener, LocalSocketStream};
+use std::{io::prelude::*, thread};
+
+static DATA: &[u8] = b"HELLO v1\nsecond line\n\x00\x01binary tail without a delimiter";
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let client = thread::spawn(move || -> TestResult {
+        let mut conn = LocalSocketStream::connect(&*name).context("connect failed")?;
+        conn.write_all(DATA).context("write failed")?;
+        Ok(())
+    });
+    let mut conn = listener.accept().context("accept failed")?;
+    client.join().unwrap()?;
+
+    let mut buf = Vec::new();
+    ensure_eq!(conn.read_until(b'\n', &mut buf).context("first read failed")?, 9);
+    ensure_eq!(buf, b"HELLO v1\n");
+    ensure_eq!(conn.read_until(b'\n', &mut buf).context("second read failed")?, 12);
+    ensure_eq!(buf, b"HELLO v1\nsecond line\n");
+
+    // Nothing past the delimiter was consumed, so plain reads pick up right after it.
+    let mut tail = Vec::new();
+    conn.read_to_end(&mut tail).context("read to end failed")?;
+    ensure_eq!(tail, b"\x00\x01binary tail without a delimiter");
+
+    // End-of-file ends the read without the delimiter.
+    ensure_eq!(
+        conn.read_until(b'\n', &mut buf).context("read at end-of-file failed")?,
+        0
+    );
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions};
+use std::{
+    ffi::OsStr,
+    io::{self, prelude::*},
+    thread,
+    time::{Duration, Instant},
+};
+
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    let start = Instant::now();
+    let e = listener.accept_timeout(TIMEOUT).unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::TimedOut);
+    ensure!(start.elapsed() >= TIMEOUT, "accept_timeout returned too early");
+
+    let client = thread::spawn(move || {
+        let mut client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name)?;
+        client.write_all(b"x")?;
+        io::Result::Ok(())
+    });
+    let mut conn = listener
+        .accept_timeout(Duration::from_secs(10))
+        .context("accept with timeout failed")?;
+    // The instance has to be back in blocking mode after having been waited on.
+    let mut buf = [0];
+    conn.read_exact(&mut buf).context("server read failed")?;
+    ensure_eq!(buf, *b"x");
+    client.join().unwrap().context("client failed")?;
+    Ok(())
+}
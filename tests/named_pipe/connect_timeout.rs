@@ -0,0 +1,49 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, ConnectOptions, DuplexPipeStream, PipeListenerOptions};
+use std::{ffi::OsStr, io, thread, time::Duration};
+
+type Stream = DuplexPipeStream<pipe_mode::Bytes>;
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .wait_timeout(Duration::from_millis(100))
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    // Occupy the only instance of the pipe without having it accepted.
+    let _first = Stream::connect(&*name).context("first connect failed")?;
+
+    let e = Stream::connect_with_timeout(&*name, Duration::from_millis(100)).unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::TimedOut);
+
+    const ERROR_PIPE_BUSY: i32 = 231;
+    let e = ConnectOptions::new()
+        .name(OsStr::new(&*name))
+        .retry_if_busy(false)
+        .connect_duplex::<pipe_mode::Bytes>()
+        .unwrap_err();
+    ensure_eq!(e.raw_os_error(), Some(ERROR_PIPE_BUSY));
+
+    // Without a timeout of its own, the client waits for as long as the server says.
+    const ERROR_SEM_TIMEOUT: i32 = 121;
+    let e = ConnectOptions::new()
+        .name(OsStr::new(&*name))
+        .connect_duplex::<pipe_mode::Bytes>()
+        .unwrap_err();
+    ensure_eq!(e.raw_os_error(), Some(ERROR_SEM_TIMEOUT));
+
+    let server = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        // Accepting the first client creates a new instance for the second one.
+        let first = listener.accept()?;
+        let second = listener.accept()?;
+        io::Result::Ok((first, second))
+    });
+    let second = Stream::connect_with_timeout(&*name, Duration::from_secs(5));
+    server.join().unwrap().context("accept failed")?;
+    ensure!(second.is_ok(), "second connect failed: {}", second.unwrap_err());
+    Ok(())
+}
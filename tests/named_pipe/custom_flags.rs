@@ -0,0 +1,42 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::windows::named_pipe::{pipe_mode, ConnectOptions, PipeListenerOptions};
+use std::{ffi::OsStr, io::prelude::*};
+
+const FILE_FLAG_WRITE_THROUGH: u32 = 0x80000000;
+const FILE_FLAG_SEQUENTIAL_SCAN: u32 = 0x08000000;
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_recv_only::<pipe_mode::Bytes>()
+    })?;
+    let client = ConnectOptions::new()
+        .name(OsStr::new(&*name))
+        .custom_flags(FILE_FLAG_WRITE_THROUGH)
+        .connect_send_only::<pipe_mode::Bytes>()
+        .context("send-only connect failed")?;
+    let server = listener.accept().context("accept failed")?;
+    (&client).write_all(b"ping").context("client send failed")?;
+    let mut buf = [0; 4];
+    (&server).read_exact(&mut buf).context("server receive failed")?;
+    ensure_eq!(&buf, b"ping");
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_send_only::<pipe_mode::Bytes>()
+    })?;
+    let client = ConnectOptions::new()
+        .name(OsStr::new(&*name))
+        .custom_flags(FILE_FLAG_SEQUENTIAL_SCAN)
+        .connect_recv_only::<pipe_mode::Bytes>()
+        .context("receive-only connect failed")?;
+    let server = listener.accept().context("accept failed")?;
+    (&server).write_all(b"pong").context("server send failed")?;
+    (&client).read_exact(&mut buf).context("client receive failed")?;
+    ensure_eq!(&buf, b"pong");
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions};
+use std::{
+    ffi::OsStr,
+    io::{prelude::*, BufReader},
+    sync::mpsc,
+    thread,
+};
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+    let (flushed_send, flushed_recv) = mpsc::channel();
+    let client = thread::spawn(move || {
+        let conn = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).context("connect failed")?;
+        // Don't read anything until the server is done flushing, which would deadlock if the flush were to block.
+        flushed_recv.recv().unwrap();
+        let mut buf = String::new();
+        BufReader::new(conn)
+            .read_line(&mut buf)
+            .context("client receive failed")?;
+        ensure_eq!(buf, "Hello from server!\n");
+        TestResult::Ok(())
+    });
+
+    let mut conn = listener.accept().context("accept failed")?;
+    ensure!(conn.is_flush_enabled(), "flushing is disabled by default");
+    conn.set_flush_enabled(false);
+    ensure!(!conn.is_flush_enabled(), "flushing wasn't disabled");
+    conn.write_all(b"Hello from server!\n").context("server send failed")?;
+    conn.flush().context("server flush failed")?;
+    flushed_send.send(()).unwrap();
+    // Dropping the stream sends it off to limbo, which flushes it regardless.
+    drop(conn);
+
+    client.join().unwrap()
+}
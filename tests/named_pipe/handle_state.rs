@@ -0,0 +1,54 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::{
+    os::windows::named_pipe::{self, pipe_mode, DuplexPipeStream, PipeHandleState, PipeListenerOptions, PipeMode},
+    reliable_recv_msg::*,
+};
+use std::{
+    ffi::OsStr,
+    io,
+    os::windows::io::{AsHandle, OwnedHandle},
+};
+
+const MSG: &[u8] = b"Message after adoption";
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .mode(PipeMode::Messages)
+            .create_duplex::<pipe_mode::Messages>()
+    })?;
+
+    let client = DuplexPipeStream::<pipe_mode::Messages>::connect(&*name).context("connect failed")?;
+    let conn = listener.accept().context("accept failed")?;
+
+    let state = conn.handle_state().context("server-side query failed")?;
+    ensure_eq!(state.read_mode, PipeMode::Messages);
+    ensure_eq!(state.nonblocking, false);
+
+    let state = client.handle_state().context("client-side query failed")?;
+    ensure_eq!(state.read_mode, PipeMode::Messages);
+    ensure_eq!(state.nonblocking, false);
+
+    let Ok(handle) = OwnedHandle::try_from(client) else {
+        bail!("failed to take ownership of the client handle")
+    };
+    named_pipe::set_read_mode(handle.as_handle(), PipeMode::Bytes).context("switch to byte mode failed")?;
+    let state = PipeHandleState::query(handle.as_handle()).context("query after switch failed")?;
+    ensure_eq!(state.read_mode, PipeMode::Bytes);
+    ensure_eq!(state.nonblocking, false);
+
+    named_pipe::set_read_mode(handle.as_handle(), PipeMode::Messages).context("switch to message mode failed")?;
+    let mut client = DuplexPipeStream::<pipe_mode::Messages>::try_from(handle)
+        .map_err(io::Error::from)
+        .context("adoption failed")?;
+    ensure_eq!(client.handle_state()?.read_mode, PipeMode::Messages);
+
+    conn.send(MSG).context("send failed")?;
+    let mut buf = [0; 64];
+    let msg = client.recv(&mut buf).context("receive failed")?;
+    ensure_eq!(msg.borrow_to_size(&buf), MSG);
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions};
+use std::{ffi::OsStr, io::prelude::*, thread};
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+    let client = thread::spawn(move || {
+        let conn = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).context("connect failed")?;
+        ensure!(
+            conn.impersonate_client().is_err(),
+            "client-side stream allowed impersonation"
+        );
+        // The server can only impersonate us after having read something from the pipe.
+        (&conn).write_all(&[0]).context("client send failed")?;
+        // Keep the connection open until the server is done with it.
+        let _ = (&conn).read(&mut [0]);
+        TestResult::Ok(())
+    });
+
+    let conn = listener.accept().context("accept failed")?;
+    (&conn).read_exact(&mut [0]).context("server receive failed")?;
+    let guard = conn.impersonate_client().context("impersonation failed")?;
+    guard.token().context("failed to open client token")?;
+    guard.revert().context("failed to revert to self")?;
+    conn.get_client_token().context("failed to get client token")?;
+    drop(conn);
+
+    client.join().unwrap()
+}
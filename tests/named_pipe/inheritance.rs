@@ -0,0 +1,42 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    os::windows::{
+        named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions},
+        ShareHandle,
+    },
+    unnamed_pipe,
+};
+use std::{
+    ffi::OsStr,
+    os::windows::io::{FromRawHandle, OwnedHandle},
+    process,
+};
+
+pub fn run() -> TestResult {
+    let (name, _listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+    let client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).context("connect failed")?;
+    check(&client).context("named pipe stream")?;
+
+    let (writer, reader) = unnamed_pipe::pipe().context("unnamed pipe creation failed")?;
+    check(&writer).context("unnamed pipe writer")?;
+    check(&reader).context("unnamed pipe reader")?;
+    Ok(())
+}
+
+fn check(obj: &impl ShareHandle) -> TestResult {
+    ensure_eq!(obj.is_inheritable().context("initial query failed")?, false);
+    obj.set_inheritable(true).context("enabling inheritance failed")?;
+    ensure_eq!(obj.is_inheritable().context("query failed")?, true);
+    obj.set_inheritable(false).context("disabling inheritance failed")?;
+    ensure_eq!(obj.is_inheritable().context("query failed")?, false);
+
+    // Sharing with ourselves is the same as duplicating the handle.
+    let shared = obj.share_with_pid(process::id()).context("sharing failed")?;
+    drop(unsafe { OwnedHandle::from_raw_handle(shared) });
+    Ok(())
+}
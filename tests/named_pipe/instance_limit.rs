@@ -0,0 +1,46 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, InstanceLimitReached, PipeListenerOptions};
+use std::{ffi::OsStr, num::NonZeroU8, thread, time::Duration};
+
+type Stream = DuplexPipeStream<pipe_mode::Bytes>;
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .instance_limit(NonZeroU8::new(1))
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    let client = Stream::connect(&*name).context("first connect failed")?;
+    let conn = listener.accept().context("first accept failed")?;
+
+    let e = listener.accept().unwrap_err();
+    ensure!(InstanceLimitReached::is_cause_of(&e), "unexpected error: {e}");
+
+    drop((conn, client));
+
+    // No instance of the pipe might exist for a while, so the client has to retry.
+    let client = thread::spawn(move || {
+        for _ in 0..500 {
+            if let Ok(client) = Stream::connect(&*name) {
+                return Some(client);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        None
+    });
+    // Closing the server-side handle might be deferred, so give it some time.
+    for _ in 0..500 {
+        match listener.accept() {
+            Err(e) if InstanceLimitReached::is_cause_of(&e) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => bail!("second accept failed: {e}"),
+            Ok(..) => {
+                ensure!(client.join().unwrap().is_some(), "second connect failed");
+                return Ok(());
+            }
+        }
+    }
+    bail!("instance was never freed")
+}
@@ -0,0 +1,45 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::windows::named_pipe::{pipe_mode, ConnectOptions, PipeListenerOptions};
+use std::{
+    ffi::OsStr,
+    io::{prelude::*, BufReader},
+    num::NonZeroU8,
+};
+
+const POOL_SIZE: u8 = 4;
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .pending_instances(NonZeroU8::new(POOL_SIZE).unwrap())
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    // All of those have an instance ready for them without any calls to accept, so none of them should ever see the
+    // pipe as busy.
+    let clients = (0..POOL_SIZE)
+        .map(|i| {
+            ConnectOptions::new()
+                .name(OsStr::new(&*name))
+                .retry_if_busy(false)
+                .connect_duplex::<pipe_mode::Bytes>()
+                .with_context(|| format!("connect {i} failed"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, mut client) in clients.into_iter().enumerate() {
+        writeln!(client, "{i}").context("client write failed")?;
+    }
+    // Clients are picked up in the order in which they connected.
+    for i in 0..POOL_SIZE {
+        let conn = listener.accept().with_context(|| format!("accept {i} failed"))?;
+        let mut line = String::new();
+        BufReader::new(conn)
+            .read_line(&mut line)
+            .context("server read failed")?;
+        ensure_eq!(line, format!("{i}\n"));
+    }
+    Ok(())
+}
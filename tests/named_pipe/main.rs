@@ -4,8 +4,27 @@
 mod util;
 use util::*;
 
+mod accept_timeout;
 mod bytes;
+mod connect_timeout;
+mod custom_flags;
+mod flush;
+mod handle_state;
+mod impersonation;
+mod inheritance;
+mod instance_limit;
+mod instance_pool;
 mod msg;
+mod name_occupied;
+mod nonblocking;
+mod peer_identity;
+mod pipe_info;
+mod recv_msg;
+mod reuse;
+mod security;
+mod security_qos;
+mod split;
+mod transact;
 
 use std::sync::{mpsc::Sender, Arc};
 fn mk_server(
@@ -58,3 +77,110 @@ fn named_pipe_msg_unidir_server_to_client() -> TestResult {
     install_color_eyre();
     drive_server_and_multiple_clients(mk_server(server, false, true), mk_client(client, true, false))
 }
+
+#[test]
+fn named_pipe_security_descriptor() -> TestResult {
+    install_color_eyre();
+    security::run()
+}
+
+#[test]
+fn named_pipe_impersonation() -> TestResult {
+    install_color_eyre();
+    impersonation::run()
+}
+
+#[test]
+fn named_pipe_connect_with_timeout() -> TestResult {
+    install_color_eyre();
+    connect_timeout::run()
+}
+
+#[test]
+fn named_pipe_handle_state() -> TestResult {
+    install_color_eyre();
+    handle_state::run()
+}
+#[test]
+fn named_pipe_nonblocking() -> TestResult {
+    install_color_eyre();
+    nonblocking::run()
+}
+#[test]
+fn named_pipe_pipe_info() -> TestResult {
+    install_color_eyre();
+    pipe_info::run()
+}
+#[test]
+fn named_pipe_instance_pool() -> TestResult {
+    install_color_eyre();
+    instance_pool::run()
+}
+#[test]
+fn named_pipe_accept_timeout() -> TestResult {
+    install_color_eyre();
+    accept_timeout::run()
+}
+#[test]
+fn named_pipe_reuse() -> TestResult {
+    install_color_eyre();
+    reuse::run()
+}
+#[test]
+fn named_pipe_inheritance() -> TestResult {
+    install_color_eyre();
+    inheritance::run()
+}
+#[test]
+fn named_pipe_recv_msg() -> TestResult {
+    install_color_eyre();
+    recv_msg::run()
+}
+#[test]
+fn named_pipe_peer_identity() -> TestResult {
+    install_color_eyre();
+    peer_identity::run()
+}
+#[test]
+fn named_pipe_security_qos() -> TestResult {
+    install_color_eyre();
+    security_qos::run()
+}
+#[test]
+fn named_pipe_custom_flags() -> TestResult {
+    install_color_eyre();
+    custom_flags::run()
+}
+#[test]
+fn named_pipe_split() -> TestResult {
+    install_color_eyre();
+    split::run()
+}
+#[test]
+fn named_pipe_name_occupied() -> TestResult {
+    install_color_eyre();
+    name_occupied::run()
+}
+#[test]
+fn named_pipe_instance_limit() -> TestResult {
+    install_color_eyre();
+    instance_limit::run()
+}
+
+#[test]
+fn named_pipe_noop_flush() -> TestResult {
+    install_color_eyre();
+    flush::run()
+}
+
+#[test]
+fn named_pipe_transact() -> TestResult {
+    install_color_eyre();
+    transact::run()
+}
+
+#[test]
+fn named_pipe_call() -> TestResult {
+    install_color_eyre();
+    transact::run_call()
+}
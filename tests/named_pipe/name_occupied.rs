@@ -0,0 +1,31 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, NameOccupied, PipeListenerOptions};
+use std::{ffi::OsStr, io, thread, time::Duration};
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+    let opts = PipeListenerOptions::new().name(OsStr::new(&*name));
+
+    let Err(e) = opts.create_duplex::<pipe_mode::Bytes>() else {
+        bail!("created a second listener with the same name");
+    };
+    ensure_eq!(e.kind(), io::ErrorKind::AddrInUse);
+    ensure!(NameOccupied::is_cause_of(&e), "error does not wrap NameOccupied: {e}");
+
+    // The name becomes available once the previous owner goes away.
+    let owner = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        drop(listener);
+    });
+    opts.name_takeover_timeout(Some(Duration::from_secs(10)))
+        .create_duplex::<pipe_mode::Bytes>()
+        .context("takeover failed")?;
+    owner.join().unwrap();
+
+    Ok(())
+}
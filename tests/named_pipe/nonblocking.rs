@@ -0,0 +1,59 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions};
+use std::{
+    ffi::OsStr,
+    io::{self, prelude::*},
+    thread,
+    time::Duration,
+};
+
+const MSG: &[u8] = b"Hello from client!\n";
+
+fn would_block<T>(r: io::Result<T>) -> bool {
+    matches!(r, Err(e) if e.kind() == io::ErrorKind::WouldBlock)
+}
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .nonblocking(true)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    ensure!(would_block(listener.accept()), "accept didn't fail with WouldBlock");
+
+    let mut client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).context("connect failed")?;
+    client.set_nonblocking(true).context("client set_nonblocking failed")?;
+
+    let mut conn = loop {
+        match listener.accept() {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(10)),
+            els => break els.context("accept failed")?,
+        }
+    };
+
+    let mut buf = [0; 64];
+    ensure!(
+        would_block(conn.read(&mut buf)),
+        "server-side read didn't fail with WouldBlock"
+    );
+    ensure!(
+        would_block(client.read(&mut buf)),
+        "client-side read didn't fail with WouldBlock"
+    );
+
+    client.write_all(MSG).context("client write failed")?;
+    for _ in 0..500 {
+        match conn.read(&mut buf) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(10)),
+            els => {
+                let len = els.context("server-side read failed")?;
+                ensure_eq!(&buf[..len], MSG);
+                return Ok(());
+            }
+        }
+    }
+    bail!("data never arrived")
+}
@@ -0,0 +1,36 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PeerIdentity, PipeListenerOptions};
+use std::{ffi::OsStr, process};
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    let client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).context("connect failed")?;
+    let conn = listener.accept().context("accept failed")?;
+
+    // Both ends are in the same process, so the identity of the client is our own.
+    let identity = conn.client_identity().context("client identity query failed")?;
+    ensure_eq!(identity.process_id, process::id());
+    ensure_eq!(
+        identity
+            .executable_path
+            .canonicalize()
+            .context("canonicalization failed")?,
+        std::env::current_exe()?
+            .canonicalize()
+            .context("canonicalization failed")?
+    );
+    ensure_eq!(identity.user_sid.to_string_lossy().starts_with("S-1-"), true);
+
+    // The client sees the same process on the other end.
+    let server_identity =
+        PeerIdentity::query(client.server_process_id().context("server PID query failed")?).context("query failed")?;
+    ensure_eq!(server_identity, identity);
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions, PipeMode};
+use std::{ffi::OsStr, num::NonZeroU8};
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .mode(PipeMode::Messages)
+            .instance_limit(NonZeroU8::new(3))
+            .create_duplex::<pipe_mode::Messages>()
+    })?;
+
+    let client = DuplexPipeStream::<pipe_mode::Messages>::connect(&*name).context("connect failed")?;
+    let conn = listener.accept().context("accept failed")?;
+
+    let server_info = conn.pipe_info().context("server-side query failed")?;
+    ensure_eq!(server_info.is_server, true);
+    ensure_eq!(server_info.mode, PipeMode::Messages);
+    ensure_eq!(server_info.instance_limit, NonZeroU8::new(3));
+
+    let client_info = client.pipe_info().context("client-side query failed")?;
+    ensure_eq!(client_info.is_server, false);
+    ensure_eq!(client_info.mode, PipeMode::Messages);
+    ensure_eq!(client_info.instance_limit, NonZeroU8::new(3));
+
+    // Both ends are in the same process, and thus in the same session on the same computer.
+    ensure_eq!(
+        conn.client_session_id().context("client session ID query failed")?,
+        conn.server_session_id().context("server session ID query failed")?
+    );
+    ensure_eq!(conn.client_computer_name().context("computer name query failed")?, None);
+
+    Ok(())
+}
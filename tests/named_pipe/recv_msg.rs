@@ -0,0 +1,48 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::{
+    os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions, PipeMode},
+    reliable_recv_msg::*,
+};
+use std::{ffi::OsStr, thread};
+
+const SMALL: &[u8] = b"Short";
+const BIG: &[u8] = b"A message which doesn't fit into the buffer it's being received into";
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .mode(PipeMode::Messages)
+            .create_duplex::<pipe_mode::Messages>()
+    })?;
+    let server = thread::spawn(move || {
+        let conn = listener.accept().context("accept failed")?;
+        for msg in [BIG, SMALL, BIG] {
+            conn.send(msg).context("send failed")?;
+        }
+        conn.flush().context("flush failed")?;
+        TestResult::Ok(())
+    });
+
+    let mut conn = DuplexPipeStream::<pipe_mode::Messages>::connect(&*name).context("connect failed")?;
+    let mut buf = [0; 16];
+
+    // Checking whether the message fits must not consume it.
+    let TryRecvResult { size, fit } = conn.try_recv(&mut buf).context("first try_recv failed")?;
+    ensure_eq!((size, fit), (BIG.len(), false));
+    match conn.recv(&mut buf).context("first recv failed")? {
+        RecvResult::Alloc(msg) => ensure_eq!(msg, BIG),
+        RecvResult::Fit(..) => bail!("oversized message reported as fitting"),
+    }
+
+    match conn.recv(&mut buf).context("second recv failed")? {
+        RecvResult::Fit(sz) => ensure_eq!(&buf[..sz], SMALL),
+        RecvResult::Alloc(..) => bail!("small message reported as not fitting"),
+    }
+
+    let msg = conn.recv(&mut buf).context("third recv failed")?;
+    ensure_eq!(msg.borrow_to_size(&buf), BIG);
+
+    server.join().unwrap()
+}
@@ -0,0 +1,50 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions};
+use std::{
+    ffi::OsStr,
+    io::{self, prelude::*},
+    num::NonZeroU8,
+    thread,
+    time::Duration,
+};
+
+type Stream = DuplexPipeStream<pipe_mode::Bytes>;
+
+pub fn run() -> TestResult {
+    // With only one instance allowed, the second client can only be served by the reused one.
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .instance_limit(NonZeroU8::new(1))
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    let mut client = Stream::connect(&*name).context("first connect failed")?;
+    let conn = listener.accept().context("first accept failed")?;
+    conn.disconnect().context("disconnect failed")?;
+    let mut buf = [0];
+    ensure_eq!(client.read(&mut buf).context("read after disconnect failed")?, 0);
+    drop(client);
+
+    listener.reuse(conn).context("reuse failed")?;
+
+    // The reused instance only accepts clients once it's being waited on.
+    let client = thread::spawn(move || {
+        for _ in 0..500 {
+            if let Ok(mut client) = Stream::connect(&*name) {
+                client.write_all(b"x")?;
+                return Ok(true);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        io::Result::Ok(false)
+    });
+    let mut conn = listener.accept().context("second accept failed")?;
+    conn.read_exact(&mut buf).context("server read failed")?;
+    ensure_eq!(buf, *b"x");
+    if !client.join().unwrap().context("second client failed")? {
+        bail!("second connect never succeeded");
+    }
+    Ok(())
+}
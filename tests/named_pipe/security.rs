@@ -0,0 +1,31 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::windows::{
+    named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions},
+    SecurityDescriptor,
+};
+use std::{ffi::OsStr, thread};
+
+pub fn run() -> TestResult {
+    ensure!(
+        SecurityDescriptor::from_sddl("not an SDDL string").is_err(),
+        "invalid SDDL string was accepted"
+    );
+
+    // Only the owner (which is us) may access the pipe.
+    let sd = SecurityDescriptor::from_sddl("D:P(A;;GA;;;OW)").context("SDDL parsing failed")?;
+    ensure_eq!(sd.clone(), sd);
+    let copy = unsafe { SecurityDescriptor::copy_from_raw(sd.as_ptr()) }.context("copying failed")?;
+    ensure_eq!(copy, sd);
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .security_descriptor(sd.clone())
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+    let client = thread::spawn(move || DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).map(drop));
+    listener.accept().context("accept failed")?;
+    client.join().unwrap().context("connect failed")?;
+    Ok(())
+}
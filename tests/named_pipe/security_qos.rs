@@ -0,0 +1,54 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::windows::named_pipe::{
+    pipe_mode, ConnectOptions, ImpersonationLevel, PipeListener, PipeListenerOptions,
+};
+use std::{ffi::OsStr, io::prelude::*, thread};
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    // An anonymous client can't be identified, so opening its token fails.
+    ensure!(
+        client_token_opens(&listener, &name, ImpersonationLevel::Anonymous)?.is_err(),
+        "opened the token of an anonymous client"
+    );
+    // At the identification level, the server may still query the client's token.
+    client_token_opens(&listener, &name, ImpersonationLevel::Identification)?
+        .context("failed to open client token at identification level")?;
+
+    Ok(())
+}
+
+fn client_token_opens(
+    listener: &PipeListener<pipe_mode::Bytes, pipe_mode::Bytes>,
+    name: &str,
+    level: ImpersonationLevel,
+) -> TestResult<std::io::Result<()>> {
+    let name = name.to_owned();
+    let client = thread::spawn(move || {
+        let conn = ConnectOptions::new()
+            .name(OsStr::new(&name))
+            .impersonation_level(Some(level))
+            .effective_only(true)
+            .connect_duplex::<pipe_mode::Bytes>()
+            .context("connect failed")?;
+        // The server can only impersonate us after having read something from the pipe.
+        (&conn).write_all(&[0]).context("client send failed")?;
+        // Keep the connection open until the server is done with it.
+        let _ = (&conn).read(&mut [0]);
+        TestResult::Ok(())
+    });
+
+    let conn = listener.accept().context("accept failed")?;
+    (&conn).read_exact(&mut [0]).context("server receive failed")?;
+    let result = conn.get_client_token().map(drop);
+    drop(conn);
+
+    client.join().unwrap()?;
+    Ok(result)
+}
@@ -0,0 +1,49 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions};
+use std::{ffi::OsStr, io::prelude::*, thread};
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_duplex::<pipe_mode::Bytes>()
+    })?;
+    let client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).context("connect failed")?;
+    let server = listener.accept().context("accept failed")?;
+    let other_client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name).context("second connect failed")?;
+    let _other_server = listener.accept().context("second accept failed")?;
+
+    // Each half is owned by its own thread.
+    let (mut recver, mut sender) = client.split();
+    let reader = thread::spawn(move || {
+        let mut buf = [0; 4];
+        recver.read_exact(&mut buf).context("client receive failed")?;
+        ensure_eq!(&buf, b"pong");
+        TestResult::Ok(recver)
+    });
+    sender.write_all(b"ping").context("client send failed")?;
+    let mut buf = [0; 4];
+    (&server).read_exact(&mut buf).context("server receive failed")?;
+    ensure_eq!(&buf, b"ping");
+    (&server).write_all(b"pong").context("server send failed")?;
+    let recver = reader.join().unwrap()?;
+
+    // Halves of different streams can't be reunited.
+    let (other_recver, other_sender) = other_client.split();
+    let Err(err) = DuplexPipeStream::reunite(recver, other_sender) else {
+        bail!("halves of different streams were reunited");
+    };
+    let client = DuplexPipeStream::reunite(err.recv_half, sender).context("reunite failed")?;
+    drop(other_recver);
+
+    (&client)
+        .write_all(b"done")
+        .context("client send after reunite failed")?;
+    (&server)
+        .read_exact(&mut buf)
+        .context("server receive after reunite failed")?;
+    ensure_eq!(&buf, b"done");
+
+    Ok(())
+}
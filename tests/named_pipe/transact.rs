@@ -0,0 +1,71 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::{
+    os::windows::named_pipe::{self, pipe_mode, DuplexPipeStream, PipeListenerOptions, PipeMode},
+    reliable_recv_msg::*,
+};
+use std::{ffi::OsStr, thread, time::Duration};
+
+const REQUEST: &[u8] = b"Request from client";
+const RESPONSE: &[u8] = b"Somewhat longer response from server";
+
+pub fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .mode(PipeMode::Messages)
+            .create_duplex::<pipe_mode::Messages>()
+    })?;
+    let server = thread::spawn(move || {
+        let mut conn = listener.accept().context("accept failed")?;
+        for _ in 0..2 {
+            let mut buf = [0; 64];
+            let req = conn.recv(&mut buf).context("server receive failed")?;
+            ensure_eq!(req.borrow_to_size(&buf), REQUEST);
+            conn.send(RESPONSE).context("server send failed")?;
+        }
+        conn.flush().context("server flush failed")?;
+        TestResult::Ok(())
+    });
+
+    let conn = DuplexPipeStream::<pipe_mode::Messages>::connect(&*name).context("connect failed")?;
+
+    let mut buf = [0; 64];
+    match conn.transact(REQUEST, &mut buf).context("first transaction failed")? {
+        RecvResult::Fit(sz) => ensure_eq!(&buf[..sz], RESPONSE),
+        RecvResult::Alloc(..) => bail!("response didn't fit into a buffer of sufficient size"),
+    }
+
+    // Now with a buffer that is too small.
+    let mut buf = [0; 8];
+    match conn.transact(REQUEST, &mut buf).context("second transaction failed")? {
+        RecvResult::Fit(..) => bail!("response fit into a buffer that is too small"),
+        RecvResult::Alloc(msg) => ensure_eq!(msg, RESPONSE),
+    }
+
+    server.join().unwrap()
+}
+
+pub fn run_call() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .mode(PipeMode::Messages)
+            .create_duplex::<pipe_mode::Messages>()
+    })?;
+    let server = thread::spawn(move || {
+        let mut conn = listener.accept().context("accept failed")?;
+        let mut buf = [0; 64];
+        let req = conn.recv(&mut buf).context("server receive failed")?;
+        ensure_eq!(req.borrow_to_size(&buf), REQUEST);
+        conn.send(RESPONSE).context("server send failed")?;
+        conn.flush().context("server flush failed")?;
+        TestResult::Ok(())
+    });
+
+    let mut buf = [0; 64];
+    let len = named_pipe::call(&*name, REQUEST, &mut buf, Some(Duration::from_secs(5))).context("call failed")?;
+    ensure_eq!(&buf[..len], RESPONSE);
+
+    server.join().unwrap()
+}
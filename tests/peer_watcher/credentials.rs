@@ -0,0 +1,23 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::{
+    os::unix::udsocket::{UdSocket, UdStream, UdStreamListener},
+    peer_watcher::PeerWatcher,
+};
+use std::time::Duration;
+
+pub fn run(mut namegen: NameGen) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut namegen, |nm| UdStreamListener::bind(nm))?;
+    let _client = UdStream::connect(&*name).context("connect failed")?;
+    let conn = listener.accept().context("accept failed")?;
+    let credentials = conn.get_peer_credentials().context("getting peer credentials failed")?;
+
+    // The peer is this very process, which had better not exit during the test.
+    let watcher = PeerWatcher::from_credentials(&credentials).context("watcher creation failed")?;
+    ensure!(!watcher.has_exited()?, "live peer reported as exited");
+    ensure!(
+        !watcher.wait_timeout(Duration::from_millis(10))?,
+        "wait didn't time out"
+    );
+    Ok(())
+}
@@ -0,0 +1,32 @@
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+#[cfg(uds_ucred)]
+mod credentials;
+mod process;
+
+#[cfg(uds_ucred)]
+#[test]
+fn peer_watcher_credentials() -> TestResult {
+    install_color_eyre();
+    credentials::run(NameGen::new(make_id!(), false))
+}
+#[test]
+fn peer_watcher_process() -> TestResult {
+    install_color_eyre();
+    process::run()
+}
+#[test]
+fn peer_watcher_on_exit() -> TestResult {
+    install_color_eyre();
+    process::run_on_exit()
+}
+/// The process watched by `peer_watcher_process` and `peer_watcher_on_exit`.
+#[test]
+#[ignore]
+fn peer_watcher_child() -> TestResult {
+    install_color_eyre();
+    process::run_child()
+}
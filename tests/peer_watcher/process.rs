@@ -0,0 +1,70 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::peer_watcher::PeerWatcher;
+use std::{
+    env,
+    io::{self, Read},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    time::Duration,
+};
+
+const CHILD_ENV: &str = "INTERPROCESS_TEST_PEER_WATCHER_CHILD";
+
+/// Spawns a child process which exits once its standard input is closed.
+fn spawn_child() -> TestResult<Child> {
+    Command::new(env::current_exe()?)
+        .args(["--exact", "peer_watcher_child", "--ignored", "--test-threads=1"])
+        .env(CHILD_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("spawn failed")
+}
+
+pub fn run() -> TestResult {
+    let mut child = spawn_child()?;
+    let watcher = PeerWatcher::new(child.id()).context("watcher creation failed")?;
+    ensure!(!watcher.has_exited()?, "child reported as exited right away");
+    ensure!(
+        !watcher.wait_timeout(Duration::from_millis(10))?,
+        "wait didn't time out"
+    );
+
+    drop(child.stdin.take());
+    ensure!(
+        watcher.wait_timeout(Duration::from_secs(30))?,
+        "child didn't exit after its input was closed"
+    );
+    ensure!(watcher.has_exited()?, "exited child isn't reported as such");
+    watcher.wait().context("wait failed")?;
+    child.wait().context("reaping child failed")?;
+    Ok(())
+}
+
+pub fn run_on_exit() -> TestResult {
+    let mut child = spawn_child()?;
+    let watcher = PeerWatcher::new(child.id()).context("watcher creation failed")?;
+    let (tx, rx) = mpsc::channel();
+    watcher
+        .on_exit(move || tx.send(()).unwrap())
+        .context("callback registration failed")?;
+    ensure!(
+        rx.recv_timeout(Duration::from_millis(10)).is_err(),
+        "callback invoked before the child exited"
+    );
+    drop(child.stdin.take());
+    rx.recv_timeout(Duration::from_secs(30))
+        .context("callback not invoked after the child exited")?;
+    child.wait().context("reaping child failed")?;
+    Ok(())
+}
+
+pub fn run_child() -> TestResult {
+    if env::var_os(CHILD_ENV).is_none() {
+        return Ok(());
+    }
+    io::stdin().read_to_end(&mut Vec::new())?;
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::shared_memory::SharedMemory;
+use std::io;
+
+const SIZE: usize = 4096;
+
+pub fn run() -> TestResult {
+    ensure!(
+        matches!(SharedMemory::anonymous(0), Err(e) if e.kind() == io::ErrorKind::InvalidInput),
+        "empty object was created"
+    );
+
+    let shm = SharedMemory::anonymous(SIZE).context("creation failed")?;
+    ensure_eq!(shm.len(), SIZE);
+
+    // Map the object a second time, as if its file descriptor or handle was received from another process.
+    #[cfg(unix)]
+    let other = {
+        let fd = std::os::unix::io::AsFd::as_fd(&shm).try_clone_to_owned()?;
+        SharedMemory::from_fd(fd).context("mapping from file descriptor failed")?
+    };
+    #[cfg(windows)]
+    let other = {
+        let handle = std::os::windows::io::AsHandle::as_handle(&shm).try_clone_to_owned()?;
+        SharedMemory::from_handle(handle).context("mapping from handle failed")?
+    };
+    ensure_eq!(other.len(), SIZE);
+    unsafe {
+        // SAFETY: nobody else knows about the object
+        shm.as_ptr().write_volatile(42);
+        ensure_eq!(other.as_ptr().read_volatile(), 42);
+    }
+    Ok(())
+}
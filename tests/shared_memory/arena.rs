@@ -0,0 +1,111 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::shared_memory::{ArenaOffset, SharedArena, SharedMemory};
+use std::{io, process, thread};
+
+// Each of the threads below ends up holding around 30 KiB at once.
+const CAPACITY: usize = 256 * 1024;
+const THREADS: u8 = 4;
+const ROUNDS: usize = 500;
+
+/// Attaches to the same arena through a separate mapping, as if it was in another process.
+fn attach(arena: &SharedArena) -> TestResult<SharedArena> {
+    #[cfg(unix)]
+    let shm = SharedMemory::from_fd(std::os::unix::io::AsFd::as_fd(arena.shared_memory()).try_clone_to_owned()?);
+    #[cfg(windows)]
+    let shm = SharedMemory::from_handle(
+        std::os::windows::io::AsHandle::as_handle(arena.shared_memory()).try_clone_to_owned()?,
+    );
+    SharedArena::open(shm.context("mapping a second time failed")?).context("attaching failed")
+}
+
+fn is_kind<T>(result: io::Result<T>, kind: io::ErrorKind) -> bool {
+    matches!(result, Err(e) if e.kind() == kind)
+}
+
+pub fn run() -> TestResult {
+    let shm = SharedMemory::anonymous(SharedArena::required_size(CAPACITY)).context("creation failed")?;
+    let arena = SharedArena::create(shm).context("initialization failed")?;
+    let other = attach(&arena)?;
+    ensure!(arena.capacity() >= CAPACITY, "arena smaller than requested");
+
+    // Offsets mean the same thing in both mappings.
+    let offset = arena.alloc(100).context("allocation failed")?;
+    ensure_eq!(arena.ptr(offset) as usize % 16, 0);
+    unsafe { arena.ptr(offset).write_bytes(0xAB, 100) };
+    ensure_eq!(unsafe { other.ptr(offset).add(99).read() }, 0xAB);
+    ensure_eq!(other.offset_of(other.ptr(offset)), Some(offset));
+    other.free(offset).context("freeing from another mapping failed")?;
+    ensure!(
+        is_kind(arena.free(offset), io::ErrorKind::InvalidInput),
+        "double free went unnoticed"
+    );
+    ensure!(
+        is_kind(arena.free(ArenaOffset::from_raw(3)), io::ErrorKind::InvalidInput),
+        "bogus offset was accepted"
+    );
+    ensure!(
+        is_kind(arena.alloc(arena.capacity()), io::ErrorKind::OutOfMemory),
+        "allocation larger than the arena succeeded"
+    );
+
+    // Freed blocks are merged back together, no matter the order.
+    let offsets = (0..16).map(|_| arena.alloc(1000)).collect::<io::Result<Vec<_>>>()?;
+    for offset in offsets.iter().step_by(2).chain(offsets.iter().skip(1).step_by(2)) {
+        arena.free(*offset)?;
+    }
+    let whole = arena.alloc(CAPACITY).context("free blocks weren't merged")?;
+    arena.free(whole)?;
+
+    // Quotas.
+    let pid = process::id();
+    arena.set_quota(pid, Some(256))?;
+    let first = arena.alloc(200).context("allocation within the quota failed")?;
+    ensure!(
+        is_kind(other.alloc(100), io::ErrorKind::OutOfMemory),
+        "quota was not enforced"
+    );
+    let used = other.usage(pid)?;
+    ensure!((200..=256).contains(&used), "unexpected usage of {used} bytes");
+    arena.free(first)?;
+    ensure_eq!(arena.usage(pid)?, 0);
+    arena.set_quota(pid, None)?;
+    let big = arena.alloc(1000).context("allocation failed after lifting the quota")?;
+    // The header and the size, rounded up to the alignment.
+    ensure_eq!(arena.usage(pid)?, 1024);
+    arena.free(big)?;
+
+    // Concurrent allocations never overlap.
+    let threads = (0..THREADS)
+        .map(|t| {
+            let arena = attach(&arena)?;
+            Ok(thread::spawn(move || -> TestResult {
+                let mut held = Vec::new();
+                for i in 0..ROUNDS {
+                    let len = 1 + (i * 37 + usize::from(t) * 11) % 300;
+                    let offset = arena.alloc(len)?;
+                    unsafe { arena.ptr(offset).write_bytes(t, len) };
+                    held.push((offset, len));
+                    if i % 3 == 2 {
+                        for (offset, len) in held.drain(..2) {
+                            for j in 0..len {
+                                ensure_eq!(unsafe { arena.ptr(offset).add(j).read() }, t);
+                            }
+                            arena.free(offset)?;
+                        }
+                    }
+                }
+                for (offset, _) in held {
+                    arena.free(offset)?;
+                }
+                Ok(())
+            }))
+        })
+        .collect::<TestResult<Vec<_>>>()?;
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+    let whole = arena.alloc(CAPACITY).context("memory leaked after concurrent use")?;
+    arena.free(whole)?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::{shared_memory::Doorbell, TryClone};
+use std::{thread, time::Duration};
+
+pub fn run() -> TestResult {
+    let doorbell = Doorbell::new().context("creation failed")?;
+    let other = doorbell.try_clone().context("cloning failed")?;
+    ensure!(
+        !doorbell
+            .wait_timeout(Duration::from_millis(20))
+            .context("timed wait failed")?,
+        "timed wait succeeded on a silent doorbell"
+    );
+
+    // Rings aren't lost, but they don't add up either.
+    other.ring().context("ringing failed")?;
+    other.ring().context("ringing twice failed")?;
+    ensure!(
+        doorbell
+            .wait_timeout(Duration::from_secs(10))
+            .context("timed wait failed")?,
+        "timed wait failed on a ringing doorbell"
+    );
+    ensure!(
+        !doorbell
+            .wait_timeout(Duration::from_millis(20))
+            .context("timed wait failed")?,
+        "doorbell still rang after a wait"
+    );
+
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| doorbell.wait());
+        thread::sleep(Duration::from_millis(10));
+        other.ring().context("ringing failed")?;
+        waiter.join().unwrap().context("wait failed")
+    })
+}
+
+#[cfg(all(unix, feature = "async-io"))]
+pub fn run_async_io() -> TestResult {
+    use futures::{executor::block_on, future::try_join};
+    let doorbell = Doorbell::new().context("creation failed")?;
+    let other = doorbell.try_clone().context("cloning failed")?;
+    let ring = async {
+        async_io::Timer::after(Duration::from_millis(10)).await;
+        other.ring()
+    };
+    block_on(try_join(doorbell.wait_async_io(), ring)).context("waiting failed")?;
+    ensure!(
+        !doorbell
+            .wait_timeout(Duration::from_millis(20))
+            .context("timed wait failed")?,
+        "doorbell still rang after an asynchronous wait"
+    );
+
+    // Two waits on the same doorbell at once, each finished by its own ring.
+    let ring = async {
+        for _ in 0..2 {
+            async_io::Timer::after(Duration::from_millis(10)).await;
+            other.ring()?;
+        }
+        Ok(())
+    };
+    let waits = try_join(doorbell.wait_async_io(), doorbell.wait_async_io());
+    block_on(try_join(waits, ring)).context("concurrent waits failed")?;
+    Ok(())
+}
+
+/// Timed waits which get interrupted by a signal keep waiting for the rest of the timeout.
+#[cfg(unix)]
+pub fn run_interrupted() -> TestResult {
+    use std::{mem::zeroed, os::unix::thread::JoinHandleExt, time::Instant};
+    extern "C" fn noop(_: libc::c_int) {}
+    // SIGURG is ignored by default, and ignored signals don't interrupt anything.
+    let mut action: libc::sigaction = unsafe { zeroed() };
+    action.sa_sigaction = noop as *const () as libc::sighandler_t;
+    ensure!(
+        unsafe { libc::sigaction(libc::SIGURG, &action, std::ptr::null_mut()) } != -1,
+        "failed to install the signal handler"
+    );
+
+    let doorbell = Doorbell::new().context("creation failed")?;
+    let timeout = Duration::from_millis(200);
+    let waiter = thread::spawn(move || {
+        let start = Instant::now();
+        let rang = doorbell.wait_timeout(timeout).context("interrupted wait failed")?;
+        TestResult::Ok((rang, start.elapsed()))
+    });
+    for _ in 0..10 {
+        thread::sleep(Duration::from_millis(10));
+        unsafe { libc::pthread_kill(waiter.as_pthread_t(), libc::SIGURG) };
+    }
+    let (rang, elapsed) = waiter.join().unwrap()?;
+    ensure!(!rang, "timed wait succeeded on a silent doorbell");
+    ensure!(elapsed >= timeout, "wait ended early after {elapsed:?}");
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::shared_memory::{user_scoped_name, SharedMemory};
+use std::{io, process};
+
+pub fn run(id: &'static str) -> TestResult {
+    let base = format!("{}-{:08x}", process::id(), Xorshift32::from_id(id).next());
+    let name = user_scoped_name(&base).context("name scoping failed")?;
+    ensure!(
+        name.len() > base.len() && name.to_string_lossy().ends_with(&base),
+        "scoped name {name:?} doesn't extend {base:?}"
+    );
+
+    let creator = SharedMemory::create_with_drop_guard(&name, 4096).context("creation failed")?;
+    let opener = SharedMemory::open(&name).context("opening failed")?;
+    drop(creator);
+
+    // The mapping stays valid even once the name is gone.
+    unsafe {
+        // SAFETY: nobody else knows about the object
+        opener.as_ptr().write_volatile(42);
+        ensure_eq!(opener.as_ptr().read_volatile(), 42);
+    }
+    // On Windows, the object lives on for as long as a handle to it does.
+    #[cfg(unix)]
+    match SharedMemory::open(&name) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        els => bail!("unexpected result of opening an unlinked object: {els:?}"),
+    }
+    drop(opener);
+    #[cfg(windows)]
+    match SharedMemory::open(&name) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        els => bail!("unexpected result of opening a destroyed object: {els:?}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+mod anonymous;
+mod arena;
+mod doorbell;
+mod drop_guard;
+mod named;
+mod queue;
+mod ring;
+#[cfg(any(feature = "zerocopy", feature = "bytemuck"))]
+mod typed;
+
+#[test]
+fn shared_memory_anonymous() -> TestResult {
+    install_color_eyre();
+    anonymous::run()
+}
+#[test]
+fn shared_memory_arena() -> TestResult {
+    install_color_eyre();
+    arena::run()
+}
+#[test]
+fn shared_memory_doorbell() -> TestResult {
+    install_color_eyre();
+    doorbell::run()
+}
+#[cfg(unix)]
+#[test]
+fn shared_memory_doorbell_interrupted() -> TestResult {
+    install_color_eyre();
+    doorbell::run_interrupted()
+}
+#[cfg(all(unix, feature = "async-io"))]
+#[test]
+fn shared_memory_doorbell_async_io() -> TestResult {
+    install_color_eyre();
+    doorbell::run_async_io()
+}
+#[test]
+fn shared_memory_drop_guard() -> TestResult {
+    install_color_eyre();
+    drop_guard::run(make_id!())
+}
+#[test]
+fn shared_memory_named() -> TestResult {
+    install_color_eyre();
+    named::run(make_id!())
+}
+#[test]
+fn shared_memory_queue() -> TestResult {
+    install_color_eyre();
+    queue::run(false)
+}
+#[test]
+fn shared_memory_queue_doorbells() -> TestResult {
+    install_color_eyre();
+    queue::run(true)
+}
+#[test]
+fn shared_memory_ring() -> TestResult {
+    install_color_eyre();
+    ring::run(false)
+}
+#[test]
+fn shared_memory_ring_doorbells() -> TestResult {
+    install_color_eyre();
+    ring::run(true)
+}
+#[cfg(feature = "zerocopy")]
+#[test]
+fn shared_memory_typed_zerocopy() -> TestResult {
+    install_color_eyre();
+    typed::run_zerocopy()
+}
+#[cfg(feature = "bytemuck")]
+#[test]
+fn shared_memory_typed_bytemuck() -> TestResult {
+    install_color_eyre();
+    typed::run_bytemuck()
+}
@@ -0,0 +1,38 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::shared_memory::SharedMemory;
+use std::{io, process};
+
+const SIZE: usize = 4096;
+
+pub fn run(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let creator = SharedMemory::create(&name, SIZE).context("creation failed")?;
+    let result = check(&name, &creator);
+    #[cfg(unix)]
+    interprocess::shared_memory::unlink(&name).context("unlinking failed")?;
+    result
+}
+
+fn check(name: &str, creator: &SharedMemory) -> TestResult {
+    ensure_eq!(creator.len(), SIZE);
+    match SharedMemory::create(name, SIZE) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        els => bail!("unexpected result of creating a duplicate object: {els:?}"),
+    }
+
+    let opener = SharedMemory::open(name).context("opening failed")?;
+    ensure_eq!(opener.len(), SIZE);
+    ensure!(opener.as_ptr() != creator.as_ptr(), "same mapping returned twice");
+    unsafe {
+        // SAFETY: nobody else knows about the object
+        ensure!(opener.as_slice().iter().all(|&b| b == 0), "new object is not zeroed");
+        creator.as_ptr().add(123).write_volatile(42);
+        ensure_eq!(opener.as_ptr().add(123).read_volatile(), 42);
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::shared_memory::{MessageQueue, SharedMemory};
+use std::{io, sync::Arc, thread};
+
+const CAPACITY: usize = 16;
+const MAX_LEN: usize = 64;
+const SENDERS: u32 = 4;
+const RECEIVERS: u32 = 4;
+const PER_SENDER: u32 = 2000;
+
+/// Gives a handle the same doorbells as all the others, if any.
+type Configure = dyn Fn(MessageQueue) -> io::Result<MessageQueue>;
+
+/// Attaches a handle to the same queue through a separate mapping, as if it was in another process.
+fn attach(queue: &MessageQueue, configure: &Configure) -> TestResult<MessageQueue> {
+    #[cfg(unix)]
+    let shm = SharedMemory::from_fd(std::os::unix::io::AsFd::as_fd(queue.shared_memory()).try_clone_to_owned()?);
+    #[cfg(windows)]
+    let shm = SharedMemory::from_handle(
+        std::os::windows::io::AsHandle::as_handle(queue.shared_memory()).try_clone_to_owned()?,
+    );
+    let other = MessageQueue::open(shm.context("mapping a second time failed")?).context("attaching failed")?;
+    Ok(configure(other)?)
+}
+
+pub fn run(doorbells: bool) -> TestResult {
+    let size = MessageQueue::required_size(CAPACITY, MAX_LEN);
+    let shm = SharedMemory::anonymous(size).context("creation failed")?;
+    ensure!(
+        matches!(MessageQueue::create(SharedMemory::anonymous(size)?, CAPACITY * 2, MAX_LEN), Err(e) if e.kind() == io::ErrorKind::InvalidInput),
+        "queue larger than the shared memory object was created"
+    );
+    let configure: Box<Configure> = if doorbells {
+        use interprocess::{shared_memory::Doorbell, TryClone};
+        let (sent, received) = (Doorbell::new()?, Doorbell::new()?);
+        Box::new(move |queue| Ok(queue.with_doorbells(sent.try_clone()?, received.try_clone()?)))
+    } else {
+        Box::new(Ok)
+    };
+    let queue = configure(MessageQueue::create(shm, CAPACITY, MAX_LEN).context("initialization failed")?)?;
+
+    let mut buf = [0; MAX_LEN];
+    ensure!(
+        matches!(queue.try_recv(&mut buf), Err(e) if e.kind() == io::ErrorKind::WouldBlock),
+        "empty queue didn't report WouldBlock"
+    );
+    ensure!(
+        matches!(queue.try_send(&[0; MAX_LEN + 1]), Err(e) if e.kind() == io::ErrorKind::InvalidInput),
+        "overlong message was accepted"
+    );
+
+    let senders = (0..SENDERS)
+        .map(|s| {
+            let handle = attach(&queue, &*configure)?;
+            Ok(thread::spawn(move || {
+                for i in 0..PER_SENDER {
+                    // Messages of varying length, each identifying its sender and sequence number.
+                    let msg = format!("{s}:{i}:{}", "x".repeat(i as usize % 32));
+                    handle.send(msg.as_bytes())?;
+                }
+                Ok::<_, io::Error>(())
+            }))
+        })
+        .collect::<TestResult<Vec<_>>>()?;
+    let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let receivers = (0..RECEIVERS)
+        .map(|_| {
+            let handle = attach(&queue, &*configure)?;
+            let received = Arc::clone(&received);
+            Ok(thread::spawn(move || {
+                let mut buf = [0; MAX_LEN];
+                let mut mine = Vec::new();
+                loop {
+                    let len = handle.recv(&mut buf)?;
+                    if len == 0 {
+                        // The shutdown signal.
+                        break;
+                    }
+                    mine.push(String::from_utf8(buf[..len].to_vec()).unwrap());
+                }
+                received.lock().unwrap().extend(mine);
+                Ok::<_, io::Error>(())
+            }))
+        })
+        .collect::<TestResult<Vec<_>>>()?;
+
+    for sender in senders {
+        sender.join().unwrap().context("send failed")?;
+    }
+    for _ in 0..RECEIVERS {
+        queue.send(&[])?;
+    }
+    for receiver in receivers {
+        receiver.join().unwrap().context("receive failed")?;
+    }
+
+    let mut received = Arc::try_unwrap(received).unwrap().into_inner().unwrap();
+    ensure_eq!(received.len(), (SENDERS * PER_SENDER) as usize);
+    received.sort();
+    received.dedup();
+    ensure_eq!(received.len(), (SENDERS * PER_SENDER) as usize);
+    for msg in &received {
+        let mut parts = msg.splitn(3, ':');
+        let (s, i, pad) = (parts.next(), parts.next(), parts.next());
+        let (Some(i), Some(pad)) = (i.and_then(|i| i.parse::<usize>().ok()), pad) else {
+            bail!("malformed message {msg:?}");
+        };
+        ensure!(s.is_some() && pad.len() == i % 32, "corrupted message {msg:?}");
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::shared_memory::{RingReceiver, RingSender, SharedMemory, RING_HEADER_SIZE};
+use std::{
+    io::{self, prelude::*},
+    thread,
+};
+
+const CAPACITY: usize = 4096;
+const LEN: usize = 1024 * 1024;
+
+/// Returns two mappings of the same object, as if the second one was in another process.
+pub fn mappings() -> TestResult<(SharedMemory, SharedMemory)> {
+    let shm = SharedMemory::anonymous(RING_HEADER_SIZE + CAPACITY).context("creation failed")?;
+    #[cfg(unix)]
+    let other = SharedMemory::from_fd(std::os::unix::io::AsFd::as_fd(&shm).try_clone_to_owned()?);
+    #[cfg(windows)]
+    let other = SharedMemory::from_handle(std::os::windows::io::AsHandle::as_handle(&shm).try_clone_to_owned()?);
+    Ok((shm, other.context("mapping a second time failed")?))
+}
+pub fn byte_at(i: usize) -> u8 {
+    (i % 251) as u8
+}
+
+pub fn run(doorbells: bool) -> TestResult {
+    let (shm, other) = mappings()?;
+    let sender = RingSender::create(shm).context("initialization failed")?;
+    let receiver = RingReceiver::open(other).context("attaching failed")?;
+    ensure_eq!(sender.capacity(), CAPACITY);
+    ensure_eq!(receiver.capacity(), CAPACITY);
+    let (mut sender, mut receiver) = if doorbells {
+        use interprocess::{shared_memory::Doorbell, TryClone};
+        let (data_ready, space_ready) = (Doorbell::new()?, Doorbell::new()?);
+        let receiver = receiver.with_doorbells(data_ready.try_clone()?, space_ready.try_clone()?);
+        (sender.with_doorbells(data_ready, space_ready), receiver)
+    } else {
+        (sender, receiver)
+    };
+
+    ensure!(
+        matches!(receiver.try_recv(&mut [0; 16]), Err(e) if e.kind() == io::ErrorKind::WouldBlock),
+        "empty ring buffer didn't report WouldBlock"
+    );
+
+    // Much more data than fits into the buffer, so that both ends have to wait on each other.
+    let writer = thread::spawn(move || {
+        let data = (0..LEN).map(byte_at).collect::<Vec<_>>();
+        for chunk in data.chunks(1000) {
+            sender.write_all(chunk)?;
+        }
+        Ok::<_, io::Error>(())
+    });
+    let mut received = Vec::with_capacity(LEN);
+    receiver.read_to_end(&mut received).context("receive failed")?;
+    writer.join().unwrap().context("send failed")?;
+    ensure_eq!(received.len(), LEN);
+    ensure!(
+        received.iter().enumerate().all(|(i, &b)| b == byte_at(i)),
+        "data was corrupted"
+    );
+
+    let (shm, other) = mappings()?;
+    let mut sender = RingSender::create(shm)?;
+    drop(RingReceiver::open(other)?);
+    match sender.write(b"anyone there?") {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        els => bail!("unexpected result of sending to a dropped receiver: {els:?}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::shared_memory::SharedMemory;
+use std::io;
+
+const SIZE: usize = 4096;
+
+fn expect_invalid<T>(result: io::Result<T>, what: &str) -> TestResult {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => Ok(()),
+        Err(e) => Err(e).context(format!("unexpected error from {what}")),
+        Ok(..) => bail!("{what} succeeded"),
+    }
+}
+
+#[cfg(feature = "zerocopy")]
+pub fn run_zerocopy() -> TestResult {
+    let mut shm = SharedMemory::anonymous(SIZE).context("creation failed")?;
+    // SAFETY: nobody else knows about the object, and only one reference is alive at a time
+    unsafe {
+        *shm.view_mut::<u64>(8).context("mutable view failed")? = 0x0123_4567_89ab_cdef;
+        shm.view_slice_mut::<u32>(64, 4)
+            .context("mutable slice view failed")?
+            .copy_from_slice(&[1, 2, 3, 4]);
+
+        ensure_eq!(*shm.view::<u64>(8).context("view failed")?, 0x0123_4567_89ab_cdef);
+        ensure_eq!(shm.view_slice::<u32>(64, 4).context("slice view failed")?, [1, 2, 3, 4]);
+        ensure_eq!(
+            shm.view_slice::<u8>(SIZE, 0).context("empty slice view failed")?.len(),
+            0
+        );
+
+        expect_invalid(shm.view::<u64>(4), "misaligned view")?;
+        expect_invalid(shm.view::<u64>(SIZE - 4), "out-of-bounds view")?;
+        expect_invalid(shm.view_slice::<u32>(SIZE - 8, 3), "out-of-bounds slice view")?;
+        expect_invalid(shm.view_slice::<u32>(0, usize::MAX), "overflowing slice view")?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "bytemuck")]
+pub fn run_bytemuck() -> TestResult {
+    let mut shm = SharedMemory::anonymous(SIZE).context("creation failed")?;
+    // SAFETY: as above
+    unsafe {
+        *shm.view_pod_mut::<u64>(8).context("mutable view failed")? = 0x0123_4567_89ab_cdef;
+        shm.view_pod_slice_mut::<u32>(64, 4)
+            .context("mutable slice view failed")?
+            .copy_from_slice(&[1, 2, 3, 4]);
+
+        ensure_eq!(*shm.view_pod::<u64>(8).context("view failed")?, 0x0123_4567_89ab_cdef);
+        ensure_eq!(
+            shm.view_pod_slice::<u32>(64, 4).context("slice view failed")?,
+            [1, 2, 3, 4]
+        );
+
+        expect_invalid(shm.view_pod::<u64>(4), "misaligned view")?;
+        expect_invalid(shm.view_pod::<u64>(SIZE - 4), "out-of-bounds view")?;
+        expect_invalid(shm.view_pod_slice::<u32>(0, usize::MAX), "overflowing slice view")?;
+    }
+    Ok(())
+}
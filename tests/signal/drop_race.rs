@@ -0,0 +1,46 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::unix::signal::{rt_signal, send, SignalListener};
+use std::{
+    process,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+    thread,
+};
+
+/// Repeatedly drops listeners while another thread keeps raising the signal they listen to.
+pub fn run() -> TestResult {
+    let signal = rt_signal(2).unwrap();
+    let pid = process::id() as libc::pid_t;
+    // The disposition restored by dropping a listener, so that signals which arrive in between listeners are ignored
+    // instead of terminating the process.
+    ensure!(
+        unsafe { libc::signal(signal, libc::SIG_IGN) } != libc::SIG_ERR,
+        "failed to ignore the signal"
+    );
+
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let raiser = scope.spawn(|| {
+            while !done.load(Relaxed) {
+                send(pid, signal).context("sending a signal failed")?;
+            }
+            TestResult::Ok(())
+        });
+        let result = (|| {
+            for _ in 0..1000 {
+                drop(SignalListener::new(&[signal]).context("listener creation failed")?);
+            }
+            TestResult::Ok(())
+        })();
+        done.store(true, Relaxed);
+        raiser.join().unwrap()?;
+        result
+    })?;
+
+    // Nothing was left behind that keeps the signal routed or writes into a closed pipe.
+    let listener = SignalListener::new(&[signal]).context("listener creation after the race failed")?;
+    send(pid, signal).context("sending a signal failed")?;
+    let info = listener.recv().context("receive failed")?;
+    ensure!(info.signal() == signal, "wrong signal: {info:?}");
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::os::unix::signal::{rt_signal, send, send_queued, SignalListener};
+use std::{io, process};
+
+pub fn run() -> TestResult {
+    let (first, second) = (rt_signal(0).unwrap(), rt_signal(1).unwrap());
+    let pid = process::id() as libc::pid_t;
+    let listener = SignalListener::new(&[first, second]).context("listener creation failed")?;
+    match SignalListener::new(&[second]) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        els => bail!("unexpected result of routing a signal twice: {els:?}"),
+    }
+    match listener.try_recv() {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+        els => bail!("unexpected result of receiving with no signals: {els:?}"),
+    }
+
+    // Realtime signals are queued rather than coalesced, and are delivered in order.
+    for value in [1, 2, 3] {
+        send_queued(pid, first, value).context("sending a queued signal failed")?;
+    }
+    for value in [1, 2, 3] {
+        let info = listener.recv().context("receive failed")?;
+        ensure!(info.signal() == first, "wrong signal: {info:?}");
+        ensure!(info.value() == Some(value), "wrong payload: {info:?}");
+        ensure!(info.sender_pid() == Some(pid), "wrong sender: {info:?}");
+        ensure!(
+            info.sender_uid() == Some(unsafe { libc::getuid() }),
+            "wrong sender user: {info:?}"
+        );
+    }
+
+    send(pid, second).context("sending a plain signal failed")?;
+    let info = listener.recv().context("receive failed")?;
+    ensure!(info.signal() == second, "wrong signal: {info:?}");
+    ensure!(info.value().is_none(), "payload on a plain signal: {info:?}");
+
+    // Once the listener is gone, the signal can be routed elsewhere.
+    drop(listener);
+    drop(SignalListener::new(&[second]).context("rerouting failed")?);
+    Ok(())
+}
@@ -0,0 +1,20 @@
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+mod drop_race;
+mod listener;
+
+#[test]
+fn signal_listener() -> TestResult {
+    install_color_eyre();
+    listener::run()
+}
+#[test]
+fn signal_listener_drop_race() -> TestResult {
+    install_color_eyre();
+    drop_race::run()
+}
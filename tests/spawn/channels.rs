@@ -0,0 +1,66 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::spawn::{self, Spawner};
+use std::{
+    env,
+    io::prelude::*,
+    process::{Command, Stdio},
+};
+
+const CHILD_ENV: &str = "INTERPROCESS_TEST_SPAWN_CHILD";
+
+pub fn run() -> TestResult {
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .args(["--exact", "spawn_channels_child", "--ignored", "--test-threads=1"])
+        .env(CHILD_ENV, "1")
+        .stdout(Stdio::null());
+    let mut spawner = Spawner::new(command);
+    let mut to_child = spawner.pipe_to_child().context("pipe creation failed")?;
+    let mut from_child = spawner.pipe_from_child().context("pipe creation failed")?;
+    #[cfg(unix)]
+    let mut socket = spawner.socket_pair().context("socket pair creation failed")?;
+    let mut child = spawner.spawn().context("spawn failed")?;
+
+    to_child.write_all(b"ping").context("pipe write failed")?;
+    drop(to_child);
+    let mut buf = [0; 4];
+    from_child.read_exact(&mut buf).context("pipe read failed")?;
+    ensure_eq!(&buf, b"pong");
+    #[cfg(unix)]
+    {
+        socket.write_all(b"ping").context("socket write failed")?;
+        socket.read_exact(&mut buf).context("socket read failed")?;
+        ensure_eq!(&buf, b"pong");
+    }
+    ensure!(child.wait()?.success(), "child process failed");
+    // The child's end of the pipe was closed in the parent after spawning, so the child exiting leaves no writers.
+    ensure_eq!(from_child.read(&mut buf)?, 0);
+    Ok(())
+}
+
+pub fn run_child() -> TestResult {
+    if env::var_os(CHILD_ENV).is_none() {
+        // Not spawned by the parent half.
+        return Ok(());
+    }
+    // SAFETY: spawned by a spawner which set up those channels, each of which is only taken once
+    let mut from_parent = unsafe { spawn::inherited_pipe_reader(0) }.context("taking channel 0 failed")?;
+    let mut to_parent = unsafe { spawn::inherited_pipe_writer(1) }.context("taking channel 1 failed")?;
+    let e = unsafe { spawn::inherited_pipe_reader(3) }.unwrap_err();
+    ensure_eq!(e.kind(), std::io::ErrorKind::NotFound);
+
+    let mut buf = Vec::new();
+    from_parent.read_to_end(&mut buf).context("pipe read failed")?;
+    ensure_eq!(buf, b"ping");
+    to_parent.write_all(b"pong").context("pipe write failed")?;
+    #[cfg(unix)]
+    {
+        let mut socket = unsafe { spawn::inherited_socket(2) }.context("taking channel 2 failed")?;
+        let mut buf = [0; 4];
+        socket.read_exact(&mut buf).context("socket read failed")?;
+        ensure_eq!(&buf, b"ping");
+        socket.write_all(b"pong").context("socket write failed")?;
+    }
+    Ok(())
+}
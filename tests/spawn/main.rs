@@ -0,0 +1,19 @@
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+mod channels;
+
+#[test]
+fn spawn_channels() -> TestResult {
+    install_color_eyre();
+    channels::run()
+}
+/// The half of `spawn_channels` which runs in a child process.
+#[test]
+#[ignore]
+fn spawn_channels_child() -> TestResult {
+    install_color_eyre();
+    channels::run_child()
+}
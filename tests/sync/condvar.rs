@@ -0,0 +1,88 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::sync::{NamedCondvar, NamedMutex};
+use std::{
+    process,
+    sync::atomic::{AtomicU32, Ordering::SeqCst},
+    thread,
+    time::Duration,
+};
+
+pub fn run(id: &'static str) -> TestResult {
+    let rand = Xorshift32::from_id(id).next();
+    let mutex_name = format!("interprocess-test-{}-{rand:08x}-mutex", process::id());
+    let condvar_name = format!("interprocess-test-{}-{rand:08x}-condvar", process::id());
+    let mutex = NamedMutex::create_with_drop_guard(&mutex_name).context("mutex creation failed")?;
+    let condvar = NamedCondvar::create_with_drop_guard(&condvar_name).context("condvar creation failed")?;
+
+    let guard = mutex.lock()?.into_guard();
+    let (result, timed_out) = condvar
+        .wait_timeout(guard, Duration::from_millis(20))
+        .context("timed wait failed")?;
+    ensure!(timed_out, "timed wait was woken up without a notification");
+    drop(result.into_guard());
+
+    // The counter stands in for state in shared memory, and is only modified with the lock held.
+    let counter = AtomicU32::new(0);
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| -> TestResult {
+            let mutex = NamedMutex::open(&mutex_name).context("mutex opening failed")?;
+            let condvar = NamedCondvar::open(&condvar_name).context("condvar opening failed")?;
+            let mut guard = mutex.lock()?.into_guard();
+            counter.store(1, SeqCst);
+            condvar.notify_all()?;
+            while counter.load(SeqCst) != 2 {
+                guard = condvar.wait(guard).context("wait failed")?.into_guard();
+            }
+            Ok(())
+        });
+        let mut guard = mutex.lock()?.into_guard();
+        while counter.load(SeqCst) != 1 {
+            let (result, _) = condvar
+                .wait_timeout(guard, Duration::from_secs(10))
+                .context("timed wait failed")?;
+            guard = result.into_guard();
+        }
+        counter.store(2, SeqCst);
+        condvar.notify_one()?;
+        drop(guard);
+        waiter.join().unwrap()
+    })?;
+
+    // Robust mutexes track their owner per thread, so the notifier exiting with the lock held is enough for the waiter
+    // to see owner death when reacquiring it.
+    #[cfg(any(target_os = "linux", target_os = "freebsd", windows))]
+    {
+        use interprocess::sync::LockResult;
+        use std::{mem, sync::atomic::AtomicBool};
+        let died = AtomicBool::new(false);
+        let mut guard = mutex.lock()?.into_guard();
+        thread::scope(|scope| {
+            let notifier = scope.spawn(|| -> TestResult {
+                let guard = mutex.lock().context("locking from the notifier failed")?;
+                condvar.notify_all()?;
+                died.store(true, SeqCst);
+                mem::forget(guard);
+                Ok(())
+            });
+            loop {
+                match condvar.wait(guard).context("wait failed")? {
+                    LockResult::OwnerDied(guard) => {
+                        guard.mark_consistent().context("marking as consistent failed")?;
+                        break;
+                    }
+                    LockResult::Acquired(g) => {
+                        ensure!(!died.load(SeqCst), "owner death during a wait went unnoticed");
+                        guard = g;
+                    }
+                }
+            }
+            notifier.join().unwrap()
+        })?;
+        ensure!(
+            matches!(mutex.lock()?, LockResult::Acquired(..)),
+            "mutex is not usable after recovery"
+        );
+    }
+    Ok(())
+}
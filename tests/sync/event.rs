@@ -0,0 +1,40 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::{sync::Event, TryClone};
+use std::{thread, time::Duration};
+
+pub fn run() -> TestResult {
+    let event = Event::new().context("creation failed")?;
+    let other = event.try_clone().context("cloning failed")?;
+    ensure!(!event.is_set()?, "fresh event is set");
+    ensure!(
+        !event
+            .wait_timeout(Duration::from_millis(20))
+            .context("timed wait failed")?,
+        "timed wait succeeded on an unset event"
+    );
+
+    // Every waiter is released by a single set.
+    thread::scope(|scope| {
+        let waiters = [(); 2].map(|()| scope.spawn(|| event.wait()));
+        other.set().context("setting failed")?;
+        other.set().context("setting twice failed")?;
+        for waiter in waiters {
+            waiter.join().unwrap().context("wait failed")?;
+        }
+        TestResult::Ok(())
+    })?;
+    ensure!(event.is_set()?, "event was reset by waiting on it");
+    ensure!(
+        event
+            .wait_timeout(Duration::from_secs(10))
+            .context("timed wait failed")?,
+        "timed wait failed on a set event"
+    );
+
+    other.reset().context("resetting failed")?;
+    ensure!(!event.is_set()?, "event is still set after resetting");
+    other.reset().context("resetting twice failed")?;
+    ensure!(!event.is_set()?, "event is set after resetting twice");
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::sync::{FileLock, FileLockGuard};
+use std::{env, fs, io, process, thread, time::Duration};
+
+fn expect_would_block(result: io::Result<FileLockGuard<'_>>, what: &str) -> TestResult {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+        els => bail!("unexpected result of {what}: {els:?}"),
+    }
+}
+
+pub fn run(id: &'static str) -> TestResult {
+    let path = env::temp_dir().join(format!(
+        "interprocess-test-{}-{:08x}.lock",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    ));
+    let result = test_locks(&path);
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn test_locks(path: &std::path::Path) -> TestResult {
+    // Separately opened files exclude each other even within one process.
+    let a = FileLock::open(path).context("first open failed")?;
+    let b = FileLock::open(path).context("second open failed")?;
+
+    let shared_a = a.try_lock_shared().context("first shared lock failed")?;
+    ensure!(!shared_a.is_exclusive(), "shared guard reported an exclusive lock");
+    let shared_b = b.try_lock_shared().context("second shared lock failed")?;
+    drop(shared_b);
+    expect_would_block(b.try_lock_exclusive(), "exclusively locking a shared lock")?;
+    ensure!(
+        b.lock_exclusive_timeout(Duration::from_millis(20))
+            .context("timed exclusive lock failed")?
+            .is_none(),
+        "timed exclusive lock succeeded on a shared lock"
+    );
+    drop(shared_a);
+
+    let exclusive = a.lock_exclusive().context("exclusive lock failed")?;
+    ensure!(exclusive.is_exclusive(), "exclusive guard reported a shared lock");
+    expect_would_block(b.try_lock_shared(), "locking an exclusive lock in shared mode")?;
+    expect_would_block(b.try_lock_exclusive(), "exclusively locking an exclusive lock")?;
+
+    // Releasing the lock from another thread wakes up the waiter.
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| -> TestResult {
+            let guard = b
+                .lock_shared_timeout(Duration::from_secs(10))
+                .context("timed shared lock failed")?;
+            ensure!(
+                guard.is_some(),
+                "timed shared lock timed out despite the lock being released"
+            );
+            Ok(())
+        });
+        thread::sleep(Duration::from_millis(50));
+        drop(exclusive);
+        waiter.join().unwrap()
+    })?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+mod condvar;
+mod event;
+mod file_lock;
+mod mutex;
+mod named_event;
+mod once;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos",
+    windows
+))]
+mod park;
+mod rwlock;
+mod semaphore;
+
+#[test]
+fn sync_condvar() -> TestResult {
+    install_color_eyre();
+    condvar::run(make_id!())
+}
+#[test]
+fn sync_event() -> TestResult {
+    install_color_eyre();
+    event::run()
+}
+#[test]
+fn sync_file_lock() -> TestResult {
+    install_color_eyre();
+    file_lock::run(make_id!())
+}
+#[test]
+fn sync_mutex() -> TestResult {
+    install_color_eyre();
+    mutex::run(make_id!())
+}
+#[test]
+fn sync_named_event() -> TestResult {
+    install_color_eyre();
+    named_event::run(make_id!())
+}
+#[test]
+fn sync_once() -> TestResult {
+    install_color_eyre();
+    once::run(make_id!())
+}
+#[cfg(any(target_os = "linux", target_os = "freebsd", windows))]
+#[test]
+fn sync_once_crash() -> TestResult {
+    install_color_eyre();
+    once::run_crash(make_id!())
+}
+/// The half of `sync_once_crash` which runs in a child process.
+#[cfg(any(target_os = "linux", target_os = "freebsd", windows))]
+#[test]
+#[ignore]
+fn sync_once_crash_child() -> TestResult {
+    install_color_eyre();
+    once::run_crash_child()
+}
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos",
+    windows
+))]
+#[test]
+fn sync_park() -> TestResult {
+    install_color_eyre();
+    park::run()
+}
+#[test]
+fn sync_rwlock() -> TestResult {
+    install_color_eyre();
+    rwlock::run(make_id!())
+}
+#[test]
+fn sync_semaphore() -> TestResult {
+    install_color_eyre();
+    semaphore::run(make_id!())
+}
@@ -0,0 +1,66 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::sync::NamedMutex;
+use std::{io, process, thread};
+
+pub fn run(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let creator = NamedMutex::create_with_drop_guard(&name).context("creation failed")?;
+    match NamedMutex::create(&name) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        els => bail!("unexpected result of creating a duplicate mutex: {els:?}"),
+    }
+    let opener = NamedMutex::open(&name).context("opening failed")?;
+
+    let guard = creator.lock().context("locking failed")?;
+    ensure!(!guard.owner_died(), "owner died on a fresh mutex");
+    let guard = guard.into_guard();
+    guard
+        .mark_consistent()
+        .context("marking a normally acquired lock as consistent failed")?;
+    // Windows mutexes are reentrant, so contention has to come from another thread.
+    thread::scope(|scope| {
+        scope
+            .spawn(|| match opener.try_lock() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+                els => bail!("unexpected result of locking a held mutex: {els:?}"),
+            })
+            .join()
+            .unwrap()
+    })?;
+    drop(guard);
+    drop(opener.try_lock().context("locking a released mutex failed")?);
+
+    // Robust mutexes track their owner per thread, so a thread exiting with the lock held is just as good as a process.
+    #[cfg(any(target_os = "linux", target_os = "freebsd", windows))]
+    {
+        use interprocess::sync::LockResult;
+        use std::mem;
+        thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    mem::forget(opener.lock()?);
+                    Ok::<_, io::Error>(())
+                })
+                .join()
+                .unwrap()
+        })
+        .context("locking from another thread failed")?;
+        match creator.lock().context("locking an abandoned mutex failed")? {
+            LockResult::OwnerDied(guard) => {
+                guard.mark_consistent().context("marking as consistent failed")?;
+                guard.mark_consistent().context("marking as consistent twice failed")?;
+            }
+            LockResult::Acquired(..) => bail!("owner death went unnoticed"),
+        }
+        ensure!(
+            matches!(creator.lock()?, LockResult::Acquired(..)),
+            "mutex is not usable after recovery"
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::sync::NamedEvent;
+use std::{io, process, thread, time::Duration};
+
+pub fn run(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let creator = NamedEvent::create_with_drop_guard(&name).context("creation failed")?;
+    match NamedEvent::create(&name) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        els => bail!("unexpected result of creating a duplicate event: {els:?}"),
+    }
+    let opener = NamedEvent::open(&name).context("opening failed")?;
+    ensure!(!opener.is_set()?, "fresh event is set");
+    ensure!(
+        !opener
+            .wait_timeout(Duration::from_millis(20))
+            .context("timed wait failed")?,
+        "timed wait succeeded on an unset event"
+    );
+
+    // Setting the event through one handle releases every waiter on the other.
+    thread::scope(|scope| {
+        let waiters = [(); 2].map(|()| scope.spawn(|| opener.wait()));
+        thread::sleep(Duration::from_millis(10));
+        creator.set().context("setting failed")?;
+        creator.set().context("setting twice failed")?;
+        for waiter in waiters {
+            waiter.join().unwrap().context("wait failed")?;
+        }
+        TestResult::Ok(())
+    })?;
+    ensure!(
+        opener
+            .wait_timeout(Duration::from_secs(10))
+            .context("timed wait failed")?,
+        "timed wait failed on a set event"
+    );
+
+    opener.reset().context("resetting failed")?;
+    ensure!(!creator.is_set()?, "event is still set after resetting");
+    Ok(())
+}
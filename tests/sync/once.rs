@@ -0,0 +1,98 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::sync::SharedOnce;
+use std::{
+    env, io,
+    panic::{self, AssertUnwindSafe},
+    process::{self, Command, Stdio},
+    sync::atomic::{AtomicU32, Ordering::SeqCst},
+    thread,
+    time::Duration,
+};
+
+const CHILD_ENV: &str = "INTERPROCESS_TEST_ONCE_CHILD";
+
+pub fn run(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let creator = SharedOnce::create_with_drop_guard(&name).context("creation failed")?;
+    match SharedOnce::create(&name) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        els => bail!("unexpected result of creating a duplicate once: {els:?}"),
+    }
+    ensure!(!creator.is_completed(), "fresh once is completed");
+
+    // A panicking initializer poisons the once rather than completing it.
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| creator.call_once(|_| panic!("initializer failed"))));
+    ensure!(panicked.is_err(), "panic was not propagated");
+    ensure!(!creator.is_completed(), "once completed by a panicking initializer");
+
+    // Of all the concurrent callers, only one runs its closure, and it learns about the panic.
+    let runs = AtomicU32::new(0);
+    thread::scope(|scope| {
+        let callers = [(); 4].map(|()| {
+            scope.spawn(|| -> TestResult {
+                let once = SharedOnce::open_or_create(&name).context("opening failed")?;
+                once.call_once(|state| {
+                    assert!(state.is_poisoned(), "initializer was not told about the panic");
+                    thread::sleep(Duration::from_millis(20));
+                    runs.fetch_add(1, SeqCst);
+                })
+                .context("call failed")?;
+                ensure!(once.is_completed(), "once is not completed after the call returned");
+                Ok(())
+            })
+        });
+        for caller in callers {
+            caller.join().unwrap()?;
+        }
+        TestResult::Ok(())
+    })?;
+    ensure_eq!(runs.load(SeqCst), 1);
+    creator
+        .call_once(|_| {
+            runs.fetch_add(1, SeqCst);
+        })
+        .context("call failed")?;
+    ensure_eq!(runs.load(SeqCst), 1);
+    Ok(())
+}
+
+/// An initializer whose process dies poisons the once, where owner death can be detected.
+#[cfg(any(target_os = "linux", target_os = "freebsd", windows))]
+pub fn run_crash(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let once = SharedOnce::create_with_drop_guard(&name).context("creation failed")?;
+    let status = Command::new(env::current_exe()?)
+        .args(["--exact", "sync_once_crash_child", "--ignored", "--test-threads=1"])
+        .env(CHILD_ENV, &name)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("spawn failed")?;
+    ensure!(!status.success(), "child process didn't crash");
+    ensure!(!once.is_completed(), "once completed by a crashed initializer");
+
+    let mut poisoned = false;
+    once.call_once(|state| poisoned = state.is_poisoned())
+        .context("call after crash failed")?;
+    ensure!(poisoned, "initializer was not told about the crash");
+    ensure!(once.is_completed(), "once is not completed after the call returned");
+    Ok(())
+}
+#[cfg(any(target_os = "linux", target_os = "freebsd", windows))]
+pub fn run_crash_child() -> TestResult {
+    let Some(name) = env::var_os(CHILD_ENV) else {
+        return Ok(());
+    };
+    let once = SharedOnce::open(name).context("opening failed")?;
+    once.call_once(|_| process::abort()).context("call failed")?;
+    bail!("initializer returned despite aborting")
+}
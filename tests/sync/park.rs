@@ -0,0 +1,48 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::{
+    shared_memory::SharedMemory,
+    sync::{park, park_timeout, unpark_all, unpark_one},
+};
+use std::{
+    sync::atomic::{AtomicU32, Ordering::*},
+    thread,
+    time::Duration,
+};
+
+pub fn run() -> TestResult {
+    // Two mappings of the same object put the word at two different addresses, like in two different processes.
+    let shm = SharedMemory::anonymous(4096).context("creation failed")?;
+    #[cfg(unix)]
+    let other = SharedMemory::from_fd(std::os::unix::io::AsFd::as_fd(&shm).try_clone_to_owned()?)?;
+    #[cfg(windows)]
+    let other = SharedMemory::from_handle(std::os::windows::io::AsHandle::as_handle(&shm).try_clone_to_owned()?)?;
+    // SAFETY: the mappings are page-aligned and only accessed atomically
+    let (word, other_word) = unsafe { (&*shm.as_ptr().cast::<AtomicU32>(), &*other.as_ptr().cast::<AtomicU32>()) };
+
+    // A word which doesn't hold the expected value doesn't block.
+    park(word, 1).context("park on a mismatched word failed")?;
+    ensure!(
+        !park_timeout(word, 0, Duration::from_millis(20)).context("timed park failed")?,
+        "timed park returned without being unparked"
+    );
+    unpark_one(word).context("unparking with no waiters failed")?;
+
+    thread::scope(|scope| {
+        let waiters = [(); 3].map(|()| {
+            scope.spawn(|| -> TestResult {
+                while other_word.load(Acquire) == 0 {
+                    park(other_word, 0).context("park failed")?;
+                }
+                Ok(())
+            })
+        });
+        thread::sleep(Duration::from_millis(50));
+        word.store(1, Release);
+        unpark_all(word).context("unpark failed")?;
+        for waiter in waiters {
+            waiter.join().unwrap()?;
+        }
+        Ok(())
+    })
+}
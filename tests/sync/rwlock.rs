@@ -0,0 +1,55 @@
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::sync::NamedRwLock;
+use std::{io, process, thread};
+
+fn expect_would_block<T: std::fmt::Debug>(result: io::Result<T>, what: &str) -> TestResult {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+        els => bail!("unexpected result of {what}: {els:?}"),
+    }
+}
+
+pub fn run(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let creator = NamedRwLock::create_with_drop_guard(&name).context("creation failed")?;
+    let opener = NamedRwLock::open(&name).context("opening failed")?;
+
+    // Readers share the lock and keep writers out.
+    let first = creator.read().context("read locking failed")?;
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                drop(opener.try_read().context("concurrent read locking failed")?);
+                expect_would_block(opener.try_write(), "write locking a read-locked lock")
+            })
+            .join()
+            .unwrap()
+    })?;
+    drop(first);
+
+    // Writers keep everyone out.
+    let writer = creator.write().context("write locking failed")?;
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                expect_would_block(opener.try_read(), "read locking a write-locked lock")?;
+                expect_would_block(opener.try_write(), "write locking a write-locked lock")
+            })
+            .join()
+            .unwrap()
+    })?;
+
+    // A blocked reader gets in once the writer leaves.
+    thread::scope(|scope| {
+        let reader = scope.spawn(|| opener.read().map(drop));
+        drop(writer);
+        reader.join().unwrap().context("blocking read locking failed")
+    })?;
+    drop(opener.try_write().context("write locking a released lock failed")?);
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::sync::NamedSemaphore;
+use std::{io, process, thread, time::Duration};
+
+pub fn run(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let creator = NamedSemaphore::create_with_drop_guard(&name, 2).context("creation failed")?;
+    match NamedSemaphore::create(&name, 0) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        els => bail!("unexpected result of creating a duplicate semaphore: {els:?}"),
+    }
+    let opener = NamedSemaphore::open(&name).context("opening failed")?;
+
+    creator.acquire().context("first acquire failed")?;
+    opener.try_acquire().context("second acquire failed")?;
+    match opener.try_acquire() {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+        els => bail!("unexpected result of acquiring an exhausted semaphore: {els:?}"),
+    }
+    ensure!(
+        !creator
+            .acquire_timeout(Duration::from_millis(20))
+            .context("timed acquire failed")?,
+        "timed acquire succeeded on an exhausted semaphore"
+    );
+
+    // Permits released by another thread wake up the waiters.
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| -> TestResult {
+            creator.acquire().context("blocking acquire failed")?;
+            ensure!(
+                creator
+                    .acquire_timeout(Duration::from_secs(10))
+                    .context("timed acquire failed")?,
+                "timed acquire timed out despite a released permit"
+            );
+            Ok(())
+        });
+        opener.release(2).context("release failed")?;
+        waiter.join().unwrap()
+    })?;
+    match creator.try_acquire() {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+        els => bail!("unexpected result of acquiring an exhausted semaphore: {els:?}"),
+    }
+}
@@ -0,0 +1,63 @@
+use super::util::{listen_and_pick_name, NameGen, TestResult};
+use color_eyre::eyre::{eyre, Context};
+use futures::{SinkExt, StreamExt};
+use interprocess::{
+    framing::{LengthPrefix, MessageCodec, MessageReader, MessageWriter},
+    local_socket::tokio::{LocalSocketListener, LocalSocketStream},
+};
+use std::io;
+
+const MSGS: &[&[u8]] = &[b"Hello", b"", b"from the codec!"];
+
+/// Exchanges messages between a stream framed with the codec and one wrapped in the message reader and writer, which
+/// have to agree on the format.
+pub async fn run(prefix: LengthPrefix) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let codec = MessageCodec::new().with_prefix(prefix);
+
+    let server = async {
+        let conn = listener.accept().await.context("accept failed")?;
+        let mut framed = codec.framed(conn);
+        for msg in MSGS {
+            let received = framed
+                .next()
+                .await
+                .ok_or_else(|| eyre!("unexpected end of file"))?
+                .context("server receive failed")?;
+            ensure_eq!(&received[..], *msg);
+            framed.send(*msg).await.context("server send failed")?;
+        }
+        TestResult::Ok(())
+    };
+    let client = async {
+        let conn = LocalSocketStream::connect(&*name).await.context("connect failed")?;
+        let (reader, writer) = conn.split();
+        let (mut reader, mut writer) = (
+            MessageReader::new(reader).with_prefix(prefix),
+            MessageWriter::new(writer).with_prefix(prefix),
+        );
+        for msg in MSGS {
+            writer.write_message_async(msg).await.context("client send failed")?;
+            let received = reader
+                .read_message_async()
+                .await
+                .context("client receive failed")?
+                .ok_or_else(|| eyre!("unexpected end of file"))?;
+            ensure_eq!(received, *msg);
+        }
+        TestResult::Ok(())
+    };
+    tokio::try_join!(server, client)?;
+
+    // Oversized messages are refused on both ends.
+    let mut small = MessageCodec::new().with_max_size(4);
+    let mut buf = bytes::BytesMut::new();
+    let e = tokio_util::codec::Encoder::encode(&mut small, b"12345", &mut buf).unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    buf.extend_from_slice(&[5, 1, 2]);
+    let e = tokio_util::codec::Decoder::decode(&mut small, &mut buf).unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::InvalidData);
+    Ok(())
+}
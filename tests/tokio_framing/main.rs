@@ -0,0 +1,99 @@
+#![cfg(feature = "tokio")]
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, TestResult};
+
+#[cfg(feature = "tokio-util")]
+mod codec;
+mod pipe;
+mod sink;
+mod stream;
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+mod typed;
+
+#[tokio::test]
+async fn tokio_framing_pipe() -> TestResult {
+    install_color_eyre();
+    pipe::run().await
+}
+#[tokio::test]
+async fn tokio_framing_sink() -> TestResult {
+    install_color_eyre();
+    sink::run().await
+}
+#[tokio::test]
+async fn tokio_framing_sink_forward() -> TestResult {
+    install_color_eyre();
+    sink::run_forward().await
+}
+#[tokio::test]
+async fn tokio_framing_stream() -> TestResult {
+    install_color_eyre();
+    stream::run().await
+}
+#[tokio::test]
+async fn tokio_framing_stream_truncated() -> TestResult {
+    install_color_eyre();
+    stream::run_truncated().await
+}
+#[cfg(feature = "tokio-util")]
+#[tokio::test]
+async fn tokio_framing_codec_varint() -> TestResult {
+    install_color_eyre();
+    codec::run(interprocess::framing::LengthPrefix::Varint).await
+}
+#[cfg(feature = "tokio-util")]
+#[tokio::test]
+async fn tokio_framing_codec_u32() -> TestResult {
+    install_color_eyre();
+    codec::run(interprocess::framing::LengthPrefix::U32).await
+}
+#[cfg(feature = "bincode")]
+#[tokio::test]
+async fn tokio_framing_typed_bincode() -> TestResult {
+    install_color_eyre();
+    typed::run(interprocess::framing::Bincode).await
+}
+#[cfg(feature = "postcard")]
+#[tokio::test]
+async fn tokio_framing_typed_postcard() -> TestResult {
+    install_color_eyre();
+    typed::run(interprocess::framing::Postcard).await
+}
+#[cfg(all(feature = "tokio-util", feature = "bincode"))]
+#[tokio::test]
+async fn tokio_framing_typed_codec_bincode() -> TestResult {
+    install_color_eyre();
+    typed::run_codec(interprocess::framing::Bincode).await
+}
+#[cfg(all(feature = "tokio-util", feature = "postcard"))]
+#[tokio::test]
+async fn tokio_framing_typed_codec_postcard() -> TestResult {
+    install_color_eyre();
+    typed::run_codec(interprocess::framing::Postcard).await
+}
+#[cfg(feature = "bincode")]
+#[tokio::test]
+async fn tokio_framing_typed_stream_bincode() -> TestResult {
+    install_color_eyre();
+    typed::run_stream(interprocess::framing::Bincode).await
+}
+#[cfg(feature = "postcard")]
+#[tokio::test]
+async fn tokio_framing_typed_stream_postcard() -> TestResult {
+    install_color_eyre();
+    typed::run_stream(interprocess::framing::Postcard).await
+}
+#[cfg(feature = "bincode")]
+#[tokio::test]
+async fn tokio_framing_typed_sink_bincode() -> TestResult {
+    install_color_eyre();
+    typed::run_sink(interprocess::framing::Bincode).await
+}
+#[cfg(feature = "postcard")]
+#[tokio::test]
+async fn tokio_framing_typed_sink_postcard() -> TestResult {
+    install_color_eyre();
+    typed::run_sink(interprocess::framing::Postcard).await
+}
@@ -0,0 +1,40 @@
+use super::util::TestResult;
+use color_eyre::eyre::{eyre, Context};
+use interprocess::{
+    framing::{LengthPrefix, MessageReader, MessageWriter},
+    unnamed_pipe::tokio::pipe,
+};
+use tokio::try_join;
+
+const MSGS: &[&[u8]] = &[b"Hello", b"", b"from the other end of the pipe!"];
+
+pub async fn run() -> TestResult {
+    for prefix in [LengthPrefix::Varint, LengthPrefix::U32] {
+        let (tx, rx) = pipe().context("pipe creation failed")?;
+        let mut writer = MessageWriter::new(tx).with_prefix(prefix);
+        let mut reader = MessageReader::new(rx).with_prefix(prefix);
+
+        let write = async {
+            for msg in MSGS {
+                writer.write_message_async(msg).await.context("write failed")?;
+            }
+            // Dropping the writer closes the pipe, which the reader sees as EOF.
+            drop(writer);
+            TestResult::Ok(())
+        };
+        let read = async {
+            for msg in MSGS {
+                let received = reader
+                    .read_message_async()
+                    .await
+                    .context("read failed")?
+                    .ok_or_else(|| eyre!("unexpected end of file"))?;
+                ensure_eq!(received, *msg);
+            }
+            ensure_eq!(reader.read_message_async().await?, None);
+            TestResult::Ok(())
+        };
+        try_join!(write, read)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Tests sending messages through a sink, and forwarding them from one framed transport to another.
+
+use super::util::TestResult;
+use color_eyre::eyre::Context;
+use futures::{stream, SinkExt, StreamExt, TryStreamExt};
+use interprocess::{
+    framing::{LengthPrefix, MessageReader, MessageWriter},
+    unnamed_pipe::tokio::pipe,
+};
+use std::io;
+
+fn messages() -> Vec<Vec<u8>> {
+    (0..1000_u32).map(|i| i.to_string().into_bytes()).collect()
+}
+
+pub async fn run() -> TestResult {
+    let (tx, rx) = pipe().context("pipe creation failed")?;
+    let mut writer = MessageWriter::new(tx);
+    let reader = MessageReader::new(rx);
+
+    let write = async {
+        let mut msgs = stream::iter(messages()).map(io::Result::Ok);
+        writer.send_all(&mut msgs).await.context("send failed")?;
+        // Too long to send, which doesn't disturb what was sent before.
+        let rejected = writer.send(vec![0; interprocess::framing::DEFAULT_MAX_SIZE + 1]).await;
+        ensure_eq!(rejected.map_err(|e| e.kind()), Err(io::ErrorKind::InvalidInput));
+        SinkExt::<Vec<u8>>::close(&mut writer).await.context("close failed")?;
+        drop(writer);
+        TestResult::Ok(())
+    };
+    let read = async {
+        let received = reader.try_collect::<Vec<_>>().await.context("receive failed")?;
+        ensure_eq!(received, messages());
+        TestResult::Ok(())
+    };
+    ::tokio::try_join!(write, read)?;
+    Ok(())
+}
+
+/// Forwards messages from one pipe to another, changing the length prefix on the way.
+pub async fn run_forward() -> TestResult {
+    let (tx1, rx1) = pipe().context("pipe creation failed")?;
+    let (tx2, rx2) = pipe().context("pipe creation failed")?;
+    let mut source = MessageWriter::new(tx1);
+    let relay_in = MessageReader::new(rx1);
+    let relay_out = MessageWriter::new(tx2).with_prefix(LengthPrefix::U32);
+    let sink = MessageReader::new(rx2).with_prefix(LengthPrefix::U32);
+
+    let send = async {
+        for msg in messages() {
+            source.feed(msg).await.context("send failed")?;
+        }
+        SinkExt::<Vec<u8>>::close(&mut source).await.context("close failed")?;
+        drop(source);
+        TestResult::Ok(())
+    };
+    let relay = async {
+        relay_in.forward(relay_out).await.context("forwarding failed")?;
+        TestResult::Ok(())
+    };
+    let receive = async {
+        let received = sink.try_collect::<Vec<_>>().await.context("receive failed")?;
+        ensure_eq!(received, messages());
+        TestResult::Ok(())
+    };
+    ::tokio::try_join!(send, relay, receive)?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Tests reading messages as a stream, including picking a message back up after the read that started it was
+//! cancelled.
+
+use super::util::TestResult;
+use color_eyre::eyre::Context;
+use futures::{io::AsyncWriteExt, StreamExt};
+use interprocess::{
+    framing::{LengthPrefix, MessageReader},
+    unnamed_pipe::tokio::pipe,
+};
+use std::time::Duration;
+
+pub async fn run() -> TestResult {
+    for prefix in [LengthPrefix::Varint, LengthPrefix::U32] {
+        let (mut tx, rx) = pipe().context("pipe creation failed")?;
+        let mut reader = MessageReader::new(rx).with_prefix(prefix);
+        let (first, second): (&[u8], &[u8]) = match prefix {
+            LengthPrefix::Varint => (b"\x05He", b"llo\x02!!"),
+            _ => (b"\x05\x00\x00\x00He", b"llo\x02\x00\x00\x00!!"),
+        };
+
+        // Only part of the message is there, so the read times out and is dropped halfway through it.
+        tx.write_all(first).await.context("write failed")?;
+        let cancelled = ::tokio::time::timeout(Duration::from_millis(50), reader.next()).await;
+        ensure_eq!(cancelled.is_err(), true);
+
+        tx.write_all(second).await.context("write failed")?;
+        drop(tx);
+        let msgs = reader.map(|msg| msg.map_err(|e| e.kind())).collect::<Vec<_>>().await;
+        ensure_eq!(msgs, vec![Ok(b"Hello".to_vec()), Ok(b"!!".to_vec())]);
+    }
+    Ok(())
+}
+
+/// End of file in the middle of a message is an error, rather than the end of the stream.
+pub async fn run_truncated() -> TestResult {
+    let (mut tx, rx) = pipe().context("pipe creation failed")?;
+    let mut reader = MessageReader::new(rx);
+    tx.write_all(b"\x05Hel").await.context("write failed")?;
+    drop(tx);
+    let msg = reader.next().await.map(|msg| msg.map_err(|e| e.kind()));
+    ensure_eq!(msg, Some(Err(std::io::ErrorKind::UnexpectedEof)));
+    Ok(())
+}
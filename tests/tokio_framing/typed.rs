@@ -0,0 +1,163 @@
+use super::util::{listen_and_pick_name, NameGen, TestResult};
+use color_eyre::eyre::Context;
+use interprocess::{
+    framing::{Format, TypedStream},
+    local_socket::tokio::{LocalSocketListener, LocalSocketStream},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Point {
+    name: String,
+    coords: (f64, f64),
+}
+
+fn points() -> Vec<Point> {
+    (0..10)
+        .map(|i| Point {
+            name: format!("point #{i}"),
+            coords: (f64::from(i) * 0.5, -f64::from(i)),
+        })
+        .collect()
+}
+
+/// Exchanges values between the two halves of a connection, each wrapped in a typed stream of its own.
+pub async fn run<F: Format + Copy>(format: F) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let points = points();
+
+    let server = async {
+        let conn = listener.accept().await.context("accept failed")?;
+        let mut conn = TypedStream::<Point, _, _>::new(conn, format);
+        for point in &points {
+            let received = conn.recv_async().await.context("server receive failed")?;
+            ensure_eq!(&received, point);
+            conn.send_async(&received).await.context("server send failed")?;
+        }
+        TestResult::Ok(())
+    };
+    let client = async {
+        let conn = LocalSocketStream::connect(&*name).await.context("connect failed")?;
+        let (reader, writer) = conn.split();
+        let mut reader = TypedStream::<Point, _, _>::new(reader, format);
+        let mut writer = TypedStream::<Point, _, _>::new(writer, format);
+        for point in &points {
+            writer.send_async(point).await.context("client send failed")?;
+            ensure_eq!(&reader.recv_async().await.context("client receive failed")?, point);
+        }
+        TestResult::Ok(())
+    };
+    ::tokio::try_join!(server, client)?;
+    Ok(())
+}
+
+/// Exchanges values between a stream framed with the typed codec and a typed stream, which have to agree on the format.
+#[cfg(feature = "tokio-util")]
+pub async fn run_codec<F: Format + Copy>(format: F) -> TestResult {
+    use color_eyre::eyre::eyre;
+    use futures::{SinkExt, StreamExt};
+    use interprocess::framing::TypedCodec;
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let points = points();
+
+    let server = async {
+        let conn = listener.accept().await.context("accept failed")?;
+        let mut framed = TypedCodec::<Point, _>::new(format).framed(conn);
+        for point in &points {
+            let received = framed
+                .next()
+                .await
+                .ok_or_else(|| eyre!("unexpected end of file"))?
+                .context("server receive failed")?;
+            ensure_eq!(&received, point);
+            // Both owned values and references can be sent.
+            framed.send(&received).await.context("server send failed")?;
+            framed.send(received).await.context("server send failed")?;
+        }
+        TestResult::Ok(())
+    };
+    let client = async {
+        let conn = LocalSocketStream::connect(&*name).await.context("connect failed")?;
+        let mut conn = TypedStream::<Point, _, _>::new(conn, format);
+        for point in &points {
+            conn.send_async(point).await.context("client send failed")?;
+            for _ in 0..2 {
+                ensure_eq!(&conn.recv_async().await.context("client receive failed")?, point);
+            }
+        }
+        TestResult::Ok(())
+    };
+    ::tokio::try_join!(server, client)?;
+    Ok(())
+}
+
+/// Receives values as a stream, which ends when the other end closes the connection between values.
+pub async fn run_stream<F: Format + Copy + Unpin>(format: F) -> TestResult {
+    use futures::TryStreamExt;
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let points = points();
+
+    let server = async {
+        let conn = listener.accept().await.context("accept failed")?;
+        let received = TypedStream::<Point, _, _>::new(conn, format)
+            .try_collect::<Vec<_>>()
+            .await
+            .context("server receive failed")?;
+        ensure_eq!(received, points);
+        TestResult::Ok(())
+    };
+    let client = async {
+        let conn = LocalSocketStream::connect(&*name).await.context("connect failed")?;
+        let mut conn = TypedStream::<Point, _, _>::new(conn, format);
+        for point in &points {
+            conn.send_async(point).await.context("client send failed")?;
+        }
+        TestResult::Ok(())
+    };
+    ::tokio::try_join!(server, client)?;
+    Ok(())
+}
+
+/// Sends values through a sink, both by reference and owned, and receives them as a stream.
+pub async fn run_sink<F: Format + Copy + Unpin>(format: F) -> TestResult {
+    use futures::{SinkExt, TryStreamExt};
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let points = points();
+
+    let server = async {
+        let conn = listener.accept().await.context("accept failed")?;
+        let received = TypedStream::<Point, _, _>::new(conn, format)
+            .try_collect::<Vec<_>>()
+            .await
+            .context("server receive failed")?;
+        ensure_eq!(received, [&points[..], &points[..]].concat());
+        TestResult::Ok(())
+    };
+    let client = async {
+        let conn = LocalSocketStream::connect(&*name).await.context("connect failed")?;
+        let mut conn = TypedStream::<Point, _, _>::new(conn, format);
+        for point in &points {
+            conn.feed(point).await.context("client send failed")?;
+        }
+        for point in points.clone() {
+            conn.feed(point).await.context("client send failed")?;
+        }
+        SinkExt::<&Point>::close(&mut conn)
+            .await
+            .context("client close failed")?;
+        TestResult::Ok(())
+    };
+    ::tokio::try_join!(server, client)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! Tests the buffered stream wrapper: line-by-line reading, holding writes back until a flush, and passing large
+//! writes through.
+
+use super::util::*;
+use ::tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt},
+    time,
+};
+use color_eyre::eyre::Context;
+use interprocess::{
+    buffered::BufferedStream,
+    local_socket::tokio::{LocalSocketListener, LocalSocketStream},
+};
+use std::time::Duration;
+
+pub async fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (server, client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    let (mut server, mut client) = (
+        BufferedStream::new(server),
+        BufferedStream::with_capacity(16, 16, client),
+    );
+
+    for line in ["first\n", "second\n", "third\n"] {
+        client.write_all(line.as_bytes()).await.context("write failed")?;
+    }
+    // The third line didn't fit, which pushed the first two out.
+    ensure_eq!(client.write_buffer(), b"third\n");
+    let mut line = String::new();
+    for expected in ["first\n", "second\n"] {
+        line.clear();
+        server.read_line(&mut line).await.context("read failed")?;
+        ensure_eq!(line, expected);
+    }
+    // The rest isn't sent until the flush.
+    line.clear();
+    let pending = time::timeout(Duration::from_millis(50), server.read_line(&mut line)).await;
+    ensure_eq!(pending.is_err(), true);
+    client.flush().await.context("flush failed")?;
+    ensure_eq!(client.write_buffer(), b"");
+    server.read_line(&mut line).await.context("read failed")?;
+    ensure_eq!(line, "third\n");
+
+    // Writes and reads larger than the buffers go straight through.
+    let bulk = vec![0xAB; 1000];
+    client.write_all(&bulk).await.context("bulk write failed")?;
+    ensure_eq!(client.write_buffer(), b"");
+    server.write_all(b"ack").await?;
+    server.flush().await?;
+    let mut received = vec![0; bulk.len()];
+    server.read_exact(&mut received).await.context("bulk read failed")?;
+    ensure_eq!(received, bulk);
+    let mut ack = [0; 3];
+    client.read_exact(&mut ack).await?;
+    ensure_eq!(&ack, b"ack");
+    Ok(())
+}
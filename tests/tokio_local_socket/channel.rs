@@ -0,0 +1,129 @@
+//! Tests the bounded channel adapter: backpressure once the receiver's capacity is used up, delivery in both
+//! directions, and the end of the channel once the sender is dropped.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    channel,
+    local_socket::tokio::{LocalSocketListener, LocalSocketStream},
+};
+use std::{io, time::Duration};
+
+pub async fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (server, client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    let (server_tx, mut server_rx) = channel::bounded(server, 4);
+    let (client_tx, mut client_rx) = channel::bounded(client, 64);
+
+    // The server can only take four messages before it has received any.
+    for i in 0..4_u8 {
+        client_tx.send(&[i]).await.context("send within capacity failed")?;
+    }
+    ensure_eq!(
+        client_tx.try_send(&[4]).map_err(|e| e.kind()),
+        Err(io::ErrorKind::WouldBlock)
+    );
+    let blocked = ::tokio::time::timeout(Duration::from_millis(50), client_tx.send(&[4])).await;
+    ensure_eq!(blocked.is_err(), true);
+
+    // Receiving half of them gives the credit back.
+    for i in 0..2_u8 {
+        ensure_eq!(server_rx.recv().await?, Some(vec![i]));
+    }
+    ::tokio::time::timeout(Duration::from_secs(5), client_tx.send(&[4]))
+        .await
+        .context("send after acknowledgement timed out")?
+        .context("send after acknowledgement failed")?;
+    for i in 2..5_u8 {
+        ensure_eq!(server_rx.recv().await?, Some(vec![i]));
+    }
+
+    // The other direction is independent, with a capacity of its own.
+    for i in 0..64_u8 {
+        server_tx.try_send(&[i]).context("send within capacity failed")?;
+    }
+    for i in 0..64_u8 {
+        ensure_eq!(client_rx.recv().await?, Some(vec![i]));
+    }
+
+    // Messages to a dropped receiver are discarded without stalling the sender.
+    drop(client_rx);
+    for i in 0..100_u8 {
+        ::tokio::time::timeout(Duration::from_secs(5), server_tx.send(&[i]))
+            .await
+            .context("send to a dropped receiver timed out")?
+            .context("send to a dropped receiver failed")?;
+    }
+
+    drop(client_tx);
+    ensure_eq!(server_rx.recv().await?, None);
+    Ok(())
+}
+
+/// Consumes the receiver as a stream, alongside a timer in `select!`, which polls it without waiting on it alone.
+pub async fn run_stream() -> TestResult {
+    use futures::StreamExt;
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (server, client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    let (_server_tx, mut server_rx) = channel::bounded(server, 4);
+    let (client_tx, _client_rx) = channel::bounded(client, 4);
+
+    let sending = ::tokio::spawn(async move {
+        for i in 0..20_u8 {
+            client_tx.send(&[i]).await?;
+        }
+        io::Result::Ok(())
+    });
+    let mut received = Vec::new();
+    let mut ticks = ::tokio::time::interval(Duration::from_millis(1));
+    loop {
+        ::tokio::select! {
+            msg = server_rx.next() => match msg {
+                Some(msg) => received.extend(msg.context("receive failed")?),
+                None => break,
+            },
+            _ = ticks.tick() => {}
+        }
+    }
+    sending.await?.context("send failed")?;
+    ensure_eq!(received, (0..20).collect::<Vec<u8>>());
+    Ok(())
+}
+
+/// Feeds the sender from a stream with `send_all()`, which has to wait for credit like `send()` does.
+pub async fn run_sink() -> TestResult {
+    use futures::{stream, SinkExt, StreamExt};
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (server, client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    let (_server_tx, server_rx) = channel::bounded(server, 4);
+    let (mut client_tx, _client_rx) = channel::bounded(client, 4);
+
+    let sending = ::tokio::spawn(async move {
+        let mut msgs = stream::iter(0..100_u8).map(|i| io::Result::Ok(vec![i]));
+        client_tx.send_all(&mut msgs).await?;
+        // Dropping the sender ends the stream on the other end.
+        drop(client_tx);
+        io::Result::Ok(())
+    });
+    let received = server_rx
+        .map(|msg| msg.map(|msg| msg[0]))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<io::Result<Vec<_>>>()
+        .context("receive failed")?;
+    sending.await?.context("send failed")?;
+    ensure_eq!(received, (0..100).collect::<Vec<u8>>());
+    Ok(())
+}
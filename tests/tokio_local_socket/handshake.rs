@@ -0,0 +1,28 @@
+//! Tests asynchronous protocol negotiation.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    handshake::Handshake,
+    local_socket::tokio::{LocalSocketListener, LocalSocketStream},
+};
+
+const MAGIC: [u8; 4] = *b"TEST";
+
+pub async fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (mut server, mut client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    let server_handshake = Handshake::new(MAGIC, 5).min_version(2).features(0b11);
+    let client_handshake = Handshake::new(MAGIC, 4).min_version(4).features(0b110);
+    let (server, client) = ::tokio::try_join!(
+        server_handshake.perform_async(&mut server),
+        client_handshake.perform_async(&mut client),
+    )
+    .context("handshake failed")?;
+    ensure_eq!(server, client);
+    ensure_eq!((server.version(), server.features()), (4, 0b10));
+    Ok(())
+}
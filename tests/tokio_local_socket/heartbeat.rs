@@ -0,0 +1,74 @@
+//! Tests the heartbeat wrapper: transparent data transfer, staying alive through idle periods, shutdown, and
+//! detection of a peer which has stopped responding.
+
+use super::util::*;
+use ::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use color_eyre::eyre::Context;
+use interprocess::{
+    heartbeat::{HeartbeatOptions, PeerUnresponsive},
+    local_socket::tokio::{LocalSocketListener, LocalSocketStream},
+};
+use std::{io, time::Duration};
+
+fn options() -> HeartbeatOptions {
+    HeartbeatOptions::new()
+        .interval(Duration::from_millis(20))
+        .timeout(Duration::from_millis(200))
+}
+
+async fn connect_pair() -> TestResult<(LocalSocketStream, LocalSocketStream)> {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let pair =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    Ok(pair)
+}
+
+pub async fn run() -> TestResult {
+    let (server, client) = connect_pair().await?;
+    let (mut server, mut client) = (options().wrap(server)?, options().wrap(client)?);
+
+    let big = vec![0x5A; 200_000];
+    let writer = async {
+        client.write_all(&big).await?;
+        client.flush().await
+    };
+    let mut received = vec![0; big.len()];
+    let (written, read) = ::tokio::join!(writer, server.read_exact(&mut received));
+    written.context("write failed")?;
+    read.context("read failed")?;
+    ensure_eq!(received == big, true);
+
+    // Idling for several times the timeout is fine, since the heartbeats keep going in the background.
+    ::tokio::time::sleep(Duration::from_millis(600)).await;
+    ensure_eq!(client.rtt().is_some(), true);
+    server
+        .write_all(b"still here")
+        .await
+        .context("write after idling failed")?;
+    server.flush().await?;
+    let mut buf = [0; 10];
+    client.read_exact(&mut buf).await.context("read after idling failed")?;
+    ensure_eq!(&buf, b"still here");
+
+    server.shutdown().await.context("shutdown failed")?;
+    let mut rest = Vec::new();
+    client.read_to_end(&mut rest).await.context("read to end failed")?;
+    ensure_eq!(rest.len(), 0);
+    Ok(())
+}
+
+pub async fn run_unresponsive() -> TestResult {
+    let (wedged, client) = connect_pair().await?;
+    let mut client = options().wrap(client)?;
+    let mut buf = [0; 1];
+    let e = ::tokio::time::timeout(Duration::from_secs(5), client.read(&mut buf))
+        .await
+        .context("the unresponsive peer went unnoticed")?
+        .unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::TimedOut);
+    ensure_eq!(PeerUnresponsive::is(&e), true);
+    drop(wedged);
+    Ok(())
+}
@@ -4,7 +4,18 @@
 mod util;
 use util::{install_color_eyre, TestResult};
 
+mod buffered;
+mod channel;
+mod handshake;
+mod heartbeat;
+mod mux;
 mod no_server;
+mod pubsub;
+#[cfg(unix)]
+mod read_until;
+#[cfg(feature = "bincode")]
+mod rpc;
+mod shutdown;
 mod stream;
 
 use {interprocess::local_socket::NameTypeSupport, tokio::try_join};
@@ -38,3 +49,88 @@ async fn tokio_local_socket_no_server() -> TestResult {
     }
     Ok(())
 }
+#[tokio::test]
+async fn tokio_local_socket_buffered() -> TestResult {
+    install_color_eyre();
+    buffered::run().await
+}
+#[tokio::test]
+async fn tokio_local_socket_channel() -> TestResult {
+    install_color_eyre();
+    channel::run().await
+}
+#[tokio::test]
+async fn tokio_local_socket_channel_stream() -> TestResult {
+    install_color_eyre();
+    channel::run_stream().await
+}
+#[tokio::test]
+async fn tokio_local_socket_channel_sink() -> TestResult {
+    install_color_eyre();
+    channel::run_sink().await
+}
+#[tokio::test]
+async fn tokio_local_socket_handshake() -> TestResult {
+    install_color_eyre();
+    handshake::run().await
+}
+#[tokio::test]
+async fn tokio_local_socket_heartbeat() -> TestResult {
+    install_color_eyre();
+    heartbeat::run().await
+}
+#[tokio::test]
+async fn tokio_local_socket_heartbeat_unresponsive() -> TestResult {
+    install_color_eyre();
+    heartbeat::run_unresponsive().await
+}
+#[tokio::test]
+async fn tokio_local_socket_mux() -> TestResult {
+    install_color_eyre();
+    mux::run(false).await?;
+    if NameTypeSupport::query() == NameTypeSupport::Both {
+        mux::run(true).await?;
+    }
+    Ok(())
+}
+#[tokio::test]
+async fn tokio_local_socket_mux_backlog() -> TestResult {
+    install_color_eyre();
+    mux::run_backlog().await
+}
+#[tokio::test]
+async fn tokio_local_socket_pubsub() -> TestResult {
+    install_color_eyre();
+    pubsub::run(false).await?;
+    if NameTypeSupport::query() == NameTypeSupport::Both {
+        pubsub::run(true).await?;
+    }
+    Ok(())
+}
+#[tokio::test]
+async fn tokio_local_socket_pubsub_slow_consumer() -> TestResult {
+    install_color_eyre();
+    pubsub::run_slow_consumer(false).await
+}
+#[cfg(unix)]
+#[tokio::test]
+async fn tokio_local_socket_read_until() -> TestResult {
+    install_color_eyre();
+    read_until::run().await
+}
+#[cfg(feature = "bincode")]
+#[tokio::test]
+async fn tokio_local_socket_rpc() -> TestResult {
+    install_color_eyre();
+    rpc::run().await
+}
+#[tokio::test]
+async fn tokio_local_socket_shutdown() -> TestResult {
+    install_color_eyre();
+    shutdown::run().await
+}
+#[tokio::test]
+async fn tokio_local_socket_shutdown_timeout() -> TestResult {
+    install_color_eyre();
+    shutdown::run_timeout().await
+}
@@ -0,0 +1,95 @@
+//! Tests channel multiplexing: opening and accepting from both ends, flow control keeping a stalled channel from
+//! holding up the others, shutdown, closing, and refusal once the backlog is full.
+
+use super::util::*;
+use ::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use color_eyre::eyre::Context;
+use interprocess::local_socket::tokio::{
+    mux::{Mux, MuxOptions, Side},
+    LocalSocketListener, LocalSocketStream,
+};
+use std::{io, time::Duration};
+
+const BULK_LEN: usize = 100_000;
+
+async fn connect_pair(
+    name: &str,
+    listener: &LocalSocketListener,
+    server_options: MuxOptions,
+) -> TestResult<(Mux, Mux)> {
+    let (server, client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(name)).context("connection failed")?;
+    Ok((
+        server_options.mux(server, Side::Server)?,
+        Mux::new(client, Side::Client),
+    ))
+}
+
+pub async fn run(prefer_namespaced: bool) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), prefer_namespaced), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    // A small window, so that the bulk transfer stalls well before it's done.
+    let (server, client) = connect_pair(&name, &listener, MuxOptions::new().window(4096)).await?;
+
+    let (mut bulk, mut chat) = (client.open().await?, client.open().await?);
+    ensure_eq!((bulk.id(), chat.id()), (0, 2));
+    let (mut bulk_srv, mut chat_srv) = (server.accept().await?, server.accept().await?);
+    ensure_eq!((bulk_srv.id(), chat_srv.id()), (0, 2));
+
+    // The bulk channel isn't read from until the chat channel is done with, which would deadlock if the stalled bulk
+    // transfer held up the connection.
+    let writer = ::tokio::spawn(async move {
+        bulk.write_all(&vec![0xAB; BULK_LEN]).await?;
+        bulk.shutdown().await?;
+        io::Result::Ok(bulk)
+    });
+    ::tokio::time::sleep(Duration::from_millis(50)).await;
+    ensure_eq!(writer.is_finished(), false);
+    for i in 0..10_u8 {
+        chat.write_all(&[i]).await.context("chat write failed")?;
+        ensure_eq!(chat_srv.read_u8().await.context("chat read failed")?, i);
+        chat_srv.write_all(&[i + 1]).await.context("chat reply failed")?;
+        ensure_eq!(chat.read_u8().await.context("chat reply read failed")?, i + 1);
+    }
+    let mut received = Vec::new();
+    bulk_srv.read_to_end(&mut received).await.context("bulk read failed")?;
+    ensure_eq!(received.len(), BULK_LEN);
+    ensure_eq!(received.iter().all(|&b| b == 0xAB), true);
+    let _bulk = writer.await.unwrap().context("bulk write failed")?;
+
+    // Either end can open channels, with IDs that don't collide.
+    let (mut from_server, mut accepted) = ::tokio::try_join!(server.open(), client.accept())?;
+    ensure_eq!((from_server.id(), accepted.id()), (1, 1));
+    from_server.write_all(b"hello").await?;
+    let mut buf = [0; 5];
+    accepted.read_exact(&mut buf).await?;
+    ensure_eq!(&buf, b"hello");
+
+    // Dropping a channel closes it for the other end.
+    drop(chat_srv);
+    ensure_eq!(chat.read(&mut buf).await.context("read after close failed")?, 0);
+    let e = chat.write_all(b"x").await.unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+
+    // As does dropping the multiplexer, for all of its channels.
+    drop(server);
+    let e = client.accept().await.unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::ConnectionAborted);
+    let e = accepted.write_all(b"x").await.unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::ConnectionAborted);
+    Ok(())
+}
+
+pub async fn run_backlog() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (server, client) = connect_pair(&name, &listener, MuxOptions::new().backlog(1)).await?;
+    let _first = client.open().await.context("first open failed")?;
+    let e = client.open().await.unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+    let _accepted = server.accept().await?;
+    client.open().await.context("open after accept failed")?;
+    Ok(())
+}
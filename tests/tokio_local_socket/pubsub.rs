@@ -0,0 +1,98 @@
+//! Tests the publish-subscribe broker: fan-out by topic, unsubscription and the slow consumer policy.
+
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::local_socket::tokio::pubsub::{BrokerOptions, Publisher, SlowConsumerPolicy, Subscriber};
+use std::time::Duration;
+
+pub async fn run(prefer_namespaced: bool) -> TestResult {
+    let (name, broker) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), prefer_namespaced), |nm| {
+        BrokerOptions::new().bind(nm)
+    })?;
+    let broker = ::tokio::spawn(broker.run());
+
+    let mut both = Subscriber::connect(&*name).await.context("connect failed")?;
+    both.subscribe("alpha").await.context("subscribe failed")?;
+    both.subscribe("beta").await.context("subscribe failed")?;
+    let mut alpha = Subscriber::connect(&*name).await.context("connect failed")?;
+    alpha.subscribe("alpha").await.context("subscribe failed")?;
+
+    let mut publisher = Publisher::connect(&*name).await.context("connect failed")?;
+    publisher.publish("alpha", b"first").await.context("publish failed")?;
+    publisher.publish("gamma", b"nobody").await.context("publish failed")?;
+    publisher.publish("beta", b"second").await.context("publish failed")?;
+    publisher.publish("alpha", b"third").await.context("publish failed")?;
+
+    for (topic, payload) in [("alpha", "first"), ("beta", "second"), ("alpha", "third")] {
+        let msg = both.recv().await.context("receive failed")?;
+        ensure!(
+            msg.topic() == topic && msg.payload() == payload.as_bytes(),
+            "expected {payload:?} on {topic:?}, received {msg:?}"
+        );
+        ensure!(msg.dropped_before() == 0, "unexpected dropped messages before {msg:?}");
+    }
+    for payload in ["first", "third"] {
+        let msg = alpha.recv().await.context("receive failed")?;
+        ensure!(
+            msg.topic() == "alpha" && msg.payload() == payload.as_bytes(),
+            "expected {payload:?} on \"alpha\", received {msg:?}"
+        );
+    }
+
+    both.unsubscribe("alpha").await.context("unsubscribe failed")?;
+    publisher.publish("alpha", b"fourth").await.context("publish failed")?;
+    publisher.publish("beta", b"fifth").await.context("publish failed")?;
+    let msg = both.recv().await.context("receive failed")?;
+    ensure!(
+        msg.payload() == b"fifth",
+        "expected only \"fifth\" after unsubscribing, received {msg:?}"
+    );
+    let msg = alpha.recv().await.context("receive failed")?;
+    ensure!(msg.payload() == b"fourth", "expected \"fourth\", received {msg:?}");
+
+    broker.abort();
+    Ok(())
+}
+
+pub async fn run_slow_consumer(prefer_namespaced: bool) -> TestResult {
+    const NUM_MESSAGES: u64 = 16;
+    let (name, broker) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), prefer_namespaced), |nm| {
+        BrokerOptions::new()
+            .queue_capacity(2)
+            .slow_consumer(SlowConsumerPolicy::DropOldest)
+            .bind(nm)
+    })?;
+    let broker = ::tokio::spawn(broker.run());
+
+    let mut subscriber = Subscriber::connect(&*name).await.context("connect failed")?;
+    subscriber.subscribe("bulk").await.context("subscribe failed")?;
+
+    // Large enough for the socket buffer to fill up long before the subscriber starts reading.
+    let payload = vec![0xa5; 1024 * 1024];
+    let mut publisher = Publisher::connect(&*name).await.context("connect failed")?;
+    for _ in 0..NUM_MESSAGES {
+        publisher.publish("bulk", &payload).await.context("publish failed")?;
+    }
+    publisher.publish("bulk", b"end").await.context("publish failed")?;
+    ::tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let (mut received, mut dropped) = (0, 0);
+    loop {
+        let msg = subscriber.recv().await.context("receive failed")?;
+        received += 1;
+        dropped += msg.dropped_before();
+        if msg.payload() == b"end" {
+            break;
+        }
+        ensure!(msg.payload() == payload, "received corrupted payload");
+    }
+    ensure!(
+        received + dropped == NUM_MESSAGES + 1,
+        "received {received} and dropped {dropped} messages out of {}",
+        NUM_MESSAGES + 1
+    );
+    ensure!(dropped > 0, "no messages were dropped for the slow subscriber");
+
+    broker.abort();
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! Tests asynchronously reading up to a delimiter without consuming anything past it.
+
+use super::util::*;
+use ::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use color_eyre::eyre::Context;
+use interprocess::local_socket::tokio::{LocalSocketListener, LocalSocketStream};
+
+pub async fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (mut server, mut client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    client.write_all(b"first\nsecond\ntail").await.context("write failed")?;
+    drop(client);
+
+    let mut buf = Vec::new();
+    ensure_eq!(
+        server.read_until(b'\n', &mut buf).await.context("first read failed")?,
+        6
+    );
+    ensure_eq!(
+        server.read_until(b'\n', &mut buf).await.context("second read failed")?,
+        7
+    );
+    ensure_eq!(buf, b"first\nsecond\n");
+    let mut tail = Vec::new();
+    server.read_to_end(&mut tail).await.context("read to end failed")?;
+    ensure_eq!(tail, b"tail");
+    Ok(())
+}
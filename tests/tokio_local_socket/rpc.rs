@@ -0,0 +1,63 @@
+//! Tests the RPC layer: concurrent calls answered out of order, timeouts, and the errors reported to the caller.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    framing::Bincode,
+    local_socket::tokio::{
+        rpc::{RpcClient, RpcServer},
+        LocalSocketListener,
+    },
+};
+use std::{io, time::Duration};
+
+/// Doubles the value after sleeping for the given number of milliseconds.
+async fn delayed_double((delay, value): (u64, u32)) -> u32 {
+    ::tokio::time::sleep(Duration::from_millis(delay)).await;
+    value * 2
+}
+
+pub async fn run() -> TestResult {
+    let (name, server) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        RpcServer::bind(nm, delayed_double, Bincode)
+    })?;
+    let server = ::tokio::spawn(server.run());
+
+    let client = RpcClient::<(u64, u32), u32, _>::connect(&*name, Bincode)
+        .await
+        .context("connect failed")?;
+    // The slowest call is made first, so the responses arrive in reverse order.
+    let (a, b, c) = ::tokio::try_join!(client.call(&(150, 1)), client.call(&(75, 2)), client.call(&(0, 3)),)
+        .context("concurrent calls failed")?;
+    ensure_eq!((a, b, c), (2, 4, 6));
+
+    let e = client
+        .call_timeout(&(1000, 4), Duration::from_millis(20))
+        .await
+        .unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::TimedOut);
+    // The late response to the timed out call doesn't get mixed up with this one.
+    let client = client.with_timeout(Some(Duration::from_secs(5)));
+    ensure_eq!(client.call(&(0, 5)).await.context("call after timeout failed")?, 10);
+
+    // A request of the wrong type is reported back instead of going unanswered.
+    let wrong = RpcClient::<String, u32, _>::connect(&*name, Bincode)
+        .await
+        .context("connect failed")?;
+    let e = wrong.call(&"x".to_owned()).await.unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::InvalidData);
+    server.abort();
+
+    // Losing the connection fails the calls waiting on it.
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let dropper = ::tokio::spawn(async move { listener.accept().await.map(drop) });
+    let client = RpcClient::<(u64, u32), u32, _>::connect(&*name, Bincode)
+        .await
+        .context("connect failed")?;
+    let e = client.call(&(0, 6)).await.unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::ConnectionAborted);
+    dropper.await?.context("accept failed")?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Tests the asynchronous graceful shutdown sequence: delivery of the last messages in both directions, and giving up
+//! on a peer which doesn't close its end of the connection.
+
+use super::util::*;
+use ::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use color_eyre::eyre::Context;
+use interprocess::local_socket::tokio::{
+    graceful_shutdown, graceful_shutdown_into, LocalSocketListener, LocalSocketStream,
+};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+static GOODBYE: &[u8] = b"Goodbye from server!";
+static LAST_WORDS: &[u8] = b"Last words from client!";
+
+pub async fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let server = ::tokio::spawn(async move {
+        let mut conn = listener.accept().await?;
+        conn.write_all(GOODBYE).await?;
+        let mut leftover = Vec::new();
+        graceful_shutdown_into(conn, Instant::now() + Duration::from_secs(5), &mut leftover).await?;
+        io::Result::Ok(leftover)
+    });
+
+    let mut conn = LocalSocketStream::connect(&*name).await.context("connect failed")?;
+    let mut buf = vec![0; GOODBYE.len()];
+    conn.read_exact(&mut buf).await.context("read failed")?;
+    ensure_eq!(buf, GOODBYE);
+    // Named pipes can't be half-closed, so end-of-file only comes after the server is done.
+    #[cfg(unix)]
+    {
+        buf.clear();
+        conn.read_to_end(&mut buf).await.context("read to end failed")?;
+        ensure_eq!(buf, b"");
+    }
+    conn.write_all(LAST_WORDS).await.context("write failed")?;
+    drop(conn);
+    let leftover = server.await.unwrap().context("server failed")?;
+    ensure_eq!(leftover, LAST_WORDS);
+    Ok(())
+}
+
+pub async fn run_timeout() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new_auto(make_id!(), false), |nm| {
+        LocalSocketListener::bind(nm)
+    })?;
+    let (conn, _client) =
+        ::tokio::try_join!(listener.accept(), LocalSocketStream::connect(&*name)).context("connection failed")?;
+    let e = graceful_shutdown(conn, Instant::now() + Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    ensure_eq!(e.kind(), io::ErrorKind::TimedOut);
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use super::util::{listen_and_pick_name, NameGen, TestResult};
+use color_eyre::eyre::Context;
+use futures::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    StreamExt, TryStreamExt,
+};
+use interprocess::os::windows::named_pipe::{
+    pipe_mode,
+    tokio::{DuplexPipeStream, PipeListenerOptionsExt},
+    PipeListenerOptions,
+};
+use std::ffi::OsStr;
+use tokio::task;
+
+const NUM_CLIENTS: usize = 4;
+
+pub async fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_tokio_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    let clients = (0..NUM_CLIENTS)
+        .map(|_| {
+            let name = name.clone();
+            task::spawn(async move {
+                let mut conn = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name)
+                    .await
+                    .context("connect failed")?;
+                conn.write_all(b"Hello from client!\n")
+                    .await
+                    .context("client write failed")?;
+                TestResult::Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let conns = listener
+        .incoming()
+        .take(NUM_CLIENTS)
+        .try_collect::<Vec<_>>()
+        .await
+        .context("accept failed")?;
+    for conn in conns {
+        let mut line = String::new();
+        BufReader::new(conn)
+            .read_line(&mut line)
+            .await
+            .context("server read failed")?;
+        ensure_eq!(line, "Hello from client!\n");
+    }
+    for client in clients {
+        client.await.context("client task panicked")??;
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use super::util::{listen_and_pick_name, NameGen, TestResult};
+use color_eyre::eyre::{bail, Context};
+use interprocess::{
+    os::windows::named_pipe::{pipe_mode, tokio::DuplexPipeStream},
+    reliable_recv_msg::AsyncReliableRecvMsgExt,
+};
+use std::io;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::windows::named_pipe::{NamedPipeClient, NamedPipeServer, PipeMode, ServerOptions},
+};
+
+pub async fn run() -> TestResult {
+    let (name, server) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        ServerOptions::new()
+            .first_pipe_instance(true)
+            .pipe_mode(PipeMode::Message)
+            .create(format!(r"\\.\pipe\{nm}"))
+    })?;
+
+    let client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name)
+        .await
+        .context("connect failed")?;
+    server.connect().await.context("Tokio server connect failed")?;
+
+    let server = DuplexPipeStream::<pipe_mode::Messages>::try_from(server)
+        .map_err(io::Error::from)
+        .context("server conversion failed")?;
+    ensure_eq!(server.is_server(), true);
+
+    // A client-side stream doesn't turn into a server.
+    let client = match NamedPipeServer::try_from(client) {
+        Ok(..) => bail!("client-side stream converted into a server"),
+        Err(client) => client,
+    };
+    let mut client = NamedPipeClient::try_from(client)
+        .map_err(|_| io_err("client-side stream did not convert into a client"))
+        .context("client conversion failed")?;
+
+    client.write_all(b"Hello").await.context("Tokio client write failed")?;
+    let mut buf = [0; 5];
+    let rslt = (&server).recv(&mut buf).await.context("server receive failed")?;
+    ensure_eq!(rslt.borrow_to_size(&buf), b"Hello");
+
+    let mut server = NamedPipeServer::try_from(server)
+        .map_err(|_| io_err("server-side stream did not convert into a server"))
+        .context("server conversion back failed")?;
+    server.write_all(b"World").await.context("Tokio server write failed")?;
+    client.read_exact(&mut buf).await.context("Tokio client read failed")?;
+    ensure_eq!(&buf, b"World");
+    Ok(())
+}
+
+fn io_err(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, msg)
+}
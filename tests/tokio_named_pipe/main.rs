@@ -4,7 +4,11 @@
 mod util;
 
 mod bytes;
+mod incoming;
+mod interop;
 mod msg;
+mod truncate;
+mod vectored;
 
 use color_eyre::eyre::Context;
 use interprocess::os::windows::named_pipe::PipeListenerOptions;
@@ -52,6 +56,35 @@ async fn tokio_named_pipe_msg_unidir_server_to_client() -> TestResult {
     drive_server_and_multiple_clients(server_stc, client_stc).await
 }
 
+#[tokio::test]
+async fn tokio_named_pipe_incoming() -> TestResult {
+    install_color_eyre();
+    incoming::run().await
+}
+
+#[tokio::test]
+async fn tokio_named_pipe_interop() -> TestResult {
+    install_color_eyre();
+    interop::run().await
+}
+
+#[tokio::test]
+async fn tokio_named_pipe_truncate() -> TestResult {
+    install_color_eyre();
+    truncate::run().await
+}
+
+#[tokio::test]
+async fn tokio_named_pipe_vectored() -> TestResult {
+    install_color_eyre();
+    vectored::run().await
+}
+#[tokio::test]
+async fn tokio_named_pipe_vectored_large() -> TestResult {
+    install_color_eyre();
+    vectored::run_large().await
+}
+
 async fn drive_server<L, T: Future<Output = TestResult> + Send + 'static>(
     name_sender: Sender<Arc<str>>,
     num_clients: u32,
@@ -0,0 +1,50 @@
+//! Tests receiving messages which don't fit into the buffer through the datagram interface, which goes through the
+//! pool of intermediate buffers.
+
+use super::util::{listen_and_pick_name, NameGen, TestResult};
+use color_eyre::eyre::Context;
+use interprocess::{
+    datagram::AsyncDatagramExt,
+    os::windows::named_pipe::{
+        pipe_mode,
+        tokio::{
+            buffer_pool_limits, set_buffer_pool_limits, BufferPoolLimits, DuplexPipeStream, PipeListenerOptionsExt,
+        },
+        PipeListenerOptions,
+    },
+};
+use std::ffi::OsStr;
+
+pub async fn run() -> TestResult {
+    let limits = BufferPoolLimits {
+        max_buffers: 2,
+        max_buffer_size: 1024,
+    };
+    set_buffer_pool_limits(limits);
+    ensure_eq!(buffer_pool_limits(), limits);
+
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_tokio_duplex::<pipe_mode::Messages>()
+    })?;
+    let mut client = DuplexPipeStream::<pipe_mode::Messages>::connect(&*name)
+        .await
+        .context("connect failed")?;
+    let mut conn = listener.accept().await.context("accept failed")?;
+
+    // The last message is too big to be kept in the pool, which mustn't change the outcome.
+    let msgs = [b"short".to_vec(), vec![b'x'; 100], vec![b'y'; 4096], b"tail".to_vec()];
+    for msg in &msgs {
+        client.send(msg).await.context("send failed")?;
+    }
+    for msg in &msgs {
+        let mut buf = [0; 8];
+        let received = conn.recv(&mut buf).await.context("receive failed")?;
+        let expected = &msg[..msg.len().min(buf.len())];
+        ensure_eq!(&buf[..received], expected);
+    }
+
+    set_buffer_pool_limits(BufferPoolLimits::default());
+    Ok(())
+}
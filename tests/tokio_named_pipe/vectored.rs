@@ -0,0 +1,105 @@
+use super::util::{listen_and_pick_name, NameGen, TestResult};
+use color_eyre::eyre::{ensure, Context};
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use interprocess::os::windows::named_pipe::{
+    pipe_mode,
+    tokio::{DuplexPipeStream, PipeListenerOptionsExt},
+    PipeListenerOptions,
+};
+use std::{
+    ffi::OsStr,
+    io::{IoSlice, IoSliceMut},
+};
+
+const HEADER: &[u8] = b"HDR:";
+const BODY: &[u8] = b"Body of the message, sent together with the header";
+
+pub async fn run() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_tokio_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    let mut client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name)
+        .await
+        .context("connect failed")?;
+    let mut conn = listener.accept().await.context("accept failed")?;
+
+    let written = client
+        .write_vectored(&[IoSlice::new(HEADER), IoSlice::new(&[]), IoSlice::new(BODY)])
+        .await
+        .context("vectored write failed")?;
+    // All slices have to be written at once rather than just the first one.
+    ensure_eq!(written, HEADER.len() + BODY.len());
+
+    let (mut header, mut body) = ([0; HEADER.len()], [0; BODY.len()]);
+    let received = conn
+        .read_vectored(&mut [IoSliceMut::new(&mut header), IoSliceMut::new(&mut body)])
+        .await
+        .context("vectored read failed")?;
+    ensure_eq!(received == 0, false);
+    // Whatever didn't arrive in one go is read conventionally.
+    if received < HEADER.len() {
+        conn.read_exact(&mut header[received..]).await.context("read failed")?;
+        conn.read_exact(&mut body).await.context("read failed")?;
+    } else {
+        conn.read_exact(&mut body[received - HEADER.len()..])
+            .await
+            .context("read failed")?;
+    }
+    ensure_eq!(&header, HEADER);
+    ensure_eq!(&body, BODY);
+    Ok(())
+}
+
+/// Sends a body too big to be coalesced between a small header and trailer, making sure nothing gets lost or reordered
+/// when the slices are written over several calls.
+pub async fn run_large() -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), true), |nm| {
+        PipeListenerOptions::new()
+            .name(nm.as_ref() as &OsStr)
+            .create_tokio_duplex::<pipe_mode::Bytes>()
+    })?;
+
+    let mut client = DuplexPipeStream::<pipe_mode::Bytes>::connect(&*name)
+        .await
+        .context("connect failed")?;
+    let mut conn = listener.accept().await.context("accept failed")?;
+
+    let body = (0..1024 * 1024).map(|i| i as u8).collect::<Vec<u8>>();
+    let slices = [HEADER, &body[..], BODY];
+    let expected = slices.concat();
+
+    let write = async {
+        let mut offset = 0;
+        while offset < expected.len() {
+            // Skips what's been written so far, which may end partway through a slice.
+            let mut skip = offset;
+            let mut remaining = Vec::with_capacity(slices.len());
+            for slice in slices {
+                if skip < slice.len() {
+                    remaining.push(IoSlice::new(&slice[skip..]));
+                }
+                skip = skip.saturating_sub(slice.len());
+            }
+            let written = client
+                .write_vectored(&remaining)
+                .await
+                .context("vectored write failed")?;
+            ensure!(written > 0, "vectored write wrote nothing");
+            offset += written;
+        }
+        client.flush().await.context("flush failed")?;
+        drop(client);
+        TestResult::Ok(())
+    };
+    let read = async {
+        let mut received = Vec::with_capacity(expected.len());
+        conn.read_to_end(&mut received).await.context("read failed")?;
+        ensure!(received == expected, "received data differs from what was sent");
+        TestResult::Ok(())
+    };
+    ::tokio::try_join!(write, read)?;
+    Ok(())
+}
@@ -0,0 +1,20 @@
+#![cfg(feature = "tokio")]
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, TestResult};
+
+mod process;
+
+#[tokio::test]
+async fn tokio_peer_watcher_process() -> TestResult {
+    install_color_eyre();
+    process::run().await
+}
+/// The process watched by `tokio_peer_watcher_process`.
+#[test]
+#[ignore]
+fn tokio_peer_watcher_child() -> TestResult {
+    install_color_eyre();
+    process::run_child()
+}
@@ -0,0 +1,48 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::peer_watcher::PeerWatcher;
+use std::{
+    env,
+    io::{self, Read},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+const CHILD_ENV: &str = "INTERPROCESS_TEST_TOKIO_PEER_WATCHER_CHILD";
+
+pub async fn run() -> TestResult {
+    let mut child = Command::new(env::current_exe()?)
+        .args(["--exact", "tokio_peer_watcher_child", "--ignored", "--test-threads=1"])
+        .env(CHILD_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("spawn failed")?;
+    let watcher = PeerWatcher::new(child.id()).context("watcher creation failed")?;
+    ensure!(
+        ::tokio::time::timeout(Duration::from_millis(10), watcher.wait_async())
+            .await
+            .is_err(),
+        "wait finished before the child exited"
+    );
+
+    // Both of these waits are in progress at once.
+    drop(child.stdin.take());
+    let waits = async { ::tokio::try_join!(watcher.wait_async(), watcher.wait_async()) };
+    ::tokio::time::timeout(Duration::from_secs(30), waits)
+        .await
+        .context("child didn't exit after its input was closed")?
+        .context("wait failed")?;
+    ensure!(watcher.has_exited()?, "exited child isn't reported as such");
+    child.wait().context("reaping child failed")?;
+    Ok(())
+}
+
+pub fn run_child() -> TestResult {
+    if env::var_os(CHILD_ENV).is_none() {
+        return Ok(());
+    }
+    io::stdin().read_to_end(&mut Vec::new())?;
+    Ok(())
+}
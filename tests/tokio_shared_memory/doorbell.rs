@@ -0,0 +1,19 @@
+use super::util::TestResult;
+use color_eyre::eyre::Context;
+use interprocess::shared_memory::Doorbell;
+use std::time::Duration;
+use tokio::{time::sleep, try_join};
+
+pub async fn run() -> TestResult {
+    let doorbell = Doorbell::new().context("creation failed")?;
+    // Two tasks waiting on the same doorbell at once, each woken up by its own ring.
+    let ring = async {
+        for _ in 0..2 {
+            sleep(Duration::from_millis(10)).await;
+            doorbell.ring()?;
+        }
+        Ok(())
+    };
+    try_join!(doorbell.wait_async(), doorbell.wait_async(), ring).context("concurrent waits failed")?;
+    Ok(())
+}
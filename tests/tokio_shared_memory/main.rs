@@ -0,0 +1,30 @@
+#![cfg(feature = "tokio")]
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, TestResult};
+
+mod doorbell;
+mod queue;
+mod ring;
+
+#[tokio::test]
+async fn tokio_shared_memory_doorbell() -> TestResult {
+    install_color_eyre();
+    doorbell::run().await
+}
+#[tokio::test]
+async fn tokio_shared_memory_queue() -> TestResult {
+    install_color_eyre();
+    queue::run().await
+}
+#[tokio::test]
+async fn tokio_shared_memory_ring() -> TestResult {
+    install_color_eyre();
+    ring::run(false).await
+}
+#[tokio::test]
+async fn tokio_shared_memory_ring_doorbells() -> TestResult {
+    install_color_eyre();
+    ring::run(true).await
+}
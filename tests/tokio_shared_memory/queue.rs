@@ -0,0 +1,34 @@
+use super::util::TestResult;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::shared_memory::{MessageQueue, SharedMemory};
+use tokio::try_join;
+
+const COUNT: u32 = 1000;
+
+pub async fn run() -> TestResult {
+    let shm = SharedMemory::anonymous(MessageQueue::required_size(8, 16)).context("creation failed")?;
+    let queue = MessageQueue::create(shm, 8, 16).context("initialization failed")?;
+    let queue = {
+        use interprocess::shared_memory::Doorbell;
+        queue.with_doorbells(Doorbell::new()?, Doorbell::new()?)
+    };
+
+    let send = async {
+        for i in 0..COUNT {
+            queue.send_async(&i.to_le_bytes()).await?;
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    let recv = async {
+        let mut buf = [0; 16];
+        let mut received = Vec::with_capacity(COUNT as usize);
+        for _ in 0..COUNT {
+            let len = queue.recv_async(&mut buf).await?;
+            received.push(u32::from_le_bytes(buf[..len].try_into().unwrap()));
+        }
+        Ok(received)
+    };
+    let ((), received) = try_join!(send, recv).context("transfer failed")?;
+    ensure!(received.into_iter().eq(0..COUNT), "messages were received out of order");
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use super::util::TestResult;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::shared_memory::{RingReceiver, RingSender, SharedMemory, RING_HEADER_SIZE};
+use std::io;
+use tokio::try_join;
+
+const CAPACITY: usize = 4096;
+const LEN: usize = 256 * 1024;
+
+fn byte_at(i: usize) -> u8 {
+    (i % 251) as u8
+}
+
+pub async fn run(doorbells: bool) -> TestResult {
+    let shm = SharedMemory::anonymous(RING_HEADER_SIZE + CAPACITY).context("creation failed")?;
+    #[cfg(unix)]
+    let other = SharedMemory::from_fd(std::os::unix::io::AsFd::as_fd(&shm).try_clone_to_owned()?);
+    #[cfg(windows)]
+    let other = SharedMemory::from_handle(std::os::windows::io::AsHandle::as_handle(&shm).try_clone_to_owned()?);
+    let sender = RingSender::create(shm).context("initialization failed")?;
+    let receiver = RingReceiver::open(other.context("mapping a second time failed")?).context("attaching failed")?;
+    let (mut sender, mut receiver) = if doorbells {
+        use interprocess::{shared_memory::Doorbell, TryClone};
+        let (data_ready, space_ready) = (Doorbell::new()?, Doorbell::new()?);
+        let receiver = receiver.with_doorbells(data_ready.try_clone()?, space_ready.try_clone()?);
+        (sender.with_doorbells(data_ready, space_ready), receiver)
+    } else {
+        (sender, receiver)
+    };
+
+    let write = async move {
+        let data = (0..LEN).map(byte_at).collect::<Vec<_>>();
+        let mut sent = 0;
+        while sent < LEN {
+            sent += sender.send_async(&data[sent..]).await?;
+        }
+        // Dropping the sender lets the receiver see end of file.
+        Ok::<_, io::Error>(())
+    };
+    let read = async {
+        let mut received = Vec::with_capacity(LEN);
+        let mut buf = [0; 1000];
+        loop {
+            let len = receiver.recv_async(&mut buf).await?;
+            if len == 0 {
+                return Ok(received);
+            }
+            received.extend_from_slice(&buf[..len]);
+        }
+    };
+    let ((), received) = try_join!(write, read).context("transfer failed")?;
+    ensure_eq!(received.len(), LEN);
+    ensure!(
+        received.iter().enumerate().all(|(i, &b)| b == byte_at(i)),
+        "data was corrupted"
+    );
+    Ok(())
+}
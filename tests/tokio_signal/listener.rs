@@ -0,0 +1,29 @@
+use super::util::TestResult;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::unix::signal::{rt_signal, send_queued, SignalListener};
+use std::{process, time::Duration};
+use tokio::{time::sleep, try_join};
+
+pub async fn run() -> TestResult {
+    let signal = rt_signal(0).unwrap();
+    let listener = SignalListener::new(&[signal]).context("listener creation failed")?;
+    let send = async {
+        sleep(Duration::from_millis(10)).await;
+        send_queued(process::id() as libc::pid_t, signal, 42)
+    };
+    let (info, ()) = try_join!(listener.recv_async(), send).context("receive failed")?;
+    ensure!(info.value() == Some(42), "wrong payload: {info:?}");
+
+    // Two tasks receiving from the same listener at once each get one of the signals.
+    let send = async {
+        sleep(Duration::from_millis(10)).await;
+        send_queued(process::id() as libc::pid_t, signal, 1)?;
+        send_queued(process::id() as libc::pid_t, signal, 2)
+    };
+    let (first, second, ()) =
+        try_join!(listener.recv_async(), listener.recv_async(), send).context("concurrent receives failed")?;
+    let mut values = [first.value(), second.value()];
+    values.sort();
+    ensure!(values == [Some(1), Some(2)], "wrong payloads: {first:?}, {second:?}");
+    Ok(())
+}
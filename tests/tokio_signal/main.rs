@@ -0,0 +1,13 @@
+#![cfg(all(feature = "tokio", any(target_os = "linux", target_os = "android")))]
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, TestResult};
+
+mod listener;
+
+#[tokio::test]
+async fn tokio_signal_listener() -> TestResult {
+    install_color_eyre();
+    listener::run().await
+}
@@ -0,0 +1,29 @@
+use super::util::TestResult;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::{sync::Event, TryClone};
+use std::time::Duration;
+use tokio::{time::sleep, try_join};
+
+pub async fn run() -> TestResult {
+    let event = Event::new().context("creation failed")?;
+    let other = event.try_clone().context("cloning failed")?;
+    let set = async {
+        sleep(Duration::from_millis(10)).await;
+        other.set()
+    };
+    try_join!(event.wait_async(), set).context("waiting failed")?;
+    event.reset()?;
+
+    // Any number of tasks can wait on the same event at once.
+    let set = async {
+        sleep(Duration::from_millis(10)).await;
+        other.set()
+    };
+    try_join!(event.wait_async(), event.wait_async(), set).context("concurrent waits failed")?;
+
+    // A set event makes waits finish right away.
+    event.wait_async().await.context("waiting on a set event failed")?;
+    event.reset()?;
+    ensure!(!other.is_set()?, "event is still set after resetting");
+    Ok(())
+}
@@ -0,0 +1,19 @@
+#![cfg(feature = "tokio")]
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, TestResult};
+
+mod event;
+mod named_event;
+
+#[tokio::test]
+async fn tokio_sync_event() -> TestResult {
+    install_color_eyre();
+    event::run().await
+}
+#[tokio::test]
+async fn tokio_sync_named_event() -> TestResult {
+    install_color_eyre();
+    named_event::run(make_id!()).await
+}
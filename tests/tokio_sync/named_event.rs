@@ -0,0 +1,32 @@
+use super::util::{TestResult, Xorshift32};
+use color_eyre::eyre::{ensure, Context};
+use interprocess::sync::NamedEvent;
+use std::{process, time::Duration};
+use tokio::{
+    time::{sleep, timeout},
+    try_join,
+};
+
+pub async fn run(id: &'static str) -> TestResult {
+    let name = format!(
+        "interprocess-test-{}-{:08x}",
+        process::id(),
+        Xorshift32::from_id(id).next()
+    );
+    let event = NamedEvent::create_with_drop_guard(&name).context("creation failed")?;
+    let other = NamedEvent::open(&name).context("opening failed")?;
+
+    // A wait which is given up on doesn't get in the way of later ones.
+    ensure!(
+        timeout(Duration::from_millis(20), event.wait_async()).await.is_err(),
+        "wait finished on an unset event"
+    );
+
+    let set = async {
+        sleep(Duration::from_millis(10)).await;
+        other.set()
+    };
+    try_join!(event.wait_async(), set).context("waiting failed")?;
+    event.wait_async().await.context("waiting on a set event failed")?;
+    Ok(())
+}
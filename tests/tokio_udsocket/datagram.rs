@@ -0,0 +1,39 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::unix::udsocket::{tokio::UdDatagram, UdSocketPath};
+use std::mem::MaybeUninit;
+
+pub(super) async fn run(mut namegen: NameGen) -> TestResult {
+    let mks = |nm: &str| UdDatagram::bound(nm);
+    let (a_name, a_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side A socket")?;
+    let (_, b_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side B socket")?;
+
+    // Side B sends a request without having a destination set, side A replies to whoever sent it.
+    b_socket
+        .send_to(b"Request from side B", &*a_name)
+        .await
+        .context("request send failed")?;
+
+    let mut buf = [0; 64];
+    let mut addr_buf = UdSocketPath::buffer();
+    let read = a_socket
+        .recv_from_stdbuf(&mut buf, &mut addr_buf)
+        .await
+        .context("request receive failed")?;
+    ensure_eq!(&buf[..read], b"Request from side B");
+
+    a_socket
+        .send_to(b"Reply from side A", addr_buf)
+        .await
+        .context("reply send failed")?;
+
+    let mut ubuf = [MaybeUninit::uninit(); 64];
+    let read = b_socket.recv_uninit(&mut ubuf).await.context("reply receive failed")?;
+    let reply = ubuf[..read]
+        .iter()
+        .map(|b| unsafe { b.assume_init() })
+        .collect::<Vec<_>>();
+    ensure_eq!(reply, b"Reply from side A");
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    datagram::{AsyncDatagram, AsyncDatagramExt},
+    os::unix::udsocket::{tokio::UdDatagram, UdSocketPath},
+};
+
+pub(super) async fn run(mut namegen: NameGen) -> TestResult {
+    let mks = |nm: &str| UdDatagram::bound(nm);
+    let (a_name, a_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side A socket")?;
+    let (_, b_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side B socket")?;
+    b_socket.set_destination(&*a_name).context("set destination failed")?;
+    exchange(a_socket, b_socket).await
+}
+
+/// Only uses the traits, as transport-agnostic code would.
+async fn exchange<D: AsyncDatagram<Address = UdSocketPath<'static>> + Unpin>(mut a: D, mut b: D) -> TestResult {
+    let mut buf = [0; 64];
+    b.send(b"Request from side B").await.context("request send failed")?;
+    let (read, addr) = a.recv_from(&mut buf).await.context("request receive failed")?;
+    ensure_eq!(&buf[..read], b"Request from side B");
+    let b_addr = addr.ok_or_else(|| color_eyre::eyre::eyre!("no address for a bound sender"))?;
+
+    a.send_to(b"Reply from side A", &b_addr)
+        .await
+        .context("reply send failed")?;
+    let read = b.recv(&mut buf).await.context("reply receive failed")?;
+    ensure_eq!(&buf[..read], b"Reply from side A");
+    Ok(())
+}
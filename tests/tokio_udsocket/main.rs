@@ -0,0 +1,73 @@
+#![cfg(all(unix, feature = "tokio"))]
+
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, NameGen, TestResult};
+
+mod datagram;
+mod datagram_trait;
+mod msg;
+mod reliable_recv;
+mod stream;
+mod vectored;
+
+#[tokio::test]
+async fn tokio_udsocket_datagram() -> TestResult {
+    install_color_eyre();
+    datagram::run(NameGen::new(make_id!(), false)).await?;
+    if cfg!(target_os = "linux") {
+        datagram::run(NameGen::new(make_id!(), true)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn tokio_udsocket_datagram_trait() -> TestResult {
+    install_color_eyre();
+    datagram_trait::run(NameGen::new(make_id!(), false)).await?;
+    if cfg!(target_os = "linux") {
+        datagram_trait::run(NameGen::new(make_id!(), true)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn tokio_udsocket_reliable_recv() -> TestResult {
+    install_color_eyre();
+    reliable_recv::run(NameGen::new(make_id!(), false)).await?;
+    if cfg!(target_os = "linux") {
+        reliable_recv::run(NameGen::new(make_id!(), true)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn tokio_udsocket_stream() -> TestResult {
+    install_color_eyre();
+    stream::run(NameGen::new(make_id!(), false)).await?;
+    if cfg!(target_os = "linux") {
+        stream::run(NameGen::new(make_id!(), true)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn tokio_udsocket_msg() -> TestResult {
+    install_color_eyre();
+    msg::run(NameGen::new(make_id!(), false)).await?;
+    if cfg!(target_os = "linux") {
+        msg::run(NameGen::new(make_id!(), true)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn tokio_udsocket_vectored() -> TestResult {
+    install_color_eyre();
+    vectored::run(NameGen::new(make_id!(), false)).await?;
+    if cfg!(target_os = "linux") {
+        vectored::run(NameGen::new(make_id!(), true)).await?;
+    }
+    Ok(())
+}
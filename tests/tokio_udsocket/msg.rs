@@ -0,0 +1,45 @@
+use super::util::*;
+use ::tokio::{select, time::sleep, try_join};
+use color_eyre::eyre::{ensure, Context};
+use interprocess::os::unix::udsocket::tokio::{UdStream, UdStreamListener};
+use std::time::Duration;
+
+// Big enough to not fit into the socket buffer in one go, so that the futures actually get interrupted midway.
+const MSG_LEN: usize = 1024 * 1024;
+const TICK: Duration = Duration::from_millis(1);
+
+fn make_msg() -> Vec<u8> {
+    (0..MSG_LEN).map(|i| (i % 251) as u8).collect()
+}
+
+pub(super) async fn run(mut namegen: NameGen) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut namegen, |nm| UdStreamListener::bind(nm))?;
+    let server = async {
+        let conn = listener.accept().await.context("accept failed")?;
+        let msg = make_msg();
+        let mut send = conn.send_all_msg(&msg);
+        loop {
+            select! {
+                rslt = &mut send => break rslt.context("server send failed")?,
+                _ = sleep(TICK) => {}
+            }
+        }
+        ensure_eq!(send.progress(), MSG_LEN);
+        TestResult::Ok(())
+    };
+    let client = async {
+        let conn = UdStream::connect(&*name).await.context("connect failed")?;
+        let mut buf = vec![0; MSG_LEN];
+        let mut recv = conn.recv_exact_msg(&mut buf);
+        loop {
+            select! {
+                rslt = &mut recv => break rslt.context("client receive failed")?,
+                _ = sleep(TICK) => {}
+            }
+        }
+        ensure_eq!(recv.progress(), MSG_LEN);
+        ensure!(buf == make_msg(), "received message is torn");
+        TestResult::Ok(())
+    };
+    try_join!(server, client).map(|((), ())| ())
+}
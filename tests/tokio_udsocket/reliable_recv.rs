@@ -0,0 +1,33 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{os::unix::udsocket::tokio::UdDatagram, reliable_recv_msg::AsyncReliableRecvMsgExt};
+
+pub(super) async fn run(mut namegen: NameGen) -> TestResult {
+    let mks = |nm: &str| UdDatagram::bound(nm);
+    let (a_name, mut a_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side A socket")?;
+    let b_socket = UdDatagram::unbound()?;
+    b_socket.set_destination(&*a_name).context("set destination failed")?;
+    let big = (0..3000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+    let mut buf = [0; 16];
+    // Nothing has been sent yet, so the receive has to wait for readiness.
+    let (_, rslt) = ::tokio::try_join!(
+        async {
+            ::tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            b_socket.send(&big).await
+        },
+        AsyncReliableRecvMsgExt::recv(&mut a_socket, &mut buf),
+    )
+    .context("big exchange failed")?;
+    ensure_eq!(rslt.fit(), false);
+    ensure_eq!(rslt.borrow_to_size(&buf), &big[..]);
+
+    b_socket.send(b"Small").await.context("small send failed")?;
+    // Also works by shared reference, like the other receive methods.
+    let rslt = AsyncReliableRecvMsgExt::try_recv(&mut &a_socket, &mut buf)
+        .await
+        .context("small receive failed")?;
+    ensure_eq!(rslt.to_result(), Ok(5));
+    ensure_eq!(&buf[..5], b"Small");
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use super::util::*;
+use ::tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    try_join,
+};
+use color_eyre::eyre::Context;
+use interprocess::os::unix::udsocket::{
+    tokio::{UdStream, UdStreamListener},
+    UdSocketPath,
+};
+use std::time::Duration;
+
+pub(super) async fn run(mut namegen: NameGen) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut namegen, |nm| UdStreamListener::bind(nm))?;
+    let server = async {
+        let (mut conn, addr) = listener.accept_with_addr().await.context("accept failed")?;
+        ensure_eq!(addr, UdSocketPath::Unnamed);
+        conn.write_all(b"Hello from server!")
+            .await
+            .context("server send failed")?;
+        conn.close(Some(Duration::from_secs(5)))
+            .await
+            .context("graceful close failed")?;
+        TestResult::Ok(())
+    };
+    let client = async {
+        let mut conn = UdStream::connect(&*name).await.context("connect failed")?;
+        let mut buf = String::new();
+        conn.read_to_string(&mut buf).await.context("client receive failed")?;
+        ensure_eq!(buf, "Hello from server!");
+        conn.write_all(b"Leftover data").await.context("client send failed")?;
+        drop(conn);
+        TestResult::Ok(())
+    };
+    try_join!(server, client).map(|((), ())| ())
+}
@@ -0,0 +1,23 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    datagram::AsyncDatagramExt, message_builder::MessageBuilder, os::unix::udsocket::tokio::UdDatagram,
+};
+
+pub(super) async fn run(mut namegen: NameGen) -> TestResult {
+    let mks = |nm: &str| UdDatagram::bound(nm);
+    let (a_name, mut a_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side A socket")?;
+    let (_, mut b_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side B socket")?;
+    b_socket.set_destination(&*a_name).context("set destination failed")?;
+
+    let body = [7; 300];
+    let msg = MessageBuilder::new().part(b"header:").part(&body).part(b":trailer");
+    msg.send_async(&mut b_socket).await.context("vectored send failed")?;
+
+    let mut buf = [0; 512];
+    let read = AsyncDatagramExt::recv(&mut a_socket, &mut buf)
+        .await
+        .context("receive failed")?;
+    ensure_eq!(&buf[..read], &msg.to_vec()[..]);
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use super::util::TestResult;
+use color_eyre::eyre::Context;
+use interprocess::unnamed_pipe::tokio::pipe;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    try_join,
+};
+
+const MSG: &[u8] = b"Hello from the other end of the pipe!";
+
+pub async fn run() -> TestResult {
+    let (mut writer, mut reader) = pipe().context("pipe creation failed")?;
+
+    let write = async {
+        writer.write_all(MSG).await.context("write failed")?;
+        // Dropping the writer closes the pipe, which the reader sees as EOF.
+        drop(writer);
+        TestResult::Ok(())
+    };
+    let read = async {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.context("read failed")?;
+        TestResult::Ok(buf)
+    };
+    let ((), buf) = try_join!(write, read)?;
+    ensure_eq!(buf, MSG);
+
+    Ok(())
+}
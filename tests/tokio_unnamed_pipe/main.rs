@@ -0,0 +1,13 @@
+#![cfg(feature = "tokio")]
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::{install_color_eyre, TestResult};
+
+mod basic;
+
+#[tokio::test]
+async fn tokio_unnamed_pipe_basic() -> TestResult {
+    install_color_eyre();
+    basic::run().await
+}
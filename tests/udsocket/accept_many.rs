@@ -0,0 +1,40 @@
+//! Tests accepting several waiting connections at once with a nonblocking listener.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::unix::udsocket::{UdStream, UdStreamListener};
+use std::io;
+
+const CLIENTS: usize = 5;
+
+pub(super) fn run(mut namegen: NameGen) -> TestResult {
+    let (name, listener) = listen_and_pick_name(&mut namegen, |nm| UdStreamListener::bind(nm))?;
+    listener
+        .set_nonblocking(true)
+        .context("failed to make listener nonblocking")?;
+
+    let mut conns = Vec::new();
+    ensure_eq!(
+        listener.accept_many(&mut conns, CLIENTS).map_err(|e| e.kind()),
+        Err(io::ErrorKind::WouldBlock)
+    );
+
+    let clients = (0..CLIENTS)
+        .map(|_| UdStream::connect(&*name))
+        .collect::<io::Result<Vec<_>>>()
+        .context("connect failed")?;
+
+    ensure_eq!(listener.accept_many(&mut conns, 2).context("accept failed")?, 2);
+    ensure_eq!(conns.len(), 2);
+    ensure_eq!(
+        listener.accept_many(&mut conns, usize::MAX).context("accept failed")?,
+        CLIENTS - 2
+    );
+    ensure_eq!(conns.len(), CLIENTS);
+    ensure_eq!(
+        listener.accept_many(&mut conns, CLIENTS).map_err(|e| e.kind()),
+        Err(io::ErrorKind::WouldBlock)
+    );
+    drop(clients);
+    Ok(())
+}
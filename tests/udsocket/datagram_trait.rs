@@ -0,0 +1,41 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    datagram::Datagram,
+    os::unix::udsocket::{ToUdSocketPath, UdDatagram, UdSocketPath},
+};
+
+pub(super) fn run(mut namegen: NameGen) -> TestResult {
+    let mks = |nm: &str| UdDatagram::bound(nm);
+    let (a_name, a_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side A socket")?;
+    let (_, b_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side B socket")?;
+    let a_addr = a_name.to_socket_path()?.upgrade();
+    exchange(a_socket, b_socket, UdDatagram::unbound()?, &a_addr)
+}
+
+/// Only uses the trait, as transport-agnostic code would.
+fn exchange<D: Datagram<Address = UdSocketPath<'static>>>(
+    mut a: D,
+    mut b: D,
+    mut unbound: D,
+    a_addr: &UdSocketPath<'static>,
+) -> TestResult {
+    let mut buf = [0; 64];
+    b.send_to(b"Request from side B", a_addr)
+        .context("request send failed")?;
+    let (read, b_addr) = a.recv_from(&mut buf).context("request receive failed")?;
+    ensure_eq!(&buf[..read], b"Request from side B");
+    let b_addr = b_addr.ok_or_else(|| color_eyre::eyre::eyre!("no address for a bound sender"))?;
+
+    // The reply is truncated to the size of the buffer.
+    a.send_to(b"Reply from side A", &b_addr).context("reply send failed")?;
+    let mut small = [0; 5];
+    ensure_eq!(b.recv(&mut small).context("reply receive failed")?, 5);
+    ensure_eq!(&small, b"Reply");
+
+    unbound.send_to(b"Anonymous", a_addr).context("anonymous send failed")?;
+    let (read, addr) = a.recv_from(&mut buf).context("anonymous receive failed")?;
+    ensure_eq!(&buf[..read], b"Anonymous");
+    ensure_eq!(addr, None);
+    Ok(())
+}
@@ -5,9 +5,19 @@
 mod util;
 use util::*;
 
+mod accept_many;
 mod credentials;
 mod datagram;
+mod datagram_trait;
+#[cfg(feature = "mio")]
+mod mio_source;
+mod path;
+mod reliable_recv;
+mod stdio;
 mod stream;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod uring;
+mod vectored;
 
 #[test]
 fn udsocket_stream() -> TestResult {
@@ -21,6 +31,16 @@ fn udsocket_stream() -> TestResult {
 }
 
 #[cfg(uds_cont_credentials)]
+#[test]
+fn udsocket_accept_many() -> TestResult {
+    install_color_eyre();
+    accept_many::run(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        accept_many::run(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
 #[test]
 fn udsocket_continuous_credentials() -> TestResult {
     use credentials::*;
@@ -54,3 +74,75 @@ fn udsocket_datagram() -> TestResult {
     }
     Ok(())
 }
+
+#[test]
+fn udsocket_datagram_trait() -> TestResult {
+    install_color_eyre();
+    datagram_trait::run(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        datagram_trait::run(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn udsocket_path() -> TestResult {
+    install_color_eyre();
+    path::run(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        path::run(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn udsocket_reliable_recv() -> TestResult {
+    install_color_eyre();
+    reliable_recv::run(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        reliable_recv::run(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn udsocket_vectored() -> TestResult {
+    install_color_eyre();
+    vectored::run(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        vectored::run(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "mio")]
+#[test]
+fn udsocket_mio() -> TestResult {
+    install_color_eyre();
+    mio_source::run(NameGen::new(make_id!(), false))?;
+    if cfg!(target_os = "linux") {
+        mio_source::run(NameGen::new(make_id!(), true))?;
+    }
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+#[test]
+fn udsocket_uring() -> TestResult {
+    install_color_eyre();
+    uring::run(NameGen::new(make_id!(), false))?;
+    uring::run(NameGen::new(make_id!(), true))
+}
+
+#[test]
+fn udsocket_stdio() -> TestResult {
+    install_color_eyre();
+    stdio::run()
+}
+/// The half of `udsocket_stdio` which runs in a child process.
+#[test]
+#[ignore]
+fn udsocket_stdio_child() -> TestResult {
+    install_color_eyre();
+    stdio::run_child()
+}
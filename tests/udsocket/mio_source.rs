@@ -0,0 +1,35 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::unix::udsocket::{UdDatagram, UdSocket};
+use mio::{Events, Interest, Poll, Token};
+use std::time::Duration;
+
+pub(super) fn run(mut namegen: NameGen) -> TestResult {
+    let (a_name, mut a_socket) =
+        listen_and_pick_name(&mut namegen, |nm| UdDatagram::bound(nm)).context("failed to make side A socket")?;
+    let (_, b_socket) =
+        listen_and_pick_name(&mut namegen, |nm| UdDatagram::bound(nm)).context("failed to make side B socket")?;
+    a_socket.set_nonblocking(true)?;
+
+    let mut poll = Poll::new().context("failed to create poller")?;
+    let mut events = Events::with_capacity(4);
+    poll.registry()
+        .register(&mut a_socket, Token(0), Interest::READABLE)
+        .context("registration failed")?;
+
+    b_socket.set_destination(&*a_name)?;
+    b_socket.send(b"Hello from side B!")?;
+
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .context("poll failed")?;
+    ensure_eq!(events.iter().map(|e| e.token()).collect::<Vec<_>>(), [Token(0)]);
+
+    let mut buf = [0; 64];
+    let read = a_socket.recv(&mut buf).context("receive failed")?;
+    ensure_eq!(&buf[..read], b"Hello from side B!");
+
+    poll.registry()
+        .deregister(&mut a_socket)
+        .context("deregistration failed")?;
+    Ok(())
+}
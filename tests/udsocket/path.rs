@@ -0,0 +1,39 @@
+//! Tests addressing sockets with the various string types.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::os::unix::udsocket::{UdDatagram, UdStream, UdStreamListener, MAX_UDSOCKET_PATH_LEN};
+use std::{
+    ffi::{CString, OsStr},
+    io,
+    path::Path,
+};
+
+fn invalid_input<T>(result: io::Result<T>) -> bool {
+    matches!(result, Err(e) if e.kind() == io::ErrorKind::InvalidInput)
+}
+
+pub(super) fn run(mut namegen: NameGen) -> TestResult {
+    let (name, _listener) = listen_and_pick_name(&mut namegen, |nm| UdStreamListener::bind(nm))?;
+
+    UdStream::connect(&*name).context("connect with str failed")?;
+    UdStream::connect(name.to_string()).context("connect with String failed")?;
+    UdStream::connect(OsStr::new(&*name)).context("connect with OsStr failed")?;
+    UdStream::connect(CString::new(name.as_bytes())?).context("connect with CString failed")?;
+    if !name.starts_with('@') {
+        UdStream::connect(Path::new(&*name)).context("connect with Path failed")?;
+    }
+
+    ensure_eq!(invalid_input(UdStream::connect("interior\0nul")), true);
+    let too_long = "a".repeat(MAX_UDSOCKET_PATH_LEN);
+    ensure_eq!(invalid_input(UdStream::connect(&*too_long)), true);
+    ensure_eq!(invalid_input(UdStream::connect(&too_long[1..])), false);
+
+    let (dst_name, dst) = listen_and_pick_name(&mut namegen, |nm| UdDatagram::bound(nm))?;
+    let src = UdDatagram::unbound().context("socket creation failed")?;
+    src.send_to(b"ping", &*dst_name).context("send_to failed")?;
+    let mut buf = [0; 4];
+    dst.recv(&mut buf).context("receive failed")?;
+    ensure_eq!(&buf, b"ping");
+    Ok(())
+}
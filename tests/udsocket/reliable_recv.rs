@@ -0,0 +1,54 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    os::unix::udsocket::UdDatagram,
+    reliable_recv_msg::{RecvResult, ReliableRecvMsg, TryRecvResult},
+};
+
+/// Longer than the buffers the messages are first received into, and than the initial peeking buffer used outside of
+/// Linux.
+const BIG_LEN: usize = 3000;
+
+pub(super) fn run(mut namegen: NameGen) -> TestResult {
+    let mks = |nm: &str| UdDatagram::bound(nm);
+    let (a_name, mut a_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side A socket")?;
+    let b_socket = UdDatagram::unbound()?;
+    b_socket.set_destination(&*a_name).context("set destination failed")?;
+    let big = (0..BIG_LEN).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+    b_socket.send(b"Small").context("small send failed")?;
+    b_socket.send(&big).context("big send failed")?;
+    b_socket.send(&big).context("big send failed")?;
+
+    let mut buf = [0; 16];
+    let rslt = ReliableRecvMsg::recv(&mut a_socket, &mut buf).context("small receive failed")?;
+    ensure_eq!(rslt.borrow_to_size(&buf), b"Small");
+    ensure_eq!(rslt.fit(), true);
+
+    // The message stays queued until it's received into a buffer that's big enough.
+    ensure_eq!(a_socket.peek_msg_size()?, BIG_LEN);
+    let rslt = ReliableRecvMsg::try_recv(&mut a_socket, &mut buf).context("big try_recv failed")?;
+    ensure_eq!(
+        rslt,
+        TryRecvResult {
+            size: BIG_LEN,
+            fit: false
+        }
+    );
+    let mut big_buf = vec![0; BIG_LEN];
+    let rslt = ReliableRecvMsg::try_recv(&mut a_socket, &mut big_buf).context("big try_recv failed")?;
+    ensure_eq!(
+        rslt,
+        TryRecvResult {
+            size: BIG_LEN,
+            fit: true
+        }
+    );
+    ensure_eq!(big_buf, big);
+
+    match ReliableRecvMsg::recv(&mut a_socket, &mut buf).context("big receive failed")? {
+        RecvResult::Alloc(msg) => ensure_eq!(msg, big),
+        RecvResult::Fit(..) => color_eyre::eyre::bail!("big message reported to fit into a small buffer"),
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::os::unix::udsocket::UdStream;
+use std::{
+    env,
+    io::{self, prelude::*},
+    os::{fd::OwnedFd, unix::net::UnixStream},
+    process::{Command, Stdio},
+};
+
+const CHILD_ENV: &str = "INTERPROCESS_TEST_STDIO_CHILD";
+
+/// Runs the test binary again, with only the child half of the test enabled.
+fn spawn_child(mode: &str, stdin: Stdio) -> io::Result<std::process::Child> {
+    Command::new(env::current_exe()?)
+        .args(["--exact", "udsocket_stdio_child", "--ignored", "--test-threads=1"])
+        .env(CHILD_ENV, mode)
+        .stdin(stdin)
+        .stdout(Stdio::null())
+        .spawn()
+}
+
+pub fn run() -> TestResult {
+    let (mut ours, theirs) = UnixStream::pair().context("socket pair creation failed")?;
+    let mut child = spawn_child("socket", Stdio::from(OwnedFd::from(theirs))).context("spawn failed")?;
+    ours.write_all(b"ping").context("write failed")?;
+    let mut buf = [0; 4];
+    ours.read_exact(&mut buf).context("read failed")?;
+    ensure_eq!(&buf, b"pong");
+    ensure!(child.wait()?.success(), "child process failed with a socket as stdin");
+
+    let mut child = spawn_child("null", Stdio::null()).context("spawn failed")?;
+    ensure!(child.wait()?.success(), "child process failed with a null stdin");
+    Ok(())
+}
+
+pub fn run_child() -> TestResult {
+    let expect_invalid = |result: io::Result<UdStream>, what: &str| match result {
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => Ok(()),
+        els => bail!("unexpected result of {what}: {els:?}"),
+    };
+    match env::var(CHILD_ENV).as_deref() {
+        Ok("socket") => {
+            // Standard output is not the socket.
+            expect_invalid(UdStream::from_stdio(), "adopting mismatched stdio")?;
+            let mut conn = UdStream::from_stdin().context("adopting stdin failed")?;
+            let mut buf = [0; 4];
+            conn.read_exact(&mut buf).context("read failed")?;
+            ensure_eq!(&buf, b"ping");
+            conn.write_all(b"pong").context("write failed")?;
+        }
+        Ok("null") => expect_invalid(UdStream::from_stdin(), "adopting a non-socket stdin")?,
+        // Not spawned by the parent half.
+        _ => {}
+    }
+    Ok(())
+}
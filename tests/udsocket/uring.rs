@@ -0,0 +1,115 @@
+//! Tests accepting a connection and exchanging data and file descriptors through an io_uring instance.
+
+use super::util::*;
+use color_eyre::eyre::{bail, Context};
+use interprocess::{
+    os::unix::udsocket::{
+        cmsg::{ancillary::file_descriptors::FileDescriptors, Cmsg, CmsgMutExt, CmsgVecBuf},
+        uring::{Completion, Token, UdRing},
+        UdStream, UdStreamListener,
+    },
+    unnamed_pipe::pipe,
+};
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::AsFd,
+};
+
+static SERVER_MSG: &[u8] = b"Hello from server!";
+static CLIENT_MSG: &[u8] = b"Hello from client!";
+static PIPE_MSG: &[u8] = b"Hello through a passed pipe!";
+
+fn wait_for(ring: &mut UdRing, token: Token) -> TestResult<Completion> {
+    ring.submit_and_wait(1).context("submission failed")?;
+    match ring.completions().next() {
+        Some((t, completion)) if t == token => Ok(completion),
+        Some((t, _)) => bail!("expected completion of {token:?}, got {t:?}"),
+        None => bail!("no completion"),
+    }
+}
+
+pub(super) fn run(mut namegen: NameGen) -> TestResult {
+    let mut ring = match UdRing::new(8, 4) {
+        Err(e) if matches!(e.raw_os_error(), Some(libc::ENOSYS | libc::EPERM)) => {
+            eprintln!("io_uring is unavailable, skipping: {e}");
+            return Ok(());
+        }
+        ring => ring.context("ring creation failed")?,
+    };
+    let (name, listener) = listen_and_pick_name(&mut namegen, |nm| UdStreamListener::bind(nm))?;
+    let reg_listener = ring.register(&listener).context("listener registration failed")?;
+
+    let token = ring.accept(reg_listener).context("accept failed")?;
+    let mut client = UdStream::connect(&*name).context("connect failed")?;
+    let server = match wait_for(&mut ring, token)? {
+        Completion::Accepted(conn) => conn.context("incoming connection failed")?,
+        els => bail!("unexpected completion: {els:?}"),
+    };
+    ring.unregister(reg_listener).context("unregistration failed")?;
+    ensure_eq!(
+        ring.accept(reg_listener).map_err(|e| e.kind()).unwrap_err(),
+        io::ErrorKind::InvalidInput
+    );
+    let reg_server = ring.register(&server).context("connection registration failed")?;
+
+    let token = ring.send(reg_server, SERVER_MSG.to_vec()).context("send failed")?;
+    match wait_for(&mut ring, token)? {
+        Completion::Sent { result, .. } => ensure_eq!(result.context("send failed")?, SERVER_MSG.len()),
+        els => bail!("unexpected completion: {els:?}"),
+    }
+    let mut buf = [0; SERVER_MSG.len()];
+    client.read_exact(&mut buf).context("client receive failed")?;
+    ensure_eq!(buf, SERVER_MSG);
+
+    client.write_all(CLIENT_MSG).context("client send failed")?;
+    let token = ring
+        .recv(reg_server, Vec::with_capacity(64))
+        .context("receive failed")?;
+    match wait_for(&mut ring, token)? {
+        Completion::Received { buf, result } => {
+            result.context("receive failed")?;
+            ensure_eq!(buf, CLIENT_MSG);
+        }
+        els => bail!("unexpected completion: {els:?}"),
+    }
+
+    // Passes the read end of a pipe from the client to the server, both through the ring.
+    let reg_client = ring.register(&client).context("client registration failed")?;
+    let (mut writer, reader) = pipe().context("pipe creation failed")?;
+    let mut abuf = CmsgVecBuf::new(0);
+    abuf.add_message(&FileDescriptors::new(&[reader.as_fd()]));
+    let token = ring
+        .send_msg(reg_client, CLIENT_MSG.to_vec(), abuf)
+        .context("message send failed")?;
+    match wait_for(&mut ring, token)? {
+        Completion::SentMsg { result, .. } => ensure_eq!(result.context("message send failed")?, CLIENT_MSG.len()),
+        els => bail!("unexpected completion: {els:?}"),
+    }
+    drop(reader);
+
+    let abuf = CmsgVecBuf::new(Cmsg::cmsg_len_for_payload_size(4) * 2);
+    let token = ring
+        .recv_msg(reg_server, Vec::with_capacity(64), abuf)
+        .context("message receive failed")?;
+    let received = match wait_for(&mut ring, token)? {
+        Completion::ReceivedMsg { buf, abuf, result } => {
+            result.context("message receive failed")?;
+            ensure_eq!(buf, CLIENT_MSG);
+            let fds = match abuf.as_ref().decode::<FileDescriptors>().next() {
+                Some(Ok(fds)) => fds.into_owned_fds().context("taking ownership failed")?,
+                els => bail!("no file descriptors received: {els:?}"),
+            };
+            ensure_eq!(fds.len(), 1);
+            File::from(fds.into_iter().next().unwrap())
+        }
+        els => bail!("unexpected completion: {els:?}"),
+    };
+    writer.write_all(PIPE_MSG).context("pipe write failed")?;
+    drop(writer);
+    let mut contents = Vec::new();
+    (&received).read_to_end(&mut contents).context("pipe read failed")?;
+    ensure_eq!(contents, PIPE_MSG);
+    ensure_eq!(ring.in_flight(), 0);
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{message_builder::MessageBuilder, os::unix::udsocket::UdDatagram};
+
+pub(super) fn run(mut namegen: NameGen) -> TestResult {
+    let mks = |nm: &str| UdDatagram::bound(nm);
+    let (a_name, a_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side A socket")?;
+    let (_, mut b_socket) = listen_and_pick_name(&mut namegen, mks).context("failed to make side B socket")?;
+    b_socket.set_destination(&*a_name).context("set destination failed")?;
+
+    let body = [7; 300];
+    let mut msg = MessageBuilder::with_capacity(3);
+    msg.push(b"header:");
+    msg.push(&body);
+    msg.push(b":trailer");
+    ensure_eq!(msg.len(), 315);
+    msg.send(&mut b_socket).context("vectored send failed")?;
+
+    let mut buf = [0; 512];
+    let read = a_socket.recv(&mut buf).context("receive failed")?;
+    ensure_eq!(&buf[..read], &msg.to_vec()[..]);
+
+    // An empty message is still a message.
+    MessageBuilder::new()
+        .part(b"")
+        .send(&mut b_socket)
+        .context("empty send failed")?;
+    ensure_eq!(a_socket.recv(&mut buf).context("empty receive failed")?, 0);
+    Ok(())
+}
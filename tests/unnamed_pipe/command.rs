@@ -0,0 +1,74 @@
+use super::util::*;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::unnamed_pipe::CommandPipeExt;
+use std::{fs::File, io::prelude::*, os::unix::io::AsRawFd, process::Command};
+
+pub fn run() -> TestResult {
+    stdio().context("stdio wiring failed")?;
+    extra_fd().context("extra file descriptor wiring failed")?;
+    colliding_fds().context("wiring with colliding file descriptors failed")
+}
+
+fn stdio() -> TestResult {
+    let mut command = Command::new("cat");
+    let mut stdin = command.pipe_stdin()?;
+    let mut stdout = command.pipe_stdout()?;
+    let mut child = command.spawn().context("spawn failed")?;
+    // Closes the child's ends of the pipes in this process.
+    drop(command);
+
+    stdin.write_all(b"Hello from the parent!").context("write failed")?;
+    drop(stdin);
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).context("read failed")?;
+    ensure!(child.wait()?.success(), "child process failed");
+    ensure_eq!(output, "Hello from the parent!");
+    Ok(())
+}
+
+fn extra_fd() -> TestResult {
+    let mut command = Command::new("sh");
+    command.args(["-c", "echo 'Hello from the child!' >&5"]);
+    let mut reader = command.pipe_writer_to_fd(5)?;
+    let mut child = command.spawn().context("spawn failed")?;
+    drop(command);
+
+    let mut output = String::new();
+    reader.read_to_string(&mut output).context("read failed")?;
+    ensure!(child.wait()?.success(), "child process failed");
+    ensure_eq!(output, "Hello from the child!\n");
+    Ok(())
+}
+
+/// Targets the first pipe at the file descriptor that the child end of the second one gets, which must not end up
+/// overwriting the latter before it's moved to its own target.
+fn colliding_fds() -> TestResult {
+    // Pipes take the lowest free file descriptors, so these are the ones the two pipes are going to be created with.
+    let probes = (0..4).map(|_| File::open("/dev/null")).collect::<Result<Vec<_>, _>>()?;
+    let first_target = probes[2].as_raw_fd();
+    let second_target = probes.iter().map(File::as_raw_fd).max().unwrap_or_default() + 10;
+    drop(probes);
+
+    let mut command = Command::new("sh");
+    command.args([
+        "-c",
+        "cat /dev/fd/$0; printf '|'; cat /dev/fd/$1",
+        &first_target.to_string(),
+        &second_target.to_string(),
+    ]);
+    let mut first = command.pipe_reader_to_fd(first_target)?;
+    let mut second = command.pipe_reader_to_fd(second_target)?;
+    let mut stdout = command.pipe_stdout()?;
+    let mut child = command.spawn().context("spawn failed")?;
+    drop(command);
+
+    first.write_all(b"A").context("first write failed")?;
+    drop(first);
+    second.write_all(b"B").context("second write failed")?;
+    drop(second);
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).context("read failed")?;
+    ensure!(child.wait()?.success(), "child process failed");
+    ensure_eq!(output, "A|B");
+    Ok(())
+}
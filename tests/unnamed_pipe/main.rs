@@ -0,0 +1,79 @@
+#![cfg_attr(feature = "read_buf", feature(read_buf, core_io_borrowed_buf))]
+#[path = "../util/mod.rs"]
+#[macro_use]
+mod util;
+use util::*;
+
+#[cfg(unix)]
+mod command;
+#[cfg(all(unix, feature = "mio"))]
+mod mio_source;
+mod options;
+#[cfg(target_os = "linux")]
+mod packet;
+#[cfg(feature = "read_buf")]
+mod read_buf;
+mod send_file;
+mod shared;
+#[cfg(target_os = "linux")]
+mod splice;
+mod timeout;
+#[cfg(target_os = "linux")]
+mod vmsplice;
+
+#[cfg(unix)]
+#[test]
+fn unnamed_pipe_command() -> TestResult {
+    install_color_eyre();
+    command::run()
+}
+#[cfg(all(unix, feature = "mio"))]
+#[test]
+fn unnamed_pipe_mio() -> TestResult {
+    install_color_eyre();
+    mio_source::run()
+}
+#[test]
+fn unnamed_pipe_options() -> TestResult {
+    install_color_eyre();
+    options::run()
+}
+#[cfg(target_os = "linux")]
+#[test]
+fn unnamed_pipe_packet() -> TestResult {
+    install_color_eyre();
+    packet::run()
+}
+#[cfg(feature = "read_buf")]
+#[test]
+fn unnamed_pipe_read_buf() -> TestResult {
+    install_color_eyre();
+    read_buf::run()
+}
+#[test]
+fn unnamed_pipe_send_file() -> TestResult {
+    install_color_eyre();
+    send_file::run()
+}
+#[test]
+fn unnamed_pipe_shared() -> TestResult {
+    install_color_eyre();
+    shared::run()
+}
+#[cfg(target_os = "linux")]
+#[test]
+fn unnamed_pipe_splice() -> TestResult {
+    install_color_eyre();
+    splice::run()
+}
+#[test]
+fn unnamed_pipe_timeout() -> TestResult {
+    install_color_eyre();
+    timeout::run()
+}
+#[cfg(target_os = "linux")]
+#[test]
+fn unnamed_pipe_vmsplice() -> TestResult {
+    install_color_eyre();
+    vmsplice::run()
+}
@@ -0,0 +1,42 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::unnamed_pipe::pipe;
+use mio::{Events, Interest, Poll, Token};
+use std::{io::prelude::*, time::Duration};
+
+pub fn run() -> TestResult {
+    let (mut writer, mut reader) = pipe().context("pipe creation failed")?;
+    reader.set_nonblocking(true)?;
+    writer.set_nonblocking(true)?;
+
+    let mut poll = Poll::new().context("failed to create poller")?;
+    let mut events = Events::with_capacity(4);
+    poll.registry()
+        .register(&mut reader, Token(0), Interest::READABLE)
+        .context("reader registration failed")?;
+    poll.registry()
+        .register(&mut writer, Token(1), Interest::WRITABLE)
+        .context("writer registration failed")?;
+
+    // The pipe is empty, so only the writer can be ready.
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .context("poll failed")?;
+    ensure_eq!(events.iter().map(|e| e.token()).collect::<Vec<_>>(), [Token(1)]);
+
+    writer.write_all(b"Hello from the writer!").context("write failed")?;
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .context("poll failed")?;
+    ensure_eq!(events.iter().map(|e| e.token()).collect::<Vec<_>>(), [Token(0)]);
+
+    let mut buf = [0; 64];
+    let read = reader.read(&mut buf).context("read failed")?;
+    ensure_eq!(&buf[..read], b"Hello from the writer!");
+
+    poll.registry()
+        .deregister(&mut reader)
+        .context("reader deregistration failed")?;
+    poll.registry()
+        .deregister(&mut writer)
+        .context("writer deregistration failed")?;
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use super::util::TestResult;
+use color_eyre::eyre::{bail, Context};
+use interprocess::unnamed_pipe::PipeOptions;
+use std::io::{self, prelude::*};
+
+pub fn run() -> TestResult {
+    let (mut writer, mut reader) = PipeOptions::new()
+        .nonblocking(true)
+        .create()
+        .context("pipe creation failed")?;
+
+    // Nothing has been written yet, so a nonblocking read must not wait.
+    let mut buf = [0; 5];
+    match reader.read(&mut buf) {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+        els => bail!("unexpected result of reading from an empty pipe: {els:?}"),
+    }
+
+    ensure_eq!(reader.bytes_available().context("bytes available query failed")?, 0);
+
+    writer.write_all(b"hello").context("write failed")?;
+    ensure_eq!(reader.bytes_available().context("bytes available query failed")?, 5);
+    reader.read_exact(&mut buf).context("read failed")?;
+    ensure_eq!(&buf, b"hello");
+
+    // Switching back to blocking mode must let a read wait for the data.
+    reader
+        .set_nonblocking(false)
+        .context("failed to disable nonblocking mode")?;
+    writer.write_all(b"again").context("write failed")?;
+    reader.read_exact(&mut buf).context("blocking read failed")?;
+    ensure_eq!(&buf, b"again");
+
+    #[cfg(windows)]
+    {
+        use interprocess::os::windows::{unnamed_pipe::UnnamedPipeCreationOptions, SecurityDescriptor, ShareHandle};
+        use std::num::NonZeroUsize;
+
+        ensure_eq!(reader.is_inheritable()?, false);
+        let (writer, _) = PipeOptions::new().inheritable(true).create()?;
+        ensure_eq!(writer.is_inheritable()?, true);
+
+        // Only the owner (which is us) may access the pipe.
+        let sd = SecurityDescriptor::from_sddl("D:P(A;;GA;;;OW)").context("SDDL parsing failed")?;
+        let (mut writer, mut reader) = UnnamedPipeCreationOptions::new()
+            .security_descriptor(Some(sd))
+            .buffer_size_hint(NonZeroUsize::new(64 * 1024))
+            .build()
+            .context("pipe creation with security descriptor failed")?;
+        ensure_eq!(reader.is_inheritable()?, false);
+        writer.write_all(b"hello").context("write failed")?;
+        reader.read_exact(&mut buf).context("read failed")?;
+        ensure_eq!(&buf, b"hello");
+
+        UnnamedPipeCreationOptions::new()
+            .build_overlapped()
+            .context("overlapped pipe creation failed")?;
+    }
+
+    Ok(())
+}
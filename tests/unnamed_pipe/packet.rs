@@ -0,0 +1,27 @@
+use super::util::TestResult;
+use color_eyre::eyre::{ensure, Context};
+use interprocess::unnamed_pipe::{PipeOptions, MAX_PACKET_SIZE};
+
+pub fn run() -> TestResult {
+    let (mut writer, mut reader) = PipeOptions::new()
+        .packet_mode(true)
+        .create()
+        .context("pipe creation failed")?;
+
+    writer.write_packet(b"first").context("first write failed")?;
+    writer.write_packet(b"second").context("second write failed")?;
+
+    // Packet boundaries are preserved even though both packets fit into the buffer.
+    let mut buf = [0; MAX_PACKET_SIZE];
+    let len = reader.read_packet(&mut buf).context("first read failed")?;
+    ensure_eq!(&buf[..len], b"first");
+    let len = reader.read_packet(&mut buf).context("second read failed")?;
+    ensure_eq!(&buf[..len], b"second");
+
+    ensure!(
+        writer.write_packet(&vec![0; MAX_PACKET_SIZE + 1]).is_err(),
+        "oversized packet was accepted"
+    );
+
+    Ok(())
+}
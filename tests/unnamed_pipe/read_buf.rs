@@ -0,0 +1,31 @@
+//! Tests reading from a pipe into uninitialized memory.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::unnamed_pipe::pipe;
+use std::{
+    io::{prelude::*, BorrowedBuf},
+    mem::MaybeUninit,
+};
+
+static MSG: &[u8] = b"Hello into uninitialized memory!";
+
+pub fn run() -> TestResult {
+    let (mut writer, mut reader) = pipe().context("pipe creation failed")?;
+    writer.write_all(MSG).context("write failed")?;
+    drop(writer);
+
+    let mut storage = [MaybeUninit::uninit(); 64];
+    let mut buf = BorrowedBuf::from(&mut storage[..]);
+    while buf.len() < MSG.len() {
+        let before = buf.len();
+        reader.read_buf(buf.unfilled()).context("read failed")?;
+        ensure_eq!(buf.len() > before, true);
+    }
+    ensure_eq!(buf.filled(), MSG);
+
+    // Hitting EOF leaves the buffer as it was.
+    reader.read_buf(buf.unfilled()).context("read at EOF failed")?;
+    ensure_eq!(buf.len(), MSG.len());
+    Ok(())
+}
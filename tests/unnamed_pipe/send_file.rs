@@ -0,0 +1,35 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::unnamed_pipe::pipe;
+use std::{env, fs, io::prelude::*, process};
+
+const CONTENTS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+pub fn run() -> TestResult {
+    let path = env::temp_dir().join(format!("interprocess-test-send-file-{}", process::id()));
+    fs::write(&path, CONTENTS).context("failed to create test file")?;
+    let result = send(&path);
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn send(path: &std::path::Path) -> TestResult {
+    let file = fs::File::open(path).context("failed to open test file")?;
+    let (mut writer, mut reader) = pipe().context("pipe creation failed")?;
+
+    let sent = writer.send_file(&file, 10..20).context("send failed")?;
+    ensure_eq!(sent, 10);
+    let mut buf = [0; 10];
+    reader.read_exact(&mut buf).context("read failed")?;
+    ensure_eq!(&buf, &CONTENTS[10..20]);
+
+    // The range extends past the end of the file, so only the rest of it is sent.
+    let sent = writer.send_file(&file, 30..100).context("send past the end failed")?;
+    ensure_eq!(sent, CONTENTS.len() as u64 - 30);
+    drop(writer);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).context("read failed")?;
+    ensure_eq!(rest, &CONTENTS[30..]);
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::unnamed_pipe::pipe;
+use std::{io::prelude::*, sync::Arc, thread};
+
+const THREADS: usize = 4;
+const MSG: &[u8] = b"hi!";
+
+pub fn run() -> TestResult {
+    let (writer, reader) = pipe().context("pipe creation failed")?;
+    let writer = Arc::new(writer);
+    let reader = Arc::new(reader);
+
+    let threads = (0..THREADS)
+        .map(|_| {
+            let writer = Arc::clone(&writer);
+            // Writes this small are atomic, so the messages can't interleave.
+            thread::spawn(move || (&*writer).write_all(MSG))
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap().context("write failed")?;
+    }
+
+    let mut buf = [0; THREADS * MSG.len()];
+    (&*reader).read_exact(&mut buf).context("read failed")?;
+    for chunk in buf.chunks(MSG.len()) {
+        ensure_eq!(chunk, MSG);
+    }
+    Ok(())
+}
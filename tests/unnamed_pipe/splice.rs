@@ -0,0 +1,57 @@
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::{
+    os::unix::udsocket::{UdStream, UdStreamListener},
+    unnamed_pipe::pipe,
+};
+use std::io::prelude::*;
+
+const MSG: &[u8] = b"relayed without copying";
+
+pub fn run() -> TestResult {
+    let (mut src_writer, mut src_reader) = pipe().context("pipe creation failed")?;
+    let (tee_writer, mut tee_reader) = pipe().context("pipe creation failed")?;
+    let (dst_writer, mut dst_reader) = pipe().context("pipe creation failed")?;
+
+    src_writer.write_all(MSG).context("write failed")?;
+
+    // tee() leaves the data in the source pipe...
+    let teed = src_reader.tee(&tee_writer, MSG.len()).context("tee failed")?;
+    ensure_eq!(teed, MSG.len());
+    // ...which is then moved into the destination pipe.
+    let spliced = src_reader
+        .splice_to(&dst_writer, MSG.len())
+        .context("splice to pipe failed")?;
+    ensure_eq!(spliced, MSG.len());
+
+    let mut buf = [0; MSG.len()];
+    tee_reader
+        .read_exact(&mut buf)
+        .context("read from tee destination failed")?;
+    ensure_eq!(&buf, MSG);
+    dst_reader
+        .read_exact(&mut buf)
+        .context("read from splice destination failed")?;
+    ensure_eq!(&buf, MSG);
+
+    // Socket → pipe → back into the pipe writer via splice_from().
+    let (name, listener) = listen_and_pick_name(&mut NameGen::new(make_id!(), false), |nm| UdStreamListener::bind(nm))?;
+    let mut client = UdStream::connect(&*name).context("connect failed")?;
+    let server = listener.accept().context("accept failed")?;
+    client.write_all(MSG).context("socket write failed")?;
+    let spliced = server
+        .splice_to_pipe(&dst_writer, MSG.len())
+        .context("splice from socket failed")?;
+    ensure_eq!(spliced, MSG.len());
+    let (mut relay_writer, mut relay_reader) = pipe().context("pipe creation failed")?;
+    let moved = relay_writer
+        .splice_from(&dst_reader, MSG.len())
+        .context("splice_from failed")?;
+    ensure_eq!(moved, MSG.len());
+    drop(relay_writer);
+    let mut relayed = Vec::new();
+    relay_reader.read_to_end(&mut relayed).context("relay read failed")?;
+    ensure_eq!(relayed, MSG);
+
+    Ok(())
+}
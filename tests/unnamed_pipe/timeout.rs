@@ -0,0 +1,42 @@
+use super::util::*;
+use color_eyre::eyre::{bail, ensure, Context};
+use interprocess::unnamed_pipe::pipe;
+use std::{
+    io::{self, prelude::*},
+    time::{Duration, Instant},
+};
+
+const TIMEOUT: Duration = Duration::from_millis(50);
+
+pub fn run() -> TestResult {
+    let (mut writer, mut reader) = pipe().context("pipe creation failed")?;
+    ensure_eq!(reader.read_timeout(), None);
+    ensure!(
+        reader.set_read_timeout(Some(Duration::ZERO)).is_err(),
+        "zero timeout was accepted"
+    );
+    reader
+        .set_read_timeout(Some(TIMEOUT))
+        .context("failed to set timeout")?;
+    ensure_eq!(reader.read_timeout(), Some(TIMEOUT));
+
+    let mut buf = [0; 5];
+    let start = Instant::now();
+    match reader.read(&mut buf) {
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+        els => bail!("unexpected result of reading from a silent pipe: {els:?}"),
+    }
+    ensure!(start.elapsed() >= TIMEOUT, "read timed out too early");
+
+    writer.write_all(b"hello").context("write failed")?;
+    reader.read_exact(&mut buf).context("read failed")?;
+    ensure_eq!(&buf, b"hello");
+
+    // A closed pipe is reported as end of file rather than a timeout.
+    drop(writer);
+    ensure_eq!(reader.read(&mut buf).context("read after closure failed")?, 0);
+
+    reader.set_read_timeout(None)?;
+    ensure_eq!(reader.read_timeout(), None);
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Tests mapping a large buffer into a pipe and reading it out from another thread.
+
+use super::util::*;
+use color_eyre::eyre::Context;
+use interprocess::unnamed_pipe::pipe;
+use std::{io::prelude::*, thread};
+
+const LEN: usize = 1024 * 1024;
+
+pub fn run() -> TestResult {
+    let (mut writer, mut reader) = pipe().context("pipe creation failed")?;
+    // Leaked and never modified, and thus fine to gift.
+    let data: &'static [u8] = Vec::leak((0..LEN).map(|i| (i % 251) as u8).collect());
+
+    let reader = thread::spawn(move || {
+        let mut received = Vec::with_capacity(LEN);
+        reader.read_to_end(&mut received).map(|_| received)
+    });
+    let mut written = 0;
+    while written < LEN {
+        // SAFETY: the data is leaked and never modified
+        written += unsafe { writer.write_gifted(&data[written..]) }.context("gifted write failed")?;
+    }
+    drop(writer);
+
+    let received = reader.join().unwrap().context("read failed")?;
+    ensure_eq!(received.len(), LEN);
+    ensure_eq!(received == data, true);
+    Ok(())
+}
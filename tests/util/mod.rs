@@ -1,5 +1,5 @@
 //! Test utilities for allocating an address for the server and then spawning clients to connect to it.
-#![allow(dead_code, unused_macros)]
+#![allow(dead_code, unused_imports, unused_macros)]
 
 mod choke;
 